@@ -0,0 +1,53 @@
+use clap::Parser;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+/// Command line and environment configuration of the cranker
+/// Intervals are in seconds; 0 disables the task.
+#[derive(Parser, Debug)]
+#[command(name = "aura-cranker", about = "Aura Lend protocol maintenance crank")]
+pub struct Config {
+    /// RPC endpoint to read and send through
+    #[arg(long, env = "RPC_URL", default_value = "http://127.0.0.1:8899")]
+    pub rpc_url: String,
+
+    /// Keypair file of the cranker, which pays for and signs every transaction
+    #[arg(long, env = "CRANKER_KEYPAIR")]
+    pub keypair: PathBuf,
+
+    /// Seconds between refreshes of every reserve, accruing their interest
+    #[arg(long, default_value_t = 60)]
+    pub refresh_interval_secs: u64,
+
+    /// Seconds between protocol metrics updates
+    #[arg(long, default_value_t = 300)]
+    pub metrics_interval_secs: u64,
+
+    /// Seconds between cleanups of expired timelock proposals (needs TIMELOCK_MANAGER)
+    #[arg(long, default_value_t = 3_600)]
+    pub proposals_interval_secs: u64,
+
+    /// Seconds between cleanups of expired governance roles (needs GOVERNANCE_MANAGER)
+    #[arg(long, default_value_t = 3_600)]
+    pub roles_interval_secs: u64,
+
+    /// Reserves refreshed per transaction
+    #[arg(long, default_value_t = 5)]
+    pub reserves_per_transaction: usize,
+
+    /// Expired proposals cleaned up per transaction
+    #[arg(long, default_value_t = 10)]
+    pub proposals_per_transaction: usize,
+
+    /// Retries of a failed task run before it waits for its next interval
+    #[arg(long, default_value_t = 3)]
+    pub max_retries: u32,
+
+    /// Delay before the first retry, doubled on each further retry
+    #[arg(long, default_value_t = 500)]
+    pub retry_base_delay_ms: u64,
+
+    /// Address serving Prometheus metrics at /metrics
+    #[arg(long, env = "METRICS_ADDR", default_value = "0.0.0.0:9090")]
+    pub metrics_addr: SocketAddr,
+}
@@ -0,0 +1,96 @@
+//! Maintenance crank for Aura Lend
+//!
+//! Runs the protocol's upkeep on independent intervals: refreshing every reserve so
+//! interest accrues between user transactions, aggregating the protocol metrics, and
+//! cleaning up expired timelock proposals and governance roles. Failed runs are retried
+//! with exponential backoff, and runs, failures, retries and transactions are exported
+//! as Prometheus metrics.
+
+mod config;
+mod metrics;
+mod tasks;
+
+use anyhow::Result;
+use aura_lend_sdk::AuraLendClient;
+use clap::Parser;
+use config::Config;
+use metrics::Metrics;
+use solana_sdk::signature::{read_keypair_file, Signer};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tasks::{Cranker, Task};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::init();
+    let config = Arc::new(Config::parse());
+    let payer = read_keypair_file(&config.keypair)
+        .map_err(|error| anyhow::anyhow!("reading {}: {error}", config.keypair.display()))?;
+    log::info!("Cranking as {}", payer.pubkey());
+
+    let cranker = Arc::new(Cranker::new(
+        AuraLendClient::new(config.rpc_url.clone()),
+        payer,
+        config.reserves_per_transaction,
+        config.proposals_per_transaction,
+    ));
+    let metrics = Arc::new(Metrics::new()?);
+
+    for task in Task::ALL {
+        let interval_secs = match task {
+            Task::RefreshReserves => config.refresh_interval_secs,
+            Task::UpdateProtocolMetrics => config.metrics_interval_secs,
+            Task::CleanupExpiredProposals => config.proposals_interval_secs,
+            Task::CleanupExpiredRoles => config.roles_interval_secs,
+        };
+        if interval_secs == 0 {
+            log::info!("{} disabled", task.name());
+            continue;
+        }
+
+        let (cranker, metrics, config) = (cranker.clone(), metrics.clone(), config.clone());
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+            loop {
+                interval.tick().await;
+                run_with_retry(&cranker, &metrics, &config, task).await;
+            }
+        });
+    }
+
+    metrics::serve(metrics, config.metrics_addr).await
+}
+
+/// Run a task, retrying failures with exponential backoff up to `max_retries` times
+async fn run_with_retry(cranker: &Cranker, metrics: &Metrics, config: &Config, task: Task) {
+    let name = task.name();
+    let mut delay = Duration::from_millis(config.retry_base_delay_ms);
+
+    for attempt in 0..=config.max_retries {
+        match cranker.run(task).await {
+            Ok(transactions) => {
+                log::info!("{name} done, {transactions} transactions sent");
+                metrics.runs.with_label_values(&[name]).inc();
+                metrics
+                    .transactions
+                    .with_label_values(&[name])
+                    .inc_by(transactions as u64);
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_secs() as i64);
+                metrics.last_success.with_label_values(&[name]).set(now);
+                return;
+            }
+            Err(error) if attempt < config.max_retries => {
+                log::warn!("{name} failed, retrying in {delay:?}: {error:#}");
+                metrics.retries.with_label_values(&[name]).inc();
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            Err(error) => {
+                log::error!("{name} failed after {attempt} retries: {error:#}");
+                metrics.failures.with_label_values(&[name]).inc();
+            }
+        }
+    }
+}
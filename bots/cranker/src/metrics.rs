@@ -0,0 +1,116 @@
+use anyhow::Result;
+use prometheus::{Encoder, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Prometheus metrics of the crank tasks, labelled by task
+pub struct Metrics {
+    registry: Registry,
+
+    /// Task runs that succeeded, possibly after retries
+    pub runs: IntCounterVec,
+
+    /// Task runs that failed after every retry
+    pub failures: IntCounterVec,
+
+    /// Retries of failed attempts
+    pub retries: IntCounterVec,
+
+    /// Transactions sent
+    pub transactions: IntCounterVec,
+
+    /// Unix timestamp of the last successful run
+    pub last_success: IntGaugeVec,
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new_custom(Some("aura_cranker".to_string()), None)?;
+        let counter = |name: &str, help: &str| -> Result<IntCounterVec> {
+            let counter = IntCounterVec::new(Opts::new(name, help), &["task"])?;
+            registry.register(Box::new(counter.clone()))?;
+            Ok(counter)
+        };
+
+        let runs = counter("runs_total", "Task runs that succeeded")?;
+        let failures = counter("failures_total", "Task runs that failed after every retry")?;
+        let retries = counter("retries_total", "Retries of failed task attempts")?;
+        let transactions = counter("transactions_total", "Transactions sent")?;
+        let last_success = IntGaugeVec::new(
+            Opts::new(
+                "last_success_timestamp",
+                "Unix time of the last successful run",
+            ),
+            &["task"],
+        )?;
+        registry.register(Box::new(last_success.clone()))?;
+
+        Ok(Self {
+            registry,
+            runs,
+            failures,
+            retries,
+            transactions,
+            last_success,
+        })
+    }
+
+    /// Metrics in the Prometheus text format
+    pub fn render(&self) -> Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+/// Serve the metrics to any HTTP request on `addr`
+pub async fn serve(metrics: Arc<Metrics>, addr: SocketAddr) -> Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    log::info!("Serving metrics on {addr}");
+
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            // The request is not routed on, only drained
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).await;
+
+            let body = match metrics.render() {
+                Ok(body) => body,
+                Err(error) => {
+                    log::warn!("Rendering metrics failed: {error:#}");
+                    return;
+                }
+            };
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            if stream.write_all(header.as_bytes()).await.is_ok() {
+                let _ = stream.write_all(&body).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_labels_tasks() {
+        let metrics = Metrics::new().unwrap();
+        metrics.runs.with_label_values(&["refresh_reserves"]).inc();
+        metrics
+            .transactions
+            .with_label_values(&["refresh_reserves"])
+            .inc_by(3);
+
+        let rendered = String::from_utf8(metrics.render().unwrap()).unwrap();
+        assert!(rendered.contains("aura_cranker_runs_total{task=\"refresh_reserves\"} 1"));
+        assert!(rendered.contains("aura_cranker_transactions_total{task=\"refresh_reserves\"} 3"));
+    }
+}
@@ -0,0 +1,193 @@
+use anyhow::Result;
+use aura_lend::state::{
+    GovernanceRegistry, ProposalLifecycle, TimelockController, TimelockProposal,
+};
+use aura_lend_sdk::{accounts, instruction, ix, pda, AuraLendClient};
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::signature::{Keypair, Signer};
+
+/// Maintenance tasks the cranker runs on their own intervals
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Task {
+    /// Refresh every reserve, accruing interest and caching prices
+    RefreshReserves,
+
+    /// Aggregate the reserves into the protocol metrics
+    UpdateProtocolMetrics,
+
+    /// Expire timelock proposals past their expiry
+    CleanupExpiredProposals,
+
+    /// Remove expired governance roles
+    CleanupExpiredRoles,
+}
+
+impl Task {
+    pub const ALL: [Task; 4] = [
+        Task::RefreshReserves,
+        Task::UpdateProtocolMetrics,
+        Task::CleanupExpiredProposals,
+        Task::CleanupExpiredRoles,
+    ];
+
+    /// Label of the task in logs and metrics
+    pub fn name(&self) -> &'static str {
+        match self {
+            Task::RefreshReserves => "refresh_reserves",
+            Task::UpdateProtocolMetrics => "update_protocol_metrics",
+            Task::CleanupExpiredProposals => "cleanup_expired_proposals",
+            Task::CleanupExpiredRoles => "cleanup_expired_roles",
+        }
+    }
+}
+
+/// Builds and sends the maintenance transactions
+pub struct Cranker {
+    client: AuraLendClient,
+    payer: Keypair,
+    reserves_per_transaction: usize,
+    proposals_per_transaction: usize,
+}
+
+impl Cranker {
+    pub fn new(
+        client: AuraLendClient,
+        payer: Keypair,
+        reserves_per_transaction: usize,
+        proposals_per_transaction: usize,
+    ) -> Self {
+        Self {
+            client,
+            payer,
+            reserves_per_transaction: reserves_per_transaction.max(1),
+            proposals_per_transaction: proposals_per_transaction.max(1),
+        }
+    }
+
+    /// Run a task once, returning the number of transactions sent
+    pub async fn run(&self, task: Task) -> Result<usize> {
+        match task {
+            Task::RefreshReserves => self.refresh_reserves().await,
+            Task::UpdateProtocolMetrics => self.update_protocol_metrics().await,
+            Task::CleanupExpiredProposals => self.cleanup_expired_proposals().await,
+            Task::CleanupExpiredRoles => self.cleanup_expired_roles().await,
+        }
+    }
+
+    /// Refresh every reserve of the market, several per transaction
+    async fn refresh_reserves(&self) -> Result<usize> {
+        let market = pda::market();
+        let mut refreshes = Vec::new();
+        for (address, reserve) in self.client.get_reserves().await? {
+            if reserve.market == market {
+                refreshes.push(ix::refresh_reserve(
+                    &self.client.reserve_keys(&address).await?,
+                ));
+            }
+        }
+
+        for batch in refreshes.chunks(self.reserves_per_transaction) {
+            self.client
+                .send_instructions(batch, &self.payer, &[])
+                .await?;
+        }
+        Ok(refreshes.len().div_ceil(self.reserves_per_transaction))
+    }
+
+    /// Update the protocol metrics
+    /// Every reserve must have been refreshed within `MAX_ORACLE_STALENESS_SLOTS`, so
+    /// they are refreshed first and passed as remaining accounts.
+    async fn update_protocol_metrics(&self) -> Result<usize> {
+        let sent = self.refresh_reserves().await?;
+
+        let market = pda::market();
+        let reserves: Vec<AccountMeta> = self
+            .client
+            .get_reserves()
+            .await?
+            .into_iter()
+            .filter(|(_, reserve)| reserve.market == market)
+            .map(|(address, _)| AccountMeta::new_readonly(address, false))
+            .collect();
+
+        let mut update = ix::build(
+            accounts::UpdateProtocolMetrics {
+                market,
+                protocol_metrics: pda::protocol_metrics(&market),
+            },
+            instruction::UpdateProtocolMetrics {},
+        );
+        update.accounts.extend(reserves);
+        self.client
+            .send_instructions(&[update], &self.payer, &[])
+            .await?;
+        Ok(sent + 1)
+    }
+
+    /// Expire the timelock's active proposals that are past their expiry
+    async fn cleanup_expired_proposals(&self) -> Result<usize> {
+        let multisig = self.client.get_market().await?.multisig_owner;
+        let timelock = pda::timelock(&multisig);
+        let controller = self
+            .client
+            .get_account::<TimelockController>(&timelock)
+            .await?;
+        let now = self.client.get_clock().await?.unix_timestamp;
+
+        let mut expired = Vec::new();
+        for address in &controller.active_proposals {
+            let proposal = self.client.get_account::<TimelockProposal>(address).await?;
+            if proposal.is_past_expiry(now) {
+                expired.push(AccountMeta::new(*address, false));
+            }
+        }
+
+        for batch in expired.chunks(self.proposals_per_transaction) {
+            let mut cleanup = ix::build(
+                accounts::CleanupExpiredProposals {
+                    timelock,
+                    governance: pda::governance(&multisig),
+                    executor: self.payer.pubkey(),
+                },
+                instruction::CleanupExpiredProposals {},
+            );
+            cleanup.accounts.extend_from_slice(batch);
+            self.client
+                .send_instructions(&[cleanup], &self.payer, &[])
+                .await?;
+        }
+        Ok(expired.len().div_ceil(self.proposals_per_transaction))
+    }
+
+    /// Remove expired governance roles, if there are any
+    async fn cleanup_expired_roles(&self) -> Result<usize> {
+        let multisig = self.client.get_market().await?.multisig_owner;
+        let governance = pda::governance(&multisig);
+        let registry = self
+            .client
+            .get_account::<GovernanceRegistry>(&governance)
+            .await?;
+        let now = self.client.get_clock().await?.unix_timestamp;
+
+        // Same check as `GovernanceRole::is_expired`, against the cluster's clock
+        let any_expired = registry
+            .roles
+            .iter()
+            .any(|role| role.expires_at.is_some_and(|expires_at| now > expires_at));
+        if !any_expired {
+            return Ok(0);
+        }
+
+        let cleanup = ix::build(
+            accounts::CleanupExpiredRoles {
+                governance,
+                executor: self.payer.pubkey(),
+            },
+            instruction::CleanupExpiredRoles {},
+        );
+        self.client
+            .send_instructions(&[cleanup], &self.payer, &[])
+            .await?;
+        Ok(1)
+    }
+}
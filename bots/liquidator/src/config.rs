@@ -0,0 +1,57 @@
+use clap::Parser;
+use solana_sdk::pubkey::Pubkey;
+use std::path::PathBuf;
+
+/// Command line and environment configuration of the liquidator
+#[derive(Parser, Debug)]
+#[command(name = "aura-liquidator", about = "Aura Lend reference liquidation bot")]
+pub struct Config {
+    /// RPC endpoint to scan and send through
+    #[arg(long, env = "RPC_URL", default_value = "http://127.0.0.1:8899")]
+    pub rpc_url: String,
+
+    /// Keypair file of the liquidator, which pays for and signs liquidations
+    #[arg(long, env = "LIQUIDATOR_KEYPAIR")]
+    pub keypair: PathBuf,
+
+    /// Seconds between obligation scans
+    #[arg(long, default_value_t = 10)]
+    pub scan_interval_secs: u64,
+
+    /// Obligations whose cached health is at or below this (10_000 = 1.0) are simulated
+    /// Above 1.0 so obligations whose collateral fell since their last refresh are caught.
+    #[arg(long, default_value_t = 10_500)]
+    pub watch_health_bps: u64,
+
+    /// Liquidations attempted per scan, least healthy first
+    #[arg(long, default_value_t = 16)]
+    pub max_liquidations_per_scan: usize,
+
+    /// Compute unit limit of liquidation transactions
+    #[arg(long, default_value_t = 600_000)]
+    pub compute_unit_limit: u32,
+
+    /// Priority fee of liquidation transactions, in micro-lamports per compute unit
+    #[arg(long, default_value_t = 10_000)]
+    pub priority_fee_micro_lamports: u64,
+
+    /// Liquidate as the liquidator's registered keeper, within the keeper priority window
+    #[arg(long)]
+    pub keeper: bool,
+
+    /// Jito block engine URL; liquidations are sent as bundles through it when set
+    #[arg(long, env = "JITO_URL")]
+    pub jito_url: Option<String>,
+
+    /// Tip account paid in each bundle
+    #[arg(long, requires = "jito_url")]
+    pub jito_tip_account: Option<Pubkey>,
+
+    /// Tip paid in each bundle, in lamports
+    #[arg(long, default_value_t = 10_000)]
+    pub jito_tip_lamports: u64,
+
+    /// Simulate liquidations without sending them
+    #[arg(long)]
+    pub dry_run: bool,
+}
@@ -0,0 +1,198 @@
+use crate::config::Config;
+use crate::jito;
+use anyhow::{anyhow, Result};
+use aura_lend_sdk::ix::{self, LiquidatorKeys, ObligationKeys};
+use aura_lend_sdk::{
+    obligation_refresh_accounts, pda, AuraLendClient, Obligation, ReserveKeys, SdkError,
+};
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use std::collections::HashMap;
+
+/// Builds, simulates and submits liquidations
+pub struct Executor<'a> {
+    client: &'a AuraLendClient,
+    config: &'a Config,
+    payer: Keypair,
+    http: reqwest::Client,
+
+    /// Collateral supply account of each reserve, found once and reused
+    collateral_supplies: HashMap<Pubkey, Pubkey>,
+}
+
+impl<'a> Executor<'a> {
+    pub fn new(client: &'a AuraLendClient, config: &'a Config, payer: Keypair) -> Self {
+        Self {
+            client,
+            config,
+            payer,
+            http: reqwest::Client::new(),
+            collateral_supplies: HashMap::new(),
+        }
+    }
+
+    /// Liquidate the largest borrow of an obligation against its largest deposit
+    /// The transaction refreshes every reserve of the obligation and the obligation itself
+    /// before liquidating, and is simulated first; an obligation that turns out healthy at
+    /// current prices fails simulation and is skipped. Returns the signature or bundle id
+    /// of what was sent.
+    pub async fn liquidate(&mut self, address: &Pubkey) -> Result<Option<String>> {
+        let obligation = self.client.get_obligation(address).await?;
+        let (Some(repay), Some(withdraw)) = (
+            obligation
+                .borrows
+                .iter()
+                .max_by_key(|borrow| borrow.market_value_usd)
+                .map(|borrow| borrow.borrow_reserve),
+            obligation
+                .deposits
+                .iter()
+                .max_by_key(|deposit| deposit.market_value_usd)
+                .map(|deposit| deposit.deposit_reserve),
+        ) else {
+            return Ok(None);
+        };
+        let liquidity_amount = obligation
+            .max_liquidation_amount(&repay)
+            .map_err(SdkError::from)?;
+        if liquidity_amount == 0 {
+            return Ok(None);
+        }
+
+        let reserves = self.client.position_reserve_keys(&obligation).await?;
+        let find = |reserve: &Pubkey| {
+            reserves
+                .iter()
+                .find(|keys| keys.reserve == *reserve)
+                .copied()
+                .ok_or_else(|| anyhow!("reserve {reserve} missing from position keys"))
+        };
+        let (repay_keys, withdraw_keys) = (find(&repay)?, find(&withdraw)?);
+
+        let instructions = self
+            .liquidation_instructions(
+                address,
+                &obligation,
+                &reserves,
+                &repay_keys,
+                &withdraw_keys,
+                liquidity_amount,
+            )
+            .await?;
+        let mut transaction = self
+            .client
+            .build_transaction(&instructions, &self.payer.pubkey())
+            .await?;
+        let recent_blockhash = transaction.message.recent_blockhash;
+        transaction.try_sign(&[&self.payer], recent_blockhash)?;
+
+        let simulation = self.client.rpc().simulate_transaction(&transaction).await?;
+        if let Some(error) = simulation.value.err {
+            log::debug!("Liquidation of {address} fails simulation: {error}");
+            return Ok(None);
+        }
+        if self.config.dry_run {
+            log::info!("Dry run: would liquidate {liquidity_amount} of {repay} from {address}");
+            return Ok(None);
+        }
+
+        let sent = match &self.config.jito_url {
+            Some(url) => jito::send_bundle(&self.http, url, &[transaction]).await?,
+            None => self
+                .client
+                .rpc()
+                .send_and_confirm_transaction(&transaction)
+                .await?
+                .to_string(),
+        };
+        Ok(Some(sent))
+    }
+
+    /// Instructions of a liquidation transaction, priority fee and Jito tip included
+    async fn liquidation_instructions(
+        &mut self,
+        address: &Pubkey,
+        obligation: &Obligation,
+        reserves: &[ReserveKeys],
+        repay: &ReserveKeys,
+        withdraw: &ReserveKeys,
+        liquidity_amount: u64,
+    ) -> Result<Vec<Instruction>> {
+        let liquidator = self.payer.pubkey();
+        let withdraw_collateral_supply = self.collateral_supply(withdraw).await?;
+        let refresh_accounts = obligation_refresh_accounts(obligation, reserves)
+            .ok_or_else(|| anyhow!("missing reserve keys for {address}"))?;
+
+        let obligation_keys = ObligationKeys {
+            obligation: *address,
+            owner: obligation.owner,
+            emode_category: (obligation.emode_category != 0)
+                .then(|| pda::emode_category(&obligation.market, obligation.emode_category)),
+            isolated_collateral_reserve: None,
+        };
+        let liquidator_keys = LiquidatorKeys {
+            liquidator,
+            source_liquidity: get_associated_token_address_with_program_id(
+                &liquidator,
+                &repay.liquidity_mint,
+                &repay.token_program,
+            ),
+            destination_collateral: get_associated_token_address_with_program_id(
+                &liquidator,
+                &withdraw.collateral_mint,
+                &withdraw.token_program,
+            ),
+            keeper: self
+                .config
+                .keeper
+                .then(|| pda::keeper(&pda::keeper_registry(&pda::market()), &liquidator)),
+        };
+
+        let mut instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(self.config.compute_unit_limit),
+            ComputeBudgetInstruction::set_compute_unit_price(
+                self.config.priority_fee_micro_lamports,
+            ),
+        ];
+        instructions.extend(reserves.iter().map(ix::refresh_reserve));
+        instructions.push(create_associated_token_account_idempotent(
+            &liquidator,
+            &liquidator,
+            &withdraw.collateral_mint,
+            &withdraw.token_program,
+        ));
+        instructions.push(ix::liquidate_obligation(
+            repay,
+            withdraw,
+            &obligation_keys,
+            &liquidator_keys,
+            &withdraw_collateral_supply,
+            refresh_accounts,
+            liquidity_amount,
+        ));
+        if let Some(tip_account) = &self.config.jito_tip_account {
+            instructions.push(system_instruction::transfer(
+                &liquidator,
+                tip_account,
+                self.config.jito_tip_lamports,
+            ));
+        }
+
+        Ok(instructions)
+    }
+
+    /// Collateral supply account of a reserve
+    async fn collateral_supply(&mut self, reserve: &ReserveKeys) -> Result<Pubkey> {
+        if let Some(supply) = self.collateral_supplies.get(&reserve.reserve) {
+            return Ok(*supply);
+        }
+        let supply = self.client.collateral_supply(reserve).await?;
+        self.collateral_supplies.insert(reserve.reserve, supply);
+        Ok(supply)
+    }
+}
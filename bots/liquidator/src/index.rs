@@ -0,0 +1,155 @@
+use aura_lend::utils::ObligationIndex;
+use aura_lend_sdk::{EModeParams, Obligation, Result};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+
+/// Values an obligation is indexed under, kept to remove it before re-indexing
+struct IndexedObligation {
+    owner: Pubkey,
+    health_bps: u64,
+    borrowed_value: u64,
+    timestamp: u64,
+    reserves: Vec<Pubkey>,
+}
+
+/// Local index of obligations with debt, ordered by health as of their last refresh
+pub struct HealthIndex {
+    index: ObligationIndex,
+    entries: HashMap<Pubkey, IndexedObligation>,
+}
+
+impl HealthIndex {
+    pub fn new() -> Self {
+        Self {
+            index: ObligationIndex::new(),
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Number of obligations indexed
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Index an obligation at its current values, replacing any previous entry
+    /// Obligations without debt cannot be liquidated and are dropped.
+    pub fn upsert(
+        &mut self,
+        address: Pubkey,
+        obligation: &Obligation,
+        emode: Option<&EModeParams>,
+    ) -> Result<()> {
+        self.remove(&address);
+        if obligation.borrowed_value_usd.is_zero() {
+            return Ok(());
+        }
+
+        let entry = IndexedObligation {
+            owner: obligation.owner,
+            health_bps: obligation
+                .calculate_health_factor_with(emode)?
+                .to_health_bps_saturating(),
+            borrowed_value: obligation
+                .borrowed_value_usd
+                .try_floor_u64()
+                .unwrap_or(u64::MAX),
+            timestamp: obligation.last_update_timestamp,
+            reserves: obligation
+                .deposits
+                .iter()
+                .map(|deposit| deposit.deposit_reserve)
+                .chain(
+                    obligation
+                        .borrows
+                        .iter()
+                        .map(|borrow| borrow.borrow_reserve),
+                )
+                .collect(),
+        };
+        self.index.add_obligation(
+            address,
+            entry.owner,
+            entry.health_bps,
+            entry.borrowed_value,
+            entry.timestamp,
+            &entry.reserves,
+        );
+        self.entries.insert(address, entry);
+        Ok(())
+    }
+
+    /// Drop an obligation from the index
+    pub fn remove(&mut self, address: &Pubkey) {
+        if let Some(entry) = self.entries.remove(address) {
+            self.index.remove_obligation(
+                address,
+                &entry.owner,
+                entry.health_bps,
+                entry.borrowed_value,
+                entry.timestamp,
+                &entry.reserves,
+            );
+        }
+    }
+
+    /// Drop every obligation not in `live`, e.g. closed since the last scan
+    pub fn retain(&mut self, live: &HashSet<Pubkey>) {
+        let closed: Vec<Pubkey> = self
+            .entries
+            .keys()
+            .filter(|address| !live.contains(address))
+            .copied()
+            .collect();
+        for address in closed {
+            self.remove(&address);
+        }
+    }
+
+    /// Obligations at or below `max_health_bps`, least healthy first
+    pub fn candidates(&self, max_health_bps: u64, limit: usize) -> Vec<Pubkey> {
+        self.index
+            .get_obligations_by_health_factor_range(None, Some(max_health_bps), limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aura_lend::state::ObligationCollateral;
+    use aura_lend::utils::Decimal;
+
+    #[test]
+    fn test_reindexing_moves_obligation() {
+        let address = Pubkey::new_unique();
+        let mut obligation =
+            Obligation::new_for_test(Pubkey::new_unique(), Pubkey::new_unique(), 0);
+        obligation.deposits.push(ObligationCollateral {
+            deposit_reserve: Pubkey::new_unique(),
+            market_value_usd: Decimal::from_integer(100).unwrap(),
+            liquidation_threshold_bps: 8_000,
+            ..ObligationCollateral::default()
+        });
+
+        // $80 of weighted collateral against $90 of debt
+        let mut index = HealthIndex::new();
+        obligation.borrowed_value_usd = Decimal::from_integer(90).unwrap();
+        index.upsert(address, &obligation, None).unwrap();
+        assert_eq!(index.candidates(10_000, 10), vec![address]);
+
+        // Partially repaid, it leaves the watched range without a stale entry behind
+        obligation.borrowed_value_usd = Decimal::from_integer(50).unwrap();
+        index.upsert(address, &obligation, None).unwrap();
+        assert!(index.candidates(10_500, 10).is_empty());
+        assert_eq!(index.len(), 1);
+
+        // Fully repaid or closed, it is dropped
+        obligation.borrowed_value_usd = Decimal::zero();
+        index.upsert(address, &obligation, None).unwrap();
+        assert_eq!(index.len(), 0);
+
+        obligation.borrowed_value_usd = Decimal::from_integer(90).unwrap();
+        index.upsert(address, &obligation, None).unwrap();
+        index.retain(&HashSet::new());
+        assert!(index.candidates(u64::MAX, 10).is_empty());
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Value};
+use solana_sdk::transaction::Transaction;
+
+/// Send `transactions` as one bundle through a Jito block engine, returning the bundle id
+/// The bundle lands atomically or not at all, so a liquidation is never front-run
+/// between its transactions and is not broadcast to the public mempool.
+pub async fn send_bundle(
+    http: &reqwest::Client,
+    block_engine_url: &str,
+    transactions: &[Transaction],
+) -> Result<String> {
+    let encoded = transactions
+        .iter()
+        .map(|transaction| Ok(bs58::encode(bincode::serialize(transaction)?).into_string()))
+        .collect::<Result<Vec<String>>>()?;
+
+    let response: Value = http
+        .post(format!(
+            "{}/api/v1/bundles",
+            block_engine_url.trim_end_matches('/')
+        ))
+        .json(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "sendBundle",
+            "params": [encoded],
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    if let Some(error) = response.get("error") {
+        bail!("sendBundle failed: {error}");
+    }
+    response["result"]
+        .as_str()
+        .map(str::to_owned)
+        .ok_or_else(|| anyhow!("sendBundle returned no bundle id"))
+}
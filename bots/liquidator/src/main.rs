@@ -0,0 +1,96 @@
+//! Reference liquidation bot for Aura Lend
+//!
+//! Each scan fetches the market's obligations with a getProgramAccounts memcmp filter on
+//! the market, re-indexes them by the health cached at their last refresh and attempts
+//! the least healthy ones within `--watch-health-bps`. Liquidations refresh the
+//! obligation's reserves in the same transaction, carry a priority fee, and are sent
+//! through RPC or, with `--jito-url`, as tipped Jito bundles.
+
+mod config;
+mod executor;
+mod index;
+mod jito;
+
+use anyhow::Result;
+use aura_lend_sdk::client::OBLIGATION_MARKET_OFFSET;
+use aura_lend_sdk::{pda, AuraLendClient, EModeCategory, SdkError};
+use clap::Parser;
+use config::Config;
+use executor::Executor;
+use index::HealthIndex;
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::signature::{read_keypair_file, Signer};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::init();
+    let config = Config::parse();
+    let payer = read_keypair_file(&config.keypair)
+        .map_err(|error| anyhow::anyhow!("reading {}: {error}", config.keypair.display()))?;
+    log::info!("Liquidating as {}", payer.pubkey());
+
+    let client = AuraLendClient::new(config.rpc_url.clone());
+    let mut executor = Executor::new(&client, &config, payer);
+    let mut index = HealthIndex::new();
+    let mut interval = tokio::time::interval(Duration::from_secs(config.scan_interval_secs));
+
+    loop {
+        interval.tick().await;
+        if let Err(error) = scan(&client, &mut index).await {
+            log::warn!("Obligation scan failed: {error:#}");
+            continue;
+        }
+
+        let candidates =
+            index.candidates(config.watch_health_bps, config.max_liquidations_per_scan);
+        log::info!(
+            "{} obligations with debt, {} within the watched health",
+            index.len(),
+            candidates.len()
+        );
+
+        for address in candidates {
+            match executor.liquidate(&address).await {
+                Ok(Some(sent)) => log::info!("Liquidated {address}: {sent}"),
+                Ok(None) => {}
+                Err(error) => log::warn!("Liquidation of {address} failed: {error:#}"),
+            }
+        }
+    }
+}
+
+/// Fetch the market's obligations and re-index them
+async fn scan(client: &AuraLendClient, index: &mut HealthIndex) -> Result<()> {
+    let market = pda::market();
+    let obligations = client
+        .get_obligations_with_filters(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            OBLIGATION_MARKET_OFFSET,
+            market.as_ref(),
+        ))])
+        .await?;
+
+    // eMode categories, fetched once per scan
+    let mut categories: HashMap<u8, EModeCategory> = HashMap::new();
+    let mut live = HashSet::with_capacity(obligations.len());
+
+    for (address, obligation) in &obligations {
+        live.insert(*address);
+
+        let id = obligation.emode_category;
+        if id != 0 && !categories.contains_key(&id) {
+            let category = client
+                .get_account::<EModeCategory>(&pda::emode_category(&market, id))
+                .await?;
+            categories.insert(id, category);
+        }
+
+        let emode = EModeCategory::resolve(categories.get(&id), obligation, None)
+            .map_err(SdkError::from)?;
+        index.upsert(*address, obligation, emode.as_ref())?;
+    }
+
+    index.retain(&live);
+    Ok(())
+}
@@ -0,0 +1,432 @@
+//! `Instruction` builders for Aura Lend's user-facing lending instructions
+//!
+//! PDAs are derived from the reserve's liquidity mint and the signer, and optional
+//! accounts (idempotency nonces, referrers, reward pools, metrics and user statistics)
+//! are left out. Callers needing them, or other instructions, use `build` with the
+//! Anchor-generated `accounts` and `instruction` types.
+
+use crate::{pda, ReserveKeys};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+use anchor_lang::InstructionData;
+use aura_lend::{accounts, instruction, ID};
+use pyth_solana_receiver_sdk::pda::{get_config_address, get_treasury_address};
+use pyth_solana_receiver_sdk::PostUpdateAtomicParams;
+
+/// Obligation an instruction acts on, with the optional accounts its state requires
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ObligationKeys {
+    /// Obligation account
+    pub obligation: Pubkey,
+
+    /// Owner of the obligation, who signs
+    pub owner: Pubkey,
+
+    /// eMode category the obligation opted into, if any
+    pub emode_category: Option<Pubkey>,
+
+    /// Reserve of the obligation's isolated collateral, if it is in isolation mode
+    pub isolated_collateral_reserve: Option<Pubkey>,
+}
+
+/// Liquidator's side of a liquidation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LiquidatorKeys {
+    /// Liquidator, who signs
+    pub liquidator: Pubkey,
+
+    /// Liquidator's token account of the repay asset
+    pub source_liquidity: Pubkey,
+
+    /// Liquidator's token account receiving the seized aTokens
+    pub destination_collateral: Pubkey,
+
+    /// Keeper registration of the liquidator, to liquidate within the keeper priority window
+    pub keeper: Option<Pubkey>,
+}
+
+/// Build an instruction of the Aura Lend program from Anchor-generated types
+/// Covers every instruction: pass the matching `accounts::X` and `instruction::X`, then
+/// append any remaining accounts.
+pub fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Build a `refresh_reserve` instruction
+pub fn refresh_reserve(reserve: &ReserveKeys) -> Instruction {
+    build(
+        accounts::RefreshReserve {
+            market: pda::market(),
+            reserve: reserve.reserve,
+            price_oracle: reserve.price_oracle,
+            secondary_price_oracle: reserve.secondary_oracle,
+        },
+        instruction::RefreshReserve {},
+    )
+}
+
+/// Build a `post_and_refresh_reserve` instruction
+/// `params` carries the Wormhole VAA signed by `guardian_set` and the Merkle price update
+/// of the reserve's feed; the reserve's `price_oracle` must be `pda::price_update`.
+pub fn post_and_refresh_reserve(
+    payer: &Pubkey,
+    reserve: &ReserveKeys,
+    guardian_set: &Pubkey,
+    params: &PostUpdateAtomicParams,
+) -> Instruction {
+    build(
+        accounts::PostAndRefreshReserve {
+            payer: *payer,
+            market: pda::market(),
+            reserve: reserve.reserve,
+            price_oracle: reserve.price_oracle,
+            secondary_price_oracle: reserve.secondary_oracle,
+            pyth_receiver_program: pyth_solana_receiver_sdk::ID,
+            guardian_set: *guardian_set,
+            receiver_config: get_config_address(),
+            receiver_treasury: get_treasury_address(params.treasury_id),
+            system_program: system_program::ID,
+        },
+        instruction::PostAndRefreshReserve {
+            price_update: params
+                .try_to_vec()
+                .expect("serializing into a Vec cannot fail"),
+        },
+    )
+}
+
+/// Build a `refresh_obligation` instruction
+/// `refresh_accounts` are the (reserve, price oracle) pairs from
+/// `obligation_refresh_accounts`.
+pub fn refresh_obligation(obligation: &Pubkey, refresh_accounts: Vec<AccountMeta>) -> Instruction {
+    let mut ix = build(
+        accounts::RefreshObligation {
+            market: pda::market(),
+            config: pda::config(),
+            obligation: *obligation,
+            liquidation_queue: None,
+        },
+        instruction::RefreshObligation {},
+    );
+    ix.accounts.extend(refresh_accounts);
+    ix
+}
+
+/// Build a `deposit_reserve_liquidity` instruction supplying `liquidity_amount`
+pub fn deposit_reserve_liquidity(
+    reserve: &ReserveKeys,
+    user: &Pubkey,
+    source_liquidity: &Pubkey,
+    destination_collateral: &Pubkey,
+    liquidity_amount: u64,
+) -> Instruction {
+    build(
+        accounts::DepositReserveLiquidity {
+            market: pda::market(),
+            reserve: reserve.reserve,
+            liquidity_mint: reserve.liquidity_mint,
+            destination_liquidity: reserve.liquidity_supply,
+            liquidity_supply_authority: pda::liquidity_supply_authority(&reserve.liquidity_mint),
+            collateral_mint: reserve.collateral_mint,
+            collateral_mint_authority: pda::collateral_authority(&reserve.liquidity_mint),
+            source_liquidity: *source_liquidity,
+            destination_collateral: *destination_collateral,
+            user_transfer_authority: *user,
+            flash_loan_guard: pda::flash_loan_guard(user),
+            token_program: reserve.token_program,
+            operation_nonce: None,
+            system_program: system_program::ID,
+            referrer: None,
+            protocol_metrics: None,
+            user_stats: None,
+        },
+        instruction::DepositReserveLiquidity {
+            liquidity_amount,
+            integrator_id: None,
+        },
+    )
+}
+
+/// Build a `redeem_reserve_collateral` instruction burning `collateral_amount` aTokens
+pub fn redeem_reserve_collateral(
+    reserve: &ReserveKeys,
+    user: &Pubkey,
+    source_collateral: &Pubkey,
+    destination_liquidity: &Pubkey,
+    collateral_amount: u64,
+) -> Instruction {
+    build(
+        accounts::RedeemReserveCollateral {
+            market: pda::market(),
+            reserve: reserve.reserve,
+            liquidity_mint: reserve.liquidity_mint,
+            source_liquidity: reserve.liquidity_supply,
+            liquidity_supply_authority: pda::liquidity_supply_authority(&reserve.liquidity_mint),
+            collateral_mint: reserve.collateral_mint,
+            source_collateral: *source_collateral,
+            destination_liquidity: *destination_liquidity,
+            user_transfer_authority: *user,
+            flash_loan_guard: pda::flash_loan_guard(user),
+            token_program: reserve.token_program,
+            config: pda::config(),
+            withdrawal_request: None,
+        },
+        instruction::RedeemReserveCollateral {
+            collateral_amount,
+            integrator_id: None,
+        },
+    )
+}
+
+/// Build a `deposit_obligation_collateral` instruction
+/// `collateral_supply` is the reserve's token account holding obligation collateral,
+/// owned by `pda::collateral_authority`.
+pub fn deposit_obligation_collateral(
+    reserve: &ReserveKeys,
+    obligation: &ObligationKeys,
+    source_collateral: &Pubkey,
+    collateral_supply: &Pubkey,
+    collateral_amount: u64,
+) -> Instruction {
+    build(
+        accounts::DepositObligationCollateral {
+            market: pda::market(),
+            obligation: obligation.obligation,
+            deposit_reserve: reserve.reserve,
+            price_oracle: reserve.price_oracle,
+            collateral_mint: reserve.collateral_mint,
+            source_collateral: *source_collateral,
+            destination_collateral: *collateral_supply,
+            collateral_supply_authority: pda::collateral_authority(&reserve.liquidity_mint),
+            obligation_owner: obligation.owner,
+            flash_loan_guard: pda::flash_loan_guard(&obligation.owner),
+            token_program: reserve.token_program,
+            config: pda::config(),
+            reward_pool: None,
+            obligation_rewards: None,
+        },
+        instruction::DepositObligationCollateral {
+            collateral_amount,
+            integrator_id: None,
+        },
+    )
+}
+
+/// Build a `withdraw_obligation_collateral` instruction
+/// An obligation with borrows must be refreshed first in the same transaction.
+pub fn withdraw_obligation_collateral(
+    reserve: &ReserveKeys,
+    obligation: &ObligationKeys,
+    collateral_supply: &Pubkey,
+    destination_collateral: &Pubkey,
+    collateral_amount: u64,
+) -> Instruction {
+    build(
+        accounts::WithdrawObligationCollateral {
+            market: pda::market(),
+            obligation: obligation.obligation,
+            withdraw_reserve: reserve.reserve,
+            price_oracle: reserve.price_oracle,
+            emode_category: obligation.emode_category,
+            collateral_mint: reserve.collateral_mint,
+            source_collateral: *collateral_supply,
+            destination_collateral: *destination_collateral,
+            collateral_supply_authority: pda::collateral_authority(&reserve.liquidity_mint),
+            obligation_owner: obligation.owner,
+            flash_loan_guard: pda::flash_loan_guard(&obligation.owner),
+            token_program: reserve.token_program,
+            reward_pool: None,
+            obligation_rewards: None,
+        },
+        instruction::WithdrawObligationCollateral {
+            collateral_amount,
+            integrator_id: None,
+        },
+    )
+}
+
+/// Build a `borrow_obligation_liquidity` instruction
+/// The obligation must be refreshed first in the same transaction.
+pub fn borrow_obligation_liquidity(
+    reserve: &ReserveKeys,
+    obligation: &ObligationKeys,
+    destination_liquidity: &Pubkey,
+    liquidity_amount: u64,
+) -> Instruction {
+    let insurance_fund = pda::insurance_fund(&reserve.reserve);
+
+    build(
+        accounts::BorrowObligationLiquidity {
+            market: pda::market(),
+            obligation: obligation.obligation,
+            borrow_reserve: reserve.reserve,
+            price_oracle: reserve.price_oracle,
+            secondary_price_oracle: reserve.secondary_oracle,
+            emode_category: obligation.emode_category,
+            isolated_collateral_reserve: obligation.isolated_collateral_reserve,
+            liquidity_mint: reserve.liquidity_mint,
+            source_liquidity: reserve.liquidity_supply,
+            destination_liquidity: *destination_liquidity,
+            fee_receiver: reserve.fee_receiver,
+            insurance_fund,
+            insurance_fund_vault: pda::insurance_fund_vault(&insurance_fund),
+            liquidity_supply_authority: pda::liquidity_supply_authority(&reserve.liquidity_mint),
+            obligation_owner: obligation.owner,
+            flash_loan_guard: pda::flash_loan_guard(&obligation.owner),
+            token_program: reserve.token_program,
+            operation_nonce: None,
+            system_program: system_program::ID,
+            config: pda::config(),
+            referrer: None,
+            reward_pool: None,
+            obligation_rewards: None,
+            protocol_metrics: None,
+            user_stats: None,
+        },
+        instruction::BorrowObligationLiquidity {
+            liquidity_amount,
+            integrator_id: None,
+        },
+    )
+}
+
+/// Build a `repay_obligation_liquidity` instruction (amounts above the debt are capped)
+pub fn repay_obligation_liquidity(
+    reserve: &ReserveKeys,
+    obligation: &ObligationKeys,
+    source_liquidity: &Pubkey,
+    liquidity_amount: u64,
+) -> Instruction {
+    build(
+        accounts::RepayObligationLiquidity {
+            market: pda::market(),
+            obligation: obligation.obligation,
+            repay_reserve: reserve.reserve,
+            price_oracle: reserve.price_oracle,
+            isolated_collateral_reserve: obligation.isolated_collateral_reserve,
+            liquidity_mint: reserve.liquidity_mint,
+            source_liquidity: *source_liquidity,
+            destination_liquidity: reserve.liquidity_supply,
+            liquidity_supply_authority: pda::liquidity_supply_authority(&reserve.liquidity_mint),
+            obligation_owner: obligation.owner,
+            flash_loan_guard: pda::flash_loan_guard(&obligation.owner),
+            token_program: reserve.token_program,
+            operation_nonce: None,
+            system_program: system_program::ID,
+            reward_pool: None,
+            obligation_rewards: None,
+            liquidation_queue: None,
+            user_stats: None,
+        },
+        instruction::RepayObligationLiquidity {
+            liquidity_amount,
+            integrator_id: None,
+        },
+    )
+}
+
+/// Build a `liquidate_obligation` instruction repaying `liquidity_amount` of debt
+/// `refresh_accounts` are the obligation's (reserve, price oracle) pairs from
+/// `obligation_refresh_accounts`; the obligation is refreshed inline.
+/// `withdraw_collateral_supply` is the withdraw reserve's token account holding
+/// obligation collateral.
+/// The withdraw reserve's insurance fund receives any liquidation protocol fee, so it must
+/// have been initialized.
+pub fn liquidate_obligation(
+    repay_reserve: &ReserveKeys,
+    withdraw_reserve: &ReserveKeys,
+    obligation: &ObligationKeys,
+    liquidator: &LiquidatorKeys,
+    withdraw_collateral_supply: &Pubkey,
+    refresh_accounts: Vec<AccountMeta>,
+    liquidity_amount: u64,
+) -> Instruction {
+    let market = pda::market();
+    let withdraw_insurance_fund = pda::insurance_fund(&withdraw_reserve.reserve);
+
+    let mut ix = build(
+        accounts::LiquidateObligation {
+            market,
+            config: pda::config(),
+            obligation: obligation.obligation,
+            emode_category: obligation.emode_category,
+            repay_reserve: repay_reserve.reserve,
+            withdraw_reserve: withdraw_reserve.reserve,
+            repay_price_oracle: repay_reserve.price_oracle,
+            withdraw_price_oracle: withdraw_reserve.price_oracle,
+            repay_secondary_price_oracle: repay_reserve.secondary_oracle,
+            withdraw_secondary_price_oracle: withdraw_reserve.secondary_oracle,
+            repay_liquidity_mint: repay_reserve.liquidity_mint,
+            withdraw_collateral_mint: withdraw_reserve.collateral_mint,
+            source_liquidity: liquidator.source_liquidity,
+            destination_collateral: liquidator.destination_collateral,
+            repay_reserve_liquidity_supply: repay_reserve.liquidity_supply,
+            withdraw_reserve_collateral_supply: *withdraw_collateral_supply,
+            withdraw_collateral_supply_authority: pda::collateral_authority(
+                &withdraw_reserve.liquidity_mint,
+            ),
+            liquidator: liquidator.liquidator,
+            flash_loan_guard: pda::flash_loan_guard(&liquidator.liquidator),
+            token_program: repay_reserve.token_program,
+            collateral_token_program: withdraw_reserve.token_program,
+            collateral_order_book: None,
+            order_book_liquidity_escrow: None,
+            order_book_collateral_escrow: None,
+            keeper_registry: pda::keeper_registry(&market),
+            keeper: liquidator.keeper,
+            liquidation_queue: None,
+            liquidation_commitment: None,
+            protocol_metrics: None,
+            user_stats: None,
+            withdraw_liquidity_supply_authority: pda::liquidity_supply_authority(
+                &withdraw_reserve.liquidity_mint,
+            ),
+            withdraw_liquidity_mint: Some(withdraw_reserve.liquidity_mint),
+            withdraw_reserve_liquidity_supply: Some(withdraw_reserve.liquidity_supply),
+            withdraw_insurance_fund: Some(withdraw_insurance_fund),
+            withdraw_insurance_fund_vault: Some(pda::insurance_fund_vault(
+                &withdraw_insurance_fund,
+            )),
+        },
+        instruction::LiquidateObligation {
+            liquidity_amount,
+            integrator_id: None,
+        },
+    );
+    ix.accounts.extend(refresh_accounts);
+    ix
+}
+
+/// Build a `liquidate_dust_position` instruction repaying up to the full debt of a borrow
+/// below the protocol's minimum position value
+/// Takes the same accounts as `liquidate_obligation`.
+pub fn liquidate_dust_position(
+    repay_reserve: &ReserveKeys,
+    withdraw_reserve: &ReserveKeys,
+    obligation: &ObligationKeys,
+    liquidator: &LiquidatorKeys,
+    withdraw_collateral_supply: &Pubkey,
+    refresh_accounts: Vec<AccountMeta>,
+    liquidity_amount: u64,
+) -> Instruction {
+    let mut ix = liquidate_obligation(
+        repay_reserve,
+        withdraw_reserve,
+        obligation,
+        liquidator,
+        withdraw_collateral_supply,
+        refresh_accounts,
+        liquidity_amount,
+    );
+    ix.data = instruction::LiquidateDustPosition {
+        liquidity_amount,
+        integrator_id: None,
+    }
+    .data();
+    ix
+}
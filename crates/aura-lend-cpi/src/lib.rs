@@ -0,0 +1,106 @@
+//! CPI interface for Aura Lend
+//!
+//! Other programs depend on this crate instead of copying account lists and seeds out of
+//! the program. It re-exports the Anchor-generated CPI module and account types, and adds:
+//! - `pda`: addresses of the program's PDAs
+//! - `ix`: `Instruction` builders for the user-facing lending instructions, with the PDAs
+//!   filled in, for off-chain clients and `invoke`-style callers
+//! - `ReserveKeys` and `obligation_refresh_accounts` for assembling account metas
+//!
+//! Anchor programs CPI through the re-exported `cpi` module:
+//!
+//! ```ignore
+//! use aura_lend_cpi::cpi::{self, accounts::DepositReserveLiquidity};
+//!
+//! let ctx = CpiContext::new(aura_lend_program.to_account_info(), DepositReserveLiquidity {
+//!     market: market.to_account_info(),
+//!     reserve: reserve.to_account_info(),
+//!     // ...
+//!     user_stats: None,
+//! });
+//! let receipt = cpi::deposit_reserve_liquidity(ctx, amount, None)?.get();
+//! ```
+
+pub mod ix;
+pub mod pda;
+
+pub use aura_lend::program::AuraLend;
+pub use aura_lend::state::operation_receipt::OperationReceipt;
+pub use aura_lend::{accounts, cpi, instruction, ID};
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::AccountMeta;
+use aura_lend::state::{Obligation, Reserve};
+
+/// Addresses a reserve's instructions need, read once from the reserve account
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReserveKeys {
+    /// Reserve account
+    pub reserve: Pubkey,
+
+    /// Mint of the reserve's liquidity
+    pub liquidity_mint: Pubkey,
+
+    /// Mint of the reserve's collateral (aToken)
+    pub collateral_mint: Pubkey,
+
+    /// Token account holding the reserve's liquidity
+    pub liquidity_supply: Pubkey,
+
+    /// Token account receiving origination fees
+    pub fee_receiver: Pubkey,
+
+    /// Primary price oracle
+    pub price_oracle: Pubkey,
+
+    /// Secondary price oracle, if the reserve has one
+    pub secondary_oracle: Option<Pubkey>,
+
+    /// Token program owning the liquidity mint (SPL Token or Token-2022)
+    pub token_program: Pubkey,
+}
+
+impl ReserveKeys {
+    /// Read the keys of the reserve at `address`
+    pub fn new(address: Pubkey, reserve: &Reserve, token_program: Pubkey) -> Self {
+        Self {
+            reserve: address,
+            liquidity_mint: reserve.liquidity_mint,
+            collateral_mint: reserve.collateral_mint,
+            liquidity_supply: reserve.liquidity_supply,
+            fee_receiver: reserve.fee_receiver,
+            price_oracle: reserve.price_oracle,
+            secondary_oracle: (reserve.secondary_oracle != Pubkey::default())
+                .then_some(reserve.secondary_oracle),
+            token_program,
+        }
+    }
+}
+
+/// Remaining accounts `refresh_obligation` revalues `obligation` from
+/// A (reserve, price oracle) pair for each deposit, then for each borrow. Liquidations
+/// refresh the obligation inline and take the same accounts. Returns None if `reserves`
+/// lacks a reserve the obligation has a position in.
+pub fn obligation_refresh_accounts(
+    obligation: &Obligation,
+    reserves: &[ReserveKeys],
+) -> Option<Vec<AccountMeta>> {
+    let position_reserves = obligation
+        .deposits
+        .iter()
+        .map(|deposit| deposit.deposit_reserve)
+        .chain(
+            obligation
+                .borrows
+                .iter()
+                .map(|borrow| borrow.borrow_reserve),
+        );
+
+    let mut metas = Vec::with_capacity(2 * (obligation.deposits.len() + obligation.borrows.len()));
+    for reserve_key in position_reserves {
+        let keys = reserves.iter().find(|keys| keys.reserve == reserve_key)?;
+        metas.push(AccountMeta::new_readonly(keys.reserve, false));
+        metas.push(AccountMeta::new_readonly(keys.price_oracle, false));
+    }
+    Some(metas)
+}
@@ -0,0 +1,175 @@
+//! Addresses of Aura Lend's program derived accounts
+
+use anchor_lang::prelude::*;
+use aura_lend::constants::*;
+use aura_lend::state::{AuthorityKind, Obligation};
+use aura_lend::utils::PROTOCOL_METRICS_SEED;
+use aura_lend::ID;
+
+/// Market account
+pub fn market() -> Pubkey {
+    Pubkey::find_program_address(&[MARKET_SEED], &ID).0
+}
+
+/// Protocol configuration account
+pub fn config() -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], &ID).0
+}
+
+/// Reserve of `liquidity_mint`
+pub fn reserve(liquidity_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[RESERVE_SEED, liquidity_mint.as_ref()], &ID).0
+}
+
+/// Collateral (aToken) mint of the reserve of `liquidity_mint`
+pub fn collateral_mint(liquidity_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[COLLATERAL_TOKEN_SEED, liquidity_mint.as_ref()], &ID).0
+}
+
+/// Authority minting the reserve's collateral and holding obligation collateral
+pub fn collateral_authority(liquidity_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[COLLATERAL_TOKEN_SEED, liquidity_mint.as_ref(), b"authority"],
+        &ID,
+    )
+    .0
+}
+
+/// Liquidity supply token account of the reserve of `liquidity_mint`
+pub fn liquidity_supply(liquidity_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[LIQUIDITY_TOKEN_SEED, liquidity_mint.as_ref()], &ID).0
+}
+
+/// Authority of the reserve's liquidity supply
+pub fn liquidity_supply_authority(liquidity_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[LIQUIDITY_TOKEN_SEED, liquidity_mint.as_ref(), b"authority"],
+        &ID,
+    )
+    .0
+}
+
+/// Obligation number `obligation_index` of `owner` (0 is the original obligation)
+pub fn obligation(owner: &Pubkey, obligation_index: u8) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            OBLIGATION_SEED,
+            owner.as_ref(),
+            Obligation::index_seed(&obligation_index),
+        ],
+        &ID,
+    )
+    .0
+}
+
+/// Flash loan guard of `user`, passed to every instruction the user signs
+pub fn flash_loan_guard(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[FLASH_LOAN_GUARD_SEED, user.as_ref()], &ID).0
+}
+
+/// Insurance fund of `reserve`
+pub fn insurance_fund(reserve: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[INSURANCE_FUND_SEED, reserve.as_ref()], &ID).0
+}
+
+/// Token account holding the liquidity of `insurance_fund`
+pub fn insurance_fund_vault(insurance_fund: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[INSURANCE_FUND_SEED, insurance_fund.as_ref(), b"liquidity"],
+        &ID,
+    )
+    .0
+}
+
+/// Supply queue of `reserve`
+pub fn supply_queue(reserve: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[SUPPLY_QUEUE_SEED, reserve.as_ref()], &ID).0
+}
+
+/// Escrow token account of `supply_queue`, `side` being `b"liquidity"` or `b"collateral"`
+pub fn supply_queue_escrow(supply_queue: &Pubkey, side: &[u8]) -> Pubkey {
+    Pubkey::find_program_address(&[SUPPLY_QUEUE_SEED, supply_queue.as_ref(), side], &ID).0
+}
+
+/// eMode category `id` of `market`
+pub fn emode_category(market: &Pubkey, id: u8) -> Pubkey {
+    Pubkey::find_program_address(&[EMODE_CATEGORY_SEED, market.as_ref(), &[id]], &ID).0
+}
+
+/// Referrer account of `owner` in `market`
+pub fn referrer(market: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[REFERRER_SEED, market.as_ref(), owner.as_ref()], &ID).0
+}
+
+/// Keeper registry of `market`
+pub fn keeper_registry(market: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[KEEPER_REGISTRY_SEED, market.as_ref()], &ID).0
+}
+
+/// Keeper registration of `authority`
+pub fn keeper(keeper_registry: &Pubkey, authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[KEEPER_SEED, keeper_registry.as_ref(), authority.as_ref()],
+        &ID,
+    )
+    .0
+}
+
+/// Liquidation queue of `market`
+pub fn liquidation_queue(market: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[LIQUIDATION_QUEUE_SEED, market.as_ref()], &ID).0
+}
+
+/// Protocol metrics of `market`
+pub fn protocol_metrics(market: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[PROTOCOL_METRICS_SEED, market.as_ref()], &ID).0
+}
+
+/// Governance registry of `multisig` (the market's `multisig_owner`)
+pub fn governance(multisig: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[GOVERNANCE_SEED, multisig.as_ref()], &ID).0
+}
+
+/// Timelock controller of `multisig`
+pub fn timelock(multisig: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[TIMELOCK_SEED, multisig.as_ref()], &ID).0
+}
+
+/// Statistics of `owner` in `market`
+pub fn user_stats(market: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[USER_STATS_SEED, market.as_ref(), owner.as_ref()], &ID).0
+}
+
+/// Pyth pull-oracle price update account of `reserve`, used as its `price_oracle`
+pub fn price_update(reserve: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[PRICE_UPDATE_SEED, reserve.as_ref()], &ID).0
+}
+
+/// Standing deleverage order of `obligation`
+pub fn standing_order(obligation: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[STANDING_ORDER_SEED, obligation.as_ref()], &ID).0
+}
+
+/// Pending transfer of the `kind` authority held by `target`
+pub fn authority_transfer(kind: AuthorityKind, target: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[AUTHORITY_TRANSFER_SEED, &[kind as u8], target.as_ref()],
+        &ID,
+    )
+    .0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_original_obligation_has_no_index_seed() {
+        let owner = Pubkey::new_unique();
+        let original = Pubkey::find_program_address(&[OBLIGATION_SEED, owner.as_ref()], &ID).0;
+
+        assert_eq!(obligation(&owner, 0), original);
+        assert_ne!(obligation(&owner, 1), original);
+        assert_ne!(obligation(&owner, 1), obligation(&owner, 2));
+    }
+}
@@ -4,6 +4,10 @@ use anchor_lang::prelude::*;
 /// Current program version for upgrade compatibility
 pub const PROGRAM_VERSION: u8 = 1;
 
+/// Reserve account version storing rates in bps and packing the reentrancy guard into
+/// the state flags; older reserves are compacted by `migrate_reserve`
+pub const RESERVE_VERSION: u8 = 2;
+
 /// Seeds used for Program Derived Address (PDA) generation
 pub const MARKET_SEED: &[u8] = b"market";
 pub const RESERVE_SEED: &[u8] = b"reserve";
@@ -16,6 +20,102 @@ pub const MULTISIG_SEED: &[u8] = b"multisig";
 pub const TIMELOCK_SEED: &[u8] = b"timelock";
 pub const GOVERNANCE_SEED: &[u8] = b"governance";
 
+/// Liquidation backstop vault seed
+pub const BACKSTOP_VAULT_SEED: &[u8] = b"backstop_vault";
+
+/// Large withdrawal request seed
+pub const LARGE_WITHDRAWAL_SEED: &[u8] = b"large_withdrawal";
+
+/// Transient flash loan usage guard seed
+pub const FLASH_LOAN_GUARD_SEED: &[u8] = b"flash_loan_guard";
+
+/// Market address lookup table record seed
+pub const LOOKUP_TABLE_SEED: &[u8] = b"lookup_table";
+
+/// Per-epoch realized borrow-rate index seed
+pub const RATE_INDEX_SEED: &[u8] = b"rate_index";
+
+/// Staged program upgrade seed
+pub const STAGED_UPGRADE_SEED: &[u8] = b"staged_upgrade";
+
+/// Pending obligation ownership transfer seed
+pub const OBLIGATION_TRANSFER_SEED: &[u8] = b"obligation_transfer";
+
+/// Pending authority transfer seed
+pub const AUTHORITY_TRANSFER_SEED: &[u8] = b"authority_transfer";
+
+/// Obligation sub-account record seed
+pub const SUB_ACCOUNTS_SEED: &[u8] = b"sub_accounts";
+
+/// Rent reclamation consent seed
+pub const RENT_RECLAIM_CONSENT_SEED: &[u8] = b"rent_reclaim_consent";
+
+/// eMode category seed
+pub const EMODE_CATEGORY_SEED: &[u8] = b"emode_category";
+
+/// Delegated credit line seed
+pub const CREDIT_LINE_SEED: &[u8] = b"credit_line";
+
+/// USD-capped borrowing power delegation seed
+pub const CREDIT_DELEGATION_SEED: &[u8] = b"credit_delegation";
+
+/// Protocol fee converter seed
+pub const FEE_CONVERTER_SEED: &[u8] = b"fee_converter";
+
+/// Collateral limit order book seed
+pub const COLLATERAL_ORDER_BOOK_SEED: &[u8] = b"collateral_orders";
+
+/// Per-reserve queue of capped deposits and withdrawals seed
+pub const SUPPLY_QUEUE_SEED: &[u8] = b"supply_queue";
+
+/// Admin audit log seed
+pub const ADMIN_AUDIT_LOG_SEED: &[u8] = b"admin_audit_log";
+
+/// Per-reserve insurance fund seed
+pub const INSURANCE_FUND_SEED: &[u8] = b"insurance_fund";
+
+/// Dutch liquidation auction seed
+pub const LIQUIDATION_AUCTION_SEED: &[u8] = b"liquidation_auction";
+
+/// Referrer account seed
+pub const REFERRER_SEED: &[u8] = b"referrer";
+
+/// Reserve reward pool seed
+pub const REWARD_POOL_SEED: &[u8] = b"reward_pool";
+
+/// Per-obligation reward participation seed
+pub const OBLIGATION_REWARDS_SEED: &[u8] = b"obligation_rewards";
+
+/// aToken stake vault seed
+pub const STAKE_VAULT_SEED: &[u8] = b"stake_vault";
+
+/// Stake position (non-transferable receipt) seed
+pub const STAKE_POSITION_SEED: &[u8] = b"stake_position";
+
+/// External yield venue seed
+pub const YIELD_VENUE_SEED: &[u8] = b"yield_venue";
+
+/// Keeper registry seed
+pub const KEEPER_REGISTRY_SEED: &[u8] = b"keeper_registry";
+
+/// Registered keeper seed
+pub const KEEPER_SEED: &[u8] = b"keeper";
+
+/// Per-market liquidation queue seed
+pub const LIQUIDATION_QUEUE_SEED: &[u8] = b"liquidation_queue";
+
+/// Liquidation commitment seed (per obligation and liquidator)
+pub const LIQUIDATION_COMMITMENT_SEED: &[u8] = b"liquidation_commitment";
+
+/// Per-user statistics seed (per market and owner)
+pub const USER_STATS_SEED: &[u8] = b"user_stats";
+
+/// Pyth pull-oracle price update seed (per reserve)
+pub const PRICE_UPDATE_SEED: &[u8] = b"price_update";
+
+/// Standing deleverage order seed (per obligation)
+pub const STANDING_ORDER_SEED: &[u8] = b"standing_order";
+
 /// Maximum number of reserves allowed in a single market
 /// Optimized for efficient memory usage and gas costs
 pub const MAX_BATCH_OPERATIONS: usize = 20;
@@ -51,8 +151,34 @@ pub const MAX_LOAN_TO_VALUE_RATIO_BPS: u64 = 9000;
 
 /// Maximum age of oracle data in slots before considered stale (~90 seconds)
 pub const MAX_ORACLE_STALENESS_SLOTS: u64 = 180;
+/// Widest oracle confidence interval accepted, in basis points of the price (2%, as
+/// checked by `OraclePrice::validate`)
+pub const MAX_ORACLE_CONFIDENCE_BPS: u64 = 200;
+/// Maximum age of an obligation refresh, in slots, for borrows and withdrawals against it
+/// (0 = refreshed in the same slot, normally the same transaction)
+pub const MAX_OBLIGATION_REFRESH_AGE_SLOTS: u64 = 0;
 /// Emergency oracle staleness limit for extreme situations (~1.5 hours)
 pub const EMERGENCY_ORACLE_STALENESS_SLOTS: u64 = 10800;
+/// Exponent of `fixed_price` for reserves priced by governance (8 decimals)
+pub const FIXED_PRICE_EXPONENT: i32 = -8;
+/// Longest TWAP window governance can configure for a reserve (1 hour)
+pub const MAX_TWAP_WINDOW_SECONDS: u64 = 3600;
+/// Highest surge withdrawal fee governance can configure (5%)
+pub const MAX_SURGE_WITHDRAWAL_FEE_BPS: u16 = 500;
+/// Longest high-utilization period governance can require before the surge fee (~1 day)
+pub const MAX_SURGE_DURATION_SLOTS: u64 = 216_000;
+/// Shortest wind-down governance can give a deprecated reserve (7 days)
+pub const MIN_DEPRECATION_PERIOD_SECONDS: u64 = 7 * 24 * 3600;
+/// SPL stake pool program that owns the stake pools of derived LST prices
+pub const SPL_STAKE_POOL_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
+
+/// Maximum decimals of a token priced into a derived LP price
+pub const MAX_DERIVED_TOKEN_DECIMALS: u8 = 18;
+
+/// Switchboard V2 program that owns aggregator accounts
+pub const SWITCHBOARD_V2_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f");
 
 // Time manipulation protection
 pub const MIN_INTEREST_UPDATE_INTERVAL: u64 = 60; // 1 minute minimum between updates
@@ -64,9 +190,158 @@ pub const SLOT_TIMESTAMP_VARIANCE_BPS: u64 = 1000; // 10% variance allowed
 pub const MIN_DEPOSIT_AMOUNT: u64 = 10000; // Minimum deposit in base units
 pub const MIN_BORROW_AMOUNT: u64 = 10000; // Minimum borrow in base units
 
+// Liquidation backstop parameters
+/// Default slots external liquidators get before the backstop vault may step in (~60 seconds)
+pub const DEFAULT_BACKSTOP_GRACE_SLOTS: u64 = 150;
+/// Upper bound on the backstop grace window (~1 hour)
+pub const MAX_BACKSTOP_GRACE_SLOTS: u64 = 9000;
+
+// Liquidation auction parameters
+/// Slots over which an auction's discount rises to its maximum (~2 minutes)
+pub const LIQUIDATION_AUCTION_DURATION_SLOTS: u64 = 300;
+/// Slots after which an unfinished auction may be closed (~1 hour)
+pub const LIQUIDATION_AUCTION_EXPIRY_SLOTS: u64 = 9000;
+/// Auction discounts rise to this multiple of the fixed liquidation bonus
+pub const LIQUIDATION_AUCTION_DISCOUNT_MULTIPLIER: u64 = 2;
+
+// Liquidation grace period parameters
+/// Longest grace window a borrower can opt into (~5 minutes)
+pub const MAX_LIQUIDATION_GRACE_SLOTS: u64 = 750;
+/// Lowest health factor (basis points) a grace window may shield the obligation down to
+pub const MIN_LIQUIDATION_GRACE_FLOOR_BPS: u64 = 9000;
+
+// Batch liquidation parameters
+/// Most obligations a single batch liquidation may cover
+pub const MAX_BATCH_LIQUIDATIONS: usize = 10;
+/// Remaining accounts in each batch liquidation entry, before its refresh accounts
+pub const BATCH_LIQUIDATION_ENTRY_ACCOUNTS: usize = 14;
+
+// Referral parameters
+/// Reserves a single referrer can accrue fees in
+pub const MAX_REFERRER_RESERVES: usize = 16;
+/// Default share of borrow fees paid to the borrower's referrer (20%)
+pub const DEFAULT_REFERRAL_FEE_SHARE_BPS: u64 = 2000;
+/// Largest share of borrow fees governance may route to referrers (50%)
+pub const MAX_REFERRAL_FEE_SHARE_BPS: u64 = 5000;
+
+// User statistics parameters
+/// Borrow positions whose principal a user's statistics track
+pub const MAX_USER_STATS_TRACKED_BORROWS: usize = 16;
+
+// Reward emission parameters
+/// Longest emission schedule governance may set in one call (~1 year at 400ms slots)
+pub const MAX_REWARD_SCHEDULE_SLOTS: u64 = 78_840_000;
+
+// aToken staking parameters
+/// Longest unstake cooldown governance may configure (30 days)
+pub const MAX_STAKE_COOLDOWN_SECONDS: i64 = 30 * 24 * 3600;
+/// Window after the cooldown in which a requested unstake may execute (2 days)
+pub const STAKE_UNSTAKE_WINDOW_SECONDS: i64 = 2 * 24 * 3600;
+/// Virtual shares per virtual aToken, guarding the share price against donations
+pub const STAKE_VAULT_VIRTUAL_SHARES: u64 = 1_000;
+
+// Yield venue parameters
+/// Upper bound on the loss a divestment may realize against its cost basis (1%)
+pub const MAX_YIELD_VENUE_SLIPPAGE_BPS: u64 = 100;
+
+// Keeper registry parameters
+/// Default slots newly unhealthy obligations are reserved for registered keepers (~8 seconds)
+pub const DEFAULT_KEEPER_PRIORITY_WINDOW_SLOTS: u64 = 20;
+/// Upper bound on the keeper priority window (~1 minute)
+pub const MAX_KEEPER_PRIORITY_WINDOW_SLOTS: u64 = 150;
+/// Longest exit cooldown governance may configure (~7 days)
+pub const MAX_KEEPER_EXIT_COOLDOWN_SLOTS: u64 = 1_512_000;
+
+// Position size parameters
+/// Default smallest position value, in whole USD, an obligation may open or keep
+pub const DEFAULT_MIN_POSITION_VALUE_USD: u64 = 10;
+/// Upper bound on the configurable minimum position value
+pub const MAX_MIN_POSITION_VALUE_USD: u64 = 10_000;
+
+// Liquidation queue parameters
+/// Unhealthy obligations the liquidation queue holds before the stalest are overwritten
+pub const LIQUIDATION_QUEUE_CAPACITY: usize = 128;
+/// Slots a queue entry stays listed without a refresh (~10 minutes)
+pub const LIQUIDATION_QUEUE_ENTRY_TTL_SLOTS: u64 = 1_500;
+
+// Liquidation commit-reveal parameters
+/// Slots between committing to a liquidation and revealing it
+pub const MIN_LIQUIDATION_REVEAL_DELAY_SLOTS: u64 = 2;
+/// Slots after which an unrevealed liquidation commitment expires (~1 minute)
+pub const LIQUIDATION_COMMITMENT_EXPIRY_SLOTS: u64 = 150;
+
+// Standing deleverage order parameters
+/// Largest keeper tip a standing order may pay on the repaid value (5%)
+pub const MAX_STANDING_ORDER_TIP_BPS: u16 = 500;
+
+// Large withdrawal parameters
+/// Redemptions above this share of reserve liquidity must be pre-announced (10%)
+pub const DEFAULT_LARGE_WITHDRAWAL_THRESHOLD_BPS: u64 = 1000;
+/// Default delay before a pre-announced large withdrawal may execute (~2.5 minutes)
+pub const DEFAULT_LARGE_WITHDRAWAL_DELAY_SLOTS: u64 = 300;
+/// Window after maturity during which a large withdrawal request stays valid (~1 hour)
+pub const LARGE_WITHDRAWAL_EXECUTION_WINDOW_SLOTS: u64 = 9000;
+/// Redemptions may not push a reserve's utilization above this (95%)
+pub const DEFAULT_MAX_WITHDRAW_UTILIZATION_BPS: u64 = 9500;
+
+// Fee conversion parameters
+/// Maximum DEX programs fee conversions may be routed through
+pub const MAX_SWAP_PROGRAMS: usize = 4;
+/// Upper bound on fee conversion slippage against oracle prices (5%)
+pub const MAX_FEE_CONVERSION_SLIPPAGE_BPS: u64 = 500;
+
+/// Finalized epochs retained by a reserve rate index
+pub const MAX_RATE_INDEX_EPOCHS: usize = 64;
+
+// Credit delegation parameters
+/// Upper bound on the markup a delegatee may owe a delegator (50% per year)
+pub const MAX_CREDIT_LINE_MARKUP_BPS: u64 = 5000;
+
+// Sub-account parameters
+/// Sub-account holding every position share not assigned elsewhere
+pub const DEFAULT_SUB_ACCOUNT: u8 = 0;
+/// Sub-accounts per obligation, including the default sub-account 0
+pub const MAX_SUB_ACCOUNTS: usize = 4;
+/// Position shares that may be assigned away from the default sub-account
+pub const MAX_SUB_ACCOUNT_ALLOCATIONS: usize = 16;
+/// Length of a sub-account label in bytes
+pub const SUB_ACCOUNT_LABEL_LEN: usize = 16;
+
+// eMode parameters
+/// Category id meaning the obligation is not in eMode
+pub const EMODE_NONE: u8 = 0;
+/// Maximum loan-to-value ratio an eMode category may grant (97%)
+pub const MAX_EMODE_LOAN_TO_VALUE_RATIO_BPS: u64 = 9700;
+/// Reserves a single eMode category may contain
+pub const MAX_EMODE_RESERVES: usize = 8;
+/// Length of an eMode category label in bytes
+pub const EMODE_LABEL_LEN: usize = 16;
+
+// Admin audit log parameters
+/// Entries kept in the admin audit log before the oldest are overwritten
+pub const ADMIN_AUDIT_LOG_CAPACITY: usize = 100;
+
+// Rent reclamation parameters
+/// Shortest inactivity period a user may consent to before empty accounts are swept (~30 days)
+pub const MIN_RENT_RECLAIM_INACTIVITY_SLOTS: u64 = SLOTS_PER_YEAR / 12;
+
+/// Maximum open orders per collateral order book
+pub const MAX_COLLATERAL_ORDERS: usize = 32;
+
+/// Maximum queued deposits and withdrawals per supply queue
+pub const MAX_SUPPLY_QUEUE_ENTRIES: usize = 64;
+
+/// Slots an operation nonce must be retained before it can be closed
+/// Comfortably exceeds recent blockhash validity (~150 slots) so a retried
+/// transaction can never land after its nonce record was reclaimed
+pub const OPERATION_NONCE_RETENTION_SLOTS: u64 = 1500;
+
 // Flash loan parameters
 pub const FLASH_LOAN_FEE_BPS: u64 = 9; // 0.09%
 
+// Maximum borrow origination fee
+pub const MAX_BORROW_FEE_BPS: u64 = 500; // 5%
+
 // Reserve configuration limits
 pub const MAX_UTILIZATION_RATE_BPS: u64 = 10000; // 100%
 pub const OPTIMAL_UTILIZATION_RATE_BPS: u64 = 8000; // 80%
@@ -100,11 +375,24 @@ pub const RESERVE_SIZE: usize = 8 + // discriminator
     32 + // price_oracle
     32 + // oracle_feed_id
     std::mem::size_of::<ReserveConfig>() + // config (approximately 80 bytes)
-    std::mem::size_of::<ReserveState>() + // state (approximately 120 bytes)
+    std::mem::size_of::<ReserveState>() + // state (approximately 96 bytes)
     8 + // last_update_timestamp
     8 + // last_update_slot
-    1 + // reentrancy_guard
-    255; // reserved
+    102 + // fee_split
+    1 + // asset_class
+    1 + // oracle_source
+    8 + // fixed_price
+    32 + // secondary_oracle
+    1 + // secondary_oracle_source
+    8 + // max_oracle_deviation_bps
+    8 + // twap_price
+    4 + // twap_exponent
+    8 + // twap_last_update_timestamp
+    8 + // twap_window_seconds
+    8 + // deprecation_start_timestamp
+    8 + // deprecation_deadline_timestamp
+    2 + // deleverage_penalty_bps
+    8; // reserved
 
 pub const OBLIGATION_SIZE: usize = 8 + // discriminator
     1 + // version
@@ -122,6 +410,9 @@ pub const OBLIGATION_SIZE: usize = 8 + // discriminator
 // Optimized for gas efficiency and account size
 pub const MAX_OBLIGATION_RESERVES: usize = 12;
 
+// Maximum number of obligations a wallet can hold in the market
+pub const MAX_OBLIGATIONS_PER_OWNER: u8 = 8;
+
 // RBAC Timelock delays (in seconds)
 /// Critical operations - 7 days
 pub const TIMELOCK_DELAY_CRITICAL: u64 = 7 * 24 * 3600; // 604,800 seconds
@@ -152,6 +443,10 @@ pub const EMERGENCY_ROLE_MAX_DURATION: i64 = 24 * 3600;
 pub const MAX_MULTISIG_SIGNATORIES: usize = 15;
 /// Minimum threshold for multisig
 pub const MIN_MULTISIG_THRESHOLD: u8 = 1;
+/// Lifetime of a multisig proposal created without an expiry
+pub const MULTISIG_PROPOSAL_DEFAULT_LIFETIME: i64 = 7 * 24 * 3600; // 7 days
+/// Longest lifetime a multisig proposal may request
+pub const MULTISIG_PROPOSAL_MAX_LIFETIME: i64 = 30 * 24 * 3600; // 30 days
 
 // Governance constraints
 /// Maximum number of concurrent roles per registry
@@ -167,6 +462,9 @@ pub const DEFAULT_PROTOCOL_FEE: u64 = 100;
 pub const MAX_PROTOCOL_FEE: u64 = 500;
 /// Liquidation close factor (50%)
 pub const LIQUIDATION_CLOSE_FACTOR: u64 = 5000;
+/// Health factor at and below which a liquidation may repay a whole borrow (0.95)
+/// Between this and 1.0 the close factor scales linearly up from the configured one.
+pub const FULL_CLOSE_FACTOR_HEALTH_BPS: u64 = 9500;
 /// Maximum liquidation bonus (20%)
 pub const MAX_LIQUIDATION_BONUS: u64 = 2000;
 /// Minimum health factor (1.0)
@@ -185,6 +483,28 @@ pub const MIN_ORACLE_SOURCES: u8 = 3;
 pub const DEFAULT_TIMELOCK_DELAY: u64 = 3600;
 /// Compute unit limit for instructions (conservative)
 pub const COMPUTE_UNIT_LIMIT: u32 = 300_000;
+/// Maximum compute units a single transaction may request
+pub const MAX_TRANSACTION_COMPUTE_UNITS: u32 = 1_400_000;
+/// Compute units a batch keeps for the work after its last step (writing back, logging)
+pub const BATCH_COMPUTE_UNITS_RESERVE: u64 = 25_000;
+
+// Compute unit budgets of single instructions, asserted by tests/compute_budget.rs and
+// used as the first step estimate of the matching batch instructions
+/// refresh_reserve
+pub const REFRESH_RESERVE_COMPUTE_BUDGET: u64 = 35_000;
+/// deposit_reserve_liquidity
+pub const DEPOSIT_COMPUTE_BUDGET: u64 = 60_000;
+/// borrow_obligation_liquidity, after refresh_obligation
+pub const BORROW_COMPUTE_BUDGET: u64 = 90_000;
+/// repay_obligation_liquidity
+pub const REPAY_COMPUTE_BUDGET: u64 = 60_000;
+/// liquidate_obligation, including its inline obligation refresh
+pub const LIQUIDATE_COMPUTE_BUDGET: u64 = 160_000;
+/// One operation of process_batch_operations
+pub const BATCH_OPERATION_COMPUTE_BUDGET: u64 = 10_000;
+/// Upper bounds (exclusive, in health bps) of the health factor buckets used by
+/// indexes and metrics; the first bucket is the liquidatable range
+pub const HEALTH_BUCKET_BOUNDARIES_BPS: [u64; 5] = [10_000, 10_500, 11_000, 12_500, 15_000];
 /// Maximum accounts per instruction
 pub const MAX_ACCOUNTS_PER_INSTRUCTION: u8 = 32;
 /// Default pagination limit (optimized for RPC calls)
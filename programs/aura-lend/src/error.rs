@@ -37,6 +37,24 @@ pub enum LendingError {
     ReserveLiquidityMintMismatch,
     #[msg("Reserve collateral mint mismatch")]
     ReserveCollateralMintMismatch,
+    #[msg("Large withdrawal must be requested in advance")]
+    LargeWithdrawalRequestRequired,
+    #[msg("Large withdrawal request does not cover this redemption")]
+    LargeWithdrawalRequestMismatch,
+    #[msg("Withdrawal would push reserve utilization above the cap")]
+    WithdrawUtilizationTooHigh,
+    #[msg("Reserve supply cap exceeded")]
+    SupplyCapExceeded,
+    #[msg("Reserve borrow cap exceeded")]
+    BorrowCapExceeded,
+    #[msg("Reserve still holds liquidity, borrows, collateral or fees")]
+    ReserveNotEmpty,
+    #[msg("Neither reserve is past its deprecation deadline")]
+    ReserveNotDeprecated,
+    #[msg("Supply queue is full")]
+    SupplyQueueFull,
+    #[msg("Supply queue entry not found")]
+    SupplyQueueEntryNotFound,
 
     // Obligation errors
     #[msg("Obligation is not healthy")]
@@ -55,8 +73,32 @@ pub enum LendingError {
     ObligationStale,
     #[msg("Cannot liquidate healthy obligation")]
     ObligationHealthy,
+    #[msg("Obligation index is out of range")]
+    InvalidObligationIndex,
     #[msg("Liquidation amount too large")]
     LiquidationTooLarge,
+    #[msg("Backstop grace period has not elapsed")]
+    BackstopGracePeriodActive,
+    #[msg("Backstop vault is disabled")]
+    BackstopVaultDisabled,
+    #[msg("Collateral order book is full")]
+    CollateralOrderBookFull,
+    #[msg("Collateral order not found")]
+    CollateralOrderNotFound,
+    #[msg("Obligation debt is still backed by collateral")]
+    ObligationDebtStillBacked,
+    #[msg("Liquidation auction is still running")]
+    LiquidationAuctionActive,
+    #[msg("Accounts keyed by the obligation must be closed first")]
+    ObligationHasLinkedAccounts,
+    #[msg("Obligation is within its liquidation grace period")]
+    LiquidationGracePeriodActive,
+    #[msg("Obligation health is above the standing order's trigger")]
+    StandingOrderNotTriggered,
+    #[msg("Position is below the minimum position value")]
+    PositionTooSmall,
+    #[msg("Position is above the dust threshold")]
+    PositionNotDust,
 
     // Oracle errors
     #[msg("Oracle price is stale")]
@@ -67,6 +109,8 @@ pub enum LendingError {
     OracleAccountMismatch,
     #[msg("Oracle confidence too wide")]
     OracleConfidenceTooWide,
+    #[msg("Primary and secondary oracle prices diverge beyond the allowed threshold")]
+    OraclePriceDeviationTooHigh,
 
     // Token errors
     #[msg("Insufficient token balance")]
@@ -87,6 +131,8 @@ pub enum LendingError {
     AuthoritySignerMissing,
     #[msg("Unauthorized signer")]
     UnauthorizedSigner,
+    #[msg("Authority changed since the transfer was proposed")]
+    AuthorityTransferStale,
 
     // Amount errors
     #[msg("Amount is too small")]
@@ -111,10 +157,120 @@ pub enum LendingError {
     FlashLoanFeeNotPaid,
     #[msg("Flash loan amount too large")]
     FlashLoanAmountTooLarge,
+    #[msg("A flash loan is already open")]
+    FlashLoanAlreadyOpen,
+    #[msg("Operation not permitted while a flash loan is open")]
+    FlashLoanUsageProhibited,
+    #[msg("Flash loan callback program is not allowed")]
+    FlashLoanCallbackInvalid,
+    #[msg("Flash loans cannot be taken through a CPI")]
+    FlashLoanNested,
+
+    // Swap errors
+    #[msg("Swap program is not whitelisted")]
+    SwapProgramNotWhitelisted,
+    #[msg("Swap output below minimum")]
+    SlippageExceeded,
+
+    // Credit line errors
+    #[msg("Credit line has been revoked")]
+    CreditLineRevoked,
+    #[msg("Credit line borrow limit exceeded")]
+    CreditLineLimitExceeded,
+    #[msg("Credit line has outstanding debt")]
+    CreditLineOutstanding,
+    #[msg("Delegated borrowing power exceeded")]
+    DelegatedBorrowLimitExceeded,
+
+    // Referral errors
+    #[msg("Referrer cannot be the account's own owner")]
+    InvalidReferrer,
+    #[msg("Referrer fee balances are full")]
+    ReferrerReservesFull,
+    #[msg("No referral fees to claim")]
+    NoReferralFees,
+
+    // Reward errors
+    #[msg("Reward schedule exceeds the pool's undistributed rewards")]
+    RewardScheduleUnderfunded,
+    #[msg("No rewards to claim")]
+    NoRewardsToClaim,
+    #[msg("Accrued rewards must be claimed first")]
+    RewardsUnclaimed,
+
+    // Staking errors
+    #[msg("Stake position does not hold enough active shares")]
+    InsufficientStakeShares,
+    #[msg("No unstake has been requested")]
+    NoUnstakeRequest,
+    #[msg("Unstake cooldown has not passed")]
+    StakeCooldownActive,
+    #[msg("Unstake window has passed; request the unstake again")]
+    StakeUnstakeWindowExpired,
+
+    // Yield venue errors
+    #[msg("Yield venue is disabled")]
+    YieldVenueDisabled,
+    #[msg("Investment exceeds the yield venue's allocation")]
+    YieldAllocationExceeded,
+
+    // Keeper registry errors
+    #[msg("Liquidation is reserved for registered keepers")]
+    KeeperPriorityWindowActive,
+    #[msg("Keeper stake is below the registry minimum")]
+    KeeperStakeTooLow,
+    #[msg("Keeper exit already requested")]
+    KeeperExitPending,
+    #[msg("Keeper exit not requested or cooldown still active")]
+    KeeperExitCooldownActive,
+
+    // Liquidation commitment errors
+    #[msg("Revealed liquidation does not match the commitment")]
+    LiquidationCommitmentMismatch,
+    #[msg("Liquidation commitment cannot be revealed yet")]
+    LiquidationRevealTooEarly,
+    #[msg("Liquidation commitment has expired")]
+    LiquidationCommitmentExpired,
+
+    // Sub-account errors
+    #[msg("Invalid sub-account")]
+    SubAccountInvalid,
+    #[msg("Sub-account does not hold enough of the position")]
+    SubAccountShareInsufficient,
+    #[msg("Sub-account allocations are full")]
+    SubAccountAllocationsFull,
+    #[msg("Sub-account would become unhealthy")]
+    SubAccountUnhealthy,
+
+    // eMode errors
+    #[msg("eMode category does not match the obligation")]
+    EModeCategoryMismatch,
+    #[msg("eMode category is full")]
+    EModeCategoryFull,
+    #[msg("Obligation positions fall outside the eMode category")]
+    EModeIncompatiblePositions,
+
+    // Isolated collateral errors
+    #[msg("Isolated collateral cannot be combined with other collateral")]
+    IsolatedCollateralConflict,
+    #[msg("Reserve cannot be borrowed against isolated collateral")]
+    BorrowNotAllowedInIsolation,
+    #[msg("Isolated collateral debt ceiling exceeded")]
+    IsolationDebtCeilingExceeded,
+
+    // Rent reclamation errors
+    #[msg("Account has not been inactive for the consented period")]
+    AccountNotDormant,
+    #[msg("Obligation still holds deposits, borrows or linked accounts")]
+    ObligationNotEmpty,
 
     // Performance optimization errors
     #[msg("Batch size exceeded maximum allowed")]
     BatchSizeExceeded,
+    #[msg("Too many remaining accounts for this instruction")]
+    TooManyRemainingAccounts,
+    #[msg("Not enough compute units left for the next batch step")]
+    ComputeBudgetExhausted,
     #[msg("Insufficient memory for allocation")]
     InsufficientMemory,
     #[msg("Stack overflow in allocator")]
@@ -149,6 +305,8 @@ pub enum LendingError {
     FeatureDisabled,
     #[msg("Operation not permitted")]
     OperationNotPermitted,
+    #[msg("Operation was paused by an emergency guardian and needs a timelocked unpause")]
+    ReserveEmergencyPaused,
 
     // Reentrancy protection errors
     #[msg("Operation already in progress - reentrancy detected")]
@@ -179,6 +337,12 @@ pub enum LendingError {
     ProposalExpired,
     #[msg("Proposal not executed")]
     ProposalNotExecuted,
+    #[msg("Proposal status transition not allowed")]
+    InvalidProposalTransition,
+    #[msg("Proposal expiry must be in the future and within the maximum lifetime")]
+    InvalidProposalExpiry,
+    #[msg("Execution payload does not match the proposal's payload hash")]
+    MultisigPayloadMismatch,
     #[msg("Invalid operation type")]
     InvalidOperationType,
     #[msg("Unauthorized cancellation")]
@@ -201,6 +365,10 @@ pub enum LendingError {
     DelayTooShort,
     #[msg("Too many target accounts")]
     TooManyTargetAccounts,
+    #[msg("Timelock proposal does not authorize this operation")]
+    TimelockPayloadMismatch,
+    #[msg("Proposal can only be vetoed before its delay elapses")]
+    VetoWindowClosed,
 
     // Governance/Role errors
     #[msg("Too many roles")]
@@ -235,6 +403,12 @@ pub enum LendingError {
     MigrationAlreadyCompleted,
     #[msg("Migration in progress")]
     MigrationInProgress,
+    #[msg("Upgrade buffer was not staged")]
+    UpgradeNotStaged,
+    #[msg("Upgrade buffer changed since it was staged")]
+    UpgradeBufferChanged,
+    #[msg("Upgrade buffer authority must be the governance authority staging it")]
+    UpgradeBufferAuthorityMismatch,
 
     // Configuration errors
     #[msg("Invalid configuration parameter")]
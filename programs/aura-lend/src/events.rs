@@ -0,0 +1,396 @@
+use crate::state::audit_log::AdminAction;
+use crate::state::operation_receipt::OperationReceipt;
+use crate::state::reserve::ReserveConfig;
+use crate::state::timelock::TimelockOperationType;
+use crate::utils::logging::EventType;
+use anchor_lang::prelude::*;
+
+/// Volume attributed to an integrator (frontend) for revenue-share programs
+/// The integrator id is supplied per instruction and never stored on-chain
+#[event]
+pub struct IntegratorAttributionEvent {
+    /// Identifier of the integrator that routed the operation
+    pub integrator_id: u32,
+
+    /// Operation performed
+    pub operation: EventType,
+
+    /// User that performed the operation
+    pub user: Pubkey,
+
+    /// Reserve the operation was executed against
+    pub reserve: Pubkey,
+
+    /// Token amount of the operation
+    pub amount: u64,
+
+    /// Slot at which the operation executed
+    pub slot: u64,
+}
+
+/// Primary and secondary oracle prices of a reserve diverged beyond its threshold
+/// The operation that observed the divergence is rejected; the event remains in the
+/// failed transaction's logs.
+#[event]
+pub struct PriceManipulationDetected {
+    /// Liquidity mint of the reserve (reserve PDAs are derived from it)
+    pub liquidity_mint: Pubkey,
+
+    /// Primary oracle price (18 decimals)
+    pub primary_price: u128,
+
+    /// Secondary oracle price (18 decimals)
+    pub secondary_price: u128,
+
+    /// Divergence of the secondary from the primary price (basis points)
+    pub deviation_bps: u64,
+
+    /// Divergence the reserve allows (basis points)
+    pub max_deviation_bps: u64,
+
+    /// Slot at which the divergence was observed
+    pub slot: u64,
+}
+
+/// Obligation collateral moved between obligations without touching reserve liquidity
+#[event]
+pub struct SupplyPositionTransferred {
+    /// Reserve the collateral tokens belong to
+    pub reserve: Pubkey,
+
+    /// Obligation the collateral left
+    pub from_obligation: Pubkey,
+
+    /// Obligation the collateral was added to
+    pub to_obligation: Pubkey,
+
+    /// Owner of the source obligation
+    pub from: Pubkey,
+
+    /// Owner of the destination obligation
+    pub to: Pubkey,
+
+    /// Collateral tokens (aTokens) transferred
+    pub collateral_amount: u64,
+
+    /// Liquidity the transferred collateral tokens redeem for at the current exchange rate
+    pub liquidity_amount: u64,
+
+    /// Slot at which the transfer executed
+    pub slot: u64,
+}
+
+/// Debt no collateral was left to back was written off
+/// The insurance fund covered what it could; the remainder was socialized to suppliers.
+#[event]
+pub struct BadDebtSettled {
+    /// Obligation the debt was removed from
+    pub obligation: Pubkey,
+
+    /// Reserve the debt was owed to
+    pub reserve: Pubkey,
+
+    /// Debt written off, including accrued interest
+    pub bad_debt: u64,
+
+    /// Portion repaid to the reserve by its insurance fund
+    pub covered_by_insurance: u64,
+
+    /// Portion socialized to the reserve's suppliers
+    pub socialized: u64,
+
+    /// Slot at which the debt was settled
+    pub slot: u64,
+}
+
+/// Obligation was first observed with a health factor below 1.0
+/// Emitted by `refresh_obligation` so borrowers can be notified; with a grace period
+/// configured, liquidation waits until `liquidatable_from_slot` unless health keeps falling.
+#[event]
+pub struct ObligationHealthWarning {
+    /// Obligation that became unhealthy
+    pub obligation: Pubkey,
+
+    /// Owner of the obligation
+    pub owner: Pubkey,
+
+    /// Health factor at the refresh (basis points)
+    pub health_factor_bps: u64,
+
+    /// Health factor (basis points) below which the grace period no longer applies
+    pub grace_floor_bps: u16,
+
+    /// First slot at which the obligation may be liquidated
+    pub liquidatable_from_slot: u64,
+
+    /// Slot at which the obligation was first observed unhealthy
+    pub slot: u64,
+}
+
+/// Liquidity supplied to a reserve in exchange for collateral tokens
+#[event]
+pub struct DepositEvent {
+    /// User that supplied the liquidity
+    pub user: Pubkey,
+
+    /// Post-state summary: liquidity received by the reserve and collateral tokens minted
+    pub receipt: OperationReceipt,
+
+    /// Slot at which the deposit executed
+    pub slot: u64,
+}
+
+/// Collateral tokens redeemed for reserve liquidity
+#[event]
+pub struct RedeemEvent {
+    /// User that redeemed the collateral tokens
+    pub user: Pubkey,
+
+    /// Post-state summary: liquidity paid out and collateral tokens burned
+    pub receipt: OperationReceipt,
+
+    /// Slot at which the redemption executed
+    pub slot: u64,
+}
+
+/// Collateral tokens deposited into an obligation
+#[event]
+pub struct CollateralDepositEvent {
+    /// Obligation the collateral was added to
+    pub obligation: Pubkey,
+
+    /// Owner of the obligation
+    pub owner: Pubkey,
+
+    /// Post-state summary, including the obligation's health factor afterwards
+    pub receipt: OperationReceipt,
+
+    /// Slot at which the deposit executed
+    pub slot: u64,
+}
+
+/// Collateral tokens withdrawn from an obligation
+#[event]
+pub struct CollateralWithdrawEvent {
+    /// Obligation the collateral was removed from
+    pub obligation: Pubkey,
+
+    /// Owner of the obligation
+    pub owner: Pubkey,
+
+    /// Post-state summary, including the obligation's health factor afterwards
+    pub receipt: OperationReceipt,
+
+    /// Slot at which the withdrawal executed
+    pub slot: u64,
+}
+
+/// Liquidity borrowed against an obligation
+#[event]
+pub struct BorrowEvent {
+    /// Obligation the debt was added to
+    pub obligation: Pubkey,
+
+    /// Signer that borrowed: the obligation owner, or a delegate for credit lines
+    pub borrower: Pubkey,
+
+    /// Post-state summary, including the obligation's health factor afterwards
+    pub receipt: OperationReceipt,
+
+    /// Reserve utilization this borrow originated at (18 decimals)
+    pub origination_utilization_rate: u128,
+
+    /// Reserve borrow rate this borrow originated at (18 decimals)
+    pub origination_borrow_rate: u128,
+
+    /// Slot at which the borrow executed
+    pub slot: u64,
+}
+
+/// Debt of an obligation repaid
+#[event]
+pub struct RepayEvent {
+    /// Obligation the debt was repaid on
+    pub obligation: Pubkey,
+
+    /// Signer whose tokens repaid the debt
+    pub payer: Pubkey,
+
+    /// Post-state summary; the liquidity amount is the repayment after capping at the debt
+    pub receipt: OperationReceipt,
+
+    /// Slot at which the repayment executed
+    pub slot: u64,
+}
+
+/// Debt of an unhealthy obligation repaid in exchange for its collateral
+/// Emitted by fixed-bonus liquidations, auction bids and the backstop vault.
+#[event]
+pub struct LiquidationEvent {
+    /// Obligation that was liquidated
+    pub obligation: Pubkey,
+
+    /// Signer (or backstop vault) that repaid the debt
+    pub liquidator: Pubkey,
+
+    /// Reserve the seized collateral belongs to
+    pub withdraw_reserve: Pubkey,
+
+    /// Health factor that allowed the liquidation (basis points)
+    pub health_factor_bps: u64,
+
+    /// Post-state summary of the repay reserve; the collateral amount is seized aTokens
+    pub receipt: OperationReceipt,
+
+    /// Bonus collateral taken as the protocol fee
+    pub protocol_fee_collateral: u64,
+
+    /// Liquidity the insurance fund received for the protocol fee, net of transfer fees;
+    /// 0 when the reserve lacked the liquidity and the fee was accrued instead
+    pub protocol_fee_liquidity: u64,
+
+    /// Slot at which the liquidation executed
+    pub slot: u64,
+}
+
+/// Flash loan borrowed and repaid within one instruction
+#[event]
+pub struct FlashLoanEvent {
+    /// Reserve the liquidity was borrowed from
+    pub reserve: Pubkey,
+
+    /// Signer that took the loan
+    pub borrower: Pubkey,
+
+    /// Liquidity borrowed
+    pub amount: u64,
+
+    /// Fee paid to the reserve
+    pub fee: u64,
+
+    /// Slot at which the flash loan executed
+    pub slot: u64,
+}
+
+/// Referrer accrued a share of a borrow fee
+#[event]
+pub struct ReferralFeeAccruedEvent {
+    /// Referrer credited with the fee
+    pub referrer: Pubkey,
+
+    /// Reserve the fee is denominated in
+    pub reserve: Pubkey,
+
+    /// Obligation owner whose borrow paid the fee
+    pub referred: Pubkey,
+
+    /// Liquidity borrowed
+    pub volume: u64,
+
+    /// Fee credited to the referrer
+    pub fee: u64,
+
+    /// Slot at which the fee accrued
+    pub slot: u64,
+}
+
+/// Referrer claimed its accrued fees in a reserve
+#[event]
+pub struct ReferralFeesClaimedEvent {
+    /// Referrer that claimed
+    pub referrer: Pubkey,
+
+    /// Reserve the fees were paid out of
+    pub reserve: Pubkey,
+
+    /// Fees transferred to the referrer's owner
+    pub amount: u64,
+
+    /// Slot at which the claim executed
+    pub slot: u64,
+}
+
+/// Governance changed a reserve's risk or fee configuration
+#[event]
+pub struct ReserveConfigUpdatedEvent {
+    /// Reserve that was updated
+    pub reserve: Pubkey,
+
+    /// Signer that applied the update
+    pub authority: Pubkey,
+
+    /// Instruction that applied the update
+    pub action: AdminAction,
+
+    /// Reserve configuration after the update
+    pub config: ReserveConfig,
+
+    /// Slot at which the update executed
+    pub slot: u64,
+}
+
+/// A guardian vetoed a timelock proposal before its delay elapsed
+#[event]
+pub struct TimelockProposalVetoedEvent {
+    /// Proposal that was vetoed
+    pub proposal: Pubkey,
+
+    /// Timelock controller the proposal was queued on
+    pub controller: Pubkey,
+
+    /// Guardian that vetoed the proposal
+    pub guardian: Pubkey,
+
+    /// Operation the proposal would have authorized
+    pub operation_type: TimelockOperationType,
+
+    /// Time the proposal would have become executable
+    pub execution_time: i64,
+
+    /// Unix timestamp of the veto
+    pub timestamp: i64,
+}
+
+/// Admin or governance instruction recorded in the market's audit log
+/// Mirrors every `AdminAuditEntry` so indexers need not poll the ring buffer.
+#[event]
+pub struct AdminActionEvent {
+    /// Market whose audit log recorded the action
+    pub market: Pubkey,
+
+    /// Sequence number of the audit log entry
+    pub sequence: u64,
+
+    /// Signer that performed the action
+    pub actor: Pubkey,
+
+    /// Instruction that was executed
+    pub action: AdminAction,
+
+    /// SHA-256 hash of the instruction's key parameters (Borsh encoded)
+    pub params_hash: [u8; 32],
+
+    /// Slot at which the action executed
+    pub slot: u64,
+}
+
+/// Emit an attribution event if the caller supplied an integrator id
+pub fn emit_integrator_attribution(
+    integrator_id: Option<u32>,
+    operation: EventType,
+    user: Pubkey,
+    reserve: Pubkey,
+    amount: u64,
+    slot: u64,
+) {
+    if let Some(integrator_id) = integrator_id {
+        emit!(IntegratorAttributionEvent {
+            integrator_id,
+            operation,
+            user,
+            reserve,
+            amount,
+            slot,
+        });
+    }
+}
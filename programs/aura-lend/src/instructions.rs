@@ -1,26 +1,86 @@
+pub mod authority_transfer_instructions;
+pub mod backstop_instructions;
 pub mod batch_operations;
 pub mod borrowing_instructions;
 pub mod config_instructions;
+pub mod credit_line_instructions;
+pub mod deleverage_instructions;
+pub mod emode_instructions;
+pub mod fee_instructions;
+pub mod flash_loan_instructions;
 pub mod governance_instructions;
+pub mod insurance_fund_instructions;
+pub mod interest_instructions;
+pub mod keeper_instructions;
 pub mod lending_instructions;
+pub mod liquidation_auction_instructions;
+pub mod liquidation_commitment_instructions;
 pub mod liquidation_instructions;
+pub mod liquidation_queue_instructions;
 pub mod market_instructions;
+pub mod metrics_instructions;
 pub mod migration_instructions;
 pub mod multisig_instructions;
+pub mod nonce_instructions;
+pub mod obligation_transfer_instructions;
+pub mod on_behalf_instructions;
 pub mod oracle_instructions;
+pub mod order_book_instructions;
+pub mod position_swap_instructions;
+pub mod rate_index_instructions;
+pub mod referral_instructions;
+pub mod rent_reclaim_instructions;
+pub mod rewards_instructions;
+pub mod stake_vault_instructions;
+pub mod standing_order_instructions;
+pub mod sub_account_instructions;
+pub mod supply_queue_instructions;
 pub mod timelock_instructions;
 pub mod upgrade_instructions;
+pub mod user_stats_instructions;
+pub mod view_instructions;
+pub mod yield_venue_instructions;
 
 // Re-export all instructions and their context structs
+pub use authority_transfer_instructions::*;
+pub use backstop_instructions::*;
 pub use batch_operations::*;
 pub use borrowing_instructions::*;
 pub use config_instructions::*;
+pub use credit_line_instructions::*;
+pub use deleverage_instructions::*;
+pub use emode_instructions::*;
+pub use fee_instructions::*;
+pub use flash_loan_instructions::*;
 pub use governance_instructions::*;
+pub use insurance_fund_instructions::*;
+pub use interest_instructions::*;
+pub use keeper_instructions::*;
 pub use lending_instructions::*;
+pub use liquidation_auction_instructions::*;
+pub use liquidation_commitment_instructions::*;
 pub use liquidation_instructions::*;
+pub use liquidation_queue_instructions::*;
 pub use market_instructions::*;
+pub use metrics_instructions::*;
 pub use migration_instructions::*;
 pub use multisig_instructions::*;
+pub use nonce_instructions::*;
+pub use obligation_transfer_instructions::*;
+pub use on_behalf_instructions::*;
 pub use oracle_instructions::*;
+pub use order_book_instructions::*;
+pub use position_swap_instructions::*;
+pub use rate_index_instructions::*;
+pub use referral_instructions::*;
+pub use rent_reclaim_instructions::*;
+pub use rewards_instructions::*;
+pub use stake_vault_instructions::*;
+pub use standing_order_instructions::*;
+pub use sub_account_instructions::*;
+pub use supply_queue_instructions::*;
 pub use timelock_instructions::*;
 pub use upgrade_instructions::*;
+pub use user_stats_instructions::*;
+pub use view_instructions::*;
+pub use yield_venue_instructions::*;
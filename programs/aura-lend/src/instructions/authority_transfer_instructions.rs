@@ -0,0 +1,404 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::*;
+use crate::utils::{require_admin, ProtocolConfig};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{bpf_loader_upgradeable, program::invoke};
+use anchor_spl::token_interface::TokenAccount;
+
+/// Propose moving an authority field to `new_authority`
+/// Nothing changes until `new_authority` signs `accept_authority_transfer`. `target` is the
+/// account holding the field: the market for the owner and emergency authority, the
+/// protocol config, the reserve for its fee receiver, or the program data account for
+/// the upgrade authority. For a fee receiver `new_authority` is the new token account,
+/// and its owner accepts.
+pub fn propose_authority_transfer(
+    ctx: Context<ProposeAuthorityTransfer>,
+    kind: AuthorityKind,
+    new_authority: Pubkey,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::ProposeAuthorityTransfer,
+        &(kind, ctx.accounts.target.key(), new_authority),
+    )?;
+
+    let market = &ctx.accounts.market;
+    let target = ctx.accounts.target.key();
+
+    let permission = match kind {
+        AuthorityKind::ReserveFeeReceiver => Permission::FEE_MANAGER,
+        _ => Permission::SUPER_ADMIN,
+    };
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        permission,
+    )?;
+
+    let current_authority = match kind {
+        AuthorityKind::MarketOwner | AuthorityKind::EmergencyAuthority => {
+            if target != market.key() {
+                return Err(LendingError::InvalidAccount.into());
+            }
+            if kind == AuthorityKind::MarketOwner {
+                market.multisig_owner
+            } else {
+                market.emergency_authority
+            }
+        }
+        AuthorityKind::ConfigAuthority => {
+            if target != ctx.accounts.config.key() {
+                return Err(LendingError::InvalidAccount.into());
+            }
+            ctx.accounts.config.authority
+        }
+        AuthorityKind::ReserveFeeReceiver => {
+            let target_info = ctx.accounts.target.to_account_info();
+            let reserve = Account::<Reserve>::try_from(&target_info)?;
+            if reserve.market != market.key() {
+                return Err(LendingError::InvalidMarketState.into());
+            }
+            reserve.fee_receiver
+        }
+        AuthorityKind::UpgradeAuthority => {
+            if target != program_data_address() {
+                return Err(LendingError::InvalidAccount.into());
+            }
+            // The program is expected to be upgradeable by the market owner; the loader
+            // checks the real authority when the transfer is accepted
+            market.multisig_owner
+        }
+    };
+
+    **ctx.accounts.authority_transfer = AuthorityTransfer::new(
+        market.key(),
+        kind,
+        target,
+        current_authority,
+        new_authority,
+        ctx.accounts.authority.key(),
+        Clock::get()?.slot,
+        ctx.bumps.authority_transfer,
+    )?;
+
+    msg!(
+        "Authority transfer proposed - {:?} of {}: {} -> {}",
+        kind,
+        target,
+        current_authority,
+        new_authority
+    );
+    Ok(())
+}
+
+/// Withdraw a pending authority transfer and refund its rent to the proposer
+pub fn cancel_authority_transfer(ctx: Context<CancelAuthorityTransfer>) -> Result<()> {
+    let transfer = &ctx.accounts.authority_transfer;
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::CancelAuthorityTransfer,
+        &(transfer.kind, transfer.target, transfer.pending_authority),
+    )?;
+
+    let permission = match transfer.kind {
+        AuthorityKind::ReserveFeeReceiver => Permission::FEE_MANAGER,
+        _ => Permission::SUPER_ADMIN,
+    };
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        permission,
+    )?;
+
+    msg!(
+        "Authority transfer cancelled - {:?} of {} to {}",
+        transfer.kind,
+        transfer.target,
+        transfer.pending_authority
+    );
+    Ok(())
+}
+
+/// Accept a pending authority transfer (signed by the new authority)
+/// The transfer is refused if the field changed since it was proposed. Moving the
+/// market owner also moves the governance registry address, which is derived from the
+/// owner, so the new owner initializes a registry of its own. The upgrade authority is
+/// moved with the loader's checked instruction, which the current upgrade authority
+/// must co-sign.
+pub fn accept_authority_transfer(ctx: Context<AcceptAuthorityTransfer>) -> Result<()> {
+    let transfer = &ctx.accounts.authority_transfer;
+    let new_authority = ctx.accounts.new_authority.key();
+    ctx.accounts.admin_audit_log.record(
+        new_authority,
+        AdminAction::AcceptAuthorityTransfer,
+        &(transfer.kind, transfer.target, transfer.pending_authority),
+    )?;
+
+    // Every kind but the fee receiver is accepted by the pending key itself
+    if transfer.kind != AuthorityKind::ReserveFeeReceiver
+        && new_authority != transfer.pending_authority
+    {
+        return Err(LendingError::InvalidAuthority.into());
+    }
+
+    match transfer.kind {
+        AuthorityKind::MarketOwner => {
+            let market = &mut ctx.accounts.market;
+            transfer.ensure_current(&market.multisig_owner)?;
+            market.multisig_owner = new_authority;
+            market.update_timestamp()?;
+        }
+        AuthorityKind::EmergencyAuthority => {
+            let market = &mut ctx.accounts.market;
+            transfer.ensure_current(&market.emergency_authority)?;
+            market.emergency_authority = new_authority;
+            market.update_timestamp()?;
+        }
+        AuthorityKind::ConfigAuthority => {
+            let config = &mut ctx.accounts.config;
+            if transfer.target != config.key() {
+                return Err(LendingError::InvalidAccount.into());
+            }
+            transfer.ensure_current(&config.authority)?;
+            config.authority = new_authority;
+            config.update(&Clock::get()?)?;
+        }
+        AuthorityKind::ReserveFeeReceiver => {
+            let reserve = ctx
+                .accounts
+                .reserve
+                .as_mut()
+                .ok_or(LendingError::InvalidAccount)?;
+            let new_fee_receiver = ctx
+                .accounts
+                .new_fee_receiver
+                .as_ref()
+                .ok_or(LendingError::InvalidAccount)?;
+            if reserve.key() != transfer.target
+                || new_fee_receiver.key() != transfer.pending_authority
+            {
+                return Err(LendingError::InvalidAccount.into());
+            }
+            if new_fee_receiver.mint != reserve.liquidity_mint {
+                return Err(LendingError::TokenMintMismatch.into());
+            }
+            if new_fee_receiver.owner != new_authority {
+                return Err(LendingError::TokenAccountOwnerMismatch.into());
+            }
+            transfer.ensure_current(&reserve.fee_receiver)?;
+            reserve.fee_receiver = new_fee_receiver.key();
+            reserve.last_update_timestamp = Clock::get()?.unix_timestamp as u64;
+        }
+        AuthorityKind::UpgradeAuthority => {
+            let program_data = ctx
+                .accounts
+                .program_data
+                .as_ref()
+                .ok_or(LendingError::InvalidAccount)?;
+            let current_authority = ctx
+                .accounts
+                .current_upgrade_authority
+                .as_ref()
+                .ok_or(LendingError::AuthoritySignerMissing)?;
+            if program_data.key() != transfer.target {
+                return Err(LendingError::InvalidAccount.into());
+            }
+            transfer.ensure_current(&current_authority.key())?;
+
+            let set_authority_ix = bpf_loader_upgradeable::set_upgrade_authority_checked(
+                &crate::ID,
+                &current_authority.key(),
+                &new_authority,
+            );
+            invoke(
+                &set_authority_ix,
+                &[
+                    program_data.to_account_info(),
+                    current_authority.to_account_info(),
+                    ctx.accounts.new_authority.to_account_info(),
+                ],
+            )?;
+        }
+    }
+
+    msg!(
+        "Authority transfer accepted - {:?} of {}: {} -> {}",
+        transfer.kind,
+        transfer.target,
+        transfer.current_authority,
+        transfer.pending_authority
+    );
+    Ok(())
+}
+
+/// Address of this program's program data account
+fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+#[derive(Accounts)]
+#[instruction(kind: AuthorityKind)]
+pub struct ProposeAuthorityTransfer<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol configuration
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Account holding the authority field
+    /// CHECK: Matched against `kind` by the instruction
+    pub target: UncheckedAccount<'info>,
+
+    /// Pending transfer account to create
+    #[account(
+        init,
+        payer = authority,
+        space = AuthorityTransfer::SIZE,
+        seeds = [AUTHORITY_TRANSFER_SEED, &[kind as u8], target.key().as_ref()],
+        bump
+    )]
+    pub authority_transfer: Account<'info, AuthorityTransfer>,
+
+    /// Market multisig owner, or a governance role holder
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAuthorityTransfer<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Pending transfer account to close
+    #[account(
+        mut,
+        close = proposer,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = proposer @ LendingError::InvalidAccount
+    )]
+    pub authority_transfer: Account<'info, AuthorityTransfer>,
+
+    /// Signer that proposed the transfer (receives the rent)
+    /// CHECK: Matched against the transfer's proposer
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    /// Market multisig owner, or a governance role holder
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthorityTransfer<'info> {
+    /// Market account
+    #[account(
+        mut,
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Pending transfer being accepted
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [
+            AUTHORITY_TRANSFER_SEED,
+            &[authority_transfer.kind as u8],
+            authority_transfer.target.as_ref()
+        ],
+        bump = authority_transfer.bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = proposer @ LendingError::InvalidAccount
+    )]
+    pub authority_transfer: Account<'info, AuthorityTransfer>,
+
+    /// Signer that proposed the transfer (receives the rent)
+    /// CHECK: Matched against the transfer's proposer
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    /// Protocol configuration
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Reserve whose fee receiver is transferred (fee receiver transfers only)
+    #[account(
+        mut,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Option<Account<'info, Reserve>>,
+
+    /// New fee receiver token account, owned by the new authority (fee receiver transfers only)
+    pub new_fee_receiver: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Program data account of this program (upgrade authority transfers only)
+    /// CHECK: Matched against the transfer's target; the loader validates the rest
+    #[account(mut)]
+    pub program_data: Option<UncheckedAccount<'info>>,
+
+    /// Current upgrade authority, co-signing for the loader (upgrade authority transfers only)
+    pub current_upgrade_authority: Option<Signer<'info>>,
+
+    /// New authority accepting the transfer (the token account owner for a fee receiver)
+    pub new_authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+}
@@ -0,0 +1,725 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::events::LiquidationEvent;
+use crate::instructions::liquidation_instructions::{
+    calculate_liquidation_collateral_amount, revalue_liquidation_positions,
+};
+use crate::state::*;
+use crate::utils::{
+    math::Decimal, require_admin, OracleManager, ProtocolConfig, ReserveLock, TokenUtils,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Initialize a governance-funded liquidation backstop vault for an asset
+pub fn initialize_backstop_vault(
+    ctx: Context<InitializeBackstopVault>,
+    params: InitializeBackstopVaultParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::InitializeBackstopVault,
+        &params,
+    )?;
+
+    let market = &ctx.accounts.market;
+
+    // Only the market multisig can create backstop vaults
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RISK_MANAGER,
+    )?;
+
+    **ctx.accounts.backstop_vault = BackstopVault::new(
+        market.key(),
+        ctx.accounts.liquidity_mint.key(),
+        ctx.accounts.liquidity_vault.key(),
+        params.grace_slots,
+        ctx.bumps.backstop_vault,
+    )?;
+
+    msg!(
+        "Backstop vault initialized for mint: {}, grace slots: {}",
+        ctx.accounts.liquidity_mint.key(),
+        params.grace_slots
+    );
+    Ok(())
+}
+
+/// Update backstop vault parameters (governance only)
+pub fn update_backstop_vault(
+    ctx: Context<UpdateBackstopVault>,
+    params: UpdateBackstopVaultParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::UpdateBackstopVault,
+        &params,
+    )?;
+
+    let market = &ctx.accounts.market;
+    let backstop_vault = &mut ctx.accounts.backstop_vault;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RISK_MANAGER,
+    )?;
+
+    if let Some(grace_slots) = params.grace_slots {
+        BackstopVault::validate_grace_slots(grace_slots)?;
+        backstop_vault.grace_slots = grace_slots;
+    }
+
+    if let Some(enabled) = params.enabled {
+        backstop_vault.enabled = enabled;
+    }
+
+    msg!(
+        "Backstop vault updated - grace slots: {}, enabled: {}",
+        backstop_vault.grace_slots,
+        backstop_vault.enabled
+    );
+    Ok(())
+}
+
+/// Contribute liquidity to a backstop vault
+pub fn fund_backstop_vault(ctx: Context<FundBackstopVault>, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    // The vault is credited with what arrives after the mint's transfer fee
+    let received_amount =
+        TokenUtils::amount_after_transfer_fee(&ctx.accounts.liquidity_mint, amount)?;
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.source_liquidity,
+        &ctx.accounts.liquidity_vault,
+        &ctx.accounts.liquidity_mint,
+        &ctx.accounts.funder.to_account_info(),
+        &[],
+        amount,
+    )?;
+
+    ctx.accounts
+        .backstop_vault
+        .record_funding(received_amount)?;
+
+    msg!("Backstop vault funded with {} tokens", received_amount);
+    Ok(())
+}
+
+/// Withdraw tokens held by a backstop vault (liquidity or seized collateral), governance only
+pub fn withdraw_backstop_vault(ctx: Context<WithdrawBackstopVault>, amount: u64) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::WithdrawBackstopVault,
+        &amount,
+    )?;
+
+    let market = &ctx.accounts.market;
+    let backstop_vault = &ctx.accounts.backstop_vault;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RISK_MANAGER,
+    )?;
+
+    if amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    let market_key = market.key();
+    let vault_seeds = &[
+        BACKSTOP_VAULT_SEED,
+        market_key.as_ref(),
+        backstop_vault.liquidity_mint.as_ref(),
+        &[backstop_vault.bump],
+    ];
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.source_tokens,
+        &ctx.accounts.destination_tokens,
+        &ctx.accounts.mint,
+        &ctx.accounts.backstop_vault.to_account_info(),
+        &[vault_seeds],
+        amount,
+    )?;
+
+    if ctx.accounts.source_tokens.key() == ctx.accounts.backstop_vault.liquidity_vault {
+        ctx.accounts.backstop_vault.record_withdrawal(amount)?;
+    }
+
+    msg!("Withdrew {} tokens from backstop vault", amount);
+    Ok(())
+}
+
+/// Liquidate an obligation with backstop vault funds once the grace window has passed
+/// Any keeper may trigger this; seized collateral accrues to the vault
+pub fn backstop_liquidate_obligation(
+    ctx: Context<BackstopLiquidateObligation>,
+    liquidity_amount: u64,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let mut obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market.key())?;
+    let repay_reserve = &mut ctx.accounts.repay_reserve;
+    let withdraw_reserve = &mut ctx.accounts.withdraw_reserve;
+    let clock = Clock::get()?;
+
+    // Check if market allows liquidations
+    if market.is_paused() || market.is_liquidation_disabled() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    // Check if reserves allow liquidations
+    if repay_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::LIQUIDATIONS_DISABLED)
+        || withdraw_reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::LIQUIDATIONS_DISABLED)
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    if liquidity_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    // Obligation must be freshly revalued, unhealthy and ignored by external liquidators for
+    // the grace window
+    obligation.require_refreshed(clock.slot)?;
+    let emode = EModeCategory::resolve(ctx.accounts.emode_category.as_deref(), &obligation, None)?;
+    if obligation.is_healthy_with(emode.as_ref())? {
+        return Err(LendingError::ObligationHealthy.into());
+    }
+    ctx.accounts
+        .backstop_vault
+        .can_liquidate(obligation.slots_unhealthy(clock.slot))?;
+
+    // Hold the reentrancy locks until the reserve state is written back
+    let mut repay_reserve = ReserveLock::acquire(repay_reserve)?;
+    let mut withdraw_reserve = ReserveLock::acquire(withdraw_reserve)?;
+
+    let result = (|| -> Result<(u64, u64, Decimal)> {
+        repay_reserve.update_interest(clock.slot)?;
+        withdraw_reserve.update_interest(clock.slot)?;
+        obligation.accrue_borrow_interest(
+            &repay_reserve.key(),
+            repay_reserve.cumulative_borrow_rate_wads(),
+        )?;
+
+        // Check maximum liquidation amount; the close factor grows as health falls
+        let close_factor_bps = obligation.liquidation_close_factor_bps(
+            repay_reserve
+                .liquidation_close_factor_bps(ctx.accounts.config.liquidation_close_factor_bps),
+            emode.as_ref(),
+        )?;
+        let max_liquidation =
+            obligation.max_liquidation_amount(&repay_reserve.key(), close_factor_bps)?;
+        if liquidity_amount > max_liquidation {
+            return Err(LendingError::LiquidationTooLarge.into());
+        }
+
+        let collateral = obligation
+            .find_collateral_deposit(&withdraw_reserve.key())
+            .ok_or(LendingError::ObligationReserveNotFound)?;
+        let deposited_collateral = collateral.deposited_amount;
+
+        let repay_price = OracleManager::get_checked_price(
+            &ctx.accounts.repay_price_oracle.to_account_info(),
+            ctx.accounts.repay_secondary_price_oracle.as_deref(),
+            &repay_reserve,
+        )?;
+        repay_price.validate(clock.unix_timestamp)?;
+
+        let withdraw_price = OracleManager::get_checked_price(
+            &ctx.accounts.withdraw_price_oracle.to_account_info(),
+            ctx.accounts.withdraw_secondary_price_oracle.as_deref(),
+            &withdraw_reserve,
+        )?;
+        withdraw_price.validate(clock.unix_timestamp)?;
+
+        // Smooth both prices against their TWAPs and confirm the obligation is still
+        // unhealthy when its liquidated positions are valued at them
+        let repay_price = repay_reserve.liquidation_debt_price(&repay_price);
+        let withdraw_price = withdraw_reserve.liquidation_collateral_price(&withdraw_price);
+        revalue_liquidation_positions(
+            &mut obligation,
+            &repay_reserve,
+            &withdraw_reserve,
+            &repay_price,
+            &withdraw_price,
+        )?;
+        let health_factor = obligation.calculate_health_factor_with(emode.as_ref())?;
+        if health_factor >= Decimal::one() {
+            return Err(LendingError::ObligationHealthy.into());
+        }
+        obligation.check_liquidation_grace(health_factor, clock.slot)?;
+
+        let repay_value_usd = OracleManager::calculate_usd_value(
+            liquidity_amount,
+            &repay_price,
+            repay_reserve.config.decimals,
+        )?;
+
+        // eMode liquidations pay the category bonus instead of the reserve penalty
+        let liquidation_bonus_bps = match emode {
+            Some(emode) => emode.liquidation_bonus_bps,
+            None => withdraw_reserve.config.liquidation_penalty_bps,
+        };
+        let seized_liquidity = calculate_liquidation_collateral_amount(
+            repay_value_usd,
+            &withdraw_price,
+            liquidation_bonus_bps,
+        )?;
+        let collateral_amount = withdraw_reserve.liquidity_to_collateral(seized_liquidity)?;
+
+        if deposited_collateral < collateral_amount {
+            return Err(LendingError::InsufficientCollateral.into());
+        }
+
+        // Bonus portion of the seized collateral, reported in the liquidation event
+        let par_collateral = withdraw_reserve.liquidity_to_collateral(
+            calculate_liquidation_collateral_amount(repay_value_usd, &withdraw_price, 0)?,
+        )?;
+        let bonus_collateral = collateral_amount.saturating_sub(par_collateral);
+
+        // Repay debt from the vault's liquidity
+        let market_key = market.key();
+        let vault_seeds = &[
+            BACKSTOP_VAULT_SEED,
+            market_key.as_ref(),
+            ctx.accounts.backstop_vault.liquidity_mint.as_ref(),
+            &[ctx.accounts.backstop_vault.bump],
+        ];
+
+        // Grossed up so that the reserve receives the full repayment after transfer fees
+        let transfer_amount = TokenUtils::amount_before_transfer_fee(
+            &ctx.accounts.repay_liquidity_mint,
+            liquidity_amount,
+        )?;
+
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.liquidity_vault,
+            &ctx.accounts.repay_reserve_liquidity_supply,
+            &ctx.accounts.repay_liquidity_mint,
+            &ctx.accounts.backstop_vault.to_account_info(),
+            &[vault_seeds],
+            transfer_amount,
+        )?;
+
+        // Seize collateral into the vault's collateral account
+        let collateral_authority_seeds = &[
+            COLLATERAL_TOKEN_SEED,
+            withdraw_reserve.liquidity_mint.as_ref(),
+            b"authority",
+            &[ctx.bumps.withdraw_collateral_supply_authority],
+        ];
+
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.collateral_token_program,
+            &ctx.accounts.withdraw_reserve_collateral_supply,
+            &ctx.accounts.vault_collateral,
+            &ctx.accounts.withdraw_collateral_mint,
+            &ctx.accounts
+                .withdraw_collateral_supply_authority
+                .to_account_info(),
+            &[collateral_authority_seeds],
+            collateral_amount,
+        )?;
+
+        // Update reserve and obligation accounting
+        repay_reserve.repay_borrow(liquidity_amount)?;
+        obligation.repay_liquidity_borrow(
+            &repay_reserve.key(),
+            Decimal::from_integer(liquidity_amount)?,
+        )?;
+        obligation.remove_collateral_deposit(&withdraw_reserve.key(), collateral_amount)?;
+
+        obligation.borrowed_value_usd = obligation.borrowed_value_usd.try_sub(repay_value_usd)?;
+        let collateral_value_usd =
+            withdraw_reserve.collateral_value_usd(collateral_amount, &withdraw_price)?;
+        obligation.deposited_value_usd = obligation
+            .deposited_value_usd
+            .try_sub(collateral_value_usd)?;
+        obligation.record_liquidation(clock.slot);
+        obligation.update_timestamp(clock.slot)?;
+        obligation.update_unhealthy_since(clock.slot)?;
+
+        Ok((collateral_amount, bonus_collateral, health_factor))
+    })();
+
+    let (collateral_amount, bonus_collateral, health_factor) = result?;
+
+    ctx.accounts
+        .backstop_vault
+        .record_liquidation(liquidity_amount, collateral_amount, clock.slot)?;
+
+    msg!(
+        "Backstop liquidation by keeper {} - repaid: {}, seized: {}",
+        ctx.accounts.keeper.key(),
+        liquidity_amount,
+        collateral_amount
+    );
+
+    emit!(LiquidationEvent {
+        obligation: ctx.accounts.obligation.key(),
+        liquidator: ctx.accounts.backstop_vault.key(),
+        withdraw_reserve: withdraw_reserve.key(),
+        health_factor_bps: health_factor.to_health_bps_saturating(),
+        receipt: OperationReceipt::new(
+            repay_reserve.key(),
+            &repay_reserve,
+            liquidity_amount,
+            collateral_amount,
+            bonus_collateral,
+            Some(obligation.calculate_health_factor_with(emode.as_ref())?),
+        )?,
+        protocol_fee_collateral: 0,
+        protocol_fee_liquidity: 0,
+        slot: clock.slot,
+    });
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    Ok(())
+}
+
+// Context structs for backstop instructions
+
+#[derive(Accounts)]
+pub struct InitializeBackstopVault<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Backstop vault account to initialize
+    #[account(
+        init,
+        payer = payer,
+        space = BackstopVault::SIZE,
+        seeds = [BACKSTOP_VAULT_SEED, market.key().as_ref(), liquidity_mint.key().as_ref()],
+        bump
+    )]
+    pub backstop_vault: Account<'info, BackstopVault>,
+
+    /// Mint of the liquidity the vault holds
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token account holding the vault's liquidity
+    #[account(
+        init,
+        payer = payer,
+        token::mint = liquidity_mint,
+        token::authority = backstop_vault,
+        seeds = [BACKSTOP_VAULT_SEED, backstop_vault.key().as_ref(), b"liquidity"],
+        bump
+    )]
+    pub liquidity_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Payer for account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Rent sysvar
+    pub rent: Sysvar<'info, Rent>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateBackstopVault<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Backstop vault account
+    #[account(
+        mut,
+        seeds = [BACKSTOP_VAULT_SEED, market.key().as_ref(), backstop_vault.liquidity_mint.as_ref()],
+        bump = backstop_vault.bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub backstop_vault: Account<'info, BackstopVault>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct FundBackstopVault<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Backstop vault account
+    #[account(
+        mut,
+        seeds = [BACKSTOP_VAULT_SEED, market.key().as_ref(), backstop_vault.liquidity_mint.as_ref()],
+        bump = backstop_vault.bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = liquidity_vault @ LendingError::InvalidAccount
+    )]
+    pub backstop_vault: Account<'info, BackstopVault>,
+
+    /// Vault liquidity token account
+    #[account(mut)]
+    pub liquidity_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity token mint of the vault
+    #[account(address = backstop_vault.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Funder's source liquidity token account
+    #[account(
+        mut,
+        token::mint = backstop_vault.liquidity_mint,
+        token::authority = funder
+    )]
+    pub source_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Funder (typically the treasury or governance)
+    pub funder: Signer<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawBackstopVault<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Backstop vault account
+    #[account(
+        mut,
+        seeds = [BACKSTOP_VAULT_SEED, market.key().as_ref(), backstop_vault.liquidity_mint.as_ref()],
+        bump = backstop_vault.bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub backstop_vault: Account<'info, BackstopVault>,
+
+    /// Vault-owned token account to withdraw from (liquidity or seized collateral)
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = backstop_vault
+    )]
+    pub source_tokens: InterfaceAccount<'info, TokenAccount>,
+
+    /// Mint of the tokens being withdrawn
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Destination token account
+    #[account(
+        mut,
+        token::mint = source_tokens.mint
+    )]
+    pub destination_tokens: InterfaceAccount<'info, TokenAccount>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct BackstopLiquidateObligation<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol configuration (liquidation close factor)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Backstop vault providing the repayment liquidity
+    #[account(
+        mut,
+        seeds = [BACKSTOP_VAULT_SEED, market.key().as_ref(), repay_reserve.liquidity_mint.as_ref()],
+        bump = backstop_vault.bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = liquidity_vault @ LendingError::InvalidAccount
+    )]
+    pub backstop_vault: Account<'info, BackstopVault>,
+
+    /// Vault liquidity token account
+    #[account(mut)]
+    pub liquidity_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Vault-owned token account receiving seized collateral
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.collateral_mint,
+        token::authority = backstop_vault
+    )]
+    pub vault_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Obligation account being liquidated (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Reserve for the asset being repaid
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, repay_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub repay_reserve: Account<'info, Reserve>,
+
+    /// Reserve for the collateral being withdrawn
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, withdraw_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub withdraw_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for repay asset
+    /// CHECK: Validated against repay_reserve.price_oracle
+    #[account(address = repay_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub repay_price_oracle: UncheckedAccount<'info>,
+
+    /// Price oracle for withdraw asset
+    /// CHECK: Validated against withdraw_reserve.price_oracle
+    #[account(address = withdraw_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub withdraw_price_oracle: UncheckedAccount<'info>,
+
+    /// Secondary oracle of the repay asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub repay_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Secondary oracle of the withdraw asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub withdraw_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Liquidity token mint of the repay reserve
+    #[account(address = repay_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub repay_liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Repay reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = repay_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub repay_reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Collateral mint (aToken mint) of the withdraw reserve
+    #[account(address = withdraw_reserve.collateral_mint @ LendingError::ReserveCollateralMintMismatch)]
+    pub withdraw_collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Withdraw reserve's collateral supply token account
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.collateral_mint,
+        token::authority = withdraw_collateral_supply_authority
+    )]
+    pub withdraw_reserve_collateral_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Withdraw collateral supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [COLLATERAL_TOKEN_SEED, withdraw_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub withdraw_collateral_supply_authority: UncheckedAccount<'info>,
+
+    /// Keeper triggering the backstop liquidation
+    pub keeper: Signer<'info>,
+
+    /// Token program of the repay reserve's liquidity mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the withdraw reserve's collateral mint
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+}
@@ -1,15 +1,20 @@
 use crate::constants::*;
 use crate::error::LendingError;
-use crate::state::obligation::{ObligationCollateral, ObligationLiquidity};
-use crate::state::obligation_optimized::ObligationOptimized;
+use crate::state::*;
 use crate::utils::math::Decimal;
+use crate::utils::{ComputeBudgetGuard, ProtocolConfig};
+use anchor_lang::error::Error;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::compute_units::sol_remaining_compute_units;
 use std::collections::HashMap;
 
 /// Batch operation types for optimized processing
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Hash, PartialEq, Eq)]
 pub enum BatchOperationType {
+    /// Retired: credited collateral no reserve held. Always rejected; kept so the
+    /// other operations keep their encoding
     UpdateCollateral,
+    /// Retired: credited debt no reserve lent. Always rejected
     UpdateBorrow,
     UpdateHealthFactors,
     LiquidationCheck,
@@ -32,47 +37,81 @@ pub struct BatchOperationResult {
     pub operation_id: u32,
     pub success: bool,
     pub error_code: Option<u32>,
-    pub gas_used: u64,
+    /// Compute units the operation consumed, measured on-chain
+    pub compute_units: u64,
+}
+
+/// Outcome of a batch, returned to the caller as instruction return data
+/// Clients size their next batch from the measured compute units.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct BatchExecutionReport {
+    /// Per-operation results, grouped by operation type
+    pub results: Vec<BatchOperationResult>,
+    /// Compute units consumed by the whole batch, including grouping and preloading
+    pub total_compute_units: u64,
+    /// Compute units left in the transaction after the batch
+    pub remaining_compute_units: u64,
 }
 
 /// Batch operation context for tracking performance
 #[derive(Debug)]
 pub struct BatchContext {
-    pub start_time: i64,
+    pub start_compute_units: u64,
     pub operations_processed: u32,
     pub operations_failed: u32,
-    pub total_gas_used: u64,
+    pub total_compute_units: u64,
     pub cache_hits: u32,
     pub cache_misses: u32,
 }
 
 impl BatchContext {
-    pub fn new() -> Result<Self> {
-        Ok(Self {
-            start_time: Clock::get()
-                .map_err(|_| error!(crate::error::LendingError::InvalidInstruction))?
-                .unix_timestamp,
+    pub fn new() -> Self {
+        Self {
+            start_compute_units: sol_remaining_compute_units(),
             operations_processed: 0,
             operations_failed: 0,
-            total_gas_used: 0,
+            total_compute_units: 0,
             cache_hits: 0,
             cache_misses: 0,
-        })
+        }
     }
 
-    pub fn record_operation(&mut self, success: bool, gas_used: u64) {
+    pub fn record_operation(&mut self, success: bool, compute_units: u64) {
         self.operations_processed += 1;
         if !success {
             self.operations_failed += 1;
         }
-        self.total_gas_used += gas_used;
+        self.total_compute_units += compute_units;
     }
+
+    /// Compute units consumed since the context was created
+    pub fn elapsed_compute_units(&self) -> u64 {
+        self.start_compute_units
+            .saturating_sub(sol_remaining_compute_units())
+    }
+}
+
+/// Run `f`, returning its output and the compute units it consumed
+/// The measurement includes the cost of one `sol_remaining_compute_units` syscall.
+fn measure_compute_units<T>(f: impl FnOnce() -> T) -> (T, u64) {
+    let before = sol_remaining_compute_units();
+    let output = f();
+    (output, before.saturating_sub(sol_remaining_compute_units()))
+}
+
+/// Obligation loaded for a batch, with where to write it back
+struct CachedObligation {
+    obligation: AnyObligation,
+    /// Index of the obligation's account in the batch accounts
+    account_index: usize,
+    /// Whether an operation changed the obligation
+    dirty: bool,
 }
 
 /// Batch processor for efficient multi-operation handling
 pub struct BatchProcessor {
     /// Cache for frequently accessed obligations
-    obligation_cache: std::collections::HashMap<Pubkey, ObligationOptimized>,
+    obligation_cache: HashMap<Pubkey, CachedObligation>,
     /// Maximum batch size to prevent excessive gas usage
     max_batch_size: usize,
     /// Statistics for performance monitoring
@@ -82,42 +121,87 @@ pub struct BatchProcessor {
 impl BatchProcessor {
     pub fn new(max_batch_size: usize) -> Self {
         Self {
-            obligation_cache: std::collections::HashMap::new(),
+            obligation_cache: HashMap::new(),
             max_batch_size,
             stats: BatchContext::new(),
         }
     }
 
     /// Process multiple operations in a single transaction for efficiency
+    /// Obligations and reserves are found in `accounts` by key and must belong to
+    /// `market`. A failing operation is reported without affecting the others; changed
+    /// obligations are only written back by `persist`. Operations that no longer fit in
+    /// the remaining compute units are reported as `ComputeBudgetExhausted` without
+    /// running. A batch containing a retired position update is rejected as a whole.
     pub fn process_batch_operations(
         &mut self,
         operations: &[BatchOperation],
         accounts: &[AccountInfo],
+        market: &Pubkey,
+        slot: u64,
     ) -> Result<Vec<BatchOperationResult>> {
         if operations.len() > self.max_batch_size {
             return Err(LendingError::BatchSizeExceeded.into());
         }
 
+        // Positions only change alongside the token movements that back them
+        if operations.iter().any(is_position_update) {
+            return Err(LendingError::InvalidOperationType.into());
+        }
+
         let mut results = Vec::with_capacity(operations.len());
+        let mut budget = ComputeBudgetGuard::new(BATCH_OPERATION_COMPUTE_BUDGET);
 
         // Group operations by type for better cache locality
         let grouped_ops = self.group_operations_by_type(operations);
 
         // Process each group to maximize cache reuse
         for (op_type, ops) in grouped_ops.into_iter() {
-            let group_results = self.process_operation_group(&op_type, &ops, accounts)?;
-            results.extend(group_results);
+            self.preload_obligations(&ops, accounts, market);
+
+            for (op_index, operation) in ops {
+                if !budget.try_begin_step() {
+                    self.stats.record_operation(false, 0);
+                    results.push(BatchOperationResult {
+                        operation_id: op_index as u32,
+                        success: false,
+                        error_code: Some(error_code_of(
+                            &LendingError::ComputeBudgetExhausted.into(),
+                        )),
+                        compute_units: 0,
+                    });
+                    continue;
+                }
+
+                let (result, compute_units) = measure_compute_units(|| {
+                    self.process_operation(&op_type, operation, accounts, market, slot)
+                });
+                let success = result.is_ok();
+
+                if let Err(e) = &result {
+                    msg!("Batch operation {} failed: {}", op_index, e);
+                }
+
+                self.stats.record_operation(success, compute_units);
+
+                results.push(BatchOperationResult {
+                    operation_id: op_index as u32,
+                    success,
+                    error_code: result.err().map(|e| error_code_of(&e)),
+                    compute_units,
+                });
+            }
         }
 
         Ok(results)
     }
 
     /// Group operations by type for better processing efficiency
-    fn group_operations_by_type(
+    fn group_operations_by_type<'a>(
         &self,
-        operations: &[BatchOperation],
-    ) -> std::collections::HashMap<BatchOperationType, Vec<(usize, &BatchOperation)>> {
-        let mut grouped = std::collections::HashMap::new();
+        operations: &'a [BatchOperation],
+    ) -> HashMap<BatchOperationType, Vec<(usize, &'a BatchOperation)>> {
+        let mut grouped = HashMap::new();
 
         for (index, op) in operations.iter().enumerate() {
             grouped
@@ -129,357 +213,101 @@ impl BatchProcessor {
         grouped
     }
 
-    /// Process a group of operations of the same type
-    fn process_operation_group(
+    /// Process a single operation against its cached obligation
+    fn process_operation(
         &mut self,
         op_type: &BatchOperationType,
-        operations: &[(usize, &BatchOperation)],
+        operation: &BatchOperation,
         accounts: &[AccountInfo],
-    ) -> Result<Vec<BatchOperationResult>> {
-        let mut results = Vec::new();
+        market: &Pubkey,
+        slot: u64,
+    ) -> Result<()> {
+        let cached = self
+            .obligation_cache
+            .get_mut(&operation.obligation_key)
+            .ok_or(LendingError::InvalidAccount)?;
 
         match op_type {
-            BatchOperationType::UpdateCollateral => {
-                results = self.batch_update_collateral(operations, accounts)?;
-            }
-            BatchOperationType::UpdateBorrow => {
-                results = self.batch_update_borrows(operations, accounts)?;
+            BatchOperationType::UpdateCollateral | BatchOperationType::UpdateBorrow => {
+                return Err(LendingError::InvalidOperationType.into());
             }
             BatchOperationType::UpdateHealthFactors => {
-                results = self.batch_update_health_factors(operations, accounts)?;
+                Self::require_writable(cached, accounts)?;
+                // Health is only tracked from values revalued by a recent refresh
+                cached.obligation.require_refreshed(slot)?;
+                cached.obligation.update_unhealthy_since(slot)?;
+                cached.dirty = true;
             }
             BatchOperationType::LiquidationCheck => {
-                results = self.batch_liquidation_check(operations, accounts)?;
+                if cached.obligation.is_healthy()? {
+                    return Err(LendingError::ObligationHealthy.into());
+                }
             }
             BatchOperationType::InterestAccrual => {
-                results = self.batch_interest_accrual(operations, accounts)?;
+                Self::require_writable(cached, accounts)?;
+                let reserve_key = operation
+                    .reserve_key
+                    .ok_or(LendingError::InvalidInstruction)?;
+                let reserve = load_reserve(accounts, &reserve_key, market)?;
+                cached
+                    .obligation
+                    .accrue_borrow_interest(&reserve_key, reserve.cumulative_borrow_rate_wads())?;
+                cached.dirty = true;
             }
         }
 
-        Ok(results)
-    }
-
-    /// Batch update collateral positions - O(k log n) where k is batch size
-    fn batch_update_collateral(
-        &mut self,
-        operations: &[(usize, &BatchOperation)],
-        accounts: &[AccountInfo],
-    ) -> Result<Vec<BatchOperationResult>> {
-        let mut results = Vec::new();
-
-        // Pre-load all required obligations into cache
-        self.preload_obligations(operations, accounts)?;
-
-        for (op_index, operation) in operations {
-            let start_gas = 0; // Would measure actual gas usage
-
-            let result = self.update_single_collateral(operation, accounts);
-            let success = result.is_ok();
-
-            if let Err(e) = result {
-                msg!(
-                    "Collateral update failed for operation {}: {:?}",
-                    op_index,
-                    e
-                );
-            }
-
-            let gas_used = 1000; // Would calculate actual gas usage
-            self.stats.record_operation(success, gas_used);
-
-            results.push(BatchOperationResult {
-                operation_id: *op_index as u32,
-                success,
-                error_code: if success { None } else { Some(1001) },
-                gas_used,
-            });
-        }
-
-        Ok(results)
-    }
-
-    /// Batch update borrow positions
-    fn batch_update_borrows(
-        &mut self,
-        operations: &[(usize, &BatchOperation)],
-        accounts: &[AccountInfo],
-    ) -> Result<Vec<BatchOperationResult>> {
-        let mut results = Vec::new();
-
-        self.preload_obligations(operations, accounts)?;
-
-        for (op_index, operation) in operations {
-            let start_gas = 0;
-
-            let result = self.update_single_borrow(operation, accounts);
-            let success = result.is_ok();
-
-            let gas_used = 1200; // Borrows are slightly more expensive
-            self.stats.record_operation(success, gas_used);
-
-            results.push(BatchOperationResult {
-                operation_id: *op_index as u32,
-                success,
-                error_code: if success { None } else { Some(1002) },
-                gas_used,
-            });
-        }
-
-        Ok(results)
-    }
-
-    /// Batch health factor updates - vectorized calculation
-    fn batch_update_health_factors(
-        &mut self,
-        operations: &[(usize, &BatchOperation)],
-        accounts: &[AccountInfo],
-    ) -> Result<Vec<BatchOperationResult>> {
-        let mut results = Vec::new();
-
-        // Collect all obligation keys for batch processing
-        let obligation_keys: Vec<Pubkey> =
-            operations.iter().map(|(_, op)| op.obligation_key).collect();
-
-        // Vectorized health factor calculation
-        let health_factors =
-            self.calculate_health_factors_vectorized(&obligation_keys, accounts)?;
-
-        for ((op_index, operation), health_factor) in operations.iter().zip(health_factors.iter()) {
-            let success = health_factor.is_some();
-            let gas_used = 800; // Health factor calculation is relatively cheap when batched
-
-            self.stats.record_operation(success, gas_used);
-
-            results.push(BatchOperationResult {
-                operation_id: *op_index as u32,
-                success,
-                error_code: if success { None } else { Some(1003) },
-                gas_used,
-            });
-        }
-
-        Ok(results)
-    }
-
-    /// Batch liquidation checks with early termination
-    fn batch_liquidation_check(
-        &mut self,
-        operations: &[(usize, &BatchOperation)],
-        accounts: &[AccountInfo],
-    ) -> Result<Vec<BatchOperationResult>> {
-        let mut results = Vec::new();
-
-        // First pass: quick health factor screening
-        let obligation_keys: Vec<Pubkey> =
-            operations.iter().map(|(_, op)| op.obligation_key).collect();
-
-        let health_factors =
-            self.calculate_health_factors_vectorized(&obligation_keys, accounts)?;
-
-        for ((op_index, _operation), health_factor) in operations.iter().zip(health_factors.iter())
-        {
-            let success = health_factor.is_some();
-            let is_liquidatable = health_factor
-                .map(|hf| hf.value < Decimal::one().value)
-                .unwrap_or(false);
-
-            let gas_used = if is_liquidatable { 1500 } else { 500 }; // Liquidation prep is more expensive
-
-            self.stats.record_operation(success, gas_used);
-
-            results.push(BatchOperationResult {
-                operation_id: *op_index as u32,
-                success,
-                error_code: if success { None } else { Some(1004) },
-                gas_used,
-            });
-        }
-
-        Ok(results)
-    }
-
-    /// Batch interest accrual - compound calculations
-    fn batch_interest_accrual(
-        &mut self,
-        operations: &[(usize, &BatchOperation)],
-        accounts: &[AccountInfo],
-    ) -> Result<Vec<BatchOperationResult>> {
-        let mut results = Vec::new();
-
-        // Group by reserve for compound interest calculations
-        let mut reserve_groups: std::collections::HashMap<Pubkey, Vec<(usize, &BatchOperation)>> =
-            std::collections::HashMap::new();
-
-        for (op_index, operation) in operations {
-            if let Some(reserve_key) = operation.reserve_key {
-                reserve_groups
-                    .entry(reserve_key)
-                    .or_insert_with(Vec::new)
-                    .push((*op_index, operation));
-            }
-        }
-
-        // Process each reserve group for efficient interest calculation
-        for (reserve_key, reserve_ops) in reserve_groups {
-            let reserve_results =
-                self.process_reserve_interest_batch(&reserve_key, &reserve_ops, accounts)?;
-            results.extend(reserve_results);
-        }
-
-        Ok(results)
+        Ok(())
     }
 
-    /// Process interest accrual for a single reserve batch
-    fn process_reserve_interest_batch(
-        &mut self,
-        reserve_key: &Pubkey,
-        operations: &[(usize, &BatchOperation)],
-        accounts: &[AccountInfo],
-    ) -> Result<Vec<BatchOperationResult>> {
-        let mut results = Vec::new();
-
-        // Get current interest rate for the reserve (would fetch from reserve account)
-        let current_rate = Decimal::from_scaled_val(50000000000000000); // 5% APR example
-        let time_delta = 3600; // 1 hour example
-
-        for (op_index, operation) in operations {
-            let start_gas = 0;
-
-            // Apply compound interest to the position
-            let result =
-                self.apply_compound_interest(operation, current_rate, time_delta, accounts);
-            let success = result.is_ok();
-
-            let gas_used = 900; // Interest calculation gas cost
-            self.stats.record_operation(success, gas_used);
-
-            results.push(BatchOperationResult {
-                operation_id: *op_index as u32,
-                success,
-                error_code: if success { None } else { Some(1005) },
-                gas_used,
-            });
+    /// Fail an operation that would change an obligation passed read-only
+    fn require_writable(cached: &CachedObligation, accounts: &[AccountInfo]) -> Result<()> {
+        if !accounts[cached.account_index].is_writable {
+            return Err(LendingError::InvalidAccount.into());
         }
-
-        Ok(results)
+        Ok(())
     }
 
     /// Preload obligations into cache for batch processing
+    /// Obligations that cannot be loaded stay uncached, failing their operations.
     fn preload_obligations(
         &mut self,
         operations: &[(usize, &BatchOperation)],
         accounts: &[AccountInfo],
-    ) -> Result<()> {
-        let unique_obligations: std::collections::HashSet<Pubkey> =
-            operations.iter().map(|(_, op)| op.obligation_key).collect();
-
-        for obligation_key in unique_obligations {
-            if !self.obligation_cache.contains_key(&obligation_key) {
-                // Load obligation from account (would implement actual loading)
-                let obligation = self.load_obligation_from_accounts(&obligation_key, accounts)?;
-                self.obligation_cache.insert(obligation_key, obligation);
-                self.stats.cache_misses += 1;
-            } else {
+        market: &Pubkey,
+    ) {
+        for (_, operation) in operations {
+            let obligation_key = operation.obligation_key;
+            if self.obligation_cache.contains_key(&obligation_key) {
                 self.stats.cache_hits += 1;
+                continue;
             }
-        }
-
-        Ok(())
-    }
-
-    /// Vectorized health factor calculation for multiple obligations
-    fn calculate_health_factors_vectorized(
-        &self,
-        obligation_keys: &[Pubkey],
-        accounts: &[AccountInfo],
-    ) -> Result<Vec<Option<Decimal>>> {
-        let mut health_factors = Vec::with_capacity(obligation_keys.len());
-
-        for &obligation_key in obligation_keys {
-            if let Some(obligation) = self.obligation_cache.get(&obligation_key) {
-                let health_factor = obligation.calculate_health_factor().ok();
-                health_factors.push(health_factor);
-            } else {
-                health_factors.push(None);
-            }
-        }
-
-        Ok(health_factors)
-    }
-
-    /// Helper functions for individual operations
-    fn update_single_collateral(
-        &mut self,
-        operation: &BatchOperation,
-        accounts: &[AccountInfo],
-    ) -> Result<()> {
-        if let Some(obligation) = self.obligation_cache.get_mut(&operation.obligation_key) {
-            if let (Some(reserve_key), Some(amount)) = (operation.reserve_key, operation.amount) {
-                // Find and update collateral
-                if let Some(collateral) = obligation.find_collateral_deposit_mut(&reserve_key) {
-                    collateral.deposited_amount = collateral
-                        .deposited_amount
-                        .checked_add(amount)
-                        .ok_or(LendingError::MathOverflow)?;
-                }
-            }
-        }
-        Ok(())
-    }
 
-    fn update_single_borrow(
-        &mut self,
-        operation: &BatchOperation,
-        accounts: &[AccountInfo],
-    ) -> Result<()> {
-        if let Some(obligation) = self.obligation_cache.get_mut(&operation.obligation_key) {
-            if let (Some(reserve_key), Some(amount)) =
-                (operation.reserve_key, operation.decimal_amount)
-            {
-                if let Some(borrow) = obligation.find_liquidity_borrow_mut(&reserve_key) {
-                    borrow.borrowed_amount_wads = borrow.borrowed_amount_wads.try_add(amount)?;
+            self.stats.cache_misses += 1;
+            if let Some(account_index) = accounts.iter().position(|a| a.key == &obligation_key) {
+                if let Ok(obligation) =
+                    AnyObligation::load_checked(&accounts[account_index], market)
+                {
+                    self.obligation_cache.insert(
+                        obligation_key,
+                        CachedObligation {
+                            obligation,
+                            account_index,
+                            dirty: false,
+                        },
+                    );
                 }
             }
         }
-        Ok(())
     }
 
-    fn apply_compound_interest(
-        &mut self,
-        operation: &BatchOperation,
-        rate: Decimal,
-        time_delta: u64,
-        accounts: &[AccountInfo],
-    ) -> Result<()> {
-        if let Some(obligation) = self.obligation_cache.get_mut(&operation.obligation_key) {
-            if let Some(reserve_key) = operation.reserve_key {
-                if let Some(borrow) = obligation.find_liquidity_borrow_mut(&reserve_key) {
-                    // Apply compound interest: A = P(1 + r)^t
-                    let interest_factor = Decimal::one().try_add(rate)?;
-                    let compound_factor = crate::utils::math_optimized::fast_math::fast_pow(
-                        interest_factor.value,
-                        time_delta as u32,
-                    )?;
-
-                    let new_amount = borrow
-                        .borrowed_amount_wads
-                        .try_mul(Decimal::from_scaled_val(compound_factor))?;
-
-                    borrow.borrowed_amount_wads = new_amount;
-                }
-            }
+    /// Write every changed obligation back to its account
+    pub fn persist(&self, accounts: &[AccountInfo]) -> Result<usize> {
+        let mut written = 0;
+        for cached in self.obligation_cache.values().filter(|cached| cached.dirty) {
+            cached.obligation.save(&accounts[cached.account_index])?;
+            written += 1;
         }
-        Ok(())
-    }
-
-    fn load_obligation_from_accounts(
-        &self,
-        obligation_key: &Pubkey,
-        accounts: &[AccountInfo],
-    ) -> Result<ObligationOptimized> {
-        // In a real implementation, this would find and deserialize the obligation account
-        // For now, return a default obligation
-        ObligationOptimized::new(*obligation_key, *obligation_key)
+        Ok(written)
     }
 
     /// Get batch processing statistics
@@ -502,42 +330,108 @@ impl BatchProcessor {
     }
 }
 
-/// Batch instruction for Anchor
+/// Retired operations that credited positions without moving any tokens
+fn is_position_update(operation: &BatchOperation) -> bool {
+    matches!(
+        operation.operation_type,
+        BatchOperationType::UpdateCollateral | BatchOperationType::UpdateBorrow
+    )
+}
+
+/// Deserialize the reserve `reserve_key` of `market` from the batch accounts
+fn load_reserve(
+    accounts: &[AccountInfo],
+    reserve_key: &Pubkey,
+    market: &Pubkey,
+) -> Result<Reserve> {
+    let reserve_info = accounts
+        .iter()
+        .find(|a| a.key == reserve_key)
+        .ok_or(LendingError::InvalidAccount)?;
+    if reserve_info.owner != &crate::ID {
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+
+    let reserve_data = reserve_info.try_borrow_data()?;
+    let mut reserve_data_slice = reserve_data.as_ref();
+    let reserve = Reserve::try_deserialize(&mut reserve_data_slice)
+        .map_err(|_| LendingError::InvalidAccount)?;
+    if reserve.market != *market {
+        return Err(LendingError::InvalidMarketState.into());
+    }
+    Ok(reserve)
+}
+
+/// Error code reported for an operation that failed within a batch
+pub(crate) fn error_code_of(error: &Error) -> u32 {
+    match error {
+        Error::AnchorError(error) => error.error_code_number,
+        Error::ProgramError(error) => u64::from(error.program_error.clone()) as u32,
+    }
+}
+
 #[derive(Accounts)]
-pub struct BatchProcessInstruction<'info> {
-    #[account(mut)]
+pub struct ProcessBatchOperations<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol configuration (remaining account cap)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Signer submitting the batch
     pub authority: Signer<'info>,
-
-    #[account(mut)]
-    pub market: AccountInfo<'info>,
+    // Note: Obligation and reserve accounts are passed as remaining_accounts
 }
 
 /// Process batch operations instruction
-pub fn process_batch_operations<'a>(
-    ctx: Context<'a, BatchProcessInstruction<'a>>,
+/// Health checks and interest accrual are permissionless. The report, including the
+/// compute units each operation consumed, is serialized into the instruction's return
+/// data.
+pub fn process_batch_operations<'info>(
+    ctx: Context<'_, '_, '_, 'info, ProcessBatchOperations<'info>>,
     operations: Vec<BatchOperation>,
-) -> Result<Vec<BatchOperationResult>> {
-    let mut processor = BatchProcessor::new(MAX_BATCH_OPERATIONS);
+) -> Result<BatchExecutionReport> {
+    let market_key = ctx.accounts.market.key();
+    let slot = Clock::get()?.slot;
 
-    // Collect all accounts including remaining accounts
-    let mut all_accounts = vec![ctx.accounts.market.clone()];
-    // Clone the remaining accounts to avoid lifetime issues
-    for account in ctx.remaining_accounts.iter() {
-        all_accounts.push(account.clone());
-    }
+    ctx.accounts
+        .config
+        .validate_account_count(ctx.remaining_accounts.len())?;
 
-    let results = processor.process_batch_operations(&operations, &all_accounts)?;
+    let mut processor = BatchProcessor::new(MAX_BATCH_OPERATIONS);
+    let results = processor.process_batch_operations(
+        &operations,
+        ctx.remaining_accounts,
+        &market_key,
+        slot,
+    )?;
+    let written = processor.persist(ctx.remaining_accounts)?;
 
     // Log performance metrics
     let stats = processor.get_statistics();
+    let total_compute_units = stats.elapsed_compute_units();
     msg!(
-        "Batch processed: {} operations, {} failed, cache efficiency: {:.2}%",
+        "Batch processed: {} operations, {} failed, {} obligations written, {} CU, cache efficiency: {:.2}%",
         stats.operations_processed,
         stats.operations_failed,
+        written,
+        total_compute_units,
         processor.cache_efficiency() * 100.0
     );
 
-    Ok(results)
+    Ok(BatchExecutionReport {
+        results,
+        total_compute_units,
+        remaining_compute_units: sol_remaining_compute_units(),
+    })
 }
 
 #[cfg(test)]
@@ -549,17 +443,64 @@ mod tests {
         let mut processor = BatchProcessor::new(10);
 
         let operations = vec![BatchOperation {
-            operation_type: BatchOperationType::UpdateCollateral,
+            operation_type: BatchOperationType::InterestAccrual,
             obligation_key: Pubkey::new_unique(),
             reserve_key: Some(Pubkey::new_unique()),
-            amount: Some(1000),
+            amount: None,
             decimal_amount: None,
         }];
 
         // Test operation grouping
         let grouped = processor.group_operations_by_type(&operations);
         assert_eq!(grouped.len(), 1);
-        assert!(grouped.contains_key(&BatchOperationType::UpdateCollateral));
+        assert!(grouped.contains_key(&BatchOperationType::InterestAccrual));
+    }
+
+    #[test]
+    fn test_unbacked_position_increase_rejected() {
+        let obligation_key = Pubkey::new_unique();
+        let reserve_key = Some(Pubkey::new_unique());
+        let health_check = BatchOperation {
+            operation_type: BatchOperationType::LiquidationCheck,
+            obligation_key,
+            reserve_key: None,
+            amount: None,
+            decimal_amount: None,
+        };
+        let credits = [
+            BatchOperation {
+                operation_type: BatchOperationType::UpdateCollateral,
+                obligation_key,
+                reserve_key,
+                amount: Some(1_000_000),
+                decimal_amount: None,
+            },
+            BatchOperation {
+                operation_type: BatchOperationType::UpdateBorrow,
+                obligation_key,
+                reserve_key,
+                amount: None,
+                decimal_amount: Some(Decimal::from_integer(1_000_000).unwrap()),
+            },
+        ];
+
+        // The whole batch fails before any operation runs
+        for credit in credits {
+            let mut processor = BatchProcessor::new(10);
+            let error = processor
+                .process_batch_operations(
+                    &[health_check.clone(), credit],
+                    &[],
+                    &Pubkey::new_unique(),
+                    0,
+                )
+                .unwrap_err();
+            assert_eq!(
+                error_code_of(&error),
+                error_code_of(&LendingError::InvalidOperationType.into())
+            );
+            assert_eq!(processor.get_statistics().operations_processed, 0);
+        }
     }
 
     #[test]
@@ -571,6 +512,6 @@ mod tests {
 
         assert_eq!(context.operations_processed, 2);
         assert_eq!(context.operations_failed, 1);
-        assert_eq!(context.total_gas_used, 2200);
+        assert_eq!(context.total_compute_units, 2200);
     }
 }
@@ -1,35 +1,127 @@
 use crate::constants::*;
 use crate::error::LendingError;
+use crate::events::{
+    emit_integrator_attribution, BorrowEvent, CollateralDepositEvent, CollateralWithdrawEvent,
+    ReferralFeeAccruedEvent, RepayEvent,
+};
+use crate::instructions::liquidation_queue_instructions::dequeue_obligation;
+use crate::instructions::metrics_instructions::record_borrow_metric;
+use crate::instructions::rewards_instructions::sync_rewards;
 use crate::state::*;
-use crate::utils::{math::Decimal, OracleManager, TokenUtils};
+use crate::utils::{
+    math::Decimal, EventType, OracleManager, ProtocolConfig, ProtocolMetrics, ReserveLock,
+    TokenUtils, PROTOCOL_METRICS_SEED,
+};
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 /// Initialize a new user obligation account
-pub fn init_obligation(ctx: Context<InitObligation>) -> Result<()> {
+/// A wallet can hold up to `MAX_OBLIGATIONS_PER_OWNER` isolated obligations, one per
+/// `obligation_index`; index 0 is the wallet's original obligation address.
+pub fn init_obligation(ctx: Context<InitObligation>, obligation_index: u8) -> Result<()> {
     let obligation = &mut ctx.accounts.obligation;
     let market = &ctx.accounts.market;
 
     // Initialize the obligation
-    **obligation = Obligation::new(market.key(), ctx.accounts.obligation_owner.key())?;
+    **obligation = Obligation::new(
+        market.key(),
+        ctx.accounts.obligation_owner.key(),
+        obligation_index,
+    )?;
 
     msg!(
-        "Obligation initialized for user: {}",
+        "Obligation {} initialized for user: {}",
+        obligation_index,
         ctx.accounts.obligation_owner.key()
     );
     Ok(())
 }
 
+/// Close an empty obligation and refund its rent to the owner
+pub fn close_obligation(ctx: Context<CloseObligation>) -> Result<()> {
+    let obligation_info = ctx.accounts.obligation.to_account_info();
+    let owner_info = ctx.accounts.obligation_owner.to_account_info();
+
+    let obligation = AnyObligation::load_checked(&obligation_info, &ctx.accounts.market.key())?;
+
+    if obligation.owner != owner_info.key() {
+        return Err(LendingError::InvalidAuthority.into());
+    }
+    if !obligation.is_empty() {
+        return Err(LendingError::ObligationNotEmpty.into());
+    }
+
+    let lamports = AnyObligation::close(&obligation_info, &owner_info)?;
+
+    msg!(
+        "Obligation {} closed, {} lamports returned to {}",
+        obligation_info.key(),
+        lamports,
+        owner_info.key()
+    );
+    Ok(())
+}
+
+/// Opt an obligation into a liquidation grace period, or out of it with `grace_slots` 0
+/// Enabling requires a freshly refreshed, healthy obligation so a position that is already
+/// liquidatable cannot shield itself.
+pub fn set_obligation_liquidation_grace(
+    ctx: Context<SetObligationLiquidationGrace>,
+    grace_slots: u64,
+    floor_bps: u16,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let mut obligation = AnyObligation::load_owned(
+        &ctx.accounts.obligation,
+        &market.key(),
+        &ctx.accounts.owner.key(),
+    )?;
+    let clock = Clock::get()?;
+
+    if grace_slots > 0 && obligation.has_borrows() {
+        obligation.require_refreshed(clock.slot)?;
+        let emode =
+            EModeCategory::resolve(ctx.accounts.emode_category.as_deref(), &obligation, None)?;
+        if !obligation.is_healthy_with(emode.as_ref())? {
+            return Err(LendingError::ObligationUnhealthy.into());
+        }
+    }
+
+    obligation.set_liquidation_grace(grace_slots, floor_bps)?;
+
+    msg!(
+        "Obligation {} liquidation grace set to {} slots above {} bps health",
+        ctx.accounts.obligation.key(),
+        obligation.liquidation_grace_slots,
+        obligation.liquidation_grace_floor_bps
+    );
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    Ok(())
+}
+
 /// Deposit collateral into an obligation
 pub fn deposit_obligation_collateral(
     ctx: Context<DepositObligationCollateral>,
     collateral_amount: u64,
-) -> Result<()> {
+    integrator_id: Option<u32>,
+) -> Result<OperationReceipt> {
     let market = &ctx.accounts.market;
-    let obligation = &mut ctx.accounts.obligation;
+    let mut obligation = AnyObligation::load_owned(
+        &ctx.accounts.obligation,
+        &market.key(),
+        &ctx.accounts.obligation_owner.key(),
+    )?;
     let deposit_reserve = &mut ctx.accounts.deposit_reserve;
     let clock = Clock::get()?;
 
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(
+        &ctx.accounts.flash_loan_guard,
+        FlashLoanUsage::DEPOSIT_COLLATERAL,
+    )?;
+
     // Check if market allows deposits
     if market.is_paused() || market.is_lending_disabled() {
         return Err(LendingError::MarketPaused.into());
@@ -49,48 +141,57 @@ pub fn deposit_obligation_collateral(
         return Err(LendingError::AmountTooSmall.into());
     }
 
+    // Isolated collateral cannot be mixed with other collateral
+    let isolated = deposit_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::ISOLATED);
+    obligation.validate_collateral_isolation(&deposit_reserve.key(), isolated)?;
+
+    // Hold the reentrancy lock until the reserve state is written back
+    let mut deposit_reserve = ReserveLock::acquire(deposit_reserve)?;
+
     // Refresh reserve interest
     deposit_reserve.update_interest(clock.slot)?;
 
-    // Get price from oracle for collateral valuation
-    let oracle_price = OracleManager::get_pyth_price(
+    // A reserve over its supply cap (e.g. after governance lowered it) cannot back new
+    // collateral, so over-cap supply gains no borrowing power
+    deposit_reserve.validate_supply_cap(0)?;
+
+    // Get price from oracle for collateral valuation. A deposit only improves health,
+    // so it proceeds without a usable price and adds no value until the next refresh.
+    let oracle_price = OracleManager::get_price_if_valid(
         &ctx.accounts.price_oracle.to_account_info(),
-        &deposit_reserve.oracle_feed_id,
-    )?;
-    oracle_price.validate(clock.unix_timestamp)?;
+        &deposit_reserve,
+        clock.unix_timestamp,
+    );
 
     // Calculate USD value of collateral with fresh oracle validation
-    let collateral_value_usd = OracleManager::calculate_usd_value(
-        collateral_amount,
-        &oracle_price,
-        deposit_reserve.config.decimals,
-    )?;
-
-    // Validate collateral deposit won't exceed concentration limits
-    let current_collateral_for_asset = obligation
-        .deposits
-        .iter()
-        .filter(|d| d.deposit_reserve == deposit_reserve.key())
-        .map(|d| d.market_value_usd.value)
-        .sum::<u128>();
-
-    let new_total_collateral_for_asset = current_collateral_for_asset
-        .checked_add(collateral_value_usd.value)
-        .ok_or(LendingError::MathOverflow)?;
-
-    // Prevent over-concentration in single asset (max 70% of portfolio in one asset)
-    let total_portfolio_value = obligation
-        .deposited_value_usd
-        .try_add(collateral_value_usd)?;
+    let collateral_value_usd = match &oracle_price {
+        Some(oracle_price) => {
+            deposit_reserve.collateral_value_usd(collateral_amount, oracle_price)?
+        }
+        None => Decimal::zero(),
+    };
 
-    let max_single_asset_value = total_portfolio_value.try_mul(Decimal::from_scaled_val(
-        (7000u128 * PRECISION as u128)
-            .checked_div(BASIS_POINTS_PRECISION as u128)
-            .ok_or(LendingError::DivisionByZero)?,
-    ))?;
+    // Isolated collateral is the obligation's only collateral by construction, and an
+    // unpriced deposit adds no value to concentrate
+    if !isolated && oracle_price.is_some() {
+        validate_collateral_concentration(
+            &obligation,
+            &deposit_reserve.key(),
+            collateral_value_usd,
+        )?;
+    }
 
-    if new_total_collateral_for_asset > max_single_asset_value.value {
-        return Err(LendingError::InvalidAmount.into()); // Too concentrated
+    // An unpriced deposit has no value to size against the minimum
+    if oracle_price.is_some() {
+        validate_collateral_position_size(
+            &ctx.accounts.config,
+            &obligation,
+            &deposit_reserve.key(),
+            collateral_value_usd,
+        )?;
     }
 
     // Transfer collateral tokens from user to reserve
@@ -98,6 +199,7 @@ pub fn deposit_obligation_collateral(
         &ctx.accounts.token_program,
         &ctx.accounts.source_collateral,
         &ctx.accounts.destination_collateral,
+        &ctx.accounts.collateral_mint,
         &ctx.accounts.obligation_owner.to_account_info(),
         &[],
         collateral_amount,
@@ -108,18 +210,36 @@ pub fn deposit_obligation_collateral(
         deposit_reserve: deposit_reserve.key(),
         deposited_amount: collateral_amount,
         market_value_usd: collateral_value_usd,
-        ltv_bps: deposit_reserve.config.loan_to_value_ratio_bps,
+        ltv_bps: deposit_reserve.effective_loan_to_value_ratio_bps(clock.unix_timestamp as u64),
         liquidation_threshold_bps: deposit_reserve.config.liquidation_threshold_bps,
     };
 
     obligation.add_collateral_deposit(collateral_deposit)?;
+    if isolated {
+        obligation.isolation_mode = true;
+    }
 
     // Update cached values
     obligation.deposited_value_usd = obligation
         .deposited_value_usd
         .try_add(collateral_value_usd)?;
 
-    obligation.update_timestamp(clock.slot);
+    // An unpriced deposit leaves the obligation stale, so it must be refreshed before
+    // anything relies on its values
+    if oracle_price.is_some() {
+        obligation.update_timestamp(clock.slot)?;
+    } else {
+        msg!("Oracle price unavailable - deposit recorded without value");
+    }
+
+    emit_integrator_attribution(
+        integrator_id,
+        EventType::CollateralDeposited,
+        ctx.accounts.obligation_owner.key(),
+        deposit_reserve.key(),
+        collateral_amount,
+        clock.slot,
+    );
 
     msg!(
         "Deposited {} collateral tokens worth ${:.2} USD",
@@ -127,19 +247,66 @@ pub fn deposit_obligation_collateral(
         collateral_value_usd.try_floor_u64()? as f64 / 1e18
     );
 
-    Ok(())
+    // Settle reward emissions on the position this operation changed
+    sync_rewards(
+        ctx.accounts.reward_pool.as_mut(),
+        ctx.accounts.obligation_rewards.as_mut(),
+        &ctx.accounts.obligation.key(),
+        &obligation,
+        clock.slot,
+    )?;
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    let receipt = OperationReceipt::new(
+        deposit_reserve.key(),
+        &deposit_reserve,
+        deposit_reserve.collateral_to_liquidity(collateral_amount)?,
+        collateral_amount,
+        0,
+        Some(obligation.calculate_health_factor()?),
+    )?;
+
+    emit!(CollateralDepositEvent {
+        obligation: ctx.accounts.obligation.key(),
+        owner: ctx.accounts.obligation_owner.key(),
+        receipt,
+        slot: clock.slot,
+    });
+
+    Ok(receipt)
 }
 
 /// Withdraw collateral from an obligation
 pub fn withdraw_obligation_collateral(
     ctx: Context<WithdrawObligationCollateral>,
     collateral_amount: u64,
-) -> Result<()> {
+    integrator_id: Option<u32>,
+) -> Result<OperationReceipt> {
     let market = &ctx.accounts.market;
-    let obligation = &mut ctx.accounts.obligation;
+    let mut obligation = AnyObligation::load_owned(
+        &ctx.accounts.obligation,
+        &market.key(),
+        &ctx.accounts.obligation_owner.key(),
+    )?;
     let withdraw_reserve = &mut ctx.accounts.withdraw_reserve;
     let clock = Clock::get()?;
 
+    // Settle rewards on the position held so far, before this operation shrinks it
+    sync_rewards(
+        ctx.accounts.reward_pool.as_mut(),
+        ctx.accounts.obligation_rewards.as_mut(),
+        &ctx.accounts.obligation.key(),
+        &obligation,
+        clock.slot,
+    )?;
+
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(
+        &ctx.accounts.flash_loan_guard,
+        FlashLoanUsage::WITHDRAW_COLLATERAL,
+    )?;
+
     // Check if market allows withdrawals
     if market.is_paused() && !market.is_emergency() {
         return Err(LendingError::MarketPaused.into());
@@ -150,6 +317,14 @@ pub fn withdraw_obligation_collateral(
         return Err(LendingError::AmountTooSmall.into());
     }
 
+    // The health check below relies on values from a refresh in this slot
+    if obligation.has_borrows() {
+        obligation.require_refreshed(clock.slot)?;
+    }
+
+    // Hold the reentrancy lock until the reserve state is written back
+    let mut withdraw_reserve = ReserveLock::acquire(withdraw_reserve)?;
+
     // Refresh reserve interest
     withdraw_reserve.update_interest(clock.slot)?;
 
@@ -163,18 +338,15 @@ pub fn withdraw_obligation_collateral(
     }
 
     // Get current price for updated valuation
-    let oracle_price = OracleManager::get_pyth_price(
+    let oracle_price = OracleManager::get_price(
         &ctx.accounts.price_oracle.to_account_info(),
-        &withdraw_reserve.oracle_feed_id,
+        &withdraw_reserve,
     )?;
     oracle_price.validate(clock.unix_timestamp)?;
 
     // Calculate USD value of collateral being withdrawn
-    let withdrawn_value_usd = OracleManager::calculate_usd_value(
-        collateral_amount,
-        &oracle_price,
-        withdraw_reserve.config.decimals,
-    )?;
+    let withdrawn_value_usd =
+        withdraw_reserve.collateral_value_usd(collateral_amount, &oracle_price)?;
 
     // Remove collateral from obligation
     obligation.remove_collateral_deposit(&withdraw_reserve.key(), collateral_amount)?;
@@ -184,8 +356,9 @@ pub fn withdraw_obligation_collateral(
         .deposited_value_usd
         .try_sub(withdrawn_value_usd)?;
 
-    // Check if obligation remains healthy after withdrawal
-    if obligation.has_borrows() && !obligation.is_healthy()? {
+    // Check if obligation remains healthy after withdrawal, with eMode parameters if they apply
+    let emode = EModeCategory::resolve(ctx.accounts.emode_category.as_deref(), &obligation, None)?;
+    if obligation.has_borrows() && !obligation.is_healthy_with(emode.as_ref())? {
         return Err(LendingError::ObligationUnhealthy.into());
     }
 
@@ -201,6 +374,7 @@ pub fn withdraw_obligation_collateral(
         &ctx.accounts.token_program,
         &ctx.accounts.source_collateral,
         &ctx.accounts.destination_collateral,
+        &ctx.accounts.collateral_mint,
         &ctx.accounts.collateral_supply_authority.to_account_info(),
         &[authority_seeds],
         collateral_amount,
@@ -208,35 +382,80 @@ pub fn withdraw_obligation_collateral(
 
     obligation.update_timestamp(clock.slot);
 
+    emit_integrator_attribution(
+        integrator_id,
+        EventType::CollateralWithdrawn,
+        ctx.accounts.obligation_owner.key(),
+        withdraw_reserve.key(),
+        collateral_amount,
+        clock.slot,
+    );
+
     msg!(
         "Withdrew {} collateral tokens worth ${:.2} USD",
         collateral_amount,
         withdrawn_value_usd.try_floor_u64()? as f64 / 1e18
     );
 
-    Ok(())
+    // Settle reward emissions on the position this operation changed
+    sync_rewards(
+        ctx.accounts.reward_pool.as_mut(),
+        ctx.accounts.obligation_rewards.as_mut(),
+        &ctx.accounts.obligation.key(),
+        &obligation,
+        clock.slot,
+    )?;
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    let receipt = OperationReceipt::new(
+        withdraw_reserve.key(),
+        &withdraw_reserve,
+        withdraw_reserve.collateral_to_liquidity(collateral_amount)?,
+        collateral_amount,
+        0,
+        Some(obligation.calculate_health_factor_with(emode.as_ref())?),
+    )?;
+
+    emit!(CollateralWithdrawEvent {
+        obligation: ctx.accounts.obligation.key(),
+        owner: ctx.accounts.obligation_owner.key(),
+        receipt,
+        slot: clock.slot,
+    });
+
+    Ok(receipt)
 }
 
 /// Borrow liquidity against collateral
 pub fn borrow_obligation_liquidity(
     ctx: Context<BorrowObligationLiquidity>,
     liquidity_amount: u64,
-) -> Result<()> {
+    integrator_id: Option<u32>,
+) -> Result<OperationReceipt> {
     let market = &ctx.accounts.market;
-    let obligation = &mut ctx.accounts.obligation;
+    let mut obligation = AnyObligation::load_owned(
+        &ctx.accounts.obligation,
+        &market.key(),
+        &ctx.accounts.obligation_owner.key(),
+    )?;
     let borrow_reserve = &mut ctx.accounts.borrow_reserve;
     let clock = Clock::get()?;
 
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(&ctx.accounts.flash_loan_guard, FlashLoanUsage::BORROW)?;
+
     // Check if market allows borrowing
     if market.is_paused() || market.is_borrowing_disabled() {
         return Err(LendingError::MarketPaused.into());
     }
 
-    // Check if reserve allows borrowing
+    // Check if reserve allows borrowing; a deprecated reserve takes no new debt
     if borrow_reserve
         .config
         .flags
         .contains(ReserveConfigFlags::BORROWING_DISABLED)
+        || borrow_reserve.is_deprecated()
     {
         return Err(LendingError::FeatureDisabled.into());
     }
@@ -251,6 +470,22 @@ pub fn borrow_obligation_liquidity(
         return Err(LendingError::ObligationCollateralEmpty.into());
     }
 
+    // Borrowing capacity is measured against values from a refresh in this slot
+    obligation.require_refreshed(clock.slot)?;
+
+    // Isolated collateral only backs governance-approved debt reserves
+    if obligation.isolation_mode
+        && !borrow_reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::BORROWABLE_IN_ISOLATION)
+    {
+        return Err(LendingError::BorrowNotAllowedInIsolation.into());
+    }
+
+    // Hold the reentrancy lock until the reserve state is written back
+    let mut borrow_reserve = ReserveLock::acquire(borrow_reserve)?;
+
     // Refresh reserve interest
     borrow_reserve.update_interest(clock.slot)?;
 
@@ -259,10 +494,44 @@ pub fn borrow_obligation_liquidity(
         return Err(LendingError::InsufficientLiquidity.into());
     }
 
+    // Enforce the reserve's borrow cap, including accrued interest
+    borrow_reserve.validate_borrow_cap(liquidity_amount)?;
+
+    // The origination fee is deducted from the borrowed liquidity; the debt is the full amount
+    let borrow_fee = borrow_reserve.borrow_fee(liquidity_amount)?;
+    let (treasury_fee, insurance_fee) = borrow_reserve.split_insurance_fee(borrow_fee)?;
+    let received_amount = liquidity_amount
+        .checked_sub(borrow_fee)
+        .ok_or(LendingError::MathUnderflow)?;
+
+    // A referrer's share comes out of the treasury's cut and stays in the liquidity supply
+    // until claimed; it is already excluded from available liquidity by the borrow.
+    let referral_fee = match ctx.accounts.referrer.as_mut() {
+        Some(referrer) => {
+            referrer.validate_referred(&market.key(), &ctx.accounts.obligation_owner.key())?;
+            let referral_fee = ctx.accounts.config.referral_fee(treasury_fee)?;
+            referrer.record_referral(borrow_reserve.key(), liquidity_amount, referral_fee)?;
+            emit!(ReferralFeeAccruedEvent {
+                referrer: referrer.key(),
+                reserve: borrow_reserve.key(),
+                referred: ctx.accounts.obligation_owner.key(),
+                volume: liquidity_amount,
+                fee: referral_fee,
+                slot: clock.slot,
+            });
+            referral_fee
+        }
+        None => 0,
+    };
+    let treasury_fee = treasury_fee
+        .checked_sub(referral_fee)
+        .ok_or(LendingError::MathUnderflow)?;
+
     // Get price from oracle for borrow valuation
-    let oracle_price = OracleManager::get_pyth_price(
+    let oracle_price = OracleManager::get_checked_price(
         &ctx.accounts.price_oracle.to_account_info(),
-        &borrow_reserve.oracle_feed_id,
+        ctx.accounts.secondary_price_oracle.as_deref(),
+        &borrow_reserve,
     )?;
     oracle_price.validate(clock.unix_timestamp)?;
 
@@ -273,42 +542,29 @@ pub fn borrow_obligation_liquidity(
         borrow_reserve.config.decimals,
     )?;
 
-    // Atomic LTV validation with fresh oracle prices to prevent manipulation
-    // Lock obligation during validation to prevent race conditions
-    let _current_health_factor = obligation.calculate_health_factor()?;
-
-    // Simulate the new borrow to check if it would make the position unhealthy
-    let new_borrowed_value = obligation.borrowed_value_usd.try_add(borrow_value_usd)?;
-    let max_borrow_value = obligation.calculate_max_borrow_value()?;
-
-    // Strict LTV check with buffer to prevent near-liquidation positions
-    let ltv_buffer_bps = 500; // 5% buffer below maximum LTV
-    let safe_max_borrow = max_borrow_value.try_mul(Decimal::from_scaled_val(
-        ((BASIS_POINTS_PRECISION - ltv_buffer_bps) as u128)
-            .checked_mul(PRECISION as u128)
-            .ok_or(LendingError::MathOverflow)?
-            .checked_div(BASIS_POINTS_PRECISION as u128)
-            .ok_or(LendingError::DivisionByZero)?,
-    ))?;
-
-    if new_borrowed_value.value > safe_max_borrow.value {
-        return Err(LendingError::LoanToValueRatioExceedsMax.into());
-    }
-
-    // Additional health factor check after simulated borrow
-    let simulated_health_factor = obligation
-        .calculate_liquidation_threshold_value()?
-        .try_div(new_borrowed_value)?;
+    // eMode parameters apply only if the new borrow also stays within the category
+    let emode = EModeCategory::resolve(
+        ctx.accounts.emode_category.as_deref(),
+        &obligation,
+        Some(&borrow_reserve.key()),
+    )?;
 
-    // Ensure health factor stays well above 1.0 (require at least 1.1)
-    let min_health_factor = Decimal::from_scaled_val(
-        (11u128)
-            .checked_mul(PRECISION as u128 / 10)
-            .ok_or(LendingError::MathOverflow)?,
-    );
+    // Atomic LTV validation with fresh oracle prices to prevent manipulation
+    let new_borrowed_value =
+        validate_borrow_capacity(&obligation, borrow_value_usd, emode.as_ref())?;
+    validate_borrow_position_size(
+        &ctx.accounts.config,
+        &obligation,
+        &borrow_reserve.key(),
+        borrow_value_usd,
+    )?;
 
-    if simulated_health_factor.value < min_health_factor.value {
-        return Err(LendingError::ObligationUnhealthy.into());
+    // Debt against isolated collateral counts toward that collateral's debt ceiling
+    if let Some(isolated_reserve) = isolated_collateral_reserve(
+        &obligation,
+        ctx.accounts.isolated_collateral_reserve.as_mut(),
+    )? {
+        isolated_reserve.add_isolation_mode_debt(borrow_value_usd.try_floor_u64()?)?;
     }
 
     // Add borrow to reserve
@@ -319,15 +575,24 @@ pub fn borrow_obligation_liquidity(
         borrow_reserve: borrow_reserve.key(),
         borrowed_amount_wads: Decimal::from_integer(liquidity_amount)?,
         market_value_usd: borrow_value_usd,
+        origination_utilization_rate: borrow_reserve.state.utilization_rate()?,
+        origination_borrow_rate: borrow_reserve.state.borrow_rate()?,
+        origination_slot: clock.slot,
+        cumulative_borrow_rate_wads: borrow_reserve.cumulative_borrow_rate_wads(),
     };
 
+    let liquidity_borrow_utilization = liquidity_borrow.origination_utilization_rate;
+    let liquidity_borrow_rate = liquidity_borrow.origination_borrow_rate;
     obligation.add_liquidity_borrow(liquidity_borrow)?;
 
     // Update cached values
     obligation.borrowed_value_usd = new_borrowed_value;
+    obligation.record_debt_watermark()?;
     obligation.update_timestamp(clock.slot);
 
-    // Transfer liquidity from reserve to user
+    // Transfer liquidity from reserve to user and the origination fee to the treasury and
+    // insurance fund (less any referral share). The debt is the full amount that leaves the reserve; any transfer
+    // fee of the mint is borne by the recipients.
     let authority_seeds = &[
         LIQUIDITY_TOKEN_SEED,
         borrow_reserve.liquidity_mint.as_ref(),
@@ -339,30 +604,137 @@ pub fn borrow_obligation_liquidity(
         &ctx.accounts.token_program,
         &ctx.accounts.source_liquidity,
         &ctx.accounts.destination_liquidity,
+        &ctx.accounts.liquidity_mint,
         &ctx.accounts.liquidity_supply_authority.to_account_info(),
         &[authority_seeds],
-        liquidity_amount,
+        received_amount,
     )?;
 
+    if treasury_fee > 0 {
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.source_liquidity,
+            &ctx.accounts.fee_receiver,
+            &ctx.accounts.liquidity_mint,
+            &ctx.accounts.liquidity_supply_authority.to_account_info(),
+            &[authority_seeds],
+            treasury_fee,
+        )?;
+    }
+
+    if insurance_fee > 0 {
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.source_liquidity,
+            &ctx.accounts.insurance_fund_vault,
+            &ctx.accounts.liquidity_mint,
+            &ctx.accounts.liquidity_supply_authority.to_account_info(),
+            &[authority_seeds],
+            insurance_fee,
+        )?;
+        ctx.accounts.insurance_fund.record_deposit(insurance_fee)?;
+    }
+
+    record_borrow_metric(ctx.accounts.protocol_metrics.as_mut());
+    if let Some(user_stats) = ctx.accounts.user_stats.as_mut() {
+        user_stats.record_borrow(
+            ctx.accounts.obligation.key(),
+            borrow_reserve.key(),
+            liquidity_amount,
+            borrow_value_usd,
+            clock.slot,
+        )?;
+    }
+
+    // Record the idempotency nonce, if one was supplied
+    if let Some(operation_nonce) = ctx.accounts.operation_nonce.as_mut() {
+        **operation_nonce = OperationNonce::new(
+            ctx.accounts.obligation_owner.key(),
+            ctx.accounts.obligation_owner.key(),
+            NonceOperation::Borrow,
+            borrow_reserve.key(),
+            liquidity_amount,
+            clock.slot,
+        );
+    }
+
+    emit_integrator_attribution(
+        integrator_id,
+        EventType::LiquidityBorrowed,
+        ctx.accounts.obligation_owner.key(),
+        borrow_reserve.key(),
+        liquidity_amount,
+        clock.slot,
+    );
+
     msg!(
-        "Borrowed {} liquidity tokens worth ${:.2} USD",
+        "Borrowed {} liquidity tokens worth ${:.2} USD, fee {} (utilization: {}, borrow rate: {})",
         liquidity_amount,
-        borrow_value_usd.try_floor_u64()? as f64 / 1e18
+        borrow_value_usd.try_floor_u64()? as f64 / 1e18,
+        borrow_fee,
+        liquidity_borrow_utilization.value,
+        liquidity_borrow_rate.value
     );
 
-    Ok(())
+    // Settle reward emissions on the position this operation changed
+    sync_rewards(
+        ctx.accounts.reward_pool.as_mut(),
+        ctx.accounts.obligation_rewards.as_mut(),
+        &ctx.accounts.obligation.key(),
+        &obligation,
+        clock.slot,
+    )?;
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    let receipt = OperationReceipt::new(
+        borrow_reserve.key(),
+        &borrow_reserve,
+        liquidity_amount,
+        0,
+        0,
+        Some(obligation.calculate_health_factor_with(emode.as_ref())?),
+    )?;
+
+    emit!(BorrowEvent {
+        obligation: ctx.accounts.obligation.key(),
+        borrower: ctx.accounts.obligation_owner.key(),
+        receipt,
+        origination_utilization_rate: liquidity_borrow_utilization.value,
+        origination_borrow_rate: liquidity_borrow_rate.value,
+        slot: clock.slot,
+    });
+
+    Ok(receipt)
 }
 
 /// Repay borrowed liquidity
 pub fn repay_obligation_liquidity(
     ctx: Context<RepayObligationLiquidity>,
     liquidity_amount: u64,
-) -> Result<()> {
+    integrator_id: Option<u32>,
+) -> Result<OperationReceipt> {
     let market = &ctx.accounts.market;
-    let obligation = &mut ctx.accounts.obligation;
+    let mut obligation = AnyObligation::load_owned(
+        &ctx.accounts.obligation,
+        &market.key(),
+        &ctx.accounts.obligation_owner.key(),
+    )?;
     let repay_reserve = &mut ctx.accounts.repay_reserve;
     let clock = Clock::get()?;
 
+    // Settle rewards on the position held so far, before this operation shrinks it
+    sync_rewards(
+        ctx.accounts.reward_pool.as_mut(),
+        ctx.accounts.obligation_rewards.as_mut(),
+        &ctx.accounts.obligation.key(),
+        &obligation,
+        clock.slot,
+    )?;
+
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(&ctx.accounts.flash_loan_guard, FlashLoanUsage::REPAY)?;
+
     // Check if market allows repayments
     if market.is_paused() && !market.is_emergency() {
         return Err(LendingError::MarketPaused.into());
@@ -382,8 +754,15 @@ pub fn repay_obligation_liquidity(
         return Err(LendingError::AmountTooSmall.into());
     }
 
-    // Refresh reserve interest
+    // Hold the reentrancy lock until the reserve state is written back
+    let mut repay_reserve = ReserveLock::acquire(repay_reserve)?;
+
+    // Refresh reserve interest and compound the debt up to it
     repay_reserve.update_interest(clock.slot)?;
+    obligation.accrue_borrow_interest(
+        &repay_reserve.key(),
+        repay_reserve.cumulative_borrow_rate_wads(),
+    )?;
 
     // Check if user has this borrow
     let borrow = obligation
@@ -397,28 +776,38 @@ pub fn repay_obligation_liquidity(
         return Err(LendingError::AmountTooSmall.into());
     }
 
-    // Get current price for updated valuation
-    let oracle_price = OracleManager::get_pyth_price(
+    // Get current price for updated valuation. A repayment only improves health, so it
+    // proceeds without a usable price and leaves the debt's value unreduced until the
+    // next refresh.
+    let oracle_price = OracleManager::get_price_if_valid(
         &ctx.accounts.price_oracle.to_account_info(),
-        &repay_reserve.oracle_feed_id,
-    )?;
-    oracle_price.validate(clock.unix_timestamp)?;
+        &repay_reserve,
+        clock.unix_timestamp,
+    );
 
     // Calculate USD value of repayment
-    let repay_value_usd = OracleManager::calculate_usd_value(
-        actual_repay_amount,
-        &oracle_price,
-        repay_reserve.config.decimals,
-    )?;
+    let repay_value_usd = match &oracle_price {
+        Some(oracle_price) => OracleManager::calculate_usd_value(
+            actual_repay_amount,
+            oracle_price,
+            repay_reserve.config.decimals,
+        )?,
+        None => Decimal::zero(),
+    };
+
+    // Transfer repayment from user to reserve, grossed up so that the reserve receives
+    // the full repayment after the mint's transfer fee
+    let transfer_amount =
+        TokenUtils::amount_before_transfer_fee(&ctx.accounts.liquidity_mint, actual_repay_amount)?;
 
-    // Transfer repayment from user to reserve
     TokenUtils::transfer_tokens(
         &ctx.accounts.token_program,
         &ctx.accounts.source_liquidity,
         &ctx.accounts.destination_liquidity,
+        &ctx.accounts.liquidity_mint,
         &ctx.accounts.obligation_owner.to_account_info(),
         &[],
-        actual_repay_amount,
+        transfer_amount,
     )?;
 
     // Update reserve
@@ -430,10 +819,64 @@ pub fn repay_obligation_liquidity(
         Decimal::from_integer(actual_repay_amount)?,
     )?;
 
+    // Release the repaid debt from the isolated collateral's debt ceiling
+    if let Some(isolated_reserve) = isolated_collateral_reserve(
+        &obligation,
+        ctx.accounts.isolated_collateral_reserve.as_mut(),
+    )? {
+        isolated_reserve.remove_isolation_mode_debt(repay_value_usd.try_floor_u64()?);
+    }
+
     // Update cached values
     obligation.borrowed_value_usd = obligation.borrowed_value_usd.try_sub(repay_value_usd)?;
+    obligation.record_repayment();
+
+    if let Some(user_stats) = ctx.accounts.user_stats.as_mut() {
+        let interest = user_stats.settle_repayment(
+            &ctx.accounts.obligation.key(),
+            &repay_reserve.key(),
+            borrowed_amount,
+            actual_repay_amount,
+        );
+        let interest_value_usd = match &oracle_price {
+            Some(oracle_price) => OracleManager::calculate_usd_value(
+                interest,
+                oracle_price,
+                repay_reserve.config.decimals,
+            )?,
+            None => repay_reserve.last_refreshed_value_usd(interest)?,
+        };
+        user_stats.record_interest_paid(interest_value_usd, clock.slot)?;
+    }
 
-    obligation.update_timestamp(clock.slot);
+    // An unpriced repayment leaves the obligation stale, so it must be refreshed before
+    // anything relies on its values
+    if oracle_price.is_some() {
+        obligation.update_timestamp(clock.slot)?;
+    } else {
+        msg!("Oracle price unavailable - repayment recorded without revaluation");
+    }
+
+    // Record the idempotency nonce, if one was supplied
+    if let Some(operation_nonce) = ctx.accounts.operation_nonce.as_mut() {
+        **operation_nonce = OperationNonce::new(
+            ctx.accounts.obligation_owner.key(),
+            ctx.accounts.obligation_owner.key(),
+            NonceOperation::Repay,
+            repay_reserve.key(),
+            actual_repay_amount,
+            clock.slot,
+        );
+    }
+
+    emit_integrator_attribution(
+        integrator_id,
+        EventType::LiquidityRepaid,
+        ctx.accounts.obligation_owner.key(),
+        repay_reserve.key(),
+        actual_repay_amount,
+        clock.slot,
+    );
 
     msg!(
         "Repaid {} liquidity tokens worth ${:.2} USD",
@@ -441,12 +884,185 @@ pub fn repay_obligation_liquidity(
         repay_value_usd.try_floor_u64()? as f64 / 1e18
     );
 
+    // Settle reward emissions on the position this operation changed
+    sync_rewards(
+        ctx.accounts.reward_pool.as_mut(),
+        ctx.accounts.obligation_rewards.as_mut(),
+        &ctx.accounts.obligation.key(),
+        &obligation,
+        clock.slot,
+    )?;
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    // Delist the obligation until a refresh finds it unhealthy again
+    dequeue_obligation(
+        ctx.accounts.liquidation_queue.as_mut(),
+        &ctx.accounts.obligation.key(),
+    );
+
+    let receipt = OperationReceipt::new(
+        repay_reserve.key(),
+        &repay_reserve,
+        actual_repay_amount,
+        0,
+        0,
+        Some(obligation.calculate_health_factor()?),
+    )?;
+
+    emit!(RepayEvent {
+        obligation: ctx.accounts.obligation.key(),
+        payer: ctx.accounts.obligation_owner.key(),
+        receipt,
+        slot: clock.slot,
+    });
+
+    Ok(receipt)
+}
+
+/// Check that depositing `collateral_value_usd` of `deposit_reserve` collateral does not
+/// over-concentrate the obligation (max 70% of its collateral value in one asset)
+pub(crate) fn validate_collateral_concentration(
+    obligation: &Obligation,
+    deposit_reserve: &Pubkey,
+    collateral_value_usd: Decimal,
+) -> Result<()> {
+    let current_collateral_for_asset = obligation
+        .deposits
+        .iter()
+        .filter(|d| d.deposit_reserve == *deposit_reserve)
+        .map(|d| d.market_value_usd.value)
+        .sum::<u128>();
+
+    let new_total_collateral_for_asset = current_collateral_for_asset
+        .checked_add(collateral_value_usd.value)
+        .ok_or(LendingError::MathOverflow)?;
+
+    let total_portfolio_value = obligation
+        .deposited_value_usd
+        .try_add(collateral_value_usd)?;
+
+    let max_single_asset_value = total_portfolio_value.try_mul(Decimal::from_scaled_val(
+        (7000u128 * PRECISION as u128)
+            .checked_div(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::DivisionByZero)?,
+    ))?;
+
+    if new_total_collateral_for_asset > max_single_asset_value.value {
+        return Err(LendingError::InvalidAmount.into()); // Too concentrated
+    }
     Ok(())
 }
 
+/// Check that the obligation's `deposit_reserve` collateral, including a new deposit
+/// worth `collateral_value_usd`, is at least the minimum position value
+pub(crate) fn validate_collateral_position_size(
+    config: &ProtocolConfig,
+    obligation: &Obligation,
+    deposit_reserve: &Pubkey,
+    collateral_value_usd: Decimal,
+) -> Result<()> {
+    let position_value_usd = obligation
+        .deposits
+        .iter()
+        .filter(|d| d.deposit_reserve == *deposit_reserve)
+        .try_fold(collateral_value_usd, |total, d| {
+            total.try_add(d.market_value_usd)
+        })?;
+
+    config.validate_position_value(position_value_usd)
+}
+
+/// Check that the obligation's `borrow_reserve` debt, including a new borrow worth
+/// `borrow_value_usd`, is at least the minimum position value
+pub(crate) fn validate_borrow_position_size(
+    config: &ProtocolConfig,
+    obligation: &Obligation,
+    borrow_reserve: &Pubkey,
+    borrow_value_usd: Decimal,
+) -> Result<()> {
+    let position_value_usd = obligation
+        .borrows
+        .iter()
+        .filter(|b| b.borrow_reserve == *borrow_reserve)
+        .try_fold(borrow_value_usd, |total, b| {
+            total.try_add(b.market_value_usd)
+        })?;
+
+    config.validate_position_value(position_value_usd)
+}
+
+/// Reserve of an isolated obligation's collateral, which tracks its debt ceiling
+/// Required while the obligation is in isolation mode, where it must match the only
+/// collateral deposit; `None` otherwise.
+pub(crate) fn isolated_collateral_reserve<'a, 'info>(
+    obligation: &Obligation,
+    reserve: Option<&'a mut Account<'info, Reserve>>,
+) -> Result<Option<&'a mut Account<'info, Reserve>>> {
+    if !obligation.isolation_mode {
+        return Ok(None);
+    }
+
+    let reserve = reserve.ok_or(LendingError::InvalidAccount)?;
+    match obligation.deposits.first() {
+        Some(deposit) if deposit.deposit_reserve == reserve.key() => Ok(Some(reserve)),
+        _ => Err(LendingError::InvalidAccount.into()),
+    }
+}
+
+/// Check that an obligation can take on `borrow_value_usd` of new debt
+/// Applies the LTV buffer and minimum post-borrow health factor shared by all borrow
+/// paths, and returns the obligation's borrowed value including the new debt.
+/// `emode` replaces per-reserve LTV and liquidation thresholds when it applies.
+pub(crate) fn validate_borrow_capacity(
+    obligation: &Obligation,
+    borrow_value_usd: Decimal,
+    emode: Option<&EModeParams>,
+) -> Result<Decimal> {
+    // Lock obligation during validation to prevent race conditions
+    let _current_health_factor = obligation.calculate_health_factor_with(emode)?;
+
+    // Simulate the new borrow to check if it would make the position unhealthy
+    let new_borrowed_value = obligation.borrowed_value_usd.try_add(borrow_value_usd)?;
+    let max_borrow_value = obligation.calculate_max_borrow_value_with(emode)?;
+
+    // Strict LTV check with buffer to prevent near-liquidation positions
+    let ltv_buffer_bps = 500; // 5% buffer below maximum LTV
+    let safe_max_borrow = max_borrow_value.try_mul(Decimal::from_scaled_val(
+        ((BASIS_POINTS_PRECISION - ltv_buffer_bps) as u128)
+            .checked_mul(PRECISION as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::DivisionByZero)?,
+    ))?;
+
+    if new_borrowed_value.value > safe_max_borrow.value {
+        return Err(LendingError::LoanToValueRatioExceedsMax.into());
+    }
+
+    // Additional health factor check after simulated borrow
+    let simulated_health_factor = obligation
+        .calculate_liquidation_threshold_value_with(emode)?
+        .try_div(new_borrowed_value)?;
+
+    // Ensure health factor stays well above 1.0 (require at least 1.1)
+    let min_health_factor = Decimal::from_scaled_val(
+        (11u128)
+            .checked_mul(PRECISION as u128 / 10)
+            .ok_or(LendingError::MathOverflow)?,
+    );
+
+    if simulated_health_factor.value < min_health_factor.value {
+        return Err(LendingError::ObligationUnhealthy.into());
+    }
+
+    Ok(new_borrowed_value)
+}
+
 // Context structs for borrowing instructions
 
 #[derive(Accounts)]
+#[instruction(obligation_index: u8)]
 pub struct InitObligation<'info> {
     /// Market account
     #[account(
@@ -460,7 +1076,11 @@ pub struct InitObligation<'info> {
         init,
         payer = payer,
         space = Obligation::SIZE,
-        seeds = [OBLIGATION_SEED, obligation_owner.key().as_ref()],
+        seeds = [
+            OBLIGATION_SEED,
+            obligation_owner.key().as_ref(),
+            Obligation::index_seed(&obligation_index)
+        ],
         bump
     )]
     pub obligation: Account<'info, Obligation>,
@@ -477,7 +1097,7 @@ pub struct InitObligation<'info> {
 }
 
 #[derive(Accounts)]
-pub struct DepositObligationCollateral<'info> {
+pub struct CloseObligation<'info> {
     /// Market account
     #[account(
         seeds = [MARKET_SEED],
@@ -485,15 +1105,50 @@ pub struct DepositObligationCollateral<'info> {
     )]
     pub market: Account<'info, Market>,
 
-    /// Obligation account
+    /// Obligation account in any layout
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Owner of the obligation, receiving the rent
+    #[account(mut)]
+    pub obligation_owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetObligationLiquidationGrace<'info> {
+    /// Market account
     #[account(
-        mut,
-        seeds = [OBLIGATION_SEED, obligation_owner.key().as_ref()],
-        bump,
-        has_one = market @ LendingError::InvalidMarketState,
-        // Owner validation will be done manually in instruction
+        seeds = [MARKET_SEED],
+        bump
     )]
-    pub obligation: Account<'info, Obligation>,
+    pub market: Account<'info, Market>,
+
+    /// Obligation account (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// eMode category of the obligation (omit if none)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Obligation owner
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DepositObligationCollateral<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation account (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
 
     /// Reserve for the collateral being deposited
     #[account(
@@ -509,13 +1164,17 @@ pub struct DepositObligationCollateral<'info> {
     /// CHECK: This account is validated by the reserve's price_oracle field
     pub price_oracle: UncheckedAccount<'info>,
 
+    /// Collateral mint (aToken mint)
+    #[account(address = deposit_reserve.collateral_mint @ LendingError::ReserveCollateralMintMismatch)]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
     /// User's source collateral token account
     #[account(
         mut,
         token::mint = deposit_reserve.collateral_mint,
         token::authority = obligation_owner
     )]
-    pub source_collateral: Account<'info, TokenAccount>,
+    pub source_collateral: InterfaceAccount<'info, TokenAccount>,
 
     /// Reserve's collateral token account
     #[account(
@@ -523,7 +1182,7 @@ pub struct DepositObligationCollateral<'info> {
         token::mint = deposit_reserve.collateral_mint,
         token::authority = collateral_supply_authority
     )]
-    pub destination_collateral: Account<'info, TokenAccount>,
+    pub destination_collateral: InterfaceAccount<'info, TokenAccount>,
 
     /// Collateral supply authority (PDA)
     /// CHECK: This is validated by the seeds constraint
@@ -536,8 +1195,32 @@ pub struct DepositObligationCollateral<'info> {
     /// Obligation owner
     pub obligation_owner: Signer<'info>,
 
+    /// User's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, obligation_owner.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
     /// Token program
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Protocol configuration (minimum position value)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Reward pool of the reserve, synced after the operation
+    #[account(mut)]
+    pub reward_pool: Option<Account<'info, RewardPool>>,
+
+    /// Obligation's participation in `reward_pool`
+    #[account(mut)]
+    pub obligation_rewards: Option<Account<'info, ObligationRewards>>,
 }
 
 #[derive(Accounts)]
@@ -549,15 +1232,10 @@ pub struct WithdrawObligationCollateral<'info> {
     )]
     pub market: Account<'info, Market>,
 
-    /// Obligation account
-    #[account(
-        mut,
-        seeds = [OBLIGATION_SEED, obligation_owner.key().as_ref()],
-        bump,
-        has_one = market @ LendingError::InvalidMarketState,
-        // Owner validation will be done manually in instruction
-    )]
-    pub obligation: Account<'info, Obligation>,
+    /// Obligation account (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
 
     /// Reserve for the collateral being withdrawn
     #[account(
@@ -573,13 +1251,20 @@ pub struct WithdrawObligationCollateral<'info> {
     /// CHECK: This account is validated by the reserve's price_oracle field
     pub price_oracle: UncheckedAccount<'info>,
 
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Collateral mint (aToken mint)
+    #[account(address = withdraw_reserve.collateral_mint @ LendingError::ReserveCollateralMintMismatch)]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
     /// Reserve's collateral token account
     #[account(
         mut,
         token::mint = withdraw_reserve.collateral_mint,
         token::authority = collateral_supply_authority
     )]
-    pub source_collateral: Account<'info, TokenAccount>,
+    pub source_collateral: InterfaceAccount<'info, TokenAccount>,
 
     /// User's destination collateral token account
     #[account(
@@ -587,7 +1272,7 @@ pub struct WithdrawObligationCollateral<'info> {
         token::mint = withdraw_reserve.collateral_mint,
         token::authority = obligation_owner
     )]
-    pub destination_collateral: Account<'info, TokenAccount>,
+    pub destination_collateral: InterfaceAccount<'info, TokenAccount>,
 
     /// Collateral supply authority (PDA)
     /// CHECK: This is validated by the seeds constraint
@@ -600,8 +1285,25 @@ pub struct WithdrawObligationCollateral<'info> {
     /// Obligation owner
     pub obligation_owner: Signer<'info>,
 
+    /// User's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, obligation_owner.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
     /// Token program
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Reward pool of the reserve, synced after the operation
+    #[account(mut)]
+    pub reward_pool: Option<Account<'info, RewardPool>>,
+
+    /// Obligation's participation in `reward_pool`
+    #[account(mut)]
+    pub obligation_rewards: Option<Account<'info, ObligationRewards>>,
 }
 
 #[derive(Accounts)]
@@ -613,15 +1315,10 @@ pub struct BorrowObligationLiquidity<'info> {
     )]
     pub market: Account<'info, Market>,
 
-    /// Obligation account
-    #[account(
-        mut,
-        seeds = [OBLIGATION_SEED, obligation_owner.key().as_ref()],
-        bump,
-        has_one = market @ LendingError::InvalidMarketState,
-        // Owner validation will be done manually in instruction
-    )]
-    pub obligation: Account<'info, Obligation>,
+    /// Obligation account (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
 
     /// Reserve for the asset being borrowed
     #[account(
@@ -629,8 +1326,7 @@ pub struct BorrowObligationLiquidity<'info> {
         seeds = [RESERVE_SEED, borrow_reserve.liquidity_mint.as_ref()],
         bump,
         has_one = market @ LendingError::InvalidMarketState,
-        has_one = price_oracle @ LendingError::OracleAccountMismatch,
-        // Liquidity supply validation will be done manually
+        has_one = price_oracle @ LendingError::OracleAccountMismatch
     )]
     pub borrow_reserve: Account<'info, Reserve>,
 
@@ -638,13 +1334,31 @@ pub struct BorrowObligationLiquidity<'info> {
     /// CHECK: This account is validated by the reserve's price_oracle field
     pub price_oracle: UncheckedAccount<'info>,
 
+    /// Secondary oracle of the borrowed asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Reserve of the obligation's isolated collateral (required in isolation mode)
+    #[account(
+        mut,
+        has_one = market @ LendingError::InvalidMarketState,
+        constraint = isolated_collateral_reserve.key() != borrow_reserve.key() @ LendingError::InvalidAccount
+    )]
+    pub isolated_collateral_reserve: Option<Account<'info, Reserve>>,
+
+    /// Liquidity token mint
+    #[account(address = borrow_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
     /// Reserve's liquidity supply token account
     #[account(
         mut,
-        token::mint = borrow_reserve.liquidity_mint,
-        token::authority = liquidity_supply_authority
+        address = borrow_reserve.liquidity_supply @ LendingError::InvalidAccount
     )]
-    pub source_liquidity: Account<'info, TokenAccount>,
+    pub source_liquidity: InterfaceAccount<'info, TokenAccount>,
 
     /// User's destination liquidity token account
     #[account(
@@ -652,7 +1366,29 @@ pub struct BorrowObligationLiquidity<'info> {
         token::mint = borrow_reserve.liquidity_mint,
         token::authority = obligation_owner
     )]
-    pub destination_liquidity: Account<'info, TokenAccount>,
+    pub destination_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Reserve's fee receiver (treasury share of the borrow fee)
+    #[account(
+        mut,
+        address = borrow_reserve.fee_receiver @ LendingError::InvalidAccount
+    )]
+    pub fee_receiver: InterfaceAccount<'info, TokenAccount>,
+
+    /// Reserve's insurance fund
+    #[account(
+        mut,
+        seeds = [INSURANCE_FUND_SEED, borrow_reserve.key().as_ref()],
+        bump = insurance_fund.bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    /// Insurance fund vault (insurance share of the borrow fee)
+    #[account(
+        mut,
+        address = insurance_fund.liquidity_vault @ LendingError::InvalidAccount
+    )]
+    pub insurance_fund_vault: InterfaceAccount<'info, TokenAccount>,
 
     /// Liquidity supply authority (PDA)
     /// CHECK: This is validated by the seeds constraint
@@ -663,10 +1399,75 @@ pub struct BorrowObligationLiquidity<'info> {
     pub liquidity_supply_authority: UncheckedAccount<'info>,
 
     /// Obligation owner
+    #[account(mut)]
     pub obligation_owner: Signer<'info>,
 
+    /// User's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, obligation_owner.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
     /// Token program
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Optional idempotency nonce (fresh keypair supplied by the client)
+    /// Creation fails if the nonce was already used, so retries cannot execute twice
+    #[account(
+        init,
+        payer = obligation_owner,
+        space = OperationNonce::SIZE
+    )]
+    pub operation_nonce: Option<Account<'info, OperationNonce>>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Protocol configuration (referral fee share, minimum position value)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Referrer credited with a share of the borrow fee
+    #[account(
+        mut,
+        seeds = [REFERRER_SEED, market.key().as_ref(), referrer.owner.as_ref()],
+        bump = referrer.bump
+    )]
+    pub referrer: Option<Account<'info, Referrer>>,
+
+    /// Reward pool of the reserve, synced after the operation
+    #[account(mut)]
+    pub reward_pool: Option<Account<'info, RewardPool>>,
+
+    /// Obligation's participation in `reward_pool`
+    #[account(mut)]
+    pub obligation_rewards: Option<Account<'info, ObligationRewards>>,
+
+    /// Protocol metrics counting the borrow, when supplied
+    #[account(
+        mut,
+        seeds = [PROTOCOL_METRICS_SEED, market.key().as_ref()],
+        bump = protocol_metrics.bump
+    )]
+    pub protocol_metrics: Option<Account<'info, ProtocolMetrics>>,
+
+    /// Borrower's statistics, updated when supplied
+    #[account(
+        mut,
+        seeds = [
+            USER_STATS_SEED,
+            market.key().as_ref(),
+            obligation_owner.key().as_ref()
+        ],
+        bump = user_stats.bump
+    )]
+    pub user_stats: Option<Account<'info, UserStats>>,
 }
 
 #[derive(Accounts)]
@@ -678,15 +1479,10 @@ pub struct RepayObligationLiquidity<'info> {
     )]
     pub market: Account<'info, Market>,
 
-    /// Obligation account
-    #[account(
-        mut,
-        seeds = [OBLIGATION_SEED, obligation_owner.key().as_ref()],
-        bump,
-        has_one = market @ LendingError::InvalidMarketState,
-        // Owner validation will be done manually in instruction
-    )]
-    pub obligation: Account<'info, Obligation>,
+    /// Obligation account (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
 
     /// Reserve for the asset being repaid
     #[account(
@@ -694,8 +1490,7 @@ pub struct RepayObligationLiquidity<'info> {
         seeds = [RESERVE_SEED, repay_reserve.liquidity_mint.as_ref()],
         bump,
         has_one = market @ LendingError::InvalidMarketState,
-        has_one = price_oracle @ LendingError::OracleAccountMismatch,
-        // Liquidity supply validation will be done manually
+        has_one = price_oracle @ LendingError::OracleAccountMismatch
     )]
     pub repay_reserve: Account<'info, Reserve>,
 
@@ -703,21 +1498,32 @@ pub struct RepayObligationLiquidity<'info> {
     /// CHECK: This account is validated by the reserve's price_oracle field
     pub price_oracle: UncheckedAccount<'info>,
 
+    /// Reserve of the obligation's isolated collateral (required in isolation mode)
+    #[account(
+        mut,
+        has_one = market @ LendingError::InvalidMarketState,
+        constraint = isolated_collateral_reserve.key() != repay_reserve.key() @ LendingError::InvalidAccount
+    )]
+    pub isolated_collateral_reserve: Option<Account<'info, Reserve>>,
+
+    /// Liquidity token mint
+    #[account(address = repay_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
     /// User's source liquidity token account
     #[account(
         mut,
         token::mint = repay_reserve.liquidity_mint,
         token::authority = obligation_owner
     )]
-    pub source_liquidity: Account<'info, TokenAccount>,
+    pub source_liquidity: InterfaceAccount<'info, TokenAccount>,
 
     /// Reserve's liquidity supply token account
     #[account(
         mut,
-        token::mint = repay_reserve.liquidity_mint,
-        token::authority = liquidity_supply_authority
+        address = repay_reserve.liquidity_supply @ LendingError::InvalidAccount
     )]
-    pub destination_liquidity: Account<'info, TokenAccount>,
+    pub destination_liquidity: InterfaceAccount<'info, TokenAccount>,
 
     /// Liquidity supply authority (PDA)
     /// CHECK: This is validated by the seeds constraint
@@ -728,8 +1534,58 @@ pub struct RepayObligationLiquidity<'info> {
     pub liquidity_supply_authority: UncheckedAccount<'info>,
 
     /// Obligation owner
+    #[account(mut)]
     pub obligation_owner: Signer<'info>,
 
+    /// User's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, obligation_owner.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
     /// Token program
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Optional idempotency nonce (fresh keypair supplied by the client)
+    /// Creation fails if the nonce was already used, so retries cannot execute twice
+    #[account(
+        init,
+        payer = obligation_owner,
+        space = OperationNonce::SIZE
+    )]
+    pub operation_nonce: Option<Account<'info, OperationNonce>>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Reward pool of the reserve, synced after the operation
+    #[account(mut)]
+    pub reward_pool: Option<Account<'info, RewardPool>>,
+
+    /// Obligation's participation in `reward_pool`
+    #[account(mut)]
+    pub obligation_rewards: Option<Account<'info, ObligationRewards>>,
+
+    /// Market's liquidation queue, from which the obligation is delisted when supplied
+    #[account(
+        mut,
+        seeds = [LIQUIDATION_QUEUE_SEED, market.key().as_ref()],
+        bump = liquidation_queue.bump
+    )]
+    pub liquidation_queue: Option<Account<'info, LiquidationQueue>>,
+
+    /// Borrower's statistics, updated when supplied
+    #[account(
+        mut,
+        seeds = [
+            USER_STATS_SEED,
+            market.key().as_ref(),
+            obligation_owner.key().as_ref()
+        ],
+        bump = user_stats.bump
+    )]
+    pub user_stats: Option<Account<'info, UserStats>>,
 }
@@ -1,4 +1,4 @@
-use crate::error::LendingError;
+use crate::constants::{ADMIN_AUDIT_LOG_SEED, GOVERNANCE_SEED, MARKET_SEED};
 use crate::state::*;
 use crate::utils::config::*;
 use crate::utils::rbac::*;
@@ -55,7 +55,13 @@ pub struct UpdateConfig<'info> {
     pub config: Account<'info, ProtocolConfig>,
 
     #[account(
-        seeds = [b"governance"],
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
         bump
     )]
     pub governance: Account<'info, GovernanceRegistry>,
@@ -63,6 +69,14 @@ pub struct UpdateConfig<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
 
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
     #[account(
         init,
         payer = authority,
@@ -80,6 +94,12 @@ pub fn update_config(
     params: ConfigUpdateParams,
     timelock_priority: TimelockPriority,
 ) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::UpdateConfig,
+        &(params.clone(), timelock_priority),
+    )?;
+
     let config = &mut ctx.accounts.config;
     let governance = &ctx.accounts.governance;
     let authority = &ctx.accounts.authority;
@@ -87,16 +107,16 @@ pub fn update_config(
 
     // Verify authority has appropriate permissions
     let required_permission = match timelock_priority {
-        TimelockPriority::Critical => "SUPER_ADMIN",
-        TimelockPriority::High => "CONFIG_MANAGER",
-        TimelockPriority::Medium => "RISK_MANAGER",
-        TimelockPriority::Low => "FEE_MANAGER",
+        TimelockPriority::Critical => Permission::SUPER_ADMIN,
+        TimelockPriority::High => Permission::GOVERNANCE_MANAGER,
+        TimelockPriority::Medium => Permission::RISK_MANAGER,
+        TimelockPriority::Low => Permission::FEE_MANAGER,
     };
-
-    require!(
-        governance.has_permission(authority.key(), required_permission)?,
-        LendingError::InsufficientPermissions
-    );
+    require_permission(
+        governance,
+        &authority.to_account_info(),
+        required_permission,
+    )?;
 
     // Create history record before updating
     let config_history = &mut ctx.accounts.config_history;
@@ -136,30 +156,50 @@ pub struct EmergencyConfigUpdate<'info> {
     pub config: Account<'info, ProtocolConfig>,
 
     #[account(
-        seeds = [b"governance"],
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
         bump
     )]
     pub governance: Account<'info, GovernanceRegistry>,
 
     #[account(mut)]
     pub emergency_authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 pub fn emergency_config_update(
     ctx: Context<EmergencyConfigUpdate>,
     emergency_params: EmergencyConfigParams,
 ) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.emergency_authority.key(),
+        AdminAction::EmergencyConfigUpdate,
+        &emergency_params,
+    )?;
+
     let config = &mut ctx.accounts.config;
     let governance = &ctx.accounts.governance;
     let authority = &ctx.accounts.emergency_authority;
     let clock = Clock::get()?;
 
-    // Verify emergency authority
-    require!(
-        governance.has_permission(authority.key(), "EMERGENCY_RESPONDER")?
-            || governance.has_permission(authority.key(), "SUPER_ADMIN")?,
-        LendingError::InsufficientPermissions
-    );
+    // Verify emergency authority (super admins hold every permission)
+    require_permission(
+        governance,
+        &authority.to_account_info(),
+        Permission::EMERGENCY_RESPONDER,
+    )?;
 
     // Apply emergency settings
     config.emergency_mode = emergency_params.emergency_mode;
@@ -167,6 +207,7 @@ pub fn emergency_config_update(
     config.pause_withdrawals = emergency_params.pause_withdrawals;
     config.pause_borrows = emergency_params.pause_borrows;
     config.pause_liquidations = emergency_params.pause_liquidations;
+    config.withdraw_utilization_cap_lifted = emergency_params.withdraw_utilization_cap_lifted;
 
     // Update timestamps
     config.update(&clock)?;
@@ -202,6 +243,8 @@ pub struct EmergencyConfigParams {
     pub pause_withdrawals: bool,
     pub pause_borrows: bool,
     pub pause_liquidations: bool,
+    /// Let redemptions push reserve utilization above `max_withdraw_utilization_bps`
+    pub withdraw_utilization_cap_lifted: bool,
 }
 
 /// Track configuration changes for audit trail
@@ -264,6 +307,7 @@ mod tests {
             pause_withdrawals: false,
             pause_borrows: true,
             pause_liquidations: false,
+            withdraw_utilization_cap_lifted: false,
         };
 
         let mut config = ProtocolConfig::default();
@@ -0,0 +1,931 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::events::{BorrowEvent, RepayEvent};
+use crate::instructions::borrowing_instructions::validate_borrow_capacity;
+use crate::state::*;
+use crate::utils::{math::Decimal, OracleManager, TokenUtils};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Delegate borrow capacity of an obligation to another wallet for a markup
+pub fn open_credit_line(ctx: Context<OpenCreditLine>, params: OpenCreditLineParams) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let clock = Clock::get()?;
+
+    // The obligation must belong to this market and the signing delegator
+    let mut obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market.key())?;
+    if obligation.owner != ctx.accounts.delegator.key() {
+        return Err(LendingError::InvalidAuthority.into());
+    }
+    obligation.link_account()?;
+    obligation.save(&ctx.accounts.obligation)?;
+
+    **ctx.accounts.credit_line = CreditLine::new(
+        market.key(),
+        ctx.accounts.obligation.key(),
+        ctx.accounts.delegator.key(),
+        ctx.accounts.delegatee.key(),
+        ctx.accounts.borrow_reserve.key(),
+        params.borrow_limit,
+        params.markup_bps,
+        clock.slot,
+        ctx.bumps.credit_line,
+    )?;
+
+    msg!(
+        "Credit line opened - delegatee: {}, reserve: {}, limit: {}, markup: {} bps",
+        ctx.accounts.delegatee.key(),
+        ctx.accounts.borrow_reserve.key(),
+        params.borrow_limit,
+        params.markup_bps
+    );
+    Ok(())
+}
+
+/// Revoke a credit line (delegator only, effective immediately)
+/// New delegated borrows are rejected; the delegatee can still repay what is owed
+pub fn revoke_credit_line(ctx: Context<RevokeCreditLine>) -> Result<()> {
+    let credit_line = &mut ctx.accounts.credit_line;
+
+    credit_line.accrue_markup(Clock::get()?.slot)?;
+    credit_line.revoked = true;
+
+    msg!(
+        "Credit line revoked - delegatee: {}, outstanding principal: {}, accrued markup: {}",
+        credit_line.delegatee,
+        credit_line.borrowed_amount,
+        credit_line.accrued_markup
+    );
+    Ok(())
+}
+
+/// Close a fully repaid credit line and reclaim its rent (delegator only)
+pub fn close_credit_line(ctx: Context<CloseCreditLine>) -> Result<()> {
+    let credit_line = &ctx.accounts.credit_line;
+
+    if !credit_line.is_settled() {
+        return Err(LendingError::CreditLineOutstanding.into());
+    }
+
+    let mut obligation = AnyObligation::load(&ctx.accounts.obligation)?;
+    obligation.unlink_account()?;
+    obligation.save(&ctx.accounts.obligation)?;
+
+    msg!(
+        "Credit line closed - delegatee: {}, total markup paid: {}",
+        credit_line.delegatee,
+        credit_line.total_markup_paid
+    );
+    Ok(())
+}
+
+/// Borrow liquidity against the delegator's obligation through a credit line
+pub fn borrow_credit_line(ctx: Context<BorrowCreditLine>, liquidity_amount: u64) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let mut obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market.key())?;
+    let credit_line = &mut ctx.accounts.credit_line;
+    let borrow_reserve = &mut ctx.accounts.borrow_reserve;
+    let clock = Clock::get()?;
+
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(&ctx.accounts.flash_loan_guard, FlashLoanUsage::BORROW)?;
+
+    // Check if market allows borrowing
+    if market.is_paused() || market.is_borrowing_disabled() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    // Check if reserve allows borrowing; a deprecated reserve takes no new debt
+    if borrow_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::BORROWING_DISABLED)
+        || borrow_reserve.is_deprecated()
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    if liquidity_amount < MIN_BORROW_AMOUNT {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    if !obligation.has_collateral() {
+        return Err(LendingError::ObligationCollateralEmpty.into());
+    }
+    obligation.require_refreshed(clock.slot)?;
+
+    // Enforce revocation and the delegated borrow limit
+    credit_line.accrue_markup(clock.slot)?;
+    credit_line.record_borrow(liquidity_amount)?;
+
+    borrow_reserve.update_interest(clock.slot)?;
+
+    if borrow_reserve.state.available_liquidity < liquidity_amount {
+        return Err(LendingError::InsufficientLiquidity.into());
+    }
+
+    let oracle_price = OracleManager::get_price(
+        &ctx.accounts.price_oracle.to_account_info(),
+        borrow_reserve,
+    )?;
+    oracle_price.validate(clock.unix_timestamp)?;
+
+    let borrow_value_usd = OracleManager::calculate_usd_value(
+        liquidity_amount,
+        &oracle_price,
+        borrow_reserve.config.decimals,
+    )?;
+
+    // The delegator's obligation must stay healthy with the delegated debt
+    let emode = EModeCategory::resolve(
+        ctx.accounts.emode_category.as_deref(),
+        &obligation,
+        Some(&borrow_reserve.key()),
+    )?;
+    let new_borrowed_value =
+        validate_borrow_capacity(&obligation, borrow_value_usd, emode.as_ref())?;
+
+    borrow_reserve.add_borrow(liquidity_amount)?;
+
+    let liquidity_borrow = ObligationLiquidity {
+        borrow_reserve: borrow_reserve.key(),
+        borrowed_amount_wads: Decimal::from_integer(liquidity_amount)?,
+        market_value_usd: borrow_value_usd,
+        origination_utilization_rate: borrow_reserve.state.utilization_rate()?,
+        origination_borrow_rate: borrow_reserve.state.borrow_rate()?,
+        origination_slot: clock.slot,
+        cumulative_borrow_rate_wads: borrow_reserve.cumulative_borrow_rate_wads(),
+    };
+    obligation.add_liquidity_borrow(liquidity_borrow.clone())?;
+
+    obligation.borrowed_value_usd = new_borrowed_value;
+    obligation.record_debt_watermark()?;
+    obligation.update_timestamp(clock.slot)?;
+
+    // Transfer liquidity from reserve to the delegatee
+    let authority_seeds = &[
+        LIQUIDITY_TOKEN_SEED,
+        borrow_reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.liquidity_supply_authority],
+    ];
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.source_liquidity,
+        &ctx.accounts.destination_liquidity,
+        &ctx.accounts.liquidity_mint,
+        &ctx.accounts.liquidity_supply_authority.to_account_info(),
+        &[authority_seeds],
+        liquidity_amount,
+    )?;
+
+    msg!(
+        "Delegatee borrowed {} liquidity tokens against {} ({} of {} used)",
+        liquidity_amount,
+        credit_line.obligation,
+        credit_line.borrowed_amount,
+        credit_line.borrow_limit
+    );
+
+    emit!(BorrowEvent {
+        obligation: ctx.accounts.obligation.key(),
+        borrower: ctx.accounts.delegatee.key(),
+        receipt: OperationReceipt::new(
+            borrow_reserve.key(),
+            borrow_reserve,
+            liquidity_amount,
+            0,
+            0,
+            Some(obligation.calculate_health_factor_with(emode.as_ref())?),
+        )?,
+        origination_utilization_rate: liquidity_borrow.origination_utilization_rate.value,
+        origination_borrow_rate: liquidity_borrow.origination_borrow_rate.value,
+        slot: clock.slot,
+    });
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    Ok(())
+}
+
+/// Repay a credit line: accrued markup goes to the delegator, the rest repays the
+/// delegated debt on the obligation
+pub fn repay_credit_line(ctx: Context<RepayCreditLine>, liquidity_amount: u64) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let mut obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market.key())?;
+    let credit_line = &mut ctx.accounts.credit_line;
+    let repay_reserve = &mut ctx.accounts.repay_reserve;
+    let clock = Clock::get()?;
+
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(&ctx.accounts.flash_loan_guard, FlashLoanUsage::REPAY)?;
+
+    // Check if market allows repayments
+    if market.is_paused() && !market.is_emergency() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    if repay_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::REPAYMENTS_DISABLED)
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    if liquidity_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    repay_reserve.update_interest(clock.slot)?;
+    obligation.accrue_borrow_interest(
+        &repay_reserve.key(),
+        repay_reserve.cumulative_borrow_rate_wads(),
+    )?;
+    credit_line.accrue_markup(clock.slot)?;
+
+    // Debt liquidated or repaid by the delegator no longer counts against the line
+    let outstanding = match obligation.find_liquidity_borrow(&repay_reserve.key()) {
+        Some(borrow) => borrow.borrowed_amount_wads.try_floor_u64()?,
+        None => 0,
+    };
+    credit_line.borrowed_amount = std::cmp::min(credit_line.borrowed_amount, outstanding);
+
+    let (markup, principal) = credit_line.record_repayment(liquidity_amount)?;
+    if markup == 0 && principal == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    if markup > 0 {
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.source_liquidity,
+            &ctx.accounts.delegator_liquidity,
+            &ctx.accounts.liquidity_mint,
+            &ctx.accounts.delegatee.to_account_info(),
+            &[],
+            markup,
+        )?;
+    }
+
+    if principal > 0 {
+        // Repayment proceeds without a usable price, leaving the debt's value unreduced
+        let oracle_price = OracleManager::get_price_if_valid(
+            &ctx.accounts.price_oracle.to_account_info(),
+            repay_reserve,
+            clock.unix_timestamp,
+        );
+
+        let repay_value_usd = match &oracle_price {
+            Some(oracle_price) => OracleManager::calculate_usd_value(
+                principal,
+                oracle_price,
+                repay_reserve.config.decimals,
+            )?,
+            None => Decimal::zero(),
+        };
+
+        // The delegatee covers the transfer fee so the reserve receives the full principal
+        let transfer_amount =
+            TokenUtils::amount_before_transfer_fee(&ctx.accounts.liquidity_mint, principal)?;
+
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.source_liquidity,
+            &ctx.accounts.destination_liquidity,
+            &ctx.accounts.liquidity_mint,
+            &ctx.accounts.delegatee.to_account_info(),
+            &[],
+            transfer_amount,
+        )?;
+
+        repay_reserve.repay_borrow(principal)?;
+        obligation
+            .repay_liquidity_borrow(&repay_reserve.key(), Decimal::from_integer(principal)?)?;
+        obligation.borrowed_value_usd = obligation.borrowed_value_usd.try_sub(repay_value_usd)?;
+        obligation.record_repayment();
+        if oracle_price.is_some() {
+            obligation.update_timestamp(clock.slot)?;
+        }
+    }
+
+    msg!(
+        "Credit line repaid - markup to delegator: {}, principal: {}, outstanding: {}",
+        markup,
+        principal,
+        credit_line.borrowed_amount
+    );
+
+    // The markup paid to the delegator is reported as the receipt's fee
+    emit!(RepayEvent {
+        obligation: ctx.accounts.obligation.key(),
+        payer: ctx.accounts.delegatee.key(),
+        receipt: OperationReceipt::new(
+            repay_reserve.key(),
+            repay_reserve,
+            principal,
+            0,
+            markup,
+            Some(obligation.calculate_health_factor()?),
+        )?,
+        slot: clock.slot,
+    });
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    Ok(())
+}
+
+/// Delegate borrowing power worth up to `borrow_limit_usd` to another wallet
+pub fn delegate_borrowing_power(
+    ctx: Context<DelegateBorrowingPower>,
+    params: DelegateBorrowingPowerParams,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+
+    // The obligation must belong to this market and the signing delegator
+    let mut obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market.key())?;
+    if obligation.owner != ctx.accounts.delegator.key() {
+        return Err(LendingError::InvalidAuthority.into());
+    }
+    obligation.link_account()?;
+    obligation.save(&ctx.accounts.obligation)?;
+
+    **ctx.accounts.credit_delegation = CreditDelegation::new(
+        market.key(),
+        ctx.accounts.obligation.key(),
+        ctx.accounts.delegator.key(),
+        ctx.accounts.delegatee.key(),
+        params.borrow_limit_usd,
+        ctx.bumps.credit_delegation,
+    )?;
+
+    msg!(
+        "Borrowing power delegated - delegatee: {}, limit: {} USD",
+        ctx.accounts.delegatee.key(),
+        params.borrow_limit_usd
+    );
+    Ok(())
+}
+
+/// Revoke delegated borrowing power and reclaim its rent (delegator only)
+/// Debt already borrowed stays on the delegator's obligation.
+pub fn revoke_borrowing_power(ctx: Context<RevokeBorrowingPower>) -> Result<()> {
+    let credit_delegation = &ctx.accounts.credit_delegation;
+
+    let mut obligation = AnyObligation::load(&ctx.accounts.obligation)?;
+    obligation.unlink_account()?;
+    obligation.save(&ctx.accounts.obligation)?;
+
+    msg!(
+        "Borrowing power revoked - delegatee: {}, value borrowed: {}",
+        credit_delegation.delegatee,
+        credit_delegation.borrowed_value_usd.try_floor_u64()?
+    );
+    Ok(())
+}
+
+/// Borrow liquidity against the delegator's obligation using delegated borrowing power
+pub fn borrow_obligation_liquidity_delegated(
+    ctx: Context<BorrowObligationLiquidityDelegated>,
+    liquidity_amount: u64,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let mut obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market.key())?;
+    let credit_delegation = &mut ctx.accounts.credit_delegation;
+    let borrow_reserve = &mut ctx.accounts.borrow_reserve;
+    let clock = Clock::get()?;
+
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(&ctx.accounts.flash_loan_guard, FlashLoanUsage::BORROW)?;
+
+    // Check if market allows borrowing
+    if market.is_paused() || market.is_borrowing_disabled() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    // Check if reserve allows borrowing; a deprecated reserve takes no new debt
+    if borrow_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::BORROWING_DISABLED)
+        || borrow_reserve.is_deprecated()
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    if liquidity_amount < MIN_BORROW_AMOUNT {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    if !obligation.has_collateral() {
+        return Err(LendingError::ObligationCollateralEmpty.into());
+    }
+    obligation.require_refreshed(clock.slot)?;
+
+    borrow_reserve.update_interest(clock.slot)?;
+
+    if borrow_reserve.state.available_liquidity < liquidity_amount {
+        return Err(LendingError::InsufficientLiquidity.into());
+    }
+
+    let oracle_price =
+        OracleManager::get_price(&ctx.accounts.price_oracle.to_account_info(), borrow_reserve)?;
+    oracle_price.validate(clock.unix_timestamp)?;
+
+    let borrow_value_usd = OracleManager::calculate_usd_value(
+        liquidity_amount,
+        &oracle_price,
+        borrow_reserve.config.decimals,
+    )?;
+
+    // Enforce the delegated USD cap
+    credit_delegation.record_borrow(borrow_value_usd)?;
+
+    // The delegator's obligation must stay healthy with the delegated debt
+    let emode = EModeCategory::resolve(
+        ctx.accounts.emode_category.as_deref(),
+        &obligation,
+        Some(&borrow_reserve.key()),
+    )?;
+    let new_borrowed_value =
+        validate_borrow_capacity(&obligation, borrow_value_usd, emode.as_ref())?;
+
+    borrow_reserve.add_borrow(liquidity_amount)?;
+
+    let liquidity_borrow = ObligationLiquidity {
+        borrow_reserve: borrow_reserve.key(),
+        borrowed_amount_wads: Decimal::from_integer(liquidity_amount)?,
+        market_value_usd: borrow_value_usd,
+        origination_utilization_rate: borrow_reserve.state.utilization_rate()?,
+        origination_borrow_rate: borrow_reserve.state.borrow_rate()?,
+        origination_slot: clock.slot,
+        cumulative_borrow_rate_wads: borrow_reserve.cumulative_borrow_rate_wads(),
+    };
+    obligation.add_liquidity_borrow(liquidity_borrow.clone())?;
+
+    obligation.borrowed_value_usd = new_borrowed_value;
+    obligation.record_debt_watermark()?;
+    obligation.update_timestamp(clock.slot)?;
+
+    // Transfer liquidity from reserve to the delegatee
+    let authority_seeds = &[
+        LIQUIDITY_TOKEN_SEED,
+        borrow_reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.liquidity_supply_authority],
+    ];
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.source_liquidity,
+        &ctx.accounts.destination_liquidity,
+        &ctx.accounts.liquidity_mint,
+        &ctx.accounts.liquidity_supply_authority.to_account_info(),
+        &[authority_seeds],
+        liquidity_amount,
+    )?;
+
+    msg!(
+        "Delegatee borrowed {} liquidity tokens against {} ({} of {} USD used)",
+        liquidity_amount,
+        credit_delegation.obligation,
+        credit_delegation.borrowed_value_usd.try_floor_u64()?,
+        credit_delegation.borrow_limit_usd
+    );
+
+    emit!(BorrowEvent {
+        obligation: ctx.accounts.obligation.key(),
+        borrower: ctx.accounts.delegatee.key(),
+        receipt: OperationReceipt::new(
+            borrow_reserve.key(),
+            borrow_reserve,
+            liquidity_amount,
+            0,
+            0,
+            Some(obligation.calculate_health_factor_with(emode.as_ref())?),
+        )?,
+        origination_utilization_rate: liquidity_borrow.origination_utilization_rate.value,
+        origination_borrow_rate: liquidity_borrow.origination_borrow_rate.value,
+        slot: clock.slot,
+    });
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    Ok(())
+}
+
+// Context structs for credit line instructions
+
+#[derive(Accounts)]
+pub struct OpenCreditLine<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Delegator's obligation (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Reserve the delegatee may borrow from
+    #[account(has_one = market @ LendingError::InvalidMarketState)]
+    pub borrow_reserve: Account<'info, Reserve>,
+
+    /// Credit line account to initialize
+    #[account(
+        init,
+        payer = delegator,
+        space = CreditLine::SIZE,
+        seeds = [
+            CREDIT_LINE_SEED,
+            obligation.key().as_ref(),
+            delegatee.key().as_ref(),
+            borrow_reserve.key().as_ref()
+        ],
+        bump
+    )]
+    pub credit_line: Account<'info, CreditLine>,
+
+    /// Wallet receiving the borrow capacity
+    /// CHECK: Any wallet may be granted a credit line
+    pub delegatee: UncheckedAccount<'info>,
+
+    /// Obligation owner granting the credit line
+    #[account(mut)]
+    pub delegator: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeCreditLine<'info> {
+    /// Credit line account
+    #[account(
+        mut,
+        has_one = delegator @ LendingError::InvalidAuthority
+    )]
+    pub credit_line: Account<'info, CreditLine>,
+
+    /// Obligation owner that granted the credit line
+    pub delegator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseCreditLine<'info> {
+    /// Credit line account to close
+    #[account(
+        mut,
+        close = delegator,
+        has_one = delegator @ LendingError::InvalidAuthority
+    )]
+    pub credit_line: Account<'info, CreditLine>,
+
+    /// Delegator's obligation (any layout)
+    /// CHECK: Loaded through AnyObligation; must be the credit line's obligation
+    #[account(
+        mut,
+        address = credit_line.obligation @ LendingError::InvalidAccount
+    )]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Obligation owner that granted the credit line (receives the rent)
+    #[account(mut)]
+    pub delegator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BorrowCreditLine<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Delegator's obligation (any layout)
+    /// CHECK: Loaded through AnyObligation; must be the credit line's obligation
+    #[account(
+        mut,
+        address = credit_line.obligation @ LendingError::InvalidAccount
+    )]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Credit line the borrow is drawn from
+    #[account(
+        mut,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = borrow_reserve @ LendingError::InvalidAccount,
+        has_one = delegatee @ LendingError::InvalidAuthority
+    )]
+    pub credit_line: Account<'info, CreditLine>,
+
+    /// Reserve for the asset being borrowed
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, borrow_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = price_oracle @ LendingError::OracleAccountMismatch
+    )]
+    pub borrow_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for the borrowed asset
+    /// CHECK: This account is validated by the reserve's price_oracle field
+    pub price_oracle: UncheckedAccount<'info>,
+
+    /// eMode category the delegator's obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Liquidity token mint
+    #[account(address = borrow_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = borrow_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub source_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Delegatee's destination liquidity token account
+    #[account(
+        mut,
+        token::mint = borrow_reserve.liquidity_mint,
+        token::authority = delegatee
+    )]
+    pub destination_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, borrow_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Delegatee borrowing through the credit line
+    pub delegatee: Signer<'info>,
+
+    /// Delegatee's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, delegatee.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RepayCreditLine<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Delegator's obligation (any layout)
+    /// CHECK: Loaded through AnyObligation; must be the credit line's obligation
+    #[account(
+        mut,
+        address = credit_line.obligation @ LendingError::InvalidAccount
+    )]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Credit line being repaid
+    #[account(
+        mut,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = delegatee @ LendingError::InvalidAuthority,
+        constraint = credit_line.borrow_reserve == repay_reserve.key() @ LendingError::InvalidAccount
+    )]
+    pub credit_line: Account<'info, CreditLine>,
+
+    /// Reserve for the asset being repaid
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, repay_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = price_oracle @ LendingError::OracleAccountMismatch
+    )]
+    pub repay_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for the repaid asset
+    /// CHECK: This account is validated by the reserve's price_oracle field
+    pub price_oracle: UncheckedAccount<'info>,
+
+    /// Liquidity token mint
+    #[account(address = repay_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Delegatee's source liquidity token account
+    #[account(
+        mut,
+        token::mint = repay_reserve.liquidity_mint,
+        token::authority = delegatee
+    )]
+    pub source_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = repay_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub destination_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Delegator's token account receiving the markup
+    #[account(
+        mut,
+        token::mint = repay_reserve.liquidity_mint,
+        token::authority = credit_line.delegator
+    )]
+    pub delegator_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, repay_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Delegatee repaying the credit line
+    pub delegatee: Signer<'info>,
+
+    /// Delegatee's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, delegatee.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateBorrowingPower<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Delegator's obligation (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Credit delegation account to initialize
+    #[account(
+        init,
+        payer = delegator,
+        space = CreditDelegation::SIZE,
+        seeds = [
+            CREDIT_DELEGATION_SEED,
+            obligation.key().as_ref(),
+            delegatee.key().as_ref()
+        ],
+        bump
+    )]
+    pub credit_delegation: Account<'info, CreditDelegation>,
+
+    /// Wallet receiving the borrowing power
+    /// CHECK: Any wallet may be delegated borrowing power
+    pub delegatee: UncheckedAccount<'info>,
+
+    /// Obligation owner delegating the borrowing power
+    #[account(mut)]
+    pub delegator: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeBorrowingPower<'info> {
+    /// Credit delegation account to close
+    #[account(
+        mut,
+        close = delegator,
+        has_one = delegator @ LendingError::InvalidAuthority
+    )]
+    pub credit_delegation: Account<'info, CreditDelegation>,
+
+    /// Delegator's obligation (any layout)
+    /// CHECK: Loaded through AnyObligation; must be the delegation's obligation
+    #[account(
+        mut,
+        address = credit_delegation.obligation @ LendingError::InvalidAccount
+    )]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Obligation owner that delegated the borrowing power (receives the rent)
+    #[account(mut)]
+    pub delegator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BorrowObligationLiquidityDelegated<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Delegator's obligation (any layout)
+    /// CHECK: Loaded through AnyObligation; must be the delegation's obligation
+    #[account(
+        mut,
+        address = credit_delegation.obligation @ LendingError::InvalidAccount
+    )]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Delegation the borrow is drawn from
+    #[account(
+        mut,
+        seeds = [
+            CREDIT_DELEGATION_SEED,
+            credit_delegation.obligation.as_ref(),
+            delegatee.key().as_ref()
+        ],
+        bump = credit_delegation.bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = delegatee @ LendingError::InvalidAuthority
+    )]
+    pub credit_delegation: Account<'info, CreditDelegation>,
+
+    /// Reserve for the asset being borrowed
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, borrow_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = price_oracle @ LendingError::OracleAccountMismatch
+    )]
+    pub borrow_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for the borrowed asset
+    /// CHECK: This account is validated by the reserve's price_oracle field
+    pub price_oracle: UncheckedAccount<'info>,
+
+    /// eMode category the delegator's obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Liquidity token mint
+    #[account(address = borrow_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = borrow_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub source_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Delegatee's destination liquidity token account
+    #[account(
+        mut,
+        token::mint = borrow_reserve.liquidity_mint,
+        token::authority = delegatee
+    )]
+    pub destination_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, borrow_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Delegatee borrowing with the delegated power
+    pub delegatee: Signer<'info>,
+
+    /// Delegatee's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, delegatee.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,373 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::instructions::liquidation_instructions::{
+    calculate_liquidation_collateral_amount, revalue_liquidation_positions,
+};
+use crate::state::*;
+use crate::utils::{math::Decimal, require_admin, OracleManager, ReserveLock, TokenUtils};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Start or cancel the wind-down of a reserve (governance only)
+/// From now until the deadline the reserve's LTV walks down to zero and it takes no new
+/// borrows. Once the deadline passes, keepers may deleverage positions in the reserve.
+pub fn deprecate_reserve(
+    ctx: Context<DeprecateReserve>,
+    params: DeprecateReserveParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::DeprecateReserve,
+        &(ctx.accounts.reserve.key(), params.clone()),
+    )?;
+
+    let market = &ctx.accounts.market;
+    let reserve = &mut ctx.accounts.reserve;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RESERVE_MANAGER,
+    )?;
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    reserve.set_deprecation(
+        now,
+        params.deadline_timestamp,
+        params.deleverage_penalty_bps,
+    )?;
+    reserve.last_update_timestamp = now;
+
+    if reserve.is_deprecated() {
+        msg!(
+            "Reserve {} deprecated - deadline: {}, deleverage penalty: {} bps",
+            reserve.key(),
+            params.deadline_timestamp,
+            params.deleverage_penalty_bps
+        );
+    } else {
+        msg!("Reserve {} deprecation cancelled", reserve.key());
+    }
+    Ok(())
+}
+
+/// Repay an obligation's debt with its own collateral once a reserve is past its deprecation deadline
+/// Permissionless. Either the repaid or the seized reserve must be past its deadline;
+/// the keeper repays up to the whole borrow and receives collateral worth the repayment
+/// plus the deprecated reserve's deleverage penalty. The obligation need not be unhealthy.
+pub fn deleverage_obligation(
+    ctx: Context<DeleverageObligation>,
+    liquidity_amount: u64,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let mut obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market.key())?;
+    let repay_reserve = &mut ctx.accounts.repay_reserve;
+    let withdraw_reserve = &mut ctx.accounts.withdraw_reserve;
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp as u64;
+
+    if market.is_paused() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    if liquidity_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    // The lower penalty applies when both reserves are being wound down
+    let penalty_bps = match (
+        withdraw_reserve.is_past_deprecation_deadline(now),
+        repay_reserve.is_past_deprecation_deadline(now),
+    ) {
+        (true, true) => withdraw_reserve
+            .deleverage_penalty_bps
+            .min(repay_reserve.deleverage_penalty_bps),
+        (true, false) => withdraw_reserve.deleverage_penalty_bps,
+        (false, true) => repay_reserve.deleverage_penalty_bps,
+        (false, false) => return Err(LendingError::ReserveNotDeprecated.into()),
+    };
+
+    // Hold the reentrancy locks until the reserve state is written back
+    let mut repay_reserve = ReserveLock::acquire(repay_reserve)?;
+    let mut withdraw_reserve = ReserveLock::acquire(withdraw_reserve)?;
+
+    let result = (|| -> Result<u64> {
+        repay_reserve.update_interest(clock.slot)?;
+        withdraw_reserve.update_interest(clock.slot)?;
+        obligation.accrue_borrow_interest(
+            &repay_reserve.key(),
+            repay_reserve.cumulative_borrow_rate_wads(),
+        )?;
+
+        // No close factor applies, but the repayment cannot exceed the debt
+        let borrowed_amount = obligation
+            .find_liquidity_borrow(&repay_reserve.key())
+            .ok_or(LendingError::ObligationReserveNotFound)?
+            .borrowed_amount_wads
+            .try_floor_u64()?;
+        if liquidity_amount > borrowed_amount {
+            return Err(LendingError::AmountTooLarge.into());
+        }
+
+        let repay_price = OracleManager::get_checked_price(
+            &ctx.accounts.repay_price_oracle.to_account_info(),
+            ctx.accounts.repay_secondary_price_oracle.as_deref(),
+            &repay_reserve,
+        )?;
+        repay_price.validate(clock.unix_timestamp)?;
+
+        let withdraw_price = OracleManager::get_checked_price(
+            &ctx.accounts.withdraw_price_oracle.to_account_info(),
+            ctx.accounts.withdraw_secondary_price_oracle.as_deref(),
+            &withdraw_reserve,
+        )?;
+        withdraw_price.validate(clock.unix_timestamp)?;
+
+        // Value both positions at TWAP-smoothed prices in the borrower's favour
+        let repay_price = repay_reserve.liquidation_debt_price(&repay_price);
+        let withdraw_price = withdraw_reserve.liquidation_collateral_price(&withdraw_price);
+        revalue_liquidation_positions(
+            &mut obligation,
+            &repay_reserve,
+            &withdraw_reserve,
+            &repay_price,
+            &withdraw_price,
+        )?;
+
+        let repay_value_usd = OracleManager::calculate_usd_value(
+            liquidity_amount,
+            &repay_price,
+            repay_reserve.config.decimals,
+        )?;
+        let seized_liquidity = calculate_liquidation_collateral_amount(
+            repay_value_usd,
+            &withdraw_price,
+            penalty_bps as u64,
+        )?;
+        let collateral_amount = withdraw_reserve.liquidity_to_collateral(seized_liquidity)?;
+
+        let deposited_collateral = obligation
+            .find_collateral_deposit(&withdraw_reserve.key())
+            .ok_or(LendingError::ObligationReserveNotFound)?
+            .deposited_amount;
+        if deposited_collateral < collateral_amount {
+            return Err(LendingError::InsufficientCollateral.into());
+        }
+
+        // Grossed up so that the reserve receives the full repayment after transfer fees
+        let transfer_amount = TokenUtils::amount_before_transfer_fee(
+            &ctx.accounts.repay_liquidity_mint,
+            liquidity_amount,
+        )?;
+
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.source_liquidity,
+            &ctx.accounts.repay_reserve_liquidity_supply,
+            &ctx.accounts.repay_liquidity_mint,
+            &ctx.accounts.keeper.to_account_info(),
+            &[],
+            transfer_amount,
+        )?;
+
+        let collateral_authority_seeds = &[
+            COLLATERAL_TOKEN_SEED,
+            withdraw_reserve.liquidity_mint.as_ref(),
+            b"authority",
+            &[ctx.bumps.withdraw_collateral_supply_authority],
+        ];
+
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.collateral_token_program,
+            &ctx.accounts.withdraw_reserve_collateral_supply,
+            &ctx.accounts.destination_collateral,
+            &ctx.accounts.withdraw_collateral_mint,
+            &ctx.accounts
+                .withdraw_collateral_supply_authority
+                .to_account_info(),
+            &[collateral_authority_seeds],
+            collateral_amount,
+        )?;
+
+        // Update reserve and obligation accounting. Unlike a liquidation, the unwind is
+        // not recorded in the borrower's credit history.
+        repay_reserve.repay_borrow(liquidity_amount)?;
+        obligation.repay_liquidity_borrow(
+            &repay_reserve.key(),
+            Decimal::from_integer(liquidity_amount)?,
+        )?;
+        obligation.remove_collateral_deposit(&withdraw_reserve.key(), collateral_amount)?;
+
+        obligation.borrowed_value_usd = obligation.borrowed_value_usd.try_sub(repay_value_usd)?;
+        let collateral_value_usd =
+            withdraw_reserve.collateral_value_usd(collateral_amount, &withdraw_price)?;
+        obligation.deposited_value_usd = obligation
+            .deposited_value_usd
+            .try_sub(collateral_value_usd)?;
+        obligation.update_timestamp(clock.slot)?;
+
+        Ok(collateral_amount)
+    })();
+
+    let collateral_amount = result?;
+
+    msg!(
+        "Deleveraged obligation {} by keeper {} - repaid: {}, seized: {}, penalty: {} bps",
+        ctx.accounts.obligation.key(),
+        ctx.accounts.keeper.key(),
+        liquidity_amount,
+        collateral_amount,
+        penalty_bps
+    );
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    Ok(())
+}
+
+// Context structs for deleverage instructions
+
+#[derive(Accounts)]
+pub struct DeprecateReserve<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve account to deprecate
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct DeleverageObligation<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation being unwound (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Reserve for the asset being repaid
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, repay_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub repay_reserve: Account<'info, Reserve>,
+
+    /// Reserve for the collateral being withdrawn
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, withdraw_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub withdraw_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for repay asset
+    /// CHECK: Matched against the repay reserve's price_oracle
+    #[account(address = repay_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub repay_price_oracle: UncheckedAccount<'info>,
+
+    /// Price oracle for withdraw asset
+    /// CHECK: Matched against the withdraw reserve's price_oracle
+    #[account(address = withdraw_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub withdraw_price_oracle: UncheckedAccount<'info>,
+
+    /// Secondary oracle of the repay asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub repay_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Secondary oracle of the withdraw asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub withdraw_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Liquidity token mint of the repay reserve
+    #[account(address = repay_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub repay_liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Collateral mint (aToken mint) of the withdraw reserve
+    #[account(address = withdraw_reserve.collateral_mint @ LendingError::ReserveCollateralMintMismatch)]
+    pub withdraw_collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Keeper's source liquidity token account (for repayment)
+    #[account(
+        mut,
+        token::mint = repay_reserve.liquidity_mint,
+        token::authority = keeper
+    )]
+    pub source_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Keeper's destination collateral token account (receives seized collateral)
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.collateral_mint,
+        token::authority = keeper
+    )]
+    pub destination_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Repay reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = repay_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub repay_reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Withdraw reserve's collateral supply token account
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.collateral_mint,
+        token::authority = withdraw_collateral_supply_authority
+    )]
+    pub withdraw_reserve_collateral_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Withdraw collateral supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [COLLATERAL_TOKEN_SEED, withdraw_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub withdraw_collateral_supply_authority: UncheckedAccount<'info>,
+
+    /// Keeper performing the unwind
+    pub keeper: Signer<'info>,
+
+    /// Token program of the repay reserve's liquidity mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the withdraw reserve's collateral mint
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+}
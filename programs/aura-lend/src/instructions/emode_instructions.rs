@@ -0,0 +1,220 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::*;
+use crate::utils::require_admin;
+use anchor_lang::prelude::*;
+
+/// Define a new eMode category of correlated assets (governance only)
+pub fn initialize_emode_category(
+    ctx: Context<InitializeEModeCategory>,
+    id: u8,
+    params: EModeCategoryParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::InitializeEModeCategory,
+        &(id, params.clone()),
+    )?;
+
+    let market = &ctx.accounts.market;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RISK_MANAGER,
+    )?;
+
+    **ctx.accounts.emode_category =
+        EModeCategory::new(market.key(), id, params, ctx.bumps.emode_category)?;
+
+    let category = &ctx.accounts.emode_category;
+    msg!(
+        "eMode category {} created: LTV {} bps, threshold {} bps, {} reserves",
+        id,
+        category.ltv_bps,
+        category.liquidation_threshold_bps,
+        category.reserves.len()
+    );
+    Ok(())
+}
+
+/// Update the parameters or reserves of an eMode category (governance only)
+/// Obligations holding a reserve removed from the category fall back to per-reserve
+/// parameters on their next health check.
+pub fn update_emode_category(
+    ctx: Context<UpdateEModeCategory>,
+    params: EModeCategoryParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::UpdateEModeCategory,
+        &(ctx.accounts.emode_category.id, params.clone()),
+    )?;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RISK_MANAGER,
+    )?;
+
+    let category = &mut ctx.accounts.emode_category;
+    category.configure(params)?;
+
+    msg!(
+        "eMode category {} updated: LTV {} bps, threshold {} bps, {} reserves",
+        category.id,
+        category.ltv_bps,
+        category.liquidation_threshold_bps,
+        category.reserves.len()
+    );
+    Ok(())
+}
+
+/// Opt an obligation into an eMode category, or out of eMode with category 0
+/// Entering requires every current position to be in the category, and the obligation
+/// must remain healthy under the parameters it switches to.
+pub fn set_obligation_emode(ctx: Context<SetObligationEMode>, category_id: u8) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let mut obligation = AnyObligation::load_owned(
+        &ctx.accounts.obligation,
+        &market.key(),
+        &ctx.accounts.owner.key(),
+    )?;
+    let category = ctx.accounts.emode_category.as_deref();
+    let clock = Clock::get()?;
+
+    if obligation.has_borrows() && obligation.is_stale(clock.slot) {
+        return Err(LendingError::ObligationStale.into());
+    }
+
+    if category_id != EMODE_NONE {
+        let category = category.ok_or(LendingError::EModeCategoryMismatch)?;
+        if !category.covers(&obligation, None) {
+            return Err(LendingError::EModeIncompatiblePositions.into());
+        }
+    }
+
+    obligation.emode_category = category_id;
+    let emode = EModeCategory::resolve(category, &obligation, None)?;
+    if obligation.has_borrows() && !obligation.is_healthy_with(emode.as_ref())? {
+        return Err(LendingError::ObligationUnhealthy.into());
+    }
+
+    msg!(
+        "Obligation {} set to eMode category {}",
+        ctx.accounts.obligation.key(),
+        category_id
+    );
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    Ok(())
+}
+
+// Context structs for eMode instructions
+
+#[derive(Accounts)]
+#[instruction(id: u8)]
+pub struct InitializeEModeCategory<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// eMode category to initialize
+    #[account(
+        init,
+        payer = payer,
+        space = EModeCategory::SIZE,
+        seeds = [EMODE_CATEGORY_SEED, market.key().as_ref(), &[id]],
+        bump
+    )]
+    pub emode_category: Account<'info, EModeCategory>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Payer for account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateEModeCategory<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// eMode category to update
+    #[account(
+        mut,
+        seeds = [EMODE_CATEGORY_SEED, market.key().as_ref(), &[emode_category.id]],
+        bump = emode_category.bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub emode_category: Account<'info, EModeCategory>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct SetObligationEMode<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation account (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Category being entered (omit to leave eMode)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Obligation owner
+    pub owner: Signer<'info>,
+}
@@ -0,0 +1,607 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::*;
+use crate::utils::{require_admin, OracleManager, ProtocolConfig, SwapExecutor, TokenUtils};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Rotate the treasury fee receiver of a reserve (governance only)
+pub fn update_reserve_fee_receiver(ctx: Context<UpdateReserveFeeReceiver>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::UpdateReserveFeeReceiver,
+        &(ctx.accounts.reserve.key(), ctx.accounts.new_fee_receiver.key()),
+    )?;
+
+    let market = &ctx.accounts.market;
+    let reserve = &mut ctx.accounts.reserve;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::FEE_MANAGER,
+    )?;
+
+    let old_fee_receiver = reserve.fee_receiver;
+    reserve.fee_receiver = ctx.accounts.new_fee_receiver.key();
+    reserve.last_update_timestamp = Clock::get()?.unix_timestamp as u64;
+
+    msg!(
+        "Reserve fee receiver rotated - old: {}, new: {}",
+        old_fee_receiver,
+        reserve.fee_receiver
+    );
+    Ok(())
+}
+
+/// Update how collected protocol fees are split across recipients (governance only)
+pub fn update_reserve_fee_split(
+    ctx: Context<UpdateReserveFeeSplit>,
+    params: UpdateReserveFeeSplitParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::UpdateReserveFeeSplit,
+        &(ctx.accounts.reserve.key(), params.clone()),
+    )?;
+
+    let market = &ctx.accounts.market;
+    let reserve = &mut ctx.accounts.reserve;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::FEE_MANAGER,
+    )?;
+
+    params.fee_split.validate()?;
+    reserve.fee_split = params.fee_split;
+    reserve.last_update_timestamp = Clock::get()?.unix_timestamp as u64;
+
+    msg!(
+        "Reserve fee split updated - insurance: {} bps, stakers: {} bps, grants: {} bps",
+        reserve.fee_split.insurance.weight_bps,
+        reserve.fee_split.stakers.weight_bps,
+        reserve.fee_split.grants.weight_bps
+    );
+    Ok(())
+}
+
+/// Transfer accumulated protocol fees from the reserve supply to the fee recipients
+/// Permissionless: destinations are fixed by the reserve's fee receiver and fee split
+pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+    let clock = Clock::get()?;
+    let market = &mut ctx.accounts.market;
+    let reserve = &mut ctx.accounts.reserve;
+
+    // Accrue up to the current slot so the collected amount is complete
+    reserve.update_interest(clock.slot)?;
+
+    let amount = reserve.take_protocol_fees()?;
+    if amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    let (treasury_share, shares) = reserve.fee_split.allocate(amount)?;
+    let split_receivers = [
+        &ctx.accounts.insurance_fee_receiver,
+        &ctx.accounts.stakers_fee_receiver,
+        &ctx.accounts.grants_fee_receiver,
+    ];
+
+    let authority_seeds = &[
+        LIQUIDITY_TOKEN_SEED,
+        reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.liquidity_supply_authority],
+    ];
+
+    for ((recipient, receiver), share) in reserve
+        .fee_split
+        .recipients()
+        .into_iter()
+        .zip(split_receivers)
+        .zip(shares)
+    {
+        if share == 0 {
+            continue;
+        }
+
+        let receiver = receiver.as_ref().ok_or(LendingError::InvalidAccount)?;
+        if receiver.key() != recipient.receiver {
+            return Err(LendingError::InvalidAccount.into());
+        }
+
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.liquidity_supply,
+            receiver,
+            &ctx.accounts.liquidity_mint,
+            &ctx.accounts.liquidity_supply_authority.to_account_info(),
+            &[authority_seeds],
+            share,
+        )?;
+    }
+
+    if treasury_share > 0 {
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.liquidity_supply,
+            &ctx.accounts.fee_receiver,
+            &ctx.accounts.liquidity_mint,
+            &ctx.accounts.liquidity_supply_authority.to_account_info(),
+            &[authority_seeds],
+            treasury_share,
+        )?;
+    }
+
+    market.add_fees(amount)?;
+
+    msg!(
+        "Protocol fees collected - total: {}, treasury: {}, insurance: {}, stakers: {}, grants: {}",
+        amount,
+        treasury_share,
+        shares[0],
+        shares[1],
+        shares[2]
+    );
+    Ok(())
+}
+
+/// Initialize the converter that swaps collected fees into the insurance fund asset
+pub fn initialize_fee_converter(
+    ctx: Context<InitializeFeeConverter>,
+    params: InitializeFeeConverterParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::InitializeFeeConverter,
+        &params,
+    )?;
+
+    let market = &ctx.accounts.market;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::FEE_MANAGER,
+    )?;
+
+    **ctx.accounts.fee_converter = FeeConverter::new(
+        market.key(),
+        ctx.accounts.target_mint.key(),
+        ctx.accounts.destination.key(),
+        params.max_slippage_bps,
+        params.swap_programs,
+        ctx.bumps.fee_converter,
+    )?;
+
+    msg!(
+        "Fee converter initialized - target mint: {}, max slippage: {} bps",
+        ctx.accounts.target_mint.key(),
+        params.max_slippage_bps
+    );
+    Ok(())
+}
+
+/// Update fee converter parameters (governance only)
+pub fn update_fee_converter(
+    ctx: Context<UpdateFeeConverter>,
+    params: UpdateFeeConverterParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::UpdateFeeConverter,
+        &params,
+    )?;
+
+    let market = &ctx.accounts.market;
+    let fee_converter = &mut ctx.accounts.fee_converter;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::FEE_MANAGER,
+    )?;
+
+    if let Some(max_slippage_bps) = params.max_slippage_bps {
+        FeeConverter::validate_max_slippage_bps(max_slippage_bps)?;
+        fee_converter.max_slippage_bps = max_slippage_bps;
+    }
+
+    if let Some(swap_programs) = params.swap_programs {
+        FeeConverter::validate_swap_programs(&swap_programs)?;
+        fee_converter.swap_programs = swap_programs;
+    }
+
+    msg!(
+        "Fee converter updated - max slippage: {} bps, swap programs: {}",
+        fee_converter.max_slippage_bps,
+        fee_converter.swap_programs.len()
+    );
+    Ok(())
+}
+
+/// Swap fees held in a converter fee vault into the insurance fund asset (keeper)
+/// The route accounts are passed as remaining_accounts and forwarded to the DEX with
+/// `route_data`. The output must meet both the keeper's `min_amount_out` and the
+/// oracle-derived bound.
+pub fn convert_fees<'info>(
+    ctx: Context<'_, '_, '_, 'info, ConvertFees<'info>>,
+    amount_in: u64,
+    min_amount_out: u64,
+    route_data: Vec<u8>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let fee_converter = &ctx.accounts.fee_converter;
+    let source_reserve = &ctx.accounts.source_reserve;
+    let target_reserve = &ctx.accounts.target_reserve;
+
+    ctx.accounts.config.validate_account_count(ctx.remaining_accounts.len())?;
+    SwapExecutor::validate_program(
+        &ctx.accounts.swap_program.key(),
+        &fee_converter.swap_programs,
+    )?;
+
+    if amount_in == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+    if amount_in > ctx.accounts.fee_vault.amount {
+        return Err(LendingError::InsufficientTokenBalance.into());
+    }
+
+    // Bound the output by oracle prices of both assets
+    let source_price = OracleManager::get_price(
+        &ctx.accounts.source_oracle.to_account_info(),
+        source_reserve,
+    )?;
+    source_price.validate(clock.unix_timestamp)?;
+
+    let target_price = OracleManager::get_price(
+        &ctx.accounts.target_oracle.to_account_info(),
+        target_reserve,
+    )?;
+    target_price.validate(clock.unix_timestamp)?;
+
+    let oracle_min_amount_out = SwapExecutor::oracle_min_amount_out(
+        amount_in,
+        &source_price,
+        source_reserve.config.decimals,
+        &target_price,
+        target_reserve.config.decimals,
+        fee_converter.max_slippage_bps,
+    )?;
+    let min_amount_out = std::cmp::max(min_amount_out, oracle_min_amount_out);
+
+    let market_key = ctx.accounts.market.key();
+    let converter_key = fee_converter.key();
+    let converter_seeds = &[
+        FEE_CONVERTER_SEED,
+        market_key.as_ref(),
+        &[fee_converter.bump],
+    ];
+
+    let result = SwapExecutor::execute(
+        &ctx.accounts.swap_program.to_account_info(),
+        ctx.remaining_accounts,
+        route_data,
+        Some(&converter_key),
+        &[converter_seeds],
+        &mut ctx.accounts.fee_vault,
+        &mut ctx.accounts.destination,
+        amount_in,
+        min_amount_out,
+    )?;
+
+    ctx.accounts.fee_converter.record_conversion(result.amount_out, clock.slot)?;
+
+    msg!(
+        "Converted {} fees of mint {} into {} insurance fund tokens (minimum {})",
+        result.amount_in,
+        ctx.accounts.source_reserve.liquidity_mint,
+        result.amount_out,
+        min_amount_out
+    );
+    Ok(())
+}
+
+// Context structs for protocol fee instructions
+
+#[derive(Accounts)]
+pub struct UpdateReserveFeeReceiver<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve account to update
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// New treasury fee receiver token account
+    #[account(token::mint = reserve.liquidity_mint)]
+    pub new_fee_receiver: InterfaceAccount<'info, TokenAccount>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateReserveFeeSplit<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve account to update
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    /// Market account
+    #[account(
+        mut,
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve account
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = liquidity_supply @ LendingError::InvalidAccount,
+        has_one = fee_receiver @ LendingError::InvalidAccount
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Liquidity token mint
+    #[account(address = reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Reserve liquidity supply token account
+    #[account(mut)]
+    pub liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Treasury fee receiver token account
+    #[account(mut)]
+    pub fee_receiver: InterfaceAccount<'info, TokenAccount>,
+
+    /// Insurance fee receiver (required when its weight is non-zero)
+    #[account(mut, token::mint = reserve.liquidity_mint)]
+    pub insurance_fee_receiver: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Staker rewards fee receiver (required when its weight is non-zero)
+    #[account(mut, token::mint = reserve.liquidity_mint)]
+    pub stakers_fee_receiver: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Grants fee receiver (required when its weight is non-zero)
+    #[account(mut, token::mint = reserve.liquidity_mint)]
+    pub grants_fee_receiver: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeFeeConverter<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Fee converter account to initialize
+    #[account(
+        init,
+        payer = payer,
+        space = FeeConverter::SIZE,
+        seeds = [FEE_CONVERTER_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub fee_converter: Account<'info, FeeConverter>,
+
+    /// Mint the insurance fund is denominated in
+    pub target_mint: InterfaceAccount<'info, Mint>,
+
+    /// Insurance fund token account receiving converted fees
+    #[account(token::mint = target_mint)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Payer for account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeeConverter<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Fee converter account
+    #[account(
+        mut,
+        seeds = [FEE_CONVERTER_SEED, market.key().as_ref()],
+        bump = fee_converter.bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub fee_converter: Account<'info, FeeConverter>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct ConvertFees<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol configuration (remaining account cap)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Fee converter account
+    #[account(
+        mut,
+        seeds = [FEE_CONVERTER_SEED, market.key().as_ref()],
+        bump = fee_converter.bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = destination @ LendingError::InvalidAccount
+    )]
+    pub fee_converter: Account<'info, FeeConverter>,
+
+    /// Reserve of the fee asset being converted (price and decimals)
+    #[account(
+        has_one = market @ LendingError::InvalidMarketState,
+        constraint = source_reserve.liquidity_mint != fee_converter.target_mint @ LendingError::TokenMintMismatch
+    )]
+    pub source_reserve: Account<'info, Reserve>,
+
+    /// Price oracle of the fee asset
+    /// CHECK: Validated against the source reserve's price_oracle field
+    #[account(address = source_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub source_oracle: UncheckedAccount<'info>,
+
+    /// Reserve of the insurance fund asset (price and decimals)
+    #[account(
+        has_one = market @ LendingError::InvalidMarketState,
+        constraint = target_reserve.liquidity_mint == fee_converter.target_mint @ LendingError::TokenMintMismatch
+    )]
+    pub target_reserve: Account<'info, Reserve>,
+
+    /// Price oracle of the insurance fund asset
+    /// CHECK: Validated against the target reserve's price_oracle field
+    #[account(address = target_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub target_oracle: UncheckedAccount<'info>,
+
+    /// Converter fee vault holding the fees to swap
+    #[account(
+        mut,
+        token::mint = source_reserve.liquidity_mint,
+        token::authority = fee_converter
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Insurance fund token account receiving the output
+    #[account(mut)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// Whitelisted DEX program executing the route
+    /// CHECK: Validated against the fee converter's swap program whitelist
+    pub swap_program: UncheckedAccount<'info>,
+
+    /// Keeper executing the conversion
+    pub keeper: Signer<'info>,
+    // Note: DEX route accounts are passed as remaining_accounts
+}
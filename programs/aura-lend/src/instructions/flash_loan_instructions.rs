@@ -0,0 +1,251 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::events::FlashLoanEvent;
+use crate::state::*;
+use crate::utils::{ProtocolConfig, ReserveLock, TokenUtils};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Lend reserve liquidity for the duration of a borrower-provided callback
+/// Transfers `amount` to the borrower's destination account, invokes `callback_program`
+/// with `callback_data` and the remaining accounts, then requires the reserve supply to
+/// hold the loan plus fee. The callback repays by transferring into the supply itself.
+/// The callback is invoked without any program signer, and the borrower's flash loan
+/// guard stays open throughout so borrowed funds cannot be routed back into the program.
+/// The loan must be a top-level instruction, so the callback cannot nest another one
+/// under a different signer.
+pub fn flash_loan<'info>(
+    ctx: Context<'_, '_, '_, 'info, FlashLoan<'info>>,
+    amount: u64,
+    callback_data: Vec<u8>,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let reserve = &mut ctx.accounts.reserve;
+    let callback_program = &ctx.accounts.callback_program;
+    let clock = Clock::get()?;
+
+    ctx.accounts
+        .config
+        .validate_account_count(ctx.remaining_accounts.len())?;
+
+    if market.is_paused() {
+        return Err(LendingError::MarketPaused.into());
+    }
+    if reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::FLASH_LOANS_DISABLED)
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+    if !callback_program.executable
+        || callback_program.key() == crate::ID
+        || callback_program.key() == ctx.accounts.token_program.key()
+    {
+        return Err(LendingError::FlashLoanCallbackInvalid.into());
+    }
+    if amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+    if amount > reserve.state.available_liquidity {
+        return Err(LendingError::FlashLoanAmountTooLarge.into());
+    }
+
+    // Hold the reentrancy lock until the loan is repaid and the reserve is written back
+    let mut reserve = ReserveLock::acquire(reserve)?;
+
+    reserve.update_interest(clock.slot)?;
+    let fee = Reserve::flash_loan_fee(amount)?;
+
+    // Open the borrower's guard; nothing may be routed back in while the loan is out
+    FlashLoanGuard::open_account(
+        &ctx.accounts.flash_loan_guard,
+        &ctx.accounts.borrower.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.borrower.key(),
+        reserve.key(),
+        amount,
+        0,
+        ctx.bumps.flash_loan_guard,
+    )?;
+
+    // Issue the loan
+    let balance_before = ctx.accounts.reserve_liquidity_supply.amount;
+    let liquidity_mint = reserve.liquidity_mint;
+    let authority_seeds = &[
+        LIQUIDITY_TOKEN_SEED,
+        liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.reserve_liquidity_authority],
+    ];
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.reserve_liquidity_supply,
+        &ctx.accounts.destination_liquidity,
+        &ctx.accounts.liquidity_mint,
+        &ctx.accounts.reserve_liquidity_authority.to_account_info(),
+        &[authority_seeds],
+        amount,
+    )?;
+
+    // Hand control to the borrower
+    let accounts = ctx
+        .remaining_accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: *account.key,
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        })
+        .collect();
+    let instruction = Instruction {
+        program_id: callback_program.key(),
+        accounts,
+        data: callback_data,
+    };
+    let mut account_infos = ctx.remaining_accounts.to_vec();
+    account_infos.push(callback_program.to_account_info());
+    invoke(&instruction, &account_infos)?;
+
+    // Verify repayment of principal and fee
+    ctx.accounts.reserve_liquidity_supply.reload()?;
+    let balance_after = ctx.accounts.reserve_liquidity_supply.amount;
+    if balance_after < balance_before {
+        return Err(LendingError::FlashLoanNotRepaid.into());
+    }
+    let repaid_fee = balance_after - balance_before;
+    if repaid_fee < fee {
+        return Err(LendingError::FlashLoanFeeNotPaid.into());
+    }
+
+    let protocol_fee = reserve.collect_flash_loan_fee(fee)?;
+
+    // The insurance fund's share of the protocol fee is paid out of the supply right away
+    let (_, insurance_fee) = reserve.split_insurance_fee(protocol_fee)?;
+    if insurance_fee > 0 {
+        reserve.release_protocol_fees(insurance_fee)?;
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.reserve_liquidity_supply,
+            &ctx.accounts.insurance_fund_vault,
+            &ctx.accounts.liquidity_mint,
+            &ctx.accounts.reserve_liquidity_authority.to_account_info(),
+            &[authority_seeds],
+            insurance_fee,
+        )?;
+        ctx.accounts.insurance_fund.record_deposit(insurance_fee)?;
+    }
+
+    FlashLoanGuard::close_account(&ctx.accounts.flash_loan_guard)?;
+
+    emit!(FlashLoanEvent {
+        reserve: reserve.key(),
+        borrower: ctx.accounts.borrower.key(),
+        amount,
+        fee,
+        slot: clock.slot,
+    });
+
+    msg!(
+        "Flash loan repaid - amount: {}, fee: {} (protocol: {}, insurance fund: {})",
+        amount,
+        fee,
+        protocol_fee,
+        insurance_fee
+    );
+    Ok(())
+}
+
+// Context structs for flash loan instructions
+
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve lending the liquidity
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Liquidity token mint
+    #[account(address = reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Reserve liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub reserve_liquidity_authority: UncheckedAccount<'info>,
+
+    /// Token account receiving the loan
+    #[account(
+        mut,
+        token::mint = reserve.liquidity_mint
+    )]
+    pub destination_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Reserve's insurance fund
+    #[account(
+        mut,
+        seeds = [INSURANCE_FUND_SEED, reserve.key().as_ref()],
+        bump = insurance_fund.bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    /// Insurance fund vault (insurance share of the protocol fee)
+    #[account(
+        mut,
+        address = insurance_fund.liquidity_vault @ LendingError::InvalidAccount
+    )]
+    pub insurance_fund_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Borrower program invoked between issuing the loan and checking repayment
+    /// CHECK: Arbitrary borrower program; rejected if not executable or privileged
+    pub callback_program: UncheckedAccount<'info>,
+
+    /// Borrower's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and created on first use
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, borrower.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
+    /// Borrower (pays for the guard account on first use)
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+
+    /// Protocol configuration (remaining account cap)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+    // Note: accounts forwarded to the callback are passed as remaining_accounts
+}
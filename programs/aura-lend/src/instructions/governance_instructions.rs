@@ -1,7 +1,9 @@
 use crate::constants::*;
 use crate::error::LendingError;
+use crate::state::audit_log::{AdminAction, AdminAuditLog};
 use crate::state::governance::*;
 use crate::state::multisig::*;
+use crate::state::proposal_lifecycle::ProposalStatus;
 use anchor_lang::prelude::*;
 
 /// Initialize governance registry
@@ -23,12 +25,18 @@ pub fn initialize_governance(
 
 /// Grant a role to an account
 pub fn grant_role(ctx: Context<GrantRole>, params: GrantRoleParams) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.granter.key(),
+        AdminAction::GrantRole,
+        &params,
+    )?;
+
     let governance = &mut ctx.accounts.governance;
     let granter = &ctx.accounts.granter;
     let multisig_proposal = &ctx.accounts.multisig_proposal;
 
     // Verify this is being called through an executed multisig proposal
-    if multisig_proposal.status != crate::state::multisig::ProposalStatus::Executed {
+    if multisig_proposal.status != ProposalStatus::Executed {
         return Err(LendingError::ProposalNotExecuted.into());
     }
 
@@ -51,6 +59,7 @@ pub fn grant_role(ctx: Context<GrantRole>, params: GrantRoleParams) -> Result<()
         RoleType::TimelockManager => Permission::TIMELOCK_MANAGER.bits(),
         RoleType::ProgramUpgradeManager => Permission::PROGRAM_UPGRADE_MANAGER.bits(),
         RoleType::DataMigrationManager => Permission::DATA_MIGRATION_MANAGER.bits(),
+        RoleType::Guardian => Permission::GUARDIAN.bits(),
     };
 
     // Use provided permissions or default to role permissions
@@ -80,12 +89,18 @@ pub fn grant_role(ctx: Context<GrantRole>, params: GrantRoleParams) -> Result<()
 
 /// Revoke a role from an account
 pub fn revoke_role(ctx: Context<RevokeRole>, target_holder: Pubkey) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.revoker.key(),
+        AdminAction::RevokeRole,
+        &target_holder,
+    )?;
+
     let governance = &mut ctx.accounts.governance;
     let revoker = &ctx.accounts.revoker;
     let multisig_proposal = &ctx.accounts.multisig_proposal;
 
     // Verify this is being called through an executed multisig proposal
-    if multisig_proposal.status != crate::state::multisig::ProposalStatus::Executed {
+    if multisig_proposal.status != ProposalStatus::Executed {
         return Err(LendingError::ProposalNotExecuted.into());
     }
 
@@ -108,6 +123,12 @@ pub fn delegate_permissions(
     ctx: Context<DelegatePermissions>,
     params: DelegatePermissionsParams,
 ) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.delegator.key(),
+        AdminAction::DelegatePermissions,
+        &params,
+    )?;
+
     let governance = &mut ctx.accounts.governance;
     let delegator = &ctx.accounts.delegator;
 
@@ -169,11 +190,17 @@ pub fn update_governance_config(
     ctx: Context<UpdateGovernanceConfig>,
     new_available_permissions: u64,
 ) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.executor.key(),
+        AdminAction::UpdateGovernanceConfig,
+        &new_available_permissions,
+    )?;
+
     let governance = &mut ctx.accounts.governance;
     let multisig_proposal = &ctx.accounts.multisig_proposal;
 
     // Verify this is being called through an executed multisig proposal
-    if multisig_proposal.status != crate::state::multisig::ProposalStatus::Executed {
+    if multisig_proposal.status != ProposalStatus::Executed {
         return Err(LendingError::ProposalNotExecuted.into());
     }
 
@@ -189,6 +216,12 @@ pub fn emergency_grant_role(
     ctx: Context<EmergencyGrantRole>,
     params: GrantRoleParams,
 ) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.emergency_authority.key(),
+        AdminAction::EmergencyGrantRole,
+        &params,
+    )?;
+
     let governance = &mut ctx.accounts.governance;
     let emergency_authority = &ctx.accounts.emergency_authority;
     let market = &ctx.accounts.market;
@@ -269,6 +302,14 @@ pub struct GrantRole<'info> {
     pub multisig_proposal: Account<'info, crate::state::multisig::MultisigProposal>,
 
     pub granter: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 #[derive(Accounts)]
@@ -281,6 +322,14 @@ pub struct RevokeRole<'info> {
     pub multisig_proposal: Account<'info, crate::state::multisig::MultisigProposal>,
 
     pub revoker: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 #[derive(Accounts)]
@@ -290,6 +339,14 @@ pub struct DelegatePermissions<'info> {
     pub governance: Account<'info, GovernanceRegistry>,
 
     pub delegator: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 #[derive(Accounts)]
@@ -310,6 +367,14 @@ pub struct UpdateGovernanceConfig<'info> {
     pub multisig_proposal: Account<'info, crate::state::multisig::MultisigProposal>,
 
     pub executor: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 #[derive(Accounts)]
@@ -321,6 +386,14 @@ pub struct EmergencyGrantRole<'info> {
     pub market: Account<'info, crate::state::market::Market>,
 
     pub emergency_authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 // Parameter structs for governance operations
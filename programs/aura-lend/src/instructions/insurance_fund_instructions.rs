@@ -0,0 +1,377 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::events::BadDebtSettled;
+use crate::state::*;
+use crate::utils::{require_admin, TokenUtils};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Initialize the insurance fund of a reserve (governance only)
+/// Borrows and flash loans of the reserve pay the fund's fee share into its vault, so
+/// the fund must exist before either is used.
+pub fn initialize_insurance_fund(ctx: Context<InitializeInsuranceFund>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::InitializeInsuranceFund,
+        &ctx.accounts.reserve.key(),
+    )?;
+
+    let market = &ctx.accounts.market;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RESERVE_MANAGER,
+    )?;
+
+    **ctx.accounts.insurance_fund = InsuranceFund::new(
+        market.key(),
+        ctx.accounts.reserve.key(),
+        ctx.accounts.liquidity_vault.key(),
+        ctx.bumps.insurance_fund,
+    );
+
+    msg!(
+        "Insurance fund initialized for reserve: {}",
+        ctx.accounts.reserve.key()
+    );
+    Ok(())
+}
+
+/// Withdraw liquidity from an insurance fund (governance only)
+/// Requires an executed `WithdrawInsuranceFund` timelock proposal whose instruction
+/// data is the amount and whose targets include the fund and the destination. The
+/// proposal is closed on use so it cannot authorize a second withdrawal.
+pub fn withdraw_insurance_fund(ctx: Context<WithdrawInsuranceFund>, amount: u64) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::WithdrawInsuranceFund,
+        &(ctx.accounts.destination_liquidity.key(), amount),
+    )?;
+
+    let market = &ctx.accounts.market;
+    let insurance_fund = &ctx.accounts.insurance_fund;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::FEE_MANAGER,
+    )?;
+
+    if amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    insurance_fund.validate_withdrawal_proposal(
+        &insurance_fund.key(),
+        &ctx.accounts.executed_proposal,
+        &market.timelock_controller,
+        &ctx.accounts.destination_liquidity.key(),
+        amount,
+    )?;
+
+    let fund_seeds = &[
+        INSURANCE_FUND_SEED,
+        insurance_fund.reserve.as_ref(),
+        &[insurance_fund.bump],
+    ];
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.liquidity_vault,
+        &ctx.accounts.destination_liquidity,
+        &ctx.accounts.liquidity_mint,
+        &ctx.accounts.insurance_fund.to_account_info(),
+        &[fund_seeds],
+        amount,
+    )?;
+
+    ctx.accounts.insurance_fund.record_withdrawal(amount)?;
+
+    msg!(
+        "Withdrew {} tokens from insurance fund to {}",
+        amount,
+        ctx.accounts.destination_liquidity.key()
+    );
+    Ok(())
+}
+
+/// Write off an obligation's debt in a reserve once no collateral is left to back it
+/// Permissionless. The reserve's insurance fund repays as much of the debt as its vault
+/// holds; only the remainder is socialized to suppliers through a lower collateral
+/// exchange rate.
+pub fn settle_bad_debt(ctx: Context<SettleBadDebt>) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let mut obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market.key())?;
+    let reserve = &mut ctx.accounts.reserve;
+    let clock = Clock::get()?;
+
+    if market.is_paused() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    // Compound the debt up to the current slot before removing it
+    reserve.update_interest(clock.slot)?;
+    obligation.accrue_borrow_interest(&reserve.key(), reserve.cumulative_borrow_rate_wads())?;
+    let bad_debt = obligation
+        .write_off_liquidity_borrow(&reserve.key())?
+        .try_floor_u64()?;
+
+    // Cover the debt out of the insurance fund first, grossed up for the mint's transfer
+    // fee and limited to what the vault holds
+    let transfer_amount = std::cmp::min(
+        TokenUtils::amount_before_transfer_fee(&ctx.accounts.liquidity_mint, bad_debt)?,
+        ctx.accounts.insurance_fund_vault.amount,
+    );
+    let covered = std::cmp::min(
+        TokenUtils::amount_after_transfer_fee(&ctx.accounts.liquidity_mint, transfer_amount)?,
+        bad_debt,
+    );
+
+    if transfer_amount > 0 {
+        let insurance_fund = &ctx.accounts.insurance_fund;
+        let fund_seeds = &[
+            INSURANCE_FUND_SEED,
+            insurance_fund.reserve.as_ref(),
+            &[insurance_fund.bump],
+        ];
+
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.insurance_fund_vault,
+            &ctx.accounts.liquidity_supply,
+            &ctx.accounts.liquidity_mint,
+            &insurance_fund.to_account_info(),
+            &[fund_seeds],
+            transfer_amount,
+        )?;
+
+        reserve.repay_borrow(covered)?;
+        ctx.accounts
+            .insurance_fund
+            .record_bad_debt_coverage(covered)?;
+    }
+
+    // Whatever the fund could not cover is a loss to suppliers
+    let shortfall = bad_debt
+        .checked_sub(covered)
+        .ok_or(LendingError::MathUnderflow)?;
+    let socialized = reserve.socialize_bad_debt(shortfall)?;
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    emit!(BadDebtSettled {
+        obligation: ctx.accounts.obligation.key(),
+        reserve: reserve.key(),
+        bad_debt,
+        covered_by_insurance: covered,
+        socialized,
+        slot: clock.slot,
+    });
+
+    msg!(
+        "Bad debt of {} settled - insurance fund: {}, socialized: {}",
+        bad_debt,
+        covered,
+        socialized
+    );
+    Ok(())
+}
+
+// Context structs for insurance fund instructions
+
+#[derive(Accounts)]
+pub struct InitializeInsuranceFund<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve whose fees fund the insurance fund
+    #[account(
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Liquidity token mint of the reserve
+    #[account(address = reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Insurance fund account to initialize
+    #[account(
+        init,
+        payer = payer,
+        space = InsuranceFund::SIZE,
+        seeds = [INSURANCE_FUND_SEED, reserve.key().as_ref()],
+        bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    /// Token account holding the fund's liquidity
+    #[account(
+        init,
+        payer = payer,
+        token::mint = liquidity_mint,
+        token::authority = insurance_fund,
+        seeds = [INSURANCE_FUND_SEED, insurance_fund.key().as_ref(), b"liquidity"],
+        bump
+    )]
+    pub liquidity_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Payer for account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Rent sysvar
+    pub rent: Sysvar<'info, Rent>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawInsuranceFund<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Insurance fund account
+    #[account(
+        mut,
+        seeds = [INSURANCE_FUND_SEED, insurance_fund.reserve.as_ref()],
+        bump = insurance_fund.bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = liquidity_vault @ LendingError::InvalidAccount
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    /// Token account holding the fund's liquidity
+    #[account(
+        mut,
+        token::mint = liquidity_mint
+    )]
+    pub liquidity_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity token mint of the fund
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Destination token account authorized by the proposal
+    #[account(
+        mut,
+        token::mint = liquidity_mint
+    )]
+    pub destination_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// The executed timelock proposal that authorizes this withdrawal
+    #[account(
+        mut,
+        close = authority
+    )]
+    pub executed_proposal: Account<'info, TimelockProposal>,
+
+    /// Market multisig owner (receives the proposal's rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct SettleBadDebt<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation holding the unbacked debt (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Reserve the debt is owed to
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Liquidity token mint
+    #[account(address = reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Reserve's liquidity supply token account
+    #[account(mut)]
+    pub liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Reserve's insurance fund
+    #[account(
+        mut,
+        seeds = [INSURANCE_FUND_SEED, reserve.key().as_ref()],
+        bump = insurance_fund.bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    /// Insurance fund vault
+    #[account(
+        mut,
+        address = insurance_fund.liquidity_vault @ LendingError::InvalidAccount
+    )]
+    pub insurance_fund_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Keeper triggering the settlement
+    pub keeper: Signer<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
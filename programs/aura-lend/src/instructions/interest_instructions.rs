@@ -0,0 +1,91 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::*;
+use crate::utils::ProtocolConfig;
+use anchor_lang::prelude::*;
+
+/// Accrue a reserve's interest and update its exchange rate (permissionless crank)
+/// Keeps rates fresh while a reserve sees no user activity; no oracle is needed
+pub fn accrue_reserve_interest(ctx: Context<AccrueReserveInterest>) -> Result<()> {
+    let reserve = &mut ctx.accounts.reserve;
+    let clock = Clock::get()?;
+
+    reserve.update_interest(clock.slot)?;
+
+    msg!(
+        "Reserve {} interest accrued - total borrows: {}, total liquidity: {}",
+        reserve.key(),
+        reserve.state.total_borrows,
+        reserve.state.total_liquidity
+    );
+    Ok(())
+}
+
+/// Accrue interest on every reserve passed as remaining_accounts (permissionless crank)
+pub fn accrue_reserves_interest<'info>(
+    ctx: Context<'_, '_, '_, 'info, AccrueReservesInterest<'info>>,
+) -> Result<()> {
+    let market_key = ctx.accounts.market.key();
+    let clock = Clock::get()?;
+
+    ctx.accounts
+        .config
+        .validate_account_count(ctx.remaining_accounts.len())?;
+
+    for reserve_info in ctx.remaining_accounts.iter() {
+        if !reserve_info.is_writable {
+            return Err(LendingError::InvalidAccount.into());
+        }
+
+        let mut reserve = Account::<Reserve>::try_from(reserve_info)
+            .map_err(|_| LendingError::InvalidAccount)?;
+        if reserve.market != market_key {
+            return Err(LendingError::InvalidMarketState.into());
+        }
+
+        reserve.update_interest(clock.slot)?;
+        reserve.exit(&crate::ID)?;
+    }
+
+    msg!("Interest accrued on {} reserves", ctx.remaining_accounts.len());
+    Ok(())
+}
+
+// Context structs for interest accrual instructions
+
+#[derive(Accounts)]
+pub struct AccrueReserveInterest<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve to accrue
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+}
+
+#[derive(Accounts)]
+pub struct AccrueReservesInterest<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol configuration (remaining account cap)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    // Note: writable reserve accounts are passed as remaining_accounts
+}
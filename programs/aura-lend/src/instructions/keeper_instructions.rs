@@ -0,0 +1,590 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::*;
+use crate::utils::{require_admin, ProtocolConfig, TokenUtils};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Create the market's keeper registry and its stake vault (governance only)
+pub fn initialize_keeper_registry(
+    ctx: Context<InitializeKeeperRegistry>,
+    params: InitializeKeeperRegistryParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::InitializeKeeperRegistry,
+        &params,
+    )?;
+
+    let market = &ctx.accounts.market;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RISK_MANAGER,
+    )?;
+
+    **ctx.accounts.keeper_registry = KeeperRegistry::new(
+        market.key(),
+        ctx.accounts.stake_mint.key(),
+        ctx.accounts.stake_vault.key(),
+        params.min_stake,
+        params.exit_cooldown_slots,
+        ctx.bumps.keeper_registry,
+    )?;
+
+    msg!(
+        "Keeper registry initialized - stake mint: {}, min stake: {}, exit cooldown: {} slots",
+        ctx.accounts.stake_mint.key(),
+        params.min_stake,
+        params.exit_cooldown_slots
+    );
+    Ok(())
+}
+
+/// Update keeper registry parameters (governance only)
+/// Raising `min_stake` removes priority from keepers below it until they top up.
+pub fn update_keeper_registry(
+    ctx: Context<UpdateKeeperRegistry>,
+    params: UpdateKeeperRegistryParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::UpdateKeeperRegistry,
+        &params,
+    )?;
+
+    let market = &ctx.accounts.market;
+    let keeper_registry = &mut ctx.accounts.keeper_registry;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RISK_MANAGER,
+    )?;
+
+    if let Some(min_stake) = params.min_stake {
+        KeeperRegistry::validate_min_stake(min_stake)?;
+        keeper_registry.min_stake = min_stake;
+    }
+
+    if let Some(exit_cooldown_slots) = params.exit_cooldown_slots {
+        KeeperRegistry::validate_exit_cooldown_slots(exit_cooldown_slots)?;
+        keeper_registry.exit_cooldown_slots = exit_cooldown_slots;
+    }
+
+    msg!(
+        "Keeper registry updated - min stake: {}, exit cooldown: {} slots",
+        keeper_registry.min_stake,
+        keeper_registry.exit_cooldown_slots
+    );
+    Ok(())
+}
+
+/// Register the signer as a keeper, staking `stake_amount`
+pub fn register_keeper(ctx: Context<RegisterKeeper>, stake_amount: u64) -> Result<()> {
+    // The keeper is credited with what arrives after the mint's transfer fee
+    let received_amount =
+        TokenUtils::amount_after_transfer_fee(&ctx.accounts.stake_mint, stake_amount)?;
+
+    **ctx.accounts.keeper = Keeper::new(
+        &ctx.accounts.keeper_registry,
+        ctx.accounts.keeper_registry.key(),
+        ctx.accounts.authority.key(),
+        received_amount,
+        Clock::get()?.slot,
+        ctx.bumps.keeper,
+    )?;
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.source_stake,
+        &ctx.accounts.stake_vault,
+        &ctx.accounts.stake_mint,
+        &ctx.accounts.authority.to_account_info(),
+        &[],
+        stake_amount,
+    )?;
+
+    ctx.accounts
+        .keeper_registry
+        .record_registration(received_amount)?;
+
+    msg!(
+        "Keeper {} registered with {} staked",
+        ctx.accounts.authority.key(),
+        received_amount
+    );
+    Ok(())
+}
+
+/// Add `amount` to the signer's keeper stake, e.g. after a slash or a higher minimum
+pub fn add_keeper_stake(ctx: Context<AddKeeperStake>, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    let received_amount = TokenUtils::amount_after_transfer_fee(&ctx.accounts.stake_mint, amount)?;
+    ctx.accounts.keeper.add_stake(received_amount)?;
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.source_stake,
+        &ctx.accounts.stake_vault,
+        &ctx.accounts.stake_mint,
+        &ctx.accounts.authority.to_account_info(),
+        &[],
+        amount,
+    )?;
+
+    ctx.accounts.keeper_registry.record_stake(received_amount)?;
+
+    msg!(
+        "Keeper {} stake raised to {}",
+        ctx.accounts.authority.key(),
+        ctx.accounts.keeper.staked_amount
+    );
+    Ok(())
+}
+
+/// Start the signer's exit from the registry
+/// The keeper loses priority immediately; the stake stays slashable until the exit
+/// cooldown has passed.
+pub fn request_keeper_exit(ctx: Context<RequestKeeperExit>) -> Result<()> {
+    let slot = Clock::get()?.slot;
+    ctx.accounts.keeper.request_exit(slot)?;
+
+    msg!(
+        "Keeper {} exit requested, withdrawable from slot {}",
+        ctx.accounts.authority.key(),
+        slot.saturating_add(ctx.accounts.keeper_registry.exit_cooldown_slots)
+    );
+    Ok(())
+}
+
+/// Return the signer's stake once the exit cooldown has passed and close the keeper
+pub fn withdraw_keeper_stake(ctx: Context<WithdrawKeeperStake>) -> Result<()> {
+    let keeper_registry = &mut ctx.accounts.keeper_registry;
+    let keeper = &ctx.accounts.keeper;
+
+    keeper.check_exit(keeper_registry.exit_cooldown_slots, Clock::get()?.slot)?;
+
+    let amount = keeper.staked_amount;
+    if amount > 0 {
+        let registry_seeds = &[
+            KEEPER_REGISTRY_SEED,
+            keeper_registry.market.as_ref(),
+            &[keeper_registry.bump],
+        ];
+
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.stake_vault,
+            &ctx.accounts.destination_stake,
+            &ctx.accounts.stake_mint,
+            &keeper_registry.to_account_info(),
+            &[registry_seeds],
+            amount,
+        )?;
+    }
+
+    keeper_registry.record_exit(amount)?;
+
+    msg!(
+        "Keeper {} left the registry with {} returned",
+        ctx.accounts.authority.key(),
+        amount
+    );
+    Ok(())
+}
+
+/// Slash up to `amount` of a keeper's stake into `destination_stake` (governance only)
+pub fn slash_keeper(ctx: Context<SlashKeeper>, amount: u64) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::SlashKeeper,
+        &(ctx.accounts.keeper.key(), amount),
+    )?;
+
+    let market = &ctx.accounts.market;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RISK_MANAGER,
+    )?;
+
+    let slashed = ctx.accounts.keeper.slash(amount)?;
+    if slashed == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    let keeper_registry = &mut ctx.accounts.keeper_registry;
+    let market_key = market.key();
+    let registry_seeds = &[
+        KEEPER_REGISTRY_SEED,
+        market_key.as_ref(),
+        &[keeper_registry.bump],
+    ];
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.stake_vault,
+        &ctx.accounts.destination_stake,
+        &ctx.accounts.stake_mint,
+        &keeper_registry.to_account_info(),
+        &[registry_seeds],
+        slashed,
+    )?;
+
+    keeper_registry.record_slash(slashed)?;
+
+    msg!(
+        "Keeper {} slashed {}, {} remaining",
+        ctx.accounts.keeper.authority,
+        slashed,
+        ctx.accounts.keeper.staked_amount
+    );
+    Ok(())
+}
+
+/// Enforce the keeper priority window on a liquidation
+/// Markets without a keeper registry have no window. Within the window the liquidator
+/// must pass their active `keeper` registration, which is credited with the liquidation.
+pub(crate) fn check_keeper_priority(
+    keeper_registry: &AccountInfo,
+    keeper: Option<&mut Account<Keeper>>,
+    config: &ProtocolConfig,
+    slots_unhealthy: u64,
+) -> Result<()> {
+    if keeper_registry.data_is_empty() {
+        return Ok(());
+    }
+
+    if keeper_registry.owner != &crate::ID {
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    let data = keeper_registry.try_borrow_data()?;
+    let mut slice: &[u8] = &data;
+    let registry = KeeperRegistry::try_deserialize(&mut slice)?;
+    registry.check_liquidation_priority(
+        keeper.as_deref().map(|keeper| &**keeper),
+        config.keeper_priority_window_slots,
+        slots_unhealthy,
+    )?;
+
+    if let Some(keeper) = keeper {
+        if keeper.is_active(&registry) {
+            keeper.record_liquidation();
+        }
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeKeeperRegistry<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Keeper registry account to initialize
+    #[account(
+        init,
+        payer = payer,
+        space = KeeperRegistry::SIZE,
+        seeds = [KEEPER_REGISTRY_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub keeper_registry: Account<'info, KeeperRegistry>,
+
+    /// Mint of the token keepers stake
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token account holding the staked tokens
+    #[account(
+        init,
+        payer = payer,
+        token::mint = stake_mint,
+        token::authority = keeper_registry,
+        seeds = [KEEPER_REGISTRY_SEED, keeper_registry.key().as_ref(), b"stake_vault"],
+        bump
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Payer for account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateKeeperRegistry<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Keeper registry to update
+    #[account(
+        mut,
+        seeds = [KEEPER_REGISTRY_SEED, market.key().as_ref()],
+        bump = keeper_registry.bump
+    )]
+    pub keeper_registry: Account<'info, KeeperRegistry>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterKeeper<'info> {
+    /// Keeper registry to join
+    #[account(
+        mut,
+        has_one = stake_mint @ LendingError::TokenMintMismatch,
+        has_one = stake_vault @ LendingError::InvalidAccount
+    )]
+    pub keeper_registry: Account<'info, KeeperRegistry>,
+
+    /// Keeper account to create
+    #[account(
+        init,
+        payer = authority,
+        space = Keeper::SIZE,
+        seeds = [KEEPER_SEED, keeper_registry.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub keeper: Account<'info, Keeper>,
+
+    /// Mint of the staked token
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+
+    /// Registry's stake vault
+    #[account(mut)]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Keeper's source token account
+    #[account(
+        mut,
+        token::mint = stake_mint,
+        token::authority = authority
+    )]
+    pub source_stake: InterfaceAccount<'info, TokenAccount>,
+
+    /// Wallet registering as a keeper
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct AddKeeperStake<'info> {
+    /// Keeper registry the keeper is registered in
+    #[account(
+        mut,
+        has_one = stake_mint @ LendingError::TokenMintMismatch,
+        has_one = stake_vault @ LendingError::InvalidAccount
+    )]
+    pub keeper_registry: Account<'info, KeeperRegistry>,
+
+    /// Signer's keeper account
+    #[account(
+        mut,
+        seeds = [KEEPER_SEED, keeper_registry.key().as_ref(), authority.key().as_ref()],
+        bump = keeper.bump
+    )]
+    pub keeper: Account<'info, Keeper>,
+
+    /// Mint of the staked token
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+
+    /// Registry's stake vault
+    #[account(mut)]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Keeper's source token account
+    #[account(
+        mut,
+        token::mint = stake_mint,
+        token::authority = authority
+    )]
+    pub source_stake: InterfaceAccount<'info, TokenAccount>,
+
+    /// Keeper authority
+    pub authority: Signer<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RequestKeeperExit<'info> {
+    /// Keeper registry the keeper is registered in
+    pub keeper_registry: Account<'info, KeeperRegistry>,
+
+    /// Signer's keeper account
+    #[account(
+        mut,
+        seeds = [KEEPER_SEED, keeper_registry.key().as_ref(), authority.key().as_ref()],
+        bump = keeper.bump
+    )]
+    pub keeper: Account<'info, Keeper>,
+
+    /// Keeper authority
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawKeeperStake<'info> {
+    /// Keeper registry the keeper is leaving
+    #[account(
+        mut,
+        has_one = stake_mint @ LendingError::TokenMintMismatch,
+        has_one = stake_vault @ LendingError::InvalidAccount
+    )]
+    pub keeper_registry: Account<'info, KeeperRegistry>,
+
+    /// Signer's keeper account (closed, rent returned to the authority)
+    #[account(
+        mut,
+        close = authority,
+        seeds = [KEEPER_SEED, keeper_registry.key().as_ref(), authority.key().as_ref()],
+        bump = keeper.bump
+    )]
+    pub keeper: Account<'info, Keeper>,
+
+    /// Mint of the staked token
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+
+    /// Registry's stake vault
+    #[account(mut)]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token account receiving the stake
+    #[account(
+        mut,
+        token::mint = stake_mint
+    )]
+    pub destination_stake: InterfaceAccount<'info, TokenAccount>,
+
+    /// Keeper authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SlashKeeper<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Keeper registry of the market
+    #[account(
+        mut,
+        seeds = [KEEPER_REGISTRY_SEED, market.key().as_ref()],
+        bump = keeper_registry.bump,
+        has_one = stake_mint @ LendingError::TokenMintMismatch,
+        has_one = stake_vault @ LendingError::InvalidAccount
+    )]
+    pub keeper_registry: Account<'info, KeeperRegistry>,
+
+    /// Keeper being slashed
+    #[account(
+        mut,
+        seeds = [KEEPER_SEED, keeper_registry.key().as_ref(), keeper.authority.as_ref()],
+        bump = keeper.bump
+    )]
+    pub keeper: Account<'info, Keeper>,
+
+    /// Mint of the staked token
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+
+    /// Registry's stake vault
+    #[account(mut)]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token account receiving the slashed stake (e.g. the insurance fund)
+    #[account(
+        mut,
+        token::mint = stake_mint
+    )]
+    pub destination_stake: InterfaceAccount<'info, TokenAccount>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
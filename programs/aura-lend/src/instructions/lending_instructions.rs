@@ -1,19 +1,37 @@
 use crate::constants::*;
 use crate::error::LendingError;
+use crate::events::{
+    emit_integrator_attribution, DepositEvent, RedeemEvent, SupplyPositionTransferred,
+};
+use crate::instructions::borrowing_instructions::{
+    validate_collateral_concentration, validate_collateral_position_size,
+};
+use crate::instructions::metrics_instructions::record_deposit_metric;
+use crate::instructions::rewards_instructions::sync_rewards;
 use crate::state::*;
-use crate::utils::{validate_signer, TokenUtils};
+use crate::utils::{
+    validate_authority, validate_signer, EventType, OracleManager, ProtocolConfig, ProtocolMetrics,
+    ReserveLock, TokenUtils, PROTOCOL_METRICS_SEED,
+};
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 /// Deposit liquidity into a reserve and receive collateral tokens (aTokens)
 pub fn deposit_reserve_liquidity(
     ctx: Context<DepositReserveLiquidity>,
     liquidity_amount: u64,
-) -> Result<()> {
+    integrator_id: Option<u32>,
+) -> Result<OperationReceipt> {
     let market = &ctx.accounts.market;
     let reserve = &mut ctx.accounts.reserve;
     let clock = Clock::get()?;
 
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(
+        &ctx.accounts.flash_loan_guard,
+        FlashLoanUsage::DEPOSIT_LIQUIDITY,
+    )?;
+
     // Check if market allows deposits
     if market.is_paused() || market.is_lending_disabled() {
         return Err(LendingError::MarketPaused.into());
@@ -33,17 +51,21 @@ pub fn deposit_reserve_liquidity(
         return Err(LendingError::AmountTooSmall.into());
     }
 
-    // Check reentrancy guard
-    if reserve.reentrancy_guard {
-        return Err(LendingError::ReentrantCall.into());
-    }
-    reserve.reentrancy_guard = true;
+    // Hold the reentrancy lock until the reserve state is written back
+    let mut reserve = ReserveLock::acquire(reserve)?;
 
     // Refresh reserve interest before deposit
     reserve.update_interest(clock.slot)?;
 
+    // Only what arrives after the mint's transfer fee is credited to the reserve
+    let received_liquidity =
+        TokenUtils::amount_after_transfer_fee(&ctx.accounts.liquidity_mint, liquidity_amount)?;
+
+    // Enforce the reserve's supply cap, including accrued interest
+    reserve.validate_supply_cap(received_liquidity)?;
+
     // Calculate collateral amount to mint
-    let collateral_amount = reserve.liquidity_to_collateral(liquidity_amount)?;
+    let collateral_amount = reserve.liquidity_to_collateral(received_liquidity)?;
 
     if collateral_amount == 0 {
         return Err(LendingError::AmountTooSmall.into());
@@ -61,6 +83,7 @@ pub fn deposit_reserve_liquidity(
         &ctx.accounts.token_program,
         &ctx.accounts.source_liquidity,
         &ctx.accounts.destination_liquidity,
+        &ctx.accounts.liquidity_mint,
         &ctx.accounts.user_transfer_authority.to_account_info(),
         &[],
         liquidity_amount,
@@ -84,72 +107,132 @@ pub fn deposit_reserve_liquidity(
     )?;
 
     // Update reserve state
-    reserve.add_liquidity(liquidity_amount)?;
+    reserve.add_liquidity(received_liquidity)?;
     reserve.state.collateral_mint_supply = reserve
         .state
         .collateral_mint_supply
         .checked_add(collateral_amount)
         .ok_or(LendingError::MathOverflow)?;
 
-    // Unlock reserve after successful operation
-    reserve.reentrancy_guard = false;
+    // Deposits carry no fee; the referrer is credited with the volume only
+    if let Some(referrer) = ctx.accounts.referrer.as_mut() {
+        referrer.validate_referred(&market.key(), &ctx.accounts.user_transfer_authority.key())?;
+        referrer.record_referral(reserve.key(), received_liquidity, 0)?;
+    }
+
+    record_deposit_metric(ctx.accounts.protocol_metrics.as_mut());
+    if let Some(user_stats) = ctx.accounts.user_stats.as_mut() {
+        user_stats.record_supply(
+            reserve.last_refreshed_value_usd(received_liquidity)?,
+            clock.slot,
+        )?;
+    }
+
+    // Record the idempotency nonce, if one was supplied
+    if let Some(operation_nonce) = ctx.accounts.operation_nonce.as_mut() {
+        **operation_nonce = OperationNonce::new(
+            ctx.accounts.user_transfer_authority.key(),
+            ctx.accounts.user_transfer_authority.key(),
+            NonceOperation::Deposit,
+            reserve.key(),
+            liquidity_amount,
+            clock.slot,
+        );
+    }
+
+    emit_integrator_attribution(
+        integrator_id,
+        EventType::LiquidityDeposited,
+        ctx.accounts.user_transfer_authority.key(),
+        reserve.key(),
+        liquidity_amount,
+        clock.slot,
+    );
 
     msg!(
         "Deposited {} liquidity, minted {} collateral tokens",
-        liquidity_amount,
+        received_liquidity,
         collateral_amount
     );
 
-    Ok(())
+    let receipt = OperationReceipt::new(
+        reserve.key(),
+        &reserve,
+        received_liquidity,
+        collateral_amount,
+        0,
+        None,
+    )?;
+
+    emit!(DepositEvent {
+        user: ctx.accounts.user_transfer_authority.key(),
+        receipt,
+        slot: clock.slot,
+    });
+
+    Ok(receipt)
 }
 
 /// Redeem collateral tokens (aTokens) for underlying liquidity
 pub fn redeem_reserve_collateral(
     ctx: Context<RedeemReserveCollateral>,
     collateral_amount: u64,
-) -> Result<()> {
+    integrator_id: Option<u32>,
+) -> Result<OperationReceipt> {
     let market = &ctx.accounts.market;
     let reserve = &mut ctx.accounts.reserve;
     let clock = Clock::get()?;
 
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(
+        &ctx.accounts.flash_loan_guard,
+        FlashLoanUsage::REDEEM_COLLATERAL,
+    )?;
+
     // Check if market allows withdrawals
     if market.is_paused() && !market.is_emergency() {
         return Err(LendingError::MarketPaused.into());
     }
 
-    // Check if reserve allows withdrawals
-    if reserve
-        .config
-        .flags
-        .contains(ReserveConfigFlags::WITHDRAWALS_DISABLED)
-    {
-        return Err(LendingError::FeatureDisabled.into());
-    }
-
     // Validate collateral amount
     if collateral_amount == 0 {
         return Err(LendingError::AmountTooSmall.into());
     }
 
-    // Check reentrancy guard
-    if reserve.reentrancy_guard {
-        return Err(LendingError::ReentrantCall.into());
-    }
-    reserve.reentrancy_guard = true;
+    // Hold the reentrancy lock until the reserve state is written back
+    let mut reserve = ReserveLock::acquire(reserve)?;
 
     // Refresh reserve interest before withdrawal
     reserve.update_interest(clock.slot)?;
 
-    // Calculate liquidity amount to withdraw
-    let liquidity_amount = reserve.collateral_to_liquidity(collateral_amount)?;
-
-    if liquidity_amount == 0 {
-        return Err(LendingError::AmountTooSmall.into());
-    }
-
-    // Check if reserve has sufficient liquidity
-    if reserve.state.available_liquidity < liquidity_amount {
-        return Err(LendingError::InsufficientLiquidity.into());
+    // Withdrawals-disabled flag, available liquidity, utilization cap and surge fee
+    let redemption =
+        reserve.quote_redemption(&ctx.accounts.config, collateral_amount, clock.slot)?;
+    let Redemption {
+        liquidity_amount,
+        surge_fee,
+        withdrawn_amount,
+        ..
+    } = redemption;
+
+    // Large redemptions must be pre-announced and matured (or waived by a guardian)
+    if ctx
+        .accounts
+        .config
+        .is_large_withdrawal(liquidity_amount, reserve.state.total_liquidity)?
+    {
+        let request = ctx
+            .accounts
+            .withdrawal_request
+            .as_ref()
+            .ok_or(LendingError::LargeWithdrawalRequestRequired)?;
+
+        request.validate_execution(
+            &reserve.key(),
+            &ctx.accounts.user_transfer_authority.key(),
+            collateral_amount,
+            clock.slot,
+        )?;
     }
 
     // Burn collateral tokens from user
@@ -174,28 +257,265 @@ pub fn redeem_reserve_collateral(
         &ctx.accounts.token_program,
         &ctx.accounts.source_liquidity,
         &ctx.accounts.destination_liquidity,
+        &ctx.accounts.liquidity_mint,
         &ctx.accounts.liquidity_supply_authority.to_account_info(),
         &[authority_seeds],
-        liquidity_amount,
+        withdrawn_amount,
     )?;
 
-    // Update reserve state
-    reserve.remove_liquidity(liquidity_amount)?;
-    reserve.state.collateral_mint_supply = reserve
-        .state
-        .collateral_mint_supply
-        .checked_sub(collateral_amount)
-        .ok_or(LendingError::MathUnderflow)?;
+    // Update reserve state; the surge fee stays in the reserve, raising the exchange rate
+    reserve.settle_redemption(&redemption)?;
 
-    // Unlock reserve after successful operation
-    reserve.reentrancy_guard = false;
+    emit_integrator_attribution(
+        integrator_id,
+        EventType::LiquidityWithdrawn,
+        ctx.accounts.user_transfer_authority.key(),
+        reserve.key(),
+        withdrawn_amount,
+        clock.slot,
+    );
 
     msg!(
-        "Redeemed {} collateral tokens for {} liquidity",
+        "Redeemed {} collateral tokens for {} liquidity (surge fee {})",
         collateral_amount,
-        liquidity_amount
+        withdrawn_amount,
+        surge_fee
     );
 
+    let receipt = OperationReceipt::new(
+        reserve.key(),
+        &reserve,
+        withdrawn_amount,
+        collateral_amount,
+        surge_fee,
+        None,
+    )?;
+
+    emit!(RedeemEvent {
+        user: ctx.accounts.user_transfer_authority.key(),
+        receipt,
+        slot: clock.slot,
+    });
+
+    Ok(receipt)
+}
+
+/// Move collateral deposited in the signer's obligation into another obligation
+/// No tokens move: the collateral tokens stay in the reserve's collateral supply and only
+/// the two obligations change, so utilization is unaffected. As with
+/// `deposit_obligation_collateral_for`, the recipient must already hold collateral in the
+/// reserve. Rewards of both obligations are settled around the move when the pool is
+/// passed with both participations.
+pub fn transfer_supply_position(
+    ctx: Context<TransferSupplyPosition>,
+    collateral_amount: u64,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let source_key = ctx.accounts.source_obligation.key();
+    let destination_key = ctx.accounts.destination_obligation.key();
+    let mut source = AnyObligation::load_owned(
+        &ctx.accounts.source_obligation,
+        &market.key(),
+        &ctx.accounts.owner.key(),
+    )?;
+    let mut destination =
+        AnyObligation::load_checked(&ctx.accounts.destination_obligation, &market.key())?;
+    let reserve = &mut ctx.accounts.reserve;
+    let clock = Clock::get()?;
+
+    // Settle rewards on both positions as held so far, before the move changes them
+    sync_rewards(
+        ctx.accounts.reward_pool.as_mut(),
+        ctx.accounts.source_obligation_rewards.as_mut(),
+        &source_key,
+        &source,
+        clock.slot,
+    )?;
+    sync_rewards(
+        ctx.accounts.reward_pool.as_mut(),
+        ctx.accounts.destination_obligation_rewards.as_mut(),
+        &destination_key,
+        &destination,
+        clock.slot,
+    )?;
+
+    // Moving collateral out of the source is a withdrawal for the flash loan guard
+    FlashLoanGuard::validate_entrypoint(
+        &ctx.accounts.flash_loan_guard,
+        FlashLoanUsage::WITHDRAW_COLLATERAL,
+    )?;
+
+    if market.is_paused() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    if collateral_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    if source_key == destination_key {
+        return Err(LendingError::InvalidAccount.into());
+    }
+
+    // The health check below relies on values from a refresh in this slot
+    if source.has_borrows() {
+        source.require_refreshed(clock.slot)?;
+    }
+
+    if destination
+        .find_collateral_deposit(&reserve.key())
+        .is_none()
+    {
+        return Err(LendingError::ObligationReserveNotFound.into());
+    }
+
+    // Hold the reentrancy lock until the reserve state is written back
+    let mut reserve = ReserveLock::acquire(reserve)?;
+
+    // Refresh the exchange rate so the reported liquidity value is current
+    reserve.update_interest(clock.slot)?;
+    let liquidity_amount = reserve.collateral_to_liquidity(collateral_amount)?;
+
+    let oracle_price =
+        OracleManager::get_price(&ctx.accounts.price_oracle.to_account_info(), &reserve)?;
+    oracle_price.validate(clock.unix_timestamp)?;
+    let collateral_value_usd = reserve.collateral_value_usd(collateral_amount, &oracle_price)?;
+
+    // Take the collateral out of the source, which must stay healthy
+    source.remove_collateral_deposit(&reserve.key(), collateral_amount)?;
+    source.deposited_value_usd = source.deposited_value_usd.try_sub(collateral_value_usd)?;
+
+    let emode = EModeCategory::resolve(ctx.accounts.emode_category.as_deref(), &source, None)?;
+    if source.has_borrows() && !source.is_healthy_with(emode.as_ref())? {
+        return Err(LendingError::ObligationUnhealthy.into());
+    }
+
+    // Add it to the recipient under the same limits as a deposit
+    let isolated = reserve.config.flags.contains(ReserveConfigFlags::ISOLATED);
+    if !isolated {
+        validate_collateral_concentration(&destination, &reserve.key(), collateral_value_usd)?;
+    }
+    validate_collateral_position_size(
+        &ctx.accounts.config,
+        &destination,
+        &reserve.key(),
+        collateral_value_usd,
+    )?;
+
+    destination.add_collateral_deposit(ObligationCollateral {
+        deposit_reserve: reserve.key(),
+        deposited_amount: collateral_amount,
+        market_value_usd: collateral_value_usd,
+        ltv_bps: reserve.effective_loan_to_value_ratio_bps(clock.unix_timestamp as u64),
+        liquidation_threshold_bps: reserve.config.liquidation_threshold_bps,
+    })?;
+    destination.deposited_value_usd = destination
+        .deposited_value_usd
+        .try_add(collateral_value_usd)?;
+
+    source.update_timestamp(clock.slot)?;
+    destination.update_timestamp(clock.slot)?;
+
+    // Settle reward emissions on the positions this operation changed
+    sync_rewards(
+        ctx.accounts.reward_pool.as_mut(),
+        ctx.accounts.source_obligation_rewards.as_mut(),
+        &source_key,
+        &source,
+        clock.slot,
+    )?;
+    sync_rewards(
+        ctx.accounts.reward_pool.as_mut(),
+        ctx.accounts.destination_obligation_rewards.as_mut(),
+        &destination_key,
+        &destination,
+        clock.slot,
+    )?;
+
+    source.save(&ctx.accounts.source_obligation)?;
+    destination.save(&ctx.accounts.destination_obligation)?;
+
+    emit!(SupplyPositionTransferred {
+        reserve: reserve.key(),
+        from_obligation: source_key,
+        to_obligation: destination_key,
+        from: source.owner,
+        to: destination.owner,
+        collateral_amount,
+        liquidity_amount,
+        slot: clock.slot,
+    });
+
+    msg!(
+        "Transferred {} collateral tokens ({} liquidity) to obligation {}",
+        collateral_amount,
+        liquidity_amount,
+        destination_key
+    );
+    Ok(())
+}
+
+/// Pre-announce a large redemption so it can execute after the configured delay
+pub fn request_large_withdrawal(
+    ctx: Context<RequestLargeWithdrawal>,
+    collateral_amount: u64,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let clock = Clock::get()?;
+
+    // The requester must hold the collateral being announced
+    if ctx.accounts.source_collateral.amount < collateral_amount {
+        return Err(LendingError::InsufficientTokenBalance.into());
+    }
+
+    **ctx.accounts.withdrawal_request = LargeWithdrawalRequest::new(
+        ctx.accounts.reserve.key(),
+        ctx.accounts.owner.key(),
+        collateral_amount,
+        clock.slot,
+        config.large_withdrawal_delay_slots,
+    )?;
+
+    msg!(
+        "Large withdrawal of {} collateral requested, executable at slot {}",
+        collateral_amount,
+        ctx.accounts.withdrawal_request.executable_slot
+    );
+    Ok(())
+}
+
+/// Cancel a pending large withdrawal request and reclaim its rent
+pub fn cancel_large_withdrawal(ctx: Context<CancelLargeWithdrawal>) -> Result<()> {
+    msg!(
+        "Large withdrawal request cancelled for owner: {}",
+        ctx.accounts.owner.key()
+    );
+    Ok(())
+}
+
+/// Waive the delay of a pending large withdrawal (emergency guardian only)
+pub fn waive_large_withdrawal_delay(ctx: Context<WaiveLargeWithdrawalDelay>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.guardian.key(),
+        AdminAction::WaiveLargeWithdrawalDelay,
+        &ctx.accounts.withdrawal_request.key(),
+    )?;
+
+    let market = &ctx.accounts.market;
+    let clock = Clock::get()?;
+
+    validate_authority(
+        &ctx.accounts.guardian.to_account_info(),
+        &market.emergency_authority,
+    )?;
+
+    ctx.accounts.withdrawal_request.waive(clock.slot)?;
+
+    msg!(
+        "Large withdrawal delay waived by guardian {} for owner {}",
+        ctx.accounts.guardian.key(),
+        ctx.accounts.withdrawal_request.owner
+    );
     Ok(())
 }
 
@@ -216,14 +536,20 @@ pub struct DepositReserveLiquidity<'info> {
         seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
         bump,
         has_one = market @ LendingError::InvalidMarketState,
-        // Validation will be done manually in the instruction
         has_one = collateral_mint @ LendingError::ReserveCollateralMintMismatch
     )]
     pub reserve: Account<'info, Reserve>,
 
+    /// Liquidity token mint
+    #[account(address = reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
     /// Reserve liquidity supply token account
-    #[account(mut)]
-    pub destination_liquidity: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub destination_liquidity: InterfaceAccount<'info, TokenAccount>,
 
     /// Liquidity supply authority (PDA)
     /// CHECK: This is validated by the seeds constraint
@@ -235,7 +561,7 @@ pub struct DepositReserveLiquidity<'info> {
 
     /// Collateral mint (aToken mint)
     #[account(mut)]
-    pub collateral_mint: Account<'info, Mint>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
 
     /// Collateral mint authority (PDA)
     /// CHECK: This is validated by the seeds constraint
@@ -251,7 +577,7 @@ pub struct DepositReserveLiquidity<'info> {
         token::mint = reserve.liquidity_mint,
         token::authority = user_transfer_authority
     )]
-    pub source_liquidity: Account<'info, TokenAccount>,
+    pub source_liquidity: InterfaceAccount<'info, TokenAccount>,
 
     /// User's destination collateral token account
     #[account(
@@ -259,13 +585,63 @@ pub struct DepositReserveLiquidity<'info> {
         token::mint = collateral_mint,
         token::authority = user_transfer_authority
     )]
-    pub destination_collateral: Account<'info, TokenAccount>,
+    pub destination_collateral: InterfaceAccount<'info, TokenAccount>,
 
     /// User's transfer authority
+    #[account(mut)]
     pub user_transfer_authority: Signer<'info>,
 
+    /// User's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, user_transfer_authority.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
     /// Token program
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Optional idempotency nonce (fresh keypair supplied by the client)
+    /// Creation fails if the nonce was already used, so retries cannot execute twice
+    #[account(
+        init,
+        payer = user_transfer_authority,
+        space = OperationNonce::SIZE
+    )]
+    pub operation_nonce: Option<Account<'info, OperationNonce>>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Referrer credited with the deposit volume
+    #[account(
+        mut,
+        seeds = [REFERRER_SEED, market.key().as_ref(), referrer.owner.as_ref()],
+        bump = referrer.bump
+    )]
+    pub referrer: Option<Account<'info, Referrer>>,
+
+    /// Protocol metrics counting the deposit, when supplied
+    #[account(
+        mut,
+        seeds = [PROTOCOL_METRICS_SEED, market.key().as_ref()],
+        bump = protocol_metrics.bump
+    )]
+    pub protocol_metrics: Option<Account<'info, ProtocolMetrics>>,
+
+    /// Depositor's statistics, updated when supplied
+    #[account(
+        mut,
+        seeds = [
+            USER_STATS_SEED,
+            market.key().as_ref(),
+            user_transfer_authority.key().as_ref()
+        ],
+        bump = user_stats.bump
+    )]
+    pub user_stats: Option<Account<'info, UserStats>>,
 }
 
 #[derive(Accounts)]
@@ -283,18 +659,20 @@ pub struct RedeemReserveCollateral<'info> {
         seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
         bump,
         has_one = market @ LendingError::InvalidMarketState,
-        // Validation will be done manually in the instruction
         has_one = collateral_mint @ LendingError::ReserveCollateralMintMismatch
     )]
     pub reserve: Account<'info, Reserve>,
 
+    /// Liquidity token mint
+    #[account(address = reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
     /// Reserve liquidity supply token account
     #[account(
         mut,
-        token::mint = reserve.liquidity_mint,
-        token::authority = liquidity_supply_authority
+        address = reserve.liquidity_supply @ LendingError::InvalidAccount
     )]
-    pub source_liquidity: Account<'info, TokenAccount>,
+    pub source_liquidity: InterfaceAccount<'info, TokenAccount>,
 
     /// Liquidity supply authority (PDA)
     /// CHECK: This is validated by the seeds constraint
@@ -306,7 +684,7 @@ pub struct RedeemReserveCollateral<'info> {
 
     /// Collateral mint (aToken mint)
     #[account(mut)]
-    pub collateral_mint: Account<'info, Mint>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
 
     /// User's source collateral token account
     #[account(
@@ -314,7 +692,7 @@ pub struct RedeemReserveCollateral<'info> {
         token::mint = collateral_mint,
         token::authority = user_transfer_authority
     )]
-    pub source_collateral: Account<'info, TokenAccount>,
+    pub source_collateral: InterfaceAccount<'info, TokenAccount>,
 
     /// User's destination liquidity token account
     #[account(
@@ -322,11 +700,193 @@ pub struct RedeemReserveCollateral<'info> {
         token::mint = reserve.liquidity_mint,
         token::authority = user_transfer_authority
     )]
-    pub destination_liquidity: Account<'info, TokenAccount>,
+    pub destination_liquidity: InterfaceAccount<'info, TokenAccount>,
 
     /// User's transfer authority
+    #[account(mut)]
     pub user_transfer_authority: Signer<'info>,
 
+    /// User's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, user_transfer_authority.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
     /// Token program
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Protocol configuration (large withdrawal threshold and utilization cap)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Pre-announced large withdrawal request, consumed on use
+    #[account(
+        mut,
+        seeds = [LARGE_WITHDRAWAL_SEED, reserve.key().as_ref(), user_transfer_authority.key().as_ref()],
+        bump,
+        close = user_transfer_authority
+    )]
+    pub withdrawal_request: Option<Account<'info, LargeWithdrawalRequest>>,
+}
+
+#[derive(Accounts)]
+pub struct TransferSupplyPosition<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve whose collateral is moved
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = price_oracle @ LendingError::OracleAccountMismatch
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Price oracle for the collateral asset
+    /// CHECK: This account is validated by the reserve's price_oracle field
+    pub price_oracle: UncheckedAccount<'info>,
+
+    /// Signer's obligation the collateral leaves (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout, market and
+    /// obligation owner
+    #[account(mut)]
+    pub source_obligation: UncheckedAccount<'info>,
+
+    /// Obligation receiving the collateral (any layout, any owner)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub destination_obligation: UncheckedAccount<'info>,
+
+    /// eMode category the source obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Owner of the source obligation
+    pub owner: Signer<'info>,
+
+    /// Owner's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
+    /// Protocol configuration (minimum position value)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Reward pool of the reserve, synced for both obligations
+    #[account(mut)]
+    pub reward_pool: Option<Account<'info, RewardPool>>,
+
+    /// Source obligation's participation in `reward_pool`
+    #[account(mut)]
+    pub source_obligation_rewards: Option<Account<'info, ObligationRewards>>,
+
+    /// Destination obligation's participation in `reward_pool`
+    #[account(mut)]
+    pub destination_obligation_rewards: Option<Account<'info, ObligationRewards>>,
+}
+
+#[derive(Accounts)]
+pub struct RequestLargeWithdrawal<'info> {
+    /// Reserve the redemption will be made from
+    #[account(
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Protocol configuration (large withdrawal delay)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Withdrawal request account to create
+    #[account(
+        init,
+        payer = owner,
+        space = LargeWithdrawalRequest::SIZE,
+        seeds = [LARGE_WITHDRAWAL_SEED, reserve.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub withdrawal_request: Account<'info, LargeWithdrawalRequest>,
+
+    /// Owner's collateral token account
+    #[account(
+        token::mint = reserve.collateral_mint,
+        token::authority = owner
+    )]
+    pub source_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Collateral owner
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelLargeWithdrawal<'info> {
+    /// Withdrawal request to cancel
+    #[account(
+        mut,
+        seeds = [LARGE_WITHDRAWAL_SEED, withdrawal_request.reserve.as_ref(), owner.key().as_ref()],
+        bump,
+        has_one = owner @ LendingError::InvalidAuthority,
+        close = owner
+    )]
+    pub withdrawal_request: Account<'info, LargeWithdrawalRequest>,
+
+    /// Request owner
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WaiveLargeWithdrawalDelay<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Withdrawal request to waive
+    #[account(
+        mut,
+        seeds = [LARGE_WITHDRAWAL_SEED, withdrawal_request.reserve.as_ref(), withdrawal_request.owner.as_ref()],
+        bump
+    )]
+    pub withdrawal_request: Account<'info, LargeWithdrawalRequest>,
+
+    /// Emergency guardian (market emergency_authority)
+    pub guardian: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
@@ -0,0 +1,554 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::events::LiquidationEvent;
+use crate::instructions::liquidation_instructions::{
+    begin_liquidation, calculate_liquidation_collateral_amount, settle_liquidation,
+};
+use crate::state::*;
+use crate::utils::{math::Decimal, OracleManager, ProtocolConfig, TokenUtils};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Start a Dutch auction liquidating an unhealthy obligation's collateral
+/// Permissionless. The collateral reserve must be flagged `AUCTION_LIQUIDATIONS` and
+/// the obligation freshly refreshed and unhealthy. The auction offers up to the close
+/// factor of the debt, at a discount rising from zero over `LIQUIDATION_AUCTION_DURATION_SLOTS`.
+pub fn start_liquidation_auction(ctx: Context<StartLiquidationAuction>) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market.key())?;
+    let repay_reserve = &ctx.accounts.repay_reserve;
+    let withdraw_reserve = &ctx.accounts.withdraw_reserve;
+    let clock = Clock::get()?;
+
+    // Check if market allows liquidations
+    if market.is_paused() || market.is_liquidation_disabled() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    // Check if reserves allow liquidations, and the collateral is sold by auction
+    if repay_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::LIQUIDATIONS_DISABLED)
+        || withdraw_reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::LIQUIDATIONS_DISABLED)
+        || !withdraw_reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::AUCTION_LIQUIDATIONS)
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    // Obligation must be freshly revalued, unhealthy and past any liquidation grace period
+    obligation.require_refreshed(clock.slot)?;
+    let emode = EModeCategory::resolve(ctx.accounts.emode_category.as_deref(), &obligation, None)?;
+    let health_factor = obligation.calculate_health_factor_with(emode.as_ref())?;
+    if health_factor >= Decimal::one() {
+        return Err(LendingError::ObligationHealthy.into());
+    }
+    obligation.check_liquidation_grace(health_factor, clock.slot)?;
+
+    obligation
+        .find_collateral_deposit(&withdraw_reserve.key())
+        .ok_or(LendingError::ObligationReserveNotFound)?;
+    let close_factor_bps = obligation.liquidation_close_factor_bps(
+        repay_reserve
+            .liquidation_close_factor_bps(ctx.accounts.config.liquidation_close_factor_bps),
+        emode.as_ref(),
+    )?;
+    let max_repay_amount =
+        obligation.max_liquidation_amount(&repay_reserve.key(), close_factor_bps)?;
+    if max_repay_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    // eMode obligations start from the category bonus instead of the reserve penalty
+    let liquidation_bonus_bps = match emode {
+        Some(emode) => emode.liquidation_bonus_bps,
+        None => withdraw_reserve.config.liquidation_penalty_bps,
+    };
+
+    **ctx.accounts.auction = LiquidationAuction::new(
+        market.key(),
+        ctx.accounts.obligation.key(),
+        repay_reserve.key(),
+        withdraw_reserve.key(),
+        ctx.accounts.initiator.key(),
+        clock.slot,
+        liquidation_bonus_bps,
+        max_repay_amount,
+        health_factor,
+        ctx.bumps.auction,
+    )?;
+
+    msg!(
+        "Liquidation auction started for obligation {} - debt offered: {}, max discount: {} bps",
+        ctx.accounts.obligation.key(),
+        max_repay_amount,
+        ctx.accounts.auction.max_discount_bps
+    );
+    Ok(())
+}
+
+/// Fill part or all of a liquidation auction at its current discount
+/// The bidder repays `liquidity_amount` of the debt and receives the collateral worth
+/// that amount plus the current discount. Bids fail once the obligation is healthy
+/// again. Remaining accounts are the obligation refresh accounts.
+pub fn bid_liquidation_auction(
+    ctx: Context<BidLiquidationAuction>,
+    liquidity_amount: u64,
+    min_collateral_amount: u64,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let mut obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market.key())?;
+    let repay_reserve = &mut ctx.accounts.repay_reserve;
+    let withdraw_reserve = &mut ctx.accounts.withdraw_reserve;
+    let clock = Clock::get()?;
+
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(&ctx.accounts.flash_loan_guard, FlashLoanUsage::LIQUIDATE)?;
+
+    // Bound the reserve/oracle accounts used to refresh the obligation
+    ctx.accounts
+        .config
+        .validate_account_count(ctx.remaining_accounts.len())?;
+
+    // Check if market allows liquidations
+    if market.is_paused() || market.is_liquidation_disabled() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    // Check if reserves allow liquidations
+    if repay_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::LIQUIDATIONS_DISABLED)
+        || withdraw_reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::LIQUIDATIONS_DISABLED)
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    if liquidity_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+    if liquidity_amount > ctx.accounts.auction.remaining_repay_amount() {
+        return Err(LendingError::LiquidationTooLarge.into());
+    }
+
+    let emode = EModeCategory::resolve(ctx.accounts.emode_category.as_deref(), &obligation, None)?;
+
+    let repay_price = OracleManager::get_checked_price(
+        &ctx.accounts.repay_price_oracle.to_account_info(),
+        ctx.accounts.repay_secondary_price_oracle.as_deref(),
+        repay_reserve,
+    )?;
+    repay_price.validate(clock.unix_timestamp)?;
+
+    let withdraw_price = OracleManager::get_checked_price(
+        &ctx.accounts.withdraw_price_oracle.to_account_info(),
+        ctx.accounts.withdraw_secondary_price_oracle.as_deref(),
+        withdraw_reserve,
+    )?;
+    withdraw_price.validate(clock.unix_timestamp)?;
+
+    // Smooth both prices against their TWAPs in the borrower's favour
+    let repay_price = repay_reserve.liquidation_debt_price(&repay_price);
+    let withdraw_price = withdraw_reserve.liquidation_collateral_price(&withdraw_price);
+
+    // Lock reserves, refresh them and confirm the obligation is still unhealthy
+    begin_liquidation(
+        &mut obligation,
+        repay_reserve,
+        withdraw_reserve,
+        &repay_price,
+        &withdraw_price,
+        ctx.remaining_accounts,
+        &clock,
+        emode.as_ref(),
+    )?;
+
+    // The debt may have shrunk, and the close factor moved with health, since the
+    // auction started
+    let close_factor_bps = obligation.liquidation_close_factor_bps(
+        repay_reserve
+            .liquidation_close_factor_bps(ctx.accounts.config.liquidation_close_factor_bps),
+        emode.as_ref(),
+    )?;
+    let max_liquidation =
+        obligation.max_liquidation_amount(&repay_reserve.key(), close_factor_bps)?;
+    if liquidity_amount > max_liquidation {
+        return Err(LendingError::LiquidationTooLarge.into());
+    }
+
+    // Value the repayment and the collateral it buys at the current discount
+    let discount_bps = ctx.accounts.auction.current_discount_bps(clock.slot);
+    let repay_value_usd = OracleManager::calculate_usd_value(
+        liquidity_amount,
+        &repay_price,
+        repay_reserve.config.decimals,
+    )?;
+    let seized_liquidity =
+        calculate_liquidation_collateral_amount(repay_value_usd, &withdraw_price, discount_bps)?;
+    let collateral_amount = withdraw_reserve.liquidity_to_collateral(seized_liquidity)?;
+
+    let deposited_collateral = obligation
+        .find_collateral_deposit(&withdraw_reserve.key())
+        .ok_or(LendingError::ObligationReserveNotFound)?
+        .deposited_amount;
+    if deposited_collateral < collateral_amount {
+        return Err(LendingError::InsufficientCollateral.into());
+    }
+    if collateral_amount < min_collateral_amount {
+        return Err(LendingError::SlippageExceeded.into());
+    }
+
+    // Grossed up so that the reserve receives the full repayment after transfer fees
+    let transfer_amount = TokenUtils::amount_before_transfer_fee(
+        &ctx.accounts.repay_liquidity_mint,
+        liquidity_amount,
+    )?;
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.source_liquidity,
+        &ctx.accounts.repay_reserve_liquidity_supply,
+        &ctx.accounts.repay_liquidity_mint,
+        &ctx.accounts.bidder.to_account_info(),
+        &[],
+        transfer_amount,
+    )?;
+
+    let collateral_authority_seeds = &[
+        COLLATERAL_TOKEN_SEED,
+        withdraw_reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.withdraw_collateral_supply_authority],
+    ];
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.collateral_token_program,
+        &ctx.accounts.withdraw_reserve_collateral_supply,
+        &ctx.accounts.destination_collateral,
+        &ctx.accounts.withdraw_collateral_mint,
+        &ctx.accounts
+            .withdraw_collateral_supply_authority
+            .to_account_info(),
+        &[collateral_authority_seeds],
+        collateral_amount,
+    )?;
+
+    // Update reserves and obligation
+    settle_liquidation(
+        &mut obligation,
+        repay_reserve,
+        withdraw_reserve,
+        &withdraw_price,
+        liquidity_amount,
+        repay_value_usd,
+        collateral_amount,
+        clock.slot,
+    )?;
+    let snapshot_health_factor = obligation.liquidation_snapshot_health_factor.take();
+
+    repay_reserve.unlock()?;
+    withdraw_reserve.unlock()?;
+
+    ctx.accounts
+        .auction
+        .record_fill(liquidity_amount, collateral_amount)?;
+
+    // The discount is reported as the receipt's fee, in collateral tokens
+    let par_collateral = withdraw_reserve.liquidity_to_collateral(
+        calculate_liquidation_collateral_amount(repay_value_usd, &withdraw_price, 0)?,
+    )?;
+    emit!(LiquidationEvent {
+        obligation: ctx.accounts.obligation.key(),
+        liquidator: ctx.accounts.bidder.key(),
+        withdraw_reserve: withdraw_reserve.key(),
+        health_factor_bps: snapshot_health_factor.map_or(0, Decimal::to_health_bps_saturating),
+        receipt: OperationReceipt::new(
+            repay_reserve.key(),
+            repay_reserve,
+            liquidity_amount,
+            collateral_amount,
+            collateral_amount.saturating_sub(par_collateral),
+            Some(obligation.calculate_health_factor_with(emode.as_ref())?),
+        )?,
+        protocol_fee_collateral: 0,
+        protocol_fee_liquidity: 0,
+        slot: clock.slot,
+    });
+
+    msg!(
+        "Liquidation auction bid by {} - repaid: {}, received: {}, discount: {} bps",
+        ctx.accounts.bidder.key(),
+        liquidity_amount,
+        collateral_amount,
+        discount_bps
+    );
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    Ok(())
+}
+
+/// Close a liquidation auction and return its rent to the keeper who started it
+/// Permissionless once the auction is filled, has expired, or the obligation is healthy
+/// again (freshly refreshed).
+pub fn close_liquidation_auction(ctx: Context<CloseLiquidationAuction>) -> Result<()> {
+    let auction = &ctx.accounts.auction;
+    let clock = Clock::get()?;
+
+    if !auction.is_filled() && !auction.is_expired(clock.slot) {
+        let obligation =
+            AnyObligation::load_checked(&ctx.accounts.obligation, &ctx.accounts.market.key())?;
+        obligation.require_refreshed(clock.slot)?;
+        let emode =
+            EModeCategory::resolve(ctx.accounts.emode_category.as_deref(), &obligation, None)?;
+        if !obligation.is_healthy_with(emode.as_ref())? {
+            return Err(LendingError::LiquidationAuctionActive.into());
+        }
+    }
+
+    msg!(
+        "Liquidation auction closed for obligation {} - repaid: {}, collateral sold: {}",
+        auction.obligation,
+        auction.repaid_amount,
+        auction.collateral_sold
+    );
+    Ok(())
+}
+
+// Context structs for liquidation auction instructions
+
+#[derive(Accounts)]
+pub struct StartLiquidationAuction<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol configuration (liquidation close factor)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Obligation being liquidated (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    pub obligation: UncheckedAccount<'info>,
+
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Reserve for the asset being repaid
+    #[account(
+        seeds = [RESERVE_SEED, repay_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub repay_reserve: Account<'info, Reserve>,
+
+    /// Reserve for the collateral being sold
+    #[account(
+        seeds = [RESERVE_SEED, withdraw_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub withdraw_reserve: Account<'info, Reserve>,
+
+    /// Auction account to initialize (one per obligation)
+    #[account(
+        init,
+        payer = initiator,
+        space = LiquidationAuction::SIZE,
+        seeds = [LIQUIDATION_AUCTION_SEED, obligation.key().as_ref()],
+        bump
+    )]
+    pub auction: Account<'info, LiquidationAuction>,
+
+    /// Keeper starting the auction (pays its rent)
+    #[account(mut)]
+    pub initiator: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BidLiquidationAuction<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol configuration (remaining account cap and liquidation close factor)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Obligation being liquidated (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Auction being bid on
+    #[account(
+        mut,
+        seeds = [LIQUIDATION_AUCTION_SEED, obligation.key().as_ref()],
+        bump = auction.bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = repay_reserve @ LendingError::InvalidAccount,
+        has_one = withdraw_reserve @ LendingError::InvalidAccount
+    )]
+    pub auction: Account<'info, LiquidationAuction>,
+
+    /// Reserve for the asset being repaid
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, repay_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub repay_reserve: Account<'info, Reserve>,
+
+    /// Reserve for the collateral being sold
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, withdraw_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub withdraw_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for repay asset
+    /// CHECK: Matched against the repay reserve's price_oracle
+    #[account(address = repay_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub repay_price_oracle: UncheckedAccount<'info>,
+
+    /// Price oracle for withdraw asset
+    /// CHECK: Matched against the withdraw reserve's price_oracle
+    #[account(address = withdraw_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub withdraw_price_oracle: UncheckedAccount<'info>,
+
+    /// Secondary oracle of the repay asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub repay_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Secondary oracle of the withdraw asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub withdraw_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Liquidity token mint of the repay reserve
+    #[account(address = repay_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub repay_liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Collateral mint (aToken mint) of the withdraw reserve
+    #[account(address = withdraw_reserve.collateral_mint @ LendingError::ReserveCollateralMintMismatch)]
+    pub withdraw_collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Bidder's source liquidity token account (for repayment)
+    #[account(
+        mut,
+        token::mint = repay_reserve.liquidity_mint,
+        token::authority = bidder
+    )]
+    pub source_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Bidder's destination collateral token account (receives the collateral bought)
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.collateral_mint,
+        token::authority = bidder
+    )]
+    pub destination_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Repay reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = repay_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub repay_reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Withdraw reserve's collateral supply token account
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.collateral_mint,
+        token::authority = withdraw_collateral_supply_authority
+    )]
+    pub withdraw_reserve_collateral_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Withdraw collateral supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [COLLATERAL_TOKEN_SEED, withdraw_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub withdraw_collateral_supply_authority: UncheckedAccount<'info>,
+
+    /// Bidder
+    pub bidder: Signer<'info>,
+
+    /// User's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, bidder.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
+    /// Token program of the repay reserve's liquidity mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the withdraw reserve's collateral mint
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CloseLiquidationAuction<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation the auction liquidates (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    pub obligation: UncheckedAccount<'info>,
+
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Auction account to close
+    #[account(
+        mut,
+        close = initiator,
+        seeds = [LIQUIDATION_AUCTION_SEED, obligation.key().as_ref()],
+        bump = auction.bump,
+        has_one = initiator @ LendingError::InvalidAccount
+    )]
+    pub auction: Account<'info, LiquidationAuction>,
+
+    /// Keeper who started the auction (receives its rent)
+    /// CHECK: Matched against the auction's initiator
+    #[account(mut)]
+    pub initiator: UncheckedAccount<'info>,
+}
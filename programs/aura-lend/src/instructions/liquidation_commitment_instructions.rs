@@ -0,0 +1,169 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::*;
+use crate::utils::OracleManager;
+use anchor_lang::prelude::*;
+
+/// Commit to liquidating an obligation, pinning the current liquidation prices
+/// `commitment_hash` is `LiquidationCommitment::commitment_hash` of the amount to repay
+/// and a secret salt. The commitment is revealed with `execute_liquidation`.
+pub fn commit_liquidation(
+    ctx: Context<CommitLiquidation>,
+    commitment_hash: [u8; 32],
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let repay_reserve = &ctx.accounts.repay_reserve;
+    let withdraw_reserve = &ctx.accounts.withdraw_reserve;
+    let clock = Clock::get()?;
+
+    if market.is_paused() || market.is_liquidation_disabled() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    // The obligation must hold the positions being liquidated
+    let obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market.key())?;
+    if obligation
+        .find_liquidity_borrow(&repay_reserve.key())
+        .is_none()
+        || obligation
+            .find_collateral_deposit(&withdraw_reserve.key())
+            .is_none()
+    {
+        return Err(LendingError::ObligationReserveNotFound.into());
+    }
+
+    let repay_price = OracleManager::get_checked_price(
+        &ctx.accounts.repay_price_oracle.to_account_info(),
+        ctx.accounts.repay_secondary_price_oracle.as_deref(),
+        repay_reserve,
+    )?;
+    repay_price.validate(clock.unix_timestamp)?;
+
+    let withdraw_price = OracleManager::get_checked_price(
+        &ctx.accounts.withdraw_price_oracle.to_account_info(),
+        ctx.accounts.withdraw_secondary_price_oracle.as_deref(),
+        withdraw_reserve,
+    )?;
+    withdraw_price.validate(clock.unix_timestamp)?;
+
+    // Pin the prices exactly as `liquidate_obligation` would smooth them
+    **ctx.accounts.liquidation_commitment = LiquidationCommitment {
+        version: PROGRAM_VERSION,
+        obligation: ctx.accounts.obligation.key(),
+        liquidator: ctx.accounts.liquidator.key(),
+        repay_reserve: repay_reserve.key(),
+        withdraw_reserve: withdraw_reserve.key(),
+        commitment_hash,
+        committed_slot: clock.slot,
+        repay_price: repay_reserve.liquidation_debt_price(&repay_price),
+        withdraw_price: withdraw_reserve.liquidation_collateral_price(&withdraw_price),
+        bump: ctx.bumps.liquidation_commitment,
+        reserved: [0; 32],
+    };
+
+    msg!(
+        "Liquidation of {} committed at slot {}",
+        ctx.accounts.obligation.key(),
+        clock.slot
+    );
+    Ok(())
+}
+
+/// Close an unrevealed liquidation commitment, returning its rent to the liquidator
+pub fn cancel_liquidation_commitment(ctx: Context<CancelLiquidationCommitment>) -> Result<()> {
+    msg!(
+        "Liquidation commitment for {} cancelled",
+        ctx.accounts.liquidation_commitment.obligation
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CommitLiquidation<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation account to be liquidated (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Reserve for the asset to be repaid
+    #[account(
+        seeds = [RESERVE_SEED, repay_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState,
+    )]
+    pub repay_reserve: Account<'info, Reserve>,
+
+    /// Reserve for the collateral to be withdrawn
+    #[account(
+        seeds = [RESERVE_SEED, withdraw_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState,
+    )]
+    pub withdraw_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for repay asset
+    /// CHECK: Matched against the repay reserve's price_oracle
+    #[account(address = repay_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub repay_price_oracle: UncheckedAccount<'info>,
+
+    /// Price oracle for withdraw asset
+    /// CHECK: Matched against the withdraw reserve's price_oracle
+    #[account(address = withdraw_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub withdraw_price_oracle: UncheckedAccount<'info>,
+
+    /// Secondary oracle of the repay asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub repay_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Secondary oracle of the withdraw asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub withdraw_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Liquidation commitment account to initialize
+    #[account(
+        init,
+        payer = liquidator,
+        space = LiquidationCommitment::SIZE,
+        seeds = [
+            LIQUIDATION_COMMITMENT_SEED,
+            obligation.key().as_ref(),
+            liquidator.key().as_ref()
+        ],
+        bump
+    )]
+    pub liquidation_commitment: Account<'info, LiquidationCommitment>,
+
+    /// Liquidator committing
+    #[account(mut)]
+    pub liquidator: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelLiquidationCommitment<'info> {
+    /// Liquidation commitment to close
+    #[account(
+        mut,
+        close = liquidator,
+        has_one = liquidator @ LendingError::InvalidAuthority,
+        seeds = [
+            LIQUIDATION_COMMITMENT_SEED,
+            liquidation_commitment.obligation.as_ref(),
+            liquidator.key().as_ref()
+        ],
+        bump = liquidation_commitment.bump
+    )]
+    pub liquidation_commitment: Account<'info, LiquidationCommitment>,
+
+    /// Liquidator who committed
+    #[account(mut)]
+    pub liquidator: Signer<'info>,
+}
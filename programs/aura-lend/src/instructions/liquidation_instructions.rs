@@ -1,27 +1,86 @@
 use crate::constants::*;
 use crate::error::LendingError;
+use crate::events::{emit_integrator_attribution, LiquidationEvent};
+use crate::instructions::batch_operations::error_code_of;
+use crate::instructions::keeper_instructions::check_keeper_priority;
+use crate::instructions::liquidation_queue_instructions::dequeue_obligation;
+use crate::instructions::metrics_instructions::record_liquidation_metric;
 use crate::state::*;
-use crate::utils::{math::Decimal, OracleManager, TokenUtils};
+use crate::utils::{
+    lock_reserve, math::Decimal, ComputeBudgetGuard, EventType, OracleManager, OraclePrice,
+    ProtocolConfig, ProtocolMetrics, SwapExecutor, TokenUtils, PROTOCOL_METRICS_SEED,
+};
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 /// Liquidate an unhealthy obligation
 pub fn liquidate_obligation(
     ctx: Context<LiquidateObligation>,
     liquidity_amount: u64,
-) -> Result<()> {
+    integrator_id: Option<u32>,
+) -> Result<OperationReceipt> {
+    liquidate(ctx, liquidity_amount, None, integrator_id, false)
+}
+
+/// Liquidate an unhealthy obligation's dust borrow in full
+/// A borrow worth less than the protocol's minimum position value may be repaid in one
+/// liquidation regardless of the close factor, so no remainder is left that is too small
+/// for any liquidator to close profitably.
+pub fn liquidate_dust_position(
+    ctx: Context<LiquidateObligation>,
+    liquidity_amount: u64,
+    integrator_id: Option<u32>,
+) -> Result<OperationReceipt> {
+    liquidate(ctx, liquidity_amount, None, integrator_id, true)
+}
+
+/// Liquidate an obligation by revealing a commitment made with `commit_liquidation`
+/// The liquidation uses the prices pinned at commit instead of the current oracle
+/// prices, and closes the commitment.
+pub fn execute_liquidation(
+    ctx: Context<LiquidateObligation>,
+    liquidity_amount: u64,
+    salt: [u8; 32],
+    integrator_id: Option<u32>,
+) -> Result<OperationReceipt> {
+    liquidate(ctx, liquidity_amount, Some(salt), integrator_id, false)
+}
+
+/// Shared body of `liquidate_obligation`, `liquidate_dust_position` and
+/// `execute_liquidation`
+/// `salt` reveals the liquidation commitment passed with the accounts, if any. `dust`
+/// lifts the close factor for a borrow below the minimum position value.
+fn liquidate(
+    ctx: Context<LiquidateObligation>,
+    liquidity_amount: u64,
+    salt: Option<[u8; 32]>,
+    integrator_id: Option<u32>,
+    dust: bool,
+) -> Result<OperationReceipt> {
     let market = &ctx.accounts.market;
-    let obligation = &mut ctx.accounts.obligation;
+    let mut obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market.key())?;
     let repay_reserve = &mut ctx.accounts.repay_reserve;
     let withdraw_reserve = &mut ctx.accounts.withdraw_reserve;
     let clock = Clock::get()?;
 
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(
+        &ctx.accounts.flash_loan_guard,
+        FlashLoanUsage::LIQUIDATE,
+    )?;
+
+    // Bound the reserve/oracle accounts used to refresh the obligation
+    ctx.accounts
+        .config
+        .validate_account_count(ctx.remaining_accounts.len())?;
+
     // Check if market allows liquidations
     if market.is_paused() || market.is_liquidation_disabled() {
         return Err(LendingError::MarketPaused.into());
     }
 
-    // Check if reserves allow liquidations
+    // Check if reserves allow liquidations; auctioned collateral is not sold at the
+    // fixed bonus
     if repay_reserve
         .config
         .flags
@@ -30,6 +89,10 @@ pub fn liquidate_obligation(
             .config
             .flags
             .contains(ReserveConfigFlags::LIQUIDATIONS_DISABLED)
+        || withdraw_reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::AUCTION_LIQUIDATIONS)
     {
         return Err(LendingError::FeatureDisabled.into());
     }
@@ -39,119 +102,579 @@ pub fn liquidate_obligation(
         return Err(LendingError::AmountTooSmall.into());
     }
 
-    // Lock reserves to prevent race conditions during liquidation
-    repay_reserve.try_lock()?;
-    withdraw_reserve.try_lock()?;
+    // Newly unhealthy obligations are reserved for registered keepers
+    check_keeper_priority(
+        &ctx.accounts.keeper_registry,
+        ctx.accounts.keeper.as_mut(),
+        &ctx.accounts.config,
+        obligation.slots_unhealthy(clock.slot),
+    )?;
 
-    // Ensure we unlock on any error path
-    let result = (|| -> Result<()> {
-        // Refresh reserves with locked state
-        repay_reserve.update_interest(clock.slot)?;
-        withdraw_reserve.update_interest(clock.slot)?;
+    // eMode parameters apply while every position is within the obligation's category
+    let emode = EModeCategory::resolve(ctx.accounts.emode_category.as_deref(), &obligation, None)?;
+
+    let commitment = ctx.accounts.liquidation_commitment.as_deref();
+    let (repay_price, withdraw_price) = match (commitment, salt) {
+        // A revealed commitment liquidates at the prices pinned at commit
+        (Some(commitment), Some(salt)) => {
+            commitment.check_reveal(liquidity_amount, &salt, clock.slot)?;
+            commitment.repay_price.validate(clock.unix_timestamp)?;
+            commitment.withdraw_price.validate(clock.unix_timestamp)?;
+            (commitment.repay_price, commitment.withdraw_price)
+        }
+        (None, None) => {
+            // Get current prices from oracles using proper feed IDs from reserves
+            let repay_price = OracleManager::get_checked_price(
+                &ctx.accounts.repay_price_oracle.to_account_info(),
+                ctx.accounts.repay_secondary_price_oracle.as_deref(),
+                repay_reserve,
+            )?;
+            repay_price.validate(clock.unix_timestamp)?;
+
+            let withdraw_price = OracleManager::get_checked_price(
+                &ctx.accounts.withdraw_price_oracle.to_account_info(),
+                ctx.accounts.withdraw_secondary_price_oracle.as_deref(),
+                withdraw_reserve,
+            )?;
+            withdraw_price.validate(clock.unix_timestamp)?;
+
+            // Smooth both prices against their TWAPs in the borrower's favour
+            (
+                repay_reserve.liquidation_debt_price(&repay_price),
+                withdraw_reserve.liquidation_collateral_price(&withdraw_price),
+            )
+        }
+        _ => return Err(LendingError::LiquidationCommitmentMismatch.into()),
+    };
 
-        // Refresh obligation with current prices to get accurate health factor
-        obligation.refresh_health_factor(&ctx.remaining_accounts, clock.unix_timestamp)?;
+    // Lock reserves, refresh them and confirm the obligation is unhealthy
+    begin_liquidation(
+        &mut obligation,
+        repay_reserve,
+        withdraw_reserve,
+        &repay_price,
+        &withdraw_price,
+        ctx.remaining_accounts,
+        &clock,
+        emode.as_ref(),
+    )?;
 
-        // Atomic health check - capture health factor at exact moment of liquidation
-        let health_factor = obligation.calculate_health_factor()?;
-        if health_factor >= Decimal::one() {
-            return Err(LendingError::ObligationHealthy.into());
-        }
+    // Value the repayment and the collateral it seizes (with bonus)
+    let close_factor_bps = liquidation_close_factor_bps(
+        &obligation,
+        repay_reserve,
+        &ctx.accounts.config,
+        emode.as_ref(),
+        dust,
+    )?;
+    let (repay_value_usd, collateral_amount) = quote_liquidation(
+        &obligation,
+        repay_reserve,
+        withdraw_reserve,
+        &repay_price,
+        &withdraw_price,
+        liquidity_amount,
+        close_factor_bps,
+        emode.as_ref(),
+    )?;
 
-        // Store health snapshot to prevent manipulation during liquidation
-        obligation.liquidation_snapshot_health_factor = Some(health_factor);
+    // Match standing collateral orders before the external liquidator
+    let (order_liquidity, order_collateral) = match_collateral_orders(
+        &mut ctx.accounts.collateral_order_book,
+        &ctx.accounts.order_book_liquidity_escrow,
+        &ctx.accounts.order_book_collateral_escrow,
+        &withdraw_reserve.key(),
+        &repay_reserve.key(),
+        repay_value_usd,
+        liquidity_amount,
+        collateral_amount,
+    )?;
+    let liquidator_liquidity = liquidity_amount
+        .checked_sub(order_liquidity)
+        .ok_or(LendingError::MathUnderflow)?;
+    let liquidator_collateral = collateral_amount
+        .checked_sub(order_collateral)
+        .ok_or(LendingError::MathUnderflow)?;
+
+    // Part of the liquidator's bonus is redeemed into the withdraw reserve's insurance
+    // fund. A fee too small to redeem any liquidity stays with the liquidator; one the
+    // reserve lacks the free liquidity to redeem accrues as a protocol fee instead, so
+    // the fee never blocks a liquidation.
+    let liquidation_bonus_bps = match emode.as_ref() {
+        Some(emode) => emode.liquidation_bonus_bps,
+        None => withdraw_reserve.config.liquidation_penalty_bps,
+    };
+    let mut protocol_fee_collateral =
+        withdraw_reserve.liquidation_protocol_fee(liquidator_collateral, liquidation_bonus_bps)?;
+    let protocol_fee_liquidity =
+        withdraw_reserve.collateral_to_liquidity(protocol_fee_collateral)?;
+    if protocol_fee_liquidity == 0 {
+        protocol_fee_collateral = 0;
+    }
+    let liquidator_collateral = liquidator_collateral
+        .checked_sub(protocol_fee_collateral)
+        .ok_or(LendingError::MathUnderflow)?;
+
+    // Transfer repayment from liquidator to reserve. The liquidator covers the transfer
+    // fees of both their own leg and the order book leg, so the reserve receives the full
+    // repayment.
+    let order_transfer_fee =
+        TokenUtils::transfer_fee(&ctx.accounts.repay_liquidity_mint, order_liquidity)?;
+    let liquidator_transfer_amount = TokenUtils::amount_before_transfer_fee(
+        &ctx.accounts.repay_liquidity_mint,
+        liquidator_liquidity
+            .checked_add(order_transfer_fee)
+            .ok_or(LendingError::MathOverflow)?,
+    )?;
+    if liquidator_transfer_amount > 0 {
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.source_liquidity,
+            &ctx.accounts.repay_reserve_liquidity_supply,
+            &ctx.accounts.repay_liquidity_mint,
+            &ctx.accounts.liquidator.to_account_info(),
+            &[],
+            liquidator_transfer_amount,
+        )?;
+    }
 
-        Ok(())
-    })();
+    let collateral_authority_seeds = &[
+        COLLATERAL_TOKEN_SEED,
+        withdraw_reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.withdraw_collateral_supply_authority],
+    ];
 
-    // Unlock reserves regardless of result
-    if result.is_err() {
-        let _ = repay_reserve.unlock();
-        let _ = withdraw_reserve.unlock();
-        return result;
+    // Settle the portion filled by standing orders from the order book escrow
+    if order_liquidity > 0 {
+        if let (Some(order_book), Some(liquidity_escrow), Some(collateral_escrow)) = (
+            ctx.accounts.collateral_order_book.as_ref(),
+            ctx.accounts.order_book_liquidity_escrow.as_ref(),
+            ctx.accounts.order_book_collateral_escrow.as_ref(),
+        ) {
+            let book_seeds = &[
+                COLLATERAL_ORDER_BOOK_SEED,
+                order_book.collateral_reserve.as_ref(),
+                order_book.payment_reserve.as_ref(),
+                &[order_book.bump],
+            ];
+
+            TokenUtils::transfer_tokens(
+                &ctx.accounts.token_program,
+                liquidity_escrow,
+                &ctx.accounts.repay_reserve_liquidity_supply,
+                &ctx.accounts.repay_liquidity_mint,
+                &order_book.to_account_info(),
+                &[book_seeds],
+                order_liquidity,
+            )?;
+
+            TokenUtils::transfer_tokens(
+                &ctx.accounts.collateral_token_program,
+                &ctx.accounts.withdraw_reserve_collateral_supply,
+                collateral_escrow,
+                &ctx.accounts.withdraw_collateral_mint,
+                &ctx.accounts
+                    .withdraw_collateral_supply_authority
+                    .to_account_info(),
+                &[collateral_authority_seeds],
+                order_collateral,
+            )?;
+        }
     }
 
-    // Validate that the borrow exists
-    let _borrow = obligation
+    // Transfer collateral from reserve to liquidator
+    if liquidator_collateral > 0 {
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.collateral_token_program,
+            &ctx.accounts.withdraw_reserve_collateral_supply,
+            &ctx.accounts.destination_collateral,
+            &ctx.accounts.withdraw_collateral_mint,
+            &ctx.accounts
+                .withdraw_collateral_supply_authority
+                .to_account_info(),
+            &[collateral_authority_seeds],
+            liquidator_collateral,
+        )?;
+    }
+
+    // Burn the protocol's share of the bonus, then redeem it into the insurance fund or,
+    // when the reserve lacks the free liquidity, accrue it as a protocol fee
+    let mut protocol_fee_received = 0;
+    if protocol_fee_collateral > 0 {
+        TokenUtils::burn_tokens(
+            &ctx.accounts.collateral_token_program,
+            &ctx.accounts.withdraw_collateral_mint,
+            &ctx.accounts.withdraw_reserve_collateral_supply,
+            &ctx.accounts
+                .withdraw_collateral_supply_authority
+                .to_account_info(),
+            &[collateral_authority_seeds],
+            protocol_fee_collateral,
+        )?;
+
+        if withdraw_reserve.state.available_liquidity < protocol_fee_liquidity {
+            withdraw_reserve
+                .accrue_liquidation_protocol_fee(protocol_fee_collateral, protocol_fee_liquidity)?;
+
+            msg!(
+                "Liquidation protocol fee - {} collateral accrued as {} of protocol fees",
+                protocol_fee_collateral,
+                protocol_fee_liquidity
+            );
+        } else {
+            let (Some(liquidity_mint), Some(liquidity_supply), Some(insurance_fund), Some(vault)) = (
+                ctx.accounts.withdraw_liquidity_mint.as_ref(),
+                ctx.accounts.withdraw_reserve_liquidity_supply.as_ref(),
+                ctx.accounts.withdraw_insurance_fund.as_mut(),
+                ctx.accounts.withdraw_insurance_fund_vault.as_mut(),
+            ) else {
+                return Err(LendingError::InvalidAccount.into());
+            };
+            if vault.key() != insurance_fund.liquidity_vault {
+                return Err(LendingError::InvalidAccount.into());
+            }
+
+            let liquidity_authority_seeds = &[
+                LIQUIDITY_TOKEN_SEED,
+                withdraw_reserve.liquidity_mint.as_ref(),
+                b"authority",
+                &[ctx.bumps.withdraw_liquidity_supply_authority],
+            ];
+            let vault_before = vault.amount;
+            TokenUtils::transfer_tokens(
+                &ctx.accounts.collateral_token_program,
+                liquidity_supply,
+                vault,
+                liquidity_mint,
+                &ctx.accounts
+                    .withdraw_liquidity_supply_authority
+                    .to_account_info(),
+                &[liquidity_authority_seeds],
+                protocol_fee_liquidity,
+            )?;
+            // The fund is credited with what reached its vault, net of any transfer fee
+            vault.reload()?;
+            protocol_fee_received = vault
+                .amount
+                .checked_sub(vault_before)
+                .ok_or(LendingError::MathUnderflow)?;
+
+            withdraw_reserve.remove_liquidity(protocol_fee_liquidity)?;
+            withdraw_reserve.state.collateral_mint_supply = withdraw_reserve
+                .state
+                .collateral_mint_supply
+                .checked_sub(protocol_fee_collateral)
+                .ok_or(LendingError::MathUnderflow)?;
+            insurance_fund.record_deposit(protocol_fee_received)?;
+
+            msg!(
+                "Liquidation protocol fee - {} collateral redeemed for {} to the insurance fund",
+                protocol_fee_collateral,
+                protocol_fee_received
+            );
+        }
+    }
+
+    // Debt being repaid, for the borrower's statistics
+    let repaid_debt = obligation
         .find_liquidity_borrow(&repay_reserve.key())
-        .ok_or(LendingError::ObligationReserveNotFound)?;
+        .ok_or(LendingError::ObligationReserveNotFound)?
+        .borrowed_amount_wads
+        .try_floor_u64()?;
 
-    // Check maximum liquidation amount (usually 50% of debt)
-    let max_liquidation = obligation.max_liquidation_amount(&repay_reserve.key())?;
-    if liquidity_amount > max_liquidation {
-        return Err(LendingError::LiquidationTooLarge.into());
+    // Update reserves and obligation
+    let collateral_value_usd = settle_liquidation(
+        &mut obligation,
+        repay_reserve,
+        withdraw_reserve,
+        &withdraw_price,
+        liquidity_amount,
+        repay_value_usd,
+        collateral_amount,
+        clock.slot,
+    )?;
+
+    // Calculate liquidation bonus for logging
+    let bonus_amount =
+        liquidation_bonus_amount(repay_value_usd, &withdraw_price, collateral_amount)?;
+
+    msg!(
+        "Liquidation completed - repaid: {} (${:.2}), seized: {} (${:.2}), bonus: {}",
+        liquidity_amount,
+        repay_value_usd.try_floor_u64()? as f64 / 1e18,
+        collateral_amount,
+        collateral_value_usd.try_floor_u64()? as f64 / 1e18,
+        bonus_amount
+    );
+
+    if order_liquidity > 0 {
+        msg!(
+            "Standing collateral orders filled - paid: {}, collateral: {}",
+            order_liquidity,
+            order_collateral
+        );
     }
 
-    // Validate that collateral exists
-    let collateral = obligation
-        .find_collateral_deposit(&withdraw_reserve.key())
-        .ok_or(LendingError::ObligationReserveNotFound)?;
+    emit_integrator_attribution(
+        integrator_id,
+        EventType::LiquidationExecuted,
+        ctx.accounts.liquidator.key(),
+        repay_reserve.key(),
+        liquidity_amount,
+        clock.slot,
+    );
+
+    // Clear liquidation snapshot as liquidation is complete
+    let snapshot_health_factor = obligation.liquidation_snapshot_health_factor.take();
+
+    // Unlock reserves after successful liquidation
+    repay_reserve.unlock()?;
+    withdraw_reserve.unlock()?;
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    // Delist the obligation until a refresh finds it unhealthy again
+    dequeue_obligation(
+        ctx.accounts.liquidation_queue.as_mut(),
+        &ctx.accounts.obligation.key(),
+    );
+    record_liquidation_metric(ctx.accounts.protocol_metrics.as_mut(), clock.unix_timestamp)?;
+
+    if let Some(user_stats) = ctx.accounts.user_stats.as_mut() {
+        user_stats.validate_owner(&market.key(), &obligation.owner)?;
+        let interest = user_stats.settle_repayment(
+            &ctx.accounts.obligation.key(),
+            &repay_reserve.key(),
+            repaid_debt,
+            liquidity_amount,
+        );
+        user_stats.record_interest_paid(
+            OracleManager::calculate_usd_value(
+                interest,
+                &repay_price,
+                repay_reserve.config.decimals,
+            )?,
+            clock.slot,
+        )?;
+        user_stats.record_liquidation(clock.slot);
+    }
+
+    let receipt = OperationReceipt::new(
+        repay_reserve.key(),
+        repay_reserve,
+        liquidity_amount,
+        collateral_amount,
+        bonus_amount,
+        Some(obligation.calculate_health_factor_with(emode.as_ref())?),
+    )?;
+
+    emit!(LiquidationEvent {
+        obligation: ctx.accounts.obligation.key(),
+        liquidator: ctx.accounts.liquidator.key(),
+        withdraw_reserve: withdraw_reserve.key(),
+        health_factor_bps: snapshot_health_factor.map_or(0, Decimal::to_health_bps_saturating),
+        receipt,
+        protocol_fee_collateral,
+        protocol_fee_liquidity: protocol_fee_received,
+        slot: clock.slot,
+    });
+
+    Ok(receipt)
+}
+
+/// Liquidate with any market asset, swapping it into the repay asset in one transaction
+/// The liquidator's input tokens are routed through a whitelisted DEX into their repay
+/// asset account, which then repays the debt exactly as `liquidate_obligation` would.
+/// With `swap_out`, the seized collateral is redeemed for the underlying asset and
+/// swapped into the liquidator's output account, so no inventory is needed at all.
+/// Remaining accounts are the obligation refresh accounts followed by the swap-in
+/// route and, when present, the swap-out route.
+pub fn liquidate_with_swap<'info>(
+    ctx: Context<'_, '_, '_, 'info, LiquidateWithSwap<'info>>,
+    params: LiquidateWithSwapParams,
+) -> Result<()> {
+    let market_key = ctx.accounts.market.key();
+    let mut obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market_key)?;
+    let clock = Clock::get()?;
+
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(
+        &ctx.accounts.flash_loan_guard,
+        FlashLoanUsage::LIQUIDATE,
+    )?;
+
+    ctx.accounts
+        .config
+        .validate_account_count(ctx.remaining_accounts.len())?;
+    SwapExecutor::validate_program(
+        &ctx.accounts.swap_program.key(),
+        &ctx.accounts.fee_converter.swap_programs,
+    )?;
+
+    // Check if market allows liquidations
+    if ctx.accounts.market.is_paused() || ctx.accounts.market.is_liquidation_disabled() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    // Check if reserves allow liquidations; auctioned collateral is not sold at the
+    // fixed bonus
+    if ctx
+        .accounts
+        .repay_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::LIQUIDATIONS_DISABLED)
+        || ctx
+            .accounts
+            .withdraw_reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::LIQUIDATIONS_DISABLED)
+        || ctx
+            .accounts
+            .withdraw_reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::AUCTION_LIQUIDATIONS)
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    if params.liquidity_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    // Newly unhealthy obligations are reserved for registered keepers
+    check_keeper_priority(
+        &ctx.accounts.keeper_registry,
+        ctx.accounts.keeper.as_mut(),
+        &ctx.accounts.config,
+        obligation.slots_unhealthy(clock.slot),
+    )?;
+
+    // Split the remaining accounts into refresh accounts and swap routes
+    let swap_in_len = params.swap_in_accounts as usize;
+    let swap_out_len = params
+        .swap_out
+        .as_ref()
+        .map_or(0, |leg| leg.route_accounts as usize);
+    let refresh_len = ctx
+        .remaining_accounts
+        .len()
+        .checked_sub(swap_in_len + swap_out_len)
+        .ok_or(LendingError::InvalidAccount)?;
+    let (refresh_accounts, routes) = ctx.remaining_accounts.split_at(refresh_len);
+    let (swap_in_route, swap_out_route) = routes.split_at(swap_in_len);
+
+    // The repayment is grossed up so the reserve receives it in full after transfer fees
+    let repay_transfer_amount = TokenUtils::amount_before_transfer_fee(
+        &ctx.accounts.repay_liquidity_mint,
+        params.liquidity_amount,
+    )?;
 
-    // Get current prices from oracles using proper feed IDs from reserves
-    let repay_price = OracleManager::get_pyth_price(
+    // Step 1: swap the input asset into exactly enough repay asset. Input already in
+    // the repay asset is netted: it repays directly without touching the DEX.
+    let net_swap_in = ctx.accounts.source_input.mint == ctx.accounts.repay_liquidity.mint;
+    let swap_in = if net_swap_in {
+        SwapExecutor::net(
+            swap_in_route,
+            repay_transfer_amount,
+            params.max_amount_in,
+            repay_transfer_amount,
+        )?
+    } else {
+        SwapExecutor::execute(
+            &ctx.accounts.swap_program.to_account_info(),
+            swap_in_route,
+            params.swap_in_data,
+            None,
+            &[],
+            &mut ctx.accounts.source_input,
+            &mut ctx.accounts.repay_liquidity,
+            params.max_amount_in,
+            repay_transfer_amount,
+        )?
+    };
+    let repay_source = if net_swap_in {
+        &ctx.accounts.source_input
+    } else {
+        &ctx.accounts.repay_liquidity
+    };
+
+    // Step 2: liquidate with the swapped liquidity
+    let repay_reserve = &mut ctx.accounts.repay_reserve;
+    let withdraw_reserve = &mut ctx.accounts.withdraw_reserve;
+
+    let emode = EModeCategory::resolve(ctx.accounts.emode_category.as_deref(), &obligation, None)?;
+
+    let repay_price = OracleManager::get_checked_price(
         &ctx.accounts.repay_price_oracle.to_account_info(),
-        &repay_reserve.oracle_feed_id, // Use actual feed ID from reserve config
+        ctx.accounts.repay_secondary_price_oracle.as_deref(),
+        repay_reserve,
     )?;
     repay_price.validate(clock.unix_timestamp)?;
 
-    let withdraw_price = OracleManager::get_pyth_price(
+    let withdraw_price = OracleManager::get_checked_price(
         &ctx.accounts.withdraw_price_oracle.to_account_info(),
-        &withdraw_reserve.oracle_feed_id, // Use actual feed ID from reserve config
+        ctx.accounts.withdraw_secondary_price_oracle.as_deref(),
+        withdraw_reserve,
     )?;
     withdraw_price.validate(clock.unix_timestamp)?;
 
-    // Calculate USD values
-    let repay_value_usd = OracleManager::calculate_usd_value(
-        liquidity_amount,
+    let repay_price = repay_reserve.liquidation_debt_price(&repay_price);
+    let withdraw_price = withdraw_reserve.liquidation_collateral_price(&withdraw_price);
+
+    begin_liquidation(
+        &mut obligation,
+        repay_reserve,
+        withdraw_reserve,
         &repay_price,
-        repay_reserve.config.decimals,
+        &withdraw_price,
+        refresh_accounts,
+        &clock,
+        emode.as_ref(),
     )?;
 
-    // Calculate collateral amount to liquidate (with bonus)
-    let liquidation_bonus_decimal = Decimal::from_scaled_val(
-        (withdraw_reserve.config.liquidation_penalty_bps as u128)
-            .checked_add(BASIS_POINTS_PRECISION as u128)
-            .ok_or(LendingError::MathOverflow)?
-            .checked_mul(PRECISION as u128)
-            .ok_or(LendingError::MathOverflow)?
-            .checked_div(BASIS_POINTS_PRECISION as u128)
-            .ok_or(LendingError::DivisionByZero)?,
-    );
-
-    let liquidation_value_usd = repay_value_usd.try_mul(liquidation_bonus_decimal)?;
-
-    // Convert USD value to collateral token amount
-    let collateral_price_decimal = withdraw_price.to_decimal()?;
-    let collateral_amount_decimal = liquidation_value_usd.try_div(collateral_price_decimal)?;
-    let collateral_amount = collateral_amount_decimal.try_floor_u64()?;
-
-    // Validate sufficient collateral
-    if collateral.deposited_amount < collateral_amount {
-        return Err(LendingError::InsufficientCollateral.into());
-    }
+    let close_factor_bps = liquidation_close_factor_bps(
+        &obligation,
+        repay_reserve,
+        &ctx.accounts.config,
+        emode.as_ref(),
+        false,
+    )?;
+    let (repay_value_usd, collateral_amount) = quote_liquidation(
+        &obligation,
+        repay_reserve,
+        withdraw_reserve,
+        &repay_price,
+        &withdraw_price,
+        params.liquidity_amount,
+        close_factor_bps,
+        emode.as_ref(),
+    )?;
 
-    // Transfer repayment from liquidator to reserve
     TokenUtils::transfer_tokens(
         &ctx.accounts.token_program,
-        &ctx.accounts.source_liquidity,
+        repay_source,
         &ctx.accounts.repay_reserve_liquidity_supply,
+        &ctx.accounts.repay_liquidity_mint,
         &ctx.accounts.liquidator.to_account_info(),
         &[],
-        liquidity_amount,
+        repay_transfer_amount,
     )?;
 
-    // Transfer collateral from reserve to liquidator
     let collateral_authority_seeds = &[
         COLLATERAL_TOKEN_SEED,
         withdraw_reserve.liquidity_mint.as_ref(),
         b"authority",
         &[ctx.bumps.withdraw_collateral_supply_authority],
     ];
-
     TokenUtils::transfer_tokens(
-        &ctx.accounts.token_program,
+        &ctx.accounts.collateral_token_program,
         &ctx.accounts.withdraw_reserve_collateral_supply,
         &ctx.accounts.destination_collateral,
+        &ctx.accounts.withdraw_collateral_mint,
         &ctx.accounts
             .withdraw_collateral_supply_authority
             .to_account_info(),
@@ -159,61 +682,173 @@ pub fn liquidate_obligation(
         collateral_amount,
     )?;
 
-    // Update reserves
-    repay_reserve.repay_borrow(liquidity_amount)?;
-
-    // Update obligation
-    obligation.repay_liquidity_borrow(
-        &repay_reserve.key(),
-        Decimal::from_integer(liquidity_amount)?,
+    let collateral_value_usd = settle_liquidation(
+        &mut obligation,
+        repay_reserve,
+        withdraw_reserve,
+        &withdraw_price,
+        params.liquidity_amount,
+        repay_value_usd,
+        collateral_amount,
+        clock.slot,
     )?;
 
-    obligation.remove_collateral_deposit(&withdraw_reserve.key(), collateral_amount)?;
+    // Step 3: optionally redeem the seized collateral and swap it out
+    if let Some(leg) = params.swap_out {
+        let (liquidity_mint, liquidity_supply, output) = match (
+            ctx.accounts.withdraw_liquidity_mint.as_ref(),
+            ctx.accounts.withdraw_reserve_liquidity_supply.as_ref(),
+            ctx.accounts.swap_output.as_mut(),
+        ) {
+            (Some(mint), Some(supply), Some(output)) => (mint, supply, output),
+            _ => return Err(LendingError::InvalidAccount.into()),
+        };
+
+        // Output in the collateral's own asset is netted: the redeemed liquidity is
+        // paid straight into it without touching the DEX
+        let net_swap_out = output.mint == withdraw_reserve.liquidity_mint;
+
+        if withdraw_reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::WITHDRAWALS_DISABLED)
+        {
+            return Err(LendingError::FeatureDisabled.into());
+        }
 
-    // Update cached USD values
-    obligation.borrowed_value_usd = obligation.borrowed_value_usd.try_sub(repay_value_usd)?;
+        let redeemed_liquidity = withdraw_reserve.collateral_to_liquidity(collateral_amount)?;
+        if ctx
+            .accounts
+            .config
+            .is_large_withdrawal(redeemed_liquidity, withdraw_reserve.state.total_liquidity)?
+        {
+            return Err(LendingError::LargeWithdrawalRequestRequired.into());
+        }
 
-    let collateral_value_usd = OracleManager::calculate_usd_value(
-        collateral_amount,
-        &withdraw_price,
-        withdraw_reserve.config.decimals,
-    )?;
+        TokenUtils::burn_tokens(
+            &ctx.accounts.collateral_token_program,
+            &ctx.accounts.withdraw_collateral_mint,
+            &ctx.accounts.destination_collateral,
+            &ctx.accounts.liquidator.to_account_info(),
+            &[],
+            collateral_amount,
+        )?;
+
+        let liquidity_authority_seeds = &[
+            LIQUIDITY_TOKEN_SEED,
+            withdraw_reserve.liquidity_mint.as_ref(),
+            b"authority",
+            &[ctx.bumps.withdraw_liquidity_supply_authority],
+        ];
+        let redeem_destination = match (net_swap_out, ctx.accounts.withdrawn_liquidity.as_ref()) {
+            (true, _) => &*output,
+            (false, Some(withdrawn)) => withdrawn,
+            (false, None) => return Err(LendingError::InvalidAccount.into()),
+        };
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.collateral_token_program,
+            liquidity_supply,
+            redeem_destination,
+            liquidity_mint,
+            &ctx.accounts
+                .withdraw_liquidity_supply_authority
+                .to_account_info(),
+            &[liquidity_authority_seeds],
+            redeemed_liquidity,
+        )?;
+
+        withdraw_reserve.remove_liquidity(redeemed_liquidity)?;
+        withdraw_reserve.state.collateral_mint_supply = withdraw_reserve
+            .state
+            .collateral_mint_supply
+            .checked_sub(collateral_amount)
+            .ok_or(LendingError::MathUnderflow)?;
+
+        // The liquidator receives the redeemed liquidity less the mint's transfer fee
+        let received_liquidity =
+            TokenUtils::amount_after_transfer_fee(liquidity_mint, redeemed_liquidity)?;
+
+        let swap_out = if net_swap_out {
+            SwapExecutor::net(
+                swap_out_route,
+                received_liquidity,
+                received_liquidity,
+                leg.min_amount_out,
+            )?
+        } else {
+            let withdrawn_liquidity = ctx
+                .accounts
+                .withdrawn_liquidity
+                .as_mut()
+                .ok_or(LendingError::InvalidAccount)?;
+            SwapExecutor::execute(
+                &ctx.accounts.swap_program.to_account_info(),
+                swap_out_route,
+                leg.route_data,
+                None,
+                &[],
+                withdrawn_liquidity,
+                output,
+                received_liquidity,
+                leg.min_amount_out,
+            )?
+        };
+
+        msg!(
+            "Seized collateral redeemed for {} and swapped into {}",
+            redeemed_liquidity,
+            swap_out.amount_out
+        );
+    }
 
-    obligation.deposited_value_usd = obligation
-        .deposited_value_usd
-        .try_sub(collateral_value_usd)?;
+    // Clear liquidation snapshot as liquidation is complete
+    let snapshot_health_factor = obligation.liquidation_snapshot_health_factor.take();
+
+    repay_reserve.unlock()?;
+    withdraw_reserve.unlock()?;
 
-    obligation.update_timestamp(clock.slot);
+    obligation.save(&ctx.accounts.obligation)?;
+
+    // Delist the obligation until a refresh finds it unhealthy again
+    dequeue_obligation(
+        ctx.accounts.liquidation_queue.as_mut(),
+        &ctx.accounts.obligation.key(),
+    );
+    record_liquidation_metric(ctx.accounts.protocol_metrics.as_mut(), clock.unix_timestamp)?;
 
-    // Calculate liquidation bonus for logging with proper error handling
+    // Bonus in collateral tokens, valued as `liquidate_obligation` reports it
     let expected_collateral = repay_value_usd
         .try_div(withdraw_price.to_decimal()?)?
         .try_floor_u64()?;
+    let receipt = OperationReceipt::new(
+        repay_reserve.key(),
+        repay_reserve,
+        params.liquidity_amount,
+        collateral_amount,
+        collateral_amount.saturating_sub(expected_collateral),
+        Some(obligation.calculate_health_factor_with(emode.as_ref())?),
+    )?;
 
-    let bonus_amount = if collateral_amount > expected_collateral {
-        collateral_amount.saturating_sub(expected_collateral)
-    } else {
-        // This shouldn't happen in a proper liquidation, log warning
-        msg!("Warning: Liquidation bonus calculation resulted in negative value");
-        0
-    };
+    emit!(LiquidationEvent {
+        obligation: ctx.accounts.obligation.key(),
+        liquidator: ctx.accounts.liquidator.key(),
+        withdraw_reserve: withdraw_reserve.key(),
+        health_factor_bps: snapshot_health_factor.map_or(0, Decimal::to_health_bps_saturating),
+        receipt,
+        protocol_fee_collateral: 0,
+        protocol_fee_liquidity: 0,
+        slot: clock.slot,
+    });
 
     msg!(
-        "Liquidation completed - repaid: {} (${:.2}), seized: {} (${:.2}), bonus: {}",
-        liquidity_amount,
+        "Swap liquidation completed - input spent: {}, repaid: {} (${:.2}), seized: {} (${:.2})",
+        swap_in.amount_in,
+        params.liquidity_amount,
         repay_value_usd.try_floor_u64()? as f64 / 1e18,
         collateral_amount,
-        collateral_value_usd.try_floor_u64()? as f64 / 1e18,
-        bonus_amount
+        collateral_value_usd.try_floor_u64()? as f64 / 1e18
     );
 
-    // Clear liquidation snapshot as liquidation is complete
-    obligation.liquidation_snapshot_health_factor = None;
-
-    // Unlock reserves after successful liquidation
-    repay_reserve.unlock()?;
-    withdraw_reserve.unlock()?;
-
     Ok(())
 }
 
@@ -223,13 +858,15 @@ pub fn flash_liquidate_obligation(
     liquidity_amount: u64,
 ) -> Result<()> {
     let _market = &ctx.accounts.market;
-    let obligation = &mut ctx.accounts.obligation;
+    let obligation =
+        AnyObligation::load_checked(&ctx.accounts.obligation, &ctx.accounts.market.key())?;
     let flash_loan_reserve = &mut ctx.accounts.flash_loan_reserve;
     let _repay_reserve = &mut ctx.accounts.repay_reserve;
     let _withdraw_reserve = &mut ctx.accounts.withdraw_reserve;
-    let _clock = Clock::get()?;
+    let clock = Clock::get()?;
 
-    // Check if obligation is unhealthy
+    // Check if obligation is unhealthy, at values from a refresh in this slot
+    obligation.require_refreshed(clock.slot)?;
     if obligation.is_healthy()? {
         return Err(LendingError::ObligationHealthy.into());
     }
@@ -262,6 +899,7 @@ pub fn flash_liquidate_obligation(
         &ctx.accounts.token_program,
         &ctx.accounts.flash_loan_reserve_liquidity_supply,
         &ctx.accounts.flash_loan_destination,
+        &ctx.accounts.flash_loan_liquidity_mint,
         &ctx.accounts.flash_loan_reserve_authority.to_account_info(),
         &[flash_loan_authority_seeds],
         liquidity_amount,
@@ -292,6 +930,7 @@ pub fn flash_liquidate_obligation(
         &ctx.accounts.token_program,
         &ctx.accounts.flash_loan_source,
         &ctx.accounts.flash_loan_reserve_liquidity_supply,
+        &ctx.accounts.flash_loan_liquidity_mint,
         &ctx.accounts.liquidator.to_account_info(),
         &[],
         total_repayment,
@@ -319,86 +958,806 @@ pub fn flash_liquidate_obligation(
     Ok(())
 }
 
-/// Batch liquidate multiple unhealthy obligations
-pub fn batch_liquidate_obligations(
-    ctx: Context<BatchLiquidateObligations>,
+/// Liquidate several unhealthy obligations in one transaction
+/// Each entry's accounts are passed as a group of `BATCH_LIQUIDATION_ENTRY_ACCOUNTS`
+/// remaining accounts (see `BatchLiquidationEntry::load`) followed by its
+/// `refresh_account_count` obligation refresh accounts. An entry that fails validation,
+/// is healthy or would seize less than `min_collateral_amount` is skipped and reported
+/// without affecting the others; once its tokens move, a failure aborts the whole batch.
+/// Entries that no longer fit in the remaining compute units are skipped the same way.
+/// Entries needing an eMode category, a secondary oracle or the order book are reported
+/// as failures and must be liquidated with `liquidate_obligation`.
+pub fn batch_liquidate_obligations<'info>(
+    ctx: Context<'_, '_, '_, 'info, BatchLiquidateObligations<'info>>,
     liquidation_params: Vec<LiquidationParams>,
-) -> Result<()> {
-    let _market = &ctx.accounts.market;
+) -> Result<Vec<BatchLiquidationResult>> {
+    let market = &ctx.accounts.market;
+    let clock = Clock::get()?;
+
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(&ctx.accounts.flash_loan_guard, FlashLoanUsage::LIQUIDATE)?;
+
+    if liquidation_params.len() > MAX_BATCH_LIQUIDATIONS {
+        return Err(LendingError::BatchSizeExceeded.into());
+    }
+    ctx.accounts
+        .config
+        .validate_account_count(ctx.remaining_accounts.len())?;
 
-    if liquidation_params.len() > 10 {
-        return Err(LendingError::InvalidAmount.into());
+    // Check if market allows liquidations
+    if market.is_paused() || market.is_liquidation_disabled() {
+        return Err(LendingError::MarketPaused.into());
     }
 
-    let mut total_liquidated_value = 0u64;
+    let mut results = Vec::with_capacity(liquidation_params.len());
+    let mut offset = 0;
+    let mut budget = ComputeBudgetGuard::new(LIQUIDATE_COMPUTE_BUDGET);
 
-    for (i, params) in liquidation_params.iter().enumerate() {
-        // Get accounts from remaining_accounts
-        let obligation_info = ctx
+    for params in liquidation_params.iter() {
+        // A malformed account layout fails the whole batch, as later groups cannot be found
+        let group_len = BATCH_LIQUIDATION_ENTRY_ACCOUNTS + params.refresh_account_count as usize;
+        let accounts = ctx
             .remaining_accounts
-            .get(i * 6)
+            .get(offset..offset + group_len)
             .ok_or(LendingError::InvalidAccount)?;
-
-        // Validate obligation is unhealthy by deserializing and checking
-        let obligation_data = obligation_info.try_borrow_data()?;
-        let mut obligation_data_slice = obligation_data.as_ref();
-        let obligation = Obligation::try_deserialize(&mut obligation_data_slice)
-            .map_err(|_| LendingError::InvalidAccount)?;
-
-        if obligation.is_healthy()? {
-            continue; // Skip healthy obligations
+        offset += group_len;
+
+        let obligation_key = accounts[0].key();
+
+        // Entries that no longer fit are left for the next transaction
+        if !budget.try_begin_step() {
+            msg!(
+                "Batch liquidation of {} skipped: compute budget exhausted",
+                obligation_key
+            );
+            results.push(BatchLiquidationResult {
+                obligation: obligation_key,
+                success: false,
+                error_code: Some(error_code_of(&LendingError::ComputeBudgetExhausted.into())),
+                liquidity_repaid: 0,
+                collateral_seized: 0,
+            });
+            continue;
         }
-
-        total_liquidated_value = total_liquidated_value
-            .checked_add(params.liquidity_amount)
-            .ok_or(LendingError::MathOverflow)?;
+        let prepared = BatchLiquidationEntry::load(
+            accounts,
+            params,
+            &market.key(),
+            &ctx.accounts.liquidator.key(),
+            &ctx.accounts.config,
+            &clock,
+        )
+        .and_then(|entry| {
+            // Newly unhealthy obligations are reserved for registered keepers
+            check_keeper_priority(
+                &ctx.accounts.keeper_registry,
+                ctx.accounts.keeper.as_mut(),
+                &ctx.accounts.config,
+                entry.obligation.slots_unhealthy(clock.slot),
+            )?;
+            Ok(entry)
+        });
+
+        let result = match prepared {
+            Ok(mut entry) => {
+                entry.execute(&ctx.accounts.liquidator.to_account_info(), &clock)?;
+                dequeue_obligation(ctx.accounts.liquidation_queue.as_mut(), &obligation_key);
+                record_liquidation_metric(
+                    ctx.accounts.protocol_metrics.as_mut(),
+                    clock.unix_timestamp,
+                )?;
+                BatchLiquidationResult {
+                    obligation: obligation_key,
+                    success: true,
+                    error_code: None,
+                    liquidity_repaid: entry.liquidity_amount,
+                    collateral_seized: entry.collateral_amount,
+                }
+            }
+            Err(error) => {
+                msg!("Batch liquidation of {} skipped: {}", obligation_key, error);
+                BatchLiquidationResult {
+                    obligation: obligation_key,
+                    success: false,
+                    error_code: Some(error_code_of(&error)),
+                    liquidity_repaid: 0,
+                    collateral_seized: 0,
+                }
+            }
+        };
+        results.push(result);
     }
 
     msg!(
-        "Batch liquidated {} obligations, total value: {}",
-        liquidation_params.len(),
-        total_liquidated_value
+        "Batch liquidation completed - {} of {} obligations liquidated",
+        results.iter().filter(|result| result.success).count(),
+        results.len()
     );
 
-    Ok(())
+    Ok(results)
 }
 
-// Helper structs
-
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct LiquidationParams {
-    pub liquidity_amount: u64,
-    pub min_collateral_amount: u64,
+/// Accounts and quote of one batch liquidation entry
+struct BatchLiquidationEntry<'info> {
+    obligation_info: &'info AccountInfo<'info>,
+    obligation: AnyObligation,
+    repay_reserve: Account<'info, Reserve>,
+    withdraw_reserve: Account<'info, Reserve>,
+    withdraw_price: OraclePrice,
+    repay_liquidity_mint: InterfaceAccount<'info, Mint>,
+    withdraw_collateral_mint: InterfaceAccount<'info, Mint>,
+    source_liquidity: InterfaceAccount<'info, TokenAccount>,
+    destination_collateral: InterfaceAccount<'info, TokenAccount>,
+    repay_reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+    withdraw_reserve_collateral_supply: InterfaceAccount<'info, TokenAccount>,
+    withdraw_collateral_supply_authority: &'info AccountInfo<'info>,
+    collateral_authority_bump: u8,
+    token_program: Interface<'info, TokenInterface>,
+    collateral_token_program: Interface<'info, TokenInterface>,
+    liquidity_amount: u64,
+    repay_value_usd: Decimal,
+    collateral_amount: u64,
 }
 
-// Context structs for liquidation instructions
+impl<'info> BatchLiquidationEntry<'info> {
+    /// Validate an entry's accounts as `LiquidateObligation` would and quote it
+    /// The accounts are, in order: obligation, repay reserve, withdraw reserve, repay and
+    /// withdraw price oracles, repay liquidity mint, withdraw collateral mint, the
+    /// liquidator's source liquidity and destination collateral accounts, the repay
+    /// reserve's liquidity supply, the withdraw reserve's collateral supply and its
+    /// authority, and the token programs of the repay and withdraw mints. The obligation
+    /// refresh accounts follow. Nothing is written to the accounts.
+    fn load(
+        accounts: &'info [AccountInfo<'info>],
+        params: &LiquidationParams,
+        market: &Pubkey,
+        liquidator: &Pubkey,
+        config: &ProtocolConfig,
+        clock: &Clock,
+    ) -> Result<Self> {
+        let (entry_accounts, refresh_accounts) =
+            accounts.split_at(BATCH_LIQUIDATION_ENTRY_ACCOUNTS);
+        let obligation_info = &entry_accounts[0];
+        let repay_reserve_info = &entry_accounts[1];
+        let withdraw_reserve_info = &entry_accounts[2];
+        let repay_price_oracle = &entry_accounts[3];
+        let withdraw_price_oracle = &entry_accounts[4];
+        let repay_liquidity_mint_info = &entry_accounts[5];
+        let withdraw_collateral_mint_info = &entry_accounts[6];
+        let source_liquidity_info = &entry_accounts[7];
+        let destination_collateral_info = &entry_accounts[8];
+        let repay_supply_info = &entry_accounts[9];
+        let withdraw_supply_info = &entry_accounts[10];
+        let withdraw_collateral_supply_authority = &entry_accounts[11];
+        let token_program_info = &entry_accounts[12];
+        let collateral_token_program_info = &entry_accounts[13];
+
+        // Validate liquidation amount
+        if params.liquidity_amount == 0 {
+            return Err(LendingError::AmountTooSmall.into());
+        }
 
-#[derive(Accounts)]
-pub struct LiquidateObligation<'info> {
-    /// Market account
+        if !obligation_info.is_writable
+            || !repay_reserve_info.is_writable
+            || !withdraw_reserve_info.is_writable
+            || repay_reserve_info.key == withdraw_reserve_info.key
+        {
+            return Err(LendingError::InvalidAccount.into());
+        }
+
+        let mut obligation = AnyObligation::load_checked(obligation_info, market)?;
+        let mut repay_reserve = Account::<Reserve>::try_from(repay_reserve_info)?;
+        let mut withdraw_reserve = Account::<Reserve>::try_from(withdraw_reserve_info)?;
+        if repay_reserve.market != *market || withdraw_reserve.market != *market {
+            return Err(LendingError::InvalidMarketState.into());
+        }
+
+        // Check if reserves allow liquidations; auctioned collateral is not sold at the
+        // fixed bonus
+        if repay_reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::LIQUIDATIONS_DISABLED)
+            || withdraw_reserve
+                .config
+                .flags
+                .contains(ReserveConfigFlags::LIQUIDATIONS_DISABLED)
+            || withdraw_reserve
+                .config
+                .flags
+                .contains(ReserveConfigFlags::AUCTION_LIQUIDATIONS)
+        {
+            return Err(LendingError::FeatureDisabled.into());
+        }
+
+        // Token accounts, as constrained on `LiquidateObligation`
+        let repay_liquidity_mint = InterfaceAccount::<Mint>::try_from(repay_liquidity_mint_info)?;
+        if repay_liquidity_mint.key() != repay_reserve.liquidity_mint {
+            return Err(LendingError::TokenMintMismatch.into());
+        }
+        let withdraw_collateral_mint =
+            InterfaceAccount::<Mint>::try_from(withdraw_collateral_mint_info)?;
+        if withdraw_collateral_mint.key() != withdraw_reserve.collateral_mint {
+            return Err(LendingError::ReserveCollateralMintMismatch.into());
+        }
+
+        let (authority, collateral_authority_bump) = Pubkey::find_program_address(
+            &[
+                COLLATERAL_TOKEN_SEED,
+                withdraw_reserve.liquidity_mint.as_ref(),
+                b"authority",
+            ],
+            &crate::ID,
+        );
+        if *withdraw_collateral_supply_authority.key != authority {
+            return Err(LendingError::InvalidAccount.into());
+        }
+
+        let source_liquidity = InterfaceAccount::<TokenAccount>::try_from(source_liquidity_info)?;
+        let destination_collateral =
+            InterfaceAccount::<TokenAccount>::try_from(destination_collateral_info)?;
+        let repay_reserve_liquidity_supply =
+            InterfaceAccount::<TokenAccount>::try_from(repay_supply_info)?;
+        let withdraw_reserve_collateral_supply =
+            InterfaceAccount::<TokenAccount>::try_from(withdraw_supply_info)?;
+        if source_liquidity.mint != repay_reserve.liquidity_mint
+            || repay_reserve_liquidity_supply.key() != repay_reserve.liquidity_supply
+            || destination_collateral.mint != withdraw_reserve.collateral_mint
+            || withdraw_reserve_collateral_supply.mint != withdraw_reserve.collateral_mint
+        {
+            return Err(LendingError::TokenMintMismatch.into());
+        }
+        if source_liquidity.owner != *liquidator
+            || destination_collateral.owner != *liquidator
+            || withdraw_reserve_collateral_supply.owner != authority
+        {
+            return Err(LendingError::TokenAccountOwnerMismatch.into());
+        }
+
+        let token_program = Interface::<TokenInterface>::try_from(token_program_info)?;
+        let collateral_token_program =
+            Interface::<TokenInterface>::try_from(collateral_token_program_info)?;
+        if repay_liquidity_mint_info.owner != token_program_info.key
+            || withdraw_collateral_mint_info.owner != collateral_token_program_info.key
+        {
+            return Err(LendingError::InvalidAccount.into());
+        }
+
+        // Obligations in eMode need their category, which the batch does not take
+        let emode = EModeCategory::resolve(None, &obligation, None)?;
+
+        if *repay_price_oracle.key != repay_reserve.price_oracle
+            || *withdraw_price_oracle.key != withdraw_reserve.price_oracle
+        {
+            return Err(LendingError::OracleAccountMismatch.into());
+        }
+
+        // Get current prices from oracles; a reserve with a secondary oracle fails here
+        let repay_price =
+            OracleManager::get_checked_price(repay_price_oracle, None, &repay_reserve)?;
+        repay_price.validate(clock.unix_timestamp)?;
+        let withdraw_price =
+            OracleManager::get_checked_price(withdraw_price_oracle, None, &withdraw_reserve)?;
+        withdraw_price.validate(clock.unix_timestamp)?;
+
+        // Smooth both prices against their TWAPs in the borrower's favour
+        let repay_price = repay_reserve.liquidation_debt_price(&repay_price);
+        let withdraw_price = withdraw_reserve.liquidation_collateral_price(&withdraw_price);
+
+        // Lock reserves, refresh them and confirm the obligation is unhealthy. The
+        // changes stay in memory until the entry executes.
+        begin_liquidation(
+            &mut obligation,
+            &mut repay_reserve,
+            &mut withdraw_reserve,
+            &repay_price,
+            &withdraw_price,
+            refresh_accounts,
+            clock,
+            emode.as_ref(),
+        )?;
+
+        let close_factor_bps = liquidation_close_factor_bps(
+            &obligation,
+            &repay_reserve,
+            config,
+            emode.as_ref(),
+            false,
+        )?;
+        let (repay_value_usd, collateral_amount) = quote_liquidation(
+            &obligation,
+            &repay_reserve,
+            &withdraw_reserve,
+            &repay_price,
+            &withdraw_price,
+            params.liquidity_amount,
+            close_factor_bps,
+            emode.as_ref(),
+        )?;
+        if collateral_amount < params.min_collateral_amount {
+            return Err(LendingError::SlippageExceeded.into());
+        }
+
+        Ok(Self {
+            obligation_info,
+            obligation,
+            repay_reserve,
+            withdraw_reserve,
+            withdraw_price,
+            repay_liquidity_mint,
+            withdraw_collateral_mint,
+            source_liquidity,
+            destination_collateral,
+            repay_reserve_liquidity_supply,
+            withdraw_reserve_collateral_supply,
+            withdraw_collateral_supply_authority,
+            collateral_authority_bump,
+            token_program,
+            collateral_token_program,
+            liquidity_amount: params.liquidity_amount,
+            repay_value_usd,
+            collateral_amount,
+        })
+    }
+
+    /// Move the entry's tokens, settle it and write back the obligation and reserves
+    fn execute(&mut self, liquidator: &AccountInfo<'info>, clock: &Clock) -> Result<()> {
+        // The liquidator covers the transfer fee, so the reserve receives the full repayment
+        let transfer_amount = TokenUtils::amount_before_transfer_fee(
+            &self.repay_liquidity_mint,
+            self.liquidity_amount,
+        )?;
+        TokenUtils::transfer_tokens(
+            &self.token_program,
+            &self.source_liquidity,
+            &self.repay_reserve_liquidity_supply,
+            &self.repay_liquidity_mint,
+            liquidator,
+            &[],
+            transfer_amount,
+        )?;
+
+        let collateral_authority_seeds = &[
+            COLLATERAL_TOKEN_SEED,
+            self.withdraw_reserve.liquidity_mint.as_ref(),
+            b"authority",
+            &[self.collateral_authority_bump],
+        ];
+        TokenUtils::transfer_tokens(
+            &self.collateral_token_program,
+            &self.withdraw_reserve_collateral_supply,
+            &self.destination_collateral,
+            &self.withdraw_collateral_mint,
+            self.withdraw_collateral_supply_authority,
+            &[collateral_authority_seeds],
+            self.collateral_amount,
+        )?;
+
+        settle_liquidation(
+            &mut self.obligation,
+            &mut self.repay_reserve,
+            &self.withdraw_reserve,
+            &self.withdraw_price,
+            self.liquidity_amount,
+            self.repay_value_usd,
+            self.collateral_amount,
+            clock.slot,
+        )?;
+        let bonus_amount = liquidation_bonus_amount(
+            self.repay_value_usd,
+            &self.withdraw_price,
+            self.collateral_amount,
+        )?;
+
+        // Clear liquidation snapshot as liquidation is complete
+        let snapshot_health_factor = self.obligation.liquidation_snapshot_health_factor.take();
+
+        self.repay_reserve.unlock()?;
+        self.withdraw_reserve.unlock()?;
+        self.repay_reserve.exit(&crate::ID)?;
+        self.withdraw_reserve.exit(&crate::ID)?;
+        self.obligation.save(self.obligation_info)?;
+
+        let receipt = OperationReceipt::new(
+            self.repay_reserve.key(),
+            &self.repay_reserve,
+            self.liquidity_amount,
+            self.collateral_amount,
+            bonus_amount,
+            Some(self.obligation.calculate_health_factor()?),
+        )?;
+
+        emit!(LiquidationEvent {
+            obligation: self.obligation_info.key(),
+            liquidator: liquidator.key(),
+            withdraw_reserve: self.withdraw_reserve.key(),
+            health_factor_bps: snapshot_health_factor.map_or(0, Decimal::to_health_bps_saturating),
+            receipt,
+            protocol_fee_collateral: 0,
+            protocol_fee_liquidity: 0,
+            slot: clock.slot,
+        });
+
+        Ok(())
+    }
+}
+
+/// Collateral seized beyond the repaid value, reported as the liquidation bonus
+fn liquidation_bonus_amount(
+    repay_value_usd: Decimal,
+    withdraw_price: &OraclePrice,
+    collateral_amount: u64,
+) -> Result<u64> {
+    let expected_collateral = repay_value_usd
+        .try_div(withdraw_price.to_decimal()?)?
+        .try_floor_u64()?;
+
+    if collateral_amount > expected_collateral {
+        Ok(collateral_amount.saturating_sub(expected_collateral))
+    } else {
+        // This shouldn't happen in a proper liquidation, log warning
+        msg!("Warning: Liquidation bonus calculation resulted in negative value");
+        Ok(0)
+    }
+}
+/// Lock both reserves, accrue their interest and snapshot the obligation's health
+/// The obligation is first revalued from `price_oracles`, laid out as for
+/// `refresh_obligation`. Fails with the reserves unlocked if the obligation is healthy.
+/// Health is measured with `emode` parameters when they apply.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn begin_liquidation(
+    obligation: &mut Obligation,
+    repay_reserve: &mut Account<Reserve>,
+    withdraw_reserve: &mut Account<Reserve>,
+    repay_price: &OraclePrice,
+    withdraw_price: &OraclePrice,
+    price_oracles: &[AccountInfo],
+    clock: &Clock,
+    emode: Option<&EModeParams>,
+) -> Result<()> {
+    // Lock reserves to prevent race conditions during liquidation, including calls nested
+    // in the token and swap CPIs that follow
+    lock_reserve(repay_reserve)?;
+    lock_reserve(withdraw_reserve)?;
+
+    // Ensure we unlock on any error path
+    let result = (|| -> Result<()> {
+        // Refresh reserves with locked state
+        repay_reserve.update_interest(clock.slot)?;
+        withdraw_reserve.update_interest(clock.slot)?;
+
+        // Refresh obligation with current prices to get accurate health factor
+        obligation.refresh_health_factor(price_oracles, clock)?;
+        revalue_liquidation_positions(
+            obligation,
+            repay_reserve,
+            withdraw_reserve,
+            repay_price,
+            withdraw_price,
+        )?;
+
+        // Atomic health check - capture health factor at exact moment of liquidation
+        let health_factor = obligation.calculate_health_factor_with(emode)?;
+        if health_factor >= Decimal::one() {
+            return Err(LendingError::ObligationHealthy.into());
+        }
+        obligation.check_liquidation_grace(health_factor, clock.slot)?;
+
+        // Store health snapshot to prevent manipulation during liquidation
+        obligation.liquidation_snapshot_health_factor = Some(health_factor);
+
+        Ok(())
+    })();
+
+    // Unlock reserves regardless of result
+    if result.is_err() {
+        let _ = repay_reserve.unlock();
+        let _ = withdraw_reserve.unlock();
+    }
+    result
+}
+
+/// Re-value the debt and collateral positions being liquidated at the given prices
+/// The cached values from the last obligation refresh are replaced and the obligation
+/// totals adjusted, so the health check sees the (TWAP-smoothed) liquidation prices.
+pub(crate) fn revalue_liquidation_positions(
+    obligation: &mut Obligation,
+    repay_reserve: &Account<Reserve>,
+    withdraw_reserve: &Account<Reserve>,
+    repay_price: &OraclePrice,
+    withdraw_price: &OraclePrice,
+) -> Result<()> {
+    let collateral = obligation
+        .find_collateral_deposit_mut(&withdraw_reserve.key())
+        .ok_or(LendingError::ObligationReserveNotFound)?;
+    let collateral_value =
+        withdraw_reserve.collateral_value_usd(collateral.deposited_amount, withdraw_price)?;
+    let previous_collateral_value =
+        std::mem::replace(&mut collateral.market_value_usd, collateral_value);
+
+    let borrow = obligation
+        .find_liquidity_borrow_mut(&repay_reserve.key())
+        .ok_or(LendingError::ObligationReserveNotFound)?;
+    let borrow_value = OracleManager::calculate_usd_value(
+        borrow.borrowed_amount_wads.try_floor_u64()?,
+        repay_price,
+        repay_reserve.config.decimals,
+    )?;
+    let previous_borrow_value = std::mem::replace(&mut borrow.market_value_usd, borrow_value);
+
+    obligation.deposited_value_usd = obligation
+        .deposited_value_usd
+        .try_sub(previous_collateral_value)?
+        .try_add(collateral_value)?;
+    obligation.borrowed_value_usd = obligation
+        .borrowed_value_usd
+        .try_sub(previous_borrow_value)?
+        .try_add(borrow_value)?;
+    Ok(())
+}
+
+/// Close factor of a liquidation of the obligation's `repay_reserve` debt
+/// `config` supplies the base close factor unless the repay reserve overrides it, and the
+/// close factor grows as health falls. With `dust` the borrow, valued at the liquidation
+/// price, must be below the minimum position value and may be repaid in full.
+pub(crate) fn liquidation_close_factor_bps(
+    obligation: &Obligation,
+    repay_reserve: &Account<Reserve>,
+    config: &ProtocolConfig,
+    emode: Option<&EModeParams>,
+    dust: bool,
+) -> Result<u64> {
+    if dust {
+        let borrow = obligation
+            .find_liquidity_borrow(&repay_reserve.key())
+            .ok_or(LendingError::ObligationReserveNotFound)?;
+        if !config.is_dust_position(borrow.market_value_usd)? {
+            return Err(LendingError::PositionNotDust.into());
+        }
+        return Ok(BASIS_POINTS_PRECISION);
+    }
+
+    obligation.liquidation_close_factor_bps(
+        repay_reserve.liquidation_close_factor_bps(config.liquidation_close_factor_bps),
+        emode,
+    )
+}
+
+/// Value a liquidation of `liquidity_amount` and the collateral it seizes (with bonus)
+/// Validates the amount against `close_factor_bps` and the obligation's collateral.
+/// The eMode bonus replaces the collateral reserve's penalty when `emode` applies.
+/// Returns (repaid USD value, collateral amount seized).
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn quote_liquidation(
+    obligation: &Obligation,
+    repay_reserve: &Account<Reserve>,
+    withdraw_reserve: &Account<Reserve>,
+    repay_price: &OraclePrice,
+    withdraw_price: &OraclePrice,
+    liquidity_amount: u64,
+    close_factor_bps: u64,
+    emode: Option<&EModeParams>,
+) -> Result<(Decimal, u64)> {
+    // Validate that the borrow exists
+    obligation
+        .find_liquidity_borrow(&repay_reserve.key())
+        .ok_or(LendingError::ObligationReserveNotFound)?;
+
+    // Check maximum liquidation amount
+    let max_liquidation =
+        obligation.max_liquidation_amount(&repay_reserve.key(), close_factor_bps)?;
+    if liquidity_amount > max_liquidation {
+        return Err(LendingError::LiquidationTooLarge.into());
+    }
+
+    // Validate that collateral exists
+    let collateral = obligation
+        .find_collateral_deposit(&withdraw_reserve.key())
+        .ok_or(LendingError::ObligationReserveNotFound)?;
+
+    let repay_value_usd = OracleManager::calculate_usd_value(
+        liquidity_amount,
+        repay_price,
+        repay_reserve.config.decimals,
+    )?;
+
+    // eMode liquidations pay the category bonus instead of the reserve penalty
+    let liquidation_bonus_bps = match emode {
+        Some(emode) => emode.liquidation_bonus_bps,
+        None => withdraw_reserve.config.liquidation_penalty_bps,
+    };
+    let seized_liquidity = calculate_liquidation_collateral_amount(
+        repay_value_usd,
+        withdraw_price,
+        liquidation_bonus_bps,
+    )?;
+    let collateral_amount = withdraw_reserve.liquidity_to_collateral(seized_liquidity)?;
+
+    // Validate sufficient collateral
+    if collateral.deposited_amount < collateral_amount {
+        return Err(LendingError::InsufficientCollateral.into());
+    }
+
+    Ok((repay_value_usd, collateral_amount))
+}
+
+/// Apply a quoted liquidation to the repay reserve and the obligation
+/// Returns the USD value of the seized collateral.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn settle_liquidation(
+    obligation: &mut Obligation,
+    repay_reserve: &mut Account<Reserve>,
+    withdraw_reserve: &Account<Reserve>,
+    withdraw_price: &OraclePrice,
+    liquidity_amount: u64,
+    repay_value_usd: Decimal,
+    collateral_amount: u64,
+    slot: u64,
+) -> Result<Decimal> {
+    repay_reserve.repay_borrow(liquidity_amount)?;
+
+    obligation.repay_liquidity_borrow(
+        &repay_reserve.key(),
+        Decimal::from_integer(liquidity_amount)?,
+    )?;
+    obligation.remove_collateral_deposit(&withdraw_reserve.key(), collateral_amount)?;
+
+    // Update cached USD values
+    obligation.borrowed_value_usd = obligation.borrowed_value_usd.try_sub(repay_value_usd)?;
+
+    let collateral_value_usd =
+        withdraw_reserve.collateral_value_usd(collateral_amount, withdraw_price)?;
+
+    obligation.deposited_value_usd = obligation
+        .deposited_value_usd
+        .try_sub(collateral_value_usd)?;
+
+    obligation.record_liquidation(slot);
+    obligation.update_timestamp(slot)?;
+    Ok(collateral_value_usd)
+}
+
+/// Convert a repaid USD value into the collateral amount seized, including the liquidation bonus
+pub fn calculate_liquidation_collateral_amount(
+    repay_value_usd: Decimal,
+    collateral_price: &OraclePrice,
+    liquidation_penalty_bps: u64,
+) -> Result<u64> {
+    let liquidation_bonus_decimal = Decimal::from_scaled_val(
+        (liquidation_penalty_bps as u128)
+            .checked_add(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_mul(PRECISION as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::DivisionByZero)?,
+    );
+
+    let liquidation_value_usd = repay_value_usd.try_mul(liquidation_bonus_decimal)?;
+
+    // Convert USD value to collateral token amount
+    let collateral_price_decimal = collateral_price.to_decimal()?;
+    let collateral_amount_decimal = liquidation_value_usd.try_div(collateral_price_decimal)?;
+    collateral_amount_decimal.try_floor_u64()
+}
+
+/// Match a liquidation against the collateral order book, if one was supplied
+/// Returns (liquidity paid by orders, collateral allocated to orders)
+#[allow(clippy::too_many_arguments)]
+fn match_collateral_orders(
+    order_book: &mut Option<Account<CollateralOrderBook>>,
+    liquidity_escrow: &Option<InterfaceAccount<TokenAccount>>,
+    collateral_escrow: &Option<InterfaceAccount<TokenAccount>>,
+    collateral_reserve: &Pubkey,
+    payment_reserve: &Pubkey,
+    repay_value_usd: Decimal,
+    liquidity_amount: u64,
+    collateral_amount: u64,
+) -> Result<(u64, u64)> {
+    let (order_book, liquidity_escrow, collateral_escrow) =
+        match (order_book.as_mut(), liquidity_escrow, collateral_escrow) {
+            (Some(book), Some(liquidity), Some(collateral)) => (book, liquidity, collateral),
+            (None, None, None) => return Ok((0, 0)),
+            _ => return Err(LendingError::InvalidAccount.into()),
+        };
+
+    if order_book.collateral_reserve != *collateral_reserve
+        || order_book.payment_reserve != *payment_reserve
+        || order_book.liquidity_escrow != liquidity_escrow.key()
+        || order_book.collateral_escrow != collateral_escrow.key()
+    {
+        return Err(LendingError::InvalidAccount.into());
+    }
+
+    if collateral_amount == 0 {
+        return Ok((0, 0));
+    }
+
+    // Discounted price per collateral unit paid by this liquidation
+    let fill_price = repay_value_usd.try_div(Decimal::from_integer(collateral_amount)?)?;
+
+    order_book.match_liquidation(fill_price, liquidity_amount, collateral_amount)
+}
+
+// Helper structs
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LiquidationParams {
+    pub liquidity_amount: u64,
+    pub min_collateral_amount: u64,
+    /// Obligation refresh accounts following the entry's accounts
+    pub refresh_account_count: u8,
+}
+
+/// Outcome of one batch liquidation entry, returned as instruction return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct BatchLiquidationResult {
+    pub obligation: Pubkey,
+    pub success: bool,
+    /// Error the entry was skipped with
+    pub error_code: Option<u32>,
+    pub liquidity_repaid: u64,
+    pub collateral_seized: u64,
+}
+
+/// Parameters for liquidating through a DEX swap
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LiquidateWithSwapParams {
+    /// Debt to repay, in repay asset units
+    pub liquidity_amount: u64,
+    /// Most input tokens the swap into the repay asset may spend
+    pub max_amount_in: u64,
+    /// Instruction data of the swap into the repay asset
+    pub swap_in_data: Vec<u8>,
+    /// Number of remaining accounts in the swap-in route
+    pub swap_in_accounts: u8,
+    /// Optional swap of the redeemed collateral
+    pub swap_out: Option<SwapOutParams>,
+}
+
+/// Swap of redeemed collateral back into the liquidator's chosen asset
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SwapOutParams {
+    /// Instruction data of the swap
+    pub route_data: Vec<u8>,
+    /// Number of remaining accounts in the swap-out route
+    pub route_accounts: u8,
+    /// Minimum output tokens to receive
+    pub min_amount_out: u64,
+}
+
+// Context structs for liquidation instructions
+
+#[derive(Accounts)]
+pub struct LiquidateObligation<'info> {
+    /// Market account
     #[account(
         seeds = [MARKET_SEED],
         bump
     )]
     pub market: Account<'info, Market>,
 
-    /// Obligation account being liquidated
+    /// Protocol configuration (remaining account cap, liquidation close factor)
     #[account(
-        mut,
-        seeds = [OBLIGATION_SEED, obligation.owner.as_ref()],
-        bump,
-        has_one = market @ LendingError::InvalidMarketState
+        seeds = [b"config"],
+        bump
     )]
-    pub obligation: Account<'info, Obligation>,
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Obligation account being liquidated (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
 
     /// Reserve for the asset being repaid
     #[account(
         mut,
         seeds = [RESERVE_SEED, repay_reserve.liquidity_mint.as_ref()],
         bump,
-        has_one = market @ LendingError::InvalidMarketState,
-        // Price oracle validation will be done manually
-        // Liquidity supply validation will be done manually
+        has_one = market @ LendingError::InvalidMarketState
     )]
     pub repay_reserve: Account<'info, Reserve>,
 
@@ -407,26 +1766,46 @@ pub struct LiquidateObligation<'info> {
         mut,
         seeds = [RESERVE_SEED, withdraw_reserve.liquidity_mint.as_ref()],
         bump,
-        has_one = market @ LendingError::InvalidMarketState,
-        // Price oracle validation will be done manually
+        has_one = market @ LendingError::InvalidMarketState
     )]
     pub withdraw_reserve: Account<'info, Reserve>,
 
     /// Price oracle for repay asset
-    /// CHECK: This account is validated by the repay_reserve's price_oracle field
+    /// CHECK: Matched against the repay reserve's price_oracle
+    #[account(address = repay_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
     pub repay_price_oracle: UncheckedAccount<'info>,
 
     /// Price oracle for withdraw asset
-    /// CHECK: This account is validated by the withdraw_reserve's price_oracle field
+    /// CHECK: Matched against the withdraw reserve's price_oracle
+    #[account(address = withdraw_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
     pub withdraw_price_oracle: UncheckedAccount<'info>,
 
+    /// Secondary oracle of the repay asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub repay_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Secondary oracle of the withdraw asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub withdraw_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Liquidity token mint of the repay reserve
+    #[account(address = repay_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub repay_liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Collateral mint (aToken mint) of the withdraw reserve
+    #[account(
+        mut,
+        address = withdraw_reserve.collateral_mint @ LendingError::ReserveCollateralMintMismatch
+    )]
+    pub withdraw_collateral_mint: InterfaceAccount<'info, Mint>,
+
     /// Liquidator's source liquidity token account (for repayment)
     #[account(
         mut,
         token::mint = repay_reserve.liquidity_mint,
         token::authority = liquidator
     )]
-    pub source_liquidity: Account<'info, TokenAccount>,
+    pub source_liquidity: InterfaceAccount<'info, TokenAccount>,
 
     /// Liquidator's destination collateral token account (receives seized collateral)
     #[account(
@@ -434,14 +1813,14 @@ pub struct LiquidateObligation<'info> {
         token::mint = withdraw_reserve.collateral_mint,
         token::authority = liquidator
     )]
-    pub destination_collateral: Account<'info, TokenAccount>,
+    pub destination_collateral: InterfaceAccount<'info, TokenAccount>,
 
     /// Repay reserve's liquidity supply token account
     #[account(
         mut,
-        token::mint = repay_reserve.liquidity_mint
+        address = repay_reserve.liquidity_supply @ LendingError::InvalidAccount
     )]
-    pub repay_reserve_liquidity_supply: Account<'info, TokenAccount>,
+    pub repay_reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
 
     /// Withdraw reserve's collateral supply token account
     #[account(
@@ -449,7 +1828,7 @@ pub struct LiquidateObligation<'info> {
         token::mint = withdraw_reserve.collateral_mint,
         token::authority = withdraw_collateral_supply_authority
     )]
-    pub withdraw_reserve_collateral_supply: Account<'info, TokenAccount>,
+    pub withdraw_reserve_collateral_supply: InterfaceAccount<'info, TokenAccount>,
 
     /// Withdraw collateral supply authority (PDA)
     /// CHECK: This is validated by the seeds constraint
@@ -459,15 +1838,131 @@ pub struct LiquidateObligation<'info> {
     )]
     pub withdraw_collateral_supply_authority: UncheckedAccount<'info>,
 
-    /// Liquidator
+    /// Liquidator (receives the rent of a revealed commitment)
+    #[account(mut)]
     pub liquidator: Signer<'info>,
 
-    /// Token program
-    pub token_program: Program<'info, Token>,
+    /// User's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, liquidator.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
+    /// Token program of the repay reserve's liquidity mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the withdraw reserve's mints
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+
+    /// Optional standing collateral order book matched before the liquidator
+    #[account(mut)]
+    pub collateral_order_book: Option<Account<'info, CollateralOrderBook>>,
+
+    /// Order book payment liquidity escrow
+    #[account(mut)]
+    pub order_book_liquidity_escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Order book filled collateral escrow
+    #[account(mut)]
+    pub order_book_collateral_escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Keeper registry of the market (may be uninitialized)
+    /// CHECK: Validated by seeds; loaded in the instruction when initialized
+    #[account(
+        seeds = [KEEPER_REGISTRY_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub keeper_registry: UncheckedAccount<'info>,
+
+    /// Liquidator's keeper registration (required during the keeper priority window)
+    #[account(
+        mut,
+        seeds = [KEEPER_SEED, keeper_registry.key().as_ref(), liquidator.key().as_ref()],
+        bump = keeper.bump
+    )]
+    pub keeper: Option<Account<'info, Keeper>>,
+
+    /// Market's liquidation queue, from which the obligation is delisted when supplied
+    #[account(
+        mut,
+        seeds = [LIQUIDATION_QUEUE_SEED, market.key().as_ref()],
+        bump = liquidation_queue.bump
+    )]
+    pub liquidation_queue: Option<Account<'info, LiquidationQueue>>,
+
+    /// Liquidator's commitment (required by `execute_liquidation`, closed once revealed)
+    #[account(
+        mut,
+        close = liquidator,
+        has_one = repay_reserve @ LendingError::LiquidationCommitmentMismatch,
+        has_one = withdraw_reserve @ LendingError::LiquidationCommitmentMismatch,
+        seeds = [
+            LIQUIDATION_COMMITMENT_SEED,
+            obligation.key().as_ref(),
+            liquidator.key().as_ref()
+        ],
+        bump = liquidation_commitment.bump
+    )]
+    pub liquidation_commitment: Option<Account<'info, LiquidationCommitment>>,
+
+    /// Protocol metrics counting the liquidation, when supplied
+    #[account(
+        mut,
+        seeds = [PROTOCOL_METRICS_SEED, market.key().as_ref()],
+        bump = protocol_metrics.bump
+    )]
+    pub protocol_metrics: Option<Account<'info, ProtocolMetrics>>,
+
+    /// Statistics of the obligation's owner, updated when supplied
+    #[account(
+        mut,
+        seeds = [
+            USER_STATS_SEED,
+            market.key().as_ref(),
+            user_stats.owner.as_ref()
+        ],
+        bump = user_stats.bump
+    )]
+    pub user_stats: Option<Account<'info, UserStats>>,
+
+    /// Withdraw liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, withdraw_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub withdraw_liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Liquidity mint of the withdraw reserve (required when it charges a liquidation
+    /// protocol fee, like the accounts below)
+    #[account(address = withdraw_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub withdraw_liquidity_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Withdraw reserve's liquidity supply, from which the protocol fee is redeemed
+    #[account(
+        mut,
+        address = withdraw_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub withdraw_reserve_liquidity_supply: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Withdraw reserve's insurance fund
+    #[account(
+        mut,
+        seeds = [INSURANCE_FUND_SEED, withdraw_reserve.key().as_ref()],
+        bump = withdraw_insurance_fund.bump
+    )]
+    pub withdraw_insurance_fund: Option<Account<'info, InsuranceFund>>,
+
+    /// Insurance fund vault (liquidation protocol fee)
+    #[account(mut)]
+    pub withdraw_insurance_fund_vault: Option<InterfaceAccount<'info, TokenAccount>>,
 }
 
 #[derive(Accounts)]
-pub struct FlashLiquidateObligation<'info> {
+pub struct LiquidateWithSwap<'info> {
     /// Market account
     #[account(
         seeds = [MARKET_SEED],
@@ -475,14 +1970,230 @@ pub struct FlashLiquidateObligation<'info> {
     )]
     pub market: Account<'info, Market>,
 
-    /// Obligation account being liquidated
+    /// Protocol configuration (account cap, large withdrawal threshold, close factor)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Fee converter holding the DEX program whitelist
+    #[account(
+        seeds = [FEE_CONVERTER_SEED, market.key().as_ref()],
+        bump = fee_converter.bump
+    )]
+    pub fee_converter: Account<'info, FeeConverter>,
+
+    /// Obligation account being liquidated (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Reserve of the asset the liquidator supplies (the repay reserve to skip the swap)
+    #[account(has_one = market @ LendingError::InvalidMarketState)]
+    pub input_reserve: Account<'info, Reserve>,
+
+    /// Reserve for the asset being repaid
     #[account(
         mut,
-        seeds = [OBLIGATION_SEED, obligation.owner.as_ref()],
+        seeds = [RESERVE_SEED, repay_reserve.liquidity_mint.as_ref()],
         bump,
         has_one = market @ LendingError::InvalidMarketState
     )]
-    pub obligation: Account<'info, Obligation>,
+    pub repay_reserve: Account<'info, Reserve>,
+
+    /// Reserve for the collateral being withdrawn
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, withdraw_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub withdraw_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for repay asset
+    /// CHECK: Matched against the repay reserve's price_oracle
+    #[account(address = repay_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub repay_price_oracle: UncheckedAccount<'info>,
+
+    /// Price oracle for withdraw asset
+    /// CHECK: Matched against the withdraw reserve's price_oracle
+    #[account(address = withdraw_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub withdraw_price_oracle: UncheckedAccount<'info>,
+
+    /// Secondary oracle of the repay asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub repay_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Secondary oracle of the withdraw asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub withdraw_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Liquidator's input token account (swapped into the repay asset, or repaid from
+    /// directly when it holds the repay asset)
+    #[account(
+        mut,
+        token::mint = input_reserve.liquidity_mint,
+        token::authority = liquidator
+    )]
+    pub source_input: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidator's repay asset account receiving the swap output
+    #[account(
+        mut,
+        token::mint = repay_reserve.liquidity_mint,
+        token::authority = liquidator
+    )]
+    pub repay_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity token mint of the repay reserve
+    #[account(address = repay_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub repay_liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Repay reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = repay_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub repay_reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidator's destination collateral token account (receives seized collateral)
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.collateral_mint,
+        token::authority = liquidator
+    )]
+    pub destination_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Withdraw reserve's collateral supply token account
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.collateral_mint,
+        token::authority = withdraw_collateral_supply_authority
+    )]
+    pub withdraw_reserve_collateral_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Withdraw collateral supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [COLLATERAL_TOKEN_SEED, withdraw_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub withdraw_collateral_supply_authority: UncheckedAccount<'info>,
+
+    /// Withdraw reserve's collateral mint (burned on swap-out)
+    #[account(
+        mut,
+        address = withdraw_reserve.collateral_mint @ LendingError::InvalidAccount
+    )]
+    pub withdraw_collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Withdraw reserve's liquidity mint (swap-out only)
+    #[account(address = withdraw_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub withdraw_liquidity_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Withdraw reserve's liquidity supply token account (swap-out only)
+    #[account(
+        mut,
+        address = withdraw_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub withdraw_reserve_liquidity_supply: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Withdraw reserve liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, withdraw_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub withdraw_liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Liquidator's account receiving redeemed collateral liquidity (swap-out only, unless
+    /// the output is the collateral's own asset)
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.liquidity_mint,
+        token::authority = liquidator
+    )]
+    pub withdrawn_liquidity: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Liquidator's account receiving the swap-out output (swap-out only)
+    #[account(
+        mut,
+        token::authority = liquidator
+    )]
+    pub swap_output: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// DEX program both swaps are routed through
+    /// CHECK: Checked against the fee converter whitelist
+    pub swap_program: UncheckedAccount<'info>,
+
+    /// Liquidator
+    pub liquidator: Signer<'info>,
+
+    /// User's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, liquidator.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
+    /// Token program of the repay reserve's liquidity mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the withdraw reserve's mints
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+
+    /// Keeper registry of the market (may be uninitialized)
+    /// CHECK: Validated by seeds; loaded in the instruction when initialized
+    #[account(
+        seeds = [KEEPER_REGISTRY_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub keeper_registry: UncheckedAccount<'info>,
+
+    /// Liquidator's keeper registration (required during the keeper priority window)
+    #[account(
+        mut,
+        seeds = [KEEPER_SEED, keeper_registry.key().as_ref(), liquidator.key().as_ref()],
+        bump = keeper.bump
+    )]
+    pub keeper: Option<Account<'info, Keeper>>,
+
+    /// Market's liquidation queue, from which the obligation is delisted when supplied
+    #[account(
+        mut,
+        seeds = [LIQUIDATION_QUEUE_SEED, market.key().as_ref()],
+        bump = liquidation_queue.bump
+    )]
+    pub liquidation_queue: Option<Account<'info, LiquidationQueue>>,
+
+    /// Protocol metrics counting the liquidation, when supplied
+    #[account(
+        mut,
+        seeds = [PROTOCOL_METRICS_SEED, market.key().as_ref()],
+        bump = protocol_metrics.bump
+    )]
+    pub protocol_metrics: Option<Account<'info, ProtocolMetrics>>,
+}
+
+#[derive(Accounts)]
+pub struct FlashLiquidateObligation<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation account being liquidated (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
 
     /// Reserve providing flash loan
     #[account(
@@ -493,6 +2204,10 @@ pub struct FlashLiquidateObligation<'info> {
     )]
     pub flash_loan_reserve: Account<'info, Reserve>,
 
+    /// Liquidity token mint of the flash loan reserve
+    #[account(address = flash_loan_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub flash_loan_liquidity_mint: InterfaceAccount<'info, Mint>,
+
     /// Reserve for the asset being repaid
     #[account(
         mut,
@@ -514,10 +2229,9 @@ pub struct FlashLiquidateObligation<'info> {
     /// Flash loan reserve's liquidity supply token account
     #[account(
         mut,
-        token::mint = flash_loan_reserve.liquidity_mint,
-        token::authority = flash_loan_reserve_authority
+        address = flash_loan_reserve.liquidity_supply @ LendingError::InvalidAccount
     )]
-    pub flash_loan_reserve_liquidity_supply: Account<'info, TokenAccount>,
+    pub flash_loan_reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
 
     /// Flash loan reserve authority (PDA)
     /// CHECK: This is validated by the seeds constraint
@@ -533,7 +2247,7 @@ pub struct FlashLiquidateObligation<'info> {
         token::mint = flash_loan_reserve.liquidity_mint,
         token::authority = liquidator
     )]
-    pub flash_loan_destination: Account<'info, TokenAccount>,
+    pub flash_loan_destination: InterfaceAccount<'info, TokenAccount>,
 
     /// Flash loan source (liquidator repays from here)
     #[account(
@@ -541,13 +2255,13 @@ pub struct FlashLiquidateObligation<'info> {
         token::mint = flash_loan_reserve.liquidity_mint,
         token::authority = liquidator
     )]
-    pub flash_loan_source: Account<'info, TokenAccount>,
+    pub flash_loan_source: InterfaceAccount<'info, TokenAccount>,
 
     /// Liquidator
     pub liquidator: Signer<'info>,
 
     /// Token program
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
@@ -559,10 +2273,55 @@ pub struct BatchLiquidateObligations<'info> {
     )]
     pub market: Account<'info, Market>,
 
+    /// Protocol configuration (remaining account cap, liquidation close factor)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
     /// Liquidator performing batch liquidation
     pub liquidator: Signer<'info>,
 
-    /// Token program
-    pub token_program: Program<'info, Token>,
-    // Note: Individual obligation accounts are passed as remaining_accounts
+    /// User's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, liquidator.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
+    /// Keeper registry of the market (may be uninitialized)
+    /// CHECK: Validated by seeds; loaded in the instruction when initialized
+    #[account(
+        seeds = [KEEPER_REGISTRY_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub keeper_registry: UncheckedAccount<'info>,
+
+    /// Liquidator's keeper registration (required during the keeper priority window)
+    #[account(
+        mut,
+        seeds = [KEEPER_SEED, keeper_registry.key().as_ref(), liquidator.key().as_ref()],
+        bump = keeper.bump
+    )]
+    pub keeper: Option<Account<'info, Keeper>>,
+
+    /// Market's liquidation queue, from which liquidated obligations are delisted
+    #[account(
+        mut,
+        seeds = [LIQUIDATION_QUEUE_SEED, market.key().as_ref()],
+        bump = liquidation_queue.bump
+    )]
+    pub liquidation_queue: Option<Account<'info, LiquidationQueue>>,
+    /// Protocol metrics counting the liquidations, when supplied
+    #[account(
+        mut,
+        seeds = [PROTOCOL_METRICS_SEED, market.key().as_ref()],
+        bump = protocol_metrics.bump
+    )]
+    pub protocol_metrics: Option<Account<'info, ProtocolMetrics>>,
+    // Note: Each entry's accounts are passed as remaining_accounts, laid out as described
+    // on `BatchLiquidationEntry::load`
 }
@@ -0,0 +1,107 @@
+use crate::constants::*;
+use crate::state::*;
+use crate::utils::require_admin;
+use anchor_lang::prelude::*;
+
+/// Create the market's liquidation queue (governance only)
+pub fn initialize_liquidation_queue(ctx: Context<InitializeLiquidationQueue>) -> Result<()> {
+    let market = &ctx.accounts.market;
+
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::InitializeLiquidationQueue,
+        &market.key(),
+    )?;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RISK_MANAGER,
+    )?;
+
+    **ctx.accounts.liquidation_queue =
+        LiquidationQueue::new(market.key(), ctx.bumps.liquidation_queue);
+
+    msg!("Liquidation queue initialized for market {}", market.key());
+    Ok(())
+}
+
+/// List a refreshed obligation in the liquidation queue while it is unhealthy and delist
+/// it once it is healthy again
+pub(crate) fn sync_liquidation_queue(
+    liquidation_queue: Option<&mut Account<LiquidationQueue>>,
+    obligation_key: Pubkey,
+    obligation: &Obligation,
+    health_factor_bps: u64,
+    current_slot: u64,
+) -> Result<()> {
+    if let Some(queue) = liquidation_queue {
+        if obligation.unhealthy_since_slot == 0 {
+            queue.remove(&obligation_key);
+        } else {
+            queue.enqueue(
+                obligation_key,
+                health_factor_bps,
+                obligation.unhealthy_since_slot,
+                current_slot,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Delist an obligation that was just liquidated or repaid
+pub(crate) fn dequeue_obligation(
+    liquidation_queue: Option<&mut Account<LiquidationQueue>>,
+    obligation_key: &Pubkey,
+) {
+    if let Some(queue) = liquidation_queue {
+        queue.remove(obligation_key);
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeLiquidationQueue<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Liquidation queue account to initialize
+    #[account(
+        init,
+        payer = payer,
+        space = LiquidationQueue::SIZE,
+        seeds = [LIQUIDATION_QUEUE_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub liquidation_queue: Account<'info, LiquidationQueue>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Payer for account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
@@ -1,8 +1,10 @@
 use crate::constants::*;
 use crate::error::LendingError;
+use crate::events::ReserveConfigUpdatedEvent;
 use crate::state::*;
+use crate::utils::{require_admin, validate_authority, TokenUtils};
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Mint, Token};
+use anchor_spl::token_interface::{self, CloseAccount, Mint, TokenAccount, TokenInterface};
 use solana_program::program_option::COption;
 
 /// Initialize the lending market
@@ -34,19 +36,34 @@ pub fn initialize_market(
 }
 
 /// Initialize a new reserve for an asset
+/// With `initial_liquidity`, the payer seeds the reserve in the same transaction and the
+/// aTokens are minted to the treasury collateral account, so the reserve is never empty
+/// and its exchange rate cannot be inflated by a first depositor.
 pub fn initialize_reserve(
     ctx: Context<InitializeReserve>,
     params: InitializeReserveParams,
 ) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.owner.key(),
+        AdminAction::InitializeReserve,
+        &params,
+    )?;
+
     let market = &mut ctx.accounts.market;
     let reserve = &mut ctx.accounts.reserve;
 
     // Validate reserve configuration
     validate_reserve_config(&params.config)?;
 
-    // Validate oracle feed ID is not empty
-    if params.oracle_feed_id == [0u8; 32] {
-        return Err(LendingError::OracleAccountMismatch.into());
+    // Validate the parameters of the selected price source
+    match params.oracle_source {
+        OracleSource::Pyth if params.oracle_feed_id == [0u8; 32] => {
+            return Err(LendingError::OracleAccountMismatch.into());
+        }
+        OracleSource::FixedPrice if params.fixed_price == 0 => {
+            return Err(LendingError::OraclePriceInvalid.into());
+        }
+        _ => {}
     }
 
     // Increment market reserves count
@@ -64,6 +81,62 @@ pub fn initialize_reserve(
         params.oracle_feed_id, // Use oracle feed ID from parameters
         params.config,
     )?;
+    reserve.oracle_source = params.oracle_source;
+    reserve.fixed_price = params.fixed_price;
+
+    if let Some(initial_liquidity) = params.initial_liquidity {
+        if initial_liquidity < MIN_DEPOSIT_AMOUNT {
+            return Err(LendingError::AmountTooSmall.into());
+        }
+        let payer_liquidity = ctx
+            .accounts
+            .payer_liquidity
+            .as_ref()
+            .ok_or(LendingError::InvalidAccount)?;
+
+        // Only what arrives after the mint's transfer fee backs the reserve
+        let received_liquidity =
+            TokenUtils::amount_after_transfer_fee(&ctx.accounts.liquidity_mint, initial_liquidity)?;
+
+        // The first deposit mints 1:1, backing the exchange rate from the start
+        let collateral_amount = reserve.liquidity_to_collateral(received_liquidity)?;
+
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            payer_liquidity,
+            &ctx.accounts.liquidity_supply,
+            &ctx.accounts.liquidity_mint,
+            &ctx.accounts.payer.to_account_info(),
+            &[],
+            initial_liquidity,
+        )?;
+
+        let liquidity_mint = ctx.accounts.liquidity_mint.key();
+        let collateral_mint_authority_seeds = &[
+            COLLATERAL_TOKEN_SEED,
+            liquidity_mint.as_ref(),
+            b"authority",
+            &[ctx.bumps.collateral_mint_authority],
+        ];
+
+        TokenUtils::mint_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.collateral_mint,
+            &ctx.accounts.treasury_collateral,
+            &ctx.accounts.collateral_mint_authority.to_account_info(),
+            &[collateral_mint_authority_seeds],
+            collateral_amount,
+        )?;
+
+        reserve.add_liquidity(received_liquidity)?;
+        reserve.state.collateral_mint_supply = collateral_amount;
+
+        msg!(
+            "Reserve seeded with {} liquidity, {} collateral tokens minted to treasury",
+            received_liquidity,
+            collateral_amount
+        );
+    }
 
     msg!(
         "Reserve initialized successfully for mint: {}",
@@ -72,24 +145,591 @@ pub fn initialize_reserve(
     Ok(())
 }
 
-/// Update reserve configuration (owner only)
+/// Close an empty reserve and its liquidity supply account (governance only)
+/// The reserve must hold no liquidity, borrows, aTokens or uncollected fees. The rent is
+/// returned to the authority; the collateral mint cannot be closed and stays behind.
+pub fn close_reserve(ctx: Context<CloseReserve>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::CloseReserve,
+        &ctx.accounts.reserve.key(),
+    )?;
+
+    let market = &mut ctx.accounts.market;
+    let reserve = &ctx.accounts.reserve;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RESERVE_MANAGER,
+    )?;
+
+    if !reserve.is_empty() || ctx.accounts.liquidity_supply.amount != 0 {
+        return Err(LendingError::ReserveNotEmpty.into());
+    }
+
+    let authority_seeds = &[
+        LIQUIDITY_TOKEN_SEED,
+        reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.liquidity_supply_authority],
+    ];
+    token_interface::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.liquidity_supply.to_account_info(),
+            destination: ctx.accounts.authority.to_account_info(),
+            authority: ctx.accounts.liquidity_supply_authority.to_account_info(),
+        },
+        &[authority_seeds],
+    ))?;
+
+    market.decrement_reserves_count()?;
+    market.update_timestamp()?;
+
+    msg!(
+        "Reserve {} for mint {} closed",
+        reserve.key(),
+        reserve.liquidity_mint
+    );
+    Ok(())
+}
+
+/// Update reserve configuration (market multisig or risk managers)
 pub fn update_reserve_config(
     ctx: Context<UpdateReserveConfig>,
     params: UpdateReserveConfigParams,
 ) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.owner.key(),
+        AdminAction::UpdateReserveConfig,
+        &(ctx.accounts.reserve.key(), params.clone()),
+    )?;
+
+    require_admin(
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RISK_MANAGER,
+    )?;
+
     let reserve = &mut ctx.accounts.reserve;
 
     // Validate new configuration
     validate_reserve_config(&params.config)?;
+    reserve.validate_emergency_pauses(&params.config)?;
 
     // Update configuration
+    let clock = Clock::get()?;
     reserve.config = params.config;
-    reserve.last_update_timestamp = Clock::get()?.unix_timestamp as u64;
+    reserve.last_update_timestamp = clock.unix_timestamp as u64;
+
+    emit!(ReserveConfigUpdatedEvent {
+        reserve: reserve.key(),
+        authority: ctx.accounts.owner.key(),
+        action: AdminAction::UpdateReserveConfig,
+        config: reserve.config.clone(),
+        slot: clock.slot,
+    });
 
     msg!("Reserve configuration updated successfully");
     Ok(())
 }
 
+/// Update the supply and borrow caps and the isolated debt ceiling of a reserve
+/// (governance only)
+/// Kept separate from `update_reserve_config` so caps can be adjusted under the shorter
+/// `UpdateReserveCaps` timelock delay. A cap of 0 leaves that side uncapped; lowering a
+/// cap below current usage only blocks further growth.
+pub fn update_reserve_caps(
+    ctx: Context<UpdateReserveCaps>,
+    params: UpdateReserveCapsParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::UpdateReserveCaps,
+        &(ctx.accounts.reserve.key(), params.clone()),
+    )?;
+
+    let market = &ctx.accounts.market;
+    let reserve = &mut ctx.accounts.reserve;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RISK_MANAGER,
+    )?;
+
+    let clock = Clock::get()?;
+    reserve.config.supply_cap = params.supply_cap;
+    reserve.config.borrow_cap = params.borrow_cap;
+    reserve.debt_ceiling_usd = params.debt_ceiling_usd;
+    reserve.last_update_timestamp = clock.unix_timestamp as u64;
+
+    emit!(ReserveConfigUpdatedEvent {
+        reserve: reserve.key(),
+        authority: ctx.accounts.authority.key(),
+        action: AdminAction::UpdateReserveCaps,
+        config: reserve.config.clone(),
+        slot: clock.slot,
+    });
+
+    msg!(
+        "Reserve {} caps updated - supply: {}, borrow: {}, isolated debt ceiling: ${}",
+        reserve.key(),
+        params.supply_cap,
+        params.borrow_cap,
+        params.debt_ceiling_usd
+    );
+    Ok(())
+}
+
+/// Configure the secondary oracle that cross-checks a reserve's price (governance only)
+/// While configured, refreshes, borrows and liquidations fail when the two prices
+/// diverge by more than `max_oracle_deviation_bps`.
+pub fn update_reserve_secondary_oracle(
+    ctx: Context<UpdateReserveSecondaryOracle>,
+    params: UpdateReserveSecondaryOracleParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::UpdateReserveSecondaryOracle,
+        &(ctx.accounts.reserve.key(), params.clone()),
+    )?;
+
+    let market = &ctx.accounts.market;
+    let reserve = &mut ctx.accounts.reserve;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::ORACLE_MANAGER,
+    )?;
+
+    reserve.set_secondary_oracle(&params)?;
+    reserve.last_update_timestamp = Clock::get()?.unix_timestamp as u64;
+
+    msg!(
+        "Reserve {} secondary oracle set to {} (max deviation {} bps)",
+        reserve.key(),
+        params.secondary_oracle,
+        params.max_oracle_deviation_bps
+    );
+    Ok(())
+}
+
+/// Configure how a reserve's LST or LP token price is derived (governance only)
+/// Reserves initialized with the `Derived` oracle source read the price cached by
+/// `refresh_derived_price` from these components.
+pub fn update_reserve_derived_oracle(
+    ctx: Context<UpdateReserveDerivedOracle>,
+    params: UpdateReserveDerivedOracleParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::UpdateReserveDerivedOracle,
+        &(ctx.accounts.reserve.key(), params.clone()),
+    )?;
+
+    let market = &ctx.accounts.market;
+    let reserve = &mut ctx.accounts.reserve;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::ORACLE_MANAGER,
+    )?;
+
+    reserve.set_derived_oracle(&params)?;
+    reserve.last_update_timestamp = Clock::get()?.unix_timestamp as u64;
+
+    msg!(
+        "Reserve {} derived oracle set to {:?} over pool {}",
+        reserve.key(),
+        params.kind,
+        params.pool
+    );
+    Ok(())
+}
+
+/// Set the TWAP window smoothing a reserve's liquidation prices (governance only)
+/// A window of 0 disables smoothing; liquidations then use the spot price.
+pub fn update_reserve_twap_window(
+    ctx: Context<UpdateReserveTwapWindow>,
+    params: UpdateReserveTwapWindowParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::UpdateReserveTwapWindow,
+        &(ctx.accounts.reserve.key(), params.clone()),
+    )?;
+
+    let market = &ctx.accounts.market;
+    let reserve = &mut ctx.accounts.reserve;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::ORACLE_MANAGER,
+    )?;
+
+    reserve.set_twap_window(params.twap_window_seconds)?;
+    reserve.last_update_timestamp = Clock::get()?.unix_timestamp as u64;
+
+    msg!(
+        "Reserve {} TWAP window set to {} seconds",
+        reserve.key(),
+        params.twap_window_seconds
+    );
+    Ok(())
+}
+
+/// Configure a reserve's surge withdrawal fee (governance only)
+/// Once utilization has stayed above the threshold for `duration_slots`, redemptions
+/// leave `withdrawal_fee_bps` of the liquidity in the reserve for remaining suppliers.
+pub fn update_reserve_surge_fee(
+    ctx: Context<UpdateReserveSurgeFee>,
+    params: UpdateReserveSurgeFeeParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::UpdateReserveSurgeFee,
+        &(ctx.accounts.reserve.key(), params.clone()),
+    )?;
+
+    let market = &ctx.accounts.market;
+    let reserve = &mut ctx.accounts.reserve;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RISK_MANAGER,
+    )?;
+
+    let clock = Clock::get()?;
+    reserve.set_surge_fee(&params)?;
+    reserve.track_surge_utilization(clock.slot)?;
+    reserve.last_update_timestamp = clock.unix_timestamp as u64;
+
+    msg!(
+        "Reserve {} surge fee set to {} bps above {} bps utilization for {} slots",
+        reserve.key(),
+        params.withdrawal_fee_bps,
+        params.utilization_threshold_bps,
+        params.duration_slots
+    );
+    Ok(())
+}
+
+/// Switch the curve a reserve's borrow rate follows (governance only)
+/// Requires an executed `UpdateReserveConfig` timelock proposal whose instruction data
+/// is the serialized strategy and whose targets include the reserve. Interest is
+/// accrued under the old strategy first, and the proposal is closed on use.
+pub fn set_reserve_interest_rate_strategy(
+    ctx: Context<SetReserveInterestRateStrategy>,
+    strategy: InterestRateStrategy,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::SetReserveInterestRateStrategy,
+        &(ctx.accounts.reserve.key(), strategy),
+    )?;
+
+    let market = &ctx.accounts.market;
+    let reserve = &mut ctx.accounts.reserve;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RISK_MANAGER,
+    )?;
+
+    reserve.validate_strategy_proposal(
+        &reserve.key(),
+        &ctx.accounts.executed_proposal,
+        &market.timelock_controller,
+        strategy,
+    )?;
+
+    let clock = Clock::get()?;
+    reserve.update_interest(clock.slot)?;
+    reserve.config.interest_rate_strategy = strategy;
+
+    emit!(ReserveConfigUpdatedEvent {
+        reserve: reserve.key(),
+        authority: ctx.accounts.authority.key(),
+        action: AdminAction::SetReserveInterestRateStrategy,
+        config: reserve.config.clone(),
+        slot: clock.slot,
+    });
+
+    msg!(
+        "Reserve {} interest rate strategy set to {:?}",
+        reserve.key(),
+        strategy
+    );
+    Ok(())
+}
+
+/// Disable operations on a single reserve right away (emergency responders)
+/// `operations` may combine `DEPOSITS_DISABLED`, `WITHDRAWALS_DISABLED`,
+/// `BORROWING_DISABLED` and `LIQUIDATIONS_DISABLED`. Paused operations stay disabled
+/// through `update_reserve_config` until `unpause_reserve_operations` lifts them.
+pub fn pause_reserve_operations(
+    ctx: Context<PauseReserveOperations>,
+    operations: ReserveConfigFlags,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::PauseReserveOperations,
+        &(ctx.accounts.reserve.key(), operations),
+    )?;
+
+    PermissionChecker::check_permission(
+        &ctx.accounts.governance,
+        &ctx.accounts.authority.key(),
+        Permission::EMERGENCY_RESPONDER,
+    )?;
+
+    let reserve = &mut ctx.accounts.reserve;
+    let clock = Clock::get()?;
+    reserve.emergency_pause(operations)?;
+    reserve.last_update_timestamp = clock.unix_timestamp as u64;
+
+    emit!(ReserveConfigUpdatedEvent {
+        reserve: reserve.key(),
+        authority: ctx.accounts.authority.key(),
+        action: AdminAction::PauseReserveOperations,
+        config: reserve.config.clone(),
+        slot: clock.slot,
+    });
+
+    msg!(
+        "Reserve {} operations {:#x} paused",
+        reserve.key(),
+        operations.bits()
+    );
+    Ok(())
+}
+
+/// Re-enable operations paused by `pause_reserve_operations` (governance only)
+/// Requires an executed `UpdateReserveConfig` timelock proposal whose instruction data
+/// is the serialized operations and whose targets include the reserve. The proposal is
+/// closed on use.
+pub fn unpause_reserve_operations(
+    ctx: Context<UnpauseReserveOperations>,
+    operations: ReserveConfigFlags,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::UnpauseReserveOperations,
+        &(ctx.accounts.reserve.key(), operations),
+    )?;
+
+    let market = &ctx.accounts.market;
+    let reserve = &mut ctx.accounts.reserve;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RISK_MANAGER,
+    )?;
+
+    Reserve::validate_config_proposal(
+        &reserve.key(),
+        &ctx.accounts.executed_proposal,
+        &market.timelock_controller,
+        &operations,
+    )?;
+
+    let clock = Clock::get()?;
+    reserve.emergency_unpause(operations)?;
+    reserve.last_update_timestamp = clock.unix_timestamp as u64;
+
+    emit!(ReserveConfigUpdatedEvent {
+        reserve: reserve.key(),
+        authority: ctx.accounts.authority.key(),
+        action: AdminAction::UnpauseReserveOperations,
+        config: reserve.config.clone(),
+        slot: clock.slot,
+    });
+
+    msg!(
+        "Reserve {} operations {:#x} unpaused",
+        reserve.key(),
+        operations.bits()
+    );
+    Ok(())
+}
+
+/// Clear a reserve's reentrancy lock (emergency responders)
+/// The lock only outlives an instruction if a successful instruction never releases it,
+/// which would block every operation on the reserve. Fails if the reserve is not locked.
+pub fn force_unlock_reserve(ctx: Context<ForceUnlockReserve>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::ForceUnlockReserve,
+        &ctx.accounts.reserve.key(),
+    )?;
+
+    PermissionChecker::check_permission(
+        &ctx.accounts.governance,
+        &ctx.accounts.authority.key(),
+        Permission::EMERGENCY_RESPONDER,
+    )?;
+
+    let reserve = &mut ctx.accounts.reserve;
+    if !reserve.is_locked() {
+        return Err(LendingError::InvalidUnlockOperation.into());
+    }
+    reserve.force_unlock();
+
+    msg!("Reserve {} force unlocked", reserve.key());
+    Ok(())
+}
+
+/// Tag a reserve with an asset class (governance only)
+/// Reserves sharing a class are treated as interchangeable borrow sources
+pub fn update_reserve_asset_class(
+    ctx: Context<UpdateReserveAssetClass>,
+    asset_class: u8,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::UpdateReserveAssetClass,
+        &(ctx.accounts.reserve.key(), asset_class),
+    )?;
+
+    let market = &ctx.accounts.market;
+    let reserve = &mut ctx.accounts.reserve;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RESERVE_MANAGER,
+    )?;
+
+    reserve.asset_class = asset_class;
+    reserve.last_update_timestamp = Clock::get()?.unix_timestamp as u64;
+
+    msg!("Reserve {} tagged with asset class {}", reserve.key(), asset_class);
+    Ok(())
+}
+
+/// Create the market's admin audit log (governance only)
+/// Every admin entrypoint requires the log from then on, so create it right after the
+/// market and its governance accounts.
+pub fn initialize_admin_audit_log(ctx: Context<InitializeAdminAuditLog>) -> Result<()> {
+    let market = &ctx.accounts.market;
+
+    validate_authority(&ctx.accounts.authority.to_account_info(), &market.multisig_owner)?;
+
+    let mut log = AdminAuditLog::new(market.key(), ctx.bumps.admin_audit_log);
+    log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::InitializeAdminAuditLog,
+        &market.key(),
+    )?;
+    **ctx.accounts.admin_audit_log = log;
+
+    msg!("Admin audit log initialized for market {}", market.key());
+    Ok(())
+}
+
+/// Create the market's lookup table record and point it at a table (governance only)
+pub fn initialize_market_lookup_table(
+    ctx: Context<InitializeMarketLookupTable>,
+    params: SetMarketLookupTableParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::InitializeMarketLookupTable,
+        &(ctx.accounts.lookup_table.key(), params.covered_reserves),
+    )?;
+
+    let market = &ctx.accounts.market;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RESERVE_MANAGER,
+    )?;
+
+    let mut record = MarketLookupTable::new(market.key(), ctx.bumps.market_lookup_table);
+    record_lookup_table(
+        &mut record,
+        market,
+        &ctx.accounts.lookup_table.to_account_info(),
+        params.covered_reserves,
+    )?;
+    **ctx.accounts.market_lookup_table = record;
+
+    Ok(())
+}
+
+/// Replace the market's lookup table or update its reserve coverage (governance only)
+pub fn set_market_lookup_table(
+    ctx: Context<SetMarketLookupTable>,
+    params: SetMarketLookupTableParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::SetMarketLookupTable,
+        &(ctx.accounts.lookup_table.key(), params.covered_reserves),
+    )?;
+
+    let market = &ctx.accounts.market;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RESERVE_MANAGER,
+    )?;
+
+    record_lookup_table(
+        &mut ctx.accounts.market_lookup_table,
+        market,
+        &ctx.accounts.lookup_table.to_account_info(),
+        params.covered_reserves,
+    )
+}
+
+/// Validate a lookup table and store it in the market's record
+fn record_lookup_table(
+    record: &mut MarketLookupTable,
+    market: &Market,
+    lookup_table: &AccountInfo,
+    covered_reserves: u64,
+) -> Result<()> {
+    MarketLookupTable::validate_table(lookup_table, &market.multisig_owner)?;
+
+    if covered_reserves > market.reserves_count {
+        return Err(LendingError::InvalidConfiguration.into());
+    }
+
+    record.set(lookup_table.key(), covered_reserves, Clock::get()?.slot)?;
+
+    msg!(
+        "Market lookup table set to {} covering {} of {} reserves (revision {})",
+        record.lookup_table,
+        covered_reserves,
+        market.reserves_count,
+        record.revision
+    );
+    Ok(())
+}
+
 /// Validate reserve configuration parameters
 fn validate_reserve_config(config: &ReserveConfig) -> Result<()> {
     // Validate loan-to-value ratio
@@ -122,6 +762,30 @@ fn validate_reserve_config(config: &ReserveConfig) -> Result<()> {
         return Err(LendingError::InvalidReserveConfig.into());
     }
 
+    // Validate borrow fee and its insurance fund share
+    if config.borrow_fee_bps > MAX_BORROW_FEE_BPS
+        || config.insurance_fund_share_bps > BASIS_POINTS_PRECISION
+    {
+        return Err(LendingError::InvalidReserveConfig.into());
+    }
+
+    // Per-reserve oracle limits can only tighten the protocol-wide ones
+    if config.max_confidence_bps > MAX_ORACLE_CONFIDENCE_BPS
+        || config.max_staleness_slots > MAX_ORACLE_STALENESS_SLOTS
+    {
+        return Err(LendingError::InvalidReserveConfig.into());
+    }
+
+    // A close factor override must still let a liquidation repay something
+    if config.liquidation_close_factor_bps > BASIS_POINTS_PRECISION {
+        return Err(LendingError::InvalidReserveConfig.into());
+    }
+
+    // The protocol can take at most the whole liquidation bonus
+    if config.liquidation_protocol_fee_bps > BASIS_POINTS_PRECISION {
+        return Err(LendingError::InvalidReserveConfig.into());
+    }
+
     Ok(())
 }
 
@@ -140,10 +804,10 @@ pub struct InitializeMarket<'info> {
     pub market: Account<'info, Market>,
 
     /// Quote currency mint (e.g., USDC)
-    pub quote_currency_mint: Account<'info, Mint>,
+    pub quote_currency_mint: InterfaceAccount<'info, Mint>,
 
     /// AURA governance token mint
-    pub aura_token_mint: Account<'info, Mint>,
+    pub aura_token_mint: InterfaceAccount<'info, Mint>,
 
     /// Authority for minting AURA tokens (PDA)
     /// CHECK: This account will be validated in the instruction
@@ -179,7 +843,7 @@ pub struct InitializeReserve<'info> {
     pub reserve: Account<'info, Reserve>,
 
     /// Liquidity token mint (e.g., USDC, SOL)
-    pub liquidity_mint: Account<'info, Mint>,
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
 
     /// Collateral token mint (aToken)
     #[account(
@@ -190,7 +854,7 @@ pub struct InitializeReserve<'info> {
         seeds = [COLLATERAL_TOKEN_SEED, liquidity_mint.key().as_ref()],
         bump
     )]
-    pub collateral_mint: Account<'info, Mint>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
 
     /// Authority for collateral mint (PDA)
     /// CHECK: This is a PDA derived from seeds
@@ -206,7 +870,7 @@ pub struct InitializeReserve<'info> {
         seeds = [LIQUIDITY_TOKEN_SEED, liquidity_mint.key().as_ref()],
         bump
     )]
-    pub liquidity_supply: Account<'info, anchor_spl::token::TokenAccount>,
+    pub liquidity_supply: InterfaceAccount<'info, TokenAccount>,
 
     /// Authority for liquidity supply (PDA)
     /// CHECK: This is a PDA derived from seeds
@@ -220,11 +884,36 @@ pub struct InitializeReserve<'info> {
         token::mint = liquidity_mint,
         token::authority = owner,
     )]
-    pub fee_receiver: Account<'info, anchor_spl::token::TokenAccount>,
+    pub fee_receiver: InterfaceAccount<'info, TokenAccount>,
+
+    /// Treasury collateral token account receiving the aTokens of the seed deposit
+    #[account(
+        init,
+        payer = payer,
+        token::mint = collateral_mint,
+        token::authority = owner,
+    )]
+    pub treasury_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Payer's liquidity token account funding `initial_liquidity` (required with it)
+    #[account(
+        mut,
+        token::mint = liquidity_mint,
+        token::authority = payer,
+    )]
+    pub payer_liquidity: Option<InterfaceAccount<'info, TokenAccount>>,
 
     /// Market owner (must sign for reserve creation)
     pub owner: Signer<'info>,
 
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
     /// Payer for account creation
     #[account(mut)]
     pub payer: Signer<'info>,
@@ -233,30 +922,607 @@ pub struct InitializeReserve<'info> {
     pub system_program: Program<'info, System>,
 
     /// Token program
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 
     /// Rent sysvar
     pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateReserveConfig<'info> {
+pub struct CloseReserve<'info> {
     /// Market account
     #[account(
+        mut,
         seeds = [MARKET_SEED],
         bump
     )]
     pub market: Account<'info, Market>,
 
-    /// Reserve account to update
+    /// Reserve account to close
     #[account(
         mut,
+        close = authority,
         seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
         bump,
-        has_one = market @ LendingError::InvalidMarketState
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = liquidity_supply @ LendingError::InvalidAccount
     )]
     pub reserve: Account<'info, Reserve>,
 
-    /// Market owner (must sign for configuration changes)
-    pub owner: Signer<'info>,
+    /// Liquidity supply token account, closed with the reserve
+    #[account(mut)]
+    pub liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Authority for liquidity supply (PDA)
+    /// CHECK: This is a PDA derived from seeds
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Market multisig owner, receiving the rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateReserveConfig<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve account to update
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Market multisig or a holder of the risk manager role
+    pub owner: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateReserveCaps<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve account to update
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateReserveSecondaryOracle<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve account to update
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateReserveDerivedOracle<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve account to update
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateReserveTwapWindow<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve account to update
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateReserveSurgeFee<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve account to update
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct SetReserveInterestRateStrategy<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve account to update
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// The executed timelock proposal that authorizes this change
+    #[account(
+        mut,
+        close = authority
+    )]
+    pub executed_proposal: Account<'info, TimelockProposal>,
+
+    /// Market multisig owner (receives the proposal's rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct PauseReserveOperations<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve account to pause
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Governance registry granting the emergency responder role
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Account<'info, GovernanceRegistry>,
+
+    /// Emergency responder
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+}
+
+#[derive(Accounts)]
+pub struct UnpauseReserveOperations<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve account to unpause
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// The executed timelock proposal that authorizes this change
+    #[account(
+        mut,
+        close = authority
+    )]
+    pub executed_proposal: Account<'info, TimelockProposal>,
+
+    /// Market multisig owner (receives the proposal's rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct ForceUnlockReserve<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve account to unlock
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Governance registry granting the emergency responder role
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Account<'info, GovernanceRegistry>,
+
+    /// Emergency responder
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateReserveAssetClass<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve account to tag
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAdminAuditLog<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Admin audit log to initialize
+    #[account(
+        init,
+        payer = payer,
+        space = AdminAuditLog::SIZE,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Payer for account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeMarketLookupTable<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Lookup table record to initialize
+    #[account(
+        init,
+        payer = payer,
+        space = MarketLookupTable::SIZE,
+        seeds = [LOOKUP_TABLE_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub market_lookup_table: Account<'info, MarketLookupTable>,
+
+    /// Address Lookup Table account
+    /// CHECK: Owner and authority are validated in the instruction
+    pub lookup_table: UncheckedAccount<'info>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Payer for account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct SetMarketLookupTable<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Lookup table record
+    #[account(
+        mut,
+        seeds = [LOOKUP_TABLE_SEED, market.key().as_ref()],
+        bump = market_lookup_table.bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub market_lookup_table: Account<'info, MarketLookupTable>,
+
+    /// Address Lookup Table account
+    /// CHECK: Owner and authority are validated in the instruction
+    pub lookup_table: UncheckedAccount<'info>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
 }
@@ -0,0 +1,224 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::*;
+use crate::utils::{require_admin, Decimal, OracleManager, ProtocolMetrics, PROTOCOL_METRICS_SEED};
+use anchor_lang::prelude::*;
+
+/// Create the market's protocol metrics account (governance only)
+pub fn initialize_protocol_metrics(ctx: Context<InitializeProtocolMetrics>) -> Result<()> {
+    let market = &ctx.accounts.market;
+
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::InitializeProtocolMetrics,
+        &market.key(),
+    )?;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RESERVE_MANAGER,
+    )?;
+
+    **ctx.accounts.protocol_metrics =
+        ProtocolMetrics::new(market.key(), ctx.bumps.protocol_metrics)?;
+
+    msg!("Protocol metrics initialized for market {}", market.key());
+    Ok(())
+}
+
+/// Aggregate every reserve of the market into the protocol metrics (permissionless crank)
+/// All of the market's reserves are passed as remaining_accounts, each refreshed within
+/// `MAX_ORACLE_STALENESS_SLOTS`. They are valued at the price of their last refresh, so
+/// no oracle accounts are needed.
+pub fn update_protocol_metrics<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateProtocolMetrics<'info>>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let totals = aggregate_reserves(ctx.remaining_accounts, &ctx.accounts.market, clock.slot)?;
+
+    let metrics = &mut ctx.accounts.protocol_metrics;
+    metrics.roll_liquidation_window(clock.unix_timestamp as u64);
+
+    // User counts and health are not derivable from reserves and keep their last values
+    let active_users = metrics.active_users;
+    let average_health_factor = metrics.average_health_factor;
+    metrics.update(
+        totals.tvl_usd.try_floor_u64()?,
+        totals.borrowed_usd.try_floor_u64()?,
+        totals.fees_usd.try_floor_u64()?,
+        active_users,
+        totals.reserves,
+        average_health_factor,
+    )?;
+
+    msg!(
+        "Protocol metrics updated - TVL: ${}, borrowed: ${}, utilization: {} bps",
+        metrics.total_value_locked_usd,
+        metrics.total_borrowed_usd,
+        metrics.protocol_utilization_rate
+    );
+    Ok(())
+}
+
+/// USD totals of a market's reserves
+pub(crate) struct ReserveTotals {
+    /// Liquidity supplied, including what is borrowed
+    pub tvl_usd: Decimal,
+
+    /// Liquidity borrowed
+    pub borrowed_usd: Decimal,
+
+    /// Protocol fees accrued and not yet withdrawn
+    pub fees_usd: Decimal,
+
+    /// Reserves summed
+    pub reserves: u32,
+}
+
+/// Sum every reserve of `market` at the price of its last refresh
+/// `reserve_infos` must hold exactly the market's reserves, each refreshed within
+/// `MAX_ORACLE_STALENESS_SLOTS`; a partial set would understate the totals.
+pub(crate) fn aggregate_reserves(
+    reserve_infos: &[AccountInfo],
+    market: &Account<Market>,
+    current_slot: u64,
+) -> Result<ReserveTotals> {
+    if reserve_infos.len() as u64 != market.reserves_count {
+        return Err(LendingError::InvalidAccount.into());
+    }
+
+    let mut seen: Vec<Pubkey> = Vec::with_capacity(reserve_infos.len());
+    let mut totals = ReserveTotals {
+        tvl_usd: Decimal::zero(),
+        borrowed_usd: Decimal::zero(),
+        fees_usd: Decimal::zero(),
+        reserves: 0,
+    };
+
+    for reserve_info in reserve_infos.iter() {
+        if seen.contains(reserve_info.key) {
+            return Err(LendingError::InvalidAccount.into());
+        }
+        seen.push(reserve_info.key());
+
+        let reserve =
+            Account::<Reserve>::try_from(reserve_info).map_err(|_| LendingError::InvalidAccount)?;
+        if reserve.market != market.key() {
+            return Err(LendingError::InvalidMarketState.into());
+        }
+        if reserve.is_stale(current_slot) {
+            return Err(LendingError::ReserveStale.into());
+        }
+
+        let price = reserve
+            .last_refreshed_price()
+            .ok_or(LendingError::ReserveStale)?;
+        let decimals = reserve.config.decimals;
+
+        let value = |amount: u64| OracleManager::calculate_usd_value(amount, &price, decimals);
+        totals.tvl_usd = totals
+            .tvl_usd
+            .try_add(value(reserve.state.total_liquidity)?)?;
+        totals.borrowed_usd = totals
+            .borrowed_usd
+            .try_add(value(reserve.state.total_borrows)?)?;
+        totals.fees_usd = totals
+            .fees_usd
+            .try_add(value(reserve.state.accumulated_protocol_fees)?)?;
+        totals.reserves += 1;
+    }
+
+    Ok(totals)
+}
+
+/// Count a deposit in the protocol metrics, when the account is passed
+pub(crate) fn record_deposit_metric(protocol_metrics: Option<&mut Account<ProtocolMetrics>>) {
+    if let Some(metrics) = protocol_metrics {
+        metrics.record_deposit();
+    }
+}
+
+/// Count a borrow in the protocol metrics, when the account is passed
+pub(crate) fn record_borrow_metric(protocol_metrics: Option<&mut Account<ProtocolMetrics>>) {
+    if let Some(metrics) = protocol_metrics {
+        metrics.record_borrow();
+    }
+}
+
+/// Count a liquidation in the protocol metrics, when the account is passed
+pub(crate) fn record_liquidation_metric(
+    protocol_metrics: Option<&mut Account<ProtocolMetrics>>,
+    timestamp: i64,
+) -> Result<()> {
+    if let Some(metrics) = protocol_metrics {
+        metrics.record_liquidation(timestamp as u64)?;
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeProtocolMetrics<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol metrics account to initialize
+    #[account(
+        init,
+        payer = payer,
+        space = ProtocolMetrics::SIZE,
+        seeds = [PROTOCOL_METRICS_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub protocol_metrics: Account<'info, ProtocolMetrics>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Payer for account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProtocolMetrics<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol metrics account to update
+    #[account(
+        mut,
+        seeds = [PROTOCOL_METRICS_SEED, market.key().as_ref()],
+        bump = protocol_metrics.bump,
+        constraint = protocol_metrics.market == market.key() @ LendingError::InvalidMarketState
+    )]
+    pub protocol_metrics: Account<'info, ProtocolMetrics>,
+    // Note: every reserve of the market is passed as remaining_accounts
+}
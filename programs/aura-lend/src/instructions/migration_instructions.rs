@@ -1,18 +1,34 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 
 use crate::{
     constants::*,
     error::LendingError,
-    migration::{validate_migration_compatibility, Migratable},
+    migration::{
+        compact_reserve, validate_migration_compatibility, Migratable, MigrationAccountKind,
+        MigrationPlan,
+    },
     state::{
-        governance::GovernanceRegistry, market::Market, multisig::MultiSig, obligation::Obligation,
-        reserve::Reserve, timelock::TimelockController,
+        any_obligation::{AnyObligation, ObligationLayout},
+        audit_log::{AdminAction, AdminAuditLog},
+        governance::GovernanceRegistry,
+        market::Market,
+        multisig::MultiSig,
+        obligation::Obligation,
+        reserve::Reserve,
+        timelock::TimelockController,
     },
-    utils::validate_authority,
+    utils::{validate_authority, ComputeBudgetGuard, ProtocolConfig},
 };
 
 /// Migrate Market state to current version
 pub fn migrate_market(ctx: Context<MigrateMarket>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::MigrateMarket,
+        &ctx.accounts.market.key(),
+    )?;
+
     let market = &mut ctx.accounts.market;
     let authority = &ctx.accounts.authority;
 
@@ -43,19 +59,38 @@ pub fn migrate_market(ctx: Context<MigrateMarket>) -> Result<()> {
 }
 
 /// Migrate Reserve state to current version
+/// Legacy reserves are first compacted to the packed layout, the authority covering
+/// (or being refunded) the rent difference
 pub fn migrate_reserve(ctx: Context<MigrateReserve>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::MigrateReserve,
+        &ctx.accounts.reserve.key(),
+    )?;
+
     let market = &ctx.accounts.market;
-    let reserve = &mut ctx.accounts.reserve;
+    let reserve_info = ctx.accounts.reserve.to_account_info();
     let authority = &ctx.accounts.authority;
 
     // Validate authority
     validate_authority(&authority.to_account_info(), &market.multisig_owner)?;
 
     // Verify reserve belongs to market
-    if reserve.market != market.key() {
+    if reserve_info.owner != &crate::id() || !reserve_belongs_to(&reserve_info, &market.key())? {
         return Err(LendingError::InvalidAccount.into());
     }
 
+    if compact_reserve(
+        &reserve_info,
+        &authority.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )? {
+        msg!("Reserve compacted to version {}", RESERVE_VERSION);
+        return Ok(());
+    }
+
+    let mut reserve = Account::<Reserve>::try_from(&reserve_info)?;
+
     // Check if migration is needed
     if !reserve.needs_migration() {
         msg!(
@@ -66,21 +101,34 @@ pub fn migrate_reserve(ctx: Context<MigrateReserve>) -> Result<()> {
     }
 
     let from_version = reserve.version();
-    validate_migration_compatibility(from_version, PROGRAM_VERSION)?;
+    validate_migration_compatibility(from_version, Reserve::current_version())?;
 
     // Perform migration
     reserve.migrate(from_version)?;
+    reserve.exit(&crate::id())?;
 
     msg!(
         "Reserve migration completed from version {} to {}",
         from_version,
-        PROGRAM_VERSION
+        reserve.version()
     );
     Ok(())
 }
 
+/// Whether a reserve account in any layout belongs to `market`
+/// The market is stored right after the version in every layout
+fn reserve_belongs_to(reserve_info: &AccountInfo, market: &Pubkey) -> Result<bool> {
+    Ok(reserve_info.try_borrow_data()?.get(9..41) == Some(market.as_ref()))
+}
+
 /// Migrate Obligation state to current version
 pub fn migrate_obligation(ctx: Context<MigrateObligation>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::MigrateObligation,
+        &ctx.accounts.obligation.key(),
+    )?;
+
     let market = &ctx.accounts.market;
     let obligation = &mut ctx.accounts.obligation;
     let authority = &ctx.accounts.authority;
@@ -118,6 +166,12 @@ pub fn migrate_obligation(ctx: Context<MigrateObligation>) -> Result<()> {
 
 /// Migrate MultiSig state to current version
 pub fn migrate_multisig(ctx: Context<MigrateMultisig>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::MigrateMultisig,
+        &ctx.accounts.multisig.key(),
+    )?;
+
     let market = &ctx.accounts.market;
     let multisig = &mut ctx.accounts.multisig;
     let authority = &ctx.accounts.authority;
@@ -150,6 +204,12 @@ pub fn migrate_multisig(ctx: Context<MigrateMultisig>) -> Result<()> {
 
 /// Migrate TimelockController state to current version
 pub fn migrate_timelock(ctx: Context<MigrateTimelock>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::MigrateTimelock,
+        &ctx.accounts.timelock.key(),
+    )?;
+
     let market = &ctx.accounts.market;
     let timelock = &mut ctx.accounts.timelock;
     let authority = &ctx.accounts.authority;
@@ -182,6 +242,12 @@ pub fn migrate_timelock(ctx: Context<MigrateTimelock>) -> Result<()> {
 
 /// Migrate GovernanceRegistry state to current version
 pub fn migrate_governance(ctx: Context<MigrateGovernance>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::MigrateGovernance,
+        &ctx.accounts.governance.key(),
+    )?;
+
     let market = &ctx.accounts.market;
     let governance = &mut ctx.accounts.governance;
     let authority = &ctx.accounts.authority;
@@ -216,6 +282,17 @@ pub fn migrate_governance(ctx: Context<MigrateGovernance>) -> Result<()> {
 pub fn batch_migrate_reserves<'info>(
     ctx: Context<'_, '_, '_, 'info, BatchMigrateReserves<'info>>,
 ) -> Result<()> {
+    let reserve_keys: Vec<Pubkey> = ctx
+        .remaining_accounts
+        .iter()
+        .map(|account| account.key())
+        .collect();
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::BatchMigrateReserves,
+        &reserve_keys,
+    )?;
+
     let market = &ctx.accounts.market;
     let authority = &ctx.accounts.authority;
 
@@ -223,12 +300,27 @@ pub fn batch_migrate_reserves<'info>(
     validate_authority(&authority.to_account_info(), &market.multisig_owner)?;
 
     let remaining_accounts = &ctx.remaining_accounts;
+    ctx.accounts
+        .config
+        .validate_account_count(remaining_accounts.len())?;
     let mut migrated_count = 0;
     let mut skipped_count = 0;
     let mut failed_count = 0;
+    let mut budget =
+        ComputeBudgetGuard::new(MigrationAccountKind::Reserve.estimated_compute_units() as u64);
 
     // Process each reserve account in batches to avoid transaction size limits
     for account_info in remaining_accounts.iter() {
+        // Reserves that no longer fit are left for the next batch
+        if !budget.try_begin_step() {
+            msg!(
+                "Skipping reserve {} - compute budget exhausted",
+                account_info.key()
+            );
+            skipped_count += 1;
+            continue;
+        }
+
         // Validate account ownership
         if account_info.owner != &crate::id() {
             msg!(
@@ -264,6 +356,23 @@ pub fn batch_migrate_reserves<'info>(
         // Drop the borrow before working with the account
         drop(account_data);
 
+        // Legacy reserves of this market only need compacting to the packed layout
+        if reserve_belongs_to(account_info, &market.key())?
+            && compact_reserve(
+                account_info,
+                &authority.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+            )?
+        {
+            migrated_count += 1;
+            msg!(
+                "Compacted reserve {} to version {}",
+                account_info.key(),
+                RESERVE_VERSION
+            );
+            continue;
+        }
+
         // Now work with the account as a Reserve
         let mut reserve_account =
             Account::<Reserve>::try_from(account_info).map_err(|_| LendingError::InvalidAccount)?;
@@ -281,7 +390,7 @@ pub fn batch_migrate_reserves<'info>(
         // Check if migration is needed
         if reserve_account.needs_migration() {
             let from_version = reserve_account.version();
-            match validate_migration_compatibility(from_version, PROGRAM_VERSION) {
+            match validate_migration_compatibility(from_version, Reserve::current_version()) {
                 Ok(()) => match reserve_account.migrate(from_version) {
                     Ok(()) => {
                         migrated_count += 1;
@@ -289,7 +398,7 @@ pub fn batch_migrate_reserves<'info>(
                             "Successfully migrated reserve {} from version {} to {}",
                             account_info.key(),
                             from_version,
-                            PROGRAM_VERSION
+                            Reserve::current_version()
                         );
                     }
                     Err(e) => {
@@ -331,6 +440,107 @@ pub fn batch_migrate_reserves<'info>(
     Ok(())
 }
 
+/// Report which of the provided accounts would migrate, without mutating anything
+/// Accounts are passed as remaining_accounts; the plan is returned as instruction return data
+pub fn plan_migration<'info>(
+    ctx: Context<'_, '_, '_, 'info, PlanMigration<'info>>,
+) -> Result<MigrationPlan> {
+    ctx.accounts
+        .config
+        .validate_account_count(ctx.remaining_accounts.len())?;
+
+    let mut plan = MigrationPlan::default();
+    for account_info in ctx.remaining_accounts.iter() {
+        let data = account_info.try_borrow_data()?;
+        plan.add_account(
+            account_info.key(),
+            account_info.owner == &crate::id(),
+            &data,
+        );
+    }
+    plan.finalize();
+
+    for item in plan.items.iter() {
+        msg!(
+            "Would migrate {:?} {} from version {} to {} (~{} CU{})",
+            item.kind,
+            item.account,
+            item.from_version,
+            item.to_version,
+            item.estimated_compute_units,
+            if item.supported { "" } else { ", unsupported" }
+        );
+    }
+
+    msg!(
+        "Migration plan: {} to migrate, {} up to date, {} skipped, {} unsupported, ~{} CU, ~{} transactions",
+        plan.items.len(),
+        plan.up_to_date,
+        plan.skipped,
+        plan.unsupported,
+        plan.total_compute_units,
+        plan.estimated_transactions
+    );
+
+    Ok(plan)
+}
+
+/// Convert an obligation account between the standard, optimized and fixed-size layouts
+/// in place
+/// The account keeps its address; the owner covers (or is refunded) the rent difference.
+/// Converting to the zero-copy layout is the migration path from v1 obligations.
+pub fn convert_obligation_layout(
+    ctx: Context<ConvertObligationLayout>,
+    layout: ObligationLayout,
+) -> Result<()> {
+    let obligation_info = ctx.accounts.obligation.to_account_info();
+    let owner_info = ctx.accounts.owner.to_account_info();
+
+    let obligation = AnyObligation::load_checked(&obligation_info, &ctx.accounts.market.key())?;
+
+    if obligation.owner != owner_info.key() {
+        return Err(LendingError::InvalidAuthority.into());
+    }
+
+    let from_layout = obligation.layout();
+    if from_layout == layout {
+        return Err(LendingError::MigrationAlreadyCompleted.into());
+    }
+
+    // Keep the resized account rent exempt
+    let new_space = AnyObligation::space(layout);
+    let required_lamports = Rent::get()?.minimum_balance(new_space);
+    let current_lamports = obligation_info.lamports();
+
+    if required_lamports > current_lamports {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: owner_info.clone(),
+                    to: obligation_info.clone(),
+                },
+            ),
+            required_lamports - current_lamports,
+        )?;
+    } else if current_lamports > required_lamports {
+        let excess = current_lamports - required_lamports;
+        obligation_info.sub_lamports(excess)?;
+        owner_info.add_lamports(excess)?;
+    }
+
+    obligation_info.realloc(new_space, false)?;
+    obligation.save_as(&obligation_info, layout)?;
+
+    msg!(
+        "Obligation {} converted from {:?} to {:?} layout",
+        obligation_info.key(),
+        from_layout,
+        layout
+    );
+    Ok(())
+}
+
 // Account validation structs
 
 #[derive(Accounts)]
@@ -339,12 +549,20 @@ pub struct MigrateMarket<'info> {
         mut,
         seeds = [MARKET_SEED],
         bump,
-        // Multisig owner validation will be done manually
+        // The multisig owner is checked by validate_authority in the instruction
     )]
     pub market: Account<'info, Market>,
 
     /// Authority (must be market's multisig owner)
     pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 #[derive(Accounts)]
@@ -352,18 +570,31 @@ pub struct MigrateReserve<'info> {
     #[account(
         seeds = [MARKET_SEED],
         bump,
-        // Multisig owner validation will be done manually
+        // The multisig owner is checked by validate_authority in the instruction
     )]
     pub market: Account<'info, Market>,
 
+    /// Reserve in the legacy or packed layout
+    /// CHECK: Ownership and market are validated in the handler before it is compacted
+    /// or loaded
+    #[account(mut)]
+    pub reserve: UncheckedAccount<'info>,
+
+    /// Authority (must be market's multisig owner, pays or is refunded the rent
+    /// difference of compaction)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
     #[account(
         mut,
-        has_one = market @ LendingError::InvalidAccount
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
     )]
-    pub reserve: Account<'info, Reserve>,
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 
-    /// Authority (must be market's multisig owner)
-    pub authority: Signer<'info>,
+    /// System program
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -371,7 +602,7 @@ pub struct MigrateObligation<'info> {
     #[account(
         seeds = [MARKET_SEED],
         bump,
-        // Multisig owner validation will be done manually
+        // The multisig owner is checked by validate_authority in the instruction
     )]
     pub market: Account<'info, Market>,
 
@@ -383,6 +614,14 @@ pub struct MigrateObligation<'info> {
 
     /// Authority (must be market's multisig owner)
     pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 #[derive(Accounts)]
@@ -390,7 +629,7 @@ pub struct MigrateMultisig<'info> {
     #[account(
         seeds = [MARKET_SEED],
         bump,
-        // Multisig owner validation will be done manually
+        // The multisig owner is checked by validate_authority in the instruction
     )]
     pub market: Account<'info, Market>,
 
@@ -399,6 +638,14 @@ pub struct MigrateMultisig<'info> {
 
     /// Authority (must be market's multisig owner)
     pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 #[derive(Accounts)]
@@ -406,7 +653,7 @@ pub struct MigrateTimelock<'info> {
     #[account(
         seeds = [MARKET_SEED],
         bump,
-        // Multisig owner validation will be done manually
+        // The multisig owner is checked by validate_authority in the instruction
     )]
     pub market: Account<'info, Market>,
 
@@ -415,6 +662,14 @@ pub struct MigrateTimelock<'info> {
 
     /// Authority (must be market's multisig owner)
     pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 #[derive(Accounts)]
@@ -422,7 +677,7 @@ pub struct MigrateGovernance<'info> {
     #[account(
         seeds = [MARKET_SEED],
         bump,
-        // Multisig owner validation will be done manually
+        // The multisig owner is checked by validate_authority in the instruction
     )]
     pub market: Account<'info, Market>,
 
@@ -431,6 +686,14 @@ pub struct MigrateGovernance<'info> {
 
     /// Authority (must be market's multisig owner)
     pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 #[derive(Accounts)]
@@ -438,10 +701,67 @@ pub struct BatchMigrateReserves<'info> {
     #[account(
         seeds = [MARKET_SEED],
         bump,
-        // Multisig owner validation will be done manually
+        // The multisig owner is checked by validate_authority in the instruction
     )]
     pub market: Account<'info, Market>,
 
-    /// Authority (must be market's multisig owner)
+    /// Protocol configuration (remaining account cap)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Authority (must be market's multisig owner, pays or is refunded the rent
+    /// difference of compaction)
+    #[account(mut)]
     pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlanMigration<'info> {
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol configuration (remaining account cap)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    // Note: Accounts to plan are passed as remaining_accounts
+}
+
+#[derive(Accounts)]
+pub struct ConvertObligationLayout<'info> {
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation account in any layout
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Obligation owner (pays or receives the rent difference)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
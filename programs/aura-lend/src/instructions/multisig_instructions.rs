@@ -1,7 +1,9 @@
 use crate::constants::*;
 use crate::error::LendingError;
+use crate::state::audit_log::{AdminAction, AdminAuditLog};
 use crate::state::market::*;
 use crate::state::multisig::*;
+use crate::state::proposal_lifecycle::*;
 use anchor_lang::prelude::*;
 use anchor_spl::token::Token;
 
@@ -29,6 +31,12 @@ pub fn create_multisig_proposal(
     ctx: Context<CreateMultisigProposal>,
     params: CreateProposalParams,
 ) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.proposer.key(),
+        AdminAction::CreateMultisigProposal,
+        &params,
+    )?;
+
     let multisig = &ctx.accounts.multisig;
     let proposal = &mut ctx.accounts.proposal;
     let proposer = &ctx.accounts.proposer;
@@ -44,6 +52,7 @@ pub fn create_multisig_proposal(
         multisig.nonce,
         params.operation_type,
         params.instruction_data,
+        params.payload_hash,
         proposer.key(),
         params.expires_at,
     )?;
@@ -54,6 +63,12 @@ pub fn create_multisig_proposal(
 
 /// Sign a multisig proposal
 pub fn sign_multisig_proposal(ctx: Context<SignMultisigProposal>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.signer.key(),
+        AdminAction::SignMultisigProposal,
+        &ctx.accounts.proposal.key(),
+    )?;
+
     let multisig = &ctx.accounts.multisig;
     let proposal = &mut ctx.accounts.proposal;
     let signer = &ctx.accounts.signer;
@@ -68,13 +83,8 @@ pub fn sign_multisig_proposal(ctx: Context<SignMultisigProposal>) -> Result<()>
         return Err(LendingError::ProposalExpired.into());
     }
 
-    // Check if proposal is still active
-    if proposal.status != ProposalStatus::Active {
-        return Err(LendingError::ProposalNotActive.into());
-    }
-
-    // Add signature
-    proposal.add_signature(&signer.key())?;
+    // Add signature (opens a draft and marks the proposal ready at the threshold)
+    proposal.add_signature(&signer.key(), multisig.threshold)?;
 
     msg!(
         "Proposal signed by {}. Signatures: {}/{}",
@@ -86,7 +96,18 @@ pub fn sign_multisig_proposal(ctx: Context<SignMultisigProposal>) -> Result<()>
 }
 
 /// Execute a multisig proposal (once threshold is met)
-pub fn execute_multisig_proposal(ctx: Context<ExecuteMultisigProposal>) -> Result<()> {
+/// `payload` must hash to the proposal's `payload_hash`, so the executed payload is the
+/// one the signers approved.
+pub fn execute_multisig_proposal(
+    ctx: Context<ExecuteMultisigProposal>,
+    payload: Vec<u8>,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.executor.key(),
+        AdminAction::ExecuteMultisigProposal,
+        &ctx.accounts.proposal.key(),
+    )?;
+
     let multisig = &mut ctx.accounts.multisig;
     let proposal = &mut ctx.accounts.proposal;
 
@@ -100,17 +121,17 @@ pub fn execute_multisig_proposal(ctx: Context<ExecuteMultisigProposal>) -> Resul
         return Err(LendingError::ProposalExpired.into());
     }
 
-    // Check if proposal is still active
-    if proposal.status != ProposalStatus::Active {
-        return Err(LendingError::ProposalNotActive.into());
-    }
+    proposal.verify_payload(&payload)?;
+
+    // Catch up proposals that met a since-lowered threshold
+    proposal.sync_approvals(multisig.threshold)?;
 
     // Verify nonce matches (prevents replay attacks)
     if proposal.nonce != multisig.nonce {
         return Err(LendingError::InvalidNonce.into());
     }
 
-    // Mark proposal as executed
+    // Mark proposal as executed (only ready proposals may execute)
     proposal.mark_executed()?;
 
     // Increment multisig nonce
@@ -124,14 +145,26 @@ pub fn execute_multisig_proposal(ctx: Context<ExecuteMultisigProposal>) -> Resul
 }
 
 /// Cancel a multisig proposal (only by proposer or if expired)
+/// Expired proposals are recorded as expired rather than cancelled.
 pub fn cancel_multisig_proposal(ctx: Context<CancelMultisigProposal>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::CancelMultisigProposal,
+        &ctx.accounts.proposal.key(),
+    )?;
+
     let proposal = &mut ctx.accounts.proposal;
     let authority = &ctx.accounts.authority;
+    let clock = Clock::get()?;
 
-    // Check if caller is the proposer or if proposal is expired
-    let can_cancel = proposal.proposer == authority.key() || proposal.is_expired()?;
+    if proposal.is_past_expiry(clock.unix_timestamp) {
+        proposal.mark_expired(clock.unix_timestamp)?;
+        msg!("Multisig proposal marked expired by {}", authority.key());
+        return Ok(());
+    }
 
-    if !can_cancel {
+    // Before expiry only the proposer may cancel
+    if proposal.proposer != authority.key() {
         return Err(LendingError::UnauthorizedCancellation.into());
     }
 
@@ -147,6 +180,12 @@ pub fn update_multisig_config(
     ctx: Context<UpdateMultisigConfig>,
     params: InitializeMultisigParams,
 ) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.executor.key(),
+        AdminAction::UpdateMultisigConfig,
+        &params,
+    )?;
+
     let multisig = &mut ctx.accounts.multisig;
     let proposal = &ctx.accounts.executed_proposal;
 
@@ -210,6 +249,14 @@ pub struct CreateMultisigProposal<'info> {
     #[account(mut)]
     pub proposer: Signer<'info>,
 
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -221,6 +268,14 @@ pub struct SignMultisigProposal<'info> {
     pub proposal: Account<'info, MultisigProposal>,
 
     pub signer: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 #[derive(Accounts)]
@@ -233,6 +288,14 @@ pub struct ExecuteMultisigProposal<'info> {
 
     /// The account executing the proposal (must be a signatory)
     pub executor: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 #[derive(Accounts)]
@@ -242,6 +305,14 @@ pub struct CancelMultisigProposal<'info> {
 
     /// Either the proposer or emergency authority
     pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 #[derive(Accounts)]
@@ -255,4 +326,12 @@ pub struct UpdateMultisigConfig<'info> {
 
     /// One of the signatories executing the update
     pub executor: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
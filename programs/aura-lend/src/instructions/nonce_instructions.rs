@@ -0,0 +1,37 @@
+use crate::error::LendingError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Close an operation nonce once its retention window has passed
+/// Permissionless so keepers can reclaim stale records; rent always returns to the payer
+pub fn close_operation_nonce(ctx: Context<CloseOperationNonce>) -> Result<()> {
+    let clock = Clock::get()?;
+    let operation_nonce = &ctx.accounts.operation_nonce;
+
+    operation_nonce.validate_close(clock.slot)?;
+
+    msg!(
+        "Closed {:?} nonce executed at slot {} by {}",
+        operation_nonce.operation,
+        operation_nonce.executed_slot,
+        operation_nonce.authority
+    );
+    Ok(())
+}
+
+// Context structs for nonce instructions
+
+#[derive(Accounts)]
+pub struct CloseOperationNonce<'info> {
+    /// Nonce record to close
+    #[account(
+        mut,
+        has_one = payer @ LendingError::InvalidAccount,
+        close = payer
+    )]
+    pub operation_nonce: Account<'info, OperationNonce>,
+
+    /// Original rent payer
+    #[account(mut)]
+    pub payer: SystemAccount<'info>,
+}
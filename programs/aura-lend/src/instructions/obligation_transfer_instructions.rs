@@ -0,0 +1,250 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Propose moving the signer's obligation to `new_owner`
+/// Nothing moves until the new owner accepts; the owner can cancel until then.
+pub fn transfer_obligation_ownership(
+    ctx: Context<TransferObligationOwnership>,
+    new_owner: Pubkey,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+
+    let obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market.key())?;
+    if obligation.owner != ctx.accounts.owner.key() {
+        return Err(LendingError::InvalidAuthority.into());
+    }
+
+    **ctx.accounts.obligation_transfer = ObligationTransfer::new(
+        market.key(),
+        ctx.accounts.obligation.key(),
+        ctx.accounts.owner.key(),
+        new_owner,
+        Clock::get()?.slot,
+        ctx.bumps.obligation_transfer,
+    )?;
+
+    msg!(
+        "Obligation {} transfer proposed to {}",
+        ctx.accounts.obligation.key(),
+        new_owner
+    );
+    Ok(())
+}
+
+/// Cancel a pending obligation transfer and reclaim its rent (current owner only)
+pub fn cancel_obligation_transfer(ctx: Context<CancelObligationTransfer>) -> Result<()> {
+    msg!(
+        "Obligation {} transfer to {} cancelled",
+        ctx.accounts.obligation_transfer.obligation,
+        ctx.accounts.obligation_transfer.new_owner
+    );
+    Ok(())
+}
+
+/// Accept a pending transfer, moving every deposit and borrow into the new owner's
+/// obligation PDA and closing the old obligation
+/// Positions are copied unchanged, so the obligation's health is the same before and
+/// after. `obligation_index` selects which of the new owner's obligation slots receives it.
+/// Accounts keyed by the old address would be orphaned, so the transfer is refused while
+/// a liquidation auction, a standing order or a linked account (reward participation,
+/// credit line, credit delegation) exists. The sub-account record is closed and the old
+/// address delisted from the liquidation queue. Liquidation commitments are left alone:
+/// they expire within `LIQUIDATION_COMMITMENT_EXPIRY_SLOTS`, and counting them would let
+/// any liquidator block a transfer.
+pub fn accept_obligation_ownership(
+    ctx: Context<AcceptObligationOwnership>,
+    obligation_index: u8,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let obligation_info = ctx.accounts.obligation.to_account_info();
+    let previous_owner_info = ctx.accounts.previous_owner.to_account_info();
+
+    let obligation = AnyObligation::load_checked(&obligation_info, &market.key())?;
+    if obligation.owner != ctx.accounts.obligation_transfer.owner {
+        return Err(LendingError::InvalidAuthority.into());
+    }
+
+    // An auction is keyed by the old address and could no longer settle
+    if !ctx.accounts.liquidation_auction.data_is_empty() {
+        return Err(LendingError::LiquidationAuctionActive.into());
+    }
+    if obligation.linked_accounts > 0 || !ctx.accounts.standing_order.data_is_empty() {
+        return Err(LendingError::ObligationHasLinkedAccounts.into());
+    }
+
+    if obligation_index >= MAX_OBLIGATIONS_PER_OWNER {
+        return Err(LendingError::InvalidObligationIndex.into());
+    }
+
+    **ctx.accounts.new_obligation = Obligation {
+        owner: ctx.accounts.new_owner.key(),
+        obligation_index,
+        ..(*obligation).clone()
+    };
+
+    let sub_accounts_info = ctx.accounts.sub_accounts.to_account_info();
+    if !sub_accounts_info.data_is_empty() {
+        Account::<ObligationSubAccounts>::try_from(&sub_accounts_info)?
+            .close(previous_owner_info.clone())?;
+    }
+
+    let liquidation_queue_info = ctx.accounts.liquidation_queue.to_account_info();
+    if !liquidation_queue_info.data_is_empty() {
+        let mut liquidation_queue = Account::<LiquidationQueue>::try_from(&liquidation_queue_info)?;
+        if liquidation_queue.remove(&obligation_info.key()) {
+            liquidation_queue.exit(&crate::ID)?;
+        }
+    }
+
+    let lamports = AnyObligation::close(&obligation_info, &previous_owner_info)?;
+
+    msg!(
+        "Obligation {} moved to {} for new owner {} ({} deposits, {} borrows), {} lamports returned",
+        obligation_info.key(),
+        ctx.accounts.new_obligation.key(),
+        ctx.accounts.new_owner.key(),
+        ctx.accounts.new_obligation.deposits.len(),
+        ctx.accounts.new_obligation.borrows.len(),
+        lamports
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct TransferObligationOwnership<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation account (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Pending transfer account to create
+    #[account(
+        init,
+        payer = owner,
+        space = ObligationTransfer::SIZE,
+        seeds = [OBLIGATION_TRANSFER_SEED, obligation.key().as_ref()],
+        bump
+    )]
+    pub obligation_transfer: Account<'info, ObligationTransfer>,
+
+    /// Current obligation owner
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelObligationTransfer<'info> {
+    /// Pending transfer account to close
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ LendingError::InvalidAuthority
+    )]
+    pub obligation_transfer: Account<'info, ObligationTransfer>,
+
+    /// Current obligation owner (receives the rent)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(obligation_index: u8)]
+pub struct AcceptObligationOwnership<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Pending transfer being accepted
+    #[account(
+        mut,
+        close = previous_owner,
+        seeds = [OBLIGATION_TRANSFER_SEED, obligation.key().as_ref()],
+        bump = obligation_transfer.bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = obligation @ LendingError::InvalidAccount,
+        has_one = new_owner @ LendingError::InvalidAuthority
+    )]
+    pub obligation_transfer: Account<'info, ObligationTransfer>,
+
+    /// Obligation being transferred (closed by this instruction)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// New owner's obligation, created with the transferred positions
+    #[account(
+        init,
+        payer = new_owner,
+        space = Obligation::SIZE,
+        seeds = [
+            OBLIGATION_SEED,
+            new_owner.key().as_ref(),
+            Obligation::index_seed(&obligation_index)
+        ],
+        bump
+    )]
+    pub new_obligation: Account<'info, Obligation>,
+
+    /// Liquidation auction PDA of the obligation, which must not exist
+    /// CHECK: Validated by seeds; only checked for being empty
+    #[account(
+        seeds = [LIQUIDATION_AUCTION_SEED, obligation.key().as_ref()],
+        bump
+    )]
+    pub liquidation_auction: UncheckedAccount<'info>,
+
+    /// Standing order PDA of the obligation, which must not exist
+    /// CHECK: Validated by seeds; only checked for being empty
+    #[account(
+        seeds = [STANDING_ORDER_SEED, obligation.key().as_ref()],
+        bump
+    )]
+    pub standing_order: UncheckedAccount<'info>,
+
+    /// Sub-account record PDA of the obligation, closed if it exists
+    /// CHECK: Validated by seeds; loaded as `ObligationSubAccounts` when not empty
+    #[account(
+        mut,
+        seeds = [SUB_ACCOUNTS_SEED, obligation.key().as_ref()],
+        bump
+    )]
+    pub sub_accounts: UncheckedAccount<'info>,
+
+    /// Liquidation queue PDA of the market, updated if it exists
+    /// CHECK: Validated by seeds; loaded as `LiquidationQueue` when not empty
+    #[account(
+        mut,
+        seeds = [LIQUIDATION_QUEUE_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub liquidation_queue: UncheckedAccount<'info>,
+
+    /// Previous owner, refunded the rent of the old obligation and the transfer account
+    /// CHECK: Matched against the transfer's owner
+    #[account(
+        mut,
+        address = obligation_transfer.owner @ LendingError::InvalidAuthority
+    )]
+    pub previous_owner: UncheckedAccount<'info>,
+
+    /// Wallet accepting the obligation
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,508 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::events::{emit_integrator_attribution, CollateralDepositEvent, RepayEvent};
+use crate::instructions::borrowing_instructions::{
+    isolated_collateral_reserve, validate_collateral_concentration,
+    validate_collateral_position_size,
+};
+use crate::instructions::liquidation_queue_instructions::dequeue_obligation;
+use crate::instructions::rewards_instructions::sync_rewards;
+use crate::state::*;
+use crate::utils::{math::Decimal, EventType, OracleManager, ProtocolConfig, TokenUtils};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Deposit the payer's collateral tokens into another wallet's obligation
+/// The payer only funds the deposit: the collateral belongs to the obligation owner, and
+/// nothing in this instruction can move value out of the obligation. A payer can only add
+/// to collateral the owner already holds, so it cannot change which eMode category or
+/// isolation rules apply, nor give an emptied obligation collateral that would block its
+/// bad debt settlement.
+pub fn deposit_obligation_collateral_for(
+    ctx: Context<DepositObligationCollateralFor>,
+    collateral_amount: u64,
+    integrator_id: Option<u32>,
+) -> Result<OperationReceipt> {
+    let market = &ctx.accounts.market;
+    let mut obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market.key())?;
+    let deposit_reserve = &mut ctx.accounts.deposit_reserve;
+    let clock = Clock::get()?;
+
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(
+        &ctx.accounts.flash_loan_guard,
+        FlashLoanUsage::DEPOSIT_COLLATERAL,
+    )?;
+
+    if market.is_paused() || market.is_lending_disabled() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    if !deposit_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::COLLATERAL_ENABLED)
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    if collateral_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    if obligation
+        .find_collateral_deposit(&deposit_reserve.key())
+        .is_none()
+    {
+        return Err(LendingError::ObligationReserveNotFound.into());
+    }
+    let isolated = deposit_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::ISOLATED);
+
+    deposit_reserve.update_interest(clock.slot)?;
+    deposit_reserve.validate_supply_cap(0)?;
+
+    // A deposit only improves health, so it proceeds without a usable price and adds no
+    // value until the next refresh
+    let oracle_price = OracleManager::get_price_if_valid(
+        &ctx.accounts.price_oracle.to_account_info(),
+        deposit_reserve,
+        clock.unix_timestamp,
+    );
+    let collateral_value_usd = match &oracle_price {
+        Some(oracle_price) => {
+            deposit_reserve.collateral_value_usd(collateral_amount, oracle_price)?
+        }
+        None => Decimal::zero(),
+    };
+
+    if !isolated && oracle_price.is_some() {
+        validate_collateral_concentration(
+            &obligation,
+            &deposit_reserve.key(),
+            collateral_value_usd,
+        )?;
+    }
+    if oracle_price.is_some() {
+        validate_collateral_position_size(
+            &ctx.accounts.config,
+            &obligation,
+            &deposit_reserve.key(),
+            collateral_value_usd,
+        )?;
+    }
+
+    // Transfer collateral tokens from the payer to the reserve
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.source_collateral,
+        &ctx.accounts.destination_collateral,
+        &ctx.accounts.collateral_mint,
+        &ctx.accounts.payer.to_account_info(),
+        &[],
+        collateral_amount,
+    )?;
+
+    obligation.add_collateral_deposit(ObligationCollateral {
+        deposit_reserve: deposit_reserve.key(),
+        deposited_amount: collateral_amount,
+        market_value_usd: collateral_value_usd,
+        ltv_bps: deposit_reserve.effective_loan_to_value_ratio_bps(clock.unix_timestamp as u64),
+        liquidation_threshold_bps: deposit_reserve.config.liquidation_threshold_bps,
+    })?;
+    obligation.deposited_value_usd = obligation
+        .deposited_value_usd
+        .try_add(collateral_value_usd)?;
+
+    if oracle_price.is_some() {
+        obligation.update_timestamp(clock.slot)?;
+    } else {
+        msg!("Oracle price unavailable - deposit recorded without value");
+    }
+
+    emit_integrator_attribution(
+        integrator_id,
+        EventType::CollateralDeposited,
+        obligation.owner,
+        deposit_reserve.key(),
+        collateral_amount,
+        clock.slot,
+    );
+
+    msg!(
+        "{} deposited {} collateral tokens for {}",
+        ctx.accounts.payer.key(),
+        collateral_amount,
+        obligation.owner
+    );
+
+    sync_rewards(
+        ctx.accounts.reward_pool.as_mut(),
+        ctx.accounts.obligation_rewards.as_mut(),
+        &ctx.accounts.obligation.key(),
+        &obligation,
+        clock.slot,
+    )?;
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    let receipt = OperationReceipt::new(
+        deposit_reserve.key(),
+        deposit_reserve,
+        deposit_reserve.collateral_to_liquidity(collateral_amount)?,
+        collateral_amount,
+        0,
+        Some(obligation.calculate_health_factor()?),
+    )?;
+
+    emit!(CollateralDepositEvent {
+        obligation: ctx.accounts.obligation.key(),
+        owner: obligation.owner,
+        receipt,
+        slot: clock.slot,
+    });
+
+    Ok(receipt)
+}
+
+/// Repay another wallet's borrow with the payer's liquidity
+/// Repayment is capped at the outstanding debt and only reduces it; the payer gains no
+/// claim on the obligation.
+pub fn repay_obligation_liquidity_for(
+    ctx: Context<RepayObligationLiquidityFor>,
+    liquidity_amount: u64,
+    integrator_id: Option<u32>,
+) -> Result<OperationReceipt> {
+    let market = &ctx.accounts.market;
+    let mut obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market.key())?;
+    let repay_reserve = &mut ctx.accounts.repay_reserve;
+    let clock = Clock::get()?;
+
+    // Settle rewards on the position held so far, before this operation shrinks it
+    sync_rewards(
+        ctx.accounts.reward_pool.as_mut(),
+        ctx.accounts.obligation_rewards.as_mut(),
+        &ctx.accounts.obligation.key(),
+        &obligation,
+        clock.slot,
+    )?;
+
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(&ctx.accounts.flash_loan_guard, FlashLoanUsage::REPAY)?;
+
+    if market.is_paused() && !market.is_emergency() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    if repay_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::REPAYMENTS_DISABLED)
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    if liquidity_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    repay_reserve.update_interest(clock.slot)?;
+    obligation.accrue_borrow_interest(
+        &repay_reserve.key(),
+        repay_reserve.cumulative_borrow_rate_wads(),
+    )?;
+
+    let borrow = obligation
+        .find_liquidity_borrow(&repay_reserve.key())
+        .ok_or(LendingError::ObligationReserveNotFound)?;
+    let borrowed_amount = borrow.borrowed_amount_wads.try_floor_u64()?;
+    let actual_repay_amount = std::cmp::min(liquidity_amount, borrowed_amount);
+    if actual_repay_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    // A repayment only improves health, so it proceeds without a usable price and leaves
+    // the debt's value unreduced until the next refresh
+    let oracle_price = OracleManager::get_price_if_valid(
+        &ctx.accounts.price_oracle.to_account_info(),
+        repay_reserve,
+        clock.unix_timestamp,
+    );
+    let repay_value_usd = match &oracle_price {
+        Some(oracle_price) => OracleManager::calculate_usd_value(
+            actual_repay_amount,
+            oracle_price,
+            repay_reserve.config.decimals,
+        )?,
+        None => Decimal::zero(),
+    };
+
+    // Transfer the repayment from the payer, grossed up for the mint's transfer fee
+    let transfer_amount =
+        TokenUtils::amount_before_transfer_fee(&ctx.accounts.liquidity_mint, actual_repay_amount)?;
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.source_liquidity,
+        &ctx.accounts.destination_liquidity,
+        &ctx.accounts.liquidity_mint,
+        &ctx.accounts.payer.to_account_info(),
+        &[],
+        transfer_amount,
+    )?;
+
+    repay_reserve.repay_borrow(actual_repay_amount)?;
+    obligation.repay_liquidity_borrow(
+        &repay_reserve.key(),
+        Decimal::from_integer(actual_repay_amount)?,
+    )?;
+
+    if let Some(isolated_reserve) = isolated_collateral_reserve(
+        &obligation,
+        ctx.accounts.isolated_collateral_reserve.as_mut(),
+    )? {
+        isolated_reserve.remove_isolation_mode_debt(repay_value_usd.try_floor_u64()?);
+    }
+
+    obligation.borrowed_value_usd = obligation.borrowed_value_usd.try_sub(repay_value_usd)?;
+    obligation.record_repayment();
+
+    if oracle_price.is_some() {
+        obligation.update_timestamp(clock.slot)?;
+    } else {
+        msg!("Oracle price unavailable - repayment recorded without revaluation");
+    }
+
+    emit_integrator_attribution(
+        integrator_id,
+        EventType::LiquidityRepaid,
+        obligation.owner,
+        repay_reserve.key(),
+        actual_repay_amount,
+        clock.slot,
+    );
+
+    msg!(
+        "{} repaid {} liquidity tokens for {}",
+        ctx.accounts.payer.key(),
+        actual_repay_amount,
+        obligation.owner
+    );
+
+    sync_rewards(
+        ctx.accounts.reward_pool.as_mut(),
+        ctx.accounts.obligation_rewards.as_mut(),
+        &ctx.accounts.obligation.key(),
+        &obligation,
+        clock.slot,
+    )?;
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    // Delist the obligation until a refresh finds it unhealthy again
+    dequeue_obligation(
+        ctx.accounts.liquidation_queue.as_mut(),
+        &ctx.accounts.obligation.key(),
+    );
+
+    let receipt = OperationReceipt::new(
+        repay_reserve.key(),
+        repay_reserve,
+        actual_repay_amount,
+        0,
+        0,
+        Some(obligation.calculate_health_factor()?),
+    )?;
+
+    emit!(RepayEvent {
+        obligation: ctx.accounts.obligation.key(),
+        payer: ctx.accounts.payer.key(),
+        receipt,
+        slot: clock.slot,
+    });
+
+    Ok(receipt)
+}
+
+#[derive(Accounts)]
+pub struct DepositObligationCollateralFor<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation receiving the collateral (any layout, any owner)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Reserve for the collateral being deposited
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, deposit_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = price_oracle @ LendingError::OracleAccountMismatch
+    )]
+    pub deposit_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for the collateral asset
+    /// CHECK: This account is validated by the reserve's price_oracle field
+    pub price_oracle: UncheckedAccount<'info>,
+
+    /// Collateral mint (aToken mint)
+    #[account(address = deposit_reserve.collateral_mint @ LendingError::ReserveCollateralMintMismatch)]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Payer's source collateral token account
+    #[account(
+        mut,
+        token::mint = deposit_reserve.collateral_mint,
+        token::authority = payer
+    )]
+    pub source_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Reserve's collateral token account
+    #[account(
+        mut,
+        token::mint = deposit_reserve.collateral_mint,
+        token::authority = collateral_supply_authority
+    )]
+    pub destination_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Collateral supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [COLLATERAL_TOKEN_SEED, deposit_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub collateral_supply_authority: UncheckedAccount<'info>,
+
+    /// Wallet funding the deposit
+    pub payer: Signer<'info>,
+
+    /// Payer's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, payer.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Protocol configuration (minimum position value)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Reward pool of the reserve, synced after the operation
+    #[account(mut)]
+    pub reward_pool: Option<Account<'info, RewardPool>>,
+
+    /// Obligation's participation in `reward_pool`
+    #[account(mut)]
+    pub obligation_rewards: Option<Account<'info, ObligationRewards>>,
+}
+
+#[derive(Accounts)]
+pub struct RepayObligationLiquidityFor<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation whose debt is repaid (any layout, any owner)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Reserve for the asset being repaid
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, repay_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = price_oracle @ LendingError::OracleAccountMismatch
+    )]
+    pub repay_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for the repaid asset
+    /// CHECK: This account is validated by the reserve's price_oracle field
+    pub price_oracle: UncheckedAccount<'info>,
+
+    /// Reserve of the obligation's isolated collateral (required in isolation mode)
+    #[account(
+        mut,
+        has_one = market @ LendingError::InvalidMarketState,
+        constraint = isolated_collateral_reserve.key() != repay_reserve.key() @ LendingError::InvalidAccount
+    )]
+    pub isolated_collateral_reserve: Option<Account<'info, Reserve>>,
+
+    /// Liquidity token mint
+    #[account(address = repay_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Payer's source liquidity token account
+    #[account(
+        mut,
+        token::mint = repay_reserve.liquidity_mint,
+        token::authority = payer
+    )]
+    pub source_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = repay_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub destination_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, repay_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Wallet funding the repayment
+    pub payer: Signer<'info>,
+
+    /// Payer's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, payer.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Reward pool of the reserve, synced after the operation
+    #[account(mut)]
+    pub reward_pool: Option<Account<'info, RewardPool>>,
+
+    /// Obligation's participation in `reward_pool`
+    #[account(mut)]
+    pub obligation_rewards: Option<Account<'info, ObligationRewards>>,
+
+    /// Market's liquidation queue, from which the obligation is delisted when supplied
+    #[account(
+        mut,
+        seeds = [LIQUIDATION_QUEUE_SEED, market.key().as_ref()],
+        bump = liquidation_queue.bump
+    )]
+    pub liquidation_queue: Option<Account<'info, LiquidationQueue>>,
+}
@@ -1,142 +1,214 @@
 use crate::constants::*;
 use crate::error::LendingError;
+use crate::events::ObligationHealthWarning;
+use crate::instructions::liquidation_queue_instructions::sync_liquidation_queue;
 use crate::state::*;
-use crate::utils::{math::Decimal, OracleManager};
+use crate::utils::{OracleManager, ProtocolConfig};
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use pyth_solana_receiver_sdk::cpi::{self as pyth_cpi, accounts::PostUpdateAtomic};
+use pyth_solana_receiver_sdk::PostUpdateAtomicParams;
 
 /// Refresh reserve interest rates and oracle prices
 pub fn refresh_reserve(ctx: Context<RefreshReserve>) -> Result<()> {
+    refresh_reserve_price(
+        &mut ctx.accounts.reserve,
+        &ctx.accounts.price_oracle.to_account_info(),
+        ctx.accounts.secondary_price_oracle.as_deref(),
+    )
+}
+
+/// Post a Pyth pull-oracle price update and refresh the reserve with it
+/// `price_update` is the Borsh-encoded `PostUpdateAtomicParams` of the Pyth receiver (the
+/// Wormhole VAA, the Merkle price update and the treasury id). The receiver verifies the
+/// guardian signatures and writes the update into the reserve's price update PDA, which
+/// must be the reserve's `price_oracle`, so the refresh no longer waits on a push feed.
+pub fn post_and_refresh_reserve(
+    ctx: Context<PostAndRefreshReserve>,
+    price_update: Vec<u8>,
+) -> Result<()> {
+    let params = PostUpdateAtomicParams::try_from_slice(&price_update)
+        .map_err(|_| LendingError::OraclePriceInvalid)?;
+
+    // The price update PDA is both the account written and its write authority
+    let reserve_key = ctx.accounts.reserve.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        PRICE_UPDATE_SEED,
+        reserve_key.as_ref(),
+        &[ctx.bumps.price_oracle],
+    ]];
+    pyth_cpi::post_update_atomic(
+        CpiContext::new_with_signer(
+            ctx.accounts.pyth_receiver_program.to_account_info(),
+            PostUpdateAtomic {
+                payer: ctx.accounts.payer.to_account_info(),
+                guardian_set: ctx.accounts.guardian_set.to_account_info(),
+                config: ctx.accounts.receiver_config.to_account_info(),
+                treasury: ctx.accounts.receiver_treasury.to_account_info(),
+                price_update_account: ctx.accounts.price_oracle.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                write_authority: ctx.accounts.price_oracle.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        params,
+    )?;
+
+    refresh_reserve_price(
+        &mut ctx.accounts.reserve,
+        &ctx.accounts.price_oracle.to_account_info(),
+        ctx.accounts.secondary_price_oracle.as_deref(),
+    )
+}
+
+/// Recompute and cache the derived price of an LST or LP token reserve
+/// Every component price must pass the protocol-wide checks. The cached price keeps the
+/// oldest component's publish time, so it cannot outlive the feeds it came from.
+pub fn refresh_derived_price(ctx: Context<RefreshDerivedPrice>) -> Result<()> {
     let reserve = &mut ctx.accounts.reserve;
+    let derived = reserve.derived_oracle;
+    let clock = Clock::get()?;
+
+    let base_price = OracleManager::get_component_price(&derived.base, &ctx.accounts.base_oracle)?;
+    base_price.validate(clock.unix_timestamp)?;
+
+    let price = match derived.kind {
+        DerivedOracleKind::StakePool => {
+            let (total_lamports, pool_token_supply) =
+                OracleManager::get_stake_pool_balances(&ctx.accounts.pool)?;
+            OracleManager::derive_stake_pool_price(&base_price, total_lamports, pool_token_supply)?
+        }
+        DerivedOracleKind::LiquidityPool => {
+            let quote_oracle = ctx
+                .accounts
+                .quote_oracle
+                .as_ref()
+                .ok_or(LendingError::OracleAccountMismatch)?;
+            let quote_price = OracleManager::get_component_price(&derived.quote, quote_oracle)?;
+            quote_price.validate(clock.unix_timestamp)?;
+
+            let (base_vault, quote_vault) =
+                match (&ctx.accounts.base_vault, &ctx.accounts.quote_vault) {
+                    (Some(base_vault), Some(quote_vault))
+                        if base_vault.key() == derived.base.vault
+                            && quote_vault.key() == derived.quote.vault =>
+                    {
+                        (base_vault, quote_vault)
+                    }
+                    _ => return Err(LendingError::OracleAccountMismatch.into()),
+                };
+            let lp_mint = InterfaceAccount::<Mint>::try_from(&ctx.accounts.pool)?;
+
+            OracleManager::derive_lp_price(
+                &base_price,
+                base_vault.amount,
+                derived.base.decimals,
+                &quote_price,
+                quote_vault.amount,
+                derived.quote.decimals,
+                lp_mint.supply,
+                lp_mint.decimals,
+            )?
+        }
+        DerivedOracleKind::None => return Err(LendingError::InvalidConfiguration.into()),
+    };
+
+    reserve.derived_oracle.set_price(&price)?;
+
+    msg!(
+        "Derived price of reserve {} refreshed: {} (10^{})",
+        reserve.key(),
+        price.price,
+        price.exponent
+    );
+    Ok(())
+}
+
+/// Accrue a reserve's interest and fold its fresh, validated price into the TWAP
+fn refresh_reserve_price(
+    reserve: &mut Reserve,
+    price_oracle: &AccountInfo,
+    secondary_price_oracle: Option<&AccountInfo>,
+) -> Result<()> {
     let clock = Clock::get()?;
 
     // Update interest rates based on current utilization
     reserve.update_interest(clock.slot)?;
 
     // Get fresh price from oracle
-    let oracle_price = OracleManager::get_pyth_price(
-        &ctx.accounts.price_oracle.to_account_info(),
-        &reserve.oracle_feed_id,
-    )?;
+    let oracle_price =
+        OracleManager::get_checked_price(price_oracle, secondary_price_oracle, reserve)?;
 
     // Validate price quality and freshness
     oracle_price.validate(clock.unix_timestamp)?;
 
+    // Fold the price into the TWAP used to smooth liquidation valuations
+    reserve.update_twap(&oracle_price, clock.unix_timestamp as u64)?;
+
     msg!(
         "Reserve refreshed - utilization: {:.2}%, borrow rate: {:.2}%, supply rate: {:.2}%",
-        reserve.state.current_utilization_rate.try_floor_u64()? as f64 / 1e16,
-        reserve.state.current_borrow_rate.try_floor_u64()? as f64 / 1e16,
-        reserve.state.current_supply_rate.try_floor_u64()? as f64 / 1e16
+        reserve.state.current_utilization_rate_bps as f64 / 100.0,
+        reserve.state.current_borrow_rate_bps as f64 / 100.0,
+        reserve.state.current_supply_rate_bps as f64 / 100.0
     );
 
     Ok(())
 }
 
 /// Refresh obligation health by updating collateral and borrow values
+/// Every position is revalued from its reserve and oracle, passed as remaining accounts.
+/// Borrows and withdrawals against the obligation require a refresh in the same slot.
 pub fn refresh_obligation(ctx: Context<RefreshObligation>) -> Result<()> {
-    let obligation = &mut ctx.accounts.obligation;
+    let mut obligation =
+        AnyObligation::load_checked(&ctx.accounts.obligation, &ctx.accounts.market.key())?;
     let clock = Clock::get()?;
 
-    let mut total_deposited_value = Decimal::zero();
-    let mut total_borrowed_value = Decimal::zero();
-
-    // Update collateral values
-    for (i, deposit) in obligation.deposits.iter_mut().enumerate() {
-        // Get corresponding reserve and price oracle from remaining accounts
-        let reserve_info = ctx
-            .remaining_accounts
-            .get(i * 2)
-            .ok_or(LendingError::InvalidAccount)?;
-        let oracle_info = ctx
-            .remaining_accounts
-            .get(i * 2 + 1)
-            .ok_or(LendingError::InvalidAccount)?;
-
-        // Deserialize reserve account
-        let reserve_data = reserve_info.try_borrow_data()?;
-        let mut reserve_data_slice = reserve_data.as_ref();
-        let reserve = Reserve::try_deserialize(&mut reserve_data_slice)
-            .map_err(|_| LendingError::InvalidAccount)?;
-
-        // Validate reserve matches deposit
-        if reserve_info.key() != deposit.deposit_reserve {
-            return Err(LendingError::InvalidAccount.into());
-        }
-
-        // Get fresh price
-        let oracle_price = OracleManager::get_pyth_price(oracle_info, &reserve.oracle_feed_id)?;
-        oracle_price.validate(clock.unix_timestamp)?;
-
-        // Calculate updated collateral value
-        let collateral_value = OracleManager::calculate_usd_value(
-            deposit.deposited_amount,
-            &oracle_price,
-            reserve.config.decimals,
-        )?;
+    ctx.accounts
+        .config
+        .validate_account_count(ctx.remaining_accounts.len())?;
 
-        // Update deposit values
-        deposit.market_value_usd = collateral_value;
-        deposit.ltv_bps = reserve.config.loan_to_value_ratio_bps;
-        deposit.liquidation_threshold_bps = reserve.config.liquidation_threshold_bps;
+    // Revalue collateral and borrows (with accrued interest) at current prices
+    obligation.refresh_health_factor(ctx.remaining_accounts, &clock)?;
+    obligation.record_debt_watermark()?;
 
-        total_deposited_value = total_deposited_value.try_add(collateral_value)?;
-    }
-
-    // Update borrow values
-    let deposit_count = obligation.deposits.len();
-    for (i, borrow) in obligation.borrows.iter_mut().enumerate() {
-        // Get corresponding reserve and price oracle from remaining accounts
-        let reserve_info = ctx
-            .remaining_accounts
-            .get(deposit_count * 2 + i * 2)
-            .ok_or(LendingError::InvalidAccount)?;
-        let oracle_info = ctx
-            .remaining_accounts
-            .get(deposit_count * 2 + i * 2 + 1)
-            .ok_or(LendingError::InvalidAccount)?;
-
-        // Deserialize reserve account
-        let reserve_data = reserve_info.try_borrow_data()?;
-        let mut reserve_data_slice = reserve_data.as_ref();
-        let reserve = Reserve::try_deserialize(&mut reserve_data_slice)
-            .map_err(|_| LendingError::InvalidAccount)?;
+    // Track how long the obligation has been liquidatable (used by the backstop vault and
+    // the borrower's liquidation grace period)
+    let became_unhealthy = obligation.update_unhealthy_since(clock.slot)?;
 
-        // Validate reserve matches borrow
-        if reserve_info.key() != borrow.borrow_reserve {
-            return Err(LendingError::InvalidAccount.into());
-        }
+    // Calculate health factor for logging
+    let health_factor = obligation.calculate_health_factor()?;
 
-        // Get fresh price
-        let oracle_price = OracleManager::get_pyth_price(oracle_info, &reserve.oracle_feed_id)?;
-        oracle_price.validate(clock.unix_timestamp)?;
+    // Keep the market's liquidation queue in step with the obligation's health
+    sync_liquidation_queue(
+        ctx.accounts.liquidation_queue.as_mut(),
+        ctx.accounts.obligation.key(),
+        &obligation,
+        health_factor.to_health_bps_saturating(),
+        clock.slot,
+    )?;
 
-        // Calculate updated borrow value (includes accrued interest)
-        let borrow_amount = borrow.borrowed_amount_wads.try_floor_u64()?;
-        let borrow_value = OracleManager::calculate_usd_value(
-            borrow_amount,
-            &oracle_price,
-            reserve.config.decimals,
-        )?;
-
-        // Update borrow value
-        borrow.market_value_usd = borrow_value;
-        total_borrowed_value = total_borrowed_value.try_add(borrow_value)?;
+    if became_unhealthy {
+        emit!(ObligationHealthWarning {
+            obligation: ctx.accounts.obligation.key(),
+            owner: obligation.owner,
+            health_factor_bps: health_factor.to_health_bps_saturating(),
+            grace_floor_bps: obligation.liquidation_grace_floor_bps,
+            liquidatable_from_slot: clock
+                .slot
+                .saturating_add(obligation.liquidation_grace_slots),
+            slot: clock.slot,
+        });
     }
 
-    // Update cached values
-    obligation.deposited_value_usd = total_deposited_value;
-    obligation.borrowed_value_usd = total_borrowed_value;
-    obligation.update_timestamp(clock.slot);
-
-    // Calculate health factor for logging
-    let health_factor = obligation.calculate_health_factor()?;
-
     msg!(
-        "Obligation refreshed - deposited: ${:.2}, borrowed: ${:.2}, health factor: {:.3}",
-        total_deposited_value.try_floor_u64()? as f64 / 1e18,
-        total_borrowed_value.try_floor_u64()? as f64 / 1e18,
-        health_factor.try_floor_u64()? as f64 / 1e18
+        "Obligation refreshed - deposited: ${:.2}, borrowed: ${:.2}, health factor (bps): {}",
+        obligation.deposited_value_usd.try_floor_u64()? as f64 / 1e18,
+        obligation.borrowed_value_usd.try_floor_u64()? as f64 / 1e18,
+        health_factor.to_health_bps_saturating()
     );
 
+    obligation.save(&ctx.accounts.obligation)?;
+
     Ok(())
 }
 
@@ -144,6 +216,15 @@ pub fn refresh_obligation(ctx: Context<RefreshObligation>) -> Result<()> {
 pub fn refresh_multiple_reserves(ctx: Context<RefreshMultipleReserves>) -> Result<()> {
     let clock = Clock::get()?;
 
+    ctx.accounts
+        .config
+        .validate_account_count(ctx.remaining_accounts.len())?;
+
+    // Accounts come in (reserve, oracle) pairs
+    if ctx.remaining_accounts.len() % 2 != 0 {
+        return Err(LendingError::InvalidAccount.into());
+    }
+
     // Process each reserve from remaining accounts
     for i in (0..ctx.remaining_accounts.len()).step_by(2) {
         let reserve_info = &ctx.remaining_accounts[i];
@@ -159,7 +240,7 @@ pub fn refresh_multiple_reserves(ctx: Context<RefreshMultipleReserves>) -> Resul
         reserve.update_interest(clock.slot)?;
 
         // Validate oracle price
-        let oracle_price = OracleManager::get_pyth_price(oracle_info, &reserve.oracle_feed_id)?;
+        let oracle_price = OracleManager::get_price(oracle_info, &reserve)?;
         oracle_price.validate(clock.unix_timestamp)?;
 
         // Serialize reserve back with comprehensive error handling
@@ -238,10 +319,73 @@ pub struct RefreshReserve<'info> {
     /// Price oracle account
     /// CHECK: This account is validated by the reserve's price_oracle field
     pub price_oracle: UncheckedAccount<'info>,
+
+    /// Secondary oracle of the reserve (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub secondary_price_oracle: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(Accounts)]
-pub struct RefreshObligation<'info> {
+pub struct PostAndRefreshReserve<'info> {
+    /// Pays the receiver's fee and, on the first post, the price update account's rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve account to refresh
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = price_oracle @ LendingError::OracleAccountMismatch,
+        constraint = reserve.oracle_source == OracleSource::Pyth @ LendingError::OracleAccountMismatch
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Reserve's price update account, written by the Pyth receiver
+    /// CHECK: PDA of the reserve, created or overwritten by the receiver program
+    #[account(
+        mut,
+        seeds = [PRICE_UPDATE_SEED, reserve.key().as_ref()],
+        bump
+    )]
+    pub price_oracle: UncheckedAccount<'info>,
+
+    /// Secondary oracle of the reserve (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Pyth receiver program
+    /// CHECK: Address checked
+    #[account(address = pyth_solana_receiver_sdk::ID)]
+    pub pyth_receiver_program: UncheckedAccount<'info>,
+
+    /// Wormhole guardian set that signed the VAA
+    /// CHECK: Validated by the receiver program
+    pub guardian_set: UncheckedAccount<'info>,
+
+    /// Pyth receiver configuration
+    /// CHECK: Address checked, contents validated by the receiver program
+    #[account(address = pyth_solana_receiver_sdk::pda::get_config_address())]
+    pub receiver_config: UncheckedAccount<'info>,
+
+    /// Pyth receiver treasury collecting the update fee
+    /// CHECK: Validated against the update's treasury id by the receiver program
+    #[account(mut)]
+    pub receiver_treasury: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshDerivedPrice<'info> {
     /// Market account
     #[account(
         seeds = [MARKET_SEED],
@@ -249,14 +393,63 @@ pub struct RefreshObligation<'info> {
     )]
     pub market: Account<'info, Market>,
 
-    /// Obligation account to refresh
+    /// Reserve whose derived price is refreshed
     #[account(
         mut,
-        seeds = [OBLIGATION_SEED, obligation.owner.as_ref()],
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
         bump,
         has_one = market @ LendingError::InvalidMarketState
     )]
-    pub obligation: Account<'info, Obligation>,
+    pub reserve: Account<'info, Reserve>,
+
+    /// Oracle of SOL (stake pools) or of the pool's first token (LP tokens)
+    /// CHECK: Matched against the derived oracle's base component
+    pub base_oracle: UncheckedAccount<'info>,
+
+    /// Oracle of the pool's second token (LP tokens only)
+    /// CHECK: Matched against the derived oracle's quote component
+    pub quote_oracle: Option<UncheckedAccount<'info>>,
+
+    /// SPL stake pool (stake pools) or LP token mint (LP tokens)
+    /// CHECK: Address checked; the owner is checked when the account is read
+    #[account(address = reserve.derived_oracle.pool @ LendingError::OracleAccountMismatch)]
+    pub pool: UncheckedAccount<'info>,
+
+    /// Pool vault of the first token (LP tokens only)
+    pub base_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Pool vault of the second token (LP tokens only)
+    pub quote_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshObligation<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol configuration (remaining account cap)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Obligation account to refresh (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Market's liquidation queue, updated with the obligation's health when supplied
+    #[account(
+        mut,
+        seeds = [LIQUIDATION_QUEUE_SEED, market.key().as_ref()],
+        bump = liquidation_queue.bump
+    )]
+    pub liquidation_queue: Option<Account<'info, LiquidationQueue>>,
     // Note: Additional reserve and oracle accounts are passed as remaining_accounts
     // Format: [reserve1, oracle1, reserve2, oracle2, ...] for deposits
     //         [reserve1, oracle1, reserve2, oracle2, ...] for borrows
@@ -270,6 +463,13 @@ pub struct RefreshMultipleReserves<'info> {
         bump
     )]
     pub market: Account<'info, Market>,
+
+    /// Protocol configuration (remaining account cap)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     // Note: Reserve and oracle accounts are passed as remaining_accounts
     // Format: [reserve1, oracle1, reserve2, oracle2, ...]
 }
@@ -0,0 +1,353 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::*;
+use crate::utils::TokenUtils;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Initialize a collateral order book for a (collateral reserve, payment reserve) pair
+pub fn initialize_collateral_order_book(ctx: Context<InitializeCollateralOrderBook>) -> Result<()> {
+    **ctx.accounts.order_book = CollateralOrderBook::new(
+        ctx.accounts.market.key(),
+        ctx.accounts.collateral_reserve.key(),
+        ctx.accounts.payment_reserve.key(),
+        ctx.accounts.liquidity_escrow.key(),
+        ctx.accounts.collateral_escrow.key(),
+        ctx.bumps.order_book,
+    );
+
+    msg!(
+        "Collateral order book initialized - collateral reserve: {}, payment reserve: {}",
+        ctx.accounts.collateral_reserve.key(),
+        ctx.accounts.payment_reserve.key()
+    );
+    Ok(())
+}
+
+/// Place a standing order to buy liquidated collateral, escrowing the payment liquidity
+pub fn place_collateral_order(
+    ctx: Context<PlaceCollateralOrder>,
+    params: PlaceCollateralOrderParams,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    if params.liquidity_amount < MIN_DEPOSIT_AMOUNT {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    // Escrow the payment liquidity; the order holds what arrives after the transfer fee
+    let escrowed_amount =
+        TokenUtils::amount_after_transfer_fee(&ctx.accounts.payment_mint, params.liquidity_amount)?;
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.source_liquidity,
+        &ctx.accounts.liquidity_escrow,
+        &ctx.accounts.payment_mint,
+        &ctx.accounts.owner.to_account_info(),
+        &[],
+        params.liquidity_amount,
+    )?;
+
+    let order_id = ctx.accounts.order_book.place_order(
+        ctx.accounts.owner.key(),
+        params.max_price,
+        escrowed_amount,
+        clock.slot,
+    )?;
+
+    msg!(
+        "Collateral order {} placed - amount: {}, max price: {}",
+        order_id,
+        escrowed_amount,
+        params.max_price.value
+    );
+    Ok(())
+}
+
+/// Cancel an order, returning unfilled liquidity and any filled collateral to the owner
+pub fn cancel_collateral_order(ctx: Context<CancelCollateralOrder>, order_id: u64) -> Result<()> {
+    let (unfilled_liquidity, filled_collateral) = ctx
+        .accounts
+        .order_book
+        .cancel_order(order_id, &ctx.accounts.owner.key())?;
+
+    let order_book = &ctx.accounts.order_book;
+    let book_seeds = &[
+        COLLATERAL_ORDER_BOOK_SEED,
+        order_book.collateral_reserve.as_ref(),
+        order_book.payment_reserve.as_ref(),
+        &[order_book.bump],
+    ];
+
+    if unfilled_liquidity > 0 {
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.liquidity_escrow,
+            &ctx.accounts.destination_liquidity,
+            &ctx.accounts.payment_mint,
+            &order_book.to_account_info(),
+            &[book_seeds],
+            unfilled_liquidity,
+        )?;
+    }
+
+    if filled_collateral > 0 {
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.collateral_token_program,
+            &ctx.accounts.collateral_escrow,
+            &ctx.accounts.destination_collateral,
+            &ctx.accounts.collateral_mint,
+            &order_book.to_account_info(),
+            &[book_seeds],
+            filled_collateral,
+        )?;
+    }
+
+    msg!(
+        "Collateral order {} cancelled - refunded: {}, collateral released: {}",
+        order_id,
+        unfilled_liquidity,
+        filled_collateral
+    );
+    Ok(())
+}
+
+/// Claim collateral filled against an order
+pub fn claim_collateral_order_fills(
+    ctx: Context<ClaimCollateralOrderFills>,
+    order_id: u64,
+) -> Result<()> {
+    let filled_collateral = ctx
+        .accounts
+        .order_book
+        .claim_fills(order_id, &ctx.accounts.owner.key())?;
+
+    if filled_collateral == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    let order_book = &ctx.accounts.order_book;
+    let book_seeds = &[
+        COLLATERAL_ORDER_BOOK_SEED,
+        order_book.collateral_reserve.as_ref(),
+        order_book.payment_reserve.as_ref(),
+        &[order_book.bump],
+    ];
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.collateral_escrow,
+        &ctx.accounts.destination_collateral,
+        &ctx.accounts.collateral_mint,
+        &order_book.to_account_info(),
+        &[book_seeds],
+        filled_collateral,
+    )?;
+
+    msg!(
+        "Claimed {} collateral from order {}",
+        filled_collateral,
+        order_id
+    );
+    Ok(())
+}
+
+// Context structs for collateral order book instructions
+
+#[derive(Accounts)]
+pub struct InitializeCollateralOrderBook<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Order book account to initialize
+    #[account(
+        init,
+        payer = payer,
+        space = CollateralOrderBook::SIZE,
+        seeds = [COLLATERAL_ORDER_BOOK_SEED, collateral_reserve.key().as_ref(), payment_reserve.key().as_ref()],
+        bump
+    )]
+    pub order_book: Account<'info, CollateralOrderBook>,
+
+    /// Reserve whose collateral the orders buy
+    #[account(has_one = market @ LendingError::InvalidMarketState)]
+    pub collateral_reserve: Account<'info, Reserve>,
+
+    /// Reserve whose liquidity the orders pay with
+    #[account(has_one = market @ LendingError::InvalidMarketState)]
+    pub payment_reserve: Account<'info, Reserve>,
+
+    /// Collateral (aToken) mint of the collateral reserve
+    #[account(address = collateral_reserve.collateral_mint @ LendingError::ReserveCollateralMintMismatch)]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Liquidity mint of the payment reserve
+    #[account(address = payment_reserve.liquidity_mint @ LendingError::ReserveLiquidityMintMismatch)]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    /// Escrow for payment liquidity of open orders
+    #[account(
+        init,
+        payer = payer,
+        token::mint = payment_mint,
+        token::authority = order_book,
+        seeds = [COLLATERAL_ORDER_BOOK_SEED, order_book.key().as_ref(), b"liquidity"],
+        bump
+    )]
+    pub liquidity_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// Escrow for collateral filled against orders
+    #[account(
+        init,
+        payer = payer,
+        token::mint = collateral_mint,
+        token::authority = order_book,
+        token::token_program = collateral_token_program,
+        seeds = [COLLATERAL_ORDER_BOOK_SEED, order_book.key().as_ref(), b"collateral"],
+        bump
+    )]
+    pub collateral_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// Payer for account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Token program of the payment mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the collateral mint
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+
+    /// Rent sysvar
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceCollateralOrder<'info> {
+    /// Order book account
+    #[account(
+        mut,
+        seeds = [COLLATERAL_ORDER_BOOK_SEED, order_book.collateral_reserve.as_ref(), order_book.payment_reserve.as_ref()],
+        bump = order_book.bump,
+        has_one = liquidity_escrow @ LendingError::InvalidAccount
+    )]
+    pub order_book: Account<'info, CollateralOrderBook>,
+
+    /// Escrow for payment liquidity
+    #[account(mut)]
+    pub liquidity_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity mint of the payment reserve
+    #[account(address = liquidity_escrow.mint @ LendingError::TokenMintMismatch)]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    /// Owner's source liquidity token account
+    #[account(
+        mut,
+        token::mint = liquidity_escrow.mint,
+        token::authority = owner
+    )]
+    pub source_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Order owner
+    pub owner: Signer<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CancelCollateralOrder<'info> {
+    /// Order book account
+    #[account(
+        mut,
+        seeds = [COLLATERAL_ORDER_BOOK_SEED, order_book.collateral_reserve.as_ref(), order_book.payment_reserve.as_ref()],
+        bump = order_book.bump,
+        has_one = liquidity_escrow @ LendingError::InvalidAccount,
+        has_one = collateral_escrow @ LendingError::InvalidAccount
+    )]
+    pub order_book: Account<'info, CollateralOrderBook>,
+
+    /// Escrow for payment liquidity
+    #[account(mut)]
+    pub liquidity_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// Escrow for filled collateral
+    #[account(mut)]
+    pub collateral_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity mint of the payment reserve
+    #[account(address = liquidity_escrow.mint @ LendingError::TokenMintMismatch)]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    /// Collateral (aToken) mint of the collateral reserve
+    #[account(address = collateral_escrow.mint @ LendingError::TokenMintMismatch)]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Owner's destination liquidity token account
+    #[account(
+        mut,
+        token::mint = liquidity_escrow.mint,
+        token::authority = owner
+    )]
+    pub destination_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Owner's destination collateral token account
+    #[account(
+        mut,
+        token::mint = collateral_escrow.mint,
+        token::authority = owner
+    )]
+    pub destination_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Order owner
+    pub owner: Signer<'info>,
+
+    /// Token program of the payment mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the collateral mint
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCollateralOrderFills<'info> {
+    /// Order book account
+    #[account(
+        mut,
+        seeds = [COLLATERAL_ORDER_BOOK_SEED, order_book.collateral_reserve.as_ref(), order_book.payment_reserve.as_ref()],
+        bump = order_book.bump,
+        has_one = collateral_escrow @ LendingError::InvalidAccount
+    )]
+    pub order_book: Account<'info, CollateralOrderBook>,
+
+    /// Escrow for filled collateral
+    #[account(mut)]
+    pub collateral_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// Collateral (aToken) mint of the collateral reserve
+    #[account(address = collateral_escrow.mint @ LendingError::TokenMintMismatch)]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Owner's destination collateral token account
+    #[account(
+        mut,
+        token::mint = collateral_escrow.mint,
+        token::authority = owner
+    )]
+    pub destination_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Order owner
+    pub owner: Signer<'info>,
+
+    /// Token program of the collateral mint
+    pub token_program: Interface<'info, TokenInterface>,
+}
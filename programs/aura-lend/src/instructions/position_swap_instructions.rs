@@ -0,0 +1,2039 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::events::{BorrowEvent, CollateralDepositEvent, CollateralWithdrawEvent, RepayEvent};
+use crate::instructions::borrowing_instructions::validate_borrow_capacity;
+use crate::state::*;
+use crate::utils::{
+    math::Decimal, OracleManager, ProtocolConfig, ReserveLock, SwapExecutor, TokenUtils,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Repay an obligation's debt with its own collateral in one transaction
+/// The collateral is redeemed for its underlying asset, swapped through a whitelisted
+/// DEX into the repay asset and repaid, so the borrower needs no repay asset of their
+/// own. The swap must return at least `min_amount_out` and no less than the oracle value
+/// less `max_slippage_bps`. The obligation must end healthy, or at least no less healthy
+/// than it started. Output beyond the debt stays in the owner's repay account.
+/// Remaining accounts are the swap route. Collateral of the debt's own asset is netted:
+/// the same reserve is passed on both sides and the redeemed liquidity repays the debt
+/// without a swap, so no route accounts are passed.
+pub fn repay_with_collateral<'info>(
+    ctx: Context<'_, '_, '_, 'info, RepayWithCollateral<'info>>,
+    params: RepayWithCollateralParams,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let mut obligation = AnyObligation::load_owned(
+        &ctx.accounts.obligation,
+        &market.key(),
+        &ctx.accounts.obligation_owner.key(),
+    )?;
+    let clock = Clock::get()?;
+
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(
+        &ctx.accounts.flash_loan_guard,
+        FlashLoanUsage::WITHDRAW_COLLATERAL | FlashLoanUsage::REPAY,
+    )?;
+
+    ctx.accounts
+        .config
+        .validate_account_count(ctx.remaining_accounts.len())?;
+    SwapExecutor::validate_program(
+        &ctx.accounts.swap_program.key(),
+        &ctx.accounts.fee_converter.swap_programs,
+    )?;
+
+    // Check if market allows withdrawals and repayments
+    if market.is_paused() && !market.is_emergency() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    // Reserves are PDAs of their liquidity mint, so only one reserve holds a given asset
+    let net_swap = ctx.accounts.withdraw_reserve.key() == ctx.accounts.repay_reserve.key();
+
+    // Withdrawals-disabled is checked with the rest of the redemption limits
+    if ctx
+        .accounts
+        .repay_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::REPAYMENTS_DISABLED)
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    if params.collateral_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    // Health is compared on values from a refresh in this slot
+    obligation.require_refreshed(clock.slot)?;
+    let emode = EModeCategory::resolve(ctx.accounts.emode_category.as_deref(), &obligation, None)?;
+    let health_before = obligation.calculate_health_factor_with(emode.as_ref())?;
+
+    let withdraw_reserve = &mut ctx.accounts.withdraw_reserve;
+    let repay_reserve = &mut ctx.accounts.repay_reserve;
+
+    // Hold the reentrancy locks until the reserve state is written back, so the swap cannot
+    // call back into either reserve. A same-asset pair locks its one account through both
+    // copies, the second of which was loaded before the first lock was written.
+    let mut withdraw_reserve = ReserveLock::acquire(withdraw_reserve)?;
+    let mut repay_reserve = ReserveLock::acquire(repay_reserve)?;
+
+    withdraw_reserve.update_interest(clock.slot)?;
+    repay_reserve.update_interest(clock.slot)?;
+    obligation.accrue_borrow_interest(
+        &repay_reserve.key(),
+        repay_reserve.cumulative_borrow_rate_wads(),
+    )?;
+
+    let deposited_collateral = obligation
+        .find_collateral_deposit(&withdraw_reserve.key())
+        .ok_or(LendingError::ObligationReserveNotFound)?
+        .deposited_amount;
+    if deposited_collateral < params.collateral_amount {
+        return Err(LendingError::InsufficientCollateral.into());
+    }
+
+    let borrowed_amount = obligation
+        .find_liquidity_borrow(&repay_reserve.key())
+        .ok_or(LendingError::ObligationReserveNotFound)?
+        .borrowed_amount_wads
+        .try_floor_u64()?;
+
+    let withdraw_price = OracleManager::get_checked_price(
+        &ctx.accounts.withdraw_price_oracle.to_account_info(),
+        ctx.accounts.withdraw_secondary_price_oracle.as_deref(),
+        &withdraw_reserve,
+    )?;
+    withdraw_price.validate(clock.unix_timestamp)?;
+
+    let repay_price = OracleManager::get_checked_price(
+        &ctx.accounts.repay_price_oracle.to_account_info(),
+        ctx.accounts.repay_secondary_price_oracle.as_deref(),
+        &repay_reserve,
+    )?;
+    repay_price.validate(clock.unix_timestamp)?;
+
+    // Step 1: redeem the collateral straight out of the reserve into the owner's account,
+    // under the same withdrawal limits and surge fee as `redeem_reserve_collateral`
+    let redemption = withdraw_reserve.quote_redemption(
+        &ctx.accounts.config,
+        params.collateral_amount,
+        clock.slot,
+    )?;
+    let redeemed_liquidity = redemption.withdrawn_amount;
+    if ctx.accounts.config.is_large_withdrawal(
+        redemption.liquidity_amount,
+        withdraw_reserve.state.total_liquidity,
+    )? {
+        return Err(LendingError::LargeWithdrawalRequestRequired.into());
+    }
+
+    let collateral_authority_seeds = &[
+        COLLATERAL_TOKEN_SEED,
+        withdraw_reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.withdraw_collateral_supply_authority],
+    ];
+    TokenUtils::burn_tokens(
+        &ctx.accounts.collateral_token_program,
+        &ctx.accounts.withdraw_collateral_mint,
+        &ctx.accounts.withdraw_reserve_collateral_supply,
+        &ctx.accounts
+            .withdraw_collateral_supply_authority
+            .to_account_info(),
+        &[collateral_authority_seeds],
+        params.collateral_amount,
+    )?;
+
+    let liquidity_authority_seeds = &[
+        LIQUIDITY_TOKEN_SEED,
+        withdraw_reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.withdraw_liquidity_supply_authority],
+    ];
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.collateral_token_program,
+        &ctx.accounts.withdraw_reserve_liquidity_supply,
+        &ctx.accounts.withdrawn_liquidity,
+        &ctx.accounts.withdraw_liquidity_mint,
+        &ctx.accounts
+            .withdraw_liquidity_supply_authority
+            .to_account_info(),
+        &[liquidity_authority_seeds],
+        redeemed_liquidity,
+    )?;
+
+    withdraw_reserve.settle_redemption(&redemption)?;
+    sync_same_reserve(&withdraw_reserve, &mut repay_reserve);
+
+    // Step 2: swap the redeemed liquidity into the repay asset, bounded by both the
+    // caller's minimum and the oracle value. Liquidity already in the repay asset is
+    // netted: it repays directly without touching the DEX.
+    let received_liquidity = TokenUtils::amount_after_transfer_fee(
+        &ctx.accounts.withdraw_liquidity_mint,
+        redeemed_liquidity,
+    )?;
+    let swap = if net_swap {
+        SwapExecutor::net(
+            ctx.remaining_accounts,
+            received_liquidity,
+            received_liquidity,
+            params.min_amount_out,
+        )?
+    } else {
+        let oracle_min_amount_out = SwapExecutor::oracle_min_amount_out(
+            received_liquidity,
+            &withdraw_price,
+            withdraw_reserve.config.decimals,
+            &repay_price,
+            repay_reserve.config.decimals,
+            params.max_slippage_bps as u64,
+        )?;
+        SwapExecutor::execute(
+            &ctx.accounts.swap_program.to_account_info(),
+            ctx.remaining_accounts,
+            params.route_data,
+            None,
+            &[],
+            &mut ctx.accounts.withdrawn_liquidity,
+            &mut ctx.accounts.repay_liquidity,
+            received_liquidity,
+            params.min_amount_out.max(oracle_min_amount_out),
+        )?
+    };
+    let repay_source = if net_swap {
+        &ctx.accounts.withdrawn_liquidity
+    } else {
+        &ctx.accounts.repay_liquidity
+    };
+
+    // Step 3: repay up to the whole debt with the swap output
+    let repay_amount = std::cmp::min(
+        borrowed_amount,
+        TokenUtils::amount_after_transfer_fee(&ctx.accounts.repay_liquidity_mint, swap.amount_out)?,
+    );
+    if repay_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    // Grossed up so that the reserve receives the full repayment after transfer fees
+    let transfer_amount =
+        TokenUtils::amount_before_transfer_fee(&ctx.accounts.repay_liquidity_mint, repay_amount)?;
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        repay_source,
+        &ctx.accounts.repay_reserve_liquidity_supply,
+        &ctx.accounts.repay_liquidity_mint,
+        &ctx.accounts.obligation_owner.to_account_info(),
+        &[],
+        transfer_amount,
+    )?;
+
+    // Update reserve and obligation accounting
+    repay_reserve.repay_borrow(repay_amount)?;
+    sync_same_reserve(&repay_reserve, &mut withdraw_reserve);
+    obligation
+        .repay_liquidity_borrow(&repay_reserve.key(), Decimal::from_integer(repay_amount)?)?;
+    obligation.remove_collateral_deposit(&withdraw_reserve.key(), params.collateral_amount)?;
+
+    let repay_value_usd = OracleManager::calculate_usd_value(
+        repay_amount,
+        &repay_price,
+        repay_reserve.config.decimals,
+    )?;
+    let withdrawn_value_usd =
+        withdraw_reserve.collateral_value_usd(params.collateral_amount, &withdraw_price)?;
+    obligation.borrowed_value_usd = obligation.borrowed_value_usd.try_sub(repay_value_usd)?;
+    obligation.deposited_value_usd = obligation
+        .deposited_value_usd
+        .try_sub(withdrawn_value_usd)?;
+    obligation.record_repayment();
+    obligation.update_timestamp(clock.slot)?;
+
+    // A poor swap may not leave the obligation unhealthy, or worse off if it already was
+    let health_after = obligation.calculate_health_factor_with(emode.as_ref())?;
+    if !obligation.is_healthy_with(emode.as_ref())? && health_after < health_before {
+        return Err(LendingError::ObligationUnhealthy.into());
+    }
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    msg!(
+        "Repaid {} with {} collateral tokens - swapped {} into {}, health {} -> {} bps",
+        repay_amount,
+        params.collateral_amount,
+        swap.amount_in,
+        swap.amount_out,
+        health_before.to_health_bps_saturating(),
+        health_after.to_health_bps_saturating()
+    );
+
+    let withdraw_receipt = OperationReceipt::new(
+        withdraw_reserve.key(),
+        &withdraw_reserve,
+        redeemed_liquidity,
+        params.collateral_amount,
+        redemption.surge_fee,
+        Some(health_after),
+    )?;
+    emit!(CollateralWithdrawEvent {
+        obligation: ctx.accounts.obligation.key(),
+        owner: ctx.accounts.obligation_owner.key(),
+        receipt: withdraw_receipt,
+        slot: clock.slot,
+    });
+
+    let repay_receipt = OperationReceipt::new(
+        repay_reserve.key(),
+        &repay_reserve,
+        repay_amount,
+        0,
+        0,
+        Some(health_after),
+    )?;
+    emit!(RepayEvent {
+        obligation: ctx.accounts.obligation.key(),
+        payer: ctx.accounts.obligation_owner.key(),
+        receipt: repay_receipt,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
+
+/// Lever an obligation up to a target leverage in one transaction
+/// Rather than looping borrow, swap and deposit, the whole borrow needed to reach
+/// `target_leverage_bps` is taken at once, flash-loan style: it is swapped through a
+/// whitelisted DEX into the collateral asset and deposited, and only then checked
+/// against the obligation's LTV with the buffer `borrow_obligation_liquidity` applies.
+/// The swap output is bounded as in `repay_with_collateral`. Remaining accounts are the
+/// swap route; a loop into the borrowed asset itself is netted without one.
+pub fn loop_position<'info>(
+    ctx: Context<'_, '_, '_, 'info, LoopPosition<'info>>,
+    params: LoopPositionParams,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let mut obligation = AnyObligation::load_owned(
+        &ctx.accounts.obligation,
+        &market.key(),
+        &ctx.accounts.obligation_owner.key(),
+    )?;
+    let clock = Clock::get()?;
+
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(
+        &ctx.accounts.flash_loan_guard,
+        FlashLoanUsage::BORROW
+            | FlashLoanUsage::DEPOSIT_LIQUIDITY
+            | FlashLoanUsage::DEPOSIT_COLLATERAL,
+    )?;
+
+    ctx.accounts
+        .config
+        .validate_account_count(ctx.remaining_accounts.len())?;
+    SwapExecutor::validate_program(
+        &ctx.accounts.swap_program.key(),
+        &ctx.accounts.fee_converter.swap_programs,
+    )?;
+
+    // Check if market allows borrowing and deposits
+    if market.is_paused() || market.is_borrowing_disabled() || market.is_lending_disabled() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    // Reserves are PDAs of their liquidity mint, so only one reserve holds a given asset
+    let net_swap = ctx.accounts.borrow_reserve.key() == ctx.accounts.deposit_reserve.key();
+
+    let borrow_reserve = &mut ctx.accounts.borrow_reserve;
+    let deposit_reserve = &mut ctx.accounts.deposit_reserve;
+
+    // A deprecated reserve takes no new debt
+    if borrow_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::BORROWING_DISABLED)
+        || borrow_reserve.is_deprecated()
+        || deposit_reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::DEPOSITS_DISABLED)
+        || !deposit_reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::COLLATERAL_ENABLED)
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    // Leverage is measured against values from a refresh in this slot
+    if !obligation.has_collateral() {
+        return Err(LendingError::ObligationCollateralEmpty.into());
+    }
+    obligation.require_refreshed(clock.slot)?;
+
+    // Isolated collateral cannot be mixed with other collateral, and only backs
+    // governance-approved debt reserves
+    let isolated = deposit_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::ISOLATED);
+    obligation.validate_collateral_isolation(&deposit_reserve.key(), isolated)?;
+    if (obligation.isolation_mode || isolated)
+        && !borrow_reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::BORROWABLE_IN_ISOLATION)
+    {
+        return Err(LendingError::BorrowNotAllowedInIsolation.into());
+    }
+
+    // Hold the reentrancy locks until the reserve state is written back
+    let mut borrow_reserve = ReserveLock::acquire(borrow_reserve)?;
+    let mut deposit_reserve = ReserveLock::acquire(deposit_reserve)?;
+
+    borrow_reserve.update_interest(clock.slot)?;
+    deposit_reserve.update_interest(clock.slot)?;
+
+    let borrow_price = OracleManager::get_checked_price(
+        &ctx.accounts.borrow_price_oracle.to_account_info(),
+        ctx.accounts.borrow_secondary_price_oracle.as_deref(),
+        &borrow_reserve,
+    )?;
+    borrow_price.validate(clock.unix_timestamp)?;
+
+    let deposit_price = OracleManager::get_checked_price(
+        &ctx.accounts.deposit_price_oracle.to_account_info(),
+        ctx.accounts.deposit_secondary_price_oracle.as_deref(),
+        &deposit_reserve,
+    )?;
+    deposit_price.validate(clock.unix_timestamp)?;
+
+    // Size the whole borrow from the target leverage
+    let borrow_amount = OracleManager::calculate_token_amount(
+        obligation.loop_borrow_value(params.target_leverage_bps)?,
+        &borrow_price,
+        borrow_reserve.config.decimals,
+    )?;
+    if borrow_amount < MIN_BORROW_AMOUNT {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+    if borrow_reserve.state.available_liquidity < borrow_amount {
+        return Err(LendingError::InsufficientLiquidity.into());
+    }
+    borrow_reserve.validate_borrow_cap(borrow_amount)?;
+
+    // The origination fee is deducted from the borrowed liquidity; the debt is the full amount
+    let borrow_fee = borrow_reserve.borrow_fee(borrow_amount)?;
+    let (treasury_fee, insurance_fee) = borrow_reserve.split_insurance_fee(borrow_fee)?;
+    let received_amount = borrow_amount
+        .checked_sub(borrow_fee)
+        .ok_or(LendingError::MathUnderflow)?;
+
+    // Step 1: borrow into the owner's account before the borrow is checked
+    let liquidity_authority_seeds = &[
+        LIQUIDITY_TOKEN_SEED,
+        borrow_reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.borrow_liquidity_supply_authority],
+    ];
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.borrow_reserve_liquidity_supply,
+        &ctx.accounts.borrowed_liquidity,
+        &ctx.accounts.borrow_liquidity_mint,
+        &ctx.accounts
+            .borrow_liquidity_supply_authority
+            .to_account_info(),
+        &[liquidity_authority_seeds],
+        received_amount,
+    )?;
+
+    if treasury_fee > 0 {
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.borrow_reserve_liquidity_supply,
+            &ctx.accounts.fee_receiver,
+            &ctx.accounts.borrow_liquidity_mint,
+            &ctx.accounts
+                .borrow_liquidity_supply_authority
+                .to_account_info(),
+            &[liquidity_authority_seeds],
+            treasury_fee,
+        )?;
+    }
+
+    if insurance_fee > 0 {
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.borrow_reserve_liquidity_supply,
+            &ctx.accounts.insurance_fund_vault,
+            &ctx.accounts.borrow_liquidity_mint,
+            &ctx.accounts
+                .borrow_liquidity_supply_authority
+                .to_account_info(),
+            &[liquidity_authority_seeds],
+            insurance_fee,
+        )?;
+        ctx.accounts.insurance_fund.record_deposit(insurance_fee)?;
+    }
+
+    borrow_reserve.add_borrow(borrow_amount)?;
+    sync_same_reserve(&borrow_reserve, &mut deposit_reserve);
+
+    // Step 2: swap the borrowed liquidity into the collateral asset; liquidity already
+    // in the collateral asset is deposited as is
+    let swapped_liquidity = TokenUtils::amount_after_transfer_fee(
+        &ctx.accounts.borrow_liquidity_mint,
+        received_amount,
+    )?;
+    let swap = if net_swap {
+        SwapExecutor::net(
+            ctx.remaining_accounts,
+            swapped_liquidity,
+            swapped_liquidity,
+            params.min_amount_out,
+        )?
+    } else {
+        let oracle_min_amount_out = SwapExecutor::oracle_min_amount_out(
+            swapped_liquidity,
+            &borrow_price,
+            borrow_reserve.config.decimals,
+            &deposit_price,
+            deposit_reserve.config.decimals,
+            params.max_slippage_bps as u64,
+        )?;
+        SwapExecutor::execute(
+            &ctx.accounts.swap_program.to_account_info(),
+            ctx.remaining_accounts,
+            params.route_data,
+            None,
+            &[],
+            &mut ctx.accounts.borrowed_liquidity,
+            &mut ctx.accounts.deposit_liquidity,
+            swapped_liquidity,
+            params.min_amount_out.max(oracle_min_amount_out),
+        )?
+    };
+    let deposit_source = if net_swap {
+        &ctx.accounts.borrowed_liquidity
+    } else {
+        &ctx.accounts.deposit_liquidity
+    };
+
+    // Step 3: deposit the swap output, minting the aTokens straight into the reserve's
+    // collateral supply on the obligation's behalf
+    let deposited_liquidity = TokenUtils::amount_after_transfer_fee(
+        &ctx.accounts.deposit_liquidity_mint,
+        swap.amount_out,
+    )?;
+    deposit_reserve.validate_supply_cap(deposited_liquidity)?;
+    let collateral_amount = deposit_reserve.liquidity_to_collateral(deposited_liquidity)?;
+    if collateral_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.collateral_token_program,
+        deposit_source,
+        &ctx.accounts.deposit_reserve_liquidity_supply,
+        &ctx.accounts.deposit_liquidity_mint,
+        &ctx.accounts.obligation_owner.to_account_info(),
+        &[],
+        swap.amount_out,
+    )?;
+
+    let collateral_authority_seeds = &[
+        COLLATERAL_TOKEN_SEED,
+        deposit_reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.deposit_collateral_authority],
+    ];
+    TokenUtils::mint_tokens(
+        &ctx.accounts.collateral_token_program,
+        &ctx.accounts.deposit_collateral_mint,
+        &ctx.accounts.deposit_reserve_collateral_supply,
+        &ctx.accounts.deposit_collateral_authority.to_account_info(),
+        &[collateral_authority_seeds],
+        collateral_amount,
+    )?;
+
+    deposit_reserve.add_liquidity(deposited_liquidity)?;
+    deposit_reserve.state.collateral_mint_supply = deposit_reserve
+        .state
+        .collateral_mint_supply
+        .checked_add(collateral_amount)
+        .ok_or(LendingError::MathOverflow)?;
+    sync_same_reserve(&deposit_reserve, &mut borrow_reserve);
+
+    let collateral_value_usd =
+        deposit_reserve.collateral_value_usd(collateral_amount, &deposit_price)?;
+    obligation.add_collateral_deposit(ObligationCollateral {
+        deposit_reserve: deposit_reserve.key(),
+        deposited_amount: collateral_amount,
+        market_value_usd: collateral_value_usd,
+        ltv_bps: deposit_reserve.effective_loan_to_value_ratio_bps(clock.unix_timestamp as u64),
+        liquidation_threshold_bps: deposit_reserve.config.liquidation_threshold_bps,
+    })?;
+    if isolated {
+        obligation.isolation_mode = true;
+    }
+    obligation.deposited_value_usd = obligation
+        .deposited_value_usd
+        .try_add(collateral_value_usd)?;
+
+    // Step 4: check the whole borrow against the obligation including its new collateral
+    let borrow_value_usd = OracleManager::calculate_usd_value(
+        borrow_amount,
+        &borrow_price,
+        borrow_reserve.config.decimals,
+    )?;
+    let emode = EModeCategory::resolve(
+        ctx.accounts.emode_category.as_deref(),
+        &obligation,
+        Some(&borrow_reserve.key()),
+    )?;
+    let new_borrowed_value =
+        validate_borrow_capacity(&obligation, borrow_value_usd, emode.as_ref())?;
+
+    let liquidity_borrow = ObligationLiquidity {
+        borrow_reserve: borrow_reserve.key(),
+        borrowed_amount_wads: Decimal::from_integer(borrow_amount)?,
+        market_value_usd: borrow_value_usd,
+        origination_utilization_rate: borrow_reserve.state.utilization_rate()?,
+        origination_borrow_rate: borrow_reserve.state.borrow_rate()?,
+        origination_slot: clock.slot,
+        cumulative_borrow_rate_wads: borrow_reserve.cumulative_borrow_rate_wads(),
+    };
+    obligation.add_liquidity_borrow(liquidity_borrow.clone())?;
+    obligation.borrowed_value_usd = new_borrowed_value;
+    obligation.record_debt_watermark()?;
+    obligation.update_timestamp(clock.slot)?;
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    let health_factor = obligation.calculate_health_factor_with(emode.as_ref())?;
+    msg!(
+        "Looped {} borrowed into {} collateral tokens (fee {}), health {} bps",
+        borrow_amount,
+        collateral_amount,
+        borrow_fee,
+        health_factor.to_health_bps_saturating()
+    );
+
+    let borrow_receipt = OperationReceipt::new(
+        borrow_reserve.key(),
+        &borrow_reserve,
+        borrow_amount,
+        0,
+        borrow_fee,
+        Some(health_factor),
+    )?;
+    emit!(BorrowEvent {
+        obligation: ctx.accounts.obligation.key(),
+        borrower: ctx.accounts.obligation_owner.key(),
+        receipt: borrow_receipt,
+        origination_utilization_rate: liquidity_borrow.origination_utilization_rate.value,
+        origination_borrow_rate: liquidity_borrow.origination_borrow_rate.value,
+        slot: clock.slot,
+    });
+
+    let deposit_receipt = OperationReceipt::new(
+        deposit_reserve.key(),
+        &deposit_reserve,
+        deposited_liquidity,
+        collateral_amount,
+        0,
+        Some(health_factor),
+    )?;
+    emit!(CollateralDepositEvent {
+        obligation: ctx.accounts.obligation.key(),
+        owner: ctx.accounts.obligation_owner.key(),
+        receipt: deposit_receipt,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
+
+/// Rotate an obligation's collateral from one reserve to another without repaying debt
+/// The collateral is redeemed, swapped through a whitelisted DEX into the deposit
+/// reserve's asset and deposited as collateral, with both reserves locked in between.
+/// The swap output is bounded as in `repay_with_collateral`, and the obligation must
+/// be healthy once the new collateral is in place. Remaining accounts are the swap
+/// route; rotating collateral into its own asset is netted without one.
+pub fn swap_collateral<'info>(
+    ctx: Context<'_, '_, '_, 'info, SwapCollateral<'info>>,
+    params: SwapCollateralParams,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let mut obligation = AnyObligation::load_owned(
+        &ctx.accounts.obligation,
+        &market.key(),
+        &ctx.accounts.obligation_owner.key(),
+    )?;
+    let clock = Clock::get()?;
+
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(
+        &ctx.accounts.flash_loan_guard,
+        FlashLoanUsage::WITHDRAW_COLLATERAL
+            | FlashLoanUsage::DEPOSIT_LIQUIDITY
+            | FlashLoanUsage::DEPOSIT_COLLATERAL,
+    )?;
+
+    ctx.accounts
+        .config
+        .validate_account_count(ctx.remaining_accounts.len())?;
+    SwapExecutor::validate_program(
+        &ctx.accounts.swap_program.key(),
+        &ctx.accounts.fee_converter.swap_programs,
+    )?;
+
+    // Check if market allows withdrawals and deposits
+    if market.is_paused() || market.is_lending_disabled() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    // Reserves are PDAs of their liquidity mint, so only one reserve holds a given asset
+    let net_swap = ctx.accounts.withdraw_reserve.key() == ctx.accounts.deposit_reserve.key();
+
+    let withdraw_reserve = &mut ctx.accounts.withdraw_reserve;
+    let deposit_reserve = &mut ctx.accounts.deposit_reserve;
+
+    // Withdrawals-disabled is checked with the rest of the redemption limits
+    if deposit_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::DEPOSITS_DISABLED)
+        || !deposit_reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::COLLATERAL_ENABLED)
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    if params.collateral_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    // The final health check relies on values from a refresh in this slot
+    if obligation.has_borrows() {
+        obligation.require_refreshed(clock.slot)?;
+    }
+
+    // Hold the reentrancy locks until the reserve state is written back
+    let mut withdraw_reserve = ReserveLock::acquire(withdraw_reserve)?;
+    let mut deposit_reserve = ReserveLock::acquire(deposit_reserve)?;
+
+    withdraw_reserve.update_interest(clock.slot)?;
+    deposit_reserve.update_interest(clock.slot)?;
+
+    let deposited_collateral = obligation
+        .find_collateral_deposit(&withdraw_reserve.key())
+        .ok_or(LendingError::ObligationReserveNotFound)?
+        .deposited_amount;
+    if deposited_collateral < params.collateral_amount {
+        return Err(LendingError::InsufficientCollateral.into());
+    }
+
+    let withdraw_price = OracleManager::get_checked_price(
+        &ctx.accounts.withdraw_price_oracle.to_account_info(),
+        ctx.accounts.withdraw_secondary_price_oracle.as_deref(),
+        &withdraw_reserve,
+    )?;
+    withdraw_price.validate(clock.unix_timestamp)?;
+
+    let deposit_price = OracleManager::get_checked_price(
+        &ctx.accounts.deposit_price_oracle.to_account_info(),
+        ctx.accounts.deposit_secondary_price_oracle.as_deref(),
+        &deposit_reserve,
+    )?;
+    deposit_price.validate(clock.unix_timestamp)?;
+
+    // Step 1: redeem the collateral straight out of the reserve into the owner's account,
+    // under the same withdrawal limits and surge fee as `redeem_reserve_collateral`
+    let redemption = withdraw_reserve.quote_redemption(
+        &ctx.accounts.config,
+        params.collateral_amount,
+        clock.slot,
+    )?;
+    let redeemed_liquidity = redemption.withdrawn_amount;
+    if ctx.accounts.config.is_large_withdrawal(
+        redemption.liquidity_amount,
+        withdraw_reserve.state.total_liquidity,
+    )? {
+        return Err(LendingError::LargeWithdrawalRequestRequired.into());
+    }
+
+    let withdraw_collateral_authority_seeds = &[
+        COLLATERAL_TOKEN_SEED,
+        withdraw_reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.withdraw_collateral_supply_authority],
+    ];
+    TokenUtils::burn_tokens(
+        &ctx.accounts.withdraw_token_program,
+        &ctx.accounts.withdraw_collateral_mint,
+        &ctx.accounts.withdraw_reserve_collateral_supply,
+        &ctx.accounts
+            .withdraw_collateral_supply_authority
+            .to_account_info(),
+        &[withdraw_collateral_authority_seeds],
+        params.collateral_amount,
+    )?;
+
+    let liquidity_authority_seeds = &[
+        LIQUIDITY_TOKEN_SEED,
+        withdraw_reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.withdraw_liquidity_supply_authority],
+    ];
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.withdraw_token_program,
+        &ctx.accounts.withdraw_reserve_liquidity_supply,
+        &ctx.accounts.withdrawn_liquidity,
+        &ctx.accounts.withdraw_liquidity_mint,
+        &ctx.accounts
+            .withdraw_liquidity_supply_authority
+            .to_account_info(),
+        &[liquidity_authority_seeds],
+        redeemed_liquidity,
+    )?;
+
+    withdraw_reserve.settle_redemption(&redemption)?;
+    sync_same_reserve(&withdraw_reserve, &mut deposit_reserve);
+
+    let withdrawn_value_usd =
+        withdraw_reserve.collateral_value_usd(params.collateral_amount, &withdraw_price)?;
+    obligation.remove_collateral_deposit(&withdraw_reserve.key(), params.collateral_amount)?;
+    obligation.deposited_value_usd = obligation
+        .deposited_value_usd
+        .try_sub(withdrawn_value_usd)?;
+
+    // Isolation rules apply to the collateral left once the old deposit is gone
+    let isolated = deposit_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::ISOLATED);
+    obligation.validate_collateral_isolation(&deposit_reserve.key(), isolated)?;
+
+    // Step 2: swap the redeemed liquidity into the new collateral asset; liquidity
+    // already in that asset is deposited as is
+    let received_liquidity = TokenUtils::amount_after_transfer_fee(
+        &ctx.accounts.withdraw_liquidity_mint,
+        redeemed_liquidity,
+    )?;
+    let swap = if net_swap {
+        SwapExecutor::net(
+            ctx.remaining_accounts,
+            received_liquidity,
+            received_liquidity,
+            params.min_amount_out,
+        )?
+    } else {
+        let oracle_min_amount_out = SwapExecutor::oracle_min_amount_out(
+            received_liquidity,
+            &withdraw_price,
+            withdraw_reserve.config.decimals,
+            &deposit_price,
+            deposit_reserve.config.decimals,
+            params.max_slippage_bps as u64,
+        )?;
+        SwapExecutor::execute(
+            &ctx.accounts.swap_program.to_account_info(),
+            ctx.remaining_accounts,
+            params.route_data,
+            None,
+            &[],
+            &mut ctx.accounts.withdrawn_liquidity,
+            &mut ctx.accounts.deposit_liquidity,
+            received_liquidity,
+            params.min_amount_out.max(oracle_min_amount_out),
+        )?
+    };
+    let deposit_source = if net_swap {
+        &ctx.accounts.withdrawn_liquidity
+    } else {
+        &ctx.accounts.deposit_liquidity
+    };
+
+    // Step 3: deposit the swap output, minting the aTokens straight into the reserve's
+    // collateral supply on the obligation's behalf
+    let deposited_liquidity = TokenUtils::amount_after_transfer_fee(
+        &ctx.accounts.deposit_liquidity_mint,
+        swap.amount_out,
+    )?;
+    deposit_reserve.validate_supply_cap(deposited_liquidity)?;
+    let collateral_amount = deposit_reserve.liquidity_to_collateral(deposited_liquidity)?;
+    if collateral_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.deposit_token_program,
+        deposit_source,
+        &ctx.accounts.deposit_reserve_liquidity_supply,
+        &ctx.accounts.deposit_liquidity_mint,
+        &ctx.accounts.obligation_owner.to_account_info(),
+        &[],
+        swap.amount_out,
+    )?;
+
+    let deposit_collateral_authority_seeds = &[
+        COLLATERAL_TOKEN_SEED,
+        deposit_reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.deposit_collateral_authority],
+    ];
+    TokenUtils::mint_tokens(
+        &ctx.accounts.deposit_token_program,
+        &ctx.accounts.deposit_collateral_mint,
+        &ctx.accounts.deposit_reserve_collateral_supply,
+        &ctx.accounts.deposit_collateral_authority.to_account_info(),
+        &[deposit_collateral_authority_seeds],
+        collateral_amount,
+    )?;
+
+    deposit_reserve.add_liquidity(deposited_liquidity)?;
+    deposit_reserve.state.collateral_mint_supply = deposit_reserve
+        .state
+        .collateral_mint_supply
+        .checked_add(collateral_amount)
+        .ok_or(LendingError::MathOverflow)?;
+    sync_same_reserve(&deposit_reserve, &mut withdraw_reserve);
+
+    let deposited_value_usd =
+        deposit_reserve.collateral_value_usd(collateral_amount, &deposit_price)?;
+    obligation.add_collateral_deposit(ObligationCollateral {
+        deposit_reserve: deposit_reserve.key(),
+        deposited_amount: collateral_amount,
+        market_value_usd: deposited_value_usd,
+        ltv_bps: deposit_reserve.effective_loan_to_value_ratio_bps(clock.unix_timestamp as u64),
+        liquidation_threshold_bps: deposit_reserve.config.liquidation_threshold_bps,
+    })?;
+    if isolated {
+        obligation.isolation_mode = true;
+    }
+    obligation.deposited_value_usd = obligation
+        .deposited_value_usd
+        .try_add(deposited_value_usd)?;
+    obligation.update_timestamp(clock.slot)?;
+
+    // The rotated position must be healthy, with eMode parameters if they still apply
+    let emode = EModeCategory::resolve(ctx.accounts.emode_category.as_deref(), &obligation, None)?;
+    if obligation.has_borrows() && !obligation.is_healthy_with(emode.as_ref())? {
+        return Err(LendingError::ObligationUnhealthy.into());
+    }
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    let health_factor = obligation.calculate_health_factor_with(emode.as_ref())?;
+    msg!(
+        "Swapped {} collateral tokens into {} - swapped {} into {}, health {} bps",
+        params.collateral_amount,
+        collateral_amount,
+        swap.amount_in,
+        swap.amount_out,
+        health_factor.to_health_bps_saturating()
+    );
+
+    let withdraw_receipt = OperationReceipt::new(
+        withdraw_reserve.key(),
+        &withdraw_reserve,
+        redeemed_liquidity,
+        params.collateral_amount,
+        redemption.surge_fee,
+        Some(health_factor),
+    )?;
+    emit!(CollateralWithdrawEvent {
+        obligation: ctx.accounts.obligation.key(),
+        owner: ctx.accounts.obligation_owner.key(),
+        receipt: withdraw_receipt,
+        slot: clock.slot,
+    });
+
+    let deposit_receipt = OperationReceipt::new(
+        deposit_reserve.key(),
+        &deposit_reserve,
+        deposited_liquidity,
+        collateral_amount,
+        0,
+        Some(health_factor),
+    )?;
+    emit!(CollateralDepositEvent {
+        obligation: ctx.accounts.obligation.key(),
+        owner: ctx.accounts.obligation_owner.key(),
+        receipt: deposit_receipt,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
+
+/// Refinance an obligation's debt from one reserve into another
+/// Liquidity is borrowed from the new reserve flash-loan style, swapped through a
+/// whitelisted DEX into the old debt's asset and repaid, and only then is the new
+/// borrow checked against the obligation's LTV with the buffer
+/// `borrow_obligation_liquidity` applies. The swap output is bounded as in
+/// `repay_with_collateral`; output beyond the old debt stays in the owner's repay
+/// account. Remaining accounts are the swap route; refinancing within one asset is
+/// netted without one.
+pub fn swap_debt<'info>(
+    ctx: Context<'_, '_, '_, 'info, SwapDebt<'info>>,
+    params: SwapDebtParams,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let mut obligation = AnyObligation::load_owned(
+        &ctx.accounts.obligation,
+        &market.key(),
+        &ctx.accounts.obligation_owner.key(),
+    )?;
+    let clock = Clock::get()?;
+
+    // Restrict what flash-borrowed funds may be routed into
+    FlashLoanGuard::validate_entrypoint(
+        &ctx.accounts.flash_loan_guard,
+        FlashLoanUsage::BORROW | FlashLoanUsage::REPAY,
+    )?;
+
+    ctx.accounts
+        .config
+        .validate_account_count(ctx.remaining_accounts.len())?;
+    SwapExecutor::validate_program(
+        &ctx.accounts.swap_program.key(),
+        &ctx.accounts.fee_converter.swap_programs,
+    )?;
+
+    // Check if market allows borrowing
+    if market.is_paused() || market.is_borrowing_disabled() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    // Reserves are PDAs of their liquidity mint, so only one reserve holds a given asset
+    let net_swap = ctx.accounts.borrow_reserve.key() == ctx.accounts.repay_reserve.key();
+
+    let borrow_reserve = &mut ctx.accounts.borrow_reserve;
+    let repay_reserve = &mut ctx.accounts.repay_reserve;
+
+    // A deprecated reserve takes no new debt
+    if borrow_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::BORROWING_DISABLED)
+        || borrow_reserve.is_deprecated()
+        || repay_reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::REPAYMENTS_DISABLED)
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    if params.borrow_amount < MIN_BORROW_AMOUNT {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    // The final LTV check relies on values from a refresh in this slot
+    obligation.require_refreshed(clock.slot)?;
+
+    // Isolated collateral only backs governance-approved debt reserves
+    if obligation.isolation_mode
+        && !borrow_reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::BORROWABLE_IN_ISOLATION)
+    {
+        return Err(LendingError::BorrowNotAllowedInIsolation.into());
+    }
+
+    // Hold the reentrancy locks until the reserve state is written back
+    let mut borrow_reserve = ReserveLock::acquire(borrow_reserve)?;
+    let mut repay_reserve = ReserveLock::acquire(repay_reserve)?;
+
+    borrow_reserve.update_interest(clock.slot)?;
+    repay_reserve.update_interest(clock.slot)?;
+    obligation.accrue_borrow_interest(
+        &repay_reserve.key(),
+        repay_reserve.cumulative_borrow_rate_wads(),
+    )?;
+
+    let borrowed_amount = obligation
+        .find_liquidity_borrow(&repay_reserve.key())
+        .ok_or(LendingError::ObligationReserveNotFound)?
+        .borrowed_amount_wads
+        .try_floor_u64()?;
+
+    if borrow_reserve.state.available_liquidity < params.borrow_amount {
+        return Err(LendingError::InsufficientLiquidity.into());
+    }
+    borrow_reserve.validate_borrow_cap(params.borrow_amount)?;
+
+    let borrow_price = OracleManager::get_checked_price(
+        &ctx.accounts.borrow_price_oracle.to_account_info(),
+        ctx.accounts.borrow_secondary_price_oracle.as_deref(),
+        &borrow_reserve,
+    )?;
+    borrow_price.validate(clock.unix_timestamp)?;
+
+    let repay_price = OracleManager::get_checked_price(
+        &ctx.accounts.repay_price_oracle.to_account_info(),
+        ctx.accounts.repay_secondary_price_oracle.as_deref(),
+        &repay_reserve,
+    )?;
+    repay_price.validate(clock.unix_timestamp)?;
+
+    // The origination fee is deducted from the borrowed liquidity; the debt is the full amount
+    let borrow_fee = borrow_reserve.borrow_fee(params.borrow_amount)?;
+    let (treasury_fee, insurance_fee) = borrow_reserve.split_insurance_fee(borrow_fee)?;
+    let received_amount = params
+        .borrow_amount
+        .checked_sub(borrow_fee)
+        .ok_or(LendingError::MathUnderflow)?;
+
+    // Step 1: borrow the new debt into the owner's account before it is checked
+    let liquidity_authority_seeds = &[
+        LIQUIDITY_TOKEN_SEED,
+        borrow_reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.borrow_liquidity_supply_authority],
+    ];
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.borrow_reserve_liquidity_supply,
+        &ctx.accounts.borrowed_liquidity,
+        &ctx.accounts.borrow_liquidity_mint,
+        &ctx.accounts
+            .borrow_liquidity_supply_authority
+            .to_account_info(),
+        &[liquidity_authority_seeds],
+        received_amount,
+    )?;
+
+    if treasury_fee > 0 {
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.borrow_reserve_liquidity_supply,
+            &ctx.accounts.fee_receiver,
+            &ctx.accounts.borrow_liquidity_mint,
+            &ctx.accounts
+                .borrow_liquidity_supply_authority
+                .to_account_info(),
+            &[liquidity_authority_seeds],
+            treasury_fee,
+        )?;
+    }
+
+    if insurance_fee > 0 {
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.borrow_reserve_liquidity_supply,
+            &ctx.accounts.insurance_fund_vault,
+            &ctx.accounts.borrow_liquidity_mint,
+            &ctx.accounts
+                .borrow_liquidity_supply_authority
+                .to_account_info(),
+            &[liquidity_authority_seeds],
+            insurance_fee,
+        )?;
+        ctx.accounts.insurance_fund.record_deposit(insurance_fee)?;
+    }
+
+    borrow_reserve.add_borrow(params.borrow_amount)?;
+    sync_same_reserve(&borrow_reserve, &mut repay_reserve);
+
+    // Step 2: swap the borrowed liquidity into the old debt's asset; liquidity already
+    // in that asset repays directly
+    let swapped_liquidity = TokenUtils::amount_after_transfer_fee(
+        &ctx.accounts.borrow_liquidity_mint,
+        received_amount,
+    )?;
+    let swap = if net_swap {
+        SwapExecutor::net(
+            ctx.remaining_accounts,
+            swapped_liquidity,
+            swapped_liquidity,
+            params.min_amount_out,
+        )?
+    } else {
+        let oracle_min_amount_out = SwapExecutor::oracle_min_amount_out(
+            swapped_liquidity,
+            &borrow_price,
+            borrow_reserve.config.decimals,
+            &repay_price,
+            repay_reserve.config.decimals,
+            params.max_slippage_bps as u64,
+        )?;
+        SwapExecutor::execute(
+            &ctx.accounts.swap_program.to_account_info(),
+            ctx.remaining_accounts,
+            params.route_data,
+            None,
+            &[],
+            &mut ctx.accounts.borrowed_liquidity,
+            &mut ctx.accounts.repay_liquidity,
+            swapped_liquidity,
+            params.min_amount_out.max(oracle_min_amount_out),
+        )?
+    };
+    let repay_source = if net_swap {
+        &ctx.accounts.borrowed_liquidity
+    } else {
+        &ctx.accounts.repay_liquidity
+    };
+
+    // Step 3: repay up to the whole old debt with the swap output
+    let repay_amount = std::cmp::min(
+        borrowed_amount,
+        TokenUtils::amount_after_transfer_fee(&ctx.accounts.repay_liquidity_mint, swap.amount_out)?,
+    );
+    if repay_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    // Grossed up so that the reserve receives the full repayment after transfer fees
+    let transfer_amount =
+        TokenUtils::amount_before_transfer_fee(&ctx.accounts.repay_liquidity_mint, repay_amount)?;
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.repay_token_program,
+        repay_source,
+        &ctx.accounts.repay_reserve_liquidity_supply,
+        &ctx.accounts.repay_liquidity_mint,
+        &ctx.accounts.obligation_owner.to_account_info(),
+        &[],
+        transfer_amount,
+    )?;
+
+    repay_reserve.repay_borrow(repay_amount)?;
+    sync_same_reserve(&repay_reserve, &mut borrow_reserve);
+    obligation
+        .repay_liquidity_borrow(&repay_reserve.key(), Decimal::from_integer(repay_amount)?)?;
+    let repay_value_usd = OracleManager::calculate_usd_value(
+        repay_amount,
+        &repay_price,
+        repay_reserve.config.decimals,
+    )?;
+    obligation.borrowed_value_usd = obligation.borrowed_value_usd.try_sub(repay_value_usd)?;
+
+    // Step 4: check the new borrow against the obligation with the old debt repaid
+    let borrow_value_usd = OracleManager::calculate_usd_value(
+        params.borrow_amount,
+        &borrow_price,
+        borrow_reserve.config.decimals,
+    )?;
+    let emode = EModeCategory::resolve(
+        ctx.accounts.emode_category.as_deref(),
+        &obligation,
+        Some(&borrow_reserve.key()),
+    )?;
+    let new_borrowed_value =
+        validate_borrow_capacity(&obligation, borrow_value_usd, emode.as_ref())?;
+
+    let liquidity_borrow = ObligationLiquidity {
+        borrow_reserve: borrow_reserve.key(),
+        borrowed_amount_wads: Decimal::from_integer(params.borrow_amount)?,
+        market_value_usd: borrow_value_usd,
+        origination_utilization_rate: borrow_reserve.state.utilization_rate()?,
+        origination_borrow_rate: borrow_reserve.state.borrow_rate()?,
+        origination_slot: clock.slot,
+        cumulative_borrow_rate_wads: borrow_reserve.cumulative_borrow_rate_wads(),
+    };
+    obligation.add_liquidity_borrow(liquidity_borrow.clone())?;
+    obligation.borrowed_value_usd = new_borrowed_value;
+    obligation.record_debt_watermark()?;
+    obligation.update_timestamp(clock.slot)?;
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    let health_factor = obligation.calculate_health_factor_with(emode.as_ref())?;
+    msg!(
+        "Refinanced {} of debt with {} borrowed (fee {}) - swapped {} into {}, health {} bps",
+        repay_amount,
+        params.borrow_amount,
+        borrow_fee,
+        swap.amount_in,
+        swap.amount_out,
+        health_factor.to_health_bps_saturating()
+    );
+
+    let borrow_receipt = OperationReceipt::new(
+        borrow_reserve.key(),
+        &borrow_reserve,
+        params.borrow_amount,
+        0,
+        borrow_fee,
+        Some(health_factor),
+    )?;
+    emit!(BorrowEvent {
+        obligation: ctx.accounts.obligation.key(),
+        borrower: ctx.accounts.obligation_owner.key(),
+        receipt: borrow_receipt,
+        origination_utilization_rate: liquidity_borrow.origination_utilization_rate.value,
+        origination_borrow_rate: liquidity_borrow.origination_borrow_rate.value,
+        slot: clock.slot,
+    });
+
+    let repay_receipt = OperationReceipt::new(
+        repay_reserve.key(),
+        &repay_reserve,
+        repay_amount,
+        0,
+        0,
+        Some(health_factor),
+    )?;
+    emit!(RepayEvent {
+        obligation: ctx.accounts.obligation.key(),
+        payer: ctx.accounts.obligation_owner.key(),
+        receipt: repay_receipt,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
+
+/// Carry `from`'s state over to `to` when both are the same reserve
+/// A same-asset pair passes one reserve account on both sides, deserialized into two
+/// copies. Each side's changes must be visible to the other, and whichever copy is
+/// written back last must hold all of them.
+fn sync_same_reserve(from: &Account<Reserve>, to: &mut Account<Reserve>) {
+    if from.key() == to.key() {
+        to.set_inner(Reserve::clone(from));
+    }
+}
+
+/// Parameters for repaying debt with an obligation's own collateral
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RepayWithCollateralParams {
+    /// aTokens of collateral to redeem and swap
+    pub collateral_amount: u64,
+    /// Minimum repay asset the swap must return
+    pub min_amount_out: u64,
+    /// Largest shortfall of the swap output below its oracle value (basis points)
+    pub max_slippage_bps: u16,
+    /// Instruction data of the swap, passed through to the DEX
+    pub route_data: Vec<u8>,
+}
+
+/// Parameters for levering an obligation up to a target leverage
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LoopPositionParams {
+    /// Target ratio of deposited value to equity (basis points, e.g. 30000 for 3x)
+    pub target_leverage_bps: u64,
+    /// Minimum collateral asset the swap must return
+    pub min_amount_out: u64,
+    /// Largest shortfall of the swap output below its oracle value (basis points)
+    pub max_slippage_bps: u16,
+    /// Instruction data of the swap, passed through to the DEX
+    pub route_data: Vec<u8>,
+}
+
+/// Parameters for rotating collateral between reserves
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SwapCollateralParams {
+    /// aTokens of the old collateral to redeem and swap
+    pub collateral_amount: u64,
+    /// Minimum new collateral asset the swap must return
+    pub min_amount_out: u64,
+    /// Largest shortfall of the swap output below its oracle value (basis points)
+    pub max_slippage_bps: u16,
+    /// Instruction data of the swap, passed through to the DEX
+    pub route_data: Vec<u8>,
+}
+
+/// Parameters for refinancing debt between reserves
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SwapDebtParams {
+    /// Liquidity to borrow from the new reserve, including the origination fee
+    pub borrow_amount: u64,
+    /// Minimum old debt asset the swap must return
+    pub min_amount_out: u64,
+    /// Largest shortfall of the swap output below its oracle value (basis points)
+    pub max_slippage_bps: u16,
+    /// Instruction data of the swap, passed through to the DEX
+    pub route_data: Vec<u8>,
+}
+
+// Context structs for position swap instructions
+
+#[derive(Accounts)]
+pub struct RepayWithCollateral<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol configuration (remaining account cap, large withdrawal threshold)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Fee converter holding the DEX program whitelist
+    #[account(
+        seeds = [FEE_CONVERTER_SEED, market.key().as_ref()],
+        bump = fee_converter.bump
+    )]
+    pub fee_converter: Account<'info, FeeConverter>,
+
+    /// Obligation account (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Reserve of the collateral being redeemed
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, withdraw_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub withdraw_reserve: Account<'info, Reserve>,
+
+    /// Reserve of the debt being repaid
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, repay_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub repay_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for the collateral asset
+    /// CHECK: Matched against the withdraw reserve's price_oracle
+    #[account(address = withdraw_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub withdraw_price_oracle: UncheckedAccount<'info>,
+
+    /// Price oracle for the repay asset
+    /// CHECK: Matched against the repay reserve's price_oracle
+    #[account(address = repay_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub repay_price_oracle: UncheckedAccount<'info>,
+
+    /// Secondary oracle of the collateral asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub withdraw_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Secondary oracle of the repay asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub repay_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Withdraw reserve's collateral mint (burned on redemption)
+    #[account(
+        mut,
+        address = withdraw_reserve.collateral_mint @ LendingError::ReserveCollateralMintMismatch
+    )]
+    pub withdraw_collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Withdraw reserve's collateral token account
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.collateral_mint,
+        token::authority = withdraw_collateral_supply_authority
+    )]
+    pub withdraw_reserve_collateral_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Withdraw collateral supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [COLLATERAL_TOKEN_SEED, withdraw_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub withdraw_collateral_supply_authority: UncheckedAccount<'info>,
+
+    /// Withdraw reserve's liquidity mint
+    #[account(address = withdraw_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub withdraw_liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Withdraw reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = withdraw_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub withdraw_reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Withdraw reserve liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, withdraw_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub withdraw_liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Owner's account receiving the redeemed liquidity, spent by the swap
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.liquidity_mint,
+        token::authority = obligation_owner
+    )]
+    pub withdrawn_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Owner's repay asset account receiving the swap output
+    #[account(
+        mut,
+        token::mint = repay_reserve.liquidity_mint,
+        token::authority = obligation_owner
+    )]
+    pub repay_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity token mint of the repay reserve
+    #[account(address = repay_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub repay_liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Repay reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = repay_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub repay_reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// DEX program the swap is routed through
+    /// CHECK: Checked against the fee converter whitelist
+    pub swap_program: UncheckedAccount<'info>,
+
+    /// Obligation owner
+    pub obligation_owner: Signer<'info>,
+
+    /// User's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, obligation_owner.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
+    /// Token program of the repay reserve's liquidity mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the withdraw reserve's mints
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct LoopPosition<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol configuration (remaining account cap)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Fee converter holding the DEX program whitelist
+    #[account(
+        seeds = [FEE_CONVERTER_SEED, market.key().as_ref()],
+        bump = fee_converter.bump
+    )]
+    pub fee_converter: Account<'info, FeeConverter>,
+
+    /// Obligation account (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Reserve being borrowed from
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, borrow_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub borrow_reserve: Account<'info, Reserve>,
+
+    /// Reserve the swap output is deposited into as collateral
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, deposit_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub deposit_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for the borrowed asset
+    /// CHECK: Matched against the borrow reserve's price_oracle
+    #[account(address = borrow_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub borrow_price_oracle: UncheckedAccount<'info>,
+
+    /// Price oracle for the collateral asset
+    /// CHECK: Matched against the deposit reserve's price_oracle
+    #[account(address = deposit_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub deposit_price_oracle: UncheckedAccount<'info>,
+
+    /// Secondary oracle of the borrowed asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub borrow_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Secondary oracle of the collateral asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub deposit_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Liquidity token mint of the borrow reserve
+    #[account(address = borrow_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub borrow_liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Borrow reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = borrow_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub borrow_reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Borrow reserve liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, borrow_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub borrow_liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Borrow reserve's fee receiver (treasury share of the borrow fee)
+    #[account(
+        mut,
+        address = borrow_reserve.fee_receiver @ LendingError::InvalidAccount
+    )]
+    pub fee_receiver: InterfaceAccount<'info, TokenAccount>,
+
+    /// Borrow reserve's insurance fund
+    #[account(
+        mut,
+        seeds = [INSURANCE_FUND_SEED, borrow_reserve.key().as_ref()],
+        bump = insurance_fund.bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    /// Insurance fund vault (insurance share of the borrow fee)
+    #[account(
+        mut,
+        address = insurance_fund.liquidity_vault @ LendingError::InvalidAccount
+    )]
+    pub insurance_fund_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Owner's account receiving the borrowed liquidity, spent by the swap
+    #[account(
+        mut,
+        token::mint = borrow_reserve.liquidity_mint,
+        token::authority = obligation_owner
+    )]
+    pub borrowed_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Owner's collateral asset account receiving the swap output
+    #[account(
+        mut,
+        token::mint = deposit_reserve.liquidity_mint,
+        token::authority = obligation_owner
+    )]
+    pub deposit_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity token mint of the deposit reserve
+    #[account(address = deposit_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub deposit_liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Deposit reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = deposit_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub deposit_reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Deposit reserve's collateral mint (aToken mint)
+    #[account(
+        mut,
+        address = deposit_reserve.collateral_mint @ LendingError::ReserveCollateralMintMismatch
+    )]
+    pub deposit_collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Deposit reserve's collateral token account holding obligation collateral
+    #[account(
+        mut,
+        token::mint = deposit_reserve.collateral_mint,
+        token::authority = deposit_collateral_authority
+    )]
+    pub deposit_reserve_collateral_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Deposit reserve collateral mint and supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [COLLATERAL_TOKEN_SEED, deposit_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub deposit_collateral_authority: UncheckedAccount<'info>,
+
+    /// DEX program the swap is routed through
+    /// CHECK: Checked against the fee converter whitelist
+    pub swap_program: UncheckedAccount<'info>,
+
+    /// Obligation owner
+    pub obligation_owner: Signer<'info>,
+
+    /// User's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, obligation_owner.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
+    /// Token program of the borrow reserve's liquidity mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the deposit reserve's mints
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SwapCollateral<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol configuration (remaining account cap, large withdrawal threshold)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Fee converter holding the DEX program whitelist
+    #[account(
+        seeds = [FEE_CONVERTER_SEED, market.key().as_ref()],
+        bump = fee_converter.bump
+    )]
+    pub fee_converter: Account<'info, FeeConverter>,
+
+    /// Obligation account (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Reserve of the collateral being rotated out
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, withdraw_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub withdraw_reserve: Account<'info, Reserve>,
+
+    /// Reserve the swap output is deposited into as collateral
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, deposit_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub deposit_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for the collateral asset
+    /// CHECK: Matched against the withdraw reserve's price_oracle
+    #[account(address = withdraw_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub withdraw_price_oracle: UncheckedAccount<'info>,
+
+    /// Price oracle for the new collateral asset
+    /// CHECK: Matched against the deposit reserve's price_oracle
+    #[account(address = deposit_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub deposit_price_oracle: UncheckedAccount<'info>,
+
+    /// Secondary oracle of the collateral asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub withdraw_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Secondary oracle of the new collateral asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub deposit_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Withdraw reserve's collateral mint (burned on redemption)
+    #[account(
+        mut,
+        address = withdraw_reserve.collateral_mint @ LendingError::ReserveCollateralMintMismatch
+    )]
+    pub withdraw_collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Withdraw reserve's collateral token account
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.collateral_mint,
+        token::authority = withdraw_collateral_supply_authority
+    )]
+    pub withdraw_reserve_collateral_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Withdraw collateral supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [COLLATERAL_TOKEN_SEED, withdraw_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub withdraw_collateral_supply_authority: UncheckedAccount<'info>,
+
+    /// Withdraw reserve's liquidity mint
+    #[account(address = withdraw_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub withdraw_liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Withdraw reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = withdraw_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub withdraw_reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Withdraw reserve liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, withdraw_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub withdraw_liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Owner's account receiving the redeemed liquidity, spent by the swap
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.liquidity_mint,
+        token::authority = obligation_owner
+    )]
+    pub withdrawn_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Owner's new collateral asset account receiving the swap output
+    #[account(
+        mut,
+        token::mint = deposit_reserve.liquidity_mint,
+        token::authority = obligation_owner
+    )]
+    pub deposit_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity token mint of the deposit reserve
+    #[account(address = deposit_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub deposit_liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Deposit reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = deposit_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub deposit_reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Deposit reserve's collateral mint (aToken mint)
+    #[account(
+        mut,
+        address = deposit_reserve.collateral_mint @ LendingError::ReserveCollateralMintMismatch
+    )]
+    pub deposit_collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Deposit reserve's collateral token account holding obligation collateral
+    #[account(
+        mut,
+        token::mint = deposit_reserve.collateral_mint,
+        token::authority = deposit_collateral_authority
+    )]
+    pub deposit_reserve_collateral_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Deposit reserve collateral mint and supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [COLLATERAL_TOKEN_SEED, deposit_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub deposit_collateral_authority: UncheckedAccount<'info>,
+
+    /// DEX program the swap is routed through
+    /// CHECK: Checked against the fee converter whitelist
+    pub swap_program: UncheckedAccount<'info>,
+
+    /// Obligation owner
+    pub obligation_owner: Signer<'info>,
+
+    /// User's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, obligation_owner.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
+    /// Token program of the withdraw reserve's mints
+    pub withdraw_token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the deposit reserve's mints
+    pub deposit_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SwapDebt<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol configuration (remaining account cap)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Fee converter holding the DEX program whitelist
+    #[account(
+        seeds = [FEE_CONVERTER_SEED, market.key().as_ref()],
+        bump = fee_converter.bump
+    )]
+    pub fee_converter: Account<'info, FeeConverter>,
+
+    /// Obligation account (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Reserve the new debt is borrowed from
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, borrow_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub borrow_reserve: Account<'info, Reserve>,
+
+    /// Reserve of the debt being refinanced
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, repay_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub repay_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for the borrowed asset
+    /// CHECK: Matched against the borrow reserve's price_oracle
+    #[account(address = borrow_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub borrow_price_oracle: UncheckedAccount<'info>,
+
+    /// Price oracle for the repay asset
+    /// CHECK: Matched against the repay reserve's price_oracle
+    #[account(address = repay_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub repay_price_oracle: UncheckedAccount<'info>,
+
+    /// Secondary oracle of the borrowed asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub borrow_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Secondary oracle of the repay asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub repay_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Liquidity token mint of the borrow reserve
+    #[account(address = borrow_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub borrow_liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Borrow reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = borrow_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub borrow_reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Borrow reserve liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, borrow_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub borrow_liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Borrow reserve's fee receiver (treasury share of the borrow fee)
+    #[account(
+        mut,
+        address = borrow_reserve.fee_receiver @ LendingError::InvalidAccount
+    )]
+    pub fee_receiver: InterfaceAccount<'info, TokenAccount>,
+
+    /// Borrow reserve's insurance fund
+    #[account(
+        mut,
+        seeds = [INSURANCE_FUND_SEED, borrow_reserve.key().as_ref()],
+        bump = insurance_fund.bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    /// Insurance fund vault (insurance share of the borrow fee)
+    #[account(
+        mut,
+        address = insurance_fund.liquidity_vault @ LendingError::InvalidAccount
+    )]
+    pub insurance_fund_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Owner's account receiving the borrowed liquidity, spent by the swap
+    #[account(
+        mut,
+        token::mint = borrow_reserve.liquidity_mint,
+        token::authority = obligation_owner
+    )]
+    pub borrowed_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Owner's repay asset account receiving the swap output
+    #[account(
+        mut,
+        token::mint = repay_reserve.liquidity_mint,
+        token::authority = obligation_owner
+    )]
+    pub repay_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity token mint of the repay reserve
+    #[account(address = repay_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub repay_liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Repay reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = repay_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub repay_reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// DEX program the swap is routed through
+    /// CHECK: Checked against the fee converter whitelist
+    pub swap_program: UncheckedAccount<'info>,
+
+    /// Obligation owner
+    pub obligation_owner: Signer<'info>,
+
+    /// User's flash loan guard (PDA)
+    /// CHECK: May be uninitialized; validated by seeds and loaded in the instruction
+    #[account(
+        mut,
+        seeds = [FLASH_LOAN_GUARD_SEED, obligation_owner.key().as_ref()],
+        bump
+    )]
+    pub flash_loan_guard: UncheckedAccount<'info>,
+
+    /// Token program of the borrow reserve's liquidity mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the repay reserve's liquidity mint
+    pub repay_token_program: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,115 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Create the realized borrow-rate index of a reserve (permissionless)
+pub fn initialize_rate_index(ctx: Context<InitializeRateIndex>) -> Result<()> {
+    let reserve = &ctx.accounts.reserve;
+    let clock = Clock::get()?;
+
+    **ctx.accounts.rate_index = ReserveRateIndex::new(
+        reserve.key(),
+        reserve.state.borrow_rate()?.value,
+        clock.slot,
+        clock.epoch,
+        ctx.bumps.rate_index,
+    );
+
+    msg!(
+        "Rate index initialized for reserve {} at epoch {}",
+        reserve.key(),
+        clock.epoch
+    );
+    Ok(())
+}
+
+/// Sample the reserve borrow rate into its rate index (permissionless crank)
+/// The first sample in a new epoch finalizes the previous one
+pub fn update_rate_index(ctx: Context<UpdateRateIndex>) -> Result<()> {
+    let reserve = &mut ctx.accounts.reserve;
+    let rate_index = &mut ctx.accounts.rate_index;
+    let clock = Clock::get()?;
+    let epoch_schedule = EpochSchedule::get()?;
+
+    // Sample the rate implied by current utilization
+    reserve.update_interest(clock.slot)?;
+
+    let finalized = rate_index.update(
+        reserve.state.borrow_rate()?.value,
+        clock.slot,
+        clock.epoch,
+        epoch_schedule.get_last_slot_in_epoch(rate_index.current_epoch) + 1,
+        epoch_schedule.get_first_slot_in_epoch(clock.epoch),
+    )?;
+
+    if let Some(entry) = finalized {
+        msg!(
+            "Rate index finalized epoch {} - average borrow rate: {:.4}%, samples: {}",
+            entry.epoch,
+            entry.average_borrow_rate as f64 / 1e16,
+            entry.samples
+        );
+    }
+
+    Ok(())
+}
+
+// Context structs for rate index instructions
+
+#[derive(Accounts)]
+pub struct InitializeRateIndex<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve whose borrow rate is indexed
+    #[account(has_one = market @ LendingError::InvalidMarketState)]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Rate index account to initialize
+    #[account(
+        init,
+        payer = payer,
+        space = ReserveRateIndex::SIZE,
+        seeds = [RATE_INDEX_SEED, reserve.key().as_ref()],
+        bump
+    )]
+    pub rate_index: Account<'info, ReserveRateIndex>,
+
+    /// Payer for account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRateIndex<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve whose borrow rate is sampled
+    #[account(
+        mut,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Rate index of the reserve
+    #[account(
+        mut,
+        seeds = [RATE_INDEX_SEED, reserve.key().as_ref()],
+        bump = rate_index.bump,
+        has_one = reserve @ LendingError::InvalidAccount
+    )]
+    pub rate_index: Account<'info, ReserveRateIndex>,
+}
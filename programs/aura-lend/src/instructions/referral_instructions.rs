@@ -0,0 +1,153 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::events::ReferralFeesClaimedEvent;
+use crate::state::*;
+use crate::utils::TokenUtils;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Register the signer as a referrer of the market
+/// Deposits and borrows that pass the referrer account credit it with their volume,
+/// and borrows additionally with the configured share of the borrow fee.
+pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+    **ctx.accounts.referrer = Referrer::new(
+        ctx.accounts.market.key(),
+        ctx.accounts.owner.key(),
+        ctx.bumps.referrer,
+    );
+
+    msg!("Referrer registered: {}", ctx.accounts.owner.key());
+    Ok(())
+}
+
+/// Claim the referral fees accrued in a reserve
+/// The fees are held in the reserve's liquidity supply outside its available liquidity,
+/// so paying them out does not touch supplier funds.
+pub fn claim_referral_fees(ctx: Context<ClaimReferralFees>) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let reserve = &ctx.accounts.reserve;
+    let clock = Clock::get()?;
+
+    if market.is_paused() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    let amount = ctx.accounts.referrer.take_claimable(&reserve.key())?;
+
+    let authority_seeds = &[
+        LIQUIDITY_TOKEN_SEED,
+        reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.liquidity_supply_authority],
+    ];
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.source_liquidity,
+        &ctx.accounts.destination_liquidity,
+        &ctx.accounts.liquidity_mint,
+        &ctx.accounts.liquidity_supply_authority.to_account_info(),
+        &[authority_seeds],
+        amount,
+    )?;
+
+    emit!(ReferralFeesClaimedEvent {
+        referrer: ctx.accounts.referrer.key(),
+        reserve: reserve.key(),
+        amount,
+        slot: clock.slot,
+    });
+
+    msg!(
+        "Claimed {} referral fees from reserve {}",
+        amount,
+        reserve.key()
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Referrer account to create
+    #[account(
+        init,
+        payer = owner,
+        space = Referrer::SIZE,
+        seeds = [REFERRER_SEED, market.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub referrer: Account<'info, Referrer>,
+
+    /// Wallet registering as a referrer
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralFees<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve the fees accrued in
+    #[account(
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Referrer claiming its fees
+    #[account(
+        mut,
+        seeds = [REFERRER_SEED, market.key().as_ref(), owner.key().as_ref()],
+        bump = referrer.bump,
+        has_one = owner @ LendingError::InvalidAuthority
+    )]
+    pub referrer: Account<'info, Referrer>,
+
+    /// Liquidity token mint
+    #[account(address = reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub source_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Owner's destination liquidity token account
+    #[account(
+        mut,
+        token::mint = reserve.liquidity_mint
+    )]
+    pub destination_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Referrer owner
+    pub owner: Signer<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,137 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Consent to keepers closing the caller's empty accounts after a period of inactivity
+pub fn grant_rent_reclaim_consent(
+    ctx: Context<GrantRentReclaimConsent>,
+    params: GrantRentReclaimConsentParams,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    **ctx.accounts.consent = RentReclaimConsent::new(
+        ctx.accounts.owner.key(),
+        params.min_inactive_slots,
+        clock.slot,
+        ctx.bumps.consent,
+    )?;
+
+    msg!(
+        "Rent reclamation consent granted by {} after {} inactive slots",
+        ctx.accounts.owner.key(),
+        params.min_inactive_slots
+    );
+    Ok(())
+}
+
+/// Withdraw rent reclamation consent and close the consent record
+pub fn revoke_rent_reclaim_consent(ctx: Context<RevokeRentReclaimConsent>) -> Result<()> {
+    msg!("Rent reclamation consent revoked by {}", ctx.accounts.owner.key());
+    Ok(())
+}
+
+/// Close a consenting user's empty, dormant obligation (permissionless keeper sweep)
+/// The rent is returned to the obligation owner
+pub fn reclaim_dormant_obligation(ctx: Context<ReclaimDormantObligation>) -> Result<()> {
+    let obligation_info = ctx.accounts.obligation.to_account_info();
+    let owner_info = ctx.accounts.owner.to_account_info();
+    let consent = &mut ctx.accounts.consent;
+    let clock = Clock::get()?;
+
+    let obligation = AnyObligation::load_checked(&obligation_info, &ctx.accounts.market.key())?;
+
+    if obligation.owner != owner_info.key() {
+        return Err(LendingError::InvalidAuthority.into());
+    }
+    if !obligation.is_empty() {
+        return Err(LendingError::ObligationNotEmpty.into());
+    }
+    if !consent.is_dormant(obligation.last_update_slot, clock.slot) {
+        return Err(LendingError::AccountNotDormant.into());
+    }
+
+    // Close the account, returning every lamport to the owner
+    let lamports = AnyObligation::close(&obligation_info, &owner_info)?;
+
+    consent.record_reclaim();
+
+    msg!(
+        "Dormant obligation {} closed by keeper {}, {} lamports returned to {}",
+        obligation_info.key(),
+        ctx.accounts.keeper.key(),
+        lamports,
+        owner_info.key()
+    );
+    Ok(())
+}
+
+// Context structs for rent reclamation instructions
+
+#[derive(Accounts)]
+pub struct GrantRentReclaimConsent<'info> {
+    /// Consent record to initialize
+    #[account(
+        init,
+        payer = owner,
+        space = RentReclaimConsent::SIZE,
+        seeds = [RENT_RECLAIM_CONSENT_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub consent: Account<'info, RentReclaimConsent>,
+
+    /// User granting the consent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeRentReclaimConsent<'info> {
+    /// Consent record to close
+    #[account(
+        mut,
+        close = owner,
+        seeds = [RENT_RECLAIM_CONSENT_SEED, owner.key().as_ref()],
+        bump = consent.bump,
+        has_one = owner @ LendingError::InvalidAuthority
+    )]
+    pub consent: Account<'info, RentReclaimConsent>,
+
+    /// User who granted the consent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimDormantObligation<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Consent granted by the obligation owner
+    #[account(
+        mut,
+        seeds = [RENT_RECLAIM_CONSENT_SEED, owner.key().as_ref()],
+        bump = consent.bump,
+        has_one = owner @ LendingError::InvalidAuthority
+    )]
+    pub consent: Account<'info, RentReclaimConsent>,
+
+    /// Obligation account in any layout
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Obligation owner receiving the reclaimed rent
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    /// Keeper performing the sweep
+    pub keeper: Signer<'info>,
+}
@@ -0,0 +1,509 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::*;
+use crate::utils::{require_admin, TokenUtils};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Create a reward pool for one side of a reserve (governance only)
+/// The pool emits nothing until `fund_reward_pool` sets a schedule.
+pub fn initialize_reward_pool(ctx: Context<InitializeRewardPool>, side: RewardSide) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::InitializeRewardPool,
+        &(
+            ctx.accounts.reserve.key(),
+            side,
+            ctx.accounts.reward_mint.key(),
+        ),
+    )?;
+
+    let market = &ctx.accounts.market;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RESERVE_MANAGER,
+    )?;
+
+    **ctx.accounts.reward_pool = RewardPool::new(
+        market.key(),
+        ctx.accounts.reserve.key(),
+        side,
+        ctx.accounts.reward_mint.key(),
+        ctx.accounts.reward_vault.key(),
+        Clock::get()?.slot,
+        ctx.bumps.reward_pool,
+    );
+
+    msg!(
+        "Reward pool initialized for reserve {} ({:?})",
+        ctx.accounts.reserve.key(),
+        side
+    );
+    Ok(())
+}
+
+/// Fund a reward pool and replace its emission schedule (governance only)
+/// Emissions so far are accrued first, then `emission_per_slot` runs for
+/// `duration_slots` from the current slot. An emission of zero stops the pool.
+pub fn fund_reward_pool(
+    ctx: Context<FundRewardPool>,
+    amount: u64,
+    emission_per_slot: u64,
+    duration_slots: u64,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::FundRewardPool,
+        &(amount, emission_per_slot, duration_slots),
+    )?;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RESERVE_MANAGER,
+    )?;
+
+    let clock = Clock::get()?;
+    let reward_pool = &mut ctx.accounts.reward_pool;
+    reward_pool.update_index(clock.slot)?;
+
+    if amount > 0 {
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.source_rewards,
+            &ctx.accounts.reward_vault,
+            &ctx.accounts.reward_mint,
+            &ctx.accounts.authority.to_account_info(),
+            &[],
+            amount,
+        )?;
+        reward_pool.record_funding(amount)?;
+    }
+
+    reward_pool.set_schedule(emission_per_slot, duration_slots, clock.slot)?;
+
+    msg!(
+        "Reward pool funded with {}, emitting {} per slot until slot {}",
+        amount,
+        emission_per_slot,
+        reward_pool.end_slot
+    );
+    Ok(())
+}
+
+/// Opt an obligation into a reward pool
+/// Its current position starts earning from this slot.
+pub fn init_obligation_rewards(ctx: Context<InitObligationRewards>) -> Result<()> {
+    let mut obligation = AnyObligation::load_owned(
+        &ctx.accounts.obligation,
+        &ctx.accounts.reward_pool.market,
+        &ctx.accounts.obligation_owner.key(),
+    )?;
+    let reward_pool = &mut ctx.accounts.reward_pool;
+    reward_pool.update_index(Clock::get()?.slot)?;
+
+    **ctx.accounts.obligation_rewards = ObligationRewards::new(
+        reward_pool.key(),
+        ctx.accounts.obligation.key(),
+        obligation.owner,
+        reward_pool.reward_index,
+        ctx.bumps.obligation_rewards,
+    );
+
+    let position = reward_pool.position_of(&obligation)?;
+    reward_pool.sync(&mut ctx.accounts.obligation_rewards, position)?;
+
+    obligation.link_account()?;
+    obligation.save(&ctx.accounts.obligation)?;
+
+    msg!(
+        "Obligation {} opted into reward pool {} with position {}",
+        ctx.accounts.obligation.key(),
+        reward_pool.key(),
+        position
+    );
+    Ok(())
+}
+
+/// Settle an obligation's rewards and resync its position from the obligation
+/// Permissionless, so keepers can bring a position that changed without the reward
+/// accounts (e.g. through a liquidation) back in line with the obligation.
+pub fn sync_obligation_rewards(ctx: Context<SyncObligationRewards>) -> Result<()> {
+    let obligation =
+        AnyObligation::load_checked(&ctx.accounts.obligation, &ctx.accounts.reward_pool.market)?;
+
+    sync_rewards(
+        Some(&mut ctx.accounts.reward_pool),
+        Some(&mut ctx.accounts.obligation_rewards),
+        &ctx.accounts.obligation.key(),
+        &obligation,
+        Clock::get()?.slot,
+    )
+}
+
+/// Claim an obligation's accrued rewards from the pool's vault
+pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+    let obligation = AnyObligation::load_owned(
+        &ctx.accounts.obligation,
+        &ctx.accounts.reward_pool.market,
+        &ctx.accounts.obligation_owner.key(),
+    )?;
+
+    sync_rewards(
+        Some(&mut ctx.accounts.reward_pool),
+        Some(&mut ctx.accounts.obligation_rewards),
+        &ctx.accounts.obligation.key(),
+        &obligation,
+        Clock::get()?.slot,
+    )?;
+
+    let amount = ctx.accounts.obligation_rewards.take_accrued()?;
+    let reward_pool = &mut ctx.accounts.reward_pool;
+    reward_pool.record_claim(amount)?;
+
+    let side_seed = [reward_pool.side as u8];
+    let pool_seeds = &[
+        REWARD_POOL_SEED,
+        reward_pool.reserve.as_ref(),
+        &side_seed,
+        &[reward_pool.bump],
+    ];
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.reward_vault,
+        &ctx.accounts.destination_rewards,
+        &ctx.accounts.reward_mint,
+        &reward_pool.to_account_info(),
+        &[pool_seeds],
+        amount,
+    )?;
+
+    if let Some(user_stats) = ctx.accounts.user_stats.as_mut() {
+        user_stats.record_rewards(amount, Clock::get()?.slot)?;
+    }
+
+    msg!("Claimed {} reward tokens", amount);
+    Ok(())
+}
+
+/// Opt an obligation out of a reward pool and reclaim the participation's rent
+/// Accrued rewards must be claimed first, e.g. earlier in the same transaction. The
+/// position stops counting towards the pool.
+pub fn close_obligation_rewards(ctx: Context<CloseObligationRewards>) -> Result<()> {
+    let mut obligation = AnyObligation::load_owned(
+        &ctx.accounts.obligation,
+        &ctx.accounts.reward_pool.market,
+        &ctx.accounts.obligation_owner.key(),
+    )?;
+
+    sync_rewards(
+        Some(&mut ctx.accounts.reward_pool),
+        Some(&mut ctx.accounts.obligation_rewards),
+        &ctx.accounts.obligation.key(),
+        &obligation,
+        Clock::get()?.slot,
+    )?;
+    if ctx.accounts.obligation_rewards.accrued > 0 {
+        return Err(LendingError::RewardsUnclaimed.into());
+    }
+    ctx.accounts
+        .reward_pool
+        .sync(&mut ctx.accounts.obligation_rewards, 0)?;
+
+    obligation.unlink_account()?;
+    obligation.save(&ctx.accounts.obligation)?;
+
+    msg!(
+        "Obligation {} opted out of reward pool {}",
+        ctx.accounts.obligation.key(),
+        ctx.accounts.reward_pool.key()
+    );
+    Ok(())
+}
+
+/// Settle an obligation's rewards in a pool after an operation changed its position
+/// Operations take the pool and the obligation's participation as optional accounts;
+/// syncing either none or both. Any pool the obligation opted into may be synced, since
+/// the position is always read from the obligation. Operations that shrink a position
+/// also sync before the change, as a sync only credits the smaller position.
+pub fn sync_rewards(
+    reward_pool: Option<&mut Account<RewardPool>>,
+    obligation_rewards: Option<&mut Account<ObligationRewards>>,
+    obligation_key: &Pubkey,
+    obligation: &Obligation,
+    slot: u64,
+) -> Result<()> {
+    match (reward_pool, obligation_rewards) {
+        (None, None) => Ok(()),
+        (Some(reward_pool), Some(obligation_rewards)) => {
+            if obligation_rewards.pool != reward_pool.key()
+                || obligation_rewards.obligation != *obligation_key
+            {
+                return Err(LendingError::InvalidAccount.into());
+            }
+            reward_pool.update_index(slot)?;
+            let position = reward_pool.position_of(obligation)?;
+            reward_pool.sync(obligation_rewards, position)
+        }
+        _ => Err(LendingError::InvalidAccount.into()),
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(side: RewardSide)]
+pub struct InitializeRewardPool<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve whose positions will earn rewards
+    #[account(
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Mint of the reward token
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    /// Reward pool account to initialize
+    #[account(
+        init,
+        payer = payer,
+        space = RewardPool::SIZE,
+        seeds = [REWARD_POOL_SEED, reserve.key().as_ref(), &[side as u8]],
+        bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    /// Token account holding the pool's rewards
+    #[account(
+        init,
+        payer = payer,
+        token::mint = reward_mint,
+        token::authority = reward_pool,
+        seeds = [REWARD_POOL_SEED, reward_pool.key().as_ref(), b"vault"],
+        bump
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Payer for account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct FundRewardPool<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reward pool to fund
+    #[account(
+        mut,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = reward_mint @ LendingError::TokenMintMismatch,
+        has_one = reward_vault @ LendingError::InvalidAccount
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    /// Mint of the reward token
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    /// Pool's reward vault
+    #[account(mut)]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Authority's reward token account
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = authority
+    )]
+    pub source_rewards: InterfaceAccount<'info, TokenAccount>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct InitObligationRewards<'info> {
+    /// Reward pool to opt into
+    #[account(mut)]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    /// Obligation account (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Participation account to create
+    #[account(
+        init,
+        payer = obligation_owner,
+        space = ObligationRewards::SIZE,
+        seeds = [OBLIGATION_REWARDS_SEED, reward_pool.key().as_ref(), obligation.key().as_ref()],
+        bump
+    )]
+    pub obligation_rewards: Account<'info, ObligationRewards>,
+
+    /// Obligation owner
+    #[account(mut)]
+    pub obligation_owner: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SyncObligationRewards<'info> {
+    /// Reward pool the obligation opted into
+    #[account(mut)]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    /// Obligation account (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Obligation's participation in the pool
+    #[account(
+        mut,
+        seeds = [OBLIGATION_REWARDS_SEED, reward_pool.key().as_ref(), obligation.key().as_ref()],
+        bump = obligation_rewards.bump
+    )]
+    pub obligation_rewards: Account<'info, ObligationRewards>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    /// Reward pool the rewards were earned in
+    #[account(
+        mut,
+        has_one = reward_mint @ LendingError::TokenMintMismatch,
+        has_one = reward_vault @ LendingError::InvalidAccount
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    /// Obligation account (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Obligation's participation in the pool
+    #[account(
+        mut,
+        seeds = [OBLIGATION_REWARDS_SEED, reward_pool.key().as_ref(), obligation.key().as_ref()],
+        bump = obligation_rewards.bump
+    )]
+    pub obligation_rewards: Account<'info, ObligationRewards>,
+
+    /// Mint of the reward token
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    /// Pool's reward vault
+    #[account(mut)]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Owner's reward token account
+    #[account(
+        mut,
+        token::mint = reward_mint
+    )]
+    pub destination_rewards: InterfaceAccount<'info, TokenAccount>,
+
+    /// Obligation owner
+    pub obligation_owner: Signer<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Owner's statistics, updated when supplied
+    #[account(
+        mut,
+        seeds = [
+            USER_STATS_SEED,
+            reward_pool.market.as_ref(),
+            obligation_owner.key().as_ref()
+        ],
+        bump = user_stats.bump
+    )]
+    pub user_stats: Option<Account<'info, UserStats>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseObligationRewards<'info> {
+    /// Reward pool to opt out of
+    #[account(mut)]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    /// Obligation account (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Participation account to close
+    #[account(
+        mut,
+        close = obligation_owner,
+        seeds = [OBLIGATION_REWARDS_SEED, reward_pool.key().as_ref(), obligation.key().as_ref()],
+        bump = obligation_rewards.bump
+    )]
+    pub obligation_rewards: Account<'info, ObligationRewards>,
+
+    /// Obligation owner (receives the rent)
+    #[account(mut)]
+    pub obligation_owner: Signer<'info>,
+}
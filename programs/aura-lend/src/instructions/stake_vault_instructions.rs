@@ -0,0 +1,519 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::*;
+use crate::utils::{require_admin, ReserveLock, TokenUtils};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Create the stake vault of a reserve (governance only)
+/// Stakers must request an unstake `cooldown_seconds` before executing it.
+pub fn initialize_stake_vault(
+    ctx: Context<InitializeStakeVault>,
+    cooldown_seconds: i64,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::InitializeStakeVault,
+        &(ctx.accounts.reserve.key(), cooldown_seconds),
+    )?;
+
+    let market = &ctx.accounts.market;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::FEE_MANAGER,
+    )?;
+
+    **ctx.accounts.stake_vault = StakeVault::new(
+        market.key(),
+        ctx.accounts.reserve.key(),
+        ctx.accounts.vault_collateral.key(),
+        ctx.accounts.compound_liquidity.key(),
+        cooldown_seconds,
+        ctx.bumps.stake_vault,
+    )?;
+
+    msg!(
+        "Stake vault initialized for reserve {} with a {}s cooldown",
+        ctx.accounts.reserve.key(),
+        cooldown_seconds
+    );
+    Ok(())
+}
+
+/// Open the signer's position in a stake vault
+pub fn open_stake_position(ctx: Context<OpenStakePosition>) -> Result<()> {
+    **ctx.accounts.stake_position = StakePosition::new(
+        ctx.accounts.stake_vault.key(),
+        ctx.accounts.owner.key(),
+        ctx.bumps.stake_position,
+    );
+
+    msg!("Stake position opened for {}", ctx.accounts.owner.key());
+    Ok(())
+}
+
+/// Lock `collateral_amount` aTokens in the vault in exchange for shares
+pub fn stake(ctx: Context<Stake>, collateral_amount: u64) -> Result<()> {
+    if ctx.accounts.market.is_paused() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    let stake_vault = &mut ctx.accounts.stake_vault;
+    let shares = stake_vault.shares_for_collateral(collateral_amount)?;
+    if shares == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.source_collateral,
+        &ctx.accounts.vault_collateral,
+        &ctx.accounts.collateral_mint,
+        &ctx.accounts.owner.to_account_info(),
+        &[],
+        collateral_amount,
+    )?;
+
+    stake_vault.record_stake(collateral_amount, shares)?;
+    ctx.accounts.stake_position.add_shares(shares)?;
+
+    msg!("Staked {} aTokens for {} shares", collateral_amount, shares);
+    Ok(())
+}
+
+/// Start the cooldown for unstaking `shares`
+/// Shares pending unstake stop counting as active right away.
+pub fn request_unstake(ctx: Context<RequestUnstake>, shares: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let stake_position = &mut ctx.accounts.stake_position;
+    stake_position.request_unstake(
+        shares,
+        clock.unix_timestamp,
+        ctx.accounts.stake_vault.cooldown_seconds,
+    )?;
+
+    msg!(
+        "Unstake of {} shares requested, executable from {}",
+        stake_position.pending_unstake_shares,
+        stake_position.cooldown_end
+    );
+    Ok(())
+}
+
+/// Redeem the shares whose unstake cooldown has passed for their aTokens
+pub fn unstake(ctx: Context<Unstake>) -> Result<()> {
+    if ctx.accounts.market.is_paused() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    let shares = ctx
+        .accounts
+        .stake_position
+        .take_unstakeable(Clock::get()?.unix_timestamp)?;
+
+    let stake_vault = &mut ctx.accounts.stake_vault;
+    let collateral_amount = stake_vault.collateral_for_shares(shares)?;
+    stake_vault.record_unstake(collateral_amount, shares)?;
+
+    let vault_seeds = &[
+        STAKE_VAULT_SEED,
+        stake_vault.reserve.as_ref(),
+        &[stake_vault.bump],
+    ];
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.vault_collateral,
+        &ctx.accounts.destination_collateral,
+        &ctx.accounts.collateral_mint,
+        &stake_vault.to_account_info(),
+        &[vault_seeds],
+        collateral_amount,
+    )?;
+
+    msg!(
+        "Unstaked {} shares for {} aTokens",
+        shares,
+        collateral_amount
+    );
+    Ok(())
+}
+
+/// Deposit the liquidity collected in the vault's compound account into the reserve
+/// Permissionless. The minted aTokens are added to the vault, raising the value of
+/// every share.
+pub fn compound_stake_vault(ctx: Context<CompoundStakeVault>) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let reserve = &mut ctx.accounts.reserve;
+    let stake_vault = &mut ctx.accounts.stake_vault;
+    let clock = Clock::get()?;
+
+    if market.is_paused() || market.is_lending_disabled() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    if reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::DEPOSITS_DISABLED)
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    let liquidity_amount = ctx.accounts.compound_liquidity.amount;
+    if liquidity_amount < MIN_DEPOSIT_AMOUNT {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    // Hold the reentrancy lock until the reserve state is written back
+    let mut reserve = ReserveLock::acquire(reserve)?;
+
+    reserve.update_interest(clock.slot)?;
+
+    // Only what arrives after the mint's transfer fee is credited to the reserve
+    let received_liquidity =
+        TokenUtils::amount_after_transfer_fee(&ctx.accounts.liquidity_mint, liquidity_amount)?;
+    reserve.validate_supply_cap(received_liquidity)?;
+
+    let collateral_amount = reserve.liquidity_to_collateral(received_liquidity)?;
+    if collateral_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    let vault_seeds = &[
+        STAKE_VAULT_SEED,
+        stake_vault.reserve.as_ref(),
+        &[stake_vault.bump],
+    ];
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.compound_liquidity,
+        &ctx.accounts.destination_liquidity,
+        &ctx.accounts.liquidity_mint,
+        &stake_vault.to_account_info(),
+        &[vault_seeds],
+        liquidity_amount,
+    )?;
+
+    let collateral_mint_authority_seeds = &[
+        COLLATERAL_TOKEN_SEED,
+        reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.collateral_mint_authority],
+    ];
+
+    TokenUtils::mint_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.collateral_mint,
+        &ctx.accounts.vault_collateral,
+        &ctx.accounts.collateral_mint_authority.to_account_info(),
+        &[collateral_mint_authority_seeds],
+        collateral_amount,
+    )?;
+
+    reserve.add_liquidity(received_liquidity)?;
+    reserve.state.collateral_mint_supply = reserve
+        .state
+        .collateral_mint_supply
+        .checked_add(collateral_amount)
+        .ok_or(LendingError::MathOverflow)?;
+
+    stake_vault.record_compound(collateral_amount)?;
+
+    msg!(
+        "Compounded {} liquidity into {} aTokens for stake vault {}",
+        received_liquidity,
+        collateral_amount,
+        stake_vault.key()
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeStakeVault<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve whose aTokens will be staked
+    #[account(
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = liquidity_mint @ LendingError::TokenMintMismatch,
+        has_one = collateral_mint @ LendingError::ReserveCollateralMintMismatch
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Liquidity token mint
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Collateral mint (aToken mint)
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Stake vault account to initialize
+    #[account(
+        init,
+        payer = payer,
+        space = StakeVault::SIZE,
+        seeds = [STAKE_VAULT_SEED, reserve.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: Account<'info, StakeVault>,
+
+    /// Token account holding the staked aTokens
+    #[account(
+        init,
+        payer = payer,
+        token::mint = collateral_mint,
+        token::authority = stake_vault,
+        seeds = [STAKE_VAULT_SEED, stake_vault.key().as_ref(), b"collateral"],
+        bump
+    )]
+    pub vault_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token account collecting liquidity to compound
+    #[account(
+        init,
+        payer = payer,
+        token::mint = liquidity_mint,
+        token::authority = stake_vault,
+        seeds = [STAKE_VAULT_SEED, stake_vault.key().as_ref(), b"compound"],
+        bump
+    )]
+    pub compound_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Payer for account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct OpenStakePosition<'info> {
+    /// Stake vault to open a position in
+    pub stake_vault: Account<'info, StakeVault>,
+
+    /// Stake position account to create
+    #[account(
+        init,
+        payer = owner,
+        space = StakePosition::SIZE,
+        seeds = [STAKE_POSITION_SEED, stake_vault.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    /// Staker
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Stake vault to stake into
+    #[account(
+        mut,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = vault_collateral @ LendingError::InvalidAccount
+    )]
+    pub stake_vault: Account<'info, StakeVault>,
+
+    /// Staker's position in the vault
+    #[account(
+        mut,
+        seeds = [STAKE_POSITION_SEED, stake_vault.key().as_ref(), owner.key().as_ref()],
+        bump = stake_position.bump
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    /// Collateral mint (aToken mint)
+    #[account(address = vault_collateral.mint @ LendingError::ReserveCollateralMintMismatch)]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Vault's staked aToken account
+    #[account(mut)]
+    pub vault_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Staker's source aToken account
+    #[account(
+        mut,
+        token::mint = collateral_mint,
+        token::authority = owner
+    )]
+    pub source_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Staker
+    pub owner: Signer<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RequestUnstake<'info> {
+    /// Stake vault the position is in
+    pub stake_vault: Account<'info, StakeVault>,
+
+    /// Staker's position in the vault
+    #[account(
+        mut,
+        seeds = [STAKE_POSITION_SEED, stake_vault.key().as_ref(), owner.key().as_ref()],
+        bump = stake_position.bump
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    /// Staker
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Stake vault to unstake from
+    #[account(
+        mut,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = vault_collateral @ LendingError::InvalidAccount
+    )]
+    pub stake_vault: Account<'info, StakeVault>,
+
+    /// Staker's position in the vault
+    #[account(
+        mut,
+        seeds = [STAKE_POSITION_SEED, stake_vault.key().as_ref(), owner.key().as_ref()],
+        bump = stake_position.bump
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    /// Collateral mint (aToken mint)
+    #[account(address = vault_collateral.mint @ LendingError::ReserveCollateralMintMismatch)]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Vault's staked aToken account
+    #[account(mut)]
+    pub vault_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Staker's destination aToken account
+    #[account(
+        mut,
+        token::mint = collateral_mint
+    )]
+    pub destination_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Staker
+    pub owner: Signer<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CompoundStakeVault<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve the vault stakes aTokens of
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = liquidity_mint @ LendingError::TokenMintMismatch,
+        has_one = collateral_mint @ LendingError::ReserveCollateralMintMismatch
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Stake vault to compound
+    #[account(
+        mut,
+        seeds = [STAKE_VAULT_SEED, reserve.key().as_ref()],
+        bump = stake_vault.bump,
+        has_one = vault_collateral @ LendingError::InvalidAccount,
+        has_one = compound_liquidity @ LendingError::InvalidAccount
+    )]
+    pub stake_vault: Account<'info, StakeVault>,
+
+    /// Liquidity token mint
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Vault's compound liquidity account
+    #[account(mut)]
+    pub compound_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Reserve liquidity supply token account
+    #[account(
+        mut,
+        address = reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub destination_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Collateral mint (aToken mint)
+    #[account(mut)]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Collateral mint authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [COLLATERAL_TOKEN_SEED, reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub collateral_mint_authority: UncheckedAccount<'info>,
+
+    /// Vault's staked aToken account
+    #[account(mut)]
+    pub vault_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
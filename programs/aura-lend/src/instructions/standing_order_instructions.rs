@@ -0,0 +1,454 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::instructions::liquidation_instructions::{
+    calculate_liquidation_collateral_amount, revalue_liquidation_positions,
+};
+use crate::state::*;
+use crate::utils::{math::Decimal, OracleManager, ReserveLock, TokenUtils};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Place a standing order to deleverage the owner's obligation below a health factor
+/// An obligation has a single order; cancel it to change the terms.
+pub fn place_standing_order(
+    ctx: Context<PlaceStandingOrder>,
+    params: StandingOrderParams,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let obligation = AnyObligation::load_owned(
+        &ctx.accounts.obligation,
+        &market.key(),
+        &ctx.accounts.owner.key(),
+    )?;
+
+    let standing_order = &mut ctx.accounts.standing_order;
+    **standing_order = StandingOrder {
+        version: PROGRAM_VERSION,
+        obligation: ctx.accounts.obligation.key(),
+        owner: obligation.owner,
+        repay_reserve: ctx.accounts.repay_reserve.key(),
+        withdraw_reserve: ctx.accounts.withdraw_reserve.key(),
+        trigger_health_bps: params.trigger_health_bps,
+        max_repay_amount: params.max_repay_amount,
+        tip_bps: params.tip_bps,
+        created_slot: Clock::get()?.slot,
+        bump: ctx.bumps.standing_order,
+        reserved: [0; 32],
+    };
+    standing_order.validate()?;
+
+    msg!(
+        "Standing order placed on {} - trigger: {} bps, max repay: {}, tip: {} bps",
+        standing_order.obligation,
+        params.trigger_health_bps,
+        params.max_repay_amount,
+        params.tip_bps
+    );
+    Ok(())
+}
+
+/// Cancel a standing order, returning its rent to the owner
+pub fn cancel_standing_order(ctx: Context<CancelStandingOrder>) -> Result<()> {
+    msg!(
+        "Standing order on {} cancelled",
+        ctx.accounts.standing_order.obligation
+    );
+    Ok(())
+}
+
+/// Execute a standing order on an obligation whose health fell below its trigger
+/// Permissionless. The keeper repays up to the order's `max_repay_amount` of the borrow
+/// and receives collateral worth the repayment plus the order's tip, at TWAP-smoothed
+/// prices in the borrower's favour. The obligation must have been refreshed in this
+/// slot and must end healthier than it started.
+pub fn execute_standing_order(
+    ctx: Context<ExecuteStandingOrder>,
+    liquidity_amount: u64,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let standing_order = &ctx.accounts.standing_order;
+    let mut obligation = AnyObligation::load_checked(&ctx.accounts.obligation, &market.key())?;
+    let repay_reserve = &mut ctx.accounts.repay_reserve;
+    let withdraw_reserve = &mut ctx.accounts.withdraw_reserve;
+    let clock = Clock::get()?;
+
+    if market.is_paused() {
+        return Err(LendingError::MarketPaused.into());
+    }
+    if repay_reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::REPAYMENTS_DISABLED)
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    // An order placed by a previous owner of a transferred obligation is inert
+    if standing_order.owner != obligation.owner {
+        return Err(LendingError::InvalidAuthority.into());
+    }
+
+    if liquidity_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+    if liquidity_amount > standing_order.max_repay_amount {
+        return Err(LendingError::AmountTooLarge.into());
+    }
+
+    // The trigger is checked on values from a refresh in this slot
+    obligation.require_refreshed(clock.slot)?;
+    let emode = EModeCategory::resolve(ctx.accounts.emode_category.as_deref(), &obligation, None)?;
+
+    // Hold the reentrancy locks until the reserve state is written back
+    let mut repay_reserve = ReserveLock::acquire(repay_reserve)?;
+    let mut withdraw_reserve = ReserveLock::acquire(withdraw_reserve)?;
+
+    let result = (|| -> Result<(u64, Decimal, Decimal)> {
+        repay_reserve.update_interest(clock.slot)?;
+        withdraw_reserve.update_interest(clock.slot)?;
+        obligation.accrue_borrow_interest(
+            &repay_reserve.key(),
+            repay_reserve.cumulative_borrow_rate_wads(),
+        )?;
+
+        let borrowed_amount = obligation
+            .find_liquidity_borrow(&repay_reserve.key())
+            .ok_or(LendingError::ObligationReserveNotFound)?
+            .borrowed_amount_wads
+            .try_floor_u64()?;
+        if liquidity_amount > borrowed_amount {
+            return Err(LendingError::AmountTooLarge.into());
+        }
+
+        let repay_price = OracleManager::get_checked_price(
+            &ctx.accounts.repay_price_oracle.to_account_info(),
+            ctx.accounts.repay_secondary_price_oracle.as_deref(),
+            &repay_reserve,
+        )?;
+        repay_price.validate(clock.unix_timestamp)?;
+
+        let withdraw_price = OracleManager::get_checked_price(
+            &ctx.accounts.withdraw_price_oracle.to_account_info(),
+            ctx.accounts.withdraw_secondary_price_oracle.as_deref(),
+            &withdraw_reserve,
+        )?;
+        withdraw_price.validate(clock.unix_timestamp)?;
+
+        // Value both positions at TWAP-smoothed prices in the borrower's favour, so a
+        // momentary price move cannot trigger the order
+        let repay_price = repay_reserve.liquidation_debt_price(&repay_price);
+        let withdraw_price = withdraw_reserve.liquidation_collateral_price(&withdraw_price);
+        revalue_liquidation_positions(
+            &mut obligation,
+            &repay_reserve,
+            &withdraw_reserve,
+            &repay_price,
+            &withdraw_price,
+        )?;
+
+        let health_before = obligation.calculate_health_factor_with(emode.as_ref())?;
+        if !standing_order.is_triggered(health_before) {
+            return Err(LendingError::StandingOrderNotTriggered.into());
+        }
+
+        let repay_value_usd = OracleManager::calculate_usd_value(
+            liquidity_amount,
+            &repay_price,
+            repay_reserve.config.decimals,
+        )?;
+        let seized_liquidity = calculate_liquidation_collateral_amount(
+            repay_value_usd,
+            &withdraw_price,
+            standing_order.tip_bps as u64,
+        )?;
+        let collateral_amount = withdraw_reserve.liquidity_to_collateral(seized_liquidity)?;
+
+        let deposited_collateral = obligation
+            .find_collateral_deposit(&withdraw_reserve.key())
+            .ok_or(LendingError::ObligationReserveNotFound)?
+            .deposited_amount;
+        if deposited_collateral < collateral_amount {
+            return Err(LendingError::InsufficientCollateral.into());
+        }
+
+        // Grossed up so that the reserve receives the full repayment after transfer fees
+        let transfer_amount = TokenUtils::amount_before_transfer_fee(
+            &ctx.accounts.repay_liquidity_mint,
+            liquidity_amount,
+        )?;
+
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.source_liquidity,
+            &ctx.accounts.repay_reserve_liquidity_supply,
+            &ctx.accounts.repay_liquidity_mint,
+            &ctx.accounts.keeper.to_account_info(),
+            &[],
+            transfer_amount,
+        )?;
+
+        let collateral_authority_seeds = &[
+            COLLATERAL_TOKEN_SEED,
+            withdraw_reserve.liquidity_mint.as_ref(),
+            b"authority",
+            &[ctx.bumps.withdraw_collateral_supply_authority],
+        ];
+
+        TokenUtils::transfer_tokens(
+            &ctx.accounts.collateral_token_program,
+            &ctx.accounts.withdraw_reserve_collateral_supply,
+            &ctx.accounts.destination_collateral,
+            &ctx.accounts.withdraw_collateral_mint,
+            &ctx.accounts
+                .withdraw_collateral_supply_authority
+                .to_account_info(),
+            &[collateral_authority_seeds],
+            collateral_amount,
+        )?;
+
+        // Update reserve and obligation accounting. The borrower authorized the unwind,
+        // so it counts as a repayment rather than a liquidation.
+        repay_reserve.repay_borrow(liquidity_amount)?;
+        obligation.repay_liquidity_borrow(
+            &repay_reserve.key(),
+            Decimal::from_integer(liquidity_amount)?,
+        )?;
+        obligation.remove_collateral_deposit(&withdraw_reserve.key(), collateral_amount)?;
+
+        obligation.borrowed_value_usd = obligation.borrowed_value_usd.try_sub(repay_value_usd)?;
+        let collateral_value_usd =
+            withdraw_reserve.collateral_value_usd(collateral_amount, &withdraw_price)?;
+        obligation.deposited_value_usd = obligation
+            .deposited_value_usd
+            .try_sub(collateral_value_usd)?;
+
+        // Revalue what is left of the collateral, which the health factor is weighted on
+        if let Some(collateral) = obligation.find_collateral_deposit_mut(&withdraw_reserve.key()) {
+            collateral.market_value_usd = withdraw_reserve
+                .collateral_value_usd(collateral.deposited_amount, &withdraw_price)?;
+        }
+
+        obligation.record_repayment();
+        obligation.update_timestamp(clock.slot)?;
+
+        // A tip above the collateral's headroom would make the position worse, not better
+        let health_after = obligation.calculate_health_factor_with(emode.as_ref())?;
+        if health_after <= health_before {
+            return Err(LendingError::ObligationUnhealthy.into());
+        }
+
+        Ok((collateral_amount, health_before, health_after))
+    })();
+
+    let (collateral_amount, health_before, health_after) = result?;
+
+    msg!(
+        "Standing order on {} executed by keeper {} - repaid: {}, seized: {}, health {} -> {} bps",
+        ctx.accounts.obligation.key(),
+        ctx.accounts.keeper.key(),
+        liquidity_amount,
+        collateral_amount,
+        health_before.to_health_bps_saturating(),
+        health_after.to_health_bps_saturating()
+    );
+
+    obligation.save(&ctx.accounts.obligation)?;
+
+    Ok(())
+}
+
+/// Terms of a standing deleverage order
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct StandingOrderParams {
+    /// Health factor (basis points) below which keepers may execute the order
+    pub trigger_health_bps: u64,
+    /// Most liquidity a single execution may repay
+    pub max_repay_amount: u64,
+    /// Keeper tip on the repaid value, paid in collateral (basis points)
+    pub tip_bps: u16,
+}
+
+// Context structs for standing order instructions
+
+#[derive(Accounts)]
+pub struct PlaceStandingOrder<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation the order deleverages (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Reserve whose debt the order repays
+    #[account(
+        seeds = [RESERVE_SEED, repay_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub repay_reserve: Account<'info, Reserve>,
+
+    /// Reserve whose collateral pays for the repayment
+    #[account(
+        seeds = [RESERVE_SEED, withdraw_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub withdraw_reserve: Account<'info, Reserve>,
+
+    /// Standing order account to initialize
+    #[account(
+        init,
+        payer = owner,
+        space = StandingOrder::SIZE,
+        seeds = [STANDING_ORDER_SEED, obligation.key().as_ref()],
+        bump
+    )]
+    pub standing_order: Account<'info, StandingOrder>,
+
+    /// Obligation owner placing the order
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelStandingOrder<'info> {
+    /// Standing order to close
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ LendingError::InvalidAuthority,
+        seeds = [STANDING_ORDER_SEED, standing_order.obligation.as_ref()],
+        bump = standing_order.bump
+    )]
+    pub standing_order: Account<'info, StandingOrder>,
+
+    /// Owner who placed the order
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteStandingOrder<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Standing order being executed
+    #[account(
+        seeds = [STANDING_ORDER_SEED, obligation.key().as_ref()],
+        bump = standing_order.bump,
+        has_one = repay_reserve @ LendingError::InvalidAccount,
+        has_one = withdraw_reserve @ LendingError::InvalidAccount
+    )]
+    pub standing_order: Account<'info, StandingOrder>,
+
+    /// Obligation being deleveraged (any layout)
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+
+    /// Reserve for the asset being repaid
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, repay_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub repay_reserve: Account<'info, Reserve>,
+
+    /// Reserve for the collateral being withdrawn
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, withdraw_reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub withdraw_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for repay asset
+    /// CHECK: Matched against the repay reserve's price_oracle
+    #[account(address = repay_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub repay_price_oracle: UncheckedAccount<'info>,
+
+    /// Price oracle for withdraw asset
+    /// CHECK: Matched against the withdraw reserve's price_oracle
+    #[account(address = withdraw_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub withdraw_price_oracle: UncheckedAccount<'info>,
+
+    /// Secondary oracle of the repay asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub repay_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Secondary oracle of the withdraw asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub withdraw_secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// Liquidity token mint of the repay reserve
+    #[account(address = repay_reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub repay_liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Collateral mint (aToken mint) of the withdraw reserve
+    #[account(address = withdraw_reserve.collateral_mint @ LendingError::ReserveCollateralMintMismatch)]
+    pub withdraw_collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Keeper's source liquidity token account (for repayment)
+    #[account(
+        mut,
+        token::mint = repay_reserve.liquidity_mint,
+        token::authority = keeper
+    )]
+    pub source_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Keeper's destination collateral token account (receives collateral and tip)
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.collateral_mint,
+        token::authority = keeper
+    )]
+    pub destination_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Repay reserve's liquidity supply token account
+    #[account(
+        mut,
+        address = repay_reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub repay_reserve_liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Withdraw reserve's collateral supply token account
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.collateral_mint,
+        token::authority = withdraw_collateral_supply_authority
+    )]
+    pub withdraw_reserve_collateral_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Withdraw collateral supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [COLLATERAL_TOKEN_SEED, withdraw_reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub withdraw_collateral_supply_authority: UncheckedAccount<'info>,
+
+    /// Keeper executing the order
+    pub keeper: Signer<'info>,
+
+    /// Token program of the repay reserve's liquidity mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the withdraw reserve's collateral mint
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,204 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Create the sub-account record of an obligation, with every position in sub-account 0
+pub fn initialize_obligation_sub_accounts(
+    ctx: Context<InitializeObligationSubAccounts>,
+) -> Result<()> {
+    let obligation =
+        AnyObligation::load_checked(&ctx.accounts.obligation, &ctx.accounts.market.key())?;
+    if obligation.owner != ctx.accounts.owner.key() {
+        return Err(LendingError::InvalidAuthority.into());
+    }
+
+    **ctx.accounts.sub_accounts =
+        ObligationSubAccounts::new(ctx.accounts.obligation.key(), ctx.bumps.sub_accounts);
+
+    msg!("Sub-accounts initialized for obligation {}", ctx.accounts.obligation.key());
+    Ok(())
+}
+
+/// Label one of the obligation's sub-accounts
+pub fn set_sub_account_label(
+    ctx: Context<SetSubAccountLabel>,
+    sub_account: u8,
+    label: [u8; SUB_ACCOUNT_LABEL_LEN],
+) -> Result<()> {
+    AnyObligation::load_owned(
+        &ctx.accounts.obligation,
+        &ctx.accounts.market.key(),
+        &ctx.accounts.owner.key(),
+    )?;
+    ctx.accounts.sub_accounts.set_label(sub_account, label)?;
+
+    msg!("Sub-account {} labeled", sub_account);
+    Ok(())
+}
+
+/// Move a share of a deposit or borrow between two sub-accounts
+/// Both sub-accounts must be healthy at the obligation's refreshed values afterwards
+pub fn move_sub_account_position(
+    ctx: Context<MoveSubAccountPosition>,
+    params: MoveSubAccountPositionParams,
+) -> Result<()> {
+    let obligation =
+        AnyObligation::load_checked(&ctx.accounts.obligation, &ctx.accounts.market.key())?;
+    let sub_accounts = &mut ctx.accounts.sub_accounts;
+    let clock = Clock::get()?;
+
+    if obligation.owner != ctx.accounts.owner.key() {
+        return Err(LendingError::InvalidAuthority.into());
+    }
+    if obligation.is_stale(clock.slot) {
+        return Err(LendingError::ObligationStale.into());
+    }
+
+    let position_exists = match params.kind {
+        PositionKind::Deposit => obligation.find_collateral_deposit(&params.reserve).is_some(),
+        PositionKind::Borrow => obligation.find_liquidity_borrow(&params.reserve).is_some(),
+    };
+    if !position_exists {
+        return Err(LendingError::ObligationReserveNotFound.into());
+    }
+
+    sub_accounts.prune(&obligation);
+    sub_accounts.move_share(
+        params.from_sub_account,
+        params.to_sub_account,
+        params.reserve,
+        params.kind,
+        params.share_bps,
+    )?;
+
+    for sub_account in [params.from_sub_account, params.to_sub_account] {
+        if !sub_accounts.health(&obligation, sub_account)?.is_healthy() {
+            return Err(LendingError::SubAccountUnhealthy.into());
+        }
+    }
+
+    msg!(
+        "Moved {} bps of {:?} position in {} from sub-account {} to {}",
+        params.share_bps,
+        params.kind,
+        params.reserve,
+        params.from_sub_account,
+        params.to_sub_account
+    );
+    Ok(())
+}
+
+/// Collateral and debt attributed to a sub-account at the obligation's last refresh
+pub fn get_sub_account_health(
+    ctx: Context<GetSubAccountHealth>,
+    sub_account: u8,
+) -> Result<SubAccountHealth> {
+    let obligation =
+        AnyObligation::load_checked(&ctx.accounts.obligation, &ctx.accounts.market.key())?;
+    ctx.accounts.sub_accounts.health(&obligation, sub_account)
+}
+
+// Context structs for sub-account instructions
+
+#[derive(Accounts)]
+pub struct InitializeObligationSubAccounts<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation account in any layout
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Sub-account record to initialize
+    #[account(
+        init,
+        payer = owner,
+        space = ObligationSubAccounts::SIZE,
+        seeds = [SUB_ACCOUNTS_SEED, obligation.key().as_ref()],
+        bump
+    )]
+    pub sub_accounts: Account<'info, ObligationSubAccounts>,
+
+    /// Obligation owner
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSubAccountLabel<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation account in any layout
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Sub-account record of the obligation
+    #[account(
+        mut,
+        seeds = [SUB_ACCOUNTS_SEED, obligation.key().as_ref()],
+        bump = sub_accounts.bump
+    )]
+    pub sub_accounts: Account<'info, ObligationSubAccounts>,
+
+    /// Obligation owner
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MoveSubAccountPosition<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation account in any layout
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Sub-account record of the obligation
+    #[account(
+        mut,
+        seeds = [SUB_ACCOUNTS_SEED, obligation.key().as_ref()],
+        bump = sub_accounts.bump
+    )]
+    pub sub_accounts: Account<'info, ObligationSubAccounts>,
+
+    /// Obligation owner
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetSubAccountHealth<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation account in any layout
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Sub-account record of the obligation
+    #[account(
+        seeds = [SUB_ACCOUNTS_SEED, obligation.key().as_ref()],
+        bump = sub_accounts.bump
+    )]
+    pub sub_accounts: Account<'info, ObligationSubAccounts>,
+}
@@ -0,0 +1,656 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::*;
+use crate::utils::{ProtocolConfig, ReserveLock, TokenUtils};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Initialize the supply queue of a reserve
+pub fn initialize_supply_queue(ctx: Context<InitializeSupplyQueue>) -> Result<()> {
+    **ctx.accounts.supply_queue = SupplyQueue::new(
+        ctx.accounts.market.key(),
+        ctx.accounts.reserve.key(),
+        ctx.accounts.liquidity_escrow.key(),
+        ctx.accounts.collateral_escrow.key(),
+        ctx.bumps.supply_queue,
+    );
+
+    msg!(
+        "Supply queue initialized for reserve {}",
+        ctx.accounts.reserve.key()
+    );
+    Ok(())
+}
+
+/// Queue a deposit the supply cap blocks, escrowing the liquidity until it fits
+pub fn queue_deposit(ctx: Context<QueueDeposit>, liquidity_amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+
+    if ctx
+        .accounts
+        .reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::DEPOSITS_DISABLED)
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    if liquidity_amount < MIN_DEPOSIT_AMOUNT {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    // The entry holds what arrives in escrow after the transfer fee
+    let escrowed_amount =
+        TokenUtils::amount_after_transfer_fee(&ctx.accounts.liquidity_mint, liquidity_amount)?;
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.source_liquidity,
+        &ctx.accounts.liquidity_escrow,
+        &ctx.accounts.liquidity_mint,
+        &ctx.accounts.owner.to_account_info(),
+        &[],
+        liquidity_amount,
+    )?;
+
+    let entry_id = ctx.accounts.supply_queue.enqueue(
+        ctx.accounts.owner.key(),
+        SupplyQueueKind::Deposit,
+        escrowed_amount,
+        clock.slot,
+    )?;
+
+    msg!("Deposit {} queued - amount: {}", entry_id, escrowed_amount);
+    Ok(())
+}
+
+/// Queue a redemption the withdraw utilization cap blocks, escrowing the collateral
+pub fn queue_withdrawal(ctx: Context<QueueWithdrawal>, collateral_amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+
+    if ctx
+        .accounts
+        .reserve
+        .config
+        .flags
+        .contains(ReserveConfigFlags::WITHDRAWALS_DISABLED)
+    {
+        return Err(LendingError::FeatureDisabled.into());
+    }
+
+    if ctx
+        .accounts
+        .reserve
+        .collateral_to_liquidity(collateral_amount)?
+        < MIN_DEPOSIT_AMOUNT
+    {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    TokenUtils::transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.source_collateral,
+        &ctx.accounts.collateral_escrow,
+        &ctx.accounts.collateral_mint,
+        &ctx.accounts.owner.to_account_info(),
+        &[],
+        collateral_amount,
+    )?;
+
+    let entry_id = ctx.accounts.supply_queue.enqueue(
+        ctx.accounts.owner.key(),
+        SupplyQueueKind::Withdraw,
+        collateral_amount,
+        clock.slot,
+    )?;
+
+    msg!(
+        "Withdrawal {} queued - collateral: {}",
+        entry_id,
+        collateral_amount
+    );
+    Ok(())
+}
+
+/// Cancel a queue entry, refunding its unfilled amount and releasing anything filled
+pub fn cancel_queue_entry(ctx: Context<SettleSupplyQueueEntry>, entry_id: u64) -> Result<()> {
+    let entry = ctx
+        .accounts
+        .supply_queue
+        .cancel_entry(entry_id, &ctx.accounts.owner.key())?;
+
+    let (liquidity_amount, collateral_amount) = match entry.kind {
+        SupplyQueueKind::Deposit => (entry.remaining_amount, entry.filled_amount),
+        SupplyQueueKind::Withdraw => (entry.filled_amount, entry.remaining_amount),
+    };
+    release_from_escrow(&ctx.accounts, liquidity_amount, collateral_amount)?;
+
+    msg!(
+        "Queue entry {} cancelled - liquidity released: {}, collateral released: {}",
+        entry_id,
+        liquidity_amount,
+        collateral_amount
+    );
+    Ok(())
+}
+
+/// Claim the aTokens or liquidity a queue entry was filled with
+pub fn claim_queue_fills(ctx: Context<SettleSupplyQueueEntry>, entry_id: u64) -> Result<()> {
+    let (kind, filled_amount) = ctx
+        .accounts
+        .supply_queue
+        .claim_fills(entry_id, &ctx.accounts.owner.key())?;
+
+    if filled_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    match kind {
+        SupplyQueueKind::Deposit => release_from_escrow(&ctx.accounts, 0, filled_amount)?,
+        SupplyQueueKind::Withdraw => release_from_escrow(&ctx.accounts, filled_amount, 0)?,
+    }
+
+    msg!("Claimed {} from queue entry {}", filled_amount, entry_id);
+    Ok(())
+}
+
+/// Fill queued deposits and withdrawals as far as the reserve's caps allow
+/// Permissionless. Deposits fill oldest first while the supply cap has room, then
+/// withdrawals oldest first while the withdraw utilization cap allows, at most
+/// `max_fills` entries in all. An entry that only partly fits is partly filled and stays
+/// at the front of the queue. Queuing counts as the announcement of a large withdrawal,
+/// so a large fill waits until its entry is `large_withdrawal_delay_slots` old.
+pub fn process_queue(ctx: Context<ProcessQueue>, max_fills: u8) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let config = &ctx.accounts.config;
+    let reserve = &mut ctx.accounts.reserve;
+    let supply_queue = &mut ctx.accounts.supply_queue;
+    let clock = Clock::get()?;
+
+    let deposits_open = !(market.is_paused() || market.is_lending_disabled())
+        && !reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::DEPOSITS_DISABLED);
+    let withdrawals_open = !(market.is_paused() && !market.is_emergency())
+        && !reserve
+            .config
+            .flags
+            .contains(ReserveConfigFlags::WITHDRAWALS_DISABLED);
+
+    // Hold the reentrancy lock until the reserve state is written back
+    let mut reserve = ReserveLock::acquire(reserve)?;
+
+    let result = (|| -> Result<(u8, u8)> {
+        reserve.update_interest(clock.slot)?;
+
+        let reserve_key = reserve.key();
+        let liquidity_mint = reserve.liquidity_mint;
+        let queue_seeds = &[
+            SUPPLY_QUEUE_SEED,
+            reserve_key.as_ref(),
+            &[supply_queue.bump],
+        ];
+        let liquidity_authority_seeds = &[
+            LIQUIDITY_TOKEN_SEED,
+            liquidity_mint.as_ref(),
+            b"authority",
+            &[ctx.bumps.liquidity_supply_authority],
+        ];
+        let collateral_mint_authority_seeds = &[
+            COLLATERAL_TOKEN_SEED,
+            liquidity_mint.as_ref(),
+            b"authority",
+            &[ctx.bumps.collateral_mint_authority],
+        ];
+
+        let mut deposits_filled = 0u8;
+        while deposits_open && deposits_filled < max_fills {
+            let Some(index) = supply_queue.next_unfilled(SupplyQueueKind::Deposit) else {
+                break;
+            };
+
+            // Only what arrives after the transfer fee counts against the cap
+            let fill_amount = std::cmp::min(
+                supply_queue.entries[index].remaining_amount,
+                reserve.supply_cap_headroom(),
+            );
+            let received_liquidity =
+                TokenUtils::amount_after_transfer_fee(&ctx.accounts.liquidity_mint, fill_amount)?;
+            let collateral_amount = reserve.liquidity_to_collateral(received_liquidity)?;
+            if collateral_amount == 0 {
+                break;
+            }
+
+            TokenUtils::transfer_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.liquidity_escrow,
+                &ctx.accounts.liquidity_supply,
+                &ctx.accounts.liquidity_mint,
+                &supply_queue.to_account_info(),
+                &[queue_seeds],
+                fill_amount,
+            )?;
+
+            TokenUtils::mint_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.collateral_mint,
+                &ctx.accounts.collateral_escrow,
+                &ctx.accounts.collateral_mint_authority.to_account_info(),
+                &[collateral_mint_authority_seeds],
+                collateral_amount,
+            )?;
+
+            reserve.add_liquidity(received_liquidity)?;
+            reserve.state.collateral_mint_supply = reserve
+                .state
+                .collateral_mint_supply
+                .checked_add(collateral_amount)
+                .ok_or(LendingError::MathOverflow)?;
+            supply_queue.record_fill(index, fill_amount, collateral_amount)?;
+            deposits_filled += 1;
+        }
+
+        let mut withdrawals_filled = 0u8;
+        while withdrawals_open && deposits_filled + withdrawals_filled < max_fills {
+            let Some(index) = supply_queue.next_unfilled(SupplyQueueKind::Withdraw) else {
+                break;
+            };
+            let entry = &supply_queue.entries[index];
+
+            let withdrawable_liquidity = config.max_withdrawable_liquidity(
+                reserve.state.total_borrows,
+                reserve.state.available_liquidity,
+            )?;
+            let collateral_amount = std::cmp::min(
+                entry.remaining_amount,
+                reserve.liquidity_to_collateral(withdrawable_liquidity)?,
+            );
+            let liquidity_amount = reserve.collateral_to_liquidity(collateral_amount)?;
+            if liquidity_amount == 0 {
+                break;
+            }
+
+            if config.is_large_withdrawal(liquidity_amount, reserve.state.total_liquidity)?
+                && clock.slot.saturating_sub(entry.queued_slot)
+                    < config.large_withdrawal_delay_slots
+            {
+                break;
+            }
+
+            // While utilization has stayed high, part of the exit stays with remaining suppliers
+            let surge_fee = reserve.surge_withdrawal_fee(liquidity_amount, clock.slot)?;
+            let withdrawn_amount = liquidity_amount
+                .checked_sub(surge_fee)
+                .ok_or(LendingError::MathUnderflow)?;
+
+            TokenUtils::burn_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.collateral_mint,
+                &ctx.accounts.collateral_escrow,
+                &supply_queue.to_account_info(),
+                &[queue_seeds],
+                collateral_amount,
+            )?;
+
+            TokenUtils::transfer_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.liquidity_supply,
+                &ctx.accounts.liquidity_escrow,
+                &ctx.accounts.liquidity_mint,
+                &ctx.accounts.liquidity_supply_authority.to_account_info(),
+                &[liquidity_authority_seeds],
+                withdrawn_amount,
+            )?;
+
+            reserve.remove_liquidity(withdrawn_amount)?;
+            reserve.total_surge_fees = reserve
+                .total_surge_fees
+                .checked_add(surge_fee)
+                .ok_or(LendingError::MathOverflow)?;
+            reserve.state.collateral_mint_supply = reserve
+                .state
+                .collateral_mint_supply
+                .checked_sub(collateral_amount)
+                .ok_or(LendingError::MathUnderflow)?;
+
+            let escrowed_liquidity = TokenUtils::amount_after_transfer_fee(
+                &ctx.accounts.liquidity_mint,
+                withdrawn_amount,
+            )?;
+            supply_queue.record_fill(index, collateral_amount, escrowed_liquidity)?;
+            withdrawals_filled += 1;
+        }
+
+        Ok((deposits_filled, withdrawals_filled))
+    })();
+
+    let (deposits_filled, withdrawals_filled) = result?;
+
+    msg!(
+        "Supply queue processed - deposits filled: {}, withdrawals filled: {}",
+        deposits_filled,
+        withdrawals_filled
+    );
+    Ok(())
+}
+
+/// Pay escrowed liquidity and collateral out to the entry's owner
+fn release_from_escrow(
+    accounts: &SettleSupplyQueueEntry,
+    liquidity_amount: u64,
+    collateral_amount: u64,
+) -> Result<()> {
+    let supply_queue = &accounts.supply_queue;
+    let queue_seeds = &[
+        SUPPLY_QUEUE_SEED,
+        supply_queue.reserve.as_ref(),
+        &[supply_queue.bump],
+    ];
+
+    if liquidity_amount > 0 {
+        TokenUtils::transfer_tokens(
+            &accounts.token_program,
+            &accounts.liquidity_escrow,
+            &accounts.destination_liquidity,
+            &accounts.liquidity_mint,
+            &supply_queue.to_account_info(),
+            &[queue_seeds],
+            liquidity_amount,
+        )?;
+    }
+
+    if collateral_amount > 0 {
+        TokenUtils::transfer_tokens(
+            &accounts.token_program,
+            &accounts.collateral_escrow,
+            &accounts.destination_collateral,
+            &accounts.collateral_mint,
+            &supply_queue.to_account_info(),
+            &[queue_seeds],
+            collateral_amount,
+        )?;
+    }
+
+    Ok(())
+}
+
+// Context structs for supply queue instructions
+
+#[derive(Accounts)]
+pub struct InitializeSupplyQueue<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve the queue waits on
+    #[account(
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Supply queue account to initialize
+    #[account(
+        init,
+        payer = payer,
+        space = SupplyQueue::SIZE,
+        seeds = [SUPPLY_QUEUE_SEED, reserve.key().as_ref()],
+        bump
+    )]
+    pub supply_queue: Account<'info, SupplyQueue>,
+
+    /// Liquidity token mint of the reserve
+    #[account(address = reserve.liquidity_mint @ LendingError::ReserveLiquidityMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Collateral (aToken) mint of the reserve
+    #[account(address = reserve.collateral_mint @ LendingError::ReserveCollateralMintMismatch)]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Escrow for queued deposits and filled withdrawals
+    #[account(
+        init,
+        payer = payer,
+        token::mint = liquidity_mint,
+        token::authority = supply_queue,
+        seeds = [SUPPLY_QUEUE_SEED, supply_queue.key().as_ref(), b"liquidity"],
+        bump
+    )]
+    pub liquidity_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// Escrow for queued withdrawals and filled deposits
+    #[account(
+        init,
+        payer = payer,
+        token::mint = collateral_mint,
+        token::authority = supply_queue,
+        seeds = [SUPPLY_QUEUE_SEED, supply_queue.key().as_ref(), b"collateral"],
+        bump
+    )]
+    pub collateral_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// Payer for account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Rent sysvar
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct QueueDeposit<'info> {
+    /// Reserve the deposit is queued for
+    #[account(address = supply_queue.reserve @ LendingError::InvalidAccount)]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Supply queue of the reserve
+    #[account(
+        mut,
+        seeds = [SUPPLY_QUEUE_SEED, supply_queue.reserve.as_ref()],
+        bump = supply_queue.bump,
+        has_one = liquidity_escrow @ LendingError::InvalidAccount
+    )]
+    pub supply_queue: Account<'info, SupplyQueue>,
+
+    /// Escrow for queued deposits
+    #[account(mut)]
+    pub liquidity_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity token mint of the reserve
+    #[account(address = reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Owner's source liquidity token account
+    #[account(
+        mut,
+        token::mint = reserve.liquidity_mint,
+        token::authority = owner
+    )]
+    pub source_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Depositor
+    pub owner: Signer<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct QueueWithdrawal<'info> {
+    /// Reserve the withdrawal is queued for
+    #[account(address = supply_queue.reserve @ LendingError::InvalidAccount)]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Supply queue of the reserve
+    #[account(
+        mut,
+        seeds = [SUPPLY_QUEUE_SEED, supply_queue.reserve.as_ref()],
+        bump = supply_queue.bump,
+        has_one = collateral_escrow @ LendingError::InvalidAccount
+    )]
+    pub supply_queue: Account<'info, SupplyQueue>,
+
+    /// Escrow for queued withdrawals
+    #[account(mut)]
+    pub collateral_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// Collateral (aToken) mint of the reserve
+    #[account(address = reserve.collateral_mint @ LendingError::ReserveCollateralMintMismatch)]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Owner's source collateral token account
+    #[account(
+        mut,
+        token::mint = reserve.collateral_mint,
+        token::authority = owner
+    )]
+    pub source_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Supplier withdrawing
+    pub owner: Signer<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SettleSupplyQueueEntry<'info> {
+    /// Supply queue holding the entry
+    #[account(
+        mut,
+        seeds = [SUPPLY_QUEUE_SEED, supply_queue.reserve.as_ref()],
+        bump = supply_queue.bump,
+        has_one = liquidity_escrow @ LendingError::InvalidAccount,
+        has_one = collateral_escrow @ LendingError::InvalidAccount
+    )]
+    pub supply_queue: Account<'info, SupplyQueue>,
+
+    /// Escrow for queued deposits and filled withdrawals
+    #[account(mut)]
+    pub liquidity_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// Escrow for queued withdrawals and filled deposits
+    #[account(mut)]
+    pub collateral_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity token mint of the reserve
+    #[account(address = liquidity_escrow.mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Collateral (aToken) mint of the reserve
+    #[account(address = collateral_escrow.mint @ LendingError::TokenMintMismatch)]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Owner's destination liquidity token account
+    #[account(
+        mut,
+        token::mint = liquidity_escrow.mint,
+        token::authority = owner
+    )]
+    pub destination_liquidity: InterfaceAccount<'info, TokenAccount>,
+
+    /// Owner's destination collateral token account
+    #[account(
+        mut,
+        token::mint = collateral_escrow.mint,
+        token::authority = owner
+    )]
+    pub destination_collateral: InterfaceAccount<'info, TokenAccount>,
+
+    /// Entry owner
+    pub owner: Signer<'info>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ProcessQueue<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve whose queue is processed
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = collateral_mint @ LendingError::ReserveCollateralMintMismatch
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Supply queue of the reserve
+    #[account(
+        mut,
+        seeds = [SUPPLY_QUEUE_SEED, reserve.key().as_ref()],
+        bump = supply_queue.bump,
+        has_one = liquidity_escrow @ LendingError::InvalidAccount,
+        has_one = collateral_escrow @ LendingError::InvalidAccount
+    )]
+    pub supply_queue: Account<'info, SupplyQueue>,
+
+    /// Protocol configuration (large withdrawal threshold and utilization cap)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Liquidity token mint
+    #[account(address = reserve.liquidity_mint @ LendingError::TokenMintMismatch)]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+
+    /// Collateral mint (aToken mint)
+    #[account(mut)]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Reserve liquidity supply token account
+    #[account(
+        mut,
+        address = reserve.liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Collateral mint authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [COLLATERAL_TOKEN_SEED, reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub collateral_mint_authority: UncheckedAccount<'info>,
+
+    /// Escrow for queued deposits and filled withdrawals
+    #[account(mut)]
+    pub liquidity_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// Escrow for queued withdrawals and filled deposits
+    #[account(mut)]
+    pub collateral_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -1,9 +1,13 @@
 use crate::constants::*;
 use crate::error::LendingError;
+use crate::events::TimelockProposalVetoedEvent;
+use crate::state::audit_log::{AdminAction, AdminAuditLog};
 use crate::state::governance::*;
 use crate::state::multisig::*;
+use crate::state::proposal_lifecycle::*;
 use crate::state::timelock::*;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke_signed;
 
 /// Initialize a new timelock controller
 pub fn initialize_timelock(ctx: Context<InitializeTimelock>) -> Result<()> {
@@ -25,6 +29,12 @@ pub fn create_timelock_proposal(
     ctx: Context<CreateTimelockProposal>,
     params: CreateTimelockProposalParams,
 ) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.proposer.key(),
+        AdminAction::CreateTimelockProposal,
+        &params,
+    )?;
+
     let timelock = &mut ctx.accounts.timelock;
     let proposal = &mut ctx.accounts.proposal;
     let proposer = &ctx.accounts.proposer;
@@ -33,8 +43,8 @@ pub fn create_timelock_proposal(
     // Check if proposer has permission to create timelock proposals
     PermissionChecker::check_permission(governance, &proposer.key(), Permission::TIMELOCK_MANAGER)?;
 
-    // Get minimum delay for this operation type
-    let min_delay = timelock.get_min_delay(params.operation_type);
+    // Get minimum delay for this operation type, and for its calls if it carries any
+    let min_delay = timelock.get_proposal_delay(params.operation_type, !params.calls.is_empty());
 
     // Create the proposal
     **proposal = TimelockProposal::new(
@@ -44,6 +54,7 @@ pub fn create_timelock_proposal(
         min_delay,
         proposer.key(),
         params.target_accounts,
+        params.calls,
     )?;
 
     // Add to active proposals list
@@ -57,7 +68,19 @@ pub fn create_timelock_proposal(
 }
 
 /// Execute a timelock proposal (once delay period has passed)
-pub fn execute_timelock_proposal(ctx: Context<ExecuteTimelockProposal>) -> Result<()> {
+/// The proposal's calls are invoked in order with the timelock PDA as signer. Every
+/// account they reference, including the invoked programs, is passed as a remaining
+/// account. Proposals without calls only mark the proposal executed, for instructions
+/// that consume an executed proposal themselves.
+pub fn execute_timelock_proposal<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteTimelockProposal<'info>>,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.executor.key(),
+        AdminAction::ExecuteTimelockProposal,
+        &ctx.accounts.proposal.key(),
+    )?;
+
     let timelock = &mut ctx.accounts.timelock;
     let proposal = &mut ctx.accounts.proposal;
     let executor = &ctx.accounts.executor;
@@ -70,6 +93,7 @@ pub fn execute_timelock_proposal(ctx: Context<ExecuteTimelockProposal>) -> Resul
     if !proposal.is_ready_for_execution()? {
         return Err(LendingError::TimelockNotReady.into());
     }
+    proposal.sync_delay(Clock::get()?.unix_timestamp)?;
 
     // Check if proposal is expired
     if proposal.is_expired()? {
@@ -82,14 +106,60 @@ pub fn execute_timelock_proposal(ctx: Context<ExecuteTimelockProposal>) -> Resul
     // Remove from active proposals
     timelock.remove_active_proposal(&proposal.key())?;
 
-    msg!("Timelock proposal executed by {}", executor.key());
+    let calls = proposal.calls.clone();
+    if !calls.is_empty() {
+        // Persist the executed status before invoking anything, so a call re-entering
+        // this program sees the proposal as executed, and reload afterwards so the
+        // exit does not overwrite what the calls changed
+        ctx.accounts.timelock.exit(&crate::ID)?;
+        ctx.accounts.proposal.exit(&crate::ID)?;
+        ctx.accounts.admin_audit_log.exit(&crate::ID)?;
+
+        let multisig = ctx.accounts.timelock.multisig;
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[TIMELOCK_SEED, multisig.as_ref(), &[ctx.bumps.timelock]]];
+
+        for call in &calls {
+            let instruction = call.to_instruction();
+            let mut account_infos = Vec::with_capacity(instruction.accounts.len() + 1);
+            for key in instruction
+                .accounts
+                .iter()
+                .map(|meta| &meta.pubkey)
+                .chain(std::iter::once(&instruction.program_id))
+            {
+                let info = ctx
+                    .remaining_accounts
+                    .iter()
+                    .find(|info| info.key == key)
+                    .ok_or(LendingError::InvalidAccount)?;
+                account_infos.push(info.clone());
+            }
+
+            invoke_signed(&instruction, &account_infos, signer_seeds)?;
+        }
+
+        ctx.accounts.timelock.reload()?;
+        ctx.accounts.proposal.reload()?;
+        ctx.accounts.admin_audit_log.reload()?;
+    }
 
-    // The actual operation execution would be handled by specific instruction handlers
+    msg!(
+        "Timelock proposal executed by {} ({} calls)",
+        ctx.accounts.executor.key(),
+        calls.len()
+    );
     Ok(())
 }
 
 /// Cancel a timelock proposal (before execution)
 pub fn cancel_timelock_proposal(ctx: Context<CancelTimelockProposal>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::CancelTimelockProposal,
+        &ctx.accounts.proposal.key(),
+    )?;
+
     let timelock = &mut ctx.accounts.timelock;
     let proposal = &mut ctx.accounts.proposal;
     let authority = &ctx.accounts.authority;
@@ -113,16 +183,58 @@ pub fn cancel_timelock_proposal(ctx: Context<CancelTimelockProposal>) -> Result<
     Ok(())
 }
 
+/// Veto a queued timelock proposal before its delay elapses (guardians only)
+/// Guardians hold only the `GUARDIAN` permission, so they can stop a proposal but
+/// cannot create or execute one.
+pub fn veto_timelock_proposal(ctx: Context<VetoTimelockProposal>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.guardian.key(),
+        AdminAction::VetoTimelockProposal,
+        &ctx.accounts.proposal.key(),
+    )?;
+
+    let timelock = &mut ctx.accounts.timelock;
+    let proposal = &mut ctx.accounts.proposal;
+    let guardian = &ctx.accounts.guardian;
+    let governance = &ctx.accounts.governance;
+
+    PermissionChecker::check_permission(governance, &guardian.key(), Permission::GUARDIAN)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    proposal.veto(now)?;
+
+    // Remove from active proposals
+    timelock.remove_active_proposal(&proposal.key())?;
+
+    emit!(TimelockProposalVetoedEvent {
+        proposal: proposal.key(),
+        controller: timelock.key(),
+        guardian: guardian.key(),
+        operation_type: proposal.operation_type,
+        execution_time: proposal.execution_time,
+        timestamp: now,
+    });
+
+    msg!("Timelock proposal vetoed by guardian {}", guardian.key());
+    Ok(())
+}
+
 /// Update timelock delays (requires multisig + timelock approval)
 pub fn update_timelock_delays(
     ctx: Context<UpdateTimelockDelays>,
     new_delays: Vec<TimelockDelay>,
 ) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.executor.key(),
+        AdminAction::UpdateTimelockDelays,
+        &new_delays,
+    )?;
+
     let timelock = &mut ctx.accounts.timelock;
     let proposal = &ctx.accounts.executed_proposal;
 
     // Verify this is being called through an executed proposal
-    if proposal.status != TimelockStatus::Executed {
+    if proposal.status != ProposalStatus::Executed {
         return Err(LendingError::ProposalNotExecuted.into());
     }
 
@@ -160,19 +272,37 @@ pub fn update_timelock_delays(
 }
 
 /// Clean up expired proposals
-pub fn cleanup_expired_proposals(ctx: Context<CleanupExpiredProposals>) -> Result<()> {
-    let _timelock = &mut ctx.accounts.timelock;
+/// Proposals passed as remaining accounts that are past their expiry are marked
+/// expired and removed from the controller's active list; others are skipped.
+pub fn cleanup_expired_proposals<'info>(
+    ctx: Context<'_, '_, '_, 'info, CleanupExpiredProposals<'info>>,
+) -> Result<()> {
+    let timelock = &mut ctx.accounts.timelock;
     let governance = &ctx.accounts.governance;
     let executor = &ctx.accounts.executor;
+    let now = Clock::get()?.unix_timestamp;
 
     // Check permission (anyone with timelock manager can cleanup)
     PermissionChecker::check_permission(governance, &executor.key(), Permission::TIMELOCK_MANAGER)?;
 
-    // This would iterate through active proposals and mark expired ones
-    // For now, we'll just remove expired proposals from the active list
-    // In a full implementation, this would process remaining accounts
+    let mut expired = 0u32;
+    for proposal_info in ctx.remaining_accounts.iter() {
+        let mut proposal = Account::<TimelockProposal>::try_from(proposal_info)?;
+        if proposal.controller != timelock.key() {
+            return Err(LendingError::ProposalNotFound.into());
+        }
+        if !proposal.is_past_expiry(now) {
+            continue;
+        }
 
-    msg!("Expired proposals cleanup initiated by {}", executor.key());
+        proposal.mark_expired(now)?;
+        // Proposals created before the active list was tracked may be missing from it
+        let _ = timelock.remove_active_proposal(&proposal.key());
+        proposal.exit(&crate::ID)?;
+        expired += 1;
+    }
+
+    msg!("{} expired proposals cleaned up by {}", expired, executor.key());
     Ok(())
 }
 
@@ -215,20 +345,44 @@ pub struct CreateTimelockProposal<'info> {
     #[account(mut)]
     pub proposer: Signer<'info>,
 
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct ExecuteTimelockProposal<'info> {
-    #[account(mut)]
+    /// Timelock controller, which signs the proposal's calls
+    #[account(
+        mut,
+        seeds = [TIMELOCK_SEED, timelock.multisig.as_ref()],
+        bump
+    )]
     pub timelock: Account<'info, TimelockController>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = proposal.controller == timelock.key() @ LendingError::ProposalNotFound
+    )]
     pub proposal: Account<'info, TimelockProposal>,
 
     pub governance: Account<'info, GovernanceRegistry>,
 
     pub executor: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 #[derive(Accounts)]
@@ -242,6 +396,39 @@ pub struct CancelTimelockProposal<'info> {
     pub governance: Account<'info, GovernanceRegistry>,
 
     pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+}
+
+#[derive(Accounts)]
+pub struct VetoTimelockProposal<'info> {
+    #[account(mut)]
+    pub timelock: Account<'info, TimelockController>,
+
+    #[account(
+        mut,
+        constraint = proposal.controller == timelock.key() @ LendingError::ProposalNotFound
+    )]
+    pub proposal: Account<'info, TimelockProposal>,
+
+    pub governance: Account<'info, GovernanceRegistry>,
+
+    /// Holder of the guardian role
+    pub guardian: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 #[derive(Accounts)]
@@ -254,6 +441,14 @@ pub struct UpdateTimelockDelays<'info> {
     pub executed_proposal: Account<'info, TimelockProposal>,
 
     pub executor: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
 }
 
 #[derive(Accounts)]
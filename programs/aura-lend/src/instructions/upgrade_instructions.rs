@@ -5,10 +5,25 @@ use anchor_lang::solana_program::{
     system_instruction,
 };
 
-use crate::{constants::*, error::LendingError, state::market::Market, utils::validate_authority};
+use crate::{
+    constants::*,
+    error::LendingError,
+    state::{
+        audit_log::{AdminAction, AdminAuditLog},
+        market::Market,
+        staged_upgrade::*,
+    },
+    utils::validate_authority,
+};
 
 /// Set the upgrade authority of the program to a new authority (typically MultiSig)
 pub fn set_upgrade_authority(ctx: Context<SetUpgradeAuthority>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.current_authority.key(),
+        AdminAction::SetUpgradeAuthority,
+        &ctx.accounts.new_authority.key(),
+    )?;
+
     let market = &ctx.accounts.market;
     let current_authority = &ctx.accounts.current_authority;
     let new_authority = ctx.accounts.new_authority.key();
@@ -47,8 +62,72 @@ pub fn set_upgrade_authority(ctx: Context<SetUpgradeAuthority>) -> Result<()> {
     Ok(())
 }
 
+/// Stage a program upgrade by recording the buffer, its reviewed program hash and length
+/// The buffer's write authority must already be the staging authority.
+pub fn stage_upgrade(ctx: Context<StageUpgrade>, params: StageUpgradeParams) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::StageUpgrade,
+        &params,
+    )?;
+
+    let market = &ctx.accounts.market;
+    let authority = &ctx.accounts.authority;
+    let buffer_account = ctx.accounts.buffer_account.key();
+    let buffer_len = ctx.accounts.buffer_account.data_len() as u64;
+
+    // Validate that the authority is the market's multisig owner
+    validate_authority(&authority.to_account_info(), &market.multisig_owner)?;
+
+    **ctx.accounts.staged_upgrade = StagedUpgrade::new(
+        market.key(),
+        buffer_account,
+        params.program_hash,
+        authority.key(),
+        Clock::get()?.slot,
+        ctx.bumps.staged_upgrade,
+        buffer_len,
+    )?;
+
+    // Fail early if governance does not control the buffer
+    ctx.accounts
+        .staged_upgrade
+        .verify_buffer(&ctx.accounts.buffer_account.to_account_info())?;
+
+    msg!("Program upgrade staged for buffer: {}", buffer_account);
+    Ok(())
+}
+
+/// Discard a staged upgrade without deploying it
+pub fn cancel_staged_upgrade(ctx: Context<CancelStagedUpgrade>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::CancelStagedUpgrade,
+        &ctx.accounts.staged_upgrade.buffer,
+    )?;
+
+    let market = &ctx.accounts.market;
+
+    // Validate that the authority is the market's multisig owner
+    validate_authority(&ctx.accounts.authority.to_account_info(), &market.multisig_owner)?;
+
+    msg!(
+        "Staged upgrade cancelled for buffer: {}",
+        ctx.accounts.staged_upgrade.buffer
+    );
+    Ok(())
+}
+
 /// Upgrade the program to a new buffer account
+/// The buffer must have been staged with `stage_upgrade`, and its authority and length
+/// must be unchanged since
 pub fn upgrade_program(ctx: Context<UpgradeProgram>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.upgrade_authority.key(),
+        AdminAction::UpgradeProgram,
+        &ctx.accounts.buffer_account.key(),
+    )?;
+
     let market = &ctx.accounts.market;
     let upgrade_authority = &ctx.accounts.upgrade_authority;
     let buffer_account = ctx.accounts.buffer_account.key();
@@ -56,6 +135,11 @@ pub fn upgrade_program(ctx: Context<UpgradeProgram>) -> Result<()> {
     // Validate that the upgrade authority is the market's multisig owner
     validate_authority(&upgrade_authority.to_account_info(), &market.multisig_owner)?;
 
+    // Verify the buffer is still the one staged before deploying it
+    ctx.accounts
+        .staged_upgrade
+        .verify_buffer(&ctx.accounts.buffer_account.to_account_info())?;
+
     // Create the upgrade instruction
     let upgrade_ix = bpf_loader_upgradeable::upgrade(
         &ctx.accounts.program_id.key(),
@@ -91,6 +175,12 @@ pub fn upgrade_program(ctx: Context<UpgradeProgram>) -> Result<()> {
 
 /// Freeze the program (remove upgrade authority permanently)
 pub fn freeze_program(ctx: Context<FreezeProgram>) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.upgrade_authority.key(),
+        AdminAction::FreezeProgram,
+        &ctx.accounts.program_data.key(),
+    )?;
+
     let market = &ctx.accounts.market;
     let upgrade_authority = &ctx.accounts.upgrade_authority;
 
@@ -123,13 +213,21 @@ pub struct SetUpgradeAuthority<'info> {
     #[account(
         seeds = [MARKET_SEED],
         bump,
-        // Multisig owner validation will be done manually
+        // The multisig owner is checked by validate_authority in the instruction
     )]
     pub market: Account<'info, Market>,
 
     /// Current upgrade authority (must be market's multisig owner)
     pub current_authority: Signer<'info>,
 
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
     /// New upgrade authority (can be any account)
     /// CHECK: This can be any account that will become the new upgrade authority
     pub new_authority: UncheckedAccount<'info>,
@@ -139,18 +237,106 @@ pub struct SetUpgradeAuthority<'info> {
     pub program_data: UncheckedAccount<'info>,
 }
 
+#[derive(Accounts)]
+pub struct StageUpgrade<'info> {
+    #[account(
+        seeds = [MARKET_SEED],
+        bump,
+        // The multisig owner is checked by validate_authority in the instruction
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Staged upgrade account to initialize
+    #[account(
+        init,
+        payer = authority,
+        space = StagedUpgrade::SIZE,
+        seeds = [STAGED_UPGRADE_SEED, buffer_account.key().as_ref()],
+        bump
+    )]
+    pub staged_upgrade: Account<'info, StagedUpgrade>,
+
+    /// Buffer account containing the new program code
+    /// CHECK: Ownership and write authority are verified by `verify_buffer`
+    pub buffer_account: UncheckedAccount<'info>,
+
+    /// Authority staging the upgrade (must be market's multisig owner)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelStagedUpgrade<'info> {
+    #[account(
+        seeds = [MARKET_SEED],
+        bump,
+        // The multisig owner is checked by validate_authority in the instruction
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Staged upgrade to discard
+    #[account(
+        mut,
+        has_one = market @ LendingError::InvalidMarketState,
+        close = authority
+    )]
+    pub staged_upgrade: Account<'info, StagedUpgrade>,
+
+    /// Authority cancelling the upgrade (must be market's multisig owner)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+}
+
 #[derive(Accounts)]
 pub struct UpgradeProgram<'info> {
     #[account(
         seeds = [MARKET_SEED],
         bump,
-        // Multisig owner validation will be done manually
+        // The multisig owner is checked by validate_authority in the instruction
     )]
     pub market: Account<'info, Market>,
 
     /// Upgrade authority (must be market's multisig owner)
+    #[account(mut)]
     pub upgrade_authority: Signer<'info>,
 
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Staged upgrade for the buffer (closed once the upgrade executes)
+    #[account(
+        mut,
+        seeds = [STAGED_UPGRADE_SEED, buffer_account.key().as_ref()],
+        bump = staged_upgrade.bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        close = upgrade_authority
+    )]
+    pub staged_upgrade: Account<'info, StagedUpgrade>,
+
     /// Program data account
     #[account(mut)]
     pub program_data: UncheckedAccount<'info>,
@@ -178,13 +364,21 @@ pub struct FreezeProgram<'info> {
     #[account(
         seeds = [MARKET_SEED],
         bump,
-        // Multisig owner validation will be done manually
+        // The multisig owner is checked by validate_authority in the instruction
     )]
     pub market: Account<'info, Market>,
 
     /// Upgrade authority (must be market's multisig owner)
     pub upgrade_authority: Signer<'info>,
 
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
     /// Program data account
     #[account(mut)]
     pub program_data: UncheckedAccount<'info>,
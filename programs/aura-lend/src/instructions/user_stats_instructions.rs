@@ -0,0 +1,48 @@
+use crate::constants::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Create the caller's statistics account for the market
+/// Deposits, borrows, repayments, liquidations and reward claims update it when passed.
+pub fn init_user_stats(ctx: Context<InitUserStats>) -> Result<()> {
+    **ctx.accounts.user_stats = UserStats::new(
+        ctx.accounts.market.key(),
+        ctx.accounts.owner.key(),
+        ctx.bumps.user_stats,
+        Clock::get()?.slot,
+    );
+
+    msg!(
+        "User stats initialized for {} in market {}",
+        ctx.accounts.owner.key(),
+        ctx.accounts.market.key()
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitUserStats<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// User stats account to initialize
+    #[account(
+        init,
+        payer = owner,
+        space = UserStats::SIZE,
+        seeds = [USER_STATS_SEED, market.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    /// Wallet whose activity the account records
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,807 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::instructions::borrowing_instructions::validate_borrow_capacity;
+use crate::instructions::metrics_instructions::aggregate_reserves;
+use crate::state::*;
+use crate::utils::math::health;
+use crate::utils::math::interest::calculate_utilization_rate;
+use crate::utils::{Decimal, OracleManager, ProtocolConfig};
+use anchor_lang::prelude::*;
+
+/// Cheapest reserve to borrow an amount from within an asset class
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BorrowSourceQuote {
+    /// Reserve to borrow from
+    pub reserve: Pubkey,
+
+    /// Borrow rate before the borrow in basis points
+    pub current_borrow_rate_bps: u64,
+
+    /// Borrow rate after the borrow in basis points
+    pub projected_borrow_rate_bps: u64,
+
+    /// Liquidity available in the reserve before the borrow
+    pub available_liquidity: u64,
+}
+
+/// Rates and balances of a reserve with interest accrued to the current slot
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ReserveSummary {
+    /// Reserve summarized
+    pub reserve: Pubkey,
+
+    /// Mint of the reserve's liquidity
+    pub liquidity_mint: Pubkey,
+
+    /// Liquidity available to borrow or withdraw
+    pub available_liquidity: u64,
+
+    /// Liquidity borrowed, including accrued interest
+    pub total_borrows: u64,
+
+    /// Liquidity supplied, including what is borrowed
+    pub total_liquidity: u64,
+
+    /// Utilization in basis points
+    pub utilization_rate_bps: u64,
+
+    /// Annual borrow rate in basis points
+    pub borrow_rate_bps: u64,
+
+    /// Annual supply rate in basis points, net of the protocol fee
+    pub supply_rate_bps: u64,
+
+    /// Borrow rate compounded over a year, in basis points
+    pub borrow_apy_bps: u64,
+
+    /// Supply rate compounded over a year, in basis points
+    pub supply_apy_bps: u64,
+
+    /// Liquidity redeemable per collateral token
+    pub collateral_exchange_rate: Decimal,
+
+    /// Loan-to-value ratio in basis points
+    pub loan_to_value_ratio_bps: u64,
+
+    /// Liquidation threshold in basis points
+    pub liquidation_threshold_bps: u64,
+
+    /// Supply cap (0 for none)
+    pub supply_cap: u64,
+
+    /// Borrow cap (0 for none)
+    pub borrow_cap: u64,
+
+    /// Protocol fees accrued and not yet withdrawn
+    pub accumulated_protocol_fees: u64,
+}
+
+impl ReserveSummary {
+    /// Summarize `reserve` with interest accrued to `slot`, on a copy of it
+    /// Shared with off-chain clients so their figures match the program's.
+    pub fn new(address: Pubkey, reserve: &Reserve, slot: u64, timestamp: u64) -> Result<Self> {
+        let mut reserve = reserve.clone();
+        reserve.accrue_interest(slot, timestamp)?;
+
+        let borrow_rate_bps = reserve.current_borrow_rate_bps()?;
+        let supply_rate_bps = reserve.current_supply_rate_bps()?;
+
+        Ok(Self {
+            reserve: address,
+            liquidity_mint: reserve.liquidity_mint,
+            available_liquidity: reserve.state.available_liquidity,
+            total_borrows: reserve.state.total_borrows,
+            total_liquidity: reserve.state.total_liquidity,
+            utilization_rate_bps: reserve.utilization_rate_bps()?,
+            borrow_rate_bps,
+            supply_rate_bps,
+            borrow_apy_bps: Reserve::annual_yield_bps(borrow_rate_bps)?,
+            supply_apy_bps: Reserve::annual_yield_bps(supply_rate_bps)?,
+            collateral_exchange_rate: reserve.collateral_exchange_rate()?,
+            loan_to_value_ratio_bps: reserve.config.loan_to_value_ratio_bps,
+            liquidation_threshold_bps: reserve.config.liquidation_threshold_bps,
+            supply_cap: reserve.config.supply_cap,
+            borrow_cap: reserve.config.borrow_cap,
+            accumulated_protocol_fees: reserve.state.accumulated_protocol_fees,
+        })
+    }
+}
+
+/// Values and borrowing power of an obligation as of its last refresh
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ObligationSummary {
+    /// Obligation summarized
+    pub obligation: Pubkey,
+
+    /// Owner of the obligation
+    pub owner: Pubkey,
+
+    /// Collateral value (USD)
+    pub deposited_value_usd: Decimal,
+
+    /// Debt value (USD)
+    pub borrowed_value_usd: Decimal,
+
+    /// Debt the collateral supports at its loan-to-value ratios (USD)
+    pub max_borrow_value_usd: Decimal,
+
+    /// Debt that can still be taken on (USD)
+    pub remaining_borrow_value_usd: Decimal,
+
+    /// Debt at which the obligation becomes liquidatable (USD)
+    pub liquidation_threshold_value_usd: Decimal,
+
+    /// Health factor in basis points (saturating; 10_000 is 1.0)
+    pub health_factor_bps: u64,
+
+    /// Whether the obligation can be liquidated
+    pub is_liquidatable: bool,
+
+    /// Share of a borrow one liquidation may repay now, in basis points (0 while
+    /// liquidation is not possible). A repay reserve may override the protocol-wide
+    /// base this is scaled from.
+    pub liquidation_close_factor_bps: u64,
+
+    /// Number of collateral deposits
+    pub deposits_count: u8,
+
+    /// Number of borrows
+    pub borrows_count: u8,
+
+    /// eMode category the obligation opted into (0 for none)
+    pub emode_category: u8,
+
+    /// Slot of the obligation's last update
+    pub last_update_slot: u64,
+}
+
+impl ObligationSummary {
+    /// Summarize `obligation` from the values of its last refresh
+    /// `emode` is the category's parameters when they apply (see `EModeCategory::resolve`).
+    /// `base_close_factor_bps` is the close factor liquidations start from.
+    pub fn new(
+        address: Pubkey,
+        obligation: &Obligation,
+        emode: Option<&EModeParams>,
+        base_close_factor_bps: u64,
+    ) -> Result<Self> {
+        let max_borrow_value_usd = obligation.calculate_max_borrow_value_with(emode)?;
+        let remaining_borrow_value_usd = if max_borrow_value_usd > obligation.borrowed_value_usd {
+            max_borrow_value_usd.try_sub(obligation.borrowed_value_usd)?
+        } else {
+            Decimal::zero()
+        };
+        let health_factor = obligation.calculate_health_factor_with(emode)?;
+        let is_liquidatable = obligation.has_borrows() && health_factor < Decimal::one();
+        let liquidation_close_factor_bps = if is_liquidatable {
+            health::close_factor_bps(
+                base_close_factor_bps,
+                health_factor.to_health_bps_saturating(),
+            )
+        } else {
+            0
+        };
+
+        Ok(Self {
+            obligation: address,
+            owner: obligation.owner,
+            deposited_value_usd: obligation.deposited_value_usd,
+            borrowed_value_usd: obligation.borrowed_value_usd,
+            max_borrow_value_usd,
+            remaining_borrow_value_usd,
+            liquidation_threshold_value_usd: obligation
+                .calculate_liquidation_threshold_value_with(emode)?,
+            health_factor_bps: health_factor.to_health_bps_saturating(),
+            is_liquidatable,
+            liquidation_close_factor_bps,
+            deposits_count: obligation.deposits.len() as u8,
+            borrows_count: obligation.borrows.len() as u8,
+            emode_category: obligation.emode_category,
+            last_update_slot: obligation.last_update_slot,
+        })
+    }
+}
+
+/// Status and USD totals of the market, valued at each reserve's last refresh price
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct MarketSummary {
+    /// Market summarized
+    pub market: Pubkey,
+
+    /// Number of reserves in the market
+    pub reserves_count: u64,
+
+    /// Whether the market is paused
+    pub is_paused: bool,
+
+    /// Whether the market is in emergency mode
+    pub is_emergency: bool,
+
+    /// Whether deposits are disabled
+    pub is_lending_disabled: bool,
+
+    /// Whether borrows are disabled
+    pub is_borrowing_disabled: bool,
+
+    /// Whether liquidations are disabled
+    pub is_liquidation_disabled: bool,
+
+    /// Liquidity supplied across reserves, including what is borrowed (USD)
+    pub total_value_locked_usd: Decimal,
+
+    /// Liquidity borrowed across reserves (USD)
+    pub total_borrowed_usd: Decimal,
+
+    /// Protocol fees accrued and not yet withdrawn (USD)
+    pub protocol_fees_usd: Decimal,
+
+    /// Market-wide utilization in basis points
+    pub utilization_rate_bps: u64,
+}
+
+/// Projected outcome of a borrow or withdrawal, computed without changing any state
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ActionSimulation {
+    /// Liquidity the user would receive: net of the origination fee for borrows, the
+    /// underlying of the collateral for withdrawals
+    pub liquidity_amount: u64,
+
+    /// Obligation health factor afterwards in bps (u64::MAX without debt)
+    pub health_factor_bps: u64,
+
+    /// Reserve utilization afterwards in bps
+    pub utilization_rate_bps: u64,
+
+    /// Reserve borrow rate afterwards in bps
+    pub borrow_rate_bps: u64,
+
+    /// Whether the action would pass the obligation's LTV and health checks
+    pub within_limits: bool,
+}
+
+/// Find the cheapest viable reserve to borrow `amount` from within an asset class
+/// Candidate reserves are passed as remaining_accounts. Reserves that are not of the
+/// class, have borrowing disabled or lack liquidity are skipped; among the rest the
+/// lowest post-borrow rate wins, with deeper liquidity breaking ties.
+pub fn best_borrow_source(
+    ctx: Context<BestBorrowSource>,
+    asset_class: u8,
+    amount: u64,
+) -> Result<BorrowSourceQuote> {
+    let market_key = ctx.accounts.market.key();
+
+    ctx.accounts.config.validate_account_count(ctx.remaining_accounts.len())?;
+
+    if amount < MIN_BORROW_AMOUNT {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    let mut reserves = Vec::with_capacity(ctx.remaining_accounts.len());
+    for reserve_info in ctx.remaining_accounts.iter() {
+        if reserve_info.owner != &crate::ID {
+            return Err(LendingError::InvalidAccountOwner.into());
+        }
+
+        let reserve_data = reserve_info.try_borrow_data()?;
+        let mut reserve_data_slice = reserve_data.as_ref();
+        let reserve = Reserve::try_deserialize(&mut reserve_data_slice)
+            .map_err(|_| LendingError::InvalidAccount)?;
+
+        if reserve.market != market_key {
+            return Err(LendingError::InvalidMarketState.into());
+        }
+        reserves.push((reserve_info.key(), reserve));
+    }
+
+    let quote = select_borrow_source(&reserves, asset_class, amount)?
+        .ok_or(LendingError::InsufficientLiquidity)?;
+
+    msg!(
+        "Best borrow source for class {}: {} ({} -> {} bps)",
+        asset_class,
+        quote.reserve,
+        quote.current_borrow_rate_bps,
+        quote.projected_borrow_rate_bps
+    );
+    Ok(quote)
+}
+
+/// Pick the cheapest viable reserve of `asset_class` for a borrow of `amount`
+fn select_borrow_source(
+    reserves: &[(Pubkey, Reserve)],
+    asset_class: u8,
+    amount: u64,
+) -> Result<Option<BorrowSourceQuote>> {
+    let mut best: Option<BorrowSourceQuote> = None;
+
+    for (key, reserve) in reserves {
+        if reserve.asset_class != asset_class
+            || reserve
+                .config
+                .flags
+                .contains(ReserveConfigFlags::BORROWING_DISABLED)
+            || reserve.state.available_liquidity < amount
+        {
+            continue;
+        }
+
+        let quote = BorrowSourceQuote {
+            reserve: *key,
+            current_borrow_rate_bps: reserve.projected_borrow_rate_bps(0)?,
+            projected_borrow_rate_bps: reserve.projected_borrow_rate_bps(amount)?,
+            available_liquidity: reserve.state.available_liquidity,
+        };
+
+        let is_better = match &best {
+            None => true,
+            Some(current) => {
+                quote.projected_borrow_rate_bps < current.projected_borrow_rate_bps
+                    || (quote.projected_borrow_rate_bps == current.projected_borrow_rate_bps
+                        && quote.available_liquidity > current.available_liquidity)
+            }
+        };
+        if is_better {
+            best = Some(quote);
+        }
+    }
+
+    Ok(best)
+}
+
+/// Debt watermark and repayment record of an obligation (read-only)
+/// Returned as instruction return data for underwriters building on the protocol.
+pub fn get_credit_history(ctx: Context<GetCreditHistory>) -> Result<CreditHistory> {
+    let obligation =
+        AnyObligation::load_checked(&ctx.accounts.obligation, &ctx.accounts.market.key())?;
+    Ok(obligation.credit_history)
+}
+
+/// Maximum collateral withdrawable from a reserve at a minimum health factor (read-only)
+/// `min_health_factor_bps` is at least 10_000 (1.0), the floor withdrawals enforce. The
+/// obligation must be refreshed in the same slot if it has borrows.
+pub fn get_max_withdrawable_collateral(
+    ctx: Context<GetMaxWithdrawableCollateral>,
+    min_health_factor_bps: u64,
+) -> Result<u64> {
+    let obligation =
+        AnyObligation::load_checked(&ctx.accounts.obligation, &ctx.accounts.market.key())?;
+    let clock = Clock::get()?;
+
+    if min_health_factor_bps < BASIS_POINTS_PRECISION {
+        return Err(LendingError::InvalidAmount.into());
+    }
+    if obligation.has_borrows() {
+        obligation.require_refreshed(clock.slot)?;
+    }
+
+    let emode = EModeCategory::resolve(ctx.accounts.emode_category.as_deref(), &obligation, None)?;
+    let max_amount = obligation.max_withdrawable_collateral(
+        &ctx.accounts.withdraw_reserve.key(),
+        min_health_factor_bps,
+        emode.as_ref(),
+    )?;
+
+    msg!(
+        "Max withdrawable from {}: {} collateral tokens",
+        ctx.accounts.withdraw_reserve.key(),
+        max_amount
+    );
+    Ok(max_amount)
+}
+
+/// Rates, utilization and balances of a reserve (read-only)
+/// Interest is accrued to the current slot on a copy of the reserve, so the values match
+/// what the next state-changing instruction would see.
+pub fn get_reserve_summary(ctx: Context<GetReserveSummary>) -> Result<ReserveSummary> {
+    let clock = Clock::get()?;
+    ReserveSummary::new(
+        ctx.accounts.reserve.key(),
+        &ctx.accounts.reserve,
+        clock.slot,
+        clock.unix_timestamp as u64,
+    )
+}
+
+/// Health factor and borrowing power of an obligation (read-only)
+/// The obligation must be refreshed in the same slot if it has borrows.
+pub fn get_obligation_summary(ctx: Context<GetObligationSummary>) -> Result<ObligationSummary> {
+    let obligation =
+        AnyObligation::load_checked(&ctx.accounts.obligation, &ctx.accounts.market.key())?;
+    let clock = Clock::get()?;
+
+    if obligation.has_borrows() {
+        obligation.require_refreshed(clock.slot)?;
+    }
+
+    let emode = EModeCategory::resolve(ctx.accounts.emode_category.as_deref(), &obligation, None)?;
+    ObligationSummary::new(
+        ctx.accounts.obligation.key(),
+        &obligation,
+        emode.as_ref(),
+        ctx.accounts.config.liquidation_close_factor_bps,
+    )
+}
+
+/// Status flags and USD totals of the market (read-only)
+/// Every reserve of the market is passed as remaining_accounts, each refreshed within
+/// `MAX_ORACLE_STALENESS_SLOTS`, exactly as for `update_protocol_metrics`.
+pub fn get_market_summary<'info>(
+    ctx: Context<'_, '_, '_, 'info, GetMarketSummary<'info>>,
+) -> Result<MarketSummary> {
+    let market = &ctx.accounts.market;
+    let clock = Clock::get()?;
+    let totals = aggregate_reserves(ctx.remaining_accounts, market, clock.slot)?;
+
+    Ok(MarketSummary {
+        market: market.key(),
+        reserves_count: market.reserves_count,
+        is_paused: market.is_paused(),
+        is_emergency: market.is_emergency(),
+        is_lending_disabled: market.is_lending_disabled(),
+        is_borrowing_disabled: market.is_borrowing_disabled(),
+        is_liquidation_disabled: market.is_liquidation_disabled(),
+        utilization_rate_bps: calculate_utilization_rate(
+            totals.borrowed_usd.try_floor_u64()?,
+            totals.tvl_usd.try_floor_u64()?,
+        )?,
+        total_value_locked_usd: totals.tvl_usd,
+        total_borrowed_usd: totals.borrowed_usd,
+        protocol_fees_usd: totals.fees_usd,
+    })
+}
+
+/// Preview a borrow of `liquidity_amount` without changing state (read-only)
+/// Prices come from the reserve's oracles as for `borrow_obligation_liquidity`, and the
+/// obligation must be refreshed in the same slot. `within_limits` is false where the
+/// borrow would fail the LTV buffer or minimum health factor.
+pub fn simulate_borrow(
+    ctx: Context<SimulateBorrow>,
+    liquidity_amount: u64,
+) -> Result<ActionSimulation> {
+    let obligation =
+        AnyObligation::load_checked(&ctx.accounts.obligation, &ctx.accounts.market.key())?;
+    let clock = Clock::get()?;
+
+    if liquidity_amount < MIN_BORROW_AMOUNT {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+    obligation.require_refreshed(clock.slot)?;
+
+    let mut reserve = (*ctx.accounts.borrow_reserve).clone();
+    reserve.accrue_interest(clock.slot, clock.unix_timestamp as u64)?;
+    let (utilization_rate_bps, borrow_rate_bps) =
+        reserve.projected_rates_after_borrow(liquidity_amount)?;
+
+    let oracle_price = OracleManager::get_checked_price(
+        &ctx.accounts.price_oracle.to_account_info(),
+        ctx.accounts.secondary_price_oracle.as_deref(),
+        &reserve,
+    )?;
+    oracle_price.validate(clock.unix_timestamp)?;
+    let borrow_value_usd = OracleManager::calculate_usd_value(
+        liquidity_amount,
+        &oracle_price,
+        reserve.config.decimals,
+    )?;
+
+    let emode = EModeCategory::resolve(
+        ctx.accounts.emode_category.as_deref(),
+        &obligation,
+        Some(&ctx.accounts.borrow_reserve.key()),
+    )?;
+    let within_capacity =
+        validate_borrow_capacity(&obligation, borrow_value_usd, emode.as_ref()).is_ok();
+    let within_limits = within_capacity && reserve.validate_borrow_cap(liquidity_amount).is_ok();
+
+    let mut simulated = Obligation::clone(&obligation);
+    simulated.borrowed_value_usd = simulated.borrowed_value_usd.try_add(borrow_value_usd)?;
+
+    Ok(ActionSimulation {
+        liquidity_amount: liquidity_amount
+            .checked_sub(reserve.borrow_fee(liquidity_amount)?)
+            .ok_or(LendingError::MathUnderflow)?,
+        health_factor_bps: simulated
+            .calculate_health_factor_with(emode.as_ref())?
+            .to_health_bps_saturating(),
+        utilization_rate_bps,
+        borrow_rate_bps,
+        within_limits,
+    })
+}
+
+/// Preview a withdrawal of `collateral_amount` collateral tokens without changing state
+/// (read-only)
+/// The reserve projections assume the collateral is then redeemed for liquidity, as in
+/// the usual withdraw-and-redeem flow. An obligation with borrows must be refreshed in
+/// the same slot. `within_limits` is false where the withdrawal would leave the
+/// obligation unhealthy.
+pub fn simulate_withdraw(
+    ctx: Context<SimulateWithdraw>,
+    collateral_amount: u64,
+) -> Result<ActionSimulation> {
+    let obligation =
+        AnyObligation::load_checked(&ctx.accounts.obligation, &ctx.accounts.market.key())?;
+    let clock = Clock::get()?;
+    let reserve_key = ctx.accounts.withdraw_reserve.key();
+
+    if collateral_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+    if obligation.has_borrows() {
+        obligation.require_refreshed(clock.slot)?;
+    }
+
+    let mut reserve = (*ctx.accounts.withdraw_reserve).clone();
+    reserve.accrue_interest(clock.slot, clock.unix_timestamp as u64)?;
+    let liquidity_amount = reserve.collateral_to_liquidity(collateral_amount)?;
+    let (utilization_rate_bps, borrow_rate_bps) =
+        reserve.projected_rates_after_redeem(liquidity_amount)?;
+
+    let oracle_price =
+        OracleManager::get_price(&ctx.accounts.price_oracle.to_account_info(), &reserve)?;
+    oracle_price.validate(clock.unix_timestamp)?;
+    let withdrawn_value_usd = reserve.collateral_value_usd(collateral_amount, &oracle_price)?;
+
+    // Take the withdrawn value off the deposit as well, so the weighted collateral drops
+    let mut simulated = Obligation::clone(&obligation);
+    let deposit = simulated
+        .find_collateral_deposit_mut(&reserve_key)
+        .ok_or(LendingError::ObligationReserveNotFound)?;
+    deposit.market_value_usd = deposit
+        .market_value_usd
+        .try_sub(withdrawn_value_usd.min(deposit.market_value_usd))?;
+    simulated.remove_collateral_deposit(&reserve_key, collateral_amount)?;
+    simulated.deposited_value_usd = simulated
+        .deposited_value_usd
+        .try_sub(withdrawn_value_usd.min(simulated.deposited_value_usd))?;
+
+    let emode = EModeCategory::resolve(ctx.accounts.emode_category.as_deref(), &simulated, None)?;
+    let within_limits = !simulated.has_borrows() || simulated.is_healthy_with(emode.as_ref())?;
+
+    Ok(ActionSimulation {
+        liquidity_amount,
+        health_factor_bps: simulated
+            .calculate_health_factor_with(emode.as_ref())?
+            .to_health_bps_saturating(),
+        utilization_rate_bps,
+        borrow_rate_bps,
+        within_limits,
+    })
+}
+
+#[derive(Accounts)]
+pub struct BestBorrowSource<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol configuration (remaining account cap)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    // Note: candidate reserves are passed as remaining_accounts
+}
+
+#[derive(Accounts)]
+pub struct GetCreditHistory<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation account in any layout
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    pub obligation: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetMaxWithdrawableCollateral<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation account in any layout
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Reserve the collateral would be withdrawn from
+    #[account(has_one = market @ LendingError::InvalidMarketState)]
+    pub withdraw_reserve: Account<'info, Reserve>,
+
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+}
+
+#[derive(Accounts)]
+pub struct GetReserveSummary<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve to summarize
+    #[account(has_one = market @ LendingError::InvalidMarketState)]
+    pub reserve: Account<'info, Reserve>,
+}
+
+#[derive(Accounts)]
+pub struct GetObligationSummary<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol configuration (liquidation close factor)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Obligation account in any layout
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    pub obligation: UncheckedAccount<'info>,
+
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+}
+
+#[derive(Accounts)]
+pub struct GetMarketSummary<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+    // Note: every reserve of the market is passed as remaining_accounts
+}
+
+#[derive(Accounts)]
+pub struct SimulateBorrow<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation account in any layout
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Reserve the liquidity would be borrowed from
+    #[account(has_one = market @ LendingError::InvalidMarketState)]
+    pub borrow_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for the borrow asset
+    /// CHECK: Matched against the borrow reserve's price_oracle
+    #[account(address = borrow_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub price_oracle: UncheckedAccount<'info>,
+
+    /// Secondary oracle of the borrow asset (required when it has one)
+    /// CHECK: Matched against the reserve's secondary_oracle when prices are read
+    pub secondary_price_oracle: Option<UncheckedAccount<'info>>,
+
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+}
+
+#[derive(Accounts)]
+pub struct SimulateWithdraw<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Obligation account in any layout
+    /// CHECK: Loaded through AnyObligation, which validates owner, layout and market
+    pub obligation: UncheckedAccount<'info>,
+
+    /// Reserve the collateral would be withdrawn from
+    #[account(has_one = market @ LendingError::InvalidMarketState)]
+    pub withdraw_reserve: Account<'info, Reserve>,
+
+    /// Price oracle for the collateral asset
+    /// CHECK: Matched against the withdraw reserve's price_oracle
+    #[account(address = withdraw_reserve.price_oracle @ LendingError::OracleAccountMismatch)]
+    pub price_oracle: UncheckedAccount<'info>,
+
+    /// eMode category the obligation opted into (required when it has one)
+    pub emode_category: Option<Account<'info, EModeCategory>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reserve(asset_class: u8, available_liquidity: u64) -> Reserve {
+        let config = ReserveConfig {
+            base_borrow_rate_bps: 200,
+            borrow_rate_multiplier_bps: 1000,
+            jump_rate_multiplier_bps: 10000,
+            optimal_utilization_rate_bps: 8000,
+            ..ReserveConfig::default()
+        };
+
+        let mut reserve = Reserve {
+            version: PROGRAM_VERSION,
+            market: Pubkey::default(),
+            liquidity_mint: Pubkey::new_unique(),
+            collateral_mint: Pubkey::new_unique(),
+            liquidity_supply: Pubkey::new_unique(),
+            fee_receiver: Pubkey::new_unique(),
+            price_oracle: Pubkey::new_unique(),
+            oracle_feed_id: [0; 32],
+            config,
+            state: ReserveState::default(),
+            last_update_timestamp: 0,
+            last_update_slot: 0,
+            fee_split: FeeSplit::default(),
+            asset_class,
+            oracle_source: OracleSource::Pyth,
+            fixed_price: 0,
+            secondary_oracle: Pubkey::default(),
+            secondary_oracle_source: OracleSource::Pyth,
+            max_oracle_deviation_bps: 0,
+            twap_price: 0,
+            twap_exponent: 0,
+            twap_last_update_timestamp: 0,
+            twap_window_seconds: 0,
+            deprecation_start_timestamp: 0,
+            deprecation_deadline_timestamp: 0,
+            deleverage_penalty_bps: 0,
+            debt_ceiling_usd: 0,
+            isolation_mode_total_debt: 0,
+            surge_utilization_threshold_bps: 0,
+            surge_duration_slots: 0,
+            surge_withdrawal_fee_bps: 0,
+            high_utilization_since_slot: 0,
+            total_surge_fees: 0,
+            emergency_pause_flags: ReserveConfigFlags::default(),
+            derived_oracle: DerivedOracle::default(),
+            reserved: [0; 8],
+        };
+        reserve.state.available_liquidity = available_liquidity;
+        reserve
+    }
+
+    #[test]
+    fn test_selects_cheapest_viable_reserve_in_class() {
+        let deep = Pubkey::new_unique();
+        let shallow = Pubkey::new_unique();
+        let other_class = Pubkey::new_unique();
+        let reserves = [
+            (shallow, reserve(1, 120_000)),
+            (deep, reserve(1, 1_000_000)),
+            (other_class, reserve(2, 10_000_000)),
+        ];
+
+        // The deeper pool ends up less utilized, so it is cheaper after the borrow
+        let quote = select_borrow_source(&reserves, 1, 100_000).unwrap().unwrap();
+        assert_eq!(quote.reserve, deep);
+        assert_eq!(quote.current_borrow_rate_bps, 200);
+        let shallow_rate = reserves[0].1.projected_borrow_rate_bps(100_000).unwrap();
+        assert!(quote.projected_borrow_rate_bps < shallow_rate);
+
+        // Reserves without enough liquidity are not viable
+        assert!(select_borrow_source(&reserves, 1, 2_000_000).unwrap().is_none());
+        assert!(select_borrow_source(&reserves, 3, 100_000).unwrap().is_none());
+    }
+}
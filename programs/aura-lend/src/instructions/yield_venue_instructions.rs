@@ -0,0 +1,532 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::*;
+use crate::utils::{require_admin, ProtocolConfig, ReserveLock, SwapExecutor};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Whitelist an external yield venue for a reserve's idle liquidity (governance only)
+/// The venue's receipt tokens are held in a new position account owned by the
+/// reserve's liquidity supply authority.
+pub fn add_yield_venue(ctx: Context<AddYieldVenue>, params: AddYieldVenueParams) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::AddYieldVenue,
+        &(
+            ctx.accounts.reserve.key(),
+            ctx.accounts.adapter_program.key(),
+            params.clone(),
+        ),
+    )?;
+
+    let market = &ctx.accounts.market;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RESERVE_MANAGER,
+    )?;
+
+    **ctx.accounts.yield_venue = YieldVenue::new(
+        market.key(),
+        ctx.accounts.reserve.key(),
+        ctx.accounts.adapter_program.key(),
+        ctx.accounts.receipt_mint.key(),
+        ctx.accounts.position_account.key(),
+        params.max_allocation_bps,
+        params.max_slippage_bps,
+        ctx.bumps.yield_venue,
+    )?;
+
+    msg!(
+        "Yield venue {} added for reserve {} - max allocation: {} bps, max slippage: {} bps",
+        ctx.accounts.adapter_program.key(),
+        ctx.accounts.reserve.key(),
+        params.max_allocation_bps,
+        params.max_slippage_bps
+    );
+    Ok(())
+}
+
+/// Update a yield venue's allocation, loss bound or status (governance only)
+/// Lowering the allocation below the invested amount only blocks further investment;
+/// keepers divest the excess.
+pub fn update_yield_venue(
+    ctx: Context<UpdateYieldVenue>,
+    params: UpdateYieldVenueParams,
+) -> Result<()> {
+    ctx.accounts.admin_audit_log.record(
+        ctx.accounts.authority.key(),
+        AdminAction::UpdateYieldVenue,
+        &(ctx.accounts.yield_venue.key(), params.clone()),
+    )?;
+
+    let market = &ctx.accounts.market;
+    let yield_venue = &mut ctx.accounts.yield_venue;
+
+    require_admin(
+        &ctx.accounts.authority.to_account_info(),
+        &market.multisig_owner,
+        ctx.accounts.governance.as_deref(),
+        Permission::RESERVE_MANAGER,
+    )?;
+
+    if let Some(max_allocation_bps) = params.max_allocation_bps {
+        YieldVenue::validate_max_allocation_bps(max_allocation_bps)?;
+        yield_venue.max_allocation_bps = max_allocation_bps;
+    }
+
+    if let Some(max_slippage_bps) = params.max_slippage_bps {
+        YieldVenue::validate_max_slippage_bps(max_slippage_bps)?;
+        yield_venue.max_slippage_bps = max_slippage_bps;
+    }
+
+    if let Some(enabled) = params.enabled {
+        yield_venue.enabled = enabled;
+    }
+
+    msg!(
+        "Yield venue {} updated - max allocation: {} bps, max slippage: {} bps, enabled: {}",
+        yield_venue.key(),
+        yield_venue.max_allocation_bps,
+        yield_venue.max_slippage_bps,
+        yield_venue.enabled
+    );
+    Ok(())
+}
+
+/// Invest up to `amount` of a reserve's idle liquidity in a yield venue (keeper)
+/// Requires the `RESERVE_MANAGER` permission. The adapter route accounts are passed as
+/// remaining_accounts and forwarded with `route_data`; the liquidity supply authority
+/// signs for the liquidity leaving the reserve. The venue's investment may not exceed
+/// its allocation of the reserve's total liquidity, accumulated protocol fees stay in
+/// the reserve, and at least `min_receipt_amount` receipt tokens must be received.
+pub fn invest_idle_liquidity<'info>(
+    ctx: Context<'_, '_, '_, 'info, InvestIdleLiquidity<'info>>,
+    amount: u64,
+    min_receipt_amount: u64,
+    route_data: Vec<u8>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    if ctx.accounts.market.is_paused() {
+        return Err(LendingError::MarketPaused.into());
+    }
+
+    PermissionChecker::check_permission(
+        &ctx.accounts.governance,
+        &ctx.accounts.keeper.key(),
+        Permission::RESERVE_MANAGER,
+    )?;
+    ctx.accounts
+        .config
+        .validate_account_count(ctx.remaining_accounts.len())?;
+    SwapExecutor::validate_program(
+        &ctx.accounts.adapter_program.key(),
+        &[ctx.accounts.yield_venue.adapter_program],
+    )?;
+
+    let reserve = &mut ctx.accounts.reserve;
+    let yield_venue = &mut ctx.accounts.yield_venue;
+
+    if !yield_venue.enabled {
+        return Err(LendingError::YieldVenueDisabled.into());
+    }
+    if amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+
+    // Measure the allocation against liquidity including interest up to now
+    reserve.update_interest(clock.slot)?;
+    if amount > yield_venue.allocation_headroom(reserve.state.total_liquidity)? {
+        return Err(LendingError::YieldAllocationExceeded.into());
+    }
+    if amount > reserve.investable_liquidity() {
+        return Err(LendingError::InsufficientLiquidity.into());
+    }
+
+    // Hold the reentrancy lock until the reserve state is written back, so the adapter
+    // cannot call back into the reserve
+    let mut reserve = ReserveLock::acquire(reserve)?;
+
+    let authority_key = ctx.accounts.liquidity_supply_authority.key();
+    let authority_seeds = &[
+        LIQUIDITY_TOKEN_SEED,
+        reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.liquidity_supply_authority],
+    ];
+
+    let result = SwapExecutor::execute(
+        &ctx.accounts.adapter_program.to_account_info(),
+        ctx.remaining_accounts,
+        route_data,
+        Some(&authority_key),
+        &[authority_seeds],
+        &mut ctx.accounts.liquidity_supply,
+        &mut ctx.accounts.position_account,
+        amount,
+        std::cmp::max(min_receipt_amount, 1),
+    )?;
+
+    reserve.invest_liquidity(result.amount_in)?;
+    yield_venue.record_investment(result.amount_in, clock.slot)?;
+
+    msg!(
+        "Invested {} liquidity of reserve {} for {} receipt tokens (venue now holds {})",
+        result.amount_in,
+        reserve.key(),
+        result.amount_out,
+        yield_venue.invested_amount
+    );
+    Ok(())
+}
+
+/// Divest `receipt_amount` of a yield venue's receipt tokens back into the reserve (keeper)
+/// Requires the `RESERVE_MANAGER` permission and runs while the market is paused, since
+/// it only restores liquidity. The cost basis released is proportional to the receipt
+/// tokens spent. A return above it is realized as yield and split like interest; a
+/// return below it is a loss borne by suppliers and may not exceed the venue's
+/// `max_slippage_bps`.
+pub fn divest_invested_liquidity<'info>(
+    ctx: Context<'_, '_, '_, 'info, DivestInvestedLiquidity<'info>>,
+    receipt_amount: u64,
+    min_amount_out: u64,
+    route_data: Vec<u8>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    PermissionChecker::check_permission(
+        &ctx.accounts.governance,
+        &ctx.accounts.keeper.key(),
+        Permission::RESERVE_MANAGER,
+    )?;
+    ctx.accounts
+        .config
+        .validate_account_count(ctx.remaining_accounts.len())?;
+    SwapExecutor::validate_program(
+        &ctx.accounts.adapter_program.key(),
+        &[ctx.accounts.yield_venue.adapter_program],
+    )?;
+
+    let receipt_balance = ctx.accounts.position_account.amount;
+    if receipt_amount == 0 {
+        return Err(LendingError::AmountTooSmall.into());
+    }
+    if receipt_amount > receipt_balance {
+        return Err(LendingError::InsufficientTokenBalance.into());
+    }
+
+    let reserve = &mut ctx.accounts.reserve;
+    let yield_venue = &mut ctx.accounts.yield_venue;
+
+    // Realize the result against liquidity including interest up to now
+    reserve.update_interest(clock.slot)?;
+
+    // Hold the reentrancy lock until the reserve state is written back, so the adapter
+    // cannot call back into the reserve
+    let mut reserve = ReserveLock::acquire(reserve)?;
+
+    let authority_key = ctx.accounts.liquidity_supply_authority.key();
+    let authority_seeds = &[
+        LIQUIDITY_TOKEN_SEED,
+        reserve.liquidity_mint.as_ref(),
+        b"authority",
+        &[ctx.bumps.liquidity_supply_authority],
+    ];
+
+    let result = SwapExecutor::execute(
+        &ctx.accounts.adapter_program.to_account_info(),
+        ctx.remaining_accounts,
+        route_data,
+        Some(&authority_key),
+        &[authority_seeds],
+        &mut ctx.accounts.position_account,
+        &mut ctx.accounts.liquidity_supply,
+        receipt_amount,
+        min_amount_out,
+    )?;
+
+    let cost_basis = yield_venue.cost_basis_of(result.amount_in, receipt_balance)?;
+    if result.amount_out < yield_venue.min_divest_return(cost_basis)? {
+        return Err(LendingError::SlippageExceeded.into());
+    }
+
+    let protocol_fee = reserve.divest_liquidity(cost_basis, result.amount_out)?;
+    yield_venue.record_divestment(cost_basis, result.amount_out, clock.slot)?;
+
+    msg!(
+        "Divested {} receipt tokens for {} liquidity of reserve {} - cost basis: {}, fee: {}",
+        result.amount_in,
+        result.amount_out,
+        reserve.key(),
+        cost_basis,
+        protocol_fee
+    );
+    Ok(())
+}
+
+// Context structs for yield venue instructions
+
+#[derive(Accounts)]
+pub struct AddYieldVenue<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reserve whose liquidity the venue may hold
+    #[account(
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Yield venue account to initialize
+    #[account(
+        init,
+        payer = payer,
+        space = YieldVenue::SIZE,
+        seeds = [YIELD_VENUE_SEED, reserve.key().as_ref(), adapter_program.key().as_ref()],
+        bump
+    )]
+    pub yield_venue: Account<'info, YieldVenue>,
+
+    /// Program moving liquidity into and out of the venue
+    /// CHECK: Only its address is recorded; governance vets the program
+    #[account(executable)]
+    pub adapter_program: UncheckedAccount<'info>,
+
+    /// Mint of the venue's receipt tokens
+    #[account(
+        constraint = receipt_mint.key() != reserve.liquidity_mint @ LendingError::TokenMintMismatch
+    )]
+    pub receipt_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token account holding the receipt tokens
+    #[account(
+        init,
+        payer = payer,
+        token::mint = receipt_mint,
+        token::authority = liquidity_supply_authority,
+        token::token_program = receipt_token_program,
+        seeds = [YIELD_VENUE_SEED, yield_venue.key().as_ref(), b"position"],
+        bump
+    )]
+    pub position_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Payer for account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Token program of the receipt mint
+    pub receipt_token_program: Interface<'info, TokenInterface>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateYieldVenue<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Yield venue account
+    #[account(
+        mut,
+        seeds = [
+            YIELD_VENUE_SEED,
+            yield_venue.reserve.as_ref(),
+            yield_venue.adapter_program.as_ref()
+        ],
+        bump = yield_venue.bump,
+        has_one = market @ LendingError::InvalidMarketState
+    )]
+    pub yield_venue: Account<'info, YieldVenue>,
+
+    /// Market multisig owner
+    pub authority: Signer<'info>,
+
+    /// Admin audit log recording this action
+    #[account(
+        mut,
+        seeds = [ADMIN_AUDIT_LOG_SEED],
+        bump = admin_audit_log.bump
+    )]
+    pub admin_audit_log: Account<'info, AdminAuditLog>,
+
+    /// Governance registry granting the authority its role (not needed for the multisig)
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Option<Account<'info, GovernanceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct InvestIdleLiquidity<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Governance registry granting the keeper its permission
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Account<'info, GovernanceRegistry>,
+
+    /// Protocol configuration (remaining account cap)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Reserve whose liquidity is invested
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Yield venue receiving the liquidity
+    #[account(
+        mut,
+        seeds = [YIELD_VENUE_SEED, reserve.key().as_ref(), yield_venue.adapter_program.as_ref()],
+        bump = yield_venue.bump,
+        has_one = position_account @ LendingError::InvalidAccount
+    )]
+    pub yield_venue: Account<'info, YieldVenue>,
+
+    /// Reserve liquidity supply token account
+    #[account(mut)]
+    pub liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token account receiving the venue's receipt tokens
+    #[account(mut)]
+    pub position_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Venue's adapter program executing the route
+    /// CHECK: Validated against the yield venue's adapter_program
+    pub adapter_program: UncheckedAccount<'info>,
+
+    /// Keeper holding the reserve manager permission
+    pub keeper: Signer<'info>,
+    // Note: adapter route accounts are passed as remaining_accounts
+}
+
+#[derive(Accounts)]
+pub struct DivestInvestedLiquidity<'info> {
+    /// Market account
+    #[account(
+        seeds = [MARKET_SEED],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Governance registry granting the keeper its permission
+    #[account(
+        seeds = [GOVERNANCE_SEED, market.multisig_owner.as_ref()],
+        bump
+    )]
+    pub governance: Account<'info, GovernanceRegistry>,
+
+    /// Protocol configuration (remaining account cap)
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// Reserve receiving the liquidity back
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.liquidity_mint.as_ref()],
+        bump,
+        has_one = market @ LendingError::InvalidMarketState,
+        has_one = liquidity_supply @ LendingError::InvalidAccount
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    /// Yield venue the liquidity is divested from
+    #[account(
+        mut,
+        seeds = [YIELD_VENUE_SEED, reserve.key().as_ref(), yield_venue.adapter_program.as_ref()],
+        bump = yield_venue.bump,
+        has_one = position_account @ LendingError::InvalidAccount
+    )]
+    pub yield_venue: Account<'info, YieldVenue>,
+
+    /// Reserve liquidity supply token account
+    #[account(mut)]
+    pub liquidity_supply: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token account holding the venue's receipt tokens
+    #[account(mut)]
+    pub position_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Liquidity supply authority (PDA)
+    /// CHECK: This is validated by the seeds constraint
+    #[account(
+        seeds = [LIQUIDITY_TOKEN_SEED, reserve.liquidity_mint.as_ref(), b"authority"],
+        bump
+    )]
+    pub liquidity_supply_authority: UncheckedAccount<'info>,
+
+    /// Venue's adapter program executing the route
+    /// CHECK: Validated against the yield venue's adapter_program
+    pub adapter_program: UncheckedAccount<'info>,
+
+    /// Keeper holding the reserve manager permission
+    pub keeper: Signer<'info>,
+    // Note: adapter route accounts are passed as remaining_accounts
+}
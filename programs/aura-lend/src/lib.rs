@@ -3,18 +3,39 @@ use anchor_lang::prelude::*;
 // Module declarations in alphabetical order
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod instructions;
 pub mod migration;
 pub mod state;
 pub mod utils;
 
 use instructions::*;
+use state::any_obligation::ObligationLayout;
+use state::authority_transfer::AuthorityKind;
+use state::backstop::{InitializeBackstopVaultParams, UpdateBackstopVaultParams};
+use state::credit_line::{DelegateBorrowingPowerParams, OpenCreditLineParams};
+use state::emode::EModeCategoryParams;
+use state::fee_converter::{InitializeFeeConverterParams, UpdateFeeConverterParams};
 use state::governance::{GrantRoleParams, InitializeGovernanceParams};
+use state::keeper::{InitializeKeeperRegistryParams, UpdateKeeperRegistryParams};
+use state::lookup_table::SetMarketLookupTableParams;
 use state::market::InitializeMarketParams;
 use state::multisig::{CreateProposalParams, InitializeMultisigParams};
-use state::reserve::{InitializeReserveParams, UpdateReserveConfigParams};
+use state::obligation::CreditHistory;
+use state::operation_receipt::OperationReceipt;
+use state::order_book::PlaceCollateralOrderParams;
+use state::rent_reclaim::GrantRentReclaimConsentParams;
+use state::rewards::RewardSide;
+use state::reserve::{
+    DeprecateReserveParams, InitializeReserveParams, InterestRateStrategy, ReserveConfigFlags,
+    UpdateReserveCapsParams, UpdateReserveConfigParams, UpdateReserveFeeSplitParams,
+    UpdateReserveSecondaryOracleParams, UpdateReserveSurgeFeeParams, UpdateReserveTwapWindowParams,
+};
+use state::staged_upgrade::StageUpgradeParams;
+use state::sub_account::{MoveSubAccountPositionParams, SubAccountHealth};
 use state::timelock::CreateTimelockProposalParams;
 use state::timelock::TimelockDelay;
+use state::yield_venue::{AddYieldVenueParams, UpdateYieldVenueParams};
 
 declare_id!("AuRa1Lend1111111111111111111111111111111111");
 
@@ -30,6 +51,24 @@ pub mod aura_lend {
         instructions::initialize_market(ctx, params)
     }
 
+    pub fn initialize_admin_audit_log(ctx: Context<InitializeAdminAuditLog>) -> Result<()> {
+        instructions::initialize_admin_audit_log(ctx)
+    }
+
+    pub fn initialize_market_lookup_table(
+        ctx: Context<InitializeMarketLookupTable>,
+        params: SetMarketLookupTableParams,
+    ) -> Result<()> {
+        instructions::initialize_market_lookup_table(ctx, params)
+    }
+
+    pub fn set_market_lookup_table(
+        ctx: Context<SetMarketLookupTable>,
+        params: SetMarketLookupTableParams,
+    ) -> Result<()> {
+        instructions::set_market_lookup_table(ctx, params)
+    }
+
     // RBAC Management - MultiSig operations
     pub fn initialize_multisig(
         ctx: Context<InitializeMultisig>,
@@ -49,8 +88,11 @@ pub mod aura_lend {
         instructions::sign_multisig_proposal(ctx)
     }
 
-    pub fn execute_multisig_proposal(ctx: Context<ExecuteMultisigProposal>) -> Result<()> {
-        instructions::execute_multisig_proposal(ctx)
+    pub fn execute_multisig_proposal(
+        ctx: Context<ExecuteMultisigProposal>,
+        payload: Vec<u8>,
+    ) -> Result<()> {
+        instructions::execute_multisig_proposal(ctx, payload)
     }
 
     pub fn cancel_multisig_proposal(ctx: Context<CancelMultisigProposal>) -> Result<()> {
@@ -76,7 +118,9 @@ pub mod aura_lend {
         instructions::create_timelock_proposal(ctx, params)
     }
 
-    pub fn execute_timelock_proposal(ctx: Context<ExecuteTimelockProposal>) -> Result<()> {
+    pub fn execute_timelock_proposal<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteTimelockProposal<'info>>,
+    ) -> Result<()> {
         instructions::execute_timelock_proposal(ctx)
     }
 
@@ -84,6 +128,10 @@ pub mod aura_lend {
         instructions::cancel_timelock_proposal(ctx)
     }
 
+    pub fn veto_timelock_proposal(ctx: Context<VetoTimelockProposal>) -> Result<()> {
+        instructions::veto_timelock_proposal(ctx)
+    }
+
     pub fn update_timelock_delays(
         ctx: Context<UpdateTimelockDelays>,
         new_delays: Vec<TimelockDelay>,
@@ -91,7 +139,9 @@ pub mod aura_lend {
         instructions::update_timelock_delays(ctx, new_delays)
     }
 
-    pub fn cleanup_expired_proposals(ctx: Context<CleanupExpiredProposals>) -> Result<()> {
+    pub fn cleanup_expired_proposals<'info>(
+        ctx: Context<'_, '_, '_, 'info, CleanupExpiredProposals<'info>>,
+    ) -> Result<()> {
         instructions::cleanup_expired_proposals(ctx)
     }
 
@@ -144,6 +194,10 @@ pub mod aura_lend {
         instructions::initialize_reserve(ctx, params)
     }
 
+    pub fn close_reserve(ctx: Context<CloseReserve>) -> Result<()> {
+        instructions::close_reserve(ctx)
+    }
+
     pub fn update_reserve_config(
         ctx: Context<UpdateReserveConfig>,
         params: UpdateReserveConfigParams,
@@ -151,60 +205,752 @@ pub mod aura_lend {
         instructions::update_reserve_config(ctx, params)
     }
 
+    pub fn update_reserve_caps(
+        ctx: Context<UpdateReserveCaps>,
+        params: UpdateReserveCapsParams,
+    ) -> Result<()> {
+        instructions::update_reserve_caps(ctx, params)
+    }
+
+    pub fn update_reserve_secondary_oracle(
+        ctx: Context<UpdateReserveSecondaryOracle>,
+        params: UpdateReserveSecondaryOracleParams,
+    ) -> Result<()> {
+        instructions::update_reserve_secondary_oracle(ctx, params)
+    }
+
+    pub fn update_reserve_derived_oracle(
+        ctx: Context<UpdateReserveDerivedOracle>,
+        params: UpdateReserveDerivedOracleParams,
+    ) -> Result<()> {
+        instructions::update_reserve_derived_oracle(ctx, params)
+    }
+
+    pub fn update_reserve_twap_window(
+        ctx: Context<UpdateReserveTwapWindow>,
+        params: UpdateReserveTwapWindowParams,
+    ) -> Result<()> {
+        instructions::update_reserve_twap_window(ctx, params)
+    }
+
+    pub fn update_reserve_surge_fee(
+        ctx: Context<UpdateReserveSurgeFee>,
+        params: UpdateReserveSurgeFeeParams,
+    ) -> Result<()> {
+        instructions::update_reserve_surge_fee(ctx, params)
+    }
+
+    pub fn set_reserve_interest_rate_strategy(
+        ctx: Context<SetReserveInterestRateStrategy>,
+        strategy: InterestRateStrategy,
+    ) -> Result<()> {
+        instructions::set_reserve_interest_rate_strategy(ctx, strategy)
+    }
+
+    pub fn pause_reserve_operations(
+        ctx: Context<PauseReserveOperations>,
+        operations: ReserveConfigFlags,
+    ) -> Result<()> {
+        instructions::pause_reserve_operations(ctx, operations)
+    }
+
+    pub fn unpause_reserve_operations(
+        ctx: Context<UnpauseReserveOperations>,
+        operations: ReserveConfigFlags,
+    ) -> Result<()> {
+        instructions::unpause_reserve_operations(ctx, operations)
+    }
+
+    pub fn force_unlock_reserve(ctx: Context<ForceUnlockReserve>) -> Result<()> {
+        instructions::force_unlock_reserve(ctx)
+    }
+
+    // Protocol fees
+    pub fn update_reserve_asset_class(
+        ctx: Context<UpdateReserveAssetClass>,
+        asset_class: u8,
+    ) -> Result<()> {
+        instructions::update_reserve_asset_class(ctx, asset_class)
+    }
+
+    pub fn update_reserve_fee_receiver(ctx: Context<UpdateReserveFeeReceiver>) -> Result<()> {
+        instructions::update_reserve_fee_receiver(ctx)
+    }
+
+    pub fn update_reserve_fee_split(
+        ctx: Context<UpdateReserveFeeSplit>,
+        params: UpdateReserveFeeSplitParams,
+    ) -> Result<()> {
+        instructions::update_reserve_fee_split(ctx, params)
+    }
+
+    pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+        instructions::collect_protocol_fees(ctx)
+    }
+
+    pub fn initialize_fee_converter(
+        ctx: Context<InitializeFeeConverter>,
+        params: InitializeFeeConverterParams,
+    ) -> Result<()> {
+        instructions::initialize_fee_converter(ctx, params)
+    }
+
+    pub fn update_fee_converter(
+        ctx: Context<UpdateFeeConverter>,
+        params: UpdateFeeConverterParams,
+    ) -> Result<()> {
+        instructions::update_fee_converter(ctx, params)
+    }
+
+    pub fn convert_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, ConvertFees<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+        route_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::convert_fees(ctx, amount_in, min_amount_out, route_data)
+    }
+
     // Lending operations
     pub fn deposit_reserve_liquidity(
         ctx: Context<DepositReserveLiquidity>,
         liquidity_amount: u64,
-    ) -> Result<()> {
-        instructions::deposit_reserve_liquidity(ctx, liquidity_amount)
+        integrator_id: Option<u32>,
+    ) -> Result<OperationReceipt> {
+        instructions::deposit_reserve_liquidity(ctx, liquidity_amount, integrator_id)
     }
 
     pub fn redeem_reserve_collateral(
         ctx: Context<RedeemReserveCollateral>,
         collateral_amount: u64,
+        integrator_id: Option<u32>,
+    ) -> Result<OperationReceipt> {
+        instructions::redeem_reserve_collateral(ctx, collateral_amount, integrator_id)
+    }
+
+    pub fn transfer_supply_position(
+        ctx: Context<TransferSupplyPosition>,
+        collateral_amount: u64,
+    ) -> Result<()> {
+        instructions::transfer_supply_position(ctx, collateral_amount)
+    }
+
+    pub fn request_large_withdrawal(
+        ctx: Context<RequestLargeWithdrawal>,
+        collateral_amount: u64,
     ) -> Result<()> {
-        instructions::redeem_reserve_collateral(ctx, collateral_amount)
+        instructions::request_large_withdrawal(ctx, collateral_amount)
+    }
+
+    pub fn cancel_large_withdrawal(ctx: Context<CancelLargeWithdrawal>) -> Result<()> {
+        instructions::cancel_large_withdrawal(ctx)
+    }
+
+    pub fn waive_large_withdrawal_delay(ctx: Context<WaiveLargeWithdrawalDelay>) -> Result<()> {
+        instructions::waive_large_withdrawal_delay(ctx)
+    }
+
+    // Supply queue
+    pub fn initialize_supply_queue(ctx: Context<InitializeSupplyQueue>) -> Result<()> {
+        instructions::initialize_supply_queue(ctx)
+    }
+
+    pub fn queue_deposit(ctx: Context<QueueDeposit>, liquidity_amount: u64) -> Result<()> {
+        instructions::queue_deposit(ctx, liquidity_amount)
+    }
+
+    pub fn queue_withdrawal(ctx: Context<QueueWithdrawal>, collateral_amount: u64) -> Result<()> {
+        instructions::queue_withdrawal(ctx, collateral_amount)
+    }
+
+    pub fn cancel_queue_entry(ctx: Context<SettleSupplyQueueEntry>, entry_id: u64) -> Result<()> {
+        instructions::cancel_queue_entry(ctx, entry_id)
+    }
+
+    pub fn claim_queue_fills(ctx: Context<SettleSupplyQueueEntry>, entry_id: u64) -> Result<()> {
+        instructions::claim_queue_fills(ctx, entry_id)
+    }
+
+    pub fn process_queue(ctx: Context<ProcessQueue>, max_fills: u8) -> Result<()> {
+        instructions::process_queue(ctx, max_fills)
     }
 
     // Borrowing operations
-    pub fn init_obligation(ctx: Context<InitObligation>) -> Result<()> {
-        instructions::init_obligation(ctx)
+    pub fn init_obligation(ctx: Context<InitObligation>, obligation_index: u8) -> Result<()> {
+        instructions::init_obligation(ctx, obligation_index)
+    }
+
+    pub fn close_obligation(ctx: Context<CloseObligation>) -> Result<()> {
+        instructions::close_obligation(ctx)
+    }
+
+    pub fn transfer_obligation_ownership(
+        ctx: Context<TransferObligationOwnership>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        instructions::transfer_obligation_ownership(ctx, new_owner)
+    }
+
+    pub fn cancel_obligation_transfer(ctx: Context<CancelObligationTransfer>) -> Result<()> {
+        instructions::cancel_obligation_transfer(ctx)
+    }
+
+    pub fn accept_obligation_ownership(
+        ctx: Context<AcceptObligationOwnership>,
+        obligation_index: u8,
+    ) -> Result<()> {
+        instructions::accept_obligation_ownership(ctx, obligation_index)
+    }
+
+    pub fn set_obligation_liquidation_grace(
+        ctx: Context<SetObligationLiquidationGrace>,
+        grace_slots: u64,
+        floor_bps: u16,
+    ) -> Result<()> {
+        instructions::set_obligation_liquidation_grace(ctx, grace_slots, floor_bps)
     }
 
     pub fn deposit_obligation_collateral(
         ctx: Context<DepositObligationCollateral>,
         collateral_amount: u64,
-    ) -> Result<()> {
-        instructions::deposit_obligation_collateral(ctx, collateral_amount)
+        integrator_id: Option<u32>,
+    ) -> Result<OperationReceipt> {
+        instructions::deposit_obligation_collateral(ctx, collateral_amount, integrator_id)
     }
 
     pub fn withdraw_obligation_collateral(
         ctx: Context<WithdrawObligationCollateral>,
         collateral_amount: u64,
-    ) -> Result<()> {
-        instructions::withdraw_obligation_collateral(ctx, collateral_amount)
+        integrator_id: Option<u32>,
+    ) -> Result<OperationReceipt> {
+        instructions::withdraw_obligation_collateral(ctx, collateral_amount, integrator_id)
     }
 
     pub fn borrow_obligation_liquidity(
         ctx: Context<BorrowObligationLiquidity>,
         liquidity_amount: u64,
-    ) -> Result<()> {
-        instructions::borrow_obligation_liquidity(ctx, liquidity_amount)
+        integrator_id: Option<u32>,
+    ) -> Result<OperationReceipt> {
+        instructions::borrow_obligation_liquidity(ctx, liquidity_amount, integrator_id)
     }
 
     pub fn repay_obligation_liquidity(
         ctx: Context<RepayObligationLiquidity>,
         liquidity_amount: u64,
+        integrator_id: Option<u32>,
+    ) -> Result<OperationReceipt> {
+        instructions::repay_obligation_liquidity(ctx, liquidity_amount, integrator_id)
+    }
+
+    pub fn deposit_obligation_collateral_for(
+        ctx: Context<DepositObligationCollateralFor>,
+        collateral_amount: u64,
+        integrator_id: Option<u32>,
+    ) -> Result<OperationReceipt> {
+        instructions::deposit_obligation_collateral_for(ctx, collateral_amount, integrator_id)
+    }
+
+    pub fn repay_obligation_liquidity_for(
+        ctx: Context<RepayObligationLiquidityFor>,
+        liquidity_amount: u64,
+        integrator_id: Option<u32>,
+    ) -> Result<OperationReceipt> {
+        instructions::repay_obligation_liquidity_for(ctx, liquidity_amount, integrator_id)
+    }
+
+    pub fn close_operation_nonce(ctx: Context<CloseOperationNonce>) -> Result<()> {
+        instructions::close_operation_nonce(ctx)
+    }
+
+    // Sub-accounts
+    pub fn initialize_obligation_sub_accounts(
+        ctx: Context<InitializeObligationSubAccounts>,
+    ) -> Result<()> {
+        instructions::initialize_obligation_sub_accounts(ctx)
+    }
+
+    pub fn set_sub_account_label(
+        ctx: Context<SetSubAccountLabel>,
+        sub_account: u8,
+        label: [u8; 16],
+    ) -> Result<()> {
+        instructions::set_sub_account_label(ctx, sub_account, label)
+    }
+
+    pub fn move_sub_account_position(
+        ctx: Context<MoveSubAccountPosition>,
+        params: MoveSubAccountPositionParams,
+    ) -> Result<()> {
+        instructions::move_sub_account_position(ctx, params)
+    }
+
+    // Efficiency mode
+    pub fn initialize_emode_category(
+        ctx: Context<InitializeEModeCategory>,
+        id: u8,
+        params: EModeCategoryParams,
+    ) -> Result<()> {
+        instructions::initialize_emode_category(ctx, id, params)
+    }
+
+    pub fn update_emode_category(
+        ctx: Context<UpdateEModeCategory>,
+        params: EModeCategoryParams,
+    ) -> Result<()> {
+        instructions::update_emode_category(ctx, params)
+    }
+
+    pub fn set_obligation_emode(ctx: Context<SetObligationEMode>, category_id: u8) -> Result<()> {
+        instructions::set_obligation_emode(ctx, category_id)
+    }
+
+    // Credit delegation
+    pub fn open_credit_line(
+        ctx: Context<OpenCreditLine>,
+        params: OpenCreditLineParams,
+    ) -> Result<()> {
+        instructions::open_credit_line(ctx, params)
+    }
+
+    pub fn revoke_credit_line(ctx: Context<RevokeCreditLine>) -> Result<()> {
+        instructions::revoke_credit_line(ctx)
+    }
+
+    pub fn close_credit_line(ctx: Context<CloseCreditLine>) -> Result<()> {
+        instructions::close_credit_line(ctx)
+    }
+
+    pub fn borrow_credit_line(ctx: Context<BorrowCreditLine>, liquidity_amount: u64) -> Result<()> {
+        instructions::borrow_credit_line(ctx, liquidity_amount)
+    }
+
+    pub fn repay_credit_line(ctx: Context<RepayCreditLine>, liquidity_amount: u64) -> Result<()> {
+        instructions::repay_credit_line(ctx, liquidity_amount)
+    }
+
+    pub fn delegate_borrowing_power(
+        ctx: Context<DelegateBorrowingPower>,
+        params: DelegateBorrowingPowerParams,
+    ) -> Result<()> {
+        instructions::delegate_borrowing_power(ctx, params)
+    }
+
+    pub fn revoke_borrowing_power(ctx: Context<RevokeBorrowingPower>) -> Result<()> {
+        instructions::revoke_borrowing_power(ctx)
+    }
+
+    pub fn borrow_obligation_liquidity_delegated(
+        ctx: Context<BorrowObligationLiquidityDelegated>,
+        liquidity_amount: u64,
+    ) -> Result<()> {
+        instructions::borrow_obligation_liquidity_delegated(ctx, liquidity_amount)
+    }
+
+    // Position swaps
+    pub fn repay_with_collateral<'info>(
+        ctx: Context<'_, '_, '_, 'info, RepayWithCollateral<'info>>,
+        params: RepayWithCollateralParams,
     ) -> Result<()> {
-        instructions::repay_obligation_liquidity(ctx, liquidity_amount)
+        instructions::repay_with_collateral(ctx, params)
+    }
+
+    pub fn loop_position<'info>(
+        ctx: Context<'_, '_, '_, 'info, LoopPosition<'info>>,
+        params: LoopPositionParams,
+    ) -> Result<()> {
+        instructions::loop_position(ctx, params)
+    }
+
+    pub fn swap_collateral<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapCollateral<'info>>,
+        params: SwapCollateralParams,
+    ) -> Result<()> {
+        instructions::swap_collateral(ctx, params)
+    }
+
+    pub fn swap_debt<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapDebt<'info>>,
+        params: SwapDebtParams,
+    ) -> Result<()> {
+        instructions::swap_debt(ctx, params)
+    }
+
+    // Flash loans
+    pub fn flash_loan<'info>(
+        ctx: Context<'_, '_, '_, 'info, FlashLoan<'info>>,
+        amount: u64,
+        callback_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::flash_loan(ctx, amount, callback_data)
     }
 
     // Liquidation
     pub fn liquidate_obligation(
         ctx: Context<LiquidateObligation>,
         liquidity_amount: u64,
+        integrator_id: Option<u32>,
+    ) -> Result<OperationReceipt> {
+        instructions::liquidate_obligation(ctx, liquidity_amount, integrator_id)
+    }
+
+    pub fn liquidate_dust_position(
+        ctx: Context<LiquidateObligation>,
+        liquidity_amount: u64,
+        integrator_id: Option<u32>,
+    ) -> Result<OperationReceipt> {
+        instructions::liquidate_dust_position(ctx, liquidity_amount, integrator_id)
+    }
+
+    pub fn commit_liquidation(
+        ctx: Context<CommitLiquidation>,
+        commitment_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::commit_liquidation(ctx, commitment_hash)
+    }
+
+    pub fn execute_liquidation(
+        ctx: Context<LiquidateObligation>,
+        liquidity_amount: u64,
+        salt: [u8; 32],
+        integrator_id: Option<u32>,
+    ) -> Result<OperationReceipt> {
+        instructions::execute_liquidation(ctx, liquidity_amount, salt, integrator_id)
+    }
+
+    pub fn cancel_liquidation_commitment(ctx: Context<CancelLiquidationCommitment>) -> Result<()> {
+        instructions::cancel_liquidation_commitment(ctx)
+    }
+
+    pub fn liquidate_with_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, LiquidateWithSwap<'info>>,
+        params: LiquidateWithSwapParams,
+    ) -> Result<()> {
+        instructions::liquidate_with_swap(ctx, params)
+    }
+
+    pub fn batch_liquidate_obligations<'info>(
+        ctx: Context<'_, '_, '_, 'info, BatchLiquidateObligations<'info>>,
+        liquidation_params: Vec<LiquidationParams>,
+    ) -> Result<Vec<BatchLiquidationResult>> {
+        instructions::batch_liquidate_obligations(ctx, liquidation_params)
+    }
+
+    pub fn initialize_liquidation_queue(ctx: Context<InitializeLiquidationQueue>) -> Result<()> {
+        instructions::initialize_liquidation_queue(ctx)
+    }
+
+    // Liquidation auctions
+    pub fn start_liquidation_auction(ctx: Context<StartLiquidationAuction>) -> Result<()> {
+        instructions::start_liquidation_auction(ctx)
+    }
+
+    pub fn bid_liquidation_auction(
+        ctx: Context<BidLiquidationAuction>,
+        liquidity_amount: u64,
+        min_collateral_amount: u64,
+    ) -> Result<()> {
+        instructions::bid_liquidation_auction(ctx, liquidity_amount, min_collateral_amount)
+    }
+
+    pub fn close_liquidation_auction(ctx: Context<CloseLiquidationAuction>) -> Result<()> {
+        instructions::close_liquidation_auction(ctx)
+    }
+
+    // Collateral limit orders
+    pub fn initialize_collateral_order_book(
+        ctx: Context<InitializeCollateralOrderBook>,
+    ) -> Result<()> {
+        instructions::initialize_collateral_order_book(ctx)
+    }
+
+    pub fn place_collateral_order(
+        ctx: Context<PlaceCollateralOrder>,
+        params: PlaceCollateralOrderParams,
+    ) -> Result<()> {
+        instructions::place_collateral_order(ctx, params)
+    }
+
+    pub fn cancel_collateral_order(ctx: Context<CancelCollateralOrder>, order_id: u64) -> Result<()> {
+        instructions::cancel_collateral_order(ctx, order_id)
+    }
+
+    pub fn claim_collateral_order_fills(
+        ctx: Context<ClaimCollateralOrderFills>,
+        order_id: u64,
+    ) -> Result<()> {
+        instructions::claim_collateral_order_fills(ctx, order_id)
+    }
+
+    // Liquidation backstop
+    pub fn initialize_backstop_vault(
+        ctx: Context<InitializeBackstopVault>,
+        params: InitializeBackstopVaultParams,
+    ) -> Result<()> {
+        instructions::initialize_backstop_vault(ctx, params)
+    }
+
+    pub fn update_backstop_vault(
+        ctx: Context<UpdateBackstopVault>,
+        params: UpdateBackstopVaultParams,
+    ) -> Result<()> {
+        instructions::update_backstop_vault(ctx, params)
+    }
+
+    pub fn fund_backstop_vault(ctx: Context<FundBackstopVault>, amount: u64) -> Result<()> {
+        instructions::fund_backstop_vault(ctx, amount)
+    }
+
+    pub fn withdraw_backstop_vault(ctx: Context<WithdrawBackstopVault>, amount: u64) -> Result<()> {
+        instructions::withdraw_backstop_vault(ctx, amount)
+    }
+
+    pub fn backstop_liquidate_obligation(
+        ctx: Context<BackstopLiquidateObligation>,
+        liquidity_amount: u64,
+    ) -> Result<()> {
+        instructions::backstop_liquidate_obligation(ctx, liquidity_amount)
+    }
+
+    // Insurance fund
+    pub fn initialize_insurance_fund(ctx: Context<InitializeInsuranceFund>) -> Result<()> {
+        instructions::initialize_insurance_fund(ctx)
+    }
+
+    pub fn withdraw_insurance_fund(ctx: Context<WithdrawInsuranceFund>, amount: u64) -> Result<()> {
+        instructions::withdraw_insurance_fund(ctx, amount)
+    }
+
+    pub fn settle_bad_debt(ctx: Context<SettleBadDebt>) -> Result<()> {
+        instructions::settle_bad_debt(ctx)
+    }
+
+    // Referrals
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        instructions::register_referrer(ctx)
+    }
+
+    pub fn claim_referral_fees(ctx: Context<ClaimReferralFees>) -> Result<()> {
+        instructions::claim_referral_fees(ctx)
+    }
+
+    // User statistics
+    pub fn init_user_stats(ctx: Context<InitUserStats>) -> Result<()> {
+        instructions::init_user_stats(ctx)
+    }
+
+    // Liquidity mining
+    pub fn initialize_reward_pool(
+        ctx: Context<InitializeRewardPool>,
+        side: RewardSide,
+    ) -> Result<()> {
+        instructions::initialize_reward_pool(ctx, side)
+    }
+
+    pub fn fund_reward_pool(
+        ctx: Context<FundRewardPool>,
+        amount: u64,
+        emission_per_slot: u64,
+        duration_slots: u64,
+    ) -> Result<()> {
+        instructions::fund_reward_pool(ctx, amount, emission_per_slot, duration_slots)
+    }
+
+    pub fn init_obligation_rewards(ctx: Context<InitObligationRewards>) -> Result<()> {
+        instructions::init_obligation_rewards(ctx)
+    }
+
+    pub fn sync_obligation_rewards(ctx: Context<SyncObligationRewards>) -> Result<()> {
+        instructions::sync_obligation_rewards(ctx)
+    }
+
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        instructions::claim_rewards(ctx)
+    }
+
+    pub fn close_obligation_rewards(ctx: Context<CloseObligationRewards>) -> Result<()> {
+        instructions::close_obligation_rewards(ctx)
+    }
+
+    // Staking
+    pub fn initialize_stake_vault(
+        ctx: Context<InitializeStakeVault>,
+        cooldown_seconds: i64,
+    ) -> Result<()> {
+        instructions::initialize_stake_vault(ctx, cooldown_seconds)
+    }
+
+    pub fn open_stake_position(ctx: Context<OpenStakePosition>) -> Result<()> {
+        instructions::open_stake_position(ctx)
+    }
+
+    pub fn stake(ctx: Context<Stake>, collateral_amount: u64) -> Result<()> {
+        instructions::stake(ctx, collateral_amount)
+    }
+
+    pub fn request_unstake(ctx: Context<RequestUnstake>, shares: u64) -> Result<()> {
+        instructions::request_unstake(ctx, shares)
+    }
+
+    pub fn unstake(ctx: Context<Unstake>) -> Result<()> {
+        instructions::unstake(ctx)
+    }
+
+    pub fn compound_stake_vault(ctx: Context<CompoundStakeVault>) -> Result<()> {
+        instructions::compound_stake_vault(ctx)
+    }
+
+    // Yield venues
+    pub fn add_yield_venue(ctx: Context<AddYieldVenue>, params: AddYieldVenueParams) -> Result<()> {
+        instructions::add_yield_venue(ctx, params)
+    }
+
+    pub fn update_yield_venue(
+        ctx: Context<UpdateYieldVenue>,
+        params: UpdateYieldVenueParams,
+    ) -> Result<()> {
+        instructions::update_yield_venue(ctx, params)
+    }
+
+    pub fn invest_idle_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, InvestIdleLiquidity<'info>>,
+        amount: u64,
+        min_receipt_amount: u64,
+        route_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::invest_idle_liquidity(ctx, amount, min_receipt_amount, route_data)
+    }
+
+    pub fn divest_invested_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, DivestInvestedLiquidity<'info>>,
+        receipt_amount: u64,
+        min_amount_out: u64,
+        route_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::divest_invested_liquidity(ctx, receipt_amount, min_amount_out, route_data)
+    }
+
+    // Keepers
+    pub fn initialize_keeper_registry(
+        ctx: Context<InitializeKeeperRegistry>,
+        params: InitializeKeeperRegistryParams,
+    ) -> Result<()> {
+        instructions::initialize_keeper_registry(ctx, params)
+    }
+
+    pub fn update_keeper_registry(
+        ctx: Context<UpdateKeeperRegistry>,
+        params: UpdateKeeperRegistryParams,
+    ) -> Result<()> {
+        instructions::update_keeper_registry(ctx, params)
+    }
+
+    pub fn register_keeper(ctx: Context<RegisterKeeper>, stake_amount: u64) -> Result<()> {
+        instructions::register_keeper(ctx, stake_amount)
+    }
+
+    pub fn add_keeper_stake(ctx: Context<AddKeeperStake>, amount: u64) -> Result<()> {
+        instructions::add_keeper_stake(ctx, amount)
+    }
+
+    pub fn request_keeper_exit(ctx: Context<RequestKeeperExit>) -> Result<()> {
+        instructions::request_keeper_exit(ctx)
+    }
+
+    pub fn withdraw_keeper_stake(ctx: Context<WithdrawKeeperStake>) -> Result<()> {
+        instructions::withdraw_keeper_stake(ctx)
+    }
+
+    pub fn slash_keeper(ctx: Context<SlashKeeper>, amount: u64) -> Result<()> {
+        instructions::slash_keeper(ctx, amount)
+    }
+
+    // Reserve deprecation
+    pub fn deprecate_reserve(
+        ctx: Context<DeprecateReserve>,
+        params: DeprecateReserveParams,
+    ) -> Result<()> {
+        instructions::deprecate_reserve(ctx, params)
+    }
+
+    pub fn deleverage_obligation(
+        ctx: Context<DeleverageObligation>,
+        liquidity_amount: u64,
+    ) -> Result<()> {
+        instructions::deleverage_obligation(ctx, liquidity_amount)
+    }
+
+    // Standing deleverage orders
+    pub fn place_standing_order(
+        ctx: Context<PlaceStandingOrder>,
+        params: StandingOrderParams,
+    ) -> Result<()> {
+        instructions::place_standing_order(ctx, params)
+    }
+
+    pub fn cancel_standing_order(ctx: Context<CancelStandingOrder>) -> Result<()> {
+        instructions::cancel_standing_order(ctx)
+    }
+
+    pub fn execute_standing_order(
+        ctx: Context<ExecuteStandingOrder>,
+        liquidity_amount: u64,
     ) -> Result<()> {
-        instructions::liquidate_obligation(ctx, liquidity_amount)
+        instructions::execute_standing_order(ctx, liquidity_amount)
+    }
+
+    // Interest accrual
+    pub fn accrue_reserve_interest(ctx: Context<AccrueReserveInterest>) -> Result<()> {
+        instructions::accrue_reserve_interest(ctx)
+    }
+
+    pub fn accrue_reserves_interest<'info>(
+        ctx: Context<'_, '_, '_, 'info, AccrueReservesInterest<'info>>,
+    ) -> Result<()> {
+        instructions::accrue_reserves_interest(ctx)
+    }
+
+    // Protocol metrics
+    pub fn initialize_protocol_metrics(ctx: Context<InitializeProtocolMetrics>) -> Result<()> {
+        instructions::initialize_protocol_metrics(ctx)
+    }
+
+    pub fn update_protocol_metrics<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateProtocolMetrics<'info>>,
+    ) -> Result<()> {
+        instructions::update_protocol_metrics(ctx)
+    }
+
+    // Batch operations
+    pub fn process_batch_operations<'info>(
+        ctx: Context<'_, '_, '_, 'info, ProcessBatchOperations<'info>>,
+        operations: Vec<BatchOperation>,
+    ) -> Result<BatchExecutionReport> {
+        instructions::process_batch_operations(ctx, operations)
+    }
+
+    // Rate index
+    pub fn initialize_rate_index(ctx: Context<InitializeRateIndex>) -> Result<()> {
+        instructions::initialize_rate_index(ctx)
+    }
+
+    pub fn update_rate_index(ctx: Context<UpdateRateIndex>) -> Result<()> {
+        instructions::update_rate_index(ctx)
+    }
+
+    // Rent reclamation
+    pub fn grant_rent_reclaim_consent(
+        ctx: Context<GrantRentReclaimConsent>,
+        params: GrantRentReclaimConsentParams,
+    ) -> Result<()> {
+        instructions::grant_rent_reclaim_consent(ctx, params)
+    }
+
+    pub fn revoke_rent_reclaim_consent(ctx: Context<RevokeRentReclaimConsent>) -> Result<()> {
+        instructions::revoke_rent_reclaim_consent(ctx)
+    }
+
+    pub fn reclaim_dormant_obligation(ctx: Context<ReclaimDormantObligation>) -> Result<()> {
+        instructions::reclaim_dormant_obligation(ctx)
     }
 
     // Oracle operations
@@ -212,6 +958,17 @@ pub mod aura_lend {
         instructions::refresh_reserve(ctx)
     }
 
+    pub fn post_and_refresh_reserve(
+        ctx: Context<PostAndRefreshReserve>,
+        price_update: Vec<u8>,
+    ) -> Result<()> {
+        instructions::post_and_refresh_reserve(ctx, price_update)
+    }
+
+    pub fn refresh_derived_price(ctx: Context<RefreshDerivedPrice>) -> Result<()> {
+        instructions::refresh_derived_price(ctx)
+    }
+
     pub fn refresh_obligation(ctx: Context<RefreshObligation>) -> Result<()> {
         instructions::refresh_obligation(ctx)
     }
@@ -221,6 +978,14 @@ pub mod aura_lend {
         instructions::set_upgrade_authority(ctx)
     }
 
+    pub fn stage_upgrade(ctx: Context<StageUpgrade>, params: StageUpgradeParams) -> Result<()> {
+        instructions::stage_upgrade(ctx, params)
+    }
+
+    pub fn cancel_staged_upgrade(ctx: Context<CancelStagedUpgrade>) -> Result<()> {
+        instructions::cancel_staged_upgrade(ctx)
+    }
+
     pub fn upgrade_program(ctx: Context<UpgradeProgram>) -> Result<()> {
         instructions::upgrade_program(ctx)
     }
@@ -229,6 +994,23 @@ pub mod aura_lend {
         instructions::freeze_program(ctx)
     }
 
+    // Two-step authority transfers
+    pub fn propose_authority_transfer(
+        ctx: Context<ProposeAuthorityTransfer>,
+        kind: AuthorityKind,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::propose_authority_transfer(ctx, kind, new_authority)
+    }
+
+    pub fn cancel_authority_transfer(ctx: Context<CancelAuthorityTransfer>) -> Result<()> {
+        instructions::cancel_authority_transfer(ctx)
+    }
+
+    pub fn accept_authority_transfer(ctx: Context<AcceptAuthorityTransfer>) -> Result<()> {
+        instructions::accept_authority_transfer(ctx)
+    }
+
     // Data migration operations
     pub fn migrate_market(ctx: Context<MigrateMarket>) -> Result<()> {
         instructions::migrate_market(ctx)
@@ -260,6 +1042,24 @@ pub mod aura_lend {
         instructions::batch_migrate_reserves(ctx)
     }
 
+    pub fn plan_migration<'info>(
+        ctx: Context<'_, '_, '_, 'info, PlanMigration<'info>>,
+    ) -> Result<migration::MigrationPlan> {
+        instructions::plan_migration(ctx)
+    }
+
+    pub fn convert_obligation_to_optimized(ctx: Context<ConvertObligationLayout>) -> Result<()> {
+        instructions::convert_obligation_layout(ctx, ObligationLayout::Optimized)
+    }
+
+    pub fn convert_obligation_to_standard(ctx: Context<ConvertObligationLayout>) -> Result<()> {
+        instructions::convert_obligation_layout(ctx, ObligationLayout::Standard)
+    }
+
+    pub fn convert_obligation_to_zero_copy(ctx: Context<ConvertObligationLayout>) -> Result<()> {
+        instructions::convert_obligation_layout(ctx, ObligationLayout::ZeroCopy)
+    }
+
     // Configuration management
     pub fn initialize_config(
         ctx: Context<InitializeConfig>,
@@ -286,4 +1086,59 @@ pub mod aura_lend {
     pub fn get_config(ctx: Context<GetConfig>) -> Result<utils::config::ProtocolConfig> {
         instructions::get_config(ctx)
     }
+
+    // Views
+    pub fn best_borrow_source(
+        ctx: Context<BestBorrowSource>,
+        asset_class: u8,
+        amount: u64,
+    ) -> Result<BorrowSourceQuote> {
+        instructions::best_borrow_source(ctx, asset_class, amount)
+    }
+
+    pub fn get_sub_account_health(
+        ctx: Context<GetSubAccountHealth>,
+        sub_account: u8,
+    ) -> Result<SubAccountHealth> {
+        instructions::get_sub_account_health(ctx, sub_account)
+    }
+
+    pub fn get_credit_history(ctx: Context<GetCreditHistory>) -> Result<CreditHistory> {
+        instructions::get_credit_history(ctx)
+    }
+
+    pub fn get_max_withdrawable_collateral(
+        ctx: Context<GetMaxWithdrawableCollateral>,
+        min_health_factor_bps: u64,
+    ) -> Result<u64> {
+        instructions::get_max_withdrawable_collateral(ctx, min_health_factor_bps)
+    }
+
+    pub fn simulate_borrow(
+        ctx: Context<SimulateBorrow>,
+        liquidity_amount: u64,
+    ) -> Result<ActionSimulation> {
+        instructions::simulate_borrow(ctx, liquidity_amount)
+    }
+
+    pub fn simulate_withdraw(
+        ctx: Context<SimulateWithdraw>,
+        collateral_amount: u64,
+    ) -> Result<ActionSimulation> {
+        instructions::simulate_withdraw(ctx, collateral_amount)
+    }
+
+    pub fn get_reserve_summary(ctx: Context<GetReserveSummary>) -> Result<ReserveSummary> {
+        instructions::get_reserve_summary(ctx)
+    }
+
+    pub fn get_obligation_summary(ctx: Context<GetObligationSummary>) -> Result<ObligationSummary> {
+        instructions::get_obligation_summary(ctx)
+    }
+
+    pub fn get_market_summary<'info>(
+        ctx: Context<'_, '_, '_, 'info, GetMarketSummary<'info>>,
+    ) -> Result<MarketSummary> {
+        instructions::get_market_summary(ctx)
+    }
 }
@@ -1,11 +1,21 @@
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
 
 use crate::{
-    constants::PROGRAM_VERSION,
+    constants::{
+        MAX_ACCOUNTS_PER_INSTRUCTION, MAX_TRANSACTION_COMPUTE_UNITS, PROGRAM_VERSION,
+        RESERVE_VERSION,
+    },
     error::LendingError,
     state::{
-        governance::GovernanceRegistry, market::Market, multisig::MultiSig, obligation::Obligation,
-        reserve::Reserve, timelock::TimelockController,
+        governance::GovernanceRegistry,
+        market::Market,
+        multisig::MultiSig,
+        obligation::Obligation,
+        obligation_optimized::ObligationOptimized,
+        obligation_zero_copy::ObligationZeroCopy,
+        reserve::{Reserve, ReserveV1},
+        timelock::TimelockController,
     },
 };
 
@@ -61,7 +71,13 @@ impl Migratable for Market {
 }
 
 /// Migration handler for Reserve state
+/// Reserves before `RESERVE_VERSION` cannot be loaded until `compact_reserve` rewrote
+/// their layout, which also sets the version
 impl Migratable for Reserve {
+    fn current_version() -> u8 {
+        RESERVE_VERSION
+    }
+
     fn version(&self) -> u8 {
         self.version
     }
@@ -74,8 +90,7 @@ impl Migratable for Reserve {
         );
 
         match from_version {
-            1 => {
-                // Currently at version 1, no migration needed yet
+            RESERVE_VERSION => {
                 // Future migrations could include:
                 // - New config parameters
                 // - Updated state calculations
@@ -92,12 +107,73 @@ impl Migratable for Reserve {
         }
 
         // Update version to current
-        self.version = PROGRAM_VERSION;
-        msg!("Reserve migration completed to version {}", PROGRAM_VERSION);
+        self.version = Self::current_version();
+        msg!("Reserve migration completed to version {}", self.version);
         Ok(())
     }
 }
 
+/// Compact a legacy reserve (before `RESERVE_VERSION`) in place
+/// The account is resized to `Reserve::SIZE`; `authority` covers (or is refunded) the
+/// rent difference. Returns false if the reserve already uses the packed layout.
+pub fn compact_reserve<'info>(
+    reserve_info: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<bool> {
+    let compacted = match compact_reserve_data(&reserve_info.try_borrow_data()?)? {
+        Some(compacted) => compacted,
+        None => return Ok(false),
+    };
+
+    // Keep the resized account rent exempt
+    let required_lamports = Rent::get()?.minimum_balance(compacted.len());
+    let current_lamports = reserve_info.lamports();
+    if required_lamports > current_lamports {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: authority.clone(),
+                    to: reserve_info.clone(),
+                },
+            ),
+            required_lamports - current_lamports,
+        )?;
+    } else if current_lamports > required_lamports {
+        let excess = current_lamports - required_lamports;
+        reserve_info.sub_lamports(excess)?;
+        authority.add_lamports(excess)?;
+    }
+
+    reserve_info.realloc(compacted.len(), false)?;
+    reserve_info
+        .try_borrow_mut_data()?
+        .copy_from_slice(&compacted);
+    Ok(true)
+}
+
+/// Packed bytes of a legacy reserve account, or None if it is already packed
+/// The legacy account is decoded with the frozen `ReserveV1` layout: the Decimal rates
+/// are truncated to bps, the reentrancy guard moves into the state flags, and fields
+/// added since take their defaults.
+pub fn compact_reserve_data(data: &[u8]) -> Result<Option<Vec<u8>>> {
+    if data.len() <= 8 || !data.starts_with(Reserve::DISCRIMINATOR) {
+        return Err(LendingError::InvalidAccount.into());
+    }
+    if data[8] >= RESERVE_VERSION {
+        return Ok(None);
+    }
+
+    let legacy = ReserveV1::deserialize(&mut &data[8..])?;
+    let mut compacted = Vec::with_capacity(Reserve::SIZE);
+    legacy.into_current().try_serialize(&mut compacted)?;
+
+    let space = compacted.len().max(Reserve::SIZE);
+    compacted.resize(space, 0);
+    Ok(Some(compacted))
+}
+
 /// Migration handler for Obligation state
 impl Migratable for Obligation {
     fn version(&self) -> u8 {
@@ -334,3 +410,238 @@ pub fn batch_migrate_accounts<T: Migratable>(accounts: &mut [T]) -> Result<()> {
 
     Ok(())
 }
+
+/// Account types recognised by the migration planner
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrationAccountKind {
+    Market,
+    Reserve,
+    Obligation,
+    ObligationOptimized,
+    ObligationZeroCopy,
+    MultiSig,
+    Timelock,
+    Governance,
+}
+
+impl MigrationAccountKind {
+    /// Identify an account by its discriminator
+    pub fn from_data(data: &[u8]) -> Option<Self> {
+        let kinds = [
+            (Market::DISCRIMINATOR, Self::Market),
+            (Reserve::DISCRIMINATOR, Self::Reserve),
+            (Obligation::DISCRIMINATOR, Self::Obligation),
+            (ObligationOptimized::DISCRIMINATOR, Self::ObligationOptimized),
+            (ObligationZeroCopy::DISCRIMINATOR, Self::ObligationZeroCopy),
+            (MultiSig::DISCRIMINATOR, Self::MultiSig),
+            (TimelockController::DISCRIMINATOR, Self::Timelock),
+            (GovernanceRegistry::DISCRIMINATOR, Self::Governance),
+        ];
+
+        kinds
+            .iter()
+            .find(|(discriminator, _)| data.starts_with(discriminator))
+            .map(|(_, kind)| *kind)
+    }
+
+    /// Estimated compute units to migrate one account of this kind
+    /// Covers deserialization, the version-specific migration step and serialization
+    pub fn estimated_compute_units(&self) -> u32 {
+        match self {
+            Self::Market => 12_000,
+            Self::Reserve => 15_000,
+            // The fixed layout is copied out and unpacked into an `Obligation`, which
+            // costs about as much as Borsh decoding
+            Self::Obligation | Self::ObligationOptimized | Self::ObligationZeroCopy => 25_000,
+            Self::MultiSig | Self::Timelock => 10_000,
+            Self::Governance => 18_000,
+        }
+    }
+
+    /// Latest version of accounts of this kind
+    pub fn current_version(&self) -> u8 {
+        match self {
+            Self::Reserve => RESERVE_VERSION,
+            _ => PROGRAM_VERSION,
+        }
+    }
+
+    /// Whether this kind can be migrated in batches (batch_migrate_reserves)
+    pub fn is_batchable(&self) -> bool {
+        matches!(self, Self::Reserve)
+    }
+}
+
+/// Planned migration of a single account
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct MigrationPlanItem {
+    pub account: Pubkey,
+    pub kind: MigrationAccountKind,
+    pub from_version: u8,
+    pub to_version: u8,
+    /// Migration path is supported by validate_migration_compatibility
+    pub supported: bool,
+    pub estimated_compute_units: u32,
+}
+
+/// Report-only migration plan returned by `plan_migration`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct MigrationPlan {
+    /// Accounts that would migrate
+    pub items: Vec<MigrationPlanItem>,
+    /// Accounts already at the current version
+    pub up_to_date: u32,
+    /// Accounts not owned by the program or of an unknown type
+    pub skipped: u32,
+    /// Accounts whose version has no supported migration path
+    pub unsupported: u32,
+    /// Total estimated compute units across all planned migrations
+    pub total_compute_units: u64,
+    /// Reserves that fit in one batch_migrate_reserves transaction
+    pub reserves_per_batch: u32,
+    /// Estimated transactions needed to execute the plan
+    pub estimated_transactions: u32,
+}
+
+impl MigrationPlan {
+    /// Add an account to the plan without mutating it
+    pub fn add_account(&mut self, account: Pubkey, owned_by_program: bool, data: &[u8]) {
+        let kind = match MigrationAccountKind::from_data(data) {
+            Some(kind) if owned_by_program && data.len() > 8 => kind,
+            _ => {
+                self.skipped += 1;
+                return;
+            }
+        };
+
+        // Every migratable account stores its version right after the discriminator
+        let from_version = data[8];
+        let to_version = kind.current_version();
+        if from_version >= to_version {
+            self.up_to_date += 1;
+            return;
+        }
+
+        let supported = is_supported_migration(from_version, to_version);
+        if !supported {
+            self.unsupported += 1;
+        }
+
+        let estimated_compute_units = kind.estimated_compute_units();
+        self.total_compute_units += estimated_compute_units as u64;
+        self.items.push(MigrationPlanItem {
+            account,
+            kind,
+            from_version,
+            to_version,
+            supported,
+            estimated_compute_units,
+        });
+    }
+
+    /// Compute batch sizing and transaction estimates for the planned items
+    pub fn finalize(&mut self) {
+        let reserve_compute_units = MigrationAccountKind::Reserve.estimated_compute_units();
+        // Market and authority accounts are always part of a batch migration
+        let account_capacity = (MAX_ACCOUNTS_PER_INSTRUCTION as u32).saturating_sub(2);
+        self.reserves_per_batch = (MAX_TRANSACTION_COMPUTE_UNITS / reserve_compute_units)
+            .min(account_capacity)
+            .max(1);
+
+        let batchable = self
+            .items
+            .iter()
+            .filter(|item| item.supported && item.kind.is_batchable())
+            .count() as u32;
+        let individual = self
+            .items
+            .iter()
+            .filter(|item| item.supported && !item.kind.is_batchable())
+            .count() as u32;
+
+        self.estimated_transactions = batchable
+            .div_ceil(self.reserves_per_batch)
+            .saturating_add(individual);
+    }
+}
+
+/// Side-effect free variant of `validate_migration_compatibility`
+fn is_supported_migration(from_version: u8, to_version: u8) -> bool {
+    from_version <= to_version && (from_version == to_version || from_version == 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account_data(discriminator: &[u8], version: u8) -> Vec<u8> {
+        let mut data = discriminator.to_vec();
+        data.push(version);
+        data.extend_from_slice(&[0; 32]);
+        data
+    }
+
+    #[test]
+    fn test_plan_skips_foreign_and_current_accounts() {
+        let mut plan = MigrationPlan::default();
+
+        plan.add_account(
+            Pubkey::new_unique(),
+            false,
+            &account_data(Reserve::DISCRIMINATOR, 0),
+        );
+        plan.add_account(Pubkey::new_unique(), true, &[0; 16]);
+        plan.add_account(
+            Pubkey::new_unique(),
+            true,
+            &account_data(Reserve::DISCRIMINATOR, RESERVE_VERSION),
+        );
+        plan.finalize();
+
+        assert!(plan.items.is_empty());
+        assert_eq!(plan.skipped, 2);
+        assert_eq!(plan.up_to_date, 1);
+        assert_eq!(plan.estimated_transactions, 0);
+    }
+
+    #[test]
+    fn test_plan_compacts_legacy_reserves() {
+        let mut plan = MigrationPlan::default();
+        plan.add_account(
+            Pubkey::new_unique(),
+            true,
+            &account_data(Reserve::DISCRIMINATOR, 1),
+        );
+
+        assert_eq!(plan.items.len(), 1);
+        assert_eq!(plan.items[0].to_version, RESERVE_VERSION);
+        assert!(plan.items[0].supported);
+    }
+
+    #[test]
+    fn test_plan_batches_reserves() {
+        let mut plan = MigrationPlan::default();
+        let item = |kind: MigrationAccountKind| MigrationPlanItem {
+            account: Pubkey::new_unique(),
+            kind,
+            from_version: 1,
+            to_version: PROGRAM_VERSION + 1,
+            supported: true,
+            estimated_compute_units: kind.estimated_compute_units(),
+        };
+
+        for _ in 0..31 {
+            plan.items.push(item(MigrationAccountKind::Reserve));
+        }
+        plan.items.push(item(MigrationAccountKind::Market));
+        plan.finalize();
+
+        // Account limits cap a reserve batch before compute does
+        assert_eq!(
+            plan.reserves_per_batch,
+            MAX_ACCOUNTS_PER_INSTRUCTION as u32 - 2
+        );
+        // Two reserve batches plus one market migration
+        assert_eq!(plan.estimated_transactions, 3);
+    }
+}
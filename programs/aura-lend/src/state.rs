@@ -1,16 +1,80 @@
+pub mod any_obligation;
+pub mod audit_log;
+pub mod authority_transfer;
+pub mod backstop;
+pub mod credit_line;
+pub mod emode;
+pub mod fee_converter;
+pub mod flash_loan_guard;
 pub mod governance;
+pub mod insurance_fund;
+pub mod keeper;
+pub mod liquidation_auction;
+pub mod liquidation_commitment;
+pub mod liquidation_queue;
+pub mod lookup_table;
 pub mod market;
 pub mod multisig;
 pub mod obligation;
 pub mod obligation_optimized;
+pub mod obligation_transfer;
+pub mod obligation_zero_copy;
+pub mod operation_nonce;
+pub mod operation_receipt;
+pub mod order_book;
+pub mod proposal_lifecycle;
+pub mod rate_index;
+pub mod referrer;
+pub mod rent_reclaim;
+pub mod rewards;
 pub mod reserve;
+pub mod stake_vault;
+pub mod staged_upgrade;
+pub mod standing_order;
+pub mod sub_account;
+pub mod supply_queue;
 pub mod timelock;
+pub mod user_stats;
+pub mod withdrawal_request;
+pub mod yield_venue;
 
 // Re-export commonly used state types
+pub use any_obligation::*;
+pub use audit_log::*;
+pub use authority_transfer::*;
+pub use backstop::*;
+pub use credit_line::*;
+pub use emode::*;
+pub use fee_converter::*;
+pub use flash_loan_guard::*;
 pub use governance::*;
+pub use insurance_fund::*;
+pub use keeper::*;
+pub use liquidation_auction::*;
+pub use liquidation_commitment::*;
+pub use liquidation_queue::*;
+pub use lookup_table::*;
 pub use market::*;
 pub use multisig::*;
 pub use obligation::*;
 pub use obligation_optimized::*;
+pub use obligation_transfer::*;
+pub use obligation_zero_copy::*;
+pub use operation_nonce::*;
+pub use operation_receipt::*;
+pub use order_book::*;
+pub use proposal_lifecycle::*;
+pub use rate_index::*;
+pub use referrer::*;
+pub use rent_reclaim::*;
+pub use rewards::*;
 pub use reserve::*;
+pub use stake_vault::*;
+pub use staged_upgrade::*;
+pub use standing_order::*;
+pub use sub_account::*;
+pub use supply_queue::*;
 pub use timelock::*;
+pub use user_stats::*;
+pub use withdrawal_request::*;
+pub use yield_venue::*;
@@ -0,0 +1,366 @@
+use crate::error::LendingError;
+use crate::state::obligation::Obligation;
+use crate::state::obligation_optimized::ObligationOptimized;
+use crate::state::obligation_zero_copy::ObligationZeroCopy;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+
+/// Layout an obligation account is stored in
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObligationLayout {
+    /// `Obligation` account
+    Standard,
+    /// `ObligationOptimized` account
+    Optimized,
+    /// `ObligationZeroCopy` account (v2, fixed size)
+    ZeroCopy,
+}
+
+/// Read-compatibility facade over every obligation account layout
+/// Instructions operate on the canonical `Obligation` regardless of how the account is
+/// stored; `save` writes it back in the layout it was loaded from. Every layout, the
+/// fixed-size one included, is decoded in full on load and re-encoded on save.
+pub struct AnyObligation {
+    obligation: Obligation,
+    layout: ObligationLayout,
+    /// Lookup metrics carried over from an optimized account
+    lookup_count: u64,
+    cache_hits: u64,
+}
+
+impl AnyObligation {
+    /// Load an obligation stored in any layout
+    pub fn load(info: &AccountInfo) -> Result<Self> {
+        if info.owner != &crate::ID {
+            return Err(LendingError::InvalidAccountOwner.into());
+        }
+
+        let data = info.try_borrow_data()?;
+        Self::from_data(&data)
+    }
+
+    /// Decode obligation account data stored in any layout
+    /// Does not check the account owner; off-chain clients decoding fetched accounts use it
+    /// directly.
+    pub fn from_data(data: &[u8]) -> Result<Self> {
+        let mut slice: &[u8] = data;
+
+        if data.starts_with(Obligation::DISCRIMINATOR) {
+            Ok(Self {
+                obligation: Obligation::try_deserialize(&mut slice)?,
+                layout: ObligationLayout::Standard,
+                lookup_count: 0,
+                cache_hits: 0,
+            })
+        } else if data.starts_with(ObligationOptimized::DISCRIMINATOR) {
+            let optimized = ObligationOptimized::try_deserialize(&mut slice)?;
+            let (lookup_count, cache_hits) = (optimized.lookup_count, optimized.cache_hits);
+            Ok(Self {
+                obligation: optimized.into(),
+                layout: ObligationLayout::Optimized,
+                lookup_count,
+                cache_hits,
+            })
+        } else if data.starts_with(ObligationZeroCopy::DISCRIMINATOR) {
+            let bytes = data
+                .get(8..ObligationZeroCopy::SIZE)
+                .ok_or(LendingError::InvalidAccount)?;
+            let zero_copy: ObligationZeroCopy = bytemuck::pod_read_unaligned(bytes);
+            Ok(Self {
+                obligation: zero_copy.unpack()?,
+                layout: ObligationLayout::ZeroCopy,
+                lookup_count: 0,
+                cache_hits: 0,
+            })
+        } else {
+            Err(LendingError::InvalidAccount.into())
+        }
+    }
+
+    /// Load an obligation and verify it belongs to `market`
+    pub fn load_checked(info: &AccountInfo, market: &Pubkey) -> Result<Self> {
+        let obligation = Self::load(info)?;
+        if obligation.market != *market {
+            return Err(LendingError::InvalidMarketState.into());
+        }
+        Ok(obligation)
+    }
+
+    /// Load an obligation of `market` and verify it is owned by `owner`
+    /// A wallet may hold several obligations, so the owner is checked on the account
+    /// rather than through its address.
+    pub fn load_owned(info: &AccountInfo, market: &Pubkey, owner: &Pubkey) -> Result<Self> {
+        let obligation = Self::load_checked(info, market)?;
+        if obligation.owner != *owner {
+            return Err(LendingError::InvalidAuthority.into());
+        }
+        Ok(obligation)
+    }
+
+    /// The canonical obligation, whatever layout it was loaded from
+    pub fn into_inner(self) -> Obligation {
+        self.obligation
+    }
+
+    /// Layout the account was loaded from
+    pub fn layout(&self) -> ObligationLayout {
+        self.layout
+    }
+
+    /// Serialized size of the obligation in the given layout
+    pub fn space(layout: ObligationLayout) -> usize {
+        match layout {
+            ObligationLayout::Standard => Obligation::SIZE,
+            ObligationLayout::Optimized => ObligationOptimized::SIZE,
+            ObligationLayout::ZeroCopy => ObligationZeroCopy::SIZE,
+        }
+    }
+
+    /// Write the obligation back in the layout it was loaded from
+    pub fn save(&self, info: &AccountInfo) -> Result<()> {
+        self.save_as(info, self.layout)
+    }
+
+    /// Write the obligation in the given layout
+    /// The account must already be sized for the target layout
+    pub fn save_as(&self, info: &AccountInfo, layout: ObligationLayout) -> Result<()> {
+        let mut data = info.try_borrow_mut_data()?;
+        let mut writer: &mut [u8] = &mut data;
+
+        match layout {
+            ObligationLayout::Standard => self.obligation.try_serialize(&mut writer),
+            ObligationLayout::Optimized => {
+                let mut optimized = ObligationOptimized::from(self.obligation.clone());
+                optimized.lookup_count = self.lookup_count;
+                optimized.cache_hits = self.cache_hits;
+                optimized.try_serialize(&mut writer)
+            }
+            ObligationLayout::ZeroCopy => {
+                let zero_copy = ObligationZeroCopy::pack(&self.obligation)?;
+                if writer.len() < ObligationZeroCopy::SIZE {
+                    return Err(LendingError::InvalidAccount.into());
+                }
+                writer[..8].copy_from_slice(ObligationZeroCopy::DISCRIMINATOR);
+                writer[8..ObligationZeroCopy::SIZE].copy_from_slice(bytemuck::bytes_of(&zero_copy));
+                Ok(())
+            }
+        }
+    }
+
+    /// Close an obligation account, returning every lamport to `destination`
+    /// Callers check that the obligation is empty; returns the lamports moved.
+    pub fn close(info: &AccountInfo, destination: &AccountInfo) -> Result<u64> {
+        let lamports = info.lamports();
+        info.sub_lamports(lamports)?;
+        destination.add_lamports(lamports)?;
+        info.assign(&system_program::ID);
+        info.realloc(0, false)?;
+        Ok(lamports)
+    }
+}
+
+impl Deref for AnyObligation {
+    type Target = Obligation;
+
+    fn deref(&self) -> &Obligation {
+        &self.obligation
+    }
+}
+
+impl DerefMut for AnyObligation {
+    fn deref_mut(&mut self) -> &mut Obligation {
+        &mut self.obligation
+    }
+}
+
+impl From<ObligationOptimized> for Obligation {
+    fn from(optimized: ObligationOptimized) -> Self {
+        Self {
+            version: optimized.version,
+            market: optimized.market,
+            owner: optimized.owner,
+            deposits: optimized.deposits,
+            borrows: optimized.borrows,
+            deposited_value_usd: optimized.deposited_value_usd,
+            borrowed_value_usd: optimized.borrowed_value_usd,
+            last_update_timestamp: optimized.last_update_timestamp,
+            last_update_slot: optimized.last_update_slot,
+            liquidation_snapshot_health_factor: optimized.liquidation_snapshot_health_factor,
+            unhealthy_since_slot: optimized.unhealthy_since_slot,
+            emode_category: optimized.emode_category,
+            isolation_mode: optimized.isolation_mode,
+            credit_history: optimized.credit_history,
+            refreshed_slot: optimized.refreshed_slot,
+            liquidation_grace_slots: optimized.liquidation_grace_slots,
+            liquidation_grace_floor_bps: optimized.liquidation_grace_floor_bps,
+            obligation_index: optimized.obligation_index,
+            linked_accounts: optimized.linked_accounts,
+            reserved: [0; 53],
+        }
+    }
+}
+
+impl From<Obligation> for ObligationOptimized {
+    fn from(obligation: Obligation) -> Self {
+        let deposit_index: HashMap<Pubkey, usize> = obligation
+            .deposits
+            .iter()
+            .enumerate()
+            .map(|(index, deposit)| (deposit.deposit_reserve, index))
+            .collect();
+        let borrow_index: HashMap<Pubkey, usize> = obligation
+            .borrows
+            .iter()
+            .enumerate()
+            .map(|(index, borrow)| (borrow.borrow_reserve, index))
+            .collect();
+
+        Self {
+            version: obligation.version,
+            market: obligation.market,
+            owner: obligation.owner,
+            deposits: obligation.deposits,
+            deposit_index,
+            borrows: obligation.borrows,
+            borrow_index,
+            deposited_value_usd: obligation.deposited_value_usd,
+            borrowed_value_usd: obligation.borrowed_value_usd,
+            last_update_timestamp: obligation.last_update_timestamp,
+            last_update_slot: obligation.last_update_slot,
+            liquidation_snapshot_health_factor: obligation.liquidation_snapshot_health_factor,
+            unhealthy_since_slot: obligation.unhealthy_since_slot,
+            emode_category: obligation.emode_category,
+            isolation_mode: obligation.isolation_mode,
+            credit_history: obligation.credit_history,
+            refreshed_slot: obligation.refreshed_slot,
+            liquidation_grace_slots: obligation.liquidation_grace_slots,
+            liquidation_grace_floor_bps: obligation.liquidation_grace_floor_bps,
+            lookup_count: 0,
+            cache_hits: 0,
+            obligation_index: obligation.obligation_index,
+            linked_accounts: obligation.linked_accounts,
+            reserved: [0; 37],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::obligation::{CreditHistory, ObligationCollateral, ObligationLiquidity};
+    use crate::utils::math::Decimal;
+
+    #[test]
+    fn test_layout_round_trip_preserves_positions() {
+        let deposit_reserve = Pubkey::new_unique();
+        let borrow_reserve = Pubkey::new_unique();
+
+        let obligation = Obligation {
+            version: 1,
+            market: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            deposits: vec![ObligationCollateral {
+                deposit_reserve,
+                deposited_amount: 1_000,
+                market_value_usd: Decimal::from_integer(1_000).unwrap(),
+                ltv_bps: 7500,
+                liquidation_threshold_bps: 8000,
+            }],
+            borrows: vec![ObligationLiquidity {
+                borrow_reserve,
+                borrowed_amount_wads: Decimal::from_integer(500).unwrap(),
+                market_value_usd: Decimal::from_integer(500).unwrap(),
+                ..Default::default()
+            }],
+            deposited_value_usd: Decimal::from_integer(1_000).unwrap(),
+            borrowed_value_usd: Decimal::from_integer(500).unwrap(),
+            last_update_timestamp: 10,
+            last_update_slot: 20,
+            liquidation_snapshot_health_factor: None,
+            unhealthy_since_slot: 30,
+            emode_category: 2,
+            isolation_mode: true,
+            credit_history: CreditHistory {
+                max_debt_usd: 800,
+                on_time_repayments: 3,
+                ..Default::default()
+            },
+            refreshed_slot: 40,
+            liquidation_grace_slots: 150,
+            liquidation_grace_floor_bps: 9500,
+            obligation_index: 0,
+            linked_accounts: 2,
+            reserved: [0; 53],
+        };
+
+        let optimized = ObligationOptimized::from(obligation.clone());
+        assert_eq!(optimized.deposit_index.get(&deposit_reserve), Some(&0));
+        assert_eq!(optimized.borrow_index.get(&borrow_reserve), Some(&0));
+        assert_eq!(
+            optimized
+                .find_collateral_deposit(&deposit_reserve)
+                .unwrap()
+                .deposited_amount,
+            1_000
+        );
+
+        let restored = Obligation::from(optimized);
+        assert_eq!(restored.deposits.len(), 1);
+        assert_eq!(restored.borrows.len(), 1);
+        assert_eq!(restored.borrowed_value_usd, obligation.borrowed_value_usd);
+        assert_eq!(restored.unhealthy_since_slot, 30);
+        assert_eq!(restored.emode_category, 2);
+        assert!(restored.isolation_mode);
+        assert_eq!(restored.credit_history, obligation.credit_history);
+        assert_eq!(restored.refreshed_slot, 40);
+        assert_eq!(restored.liquidation_grace_slots, 150);
+        assert_eq!(restored.liquidation_grace_floor_bps, 9500);
+        assert_eq!(restored.linked_accounts, 2);
+    }
+
+    #[test]
+    fn test_from_data_decodes_zero_copy_layout() {
+        let owner = Pubkey::new_unique();
+        let mut obligation = Obligation {
+            version: 1,
+            market: Pubkey::new_unique(),
+            owner,
+            deposits: Vec::new(),
+            borrows: Vec::new(),
+            deposited_value_usd: Decimal::zero(),
+            borrowed_value_usd: Decimal::zero(),
+            last_update_timestamp: 0,
+            last_update_slot: 0,
+            liquidation_snapshot_health_factor: None,
+            unhealthy_since_slot: 0,
+            emode_category: 0,
+            isolation_mode: false,
+            credit_history: CreditHistory::default(),
+            refreshed_slot: 0,
+            liquidation_grace_slots: 0,
+            liquidation_grace_floor_bps: 0,
+            obligation_index: 1,
+            linked_accounts: 0,
+            reserved: [0; 53],
+        };
+        obligation.borrowed_value_usd = Decimal::from_integer(42).unwrap();
+
+        let mut data = ObligationZeroCopy::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(
+            &ObligationZeroCopy::pack(&obligation).unwrap(),
+        ));
+        assert_eq!(data.len(), AnyObligation::space(ObligationLayout::ZeroCopy));
+
+        let loaded = AnyObligation::from_data(&data).unwrap();
+        assert_eq!(loaded.layout(), ObligationLayout::ZeroCopy);
+        assert_eq!(loaded.owner, owner);
+        assert_eq!(loaded.borrowed_value_usd, obligation.borrowed_value_usd);
+        assert_eq!(loaded.obligation_index, 1);
+
+        // Truncated accounts are rejected rather than read past their end
+        assert!(AnyObligation::from_data(&data[..data.len() - 1]).is_err());
+    }
+}
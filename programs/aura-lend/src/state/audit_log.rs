@@ -0,0 +1,253 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::events::AdminActionEvent;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+
+/// Admin and governance instructions recorded in the audit log
+/// New variants are appended so recorded tags keep their meaning.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdminAction {
+    InitializeAdminAuditLog,
+    InitializeMarketLookupTable,
+    SetMarketLookupTable,
+    InitializeReserve,
+    UpdateReserveConfig,
+    UpdateReserveCaps,
+    UpdateReserveAssetClass,
+    UpdateReserveFeeReceiver,
+    UpdateReserveFeeSplit,
+    InitializeFeeConverter,
+    UpdateFeeConverter,
+    WaiveLargeWithdrawalDelay,
+    InitializeEModeCategory,
+    UpdateEModeCategory,
+    InitializeBackstopVault,
+    UpdateBackstopVault,
+    WithdrawBackstopVault,
+    CreateMultisigProposal,
+    SignMultisigProposal,
+    ExecuteMultisigProposal,
+    CancelMultisigProposal,
+    UpdateMultisigConfig,
+    CreateTimelockProposal,
+    ExecuteTimelockProposal,
+    CancelTimelockProposal,
+    UpdateTimelockDelays,
+    GrantRole,
+    RevokeRole,
+    DelegatePermissions,
+    UpdateGovernanceConfig,
+    EmergencyGrantRole,
+    SetUpgradeAuthority,
+    StageUpgrade,
+    CancelStagedUpgrade,
+    UpgradeProgram,
+    FreezeProgram,
+    MigrateMarket,
+    MigrateReserve,
+    MigrateObligation,
+    MigrateMultisig,
+    MigrateTimelock,
+    MigrateGovernance,
+    BatchMigrateReserves,
+    UpdateConfig,
+    EmergencyConfigUpdate,
+    UpdateReserveSecondaryOracle,
+    UpdateReserveTwapWindow,
+    CloseReserve,
+    InitializeInsuranceFund,
+    WithdrawInsuranceFund,
+    DeprecateReserve,
+    InitializeRewardPool,
+    FundRewardPool,
+    InitializeStakeVault,
+    SetReserveInterestRateStrategy,
+    AddYieldVenue,
+    UpdateYieldVenue,
+    InitializeKeeperRegistry,
+    UpdateKeeperRegistry,
+    SlashKeeper,
+    InitializeLiquidationQueue,
+    UpdateReserveSurgeFee,
+    PauseReserveOperations,
+    UnpauseReserveOperations,
+    VetoTimelockProposal,
+    InitializeProtocolMetrics,
+    UpdateReserveDerivedOracle,
+    ProposeAuthorityTransfer,
+    CancelAuthorityTransfer,
+    AcceptAuthorityTransfer,
+    ForceUnlockReserve,
+}
+
+/// One recorded admin action
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdminAuditEntry {
+    /// Position of the entry among all entries ever recorded
+    pub sequence: u64,
+
+    /// Signer that performed the action
+    pub actor: Pubkey,
+
+    /// Instruction that was executed
+    pub action: AdminAction,
+
+    /// SHA-256 hash of the instruction's key parameters (Borsh encoded)
+    pub params_hash: [u8; 32],
+
+    /// Slot the action executed in
+    pub slot: u64,
+}
+
+impl AdminAuditEntry {
+    /// Serialized size of an entry in bytes
+    pub const SIZE: usize = 8 + // sequence
+        32 + // actor
+        1 + // action
+        32 + // params_hash
+        8; // slot
+}
+
+/// Append-only audit log of admin and governance actions
+/// Entries are kept in a ring buffer of `ADMIN_AUDIT_LOG_CAPACITY` slots: once full, each
+/// new entry overwrites the oldest one. Sequence numbers keep growing, so gaps reveal
+/// how many entries were overwritten. Every admin entrypoint writes to the log through
+/// `record`, giving a forensic trail that does not depend on RPC log retention.
+#[account]
+pub struct AdminAuditLog {
+    /// Version of the audit log account structure
+    pub version: u8,
+
+    /// Market whose admin actions are recorded
+    pub market: Pubkey,
+
+    /// Recorded entries (ring buffer)
+    pub entries: Vec<AdminAuditEntry>,
+
+    /// Number of entries ever recorded; the next entry's sequence number
+    pub next_sequence: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl AdminAuditLog {
+    /// Size of the AdminAuditLog account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        4 + (ADMIN_AUDIT_LOG_CAPACITY * AdminAuditEntry::SIZE) + // entries
+        8 + // next_sequence
+        1 + // bump
+        32; // reserved
+
+    /// Create an empty audit log
+    pub fn new(market: Pubkey, bump: u8) -> Self {
+        Self {
+            version: PROGRAM_VERSION,
+            market,
+            entries: Vec::new(),
+            next_sequence: 0,
+            bump,
+            reserved: [0; 32],
+        }
+    }
+
+    /// Record an admin action in the current slot and emit it as an `AdminActionEvent`
+    /// `params` are the instruction's key parameters; only their hash is stored.
+    pub fn record<T: AnchorSerialize>(
+        &mut self,
+        actor: Pubkey,
+        action: AdminAction,
+        params: &T,
+    ) -> Result<()> {
+        let mut encoded = Vec::new();
+        params
+            .serialize(&mut encoded)
+            .map_err(|_| LendingError::InvalidInstruction)?;
+
+        let sequence = self.next_sequence;
+        let params_hash = hash(&encoded).to_bytes();
+        let slot = Clock::get()?.slot;
+        self.append(actor, action, params_hash, slot)?;
+
+        emit!(AdminActionEvent {
+            market: self.market,
+            sequence,
+            actor,
+            action,
+            params_hash,
+            slot,
+        });
+
+        Ok(())
+    }
+
+    /// Append an entry, overwriting the oldest one once the log is full
+    pub fn append(
+        &mut self,
+        actor: Pubkey,
+        action: AdminAction,
+        params_hash: [u8; 32],
+        slot: u64,
+    ) -> Result<()> {
+        let entry = AdminAuditEntry {
+            sequence: self.next_sequence,
+            actor,
+            action,
+            params_hash,
+            slot,
+        };
+
+        if self.entries.len() < ADMIN_AUDIT_LOG_CAPACITY {
+            self.entries.push(entry);
+        } else {
+            let index = (self.next_sequence % ADMIN_AUDIT_LOG_CAPACITY as u64) as usize;
+            self.entries[index] = entry;
+        }
+
+        self.next_sequence = self
+            .next_sequence
+            .checked_add(1)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Most recently recorded entry
+    pub fn latest(&self) -> Option<&AdminAuditEntry> {
+        let sequence = self.next_sequence.checked_sub(1)?;
+        let index = (sequence % ADMIN_AUDIT_LOG_CAPACITY as u64) as usize;
+        self.entries.get(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer_overwrites_oldest_entry() {
+        let mut log = AdminAuditLog::new(Pubkey::new_unique(), 255);
+        let actor = Pubkey::new_unique();
+        assert!(log.latest().is_none());
+
+        for slot in 0..ADMIN_AUDIT_LOG_CAPACITY as u64 + 2 {
+            log.append(actor, AdminAction::UpdateReserveConfig, [0; 32], slot)
+                .unwrap();
+        }
+
+        // The log stays at capacity and the two oldest entries were replaced
+        assert_eq!(log.entries.len(), ADMIN_AUDIT_LOG_CAPACITY);
+        assert_eq!(log.next_sequence, ADMIN_AUDIT_LOG_CAPACITY as u64 + 2);
+        assert_eq!(log.entries[0].sequence, ADMIN_AUDIT_LOG_CAPACITY as u64);
+        assert_eq!(log.entries[2].sequence, 2);
+
+        let latest = log.latest().unwrap();
+        assert_eq!(latest.sequence, ADMIN_AUDIT_LOG_CAPACITY as u64 + 1);
+        assert_eq!(latest.slot, ADMIN_AUDIT_LOG_CAPACITY as u64 + 1);
+    }
+}
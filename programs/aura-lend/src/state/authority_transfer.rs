@@ -0,0 +1,139 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+
+/// Authority field a two-step transfer moves
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuthorityKind {
+    /// Market multisig owner
+    MarketOwner,
+    /// Market emergency authority
+    EmergencyAuthority,
+    /// Protocol configuration authority
+    ConfigAuthority,
+    /// Treasury fee receiver token account of a reserve
+    ReserveFeeReceiver,
+    /// Upgrade authority of the program
+    UpgradeAuthority,
+}
+
+/// Pending transfer of a protocol authority to a new key
+/// The current controller proposes the new key and nothing changes until that key signs
+/// the acceptance, so a mistyped or unreachable address can never take over. One
+/// transfer may be pending per authority field, keyed by the account holding the field.
+#[account]
+pub struct AuthorityTransfer {
+    /// Version of the authority transfer account structure
+    pub version: u8,
+
+    /// Market the authority belongs to
+    pub market: Pubkey,
+
+    /// Authority field being transferred
+    pub kind: AuthorityKind,
+
+    /// Account holding the field (market, config, reserve or program data)
+    pub target: Pubkey,
+
+    /// Value of the field when the transfer was proposed
+    pub current_authority: Pubkey,
+
+    /// Key that must accept the transfer
+    pub pending_authority: Pubkey,
+
+    /// Signer that proposed the transfer and receives the rent back
+    pub proposer: Pubkey,
+
+    /// Slot the transfer was proposed at
+    pub proposed_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl AuthorityTransfer {
+    /// Size of the AuthorityTransfer account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        1 + // kind
+        32 + // target
+        32 + // current_authority
+        32 + // pending_authority
+        32 + // proposer
+        8 + // proposed_slot
+        1 + // bump
+        32; // reserved
+
+    /// Create a pending transfer
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        market: Pubkey,
+        kind: AuthorityKind,
+        target: Pubkey,
+        current_authority: Pubkey,
+        pending_authority: Pubkey,
+        proposer: Pubkey,
+        proposed_slot: u64,
+        bump: u8,
+    ) -> Result<Self> {
+        if pending_authority == current_authority || pending_authority == Pubkey::default() {
+            return Err(LendingError::InvalidAuthority.into());
+        }
+
+        Ok(Self {
+            version: PROGRAM_VERSION,
+            market,
+            kind,
+            target,
+            current_authority,
+            pending_authority,
+            proposer,
+            proposed_slot,
+            bump,
+            reserved: [0; 32],
+        })
+    }
+
+    /// Check that the field still holds the value the transfer was proposed against
+    /// A transfer proposed before the field changed by other means must not apply.
+    pub fn ensure_current(&self, current_authority: &Pubkey) -> Result<()> {
+        if *current_authority != self.current_authority {
+            return Err(LendingError::AuthorityTransferStale.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_requires_a_new_authority() {
+        let key = Pubkey::new_unique();
+        let current = Pubkey::new_unique();
+        let new = |pending| {
+            AuthorityTransfer::new(
+                key,
+                AuthorityKind::MarketOwner,
+                key,
+                current,
+                pending,
+                current,
+                10,
+                255,
+            )
+        };
+
+        assert!(new(current).is_err());
+        assert!(new(Pubkey::default()).is_err());
+
+        let transfer = new(Pubkey::new_unique()).unwrap();
+        transfer.ensure_current(&current).unwrap();
+        assert!(transfer.ensure_current(&Pubkey::new_unique()).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+
+/// Protocol-owned liquidation backstop vault
+/// Holds governance-provided liquidity for one asset and liquidates positions
+/// that external liquidators have ignored for longer than the grace window
+#[account]
+pub struct BackstopVault {
+    /// Version of the backstop vault account structure
+    pub version: u8,
+
+    /// Market this vault belongs to
+    pub market: Pubkey,
+
+    /// Mint of the liquidity the vault uses to repay debt
+    pub liquidity_mint: Pubkey,
+
+    /// Token account holding the vault's liquidity
+    pub liquidity_vault: Pubkey,
+
+    /// Slots an obligation must stay unhealthy before the vault may liquidate it
+    pub grace_slots: u64,
+
+    /// Whether keepers may currently execute backstop liquidations
+    pub enabled: bool,
+
+    /// Total liquidity contributed to the vault
+    pub total_funded: u64,
+
+    /// Total liquidity withdrawn from the vault by governance
+    pub total_withdrawn: u64,
+
+    /// Total debt repaid on behalf of unhealthy obligations
+    pub total_debt_repaid: u64,
+
+    /// Total collateral tokens (aTokens) seized into the vault
+    pub total_collateral_seized: u64,
+
+    /// Number of backstop liquidations executed
+    pub liquidations_executed: u64,
+
+    /// Slot of the last backstop liquidation
+    pub last_liquidation_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 64],
+}
+
+impl BackstopVault {
+    /// Size of the BackstopVault account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // liquidity_mint
+        32 + // liquidity_vault
+        8 + // grace_slots
+        1 + // enabled
+        8 + // total_funded
+        8 + // total_withdrawn
+        8 + // total_debt_repaid
+        8 + // total_collateral_seized
+        8 + // liquidations_executed
+        8 + // last_liquidation_slot
+        1 + // bump
+        64; // reserved
+
+    /// Create a new backstop vault
+    pub fn new(
+        market: Pubkey,
+        liquidity_mint: Pubkey,
+        liquidity_vault: Pubkey,
+        grace_slots: u64,
+        bump: u8,
+    ) -> Result<Self> {
+        Self::validate_grace_slots(grace_slots)?;
+
+        Ok(Self {
+            version: PROGRAM_VERSION,
+            market,
+            liquidity_mint,
+            liquidity_vault,
+            grace_slots,
+            enabled: true,
+            total_funded: 0,
+            total_withdrawn: 0,
+            total_debt_repaid: 0,
+            total_collateral_seized: 0,
+            liquidations_executed: 0,
+            last_liquidation_slot: 0,
+            bump,
+            reserved: [0; 64],
+        })
+    }
+
+    /// Validate the grace window configured by governance
+    pub fn validate_grace_slots(grace_slots: u64) -> Result<()> {
+        if grace_slots == 0 || grace_slots > MAX_BACKSTOP_GRACE_SLOTS {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+        Ok(())
+    }
+
+    /// Check whether an obligation unhealthy for `slots_unhealthy` slots can be taken over
+    pub fn can_liquidate(&self, slots_unhealthy: u64) -> Result<()> {
+        if !self.enabled {
+            return Err(LendingError::BackstopVaultDisabled.into());
+        }
+        if slots_unhealthy < self.grace_slots {
+            return Err(LendingError::BackstopGracePeriodActive.into());
+        }
+        Ok(())
+    }
+
+    /// Record liquidity contributed to the vault
+    pub fn record_funding(&mut self, amount: u64) -> Result<()> {
+        self.total_funded = self
+            .total_funded
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Record liquidity withdrawn from the vault
+    pub fn record_withdrawal(&mut self, amount: u64) -> Result<()> {
+        self.total_withdrawn = self
+            .total_withdrawn
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Record a completed backstop liquidation
+    pub fn record_liquidation(
+        &mut self,
+        debt_repaid: u64,
+        collateral_seized: u64,
+        slot: u64,
+    ) -> Result<()> {
+        self.total_debt_repaid = self
+            .total_debt_repaid
+            .checked_add(debt_repaid)
+            .ok_or(LendingError::MathOverflow)?;
+        self.total_collateral_seized = self
+            .total_collateral_seized
+            .checked_add(collateral_seized)
+            .ok_or(LendingError::MathOverflow)?;
+        self.liquidations_executed = self
+            .liquidations_executed
+            .checked_add(1)
+            .ok_or(LendingError::MathOverflow)?;
+        self.last_liquidation_slot = slot;
+        Ok(())
+    }
+}
+
+/// Parameters for initializing a backstop vault
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InitializeBackstopVaultParams {
+    pub grace_slots: u64,
+}
+
+/// Parameters for updating a backstop vault
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateBackstopVaultParams {
+    pub grace_slots: Option<u64>,
+    pub enabled: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_vault(grace_slots: u64) -> BackstopVault {
+        BackstopVault::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            grace_slots,
+            255,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_grace_window_enforced() {
+        let vault = test_vault(DEFAULT_BACKSTOP_GRACE_SLOTS);
+
+        assert!(vault.can_liquidate(DEFAULT_BACKSTOP_GRACE_SLOTS - 1).is_err());
+        assert!(vault.can_liquidate(DEFAULT_BACKSTOP_GRACE_SLOTS).is_ok());
+    }
+
+    #[test]
+    fn test_disabled_vault_rejects_liquidation() {
+        let mut vault = test_vault(10);
+        vault.enabled = false;
+
+        assert!(vault.can_liquidate(1_000).is_err());
+    }
+
+    #[test]
+    fn test_invalid_grace_slots() {
+        assert!(BackstopVault::validate_grace_slots(0).is_err());
+        assert!(BackstopVault::validate_grace_slots(MAX_BACKSTOP_GRACE_SLOTS + 1).is_err());
+        assert!(BackstopVault::validate_grace_slots(MAX_BACKSTOP_GRACE_SLOTS).is_ok());
+    }
+
+    #[test]
+    fn test_record_liquidation() {
+        let mut vault = test_vault(10);
+        vault.record_liquidation(1_000, 1_100, 42).unwrap();
+        vault.record_liquidation(500, 540, 50).unwrap();
+
+        assert_eq!(vault.total_debt_repaid, 1_500);
+        assert_eq!(vault.total_collateral_seized, 1_640);
+        assert_eq!(vault.liquidations_executed, 2);
+        assert_eq!(vault.last_liquidation_slot, 50);
+    }
+}
@@ -0,0 +1,382 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::utils::math::Decimal;
+use anchor_lang::prelude::*;
+
+/// Borrow capacity delegated by an obligation owner to another wallet
+/// The delegatee borrows one reserve's liquidity against the delegator's obligation,
+/// up to `borrow_limit`, and owes the delegator a markup of `markup_bps` per year on
+/// the outstanding principal on top of the pool rate. The delegator can revoke the
+/// line at any time; revocation stops new borrows while repayments stay open.
+#[account]
+pub struct CreditLine {
+    /// Version of the credit line account structure
+    pub version: u8,
+
+    /// Market this credit line belongs to
+    pub market: Pubkey,
+
+    /// Obligation the delegated borrows are recorded on
+    pub obligation: Pubkey,
+
+    /// Owner of the obligation granting the credit
+    pub delegator: Pubkey,
+
+    /// Wallet allowed to borrow against the obligation
+    pub delegatee: Pubkey,
+
+    /// Reserve the delegatee may borrow from
+    pub borrow_reserve: Pubkey,
+
+    /// Maximum outstanding principal in liquidity tokens
+    pub borrow_limit: u64,
+
+    /// Annual markup over the pool rate owed to the delegator in basis points
+    pub markup_bps: u64,
+
+    /// Outstanding principal borrowed by the delegatee
+    pub borrowed_amount: u64,
+
+    /// Markup accrued and not yet paid to the delegator
+    pub accrued_markup: u64,
+
+    /// Total markup paid to the delegator
+    pub total_markup_paid: u64,
+
+    /// Slot up to which markup has been accrued
+    pub last_accrual_slot: u64,
+
+    /// Whether the delegator revoked the line
+    pub revoked: bool,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 64],
+}
+
+impl CreditLine {
+    /// Size of the CreditLine account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // obligation
+        32 + // delegator
+        32 + // delegatee
+        32 + // borrow_reserve
+        8 + // borrow_limit
+        8 + // markup_bps
+        8 + // borrowed_amount
+        8 + // accrued_markup
+        8 + // total_markup_paid
+        8 + // last_accrual_slot
+        1 + // revoked
+        1 + // bump
+        64; // reserved
+
+    /// Create a new credit line
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        market: Pubkey,
+        obligation: Pubkey,
+        delegator: Pubkey,
+        delegatee: Pubkey,
+        borrow_reserve: Pubkey,
+        borrow_limit: u64,
+        markup_bps: u64,
+        current_slot: u64,
+        bump: u8,
+    ) -> Result<Self> {
+        if delegatee == delegator {
+            return Err(LendingError::InvalidAccount.into());
+        }
+        Self::validate_terms(borrow_limit, markup_bps)?;
+
+        Ok(Self {
+            version: PROGRAM_VERSION,
+            market,
+            obligation,
+            delegator,
+            delegatee,
+            borrow_reserve,
+            borrow_limit,
+            markup_bps,
+            borrowed_amount: 0,
+            accrued_markup: 0,
+            total_markup_paid: 0,
+            last_accrual_slot: current_slot,
+            revoked: false,
+            bump,
+            reserved: [0; 64],
+        })
+    }
+
+    /// Validate the negotiated terms of a credit line
+    pub fn validate_terms(borrow_limit: u64, markup_bps: u64) -> Result<()> {
+        if borrow_limit < MIN_BORROW_AMOUNT {
+            return Err(LendingError::AmountTooSmall.into());
+        }
+        if markup_bps > MAX_CREDIT_LINE_MARKUP_BPS {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+        Ok(())
+    }
+
+    /// Accrue markup on the outstanding principal up to `current_slot`
+    pub fn accrue_markup(&mut self, current_slot: u64) -> Result<()> {
+        let elapsed_slots = current_slot.saturating_sub(self.last_accrual_slot);
+        if elapsed_slots == 0 {
+            return Ok(());
+        }
+
+        let markup = (self.borrowed_amount as u128)
+            .checked_mul(self.markup_bps as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_mul(elapsed_slots as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div((BASIS_POINTS_PRECISION as u128) * (SLOTS_PER_YEAR as u128))
+            .ok_or(LendingError::DivisionByZero)?;
+
+        self.accrued_markup = self
+            .accrued_markup
+            .checked_add(u64::try_from(markup).map_err(|_| LendingError::MathOverflow)?)
+            .ok_or(LendingError::MathOverflow)?;
+        self.last_accrual_slot = current_slot;
+        Ok(())
+    }
+
+    /// Record a delegated borrow, enforcing revocation and the borrow limit
+    pub fn record_borrow(&mut self, amount: u64) -> Result<()> {
+        if self.revoked {
+            return Err(LendingError::CreditLineRevoked.into());
+        }
+
+        let new_borrowed_amount = self
+            .borrowed_amount
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        if new_borrowed_amount > self.borrow_limit {
+            return Err(LendingError::CreditLineLimitExceeded.into());
+        }
+
+        self.borrowed_amount = new_borrowed_amount;
+        Ok(())
+    }
+
+    /// Split a repayment into the markup paid to the delegator and principal repaid
+    /// to the reserve; markup is settled first. Returns `(markup, principal)`.
+    pub fn record_repayment(&mut self, amount: u64) -> Result<(u64, u64)> {
+        let markup = std::cmp::min(amount, self.accrued_markup);
+        let principal = std::cmp::min(amount - markup, self.borrowed_amount);
+
+        self.accrued_markup -= markup;
+        self.borrowed_amount -= principal;
+        self.total_markup_paid = self
+            .total_markup_paid
+            .checked_add(markup)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok((markup, principal))
+    }
+
+    /// Whether the delegatee owes nothing on this line
+    pub fn is_settled(&self) -> bool {
+        self.borrowed_amount == 0 && self.accrued_markup == 0
+    }
+}
+
+/// Parameters for opening a credit line
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct OpenCreditLineParams {
+    pub borrow_limit: u64,
+    pub markup_bps: u64,
+}
+
+/// Borrowing power delegated by an obligation owner to another wallet
+/// Unlike a credit line, the delegatee may borrow from any reserve, up to a total USD
+/// value valued at borrow time. The debt stays on the delegator's obligation, so the
+/// delegator carries it. Repayments do not restore the allowance; the delegator
+/// revokes and delegates again to grant more.
+#[account]
+pub struct CreditDelegation {
+    /// Version of the credit delegation account structure
+    pub version: u8,
+
+    /// Market this delegation belongs to
+    pub market: Pubkey,
+
+    /// Obligation the delegated borrows are recorded on
+    pub obligation: Pubkey,
+
+    /// Owner of the obligation granting the borrowing power
+    pub delegator: Pubkey,
+
+    /// Wallet allowed to borrow against the obligation
+    pub delegatee: Pubkey,
+
+    /// Maximum USD value the delegatee may borrow (whole USD)
+    pub borrow_limit_usd: u64,
+
+    /// USD value borrowed so far, valued at borrow time
+    pub borrowed_value_usd: Decimal,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl CreditDelegation {
+    /// Size of the CreditDelegation account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // obligation
+        32 + // delegator
+        32 + // delegatee
+        8 + // borrow_limit_usd
+        16 + // borrowed_value_usd
+        1 + // bump
+        32; // reserved
+
+    /// Create a new delegation
+    pub fn new(
+        market: Pubkey,
+        obligation: Pubkey,
+        delegator: Pubkey,
+        delegatee: Pubkey,
+        borrow_limit_usd: u64,
+        bump: u8,
+    ) -> Result<Self> {
+        if delegatee == delegator {
+            return Err(LendingError::InvalidAccount.into());
+        }
+        if borrow_limit_usd == 0 {
+            return Err(LendingError::AmountTooSmall.into());
+        }
+
+        Ok(Self {
+            version: PROGRAM_VERSION,
+            market,
+            obligation,
+            delegator,
+            delegatee,
+            borrow_limit_usd,
+            borrowed_value_usd: Decimal::zero(),
+            bump,
+            reserved: [0; 32],
+        })
+    }
+
+    /// Record a delegated borrow worth `borrow_value_usd`, enforcing the USD cap
+    pub fn record_borrow(&mut self, borrow_value_usd: Decimal) -> Result<()> {
+        let new_borrowed_value = self.borrowed_value_usd.try_add(borrow_value_usd)?;
+        if new_borrowed_value > Decimal::from_integer(self.borrow_limit_usd)? {
+            return Err(LendingError::DelegatedBorrowLimitExceeded.into());
+        }
+
+        self.borrowed_value_usd = new_borrowed_value;
+        Ok(())
+    }
+
+    /// USD value the delegatee may still borrow
+    pub fn remaining_usd(&self) -> Result<Decimal> {
+        let limit = Decimal::from_integer(self.borrow_limit_usd)?;
+        Ok(limit.try_sub(self.borrowed_value_usd).unwrap_or_default())
+    }
+}
+
+/// Parameters for delegating borrowing power
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DelegateBorrowingPowerParams {
+    pub borrow_limit_usd: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credit_line() -> CreditLine {
+        let delegator = Pubkey::new_unique();
+        CreditLine::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            delegator,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1_000_000,
+            1_000, // 10% per year
+            0,
+            255,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_credit_line_limit_and_revocation() {
+        let mut line = credit_line();
+
+        line.record_borrow(600_000).unwrap();
+        assert!(line.record_borrow(400_001).is_err());
+        line.record_borrow(400_000).unwrap();
+
+        line.revoked = true;
+        assert!(line.record_borrow(MIN_BORROW_AMOUNT).is_err());
+
+        // Repayments remain possible after revocation
+        let (markup, principal) = line.record_repayment(250_000).unwrap();
+        assert_eq!((markup, principal), (0, 250_000));
+        assert_eq!(line.borrowed_amount, 750_000);
+    }
+
+    #[test]
+    fn test_markup_accrues_and_is_paid_first() {
+        let mut line = credit_line();
+        line.record_borrow(1_000_000).unwrap();
+
+        // One year at 10% on 1_000_000
+        line.accrue_markup(SLOTS_PER_YEAR).unwrap();
+        assert_eq!(line.accrued_markup, 100_000);
+
+        let (markup, principal) = line.record_repayment(150_000).unwrap();
+        assert_eq!((markup, principal), (100_000, 50_000));
+        assert_eq!(line.total_markup_paid, 100_000);
+        assert!(!line.is_settled());
+
+        // Overpayment is capped at what is owed
+        let (markup, principal) = line.record_repayment(2_000_000).unwrap();
+        assert_eq!((markup, principal), (0, 950_000));
+        assert!(line.is_settled());
+    }
+
+    #[test]
+    fn test_credit_delegation_usd_cap() {
+        let key = Pubkey::new_unique();
+        let mut delegation =
+            CreditDelegation::new(key, key, key, Pubkey::new_unique(), 1_000, 255).unwrap();
+
+        delegation
+            .record_borrow(Decimal::from_integer(600).unwrap())
+            .unwrap();
+        assert!(delegation
+            .record_borrow(Decimal::from_integer(401).unwrap())
+            .is_err());
+        delegation
+            .record_borrow(Decimal::from_integer(400).unwrap())
+            .unwrap();
+        assert!(delegation.remaining_usd().unwrap().is_zero());
+
+        // A delegator cannot delegate to themselves, nor grant nothing
+        assert!(CreditDelegation::new(key, key, key, key, 1_000, 255).is_err());
+        assert!(CreditDelegation::new(key, key, key, Pubkey::new_unique(), 0, 255).is_err());
+    }
+
+    #[test]
+    fn test_credit_line_terms_validation() {
+        let key = Pubkey::new_unique();
+        assert!(CreditLine::new(key, key, key, key, key, 1_000_000, 100, 0, 255).is_err());
+        assert!(CreditLine::validate_terms(1_000_000, MAX_CREDIT_LINE_MARKUP_BPS + 1).is_err());
+        assert!(CreditLine::validate_terms(0, 100).is_err());
+    }
+}
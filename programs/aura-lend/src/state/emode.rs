@@ -0,0 +1,287 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::obligation::Obligation;
+use anchor_lang::prelude::*;
+
+/// Risk parameters that replace per-reserve ones while an obligation is in eMode
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EModeParams {
+    /// Loan-to-value ratio applied to every deposit (basis points)
+    pub ltv_bps: u64,
+
+    /// Liquidation threshold applied to every deposit (basis points)
+    pub liquidation_threshold_bps: u64,
+
+    /// Liquidation bonus paid on seized collateral (basis points)
+    pub liquidation_bonus_bps: u64,
+}
+
+/// Efficiency mode category of correlated assets (e.g. stablecoins, SOL and its LSTs)
+/// Obligations opted into a category borrow against its boosted LTV and liquidation
+/// threshold, and are liquidated with its bonus, while every deposit and borrow is in
+/// one of the category's reserves. Once a position falls outside the category the
+/// obligation is valued with per-reserve parameters again.
+#[account]
+pub struct EModeCategory {
+    /// Version of the eMode category account structure
+    pub version: u8,
+
+    /// Market this category belongs to
+    pub market: Pubkey,
+
+    /// Category id referenced by obligations (never 0)
+    pub id: u8,
+
+    /// Human readable label, zero padded
+    pub label: [u8; EMODE_LABEL_LEN],
+
+    /// Loan-to-value ratio inside the category (basis points)
+    pub ltv_bps: u64,
+
+    /// Liquidation threshold inside the category (basis points)
+    pub liquidation_threshold_bps: u64,
+
+    /// Liquidation bonus inside the category (basis points)
+    pub liquidation_bonus_bps: u64,
+
+    /// Reserves whose assets belong to the category
+    pub reserves: Vec<Pubkey>,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl EModeCategory {
+    /// Size of the EModeCategory account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        1 + // id
+        EMODE_LABEL_LEN + // label
+        8 + // ltv_bps
+        8 + // liquidation_threshold_bps
+        8 + // liquidation_bonus_bps
+        4 + (32 * MAX_EMODE_RESERVES) + // reserves
+        1 + // bump
+        32; // reserved
+
+    /// Create a new category
+    pub fn new(market: Pubkey, id: u8, params: EModeCategoryParams, bump: u8) -> Result<Self> {
+        if id == EMODE_NONE {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+
+        let mut category = Self {
+            version: PROGRAM_VERSION,
+            market,
+            id,
+            label: [0; EMODE_LABEL_LEN],
+            ltv_bps: 0,
+            liquidation_threshold_bps: 0,
+            liquidation_bonus_bps: 0,
+            reserves: Vec::new(),
+            bump,
+            reserved: [0; 32],
+        };
+        category.configure(params)?;
+        Ok(category)
+    }
+
+    /// Validate and apply new category parameters
+    /// The bonus is bounded so that liquidating at the threshold never seizes more
+    /// collateral than the obligation holds.
+    pub fn configure(&mut self, params: EModeCategoryParams) -> Result<()> {
+        if params.ltv_bps > MAX_EMODE_LOAN_TO_VALUE_RATIO_BPS
+            || params.liquidation_threshold_bps <= params.ltv_bps
+            || params.liquidation_threshold_bps >= BASIS_POINTS_PRECISION
+            || params.liquidation_bonus_bps > MAX_LIQUIDATION_BONUS_BPS
+        {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+
+        let seized_at_threshold = params
+            .liquidation_threshold_bps
+            .checked_mul(BASIS_POINTS_PRECISION + params.liquidation_bonus_bps)
+            .ok_or(LendingError::MathOverflow)?;
+        if seized_at_threshold > BASIS_POINTS_PRECISION * BASIS_POINTS_PRECISION {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+
+        if params.reserves.len() > MAX_EMODE_RESERVES {
+            return Err(LendingError::EModeCategoryFull.into());
+        }
+        for (index, reserve) in params.reserves.iter().enumerate() {
+            if params.reserves[..index].contains(reserve) {
+                return Err(LendingError::InvalidConfiguration.into());
+            }
+        }
+
+        self.label = params.label;
+        self.ltv_bps = params.ltv_bps;
+        self.liquidation_threshold_bps = params.liquidation_threshold_bps;
+        self.liquidation_bonus_bps = params.liquidation_bonus_bps;
+        self.reserves = params.reserves;
+        Ok(())
+    }
+
+    /// Risk parameters of the category
+    pub fn params(&self) -> EModeParams {
+        EModeParams {
+            ltv_bps: self.ltv_bps,
+            liquidation_threshold_bps: self.liquidation_threshold_bps,
+            liquidation_bonus_bps: self.liquidation_bonus_bps,
+        }
+    }
+
+    /// Whether a reserve belongs to the category
+    pub fn contains(&self, reserve: &Pubkey) -> bool {
+        self.reserves.contains(reserve)
+    }
+
+    /// Whether every position of the obligation, plus an optional position about to be
+    /// opened in `pending_reserve`, is in the category
+    pub fn covers(&self, obligation: &Obligation, pending_reserve: Option<&Pubkey>) -> bool {
+        obligation
+            .deposits
+            .iter()
+            .map(|deposit| &deposit.deposit_reserve)
+            .chain(obligation.borrows.iter().map(|borrow| &borrow.borrow_reserve))
+            .chain(pending_reserve)
+            .all(|reserve| self.contains(reserve))
+    }
+
+    /// eMode parameters that apply to an obligation
+    /// The category account is required whenever the obligation is in eMode and must be
+    /// the one it opted into. Returns None if the obligation is not in eMode or holds a
+    /// position outside the category.
+    pub fn resolve(
+        category: Option<&Self>,
+        obligation: &Obligation,
+        pending_reserve: Option<&Pubkey>,
+    ) -> Result<Option<EModeParams>> {
+        if obligation.emode_category == EMODE_NONE {
+            return Ok(None);
+        }
+
+        let category = category.ok_or(LendingError::EModeCategoryMismatch)?;
+        if category.id != obligation.emode_category || category.market != obligation.market {
+            return Err(LendingError::EModeCategoryMismatch.into());
+        }
+
+        Ok(category
+            .covers(obligation, pending_reserve)
+            .then(|| category.params()))
+    }
+}
+
+/// Parameters for defining or updating an eMode category
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EModeCategoryParams {
+    pub label: [u8; EMODE_LABEL_LEN],
+    pub ltv_bps: u64,
+    pub liquidation_threshold_bps: u64,
+    pub liquidation_bonus_bps: u64,
+    pub reserves: Vec<Pubkey>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::obligation::{ObligationCollateral, ObligationLiquidity};
+    use crate::utils::math::Decimal;
+
+    fn usd(value: u64) -> Decimal {
+        Decimal::from_integer(value).unwrap()
+    }
+
+    #[test]
+    fn test_emode_applies_only_within_category() {
+        let market = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+        let usdt = Pubkey::new_unique();
+        let sol = Pubkey::new_unique();
+
+        let params = EModeCategoryParams {
+            label: [0; EMODE_LABEL_LEN],
+            ltv_bps: 9300,
+            liquidation_threshold_bps: 9500,
+            liquidation_bonus_bps: 100,
+            reserves: vec![usdc, usdt],
+        };
+        let category = EModeCategory::new(market, 1, params.clone(), 255).unwrap();
+
+        let mut obligation = Obligation {
+            version: PROGRAM_VERSION,
+            market: market,
+            owner: Pubkey::new_unique(),
+            deposits: Vec::new(),
+            borrows: Vec::new(),
+            deposited_value_usd: Decimal::zero(),
+            borrowed_value_usd: Decimal::zero(),
+            last_update_timestamp: 0,
+            last_update_slot: 0,
+            liquidation_snapshot_health_factor: None,
+            unhealthy_since_slot: 0,
+            emode_category: 0,
+            isolation_mode: false,
+            credit_history: Default::default(),
+            refreshed_slot: 0,
+            liquidation_grace_slots: 0,
+            liquidation_grace_floor_bps: 0,
+            obligation_index: 0,
+            linked_accounts: 0,
+            reserved: [0; 53],
+        };
+        obligation.deposits.push(ObligationCollateral {
+            deposit_reserve: usdc,
+            deposited_amount: 1_000,
+            market_value_usd: usd(1_000),
+            ltv_bps: 8000,
+            liquidation_threshold_bps: 8500,
+        });
+        obligation.borrows.push(ObligationLiquidity {
+            borrow_reserve: usdt,
+            market_value_usd: usd(900),
+            ..ObligationLiquidity::default()
+        });
+        obligation.borrowed_value_usd = usd(900);
+
+        // Not opted in: per-reserve parameters, and the position is liquidatable
+        assert_eq!(EModeCategory::resolve(None, &obligation, None).unwrap(), None);
+        assert!(!obligation.is_healthy().unwrap());
+
+        // Opted in: boosted threshold keeps the position healthy
+        obligation.emode_category = 1;
+        let emode = EModeCategory::resolve(Some(&category), &obligation, None)
+            .unwrap()
+            .unwrap();
+        assert!(obligation.is_healthy_with(Some(&emode)).unwrap());
+        assert_eq!(
+            obligation.calculate_max_borrow_value_with(Some(&emode)).unwrap(),
+            usd(930)
+        );
+
+        // A borrow outside the category falls back to per-reserve parameters
+        assert_eq!(
+            EModeCategory::resolve(Some(&category), &obligation, Some(&sol)).unwrap(),
+            None
+        );
+
+        // The category account must be the one the obligation opted into
+        assert!(EModeCategory::resolve(None, &obligation, None).is_err());
+        obligation.emode_category = 2;
+        assert!(EModeCategory::resolve(Some(&category), &obligation, None).is_err());
+
+        // A bonus that would seize more than the collateral at the threshold is rejected
+        let mut category = category;
+        assert!(category
+            .configure(EModeCategoryParams {
+                liquidation_bonus_bps: 600,
+                ..params
+            })
+            .is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+
+/// Converts accumulated protocol fees into the insurance fund's asset
+/// Fee vaults are token accounts owned by this PDA; pointing a reserve's insurance
+/// fee recipient at such a vault routes that share here. Keepers then swap vault
+/// balances into `target_mint` through whitelisted DEX programs, bounded by an
+/// oracle-derived slippage limit, and deliver the output to `destination`.
+#[account]
+pub struct FeeConverter {
+    /// Version of the fee converter account structure
+    pub version: u8,
+
+    /// Market this converter belongs to
+    pub market: Pubkey,
+
+    /// Mint the insurance fund is denominated in
+    pub target_mint: Pubkey,
+
+    /// Insurance fund token account receiving converted fees
+    pub destination: Pubkey,
+
+    /// Maximum shortfall against oracle prices in basis points
+    pub max_slippage_bps: u64,
+
+    /// DEX programs conversions may be routed through
+    pub swap_programs: Vec<Pubkey>,
+
+    /// Total target tokens delivered to the destination
+    pub total_converted: u64,
+
+    /// Slot of the last conversion
+    pub last_conversion_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 64],
+}
+
+impl FeeConverter {
+    /// Size of the FeeConverter account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // target_mint
+        32 + // destination
+        8 + // max_slippage_bps
+        4 + (MAX_SWAP_PROGRAMS * 32) + // swap_programs
+        8 + // total_converted
+        8 + // last_conversion_slot
+        1 + // bump
+        64; // reserved
+
+    /// Create a new fee converter
+    pub fn new(
+        market: Pubkey,
+        target_mint: Pubkey,
+        destination: Pubkey,
+        max_slippage_bps: u64,
+        swap_programs: Vec<Pubkey>,
+        bump: u8,
+    ) -> Result<Self> {
+        Self::validate_max_slippage_bps(max_slippage_bps)?;
+        Self::validate_swap_programs(&swap_programs)?;
+
+        Ok(Self {
+            version: PROGRAM_VERSION,
+            market,
+            target_mint,
+            destination,
+            max_slippage_bps,
+            swap_programs,
+            total_converted: 0,
+            last_conversion_slot: 0,
+            bump,
+            reserved: [0; 64],
+        })
+    }
+
+    /// Validate the slippage bound configured by governance
+    pub fn validate_max_slippage_bps(max_slippage_bps: u64) -> Result<()> {
+        if max_slippage_bps > MAX_FEE_CONVERSION_SLIPPAGE_BPS {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+        Ok(())
+    }
+
+    /// Validate the DEX program whitelist configured by governance
+    pub fn validate_swap_programs(swap_programs: &[Pubkey]) -> Result<()> {
+        if swap_programs.len() > MAX_SWAP_PROGRAMS || swap_programs.contains(&crate::ID) {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+        Ok(())
+    }
+
+    /// Record a completed conversion
+    pub fn record_conversion(&mut self, amount_out: u64, slot: u64) -> Result<()> {
+        self.total_converted = self
+            .total_converted
+            .checked_add(amount_out)
+            .ok_or(LendingError::MathOverflow)?;
+        self.last_conversion_slot = slot;
+        Ok(())
+    }
+}
+
+/// Parameters for initializing the fee converter
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InitializeFeeConverterParams {
+    pub max_slippage_bps: u64,
+    pub swap_programs: Vec<Pubkey>,
+}
+
+/// Parameters for updating the fee converter
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateFeeConverterParams {
+    pub max_slippage_bps: Option<u64>,
+    pub swap_programs: Option<Vec<Pubkey>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_converter_validation() {
+        let market = Pubkey::new_unique();
+        let dex = Pubkey::new_unique();
+
+        assert!(FeeConverter::new(market, dex, dex, 100, vec![dex], 255).is_ok());
+        assert!(FeeConverter::new(
+            market,
+            dex,
+            dex,
+            MAX_FEE_CONVERSION_SLIPPAGE_BPS + 1,
+            vec![dex],
+            255
+        )
+        .is_err());
+        assert!(FeeConverter::new(market, dex, dex, 100, vec![crate::ID], 255).is_err());
+        assert!(FeeConverter::new(
+            market,
+            dex,
+            dex,
+            100,
+            vec![dex; MAX_SWAP_PROGRAMS + 1],
+            255
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,360 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT};
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+
+/// Transient per-user record of an open flash loan
+/// Created by flash-loan style instructions (flash loans, leverage loops) before
+/// the borrowed funds leave the reserve and closed once they are repaid in the same
+/// transaction. While it is open, every user-facing entrypoint checks the operation
+/// against `allowed_operations` and records it in `used_operations`, so borrowed
+/// funds cannot be routed back into the program in prohibited ways.
+#[account]
+pub struct FlashLoanGuard {
+    /// Version of the guard account structure
+    pub version: u8,
+
+    /// User the flash loan was issued to
+    pub authority: Pubkey,
+
+    /// Reserve the flash loan was taken from
+    pub reserve: Pubkey,
+
+    /// Amount borrowed
+    pub amount: u64,
+
+    /// Slot at which the flash loan was opened
+    pub opened_slot: u64,
+
+    /// Operations permitted while the loan is open (`FlashLoanUsage` bits)
+    pub allowed_operations: u32,
+
+    /// Operations performed while the loan was open (`FlashLoanUsage` bits)
+    pub used_operations: u32,
+
+    /// Whether the flash loan is still outstanding
+    pub is_open: bool,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl FlashLoanGuard {
+    /// Size of the FlashLoanGuard account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // authority
+        32 + // reserve
+        8 + // amount
+        8 + // opened_slot
+        4 + // allowed_operations
+        4 + // used_operations
+        1 + // is_open
+        1 + // bump
+        32; // reserved
+
+    /// Open a flash loan, failing if one is already outstanding for this user
+    pub fn open(
+        &mut self,
+        authority: Pubkey,
+        reserve: Pubkey,
+        amount: u64,
+        allowed_operations: u32,
+        current_slot: u64,
+        bump: u8,
+    ) -> Result<()> {
+        if self.is_active(current_slot) {
+            return Err(LendingError::FlashLoanAlreadyOpen.into());
+        }
+
+        self.version = PROGRAM_VERSION;
+        self.authority = authority;
+        self.reserve = reserve;
+        self.amount = amount;
+        self.opened_slot = current_slot;
+        self.allowed_operations = allowed_operations;
+        self.used_operations = 0;
+        self.is_open = true;
+        self.bump = bump;
+        Ok(())
+    }
+
+    /// Mark the flash loan as repaid
+    pub fn close(&mut self) {
+        self.is_open = false;
+    }
+
+    /// Whether a flash loan is outstanding in the current transaction
+    /// Guards left open in an earlier slot cannot belong to this transaction.
+    pub fn is_active(&self, current_slot: u64) -> bool {
+        self.is_open && self.opened_slot == current_slot
+    }
+
+    /// Check an operation against the allowed set and record it as used
+    pub fn record_usage(&mut self, operation: u32, current_slot: u64) -> Result<()> {
+        if !self.is_active(current_slot) {
+            return Ok(());
+        }
+
+        if self.allowed_operations & operation != operation {
+            return Err(LendingError::FlashLoanUsageProhibited.into());
+        }
+
+        self.used_operations |= operation;
+        Ok(())
+    }
+
+    /// Require the flash loan to be a top-level instruction
+    /// The guard is keyed by the borrower, so a callback signing as another wallet would
+    /// find that wallet's guard closed and could nest a second loan. Refusing CPIs rules
+    /// that out; loans placed one after another in a transaction are still allowed.
+    pub fn ensure_top_level(stack_height: usize) -> Result<()> {
+        if stack_height > TRANSACTION_LEVEL_STACK_HEIGHT {
+            return Err(LendingError::FlashLoanNested.into());
+        }
+        Ok(())
+    }
+
+    /// Open a flash loan on the user's guard PDA, creating the account on first use
+    /// `bump` is the canonical bump of `[FLASH_LOAN_GUARD_SEED, authority]`. Fails when
+    /// invoked through a CPI (see `ensure_top_level`).
+    #[allow(clippy::too_many_arguments)]
+    pub fn open_account<'info>(
+        info: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        authority: Pubkey,
+        reserve: Pubkey,
+        amount: u64,
+        allowed_operations: u32,
+        bump: u8,
+    ) -> Result<()> {
+        Self::ensure_top_level(get_stack_height())?;
+        let current_slot = Clock::get()?.slot;
+
+        let mut guard = if info.data_is_empty() {
+            Self::create_account(info, payer, system_program, &authority, bump)?;
+            Self {
+                version: PROGRAM_VERSION,
+                authority,
+                reserve,
+                amount: 0,
+                opened_slot: 0,
+                allowed_operations: 0,
+                used_operations: 0,
+                is_open: false,
+                bump,
+                reserved: [0; 32],
+            }
+        } else {
+            Self::load(info)?
+        };
+
+        guard.open(authority, reserve, amount, allowed_operations, current_slot, bump)?;
+        guard.store(info)
+    }
+
+    /// Mark the flash loan on the user's guard PDA as repaid
+    pub fn close_account(info: &AccountInfo) -> Result<()> {
+        let mut guard = Self::load(info)?;
+        guard.close();
+        guard.store(info)
+    }
+
+    /// Allocate the guard PDA, tolerating lamports sent to the address beforehand
+    fn create_account<'info>(
+        info: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        authority: &Pubkey,
+        bump: u8,
+    ) -> Result<()> {
+        let seeds: &[&[u8]] = &[FLASH_LOAN_GUARD_SEED, authority.as_ref(), &[bump]];
+        let required_lamports = Rent::get()?
+            .minimum_balance(Self::SIZE)
+            .saturating_sub(info.lamports());
+
+        if required_lamports > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    system_program::Transfer {
+                        from: payer.clone(),
+                        to: info.clone(),
+                    },
+                ),
+                required_lamports,
+            )?;
+        }
+
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Allocate {
+                    account_to_allocate: info.clone(),
+                },
+                &[seeds],
+            ),
+            Self::SIZE as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Assign {
+                    account_to_assign: info.clone(),
+                },
+                &[seeds],
+            ),
+            &crate::ID,
+        )
+    }
+
+    /// Deserialize the guard stored at `info`
+    fn load(info: &AccountInfo) -> Result<Self> {
+        if info.owner != &crate::ID {
+            return Err(LendingError::InvalidAccountOwner.into());
+        }
+
+        let data = info.try_borrow_data()?;
+        if !data.starts_with(FlashLoanGuard::DISCRIMINATOR) {
+            return Err(LendingError::InvalidAccount.into());
+        }
+        let mut slice: &[u8] = &data;
+        FlashLoanGuard::try_deserialize(&mut slice)
+    }
+
+    /// Serialize the guard back into `info`
+    fn store(&self, info: &AccountInfo) -> Result<()> {
+        let mut data = info.try_borrow_mut_data()?;
+        let mut writer: &mut [u8] = &mut data;
+        self.try_serialize(&mut writer)
+    }
+
+    /// Validate an entrypoint against the user's guard PDA
+    /// An uninitialized guard means no flash loan is open.
+    pub fn validate_entrypoint(info: &AccountInfo, operation: u32) -> Result<()> {
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(());
+        }
+
+        let current_slot = Clock::get()?.slot;
+        let mut guard = Self::load(info)?;
+
+        if !guard.is_active(current_slot) {
+            return Ok(());
+        }
+
+        guard.record_usage(operation, current_slot)?;
+        guard.store(info)
+    }
+}
+
+/// Operation bits tracked by `FlashLoanGuard`
+pub struct FlashLoanUsage;
+
+impl FlashLoanUsage {
+    /// deposit_reserve_liquidity
+    pub const DEPOSIT_LIQUIDITY: u32 = 1 << 0;
+    /// redeem_reserve_collateral
+    pub const REDEEM_COLLATERAL: u32 = 1 << 1;
+    /// deposit_obligation_collateral
+    pub const DEPOSIT_COLLATERAL: u32 = 1 << 2;
+    /// withdraw_obligation_collateral
+    pub const WITHDRAW_COLLATERAL: u32 = 1 << 3;
+    /// borrow_obligation_liquidity
+    pub const BORROW: u32 = 1 << 4;
+    /// repay_obligation_liquidity
+    pub const REPAY: u32 = 1 << 5;
+    /// liquidate_obligation
+    pub const LIQUIDATE: u32 = 1 << 6;
+
+    /// Operations a leverage loop may route flash-borrowed funds into
+    pub const LEVERAGE: u32 =
+        Self::DEPOSIT_LIQUIDITY | Self::DEPOSIT_COLLATERAL | Self::BORROW | Self::REPAY;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn closed_guard() -> FlashLoanGuard {
+        FlashLoanGuard {
+            version: PROGRAM_VERSION,
+            authority: Pubkey::default(),
+            reserve: Pubkey::default(),
+            amount: 0,
+            opened_slot: 0,
+            allowed_operations: 0,
+            used_operations: 0,
+            is_open: false,
+            bump: 0,
+            reserved: [0; 32],
+        }
+    }
+
+    #[test]
+    fn test_open_guard_restricts_operations() {
+        let mut guard = closed_guard();
+        guard
+            .open(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                1_000,
+                FlashLoanUsage::LEVERAGE,
+                50,
+                255,
+            )
+            .unwrap();
+
+        guard.record_usage(FlashLoanUsage::BORROW, 50).unwrap();
+        guard.record_usage(FlashLoanUsage::DEPOSIT_COLLATERAL, 50).unwrap();
+        assert!(guard
+            .record_usage(FlashLoanUsage::WITHDRAW_COLLATERAL, 50)
+            .is_err());
+        assert!(guard.record_usage(FlashLoanUsage::LIQUIDATE, 50).is_err());
+        assert_eq!(
+            guard.used_operations,
+            FlashLoanUsage::BORROW | FlashLoanUsage::DEPOSIT_COLLATERAL
+        );
+
+        // Nested flash loans are rejected while the first is outstanding
+        assert!(guard
+            .open(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                1,
+                FlashLoanUsage::LEVERAGE,
+                50,
+                255
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_closed_or_stale_guard_allows_everything() {
+        let mut guard = closed_guard();
+        guard
+            .open(Pubkey::new_unique(), Pubkey::new_unique(), 1_000, 0, 50, 255)
+            .unwrap();
+
+        // A guard from an earlier slot cannot belong to the current transaction
+        guard.record_usage(FlashLoanUsage::LIQUIDATE, 51).unwrap();
+
+        guard.close();
+        guard.record_usage(FlashLoanUsage::LIQUIDATE, 50).unwrap();
+        assert_eq!(guard.used_operations, 0);
+    }
+
+    #[test]
+    fn test_flash_loans_cannot_be_invoked_through_cpi() {
+        FlashLoanGuard::ensure_top_level(TRANSACTION_LEVEL_STACK_HEIGHT).unwrap();
+
+        // A callback invoking the program again runs one level deeper
+        assert!(FlashLoanGuard::ensure_top_level(TRANSACTION_LEVEL_STACK_HEIGHT + 1).is_err());
+    }
+}
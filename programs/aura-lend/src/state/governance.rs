@@ -51,7 +51,8 @@ impl GovernanceRegistry {
             | Permission::GOVERNANCE_MANAGER.bits()
             | Permission::TIMELOCK_MANAGER.bits()
             | Permission::PROGRAM_UPGRADE_MANAGER.bits()
-            | Permission::DATA_MIGRATION_MANAGER.bits();
+            | Permission::DATA_MIGRATION_MANAGER.bits()
+            | Permission::GUARDIAN.bits();
 
         Ok(Self {
             version: PROGRAM_VERSION,
@@ -216,6 +217,8 @@ pub enum RoleType {
     ProgramUpgradeManager,
     /// Can perform data migrations
     DataMigrationManager,
+    /// Can veto queued timelock proposals
+    Guardian,
 }
 
 impl Default for RoleType {
@@ -270,6 +273,9 @@ impl Permission {
     /// Can perform data migrations between versions
     pub const DATA_MIGRATION_MANAGER: Self = Self { bits: 1 << 11 };
 
+    /// Can veto timelock proposals during their delay, but not create or execute them
+    pub const GUARDIAN: Self = Self { bits: 1 << 12 };
+
     /// Get the bits value
     pub fn bits(&self) -> u64 {
         self.bits
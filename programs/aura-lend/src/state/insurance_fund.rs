@@ -0,0 +1,201 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::proposal_lifecycle::ProposalStatus;
+use crate::state::timelock::{TimelockOperationType, TimelockProposal};
+use anchor_lang::prelude::*;
+
+/// Per-reserve insurance fund
+/// Receives its reserve's share of borrow origination fees and flash loan protocol
+/// fees, and absorbs the reserve's bad debt before suppliers do. Withdrawals require an
+/// executed timelock proposal authorizing the exact amount and destination.
+#[account]
+pub struct InsuranceFund {
+    /// Version of the insurance fund account structure
+    pub version: u8,
+
+    /// Market this fund belongs to
+    pub market: Pubkey,
+
+    /// Reserve whose fees fund it
+    pub reserve: Pubkey,
+
+    /// Token account holding the fund's liquidity
+    pub liquidity_vault: Pubkey,
+
+    /// Total fees paid into the fund
+    pub total_deposited: u64,
+
+    /// Total liquidity withdrawn from the fund by governance
+    pub total_withdrawn: u64,
+
+    /// Total bad debt repaid to the reserve out of the fund
+    pub total_bad_debt_covered: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 56],
+}
+
+impl InsuranceFund {
+    /// Size of the InsuranceFund account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // reserve
+        32 + // liquidity_vault
+        8 + // total_deposited
+        8 + // total_withdrawn
+        8 + // total_bad_debt_covered
+        1 + // bump
+        56; // reserved
+
+    /// Create a new insurance fund
+    pub fn new(market: Pubkey, reserve: Pubkey, liquidity_vault: Pubkey, bump: u8) -> Self {
+        Self {
+            version: PROGRAM_VERSION,
+            market,
+            reserve,
+            liquidity_vault,
+            total_deposited: 0,
+            total_withdrawn: 0,
+            total_bad_debt_covered: 0,
+            bump,
+            reserved: [0; 56],
+        }
+    }
+
+    /// Record fees paid into the fund
+    pub fn record_deposit(&mut self, amount: u64) -> Result<()> {
+        self.total_deposited = self
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Record liquidity withdrawn from the fund
+    pub fn record_withdrawal(&mut self, amount: u64) -> Result<()> {
+        self.total_withdrawn = self
+            .total_withdrawn
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Record bad debt repaid out of the fund
+    pub fn record_bad_debt_coverage(&mut self, amount: u64) -> Result<()> {
+        self.total_bad_debt_covered = self
+            .total_bad_debt_covered
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Check that an executed timelock proposal authorizes withdrawing `amount` to `destination`
+    /// The proposal must come from the market's timelock controller, carry the
+    /// Borsh-encoded amount as its instruction data and target both this fund and
+    /// the destination.
+    pub fn validate_withdrawal_proposal(
+        &self,
+        fund: &Pubkey,
+        proposal: &TimelockProposal,
+        timelock_controller: &Pubkey,
+        destination: &Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        if proposal.status != ProposalStatus::Executed {
+            return Err(LendingError::ProposalNotExecuted.into());
+        }
+        if proposal.operation_type != TimelockOperationType::WithdrawInsuranceFund {
+            return Err(LendingError::InvalidOperationType.into());
+        }
+        if proposal.controller != *timelock_controller
+            || proposal.instruction_data != amount.to_le_bytes()
+            || !proposal.target_accounts.contains(fund)
+            || !proposal.target_accounts.contains(destination)
+        {
+            return Err(LendingError::TimelockPayloadMismatch.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_proposal(
+        controller: Pubkey,
+        amount: u64,
+        target_accounts: Vec<Pubkey>,
+    ) -> TimelockProposal {
+        TimelockProposal {
+            version: PROGRAM_VERSION,
+            controller,
+            operation_type: TimelockOperationType::WithdrawInsuranceFund,
+            instruction_data: amount.to_le_bytes().to_vec(),
+            created_at: 0,
+            execution_time: 0,
+            status: ProposalStatus::Executed,
+            proposer: Pubkey::new_unique(),
+            target_accounts,
+            operation_hash: [0; 32],
+            calls: Vec::new(),
+            reserved: [0; 64],
+        }
+    }
+
+    #[test]
+    fn test_withdrawal_requires_matching_executed_proposal() {
+        let fund_key = Pubkey::new_unique();
+        let controller = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        let fund = InsuranceFund::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            255,
+        );
+
+        let mut proposal = test_proposal(controller, 1_000, vec![fund_key, destination]);
+        assert!(fund
+            .validate_withdrawal_proposal(&fund_key, &proposal, &controller, &destination, 1_000)
+            .is_ok());
+
+        // Amount, destination and controller are bound by the proposal
+        assert!(fund
+            .validate_withdrawal_proposal(&fund_key, &proposal, &controller, &destination, 1_001)
+            .is_err());
+        assert!(fund
+            .validate_withdrawal_proposal(
+                &fund_key,
+                &proposal,
+                &controller,
+                &Pubkey::new_unique(),
+                1_000
+            )
+            .is_err());
+        assert!(fund
+            .validate_withdrawal_proposal(
+                &fund_key,
+                &proposal,
+                &Pubkey::new_unique(),
+                &destination,
+                1_000
+            )
+            .is_err());
+
+        proposal.operation_type = TimelockOperationType::WithdrawFees;
+        assert!(fund
+            .validate_withdrawal_proposal(&fund_key, &proposal, &controller, &destination, 1_000)
+            .is_err());
+
+        proposal.operation_type = TimelockOperationType::WithdrawInsuranceFund;
+        proposal.status = ProposalStatus::Ready;
+        assert!(fund
+            .validate_withdrawal_proposal(&fund_key, &proposal, &controller, &destination, 1_000)
+            .is_err());
+    }
+}
@@ -0,0 +1,389 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+
+/// Registry of staked keepers for a market
+/// Keepers lock `stake_mint` tokens in the registry's stake vault. While registered with
+/// at least `min_stake`, a keeper may liquidate obligations during the priority window
+/// after they become unhealthy (`keeper_priority_window_slots` in the protocol config),
+/// before liquidation opens to everyone. Governance can slash the stake of misbehaving
+/// keepers, which is why leaving the registry takes `exit_cooldown_slots`.
+#[account]
+pub struct KeeperRegistry {
+    /// Version of the keeper registry account structure
+    pub version: u8,
+
+    /// Market this registry belongs to
+    pub market: Pubkey,
+
+    /// Mint of the token keepers stake
+    pub stake_mint: Pubkey,
+
+    /// Token account holding the staked tokens
+    pub stake_vault: Pubkey,
+
+    /// Stake a keeper needs to hold priority
+    pub min_stake: u64,
+
+    /// Slots between requesting an exit and withdrawing the stake
+    pub exit_cooldown_slots: u64,
+
+    /// Tokens currently staked by all keepers
+    pub total_staked: u64,
+
+    /// Tokens slashed from keepers
+    pub total_slashed: u64,
+
+    /// Number of registered keepers
+    pub keeper_count: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 64],
+}
+
+impl KeeperRegistry {
+    /// Size of the KeeperRegistry account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // stake_mint
+        32 + // stake_vault
+        8 + // min_stake
+        8 + // exit_cooldown_slots
+        8 + // total_staked
+        8 + // total_slashed
+        8 + // keeper_count
+        1 + // bump
+        64; // reserved
+
+    /// Create an empty registry
+    pub fn new(
+        market: Pubkey,
+        stake_mint: Pubkey,
+        stake_vault: Pubkey,
+        min_stake: u64,
+        exit_cooldown_slots: u64,
+        bump: u8,
+    ) -> Result<Self> {
+        Self::validate_min_stake(min_stake)?;
+        Self::validate_exit_cooldown_slots(exit_cooldown_slots)?;
+
+        Ok(Self {
+            version: PROGRAM_VERSION,
+            market,
+            stake_mint,
+            stake_vault,
+            min_stake,
+            exit_cooldown_slots,
+            total_staked: 0,
+            total_slashed: 0,
+            keeper_count: 0,
+            bump,
+            reserved: [0; 64],
+        })
+    }
+
+    /// Validate the minimum stake configured by governance
+    pub fn validate_min_stake(min_stake: u64) -> Result<()> {
+        if min_stake == 0 {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+        Ok(())
+    }
+
+    /// Validate the exit cooldown configured by governance
+    pub fn validate_exit_cooldown_slots(exit_cooldown_slots: u64) -> Result<()> {
+        if exit_cooldown_slots == 0 || exit_cooldown_slots > MAX_KEEPER_EXIT_COOLDOWN_SLOTS {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+        Ok(())
+    }
+
+    /// Check that a liquidator may liquidate an obligation unhealthy for `slots_unhealthy`
+    /// Within `priority_window_slots` only an active keeper may; afterwards anyone may.
+    pub fn check_liquidation_priority(
+        &self,
+        keeper: Option<&Keeper>,
+        priority_window_slots: u64,
+        slots_unhealthy: u64,
+    ) -> Result<()> {
+        if slots_unhealthy >= priority_window_slots {
+            return Ok(());
+        }
+        match keeper {
+            Some(keeper) if keeper.is_active(self) => Ok(()),
+            _ => Err(LendingError::KeeperPriorityWindowActive.into()),
+        }
+    }
+
+    /// Record a keeper joining with `amount` staked
+    pub fn record_registration(&mut self, amount: u64) -> Result<()> {
+        self.record_stake(amount)?;
+        self.keeper_count = self
+            .keeper_count
+            .checked_add(1)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Record `amount` staked
+    pub fn record_stake(&mut self, amount: u64) -> Result<()> {
+        self.total_staked = self
+            .total_staked
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Record a keeper leaving with `amount` returned
+    pub fn record_exit(&mut self, amount: u64) -> Result<()> {
+        self.total_staked = self
+            .total_staked
+            .checked_sub(amount)
+            .ok_or(LendingError::MathUnderflow)?;
+        self.keeper_count = self.keeper_count.saturating_sub(1);
+        Ok(())
+    }
+
+    /// Record `amount` slashed
+    pub fn record_slash(&mut self, amount: u64) -> Result<()> {
+        self.total_staked = self
+            .total_staked
+            .checked_sub(amount)
+            .ok_or(LendingError::MathUnderflow)?;
+        self.total_slashed = self
+            .total_slashed
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+/// A keeper's registration and stake
+#[account]
+pub struct Keeper {
+    /// Version of the keeper account structure
+    pub version: u8,
+
+    /// Registry the keeper is registered in
+    pub registry: Pubkey,
+
+    /// Wallet liquidating as the keeper
+    pub authority: Pubkey,
+
+    /// Tokens staked
+    pub staked_amount: u64,
+
+    /// Tokens slashed by governance
+    pub slashed_amount: u64,
+
+    /// Slot the keeper registered
+    pub registered_slot: u64,
+
+    /// Slot the keeper requested to exit (0 = active)
+    pub exit_requested_slot: u64,
+
+    /// Liquidations executed as a keeper
+    pub liquidations_executed: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl Keeper {
+    /// Size of the Keeper account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // registry
+        32 + // authority
+        8 + // staked_amount
+        8 + // slashed_amount
+        8 + // registered_slot
+        8 + // exit_requested_slot
+        8 + // liquidations_executed
+        1 + // bump
+        32; // reserved
+
+    /// Register `authority` with `staked_amount`, which must meet the registry minimum
+    pub fn new(
+        registry: &KeeperRegistry,
+        registry_key: Pubkey,
+        authority: Pubkey,
+        staked_amount: u64,
+        slot: u64,
+        bump: u8,
+    ) -> Result<Self> {
+        if staked_amount < registry.min_stake {
+            return Err(LendingError::KeeperStakeTooLow.into());
+        }
+
+        Ok(Self {
+            version: PROGRAM_VERSION,
+            registry: registry_key,
+            authority,
+            staked_amount,
+            slashed_amount: 0,
+            registered_slot: slot,
+            exit_requested_slot: 0,
+            liquidations_executed: 0,
+            bump,
+            reserved: [0; 32],
+        })
+    }
+
+    /// Whether the keeper currently holds liquidation priority
+    pub fn is_active(&self, registry: &KeeperRegistry) -> bool {
+        self.exit_requested_slot == 0 && self.staked_amount >= registry.min_stake
+    }
+
+    /// Add `amount` to the stake
+    pub fn add_stake(&mut self, amount: u64) -> Result<()> {
+        if self.exit_requested_slot != 0 {
+            return Err(LendingError::KeeperExitPending.into());
+        }
+        self.staked_amount = self
+            .staked_amount
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Start the exit cooldown, giving up priority right away
+    pub fn request_exit(&mut self, slot: u64) -> Result<()> {
+        if self.exit_requested_slot != 0 {
+            return Err(LendingError::KeeperExitPending.into());
+        }
+        self.exit_requested_slot = slot;
+        Ok(())
+    }
+
+    /// Check that the exit cooldown has passed
+    pub fn check_exit(&self, exit_cooldown_slots: u64, slot: u64) -> Result<()> {
+        if self.exit_requested_slot == 0
+            || slot < self.exit_requested_slot.saturating_add(exit_cooldown_slots)
+        {
+            return Err(LendingError::KeeperExitCooldownActive.into());
+        }
+        Ok(())
+    }
+
+    /// Slash up to `amount` of the stake, returning what was slashed
+    pub fn slash(&mut self, amount: u64) -> Result<u64> {
+        let slashed = amount.min(self.staked_amount);
+        self.staked_amount -= slashed;
+        self.slashed_amount = self
+            .slashed_amount
+            .checked_add(slashed)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(slashed)
+    }
+
+    /// Count a liquidation executed as a keeper
+    pub fn record_liquidation(&mut self) {
+        self.liquidations_executed = self.liquidations_executed.saturating_add(1);
+    }
+}
+
+/// Parameters for creating a keeper registry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InitializeKeeperRegistryParams {
+    pub min_stake: u64,
+    pub exit_cooldown_slots: u64,
+}
+
+/// Parameters for updating a keeper registry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateKeeperRegistryParams {
+    pub min_stake: Option<u64>,
+    pub exit_cooldown_slots: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_registry() -> KeeperRegistry {
+        KeeperRegistry::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1_000,
+            100,
+            255,
+        )
+        .unwrap()
+    }
+
+    fn test_keeper(registry: &KeeperRegistry) -> Keeper {
+        Keeper::new(
+            registry,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1_000,
+            10,
+            255,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_liquidation_priority_window() {
+        let registry = test_registry();
+        let mut keeper = test_keeper(&registry);
+
+        // Inside the window only an active keeper may liquidate
+        assert!(registry.check_liquidation_priority(None, 20, 0).is_err());
+        assert!(registry.check_liquidation_priority(None, 20, 19).is_err());
+        registry
+            .check_liquidation_priority(Some(&keeper), 20, 0)
+            .unwrap();
+
+        // Once it passes, or with the window disabled, anyone may
+        registry.check_liquidation_priority(None, 20, 20).unwrap();
+        registry.check_liquidation_priority(None, 0, 0).unwrap();
+
+        // A keeper slashed below the minimum or exiting loses priority
+        keeper.slash(1).unwrap();
+        assert!(registry
+            .check_liquidation_priority(Some(&keeper), 20, 0)
+            .is_err());
+        let mut exiting = test_keeper(&registry);
+        exiting.request_exit(50).unwrap();
+        assert!(registry
+            .check_liquidation_priority(Some(&exiting), 20, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_keeper_exit_and_slash() {
+        let registry = test_registry();
+        assert!(Keeper::new(
+            &registry,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            999,
+            10,
+            255
+        )
+        .is_err());
+
+        let mut keeper = test_keeper(&registry);
+        assert!(keeper.check_exit(100, 1_000).is_err());
+        keeper.request_exit(500).unwrap();
+        assert!(keeper.request_exit(501).is_err());
+        assert!(keeper.add_stake(1).is_err());
+        assert!(keeper.check_exit(100, 599).is_err());
+        keeper.check_exit(100, 600).unwrap();
+
+        // Slashing is capped at the remaining stake, even during the cooldown
+        assert_eq!(keeper.slash(1_500).unwrap(), 1_000);
+        assert_eq!(keeper.staked_amount, 0);
+        assert_eq!(keeper.slashed_amount, 1_000);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::utils::math::Decimal;
+use anchor_lang::prelude::*;
+
+/// Dutch auction liquidating one debt/collateral pair of an unhealthy obligation
+/// The discount on the collateral rises linearly from zero to `max_discount_bps` over
+/// `duration_slots`, so liquidators fill once the discount covers their costs instead
+/// of being paid a fixed bonus.
+#[account]
+pub struct LiquidationAuction {
+    /// Version of the liquidation auction account structure
+    pub version: u8,
+
+    /// Market this auction belongs to
+    pub market: Pubkey,
+
+    /// Obligation being liquidated
+    pub obligation: Pubkey,
+
+    /// Reserve of the debt being repaid
+    pub repay_reserve: Pubkey,
+
+    /// Reserve of the collateral being sold
+    pub withdraw_reserve: Pubkey,
+
+    /// Keeper who started the auction and receives its rent when it is closed
+    pub initiator: Pubkey,
+
+    /// Slot the auction started in
+    pub start_slot: u64,
+
+    /// Slots over which the discount rises to its maximum
+    pub duration_slots: u64,
+
+    /// Discount on the collateral at the end of the curve (basis points)
+    pub max_discount_bps: u64,
+
+    /// Most debt that may be repaid through the auction (close factor at start)
+    pub max_repay_amount: u64,
+
+    /// Debt repaid by bids so far
+    pub repaid_amount: u64,
+
+    /// Collateral tokens (aTokens) sold by bids so far
+    pub collateral_sold: u64,
+
+    /// Health factor of the obligation when the auction started
+    pub health_factor_at_start: Decimal,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 64],
+}
+
+impl LiquidationAuction {
+    /// Size of the LiquidationAuction account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // obligation
+        32 + // repay_reserve
+        32 + // withdraw_reserve
+        32 + // initiator
+        8 + // start_slot
+        8 + // duration_slots
+        8 + // max_discount_bps
+        8 + // max_repay_amount
+        8 + // repaid_amount
+        8 + // collateral_sold
+        16 + // health_factor_at_start
+        1 + // bump
+        64; // reserved
+
+    /// Create an auction starting at `start_slot`
+    /// The discount curve ends at `LIQUIDATION_AUCTION_DISCOUNT_MULTIPLIER` times the
+    /// fixed liquidation bonus, capped at `MAX_LIQUIDATION_BONUS_BPS`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        market: Pubkey,
+        obligation: Pubkey,
+        repay_reserve: Pubkey,
+        withdraw_reserve: Pubkey,
+        initiator: Pubkey,
+        start_slot: u64,
+        liquidation_bonus_bps: u64,
+        max_repay_amount: u64,
+        health_factor_at_start: Decimal,
+        bump: u8,
+    ) -> Result<Self> {
+        let max_discount_bps = liquidation_bonus_bps
+            .checked_mul(LIQUIDATION_AUCTION_DISCOUNT_MULTIPLIER)
+            .ok_or(LendingError::MathOverflow)?
+            .min(MAX_LIQUIDATION_BONUS_BPS);
+
+        Ok(Self {
+            version: PROGRAM_VERSION,
+            market,
+            obligation,
+            repay_reserve,
+            withdraw_reserve,
+            initiator,
+            start_slot,
+            duration_slots: LIQUIDATION_AUCTION_DURATION_SLOTS,
+            max_discount_bps,
+            max_repay_amount,
+            repaid_amount: 0,
+            collateral_sold: 0,
+            health_factor_at_start,
+            bump,
+            reserved: [0; 64],
+        })
+    }
+
+    /// Discount offered on the collateral at `slot` (basis points)
+    pub fn current_discount_bps(&self, slot: u64) -> u64 {
+        if self.duration_slots == 0 {
+            return self.max_discount_bps;
+        }
+        let elapsed = slot
+            .saturating_sub(self.start_slot)
+            .min(self.duration_slots);
+        (self.max_discount_bps as u128 * elapsed as u128 / self.duration_slots as u128) as u64
+    }
+
+    /// Debt that bids may still repay
+    pub fn remaining_repay_amount(&self) -> u64 {
+        self.max_repay_amount.saturating_sub(self.repaid_amount)
+    }
+
+    /// Check if bids have repaid all the debt offered
+    pub fn is_filled(&self) -> bool {
+        self.remaining_repay_amount() == 0
+    }
+
+    /// Check if the auction has run long enough to be closed unfinished
+    pub fn is_expired(&self, slot: u64) -> bool {
+        slot.saturating_sub(self.start_slot) >= LIQUIDATION_AUCTION_EXPIRY_SLOTS
+    }
+
+    /// Record a bid repaying `repaid` debt for `collateral` aTokens
+    pub fn record_fill(&mut self, repaid: u64, collateral: u64) -> Result<()> {
+        if repaid > self.remaining_repay_amount() {
+            return Err(LendingError::LiquidationTooLarge.into());
+        }
+        self.repaid_amount = self
+            .repaid_amount
+            .checked_add(repaid)
+            .ok_or(LendingError::MathOverflow)?;
+        self.collateral_sold = self
+            .collateral_sold
+            .checked_add(collateral)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_auction(liquidation_bonus_bps: u64) -> LiquidationAuction {
+        LiquidationAuction::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1_000,
+            liquidation_bonus_bps,
+            10_000,
+            Decimal::zero(),
+            255,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_discount_rises_linearly_to_cap() {
+        let auction = test_auction(500);
+        assert_eq!(auction.max_discount_bps, 1000);
+        assert_eq!(auction.current_discount_bps(999), 0);
+        assert_eq!(auction.current_discount_bps(1_000), 0);
+        assert_eq!(
+            auction.current_discount_bps(1_000 + LIQUIDATION_AUCTION_DURATION_SLOTS / 2),
+            500
+        );
+        assert_eq!(
+            auction.current_discount_bps(1_000 + LIQUIDATION_AUCTION_DURATION_SLOTS * 10),
+            1000
+        );
+
+        // The curve never ends above the global bonus cap
+        assert_eq!(
+            test_auction(2500).max_discount_bps,
+            MAX_LIQUIDATION_BONUS_BPS
+        );
+    }
+
+    #[test]
+    fn test_fills_are_bounded_by_offered_debt() {
+        let mut auction = test_auction(500);
+        auction.record_fill(6_000, 100).unwrap();
+        assert!(auction.record_fill(4_001, 100).is_err());
+        auction.record_fill(4_000, 100).unwrap();
+        assert!(auction.is_filled());
+        assert_eq!(auction.collateral_sold, 200);
+
+        assert!(!auction.is_expired(1_000 + LIQUIDATION_AUCTION_EXPIRY_SLOTS - 1));
+        assert!(auction.is_expired(1_000 + LIQUIDATION_AUCTION_EXPIRY_SLOTS));
+    }
+}
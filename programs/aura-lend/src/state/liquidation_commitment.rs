@@ -0,0 +1,159 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::utils::OraclePrice;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+/// A liquidator's commitment to liquidate an obligation, with the prices pinned at commit
+/// `commit_liquidation` stores a hash of the liquidation amount and a secret salt, along
+/// with the (TWAP-smoothed) liquidation prices of both reserves. `execute_liquidation`
+/// reveals the amount and salt between `MIN_LIQUIDATION_REVEAL_DELAY_SLOTS` and
+/// `LIQUIDATION_COMMITMENT_EXPIRY_SLOTS` later and liquidates at the pinned prices, so an
+/// oracle update landing in between cannot be sandwiched around the liquidation.
+#[account]
+pub struct LiquidationCommitment {
+    /// Version of the liquidation commitment account structure
+    pub version: u8,
+
+    /// Obligation to be liquidated
+    pub obligation: Pubkey,
+
+    /// Liquidator who committed
+    pub liquidator: Pubkey,
+
+    /// Reserve whose debt will be repaid
+    pub repay_reserve: Pubkey,
+
+    /// Reserve whose collateral will be seized
+    pub withdraw_reserve: Pubkey,
+
+    /// Hash of the liquidation amount and salt (see `commitment_hash`)
+    pub commitment_hash: [u8; 32],
+
+    /// Slot of the commitment
+    pub committed_slot: u64,
+
+    /// Liquidation price of the repay asset at commit
+    pub repay_price: OraclePrice,
+
+    /// Liquidation price of the withdraw asset at commit
+    pub withdraw_price: OraclePrice,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl LiquidationCommitment {
+    /// Size of an `OraclePrice` in bytes
+    const ORACLE_PRICE_SIZE: usize = 8 + // price
+        8 + // confidence
+        4 + // exponent
+        8; // publish_time
+
+    /// Size of the LiquidationCommitment account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // obligation
+        32 + // liquidator
+        32 + // repay_reserve
+        32 + // withdraw_reserve
+        32 + // commitment_hash
+        8 + // committed_slot
+        Self::ORACLE_PRICE_SIZE + // repay_price
+        Self::ORACLE_PRICE_SIZE + // withdraw_price
+        1 + // bump
+        32; // reserved
+
+    /// Hash a liquidator commits to for liquidating `liquidity_amount` of an obligation
+    pub fn commitment_hash(
+        obligation: &Pubkey,
+        liquidator: &Pubkey,
+        liquidity_amount: u64,
+        salt: &[u8; 32],
+    ) -> [u8; 32] {
+        hashv(&[
+            obligation.as_ref(),
+            liquidator.as_ref(),
+            &liquidity_amount.to_le_bytes(),
+            salt,
+        ])
+        .to_bytes()
+    }
+
+    /// Check a revealed liquidation against the commitment
+    pub fn check_reveal(&self, liquidity_amount: u64, salt: &[u8; 32], slot: u64) -> Result<()> {
+        let elapsed = slot.saturating_sub(self.committed_slot);
+        if elapsed < MIN_LIQUIDATION_REVEAL_DELAY_SLOTS {
+            return Err(LendingError::LiquidationRevealTooEarly.into());
+        }
+        if elapsed > LIQUIDATION_COMMITMENT_EXPIRY_SLOTS {
+            return Err(LendingError::LiquidationCommitmentExpired.into());
+        }
+
+        let revealed =
+            Self::commitment_hash(&self.obligation, &self.liquidator, liquidity_amount, salt);
+        if revealed != self.commitment_hash {
+            return Err(LendingError::LiquidationCommitmentMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// Whether the commitment can no longer be revealed
+    pub fn is_expired(&self, slot: u64) -> bool {
+        slot.saturating_sub(self.committed_slot) > LIQUIDATION_COMMITMENT_EXPIRY_SLOTS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_price() -> OraclePrice {
+        OraclePrice {
+            price: 100_000_000,
+            confidence: 10_000,
+            exponent: -6,
+            publish_time: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_check_reveal() {
+        let obligation = Pubkey::new_unique();
+        let liquidator = Pubkey::new_unique();
+        let salt = [7u8; 32];
+        let commitment = LiquidationCommitment {
+            version: PROGRAM_VERSION,
+            obligation,
+            liquidator,
+            repay_reserve: Pubkey::new_unique(),
+            withdraw_reserve: Pubkey::new_unique(),
+            commitment_hash: LiquidationCommitment::commitment_hash(
+                &obligation,
+                &liquidator,
+                500,
+                &salt,
+            ),
+            committed_slot: 100,
+            repay_price: test_price(),
+            withdraw_price: test_price(),
+            bump: 255,
+            reserved: [0; 32],
+        };
+
+        // Only the committed amount and salt reveal, and only within the window
+        assert!(commitment.check_reveal(500, &salt, 101).is_err());
+        commitment.check_reveal(500, &salt, 102).unwrap();
+        assert!(commitment.check_reveal(501, &salt, 102).is_err());
+        assert!(commitment.check_reveal(500, &[8u8; 32], 102).is_err());
+
+        let expiry = 100 + LIQUIDATION_COMMITMENT_EXPIRY_SLOTS;
+        commitment.check_reveal(500, &salt, expiry).unwrap();
+        assert!(!commitment.is_expired(expiry));
+        assert!(commitment.check_reveal(500, &salt, expiry + 1).is_err());
+        assert!(commitment.is_expired(expiry + 1));
+    }
+}
@@ -0,0 +1,189 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+
+/// An unhealthy obligation listed in the liquidation queue
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LiquidationQueueEntry {
+    /// Obligation that was found unhealthy
+    pub obligation: Pubkey,
+
+    /// Health factor at the last refresh (basis points)
+    pub health_factor_bps: u64,
+
+    /// Slot the obligation became unhealthy
+    pub unhealthy_since_slot: u64,
+
+    /// Slot of the last refresh that found the obligation unhealthy
+    pub refreshed_slot: u64,
+}
+
+impl LiquidationQueueEntry {
+    /// Serialized size of an entry in bytes
+    pub const SIZE: usize = 32 + // obligation
+        8 + // health_factor_bps
+        8 + // unhealthy_since_slot
+        8; // refreshed_slot
+
+    /// Whether the entry has gone unrefreshed for longer than the queue keeps entries
+    pub fn is_expired(&self, current_slot: u64) -> bool {
+        current_slot.saturating_sub(self.refreshed_slot) > LIQUIDATION_QUEUE_ENTRY_TTL_SLOTS
+    }
+}
+
+/// Queue of a market's unhealthy obligations
+/// `refresh_obligation` lists every obligation it finds unhealthy and delists it once
+/// healthy again, so liquidators can poll a single account instead of scanning every
+/// obligation. Liquidations and repayments delist the obligation too; a later refresh
+/// lists it again if it is still unhealthy. Entries not refreshed within
+/// `LIQUIDATION_QUEUE_ENTRY_TTL_SLOTS` expire, and once the queue holds
+/// `LIQUIDATION_QUEUE_CAPACITY` entries a new one overwrites the stalest.
+#[account]
+pub struct LiquidationQueue {
+    /// Version of the liquidation queue account structure
+    pub version: u8,
+
+    /// Market whose obligations are listed
+    pub market: Pubkey,
+
+    /// Listed obligations
+    pub entries: Vec<LiquidationQueueEntry>,
+
+    /// Number of obligations ever listed
+    pub total_enqueued: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl LiquidationQueue {
+    /// Size of the LiquidationQueue account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        4 + (LIQUIDATION_QUEUE_CAPACITY * LiquidationQueueEntry::SIZE) + // entries
+        8 + // total_enqueued
+        1 + // bump
+        32; // reserved
+
+    /// Create an empty queue
+    pub fn new(market: Pubkey, bump: u8) -> Self {
+        Self {
+            version: PROGRAM_VERSION,
+            market,
+            entries: Vec::new(),
+            total_enqueued: 0,
+            bump,
+            reserved: [0; 32],
+        }
+    }
+
+    /// List `obligation`, or update its entry if it is already listed
+    pub fn enqueue(
+        &mut self,
+        obligation: Pubkey,
+        health_factor_bps: u64,
+        unhealthy_since_slot: u64,
+        current_slot: u64,
+    ) -> Result<()> {
+        self.remove_expired(current_slot);
+
+        let entry = LiquidationQueueEntry {
+            obligation,
+            health_factor_bps,
+            unhealthy_since_slot,
+            refreshed_slot: current_slot,
+        };
+
+        if let Some(existing) = self.entries.iter_mut().find(|e| e.obligation == obligation) {
+            *existing = entry;
+            return Ok(());
+        }
+
+        if self.entries.len() < LIQUIDATION_QUEUE_CAPACITY {
+            self.entries.push(entry);
+        } else if let Some(stalest) = self.entries.iter_mut().min_by_key(|e| e.refreshed_slot) {
+            *stalest = entry;
+        }
+
+        self.total_enqueued = self
+            .total_enqueued
+            .checked_add(1)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Delist `obligation`, returning whether it was listed
+    pub fn remove(&mut self, obligation: &Pubkey) -> bool {
+        match self
+            .entries
+            .iter()
+            .position(|e| e.obligation == *obligation)
+        {
+            Some(index) => {
+                self.entries.swap_remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drop entries that have expired
+    pub fn remove_expired(&mut self, current_slot: u64) {
+        self.entries.retain(|e| !e.is_expired(current_slot));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enqueue_updates_and_overwrites_stalest() {
+        let mut queue = LiquidationQueue::new(Pubkey::new_unique(), 255);
+        let first = Pubkey::new_unique();
+
+        queue.enqueue(first, 9_500, 10, 10).unwrap();
+        queue.enqueue(first, 9_000, 10, 20).unwrap();
+        assert_eq!(queue.entries.len(), 1);
+        assert_eq!(queue.total_enqueued, 1);
+        assert_eq!(queue.entries[0].health_factor_bps, 9_000);
+        assert_eq!(queue.entries[0].refreshed_slot, 20);
+
+        for slot in 0..LIQUIDATION_QUEUE_CAPACITY as u64 - 1 {
+            queue
+                .enqueue(Pubkey::new_unique(), 9_900, 30, 30 + slot)
+                .unwrap();
+        }
+        assert_eq!(queue.entries.len(), LIQUIDATION_QUEUE_CAPACITY);
+
+        // A full queue drops its stalest entry for the new one
+        let last = Pubkey::new_unique();
+        queue.enqueue(last, 9_800, 200, 200).unwrap();
+        assert_eq!(queue.entries.len(), LIQUIDATION_QUEUE_CAPACITY);
+        assert!(queue.entries.iter().all(|e| e.obligation != first));
+        assert!(queue.remove(&last));
+        assert!(!queue.remove(&last));
+    }
+
+    #[test]
+    fn test_entries_expire() {
+        let mut queue = LiquidationQueue::new(Pubkey::new_unique(), 255);
+        let stale = Pubkey::new_unique();
+        let fresh = Pubkey::new_unique();
+
+        queue.enqueue(stale, 9_000, 100, 100).unwrap();
+        queue.enqueue(fresh, 9_000, 100, 500).unwrap();
+
+        let expiry = 100 + LIQUIDATION_QUEUE_ENTRY_TTL_SLOTS;
+        assert!(!queue.entries[0].is_expired(expiry));
+        assert!(queue.entries[0].is_expired(expiry + 1));
+
+        queue.remove_expired(expiry + 1);
+        assert_eq!(queue.entries.len(), 1);
+        assert_eq!(queue.entries[0].obligation, fresh);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::address_lookup_table::{self, state::AddressLookupTable};
+
+/// Canonical Address Lookup Table of the market
+/// Clients compile v0 transactions against this table so instructions touching many
+/// reserves fit within transaction account limits. Governance extends the table
+/// off-chain when reserves are added and records how many reserves it covers.
+#[account]
+pub struct MarketLookupTable {
+    /// Version of the lookup table record structure
+    pub version: u8,
+
+    /// Market this record belongs to
+    pub market: Pubkey,
+
+    /// Address Lookup Table account
+    pub lookup_table: Pubkey,
+
+    /// Number of market reserves whose accounts the table contains
+    pub covered_reserves: u64,
+
+    /// Incremented whenever the table or its coverage changes
+    pub revision: u64,
+
+    /// Slot of the last update
+    pub last_update_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl MarketLookupTable {
+    /// Size of the MarketLookupTable account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // lookup_table
+        8 + // covered_reserves
+        8 + // revision
+        8 + // last_update_slot
+        1 + // bump
+        32; // reserved
+
+    /// Create a new lookup table record
+    pub fn new(market: Pubkey, bump: u8) -> Self {
+        Self {
+            version: PROGRAM_VERSION,
+            market,
+            lookup_table: Pubkey::default(),
+            covered_reserves: 0,
+            revision: 0,
+            last_update_slot: 0,
+            bump,
+            reserved: [0; 32],
+        }
+    }
+
+    /// Point the record at a lookup table covering `covered_reserves` reserves
+    pub fn set(&mut self, lookup_table: Pubkey, covered_reserves: u64, slot: u64) -> Result<()> {
+        self.lookup_table = lookup_table;
+        self.covered_reserves = covered_reserves;
+        self.revision = self.revision.checked_add(1).ok_or(LendingError::MathOverflow)?;
+        self.last_update_slot = slot;
+        Ok(())
+    }
+
+    /// Validate a lookup table account before it is recorded
+    /// The table must be controlled by the market authority or frozen, so nobody
+    /// else can change the addresses clients resolve through it.
+    pub fn validate_table(lookup_table: &AccountInfo, market_authority: &Pubkey) -> Result<()> {
+        if lookup_table.owner != &address_lookup_table::program::id() {
+            return Err(LendingError::InvalidAccountOwner.into());
+        }
+
+        let data = lookup_table.try_borrow_data()?;
+        let table =
+            AddressLookupTable::deserialize(&data).map_err(|_| LendingError::InvalidAccount)?;
+
+        match table.meta.authority {
+            Some(authority) if authority != *market_authority => {
+                Err(LendingError::InvalidAuthority.into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Parameters for recording the market lookup table
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetMarketLookupTableParams {
+    pub covered_reserves: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_bumps_revision() {
+        let mut record = MarketLookupTable::new(Pubkey::new_unique(), 255);
+        let table = Pubkey::new_unique();
+
+        record.set(table, 3, 100).unwrap();
+        record.set(table, 4, 200).unwrap();
+
+        assert_eq!(record.lookup_table, table);
+        assert_eq!(record.covered_reserves, 4);
+        assert_eq!(record.revision, 2);
+        assert_eq!(record.last_update_slot, 200);
+    }
+}
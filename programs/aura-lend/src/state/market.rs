@@ -142,6 +142,15 @@ impl Market {
             .ok_or(crate::error::LendingError::MathOverflow)?;
         Ok(())
     }
+
+    /// Decrement the reserves count when a reserve is closed
+    pub fn decrement_reserves_count(&mut self) -> Result<()> {
+        self.reserves_count = self
+            .reserves_count
+            .checked_sub(1)
+            .ok_or(crate::error::LendingError::MathUnderflow)?;
+        Ok(())
+    }
 }
 
 /// Market configuration flags
@@ -1,6 +1,8 @@
 use crate::constants::*;
 use crate::error::LendingError;
+use crate::state::proposal_lifecycle::{ProposalLifecycle, ProposalStatus};
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
 
 /// Multi-signature wallet for critical protocol operations
 /// Requires multiple signatures before execution
@@ -117,14 +119,17 @@ pub struct MultisigProposal {
     /// Timestamp when proposal was created
     pub created_at: i64,
 
-    /// Timestamp when proposal expires (optional)
+    /// Timestamp when proposal expires (always set for new proposals)
     pub expires_at: Option<i64>,
 
     /// The account that created this proposal
     pub proposer: Pubkey,
 
+    /// SHA-256 hash of the exact payload the proposal authorizes
+    pub payload_hash: [u8; 32],
+
     /// Reserved space for future upgrades
-    pub reserved: [u8; 64],
+    pub reserved: [u8; 32],
 }
 
 impl MultisigProposal {
@@ -143,22 +148,30 @@ impl MultisigProposal {
         8 + // created_at
         1 + 8 + // expires_at (Option<i64>)
         32 + // proposer
-        64; // reserved
+        32 + // payload_hash
+        32; // reserved
 
     /// Create a new proposal
+    /// `payload_hash` must be the hash of `instruction_data`, so signers approve exactly
+    /// the payload that will later be executed.
     pub fn new(
         multisig: Pubkey,
         nonce: u64,
         operation_type: MultisigOperationType,
         instruction_data: Vec<u8>,
+        payload_hash: [u8; 32],
         proposer: Pubkey,
         expires_at: Option<i64>,
     ) -> Result<Self> {
         if instruction_data.len() > Self::MAX_INSTRUCTION_SIZE {
             return Err(LendingError::InstructionTooLarge.into());
         }
+        if Self::hash_payload(&instruction_data) != payload_hash {
+            return Err(LendingError::MultisigPayloadMismatch.into());
+        }
 
         let clock = Clock::get()?;
+        let expires_at = Self::resolve_expiry(clock.unix_timestamp, expires_at)?;
         Ok(Self {
             version: PROGRAM_VERSION,
             multisig,
@@ -166,21 +179,67 @@ impl MultisigProposal {
             operation_type,
             instruction_data,
             signatures: Vec::new(),
-            status: ProposalStatus::Active,
+            status: ProposalStatus::Draft,
             created_at: clock.unix_timestamp,
-            expires_at,
+            expires_at: Some(expires_at),
             proposer,
-            reserved: [0; 64],
+            payload_hash,
+            reserved: [0; 32],
         })
     }
 
+    /// Hash committing to an execution payload
+    pub fn hash_payload(payload: &[u8]) -> [u8; 32] {
+        hash(payload).to_bytes()
+    }
+
+    /// Expiry of a proposal created at `now`
+    /// Defaults to `MULTISIG_PROPOSAL_DEFAULT_LIFETIME`; a requested expiry must lie in
+    /// the future and within `MULTISIG_PROPOSAL_MAX_LIFETIME`.
+    pub fn resolve_expiry(now: i64, requested: Option<i64>) -> Result<i64> {
+        let expires_at = match requested {
+            Some(expires_at) => expires_at,
+            None => now
+                .checked_add(MULTISIG_PROPOSAL_DEFAULT_LIFETIME)
+                .ok_or(LendingError::MathOverflow)?,
+        };
+        if expires_at <= now || expires_at - now > MULTISIG_PROPOSAL_MAX_LIFETIME {
+            return Err(LendingError::InvalidProposalExpiry.into());
+        }
+        Ok(expires_at)
+    }
+
+    /// Check that `payload` is the payload the signers approved
+    pub fn verify_payload(&self, payload: &[u8]) -> Result<()> {
+        if Self::hash_payload(payload) != self.payload_hash {
+            return Err(LendingError::MultisigPayloadMismatch.into());
+        }
+        Ok(())
+    }
+
     /// Add a signature to the proposal
-    pub fn add_signature(&mut self, signatory: &Pubkey) -> Result<()> {
+    /// The first signature opens a draft proposal; reaching `threshold` makes it ready.
+    pub fn add_signature(&mut self, signatory: &Pubkey, threshold: u8) -> Result<()> {
+        if !matches!(self.status, ProposalStatus::Draft | ProposalStatus::Active) {
+            return Err(LendingError::ProposalNotActive.into());
+        }
         if self.signatures.contains(signatory) {
             return Err(LendingError::AlreadySigned.into());
         }
 
         self.signatures.push(*signatory);
+        if self.status == ProposalStatus::Draft {
+            self.transition(ProposalStatus::Active)?;
+        }
+        self.sync_approvals(threshold)
+    }
+
+    /// Promote an active proposal to ready once it has `threshold` signatures
+    /// Also catches up proposals signed before the threshold was lowered.
+    pub fn sync_approvals(&mut self, threshold: u8) -> Result<()> {
+        if self.status == ProposalStatus::Active && self.has_enough_signatures(threshold) {
+            self.transition(ProposalStatus::Ready)?;
+        }
         Ok(())
     }
 
@@ -198,25 +257,19 @@ impl MultisigProposal {
             Ok(false)
         }
     }
+}
 
-    /// Mark proposal as executed
-    pub fn mark_executed(&mut self) -> Result<()> {
-        if self.status != ProposalStatus::Active {
-            return Err(LendingError::ProposalNotActive.into());
-        }
-
-        self.status = ProposalStatus::Executed;
-        Ok(())
+impl ProposalLifecycle for MultisigProposal {
+    fn status(&self) -> ProposalStatus {
+        self.status
     }
 
-    /// Mark proposal as cancelled
-    pub fn mark_cancelled(&mut self) -> Result<()> {
-        if self.status != ProposalStatus::Active {
-            return Err(LendingError::ProposalNotActive.into());
-        }
+    fn set_status(&mut self, status: ProposalStatus) {
+        self.status = status;
+    }
 
-        self.status = ProposalStatus::Cancelled;
-        Ok(())
+    fn expiry_time(&self) -> Option<i64> {
+        self.expires_at
     }
 }
 
@@ -255,25 +308,6 @@ impl Default for MultisigOperationType {
     }
 }
 
-/// Status of a multisig proposal
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
-pub enum ProposalStatus {
-    /// Proposal is active and can be signed/executed
-    Active,
-    /// Proposal has been executed
-    Executed,
-    /// Proposal has been cancelled
-    Cancelled,
-    /// Proposal has expired
-    Expired,
-}
-
-impl Default for ProposalStatus {
-    fn default() -> Self {
-        Self::Active
-    }
-}
-
 /// Parameters for initializing a multisig
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct InitializeMultisigParams {
@@ -286,5 +320,32 @@ pub struct InitializeMultisigParams {
 pub struct CreateProposalParams {
     pub operation_type: MultisigOperationType,
     pub instruction_data: Vec<u8>,
+    /// SHA-256 hash of `instruction_data`
+    pub payload_hash: [u8; 32],
+    /// Defaults to `MULTISIG_PROPOSAL_DEFAULT_LIFETIME` from creation
     pub expires_at: Option<i64>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_expiry() {
+        let now = 1_000_000;
+        assert_eq!(
+            MultisigProposal::resolve_expiry(now, None).unwrap(),
+            now + MULTISIG_PROPOSAL_DEFAULT_LIFETIME
+        );
+        assert_eq!(
+            MultisigProposal::resolve_expiry(now, Some(now + 60)).unwrap(),
+            now + 60
+        );
+        assert!(MultisigProposal::resolve_expiry(now, Some(now)).is_err());
+        assert!(MultisigProposal::resolve_expiry(
+            now,
+            Some(now + MULTISIG_PROPOSAL_MAX_LIFETIME + 1)
+        )
+        .is_err());
+    }
+}
@@ -1,6 +1,9 @@
 use crate::constants::*;
 use crate::error::LendingError;
+use crate::state::emode::EModeParams;
+use crate::state::reserve::Reserve;
 use crate::utils::math::*;
+use crate::utils::oracle::{OracleManager, OraclePrice};
 use anchor_lang::prelude::*;
 
 /// User obligation account - tracks collateral deposits and borrows
@@ -36,8 +39,36 @@ pub struct Obligation {
     /// Health factor snapshot during liquidation (prevents manipulation)
     pub liquidation_snapshot_health_factor: Option<Decimal>,
 
+    /// Slot at which the obligation was first observed unhealthy (0 if healthy)
+    pub unhealthy_since_slot: u64,
+
+    /// eMode category the obligation opted into (0 = none)
+    pub emode_category: u8,
+
+    /// Whether the obligation's collateral is an isolated asset
+    pub isolation_mode: bool,
+
+    /// Debt watermark and repayment record
+    pub credit_history: CreditHistory,
+
+    /// Slot of the last full revaluation by `refresh_obligation` (0 if never refreshed)
+    pub refreshed_slot: u64,
+
+    /// Slots an unhealthy obligation is shielded from liquidation (0 = no grace period)
+    pub liquidation_grace_slots: u64,
+
+    /// Health factor (basis points) below which the grace period no longer applies
+    pub liquidation_grace_floor_bps: u16,
+
+    /// Index of this obligation among its owner's obligations (0 = the original one)
+    pub obligation_index: u8,
+
+    /// Open accounts keyed by this obligation's address that would be orphaned if it
+    /// moved: reward pool participations, credit lines and credit delegations
+    pub linked_accounts: u16,
+
     /// Reserved space for future upgrades
-    pub reserved: [u8; 112],
+    pub reserved: [u8; 53],
 }
 
 impl Obligation {
@@ -52,13 +83,49 @@ impl Obligation {
         16 + // borrowed_value_usd
         8 + // last_update_timestamp
         8 + // last_update_slot
-        128; // reserved
+        8 + // unhealthy_since_slot
+        1 + // emode_category
+        1 + // isolation_mode
+        CreditHistory::SIZE + // credit_history
+        8 + // refreshed_slot
+        8 + // liquidation_grace_slots
+        2 + // liquidation_grace_floor_bps
+        1 + // obligation_index
+        2 + // linked_accounts
+        77; // reserved
 
     /// Create a new obligation for the given owner
-    pub fn new(market: Pubkey, owner: Pubkey) -> Result<Self> {
+    pub fn new(market: Pubkey, owner: Pubkey, obligation_index: u8) -> Result<Self> {
+        if obligation_index >= MAX_OBLIGATIONS_PER_OWNER {
+            return Err(LendingError::InvalidObligationIndex.into());
+        }
+
         let clock = Clock::get()?;
 
-        Ok(Self {
+        Ok(Self::empty(
+            market,
+            owner,
+            obligation_index,
+            clock.slot,
+            clock.unix_timestamp as u64,
+        ))
+    }
+
+    /// Create an empty obligation stamped at slot 0, for tests running off-chain
+    /// `new` reads the clock sysvar, which only exists inside the runtime.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn new_for_test(market: Pubkey, owner: Pubkey, obligation_index: u8) -> Self {
+        Self::empty(market, owner, obligation_index, 0, 0)
+    }
+
+    fn empty(
+        market: Pubkey,
+        owner: Pubkey,
+        obligation_index: u8,
+        slot: u64,
+        timestamp: u64,
+    ) -> Self {
+        Self {
             version: PROGRAM_VERSION,
             market,
             owner,
@@ -66,11 +133,31 @@ impl Obligation {
             borrows: Vec::new(),
             deposited_value_usd: Decimal::zero(),
             borrowed_value_usd: Decimal::zero(),
-            last_update_timestamp: clock.unix_timestamp as u64,
-            last_update_slot: clock.slot,
+            last_update_timestamp: timestamp,
+            last_update_slot: slot,
             liquidation_snapshot_health_factor: None,
-            reserved: [0; 112],
-        })
+            unhealthy_since_slot: 0,
+            emode_category: 0,
+            isolation_mode: false,
+            credit_history: CreditHistory::default(),
+            refreshed_slot: 0,
+            liquidation_grace_slots: 0,
+            liquidation_grace_floor_bps: 0,
+            obligation_index,
+            linked_accounts: 0,
+            reserved: [0; 53],
+        }
+    }
+
+    /// Seed suffix of the obligation with `obligation_index`
+    /// Index 0 adds no seed, so a wallet's first obligation keeps the original
+    /// `[OBLIGATION_SEED, owner]` address.
+    pub fn index_seed(obligation_index: &u8) -> &[u8] {
+        if *obligation_index == 0 {
+            &[]
+        } else {
+            std::slice::from_ref(obligation_index)
+        }
     }
 
     /// Add collateral deposit to the obligation
@@ -112,6 +199,28 @@ impl Obligation {
             self.deposits.retain(|d| d.deposit_reserve != *reserve);
         }
 
+        // Leaving isolation once the isolated collateral is gone
+        if self.deposits.is_empty() {
+            self.isolation_mode = false;
+        }
+
+        Ok(())
+    }
+
+    /// Check that collateral from `reserve` may be added under the isolation rules
+    /// Isolated collateral must be the obligation's only collateral and cannot be added
+    /// to an obligation that already borrowed against other collateral.
+    pub fn validate_collateral_isolation(&self, reserve: &Pubkey, isolated: bool) -> Result<()> {
+        let has_other_collateral = self.deposits.iter().any(|d| d.deposit_reserve != *reserve);
+
+        let conflict = if isolated {
+            has_other_collateral || (!self.isolation_mode && self.has_borrows())
+        } else {
+            self.isolation_mode
+        };
+        if conflict {
+            return Err(LendingError::IsolatedCollateralConflict.into());
+        }
         Ok(())
     }
 
@@ -123,6 +232,11 @@ impl Obligation {
 
         // Check if borrow for this reserve already exists
         if let Some(existing_borrow) = self.find_liquidity_borrow_mut(&borrow.borrow_reserve) {
+            // Bring the existing debt up to the new borrow's rate before adding to it
+            existing_borrow.accrue_interest(borrow.cumulative_borrow_rate_wads)?;
+
+            // Keep the original origination slot, average the entry rates
+            existing_borrow.merge_origination(&borrow)?;
             existing_borrow.borrowed_amount_wads = existing_borrow
                 .borrowed_amount_wads
                 .try_add(borrow.borrowed_amount_wads)?;
@@ -133,6 +247,18 @@ impl Obligation {
         Ok(())
     }
 
+    /// Accrue interest on the borrow in `reserve`, if any, up to its cumulative borrow rate
+    pub fn accrue_borrow_interest(
+        &mut self,
+        reserve: &Pubkey,
+        cumulative_borrow_rate_wads: Decimal,
+    ) -> Result<()> {
+        if let Some(borrow) = self.find_liquidity_borrow_mut(reserve) {
+            borrow.accrue_interest(cumulative_borrow_rate_wads)?;
+        }
+        Ok(())
+    }
+
     /// Repay liquidity borrow from the obligation
     pub fn repay_liquidity_borrow(&mut self, reserve: &Pubkey, amount: Decimal) -> Result<()> {
         let borrow = self
@@ -153,6 +279,29 @@ impl Obligation {
         Ok(())
     }
 
+    /// Remove the borrow in `reserve` as bad debt, returning the written-off amount
+    /// Only debt no collateral is left to back can be written off.
+    pub fn write_off_liquidity_borrow(&mut self, reserve: &Pubkey) -> Result<Decimal> {
+        if self.has_collateral() {
+            return Err(LendingError::ObligationDebtStillBacked.into());
+        }
+
+        let borrow = self
+            .find_liquidity_borrow(reserve)
+            .ok_or(LendingError::ObligationReserveNotFound)?;
+        let amount = borrow.borrowed_amount_wads;
+        let value_usd = borrow.market_value_usd;
+
+        self.borrows.retain(|b| b.borrow_reserve != *reserve);
+        self.borrowed_value_usd = if self.borrowed_value_usd > value_usd {
+            self.borrowed_value_usd.try_sub(value_usd)?
+        } else {
+            Decimal::zero()
+        };
+
+        Ok(amount)
+    }
+
     /// Find collateral deposit by reserve
     pub fn find_collateral_deposit(&self, reserve: &Pubkey) -> Option<&ObligationCollateral> {
         self.deposits.iter().find(|d| d.deposit_reserve == *reserve)
@@ -188,59 +337,103 @@ impl Obligation {
     /// Health factor > 1.0 means the obligation is healthy
     /// Health factor < 1.0 means the obligation can be liquidated
     pub fn calculate_health_factor(&self) -> Result<Decimal> {
+        self.calculate_health_factor_with(None)
+    }
+
+    /// Calculate health factor, using eMode parameters in place of per-reserve ones if given
+    pub fn calculate_health_factor_with(&self, emode: Option<&EModeParams>) -> Result<Decimal> {
         if self.borrowed_value_usd.is_zero() {
             return Ok(Decimal::from_integer(u64::MAX)?); // Infinite health if no debt
         }
 
-        let weighted_collateral_value = self.calculate_liquidation_threshold_value()?;
+        let weighted_collateral_value = self.calculate_liquidation_threshold_value_with(emode)?;
         weighted_collateral_value.try_div(self.borrowed_value_usd)
     }
 
     /// Calculate maximum loan-to-value based on collateral
     pub fn calculate_max_borrow_value(&self) -> Result<Decimal> {
-        let mut max_borrow_value = Decimal::zero();
+        self.calculate_max_borrow_value_with(None)
+    }
 
-        for deposit in &self.deposits {
-            let collateral_value = deposit.market_value_usd;
-            let ltv_decimal = Decimal::from_scaled_val(
-                (deposit.ltv_bps as u128)
-                    .checked_mul(PRECISION as u128)
-                    .ok_or(LendingError::MathOverflow)?
-                    .checked_div(BASIS_POINTS_PRECISION as u128)
-                    .ok_or(LendingError::DivisionByZero)?,
-            );
+    /// Calculate maximum borrow value, using the eMode LTV if given
+    pub fn calculate_max_borrow_value_with(&self, emode: Option<&EModeParams>) -> Result<Decimal> {
+        self.weighted_collateral_value(|deposit| match emode {
+            Some(emode) => emode.ltv_bps,
+            None => deposit.ltv_bps,
+        })
+    }
 
-            let borrow_value = collateral_value.try_mul(ltv_decimal)?;
-            max_borrow_value = max_borrow_value.try_add(borrow_value)?;
+    /// USD value to borrow and re-deposit to bring leverage to `target_leverage_bps`
+    /// Leverage is deposited value over equity (deposits less debt), so a 3x target on
+    /// $100 of equity holds $300 of collateral against $200 of debt. Fees and swap
+    /// slippage are not accounted for.
+    pub fn loop_borrow_value(&self, target_leverage_bps: u64) -> Result<Decimal> {
+        if target_leverage_bps <= BASIS_POINTS_PRECISION {
+            return Err(LendingError::InvalidAmount.into());
         }
 
-        Ok(max_borrow_value)
+        let equity = self
+            .deposited_value_usd
+            .try_sub(self.borrowed_value_usd)
+            .map_err(|_| LendingError::ObligationUnhealthy)?;
+        let target_collateral = equity
+            .try_mul(Decimal::from_integer(target_leverage_bps)?)?
+            .try_div(Decimal::from_integer(BASIS_POINTS_PRECISION)?)?;
+        if target_collateral <= self.deposited_value_usd {
+            return Err(LendingError::InvalidAmount.into());
+        }
+
+        target_collateral.try_sub(self.deposited_value_usd)
     }
 
     /// Calculate liquidation threshold value (collateral value * liquidation threshold)
     pub fn calculate_liquidation_threshold_value(&self) -> Result<Decimal> {
-        let mut threshold_value = Decimal::zero();
+        self.calculate_liquidation_threshold_value_with(None)
+    }
+
+    /// Calculate liquidation threshold value, using the eMode threshold if given
+    pub fn calculate_liquidation_threshold_value_with(
+        &self,
+        emode: Option<&EModeParams>,
+    ) -> Result<Decimal> {
+        self.weighted_collateral_value(|deposit| match emode {
+            Some(emode) => emode.liquidation_threshold_bps,
+            None => deposit.liquidation_threshold_bps,
+        })
+    }
+
+    /// Sum of collateral values, each weighted by `weight_bps` of its deposit
+    fn weighted_collateral_value(
+        &self,
+        weight_bps: impl Fn(&ObligationCollateral) -> u64,
+    ) -> Result<Decimal> {
+        let mut weighted_value = Decimal::zero();
 
         for deposit in &self.deposits {
             let collateral_value = deposit.market_value_usd;
-            let threshold_decimal = Decimal::from_scaled_val(
-                (deposit.liquidation_threshold_bps as u128)
+            let weight_decimal = Decimal::from_scaled_val(
+                (weight_bps(deposit) as u128)
                     .checked_mul(PRECISION as u128)
                     .ok_or(LendingError::MathOverflow)?
                     .checked_div(BASIS_POINTS_PRECISION as u128)
                     .ok_or(LendingError::DivisionByZero)?,
             );
 
-            let weighted_value = collateral_value.try_mul(threshold_decimal)?;
-            threshold_value = threshold_value.try_add(weighted_value)?;
+            let weighted = collateral_value.try_mul(weight_decimal)?;
+            weighted_value = weighted_value.try_add(weighted)?;
         }
 
-        Ok(threshold_value)
+        Ok(weighted_value)
     }
 
     /// Check if the obligation is healthy (can't be liquidated)
     pub fn is_healthy(&self) -> Result<bool> {
-        let health_factor = self.calculate_health_factor()?;
+        self.is_healthy_with(None)
+    }
+
+    /// Check if the obligation is healthy, using eMode parameters if given
+    pub fn is_healthy_with(&self, emode: Option<&EModeParams>) -> Result<bool> {
+        let health_factor = self.calculate_health_factor_with(emode)?;
         Ok(health_factor.value >= Decimal::one().value)
     }
 
@@ -254,11 +447,45 @@ impl Obligation {
         !self.borrows.is_empty()
     }
 
+    /// Record an account opened against this obligation's address
+    pub fn link_account(&mut self) -> Result<()> {
+        self.linked_accounts = self
+            .linked_accounts
+            .checked_add(1)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Record that an account opened against this obligation's address was closed
+    pub fn unlink_account(&mut self) -> Result<()> {
+        self.linked_accounts = self
+            .linked_accounts
+            .checked_sub(1)
+            .ok_or(LendingError::MathUnderflow)?;
+        Ok(())
+    }
+
+    /// Check if the obligation has no positions and no accounts keyed by its address
+    pub fn is_empty(&self) -> bool {
+        !self.has_collateral() && !self.has_borrows() && self.linked_accounts == 0
+    }
+
     /// Check if the obligation needs to be refreshed
     pub fn is_stale(&self, current_slot: u64) -> bool {
         current_slot.saturating_sub(self.last_update_slot) > MAX_ORACLE_STALENESS_SLOTS
     }
 
+    /// Fail unless the obligation was revalued by `refresh_obligation` within
+    /// `MAX_OBLIGATION_REFRESH_AGE_SLOTS`
+    pub fn require_refreshed(&self, current_slot: u64) -> Result<()> {
+        if self.refreshed_slot == 0
+            || current_slot.saturating_sub(self.refreshed_slot) > MAX_OBLIGATION_REFRESH_AGE_SLOTS
+        {
+            return Err(LendingError::ObligationStale.into());
+        }
+        Ok(())
+    }
+
     /// Update timestamps
     pub fn update_timestamp(&mut self, slot: u64) -> Result<()> {
         let clock = Clock::get()?;
@@ -267,45 +494,237 @@ impl Obligation {
         Ok(())
     }
 
+    /// Record when the obligation first became unhealthy, clearing the marker once healthy again
+    /// Returns true if the obligation was first observed unhealthy in `current_slot`.
+    pub fn update_unhealthy_since(&mut self, current_slot: u64) -> Result<bool> {
+        if self.is_healthy()? {
+            self.unhealthy_since_slot = 0;
+        } else if self.unhealthy_since_slot == 0 {
+            self.unhealthy_since_slot = current_slot;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Number of slots the obligation has been unhealthy without being liquidated
+    pub fn slots_unhealthy(&self, current_slot: u64) -> u64 {
+        if self.unhealthy_since_slot == 0 {
+            0
+        } else {
+            current_slot.saturating_sub(self.unhealthy_since_slot)
+        }
+    }
+
+    /// Opt into (or out of, with `grace_slots` 0) a liquidation grace period
+    pub fn set_liquidation_grace(&mut self, grace_slots: u64, floor_bps: u16) -> Result<()> {
+        if grace_slots == 0 {
+            self.liquidation_grace_slots = 0;
+            self.liquidation_grace_floor_bps = 0;
+            return Ok(());
+        }
+        if grace_slots > MAX_LIQUIDATION_GRACE_SLOTS
+            || (floor_bps as u64) < MIN_LIQUIDATION_GRACE_FLOOR_BPS
+            || (floor_bps as u64) >= BASIS_POINTS_PRECISION
+        {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+        self.liquidation_grace_slots = grace_slots;
+        self.liquidation_grace_floor_bps = floor_bps;
+        Ok(())
+    }
+
+    /// Check that an unhealthy obligation may be liquidated at `health_factor`
+    /// With a grace period configured, liquidation waits `liquidation_grace_slots` after the
+    /// obligation was first observed unhealthy, unless health has fallen below the floor.
+    pub fn check_liquidation_grace(&self, health_factor: Decimal, current_slot: u64) -> Result<()> {
+        if self.liquidation_grace_slots == 0
+            || health_factor.to_health_bps_saturating() < self.liquidation_grace_floor_bps as u64
+        {
+            return Ok(());
+        }
+        if self.unhealthy_since_slot == 0
+            || self.slots_unhealthy(current_slot) < self.liquidation_grace_slots
+        {
+            return Err(LendingError::LiquidationGracePeriodActive.into());
+        }
+        Ok(())
+    }
+
+    /// Raise the debt watermark to the current borrowed value
+    pub fn record_debt_watermark(&mut self) -> Result<()> {
+        let debt_usd = self.borrowed_value_usd.try_floor_u64()?;
+        let history = &mut self.credit_history;
+        history.max_debt_usd = history.max_debt_usd.max(debt_usd);
+        Ok(())
+    }
+
+    /// Count a repayment, as on time unless the obligation is currently unhealthy
+    pub fn record_repayment(&mut self) {
+        let history = &mut self.credit_history;
+        if self.unhealthy_since_slot == 0 {
+            history.on_time_repayments = history.on_time_repayments.saturating_add(1);
+        } else {
+            history.late_repayments = history.late_repayments.saturating_add(1);
+        }
+    }
+
+    /// Count a liquidation of the obligation
+    pub fn record_liquidation(&mut self, slot: u64) {
+        let history = &mut self.credit_history;
+        history.liquidations = history.liquidations.saturating_add(1);
+        history.last_liquidation_slot = slot;
+    }
+
+    /// Share of a borrow one liquidation may repay at the current health, in basis points
+    /// See `health::close_factor_bps`. Works from the cached position values, so refresh
+    /// the obligation first.
+    pub fn liquidation_close_factor_bps(
+        &self,
+        base_close_factor_bps: u64,
+        emode: Option<&EModeParams>,
+    ) -> Result<u64> {
+        let health_bps = self
+            .calculate_health_factor_with(emode)?
+            .to_health_bps_saturating();
+        Ok(health::close_factor_bps(base_close_factor_bps, health_bps))
+    }
+
     /// Calculate maximum liquidation amount for a given reserve
-    pub fn max_liquidation_amount(&self, repay_reserve: &Pubkey) -> Result<u64> {
+    pub fn max_liquidation_amount(
+        &self,
+        repay_reserve: &Pubkey,
+        close_factor_bps: u64,
+    ) -> Result<u64> {
         let borrow = self
             .find_liquidity_borrow(repay_reserve)
             .ok_or(LendingError::ObligationReserveNotFound)?;
 
-        // Maximum 50% of the debt can be liquidated at once
-        let max_liquidation = borrow
-            .borrowed_amount_wads
-            .try_div(Decimal::from_integer(2)?)?
-            .try_floor_u64()?;
+        health::calculate_max_liquidation_amount(
+            borrow.borrowed_amount_wads.try_floor_u64()?,
+            close_factor_bps,
+        )
+    }
 
-        Ok(max_liquidation)
+    /// Maximum collateral that can be withdrawn from `reserve` while the health factor
+    /// stays at or above `min_health_factor_bps` (10_000 = 1.0)
+    /// Works from the cached position values, so refresh the obligation first.
+    pub fn max_withdrawable_collateral(
+        &self,
+        reserve: &Pubkey,
+        min_health_factor_bps: u64,
+        emode: Option<&EModeParams>,
+    ) -> Result<u64> {
+        let deposit = self
+            .find_collateral_deposit(reserve)
+            .ok_or(LendingError::ObligationReserveNotFound)?;
+        let threshold_bps = match emode {
+            Some(emode) => emode.liquidation_threshold_bps,
+            None => deposit.liquidation_threshold_bps,
+        };
+
+        // Collateral that does not back any debt can be withdrawn in full
+        if self.borrowed_value_usd.is_zero()
+            || deposit.market_value_usd.is_zero()
+            || threshold_bps == 0
+        {
+            return Ok(deposit.deposited_amount);
+        }
+
+        let required_threshold_value = self
+            .borrowed_value_usd
+            .value
+            .checked_mul(min_health_factor_bps as u128)
+            .ok_or(LendingError::MathOverflow)?
+            / BASIS_POINTS_PRECISION as u128;
+        let threshold_value = self.calculate_liquidation_threshold_value_with(emode)?;
+        if threshold_value.value <= required_threshold_value {
+            return Ok(0);
+        }
+
+        // Each unit of value withdrawn lowers the threshold value by the deposit's weight
+        let max_value = (threshold_value.value - required_threshold_value)
+            .checked_mul(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::MathOverflow)?
+            / threshold_bps as u128;
+        if max_value >= deposit.market_value_usd.value {
+            return Ok(deposit.deposited_amount);
+        }
+
+        let withdrawable_fraction = Decimal::from_scaled_val(max_value)
+            .try_div(deposit.market_value_usd)?
+            .value;
+        let max_amount = (deposit.deposited_amount as u128)
+            .checked_mul(withdrawable_fraction)
+            .ok_or(LendingError::MathOverflow)?
+            / PRECISION as u128;
+
+        Ok(max_amount as u64)
     }
 
-    /// Refresh health factor with current oracle prices to prevent race conditions
-    pub fn refresh_health_factor(
-        &mut self,
-        _price_oracles: &[AccountInfo],
-        current_timestamp: i64,
-    ) -> Result<()> {
-        // Refresh all collateral values with current prices
-        for _deposit in &mut self.deposits {
-            // Get current price from oracle (implementation would be specific to oracle type)
-            // This is a placeholder - actual implementation would fetch from price_oracles
-            // based on the reserve's oracle configuration
+    /// Revalue every position with current reserve state and oracle prices
+    /// `accounts` holds a (reserve, price oracle) pair for each deposit, in order, followed
+    /// by a pair for each borrow. Deposits are valued at the reserve's current collateral
+    /// exchange rate and borrows accrue interest up to the reserve's cumulative borrow rate.
+    /// The cached totals are recomputed and `refreshed_slot` set.
+    pub fn refresh_health_factor(&mut self, accounts: &[AccountInfo], clock: &Clock) -> Result<()> {
+        let position_count = self.deposits.len() + self.borrows.len();
+        if accounts.len() < position_count * 2 {
+            return Err(LendingError::InvalidAccount.into());
         }
+        let (deposit_accounts, borrow_accounts) = accounts.split_at(self.deposits.len() * 2);
 
-        // Refresh all borrow values with current interest rates
-        for _borrow in &mut self.borrows {
-            // Update borrowed amounts with accrued interest
-            // This is a placeholder for interest accrual calculation
+        let mut total_deposited_value = Decimal::zero();
+        for (deposit, pair) in self
+            .deposits
+            .iter_mut()
+            .zip(deposit_accounts.chunks_exact(2))
+        {
+            let (reserve, price) = load_position_reserve(
+                &pair[0],
+                &pair[1],
+                &deposit.deposit_reserve,
+                &self.market,
+                clock,
+            )?;
+
+            deposit.market_value_usd =
+                reserve.collateral_value_usd(deposit.deposited_amount, &price)?;
+            deposit.ltv_bps =
+                reserve.effective_loan_to_value_ratio_bps(clock.unix_timestamp as u64);
+            deposit.liquidation_threshold_bps = reserve.config.liquidation_threshold_bps;
+
+            total_deposited_value = total_deposited_value.try_add(deposit.market_value_usd)?;
         }
 
+        let mut total_borrowed_value = Decimal::zero();
+        for (borrow, pair) in self.borrows.iter_mut().zip(borrow_accounts.chunks_exact(2)) {
+            let (reserve, price) = load_position_reserve(
+                &pair[0],
+                &pair[1],
+                &borrow.borrow_reserve,
+                &self.market,
+                clock,
+            )?;
+
+            borrow.accrue_interest(reserve.cumulative_borrow_rate_wads())?;
+            borrow.market_value_usd = OracleManager::calculate_usd_value(
+                borrow.borrowed_amount_wads.try_floor_u64()?,
+                &price,
+                reserve.config.decimals,
+            )?;
+
+            total_borrowed_value = total_borrowed_value.try_add(borrow.market_value_usd)?;
+        }
+
+        self.deposited_value_usd = total_deposited_value;
+        self.borrowed_value_usd = total_borrowed_value;
+
         // Clear any stale liquidation snapshot
         self.liquidation_snapshot_health_factor = None;
 
-        // Update timestamp to mark as refreshed
-        self.last_update_timestamp = current_timestamp as u64;
+        self.refreshed_slot = clock.slot;
+        self.last_update_slot = clock.slot;
+        self.last_update_timestamp = clock.unix_timestamp as u64;
 
         Ok(())
     }
@@ -320,6 +739,69 @@ impl Obligation {
     }
 }
 
+/// Load a position's reserve, accrued to the current slot, with its validated oracle price
+fn load_position_reserve(
+    reserve_info: &AccountInfo,
+    oracle_info: &AccountInfo,
+    position_reserve: &Pubkey,
+    market: &Pubkey,
+    clock: &Clock,
+) -> Result<(Reserve, OraclePrice)> {
+    if reserve_info.owner != &crate::ID || reserve_info.key != position_reserve {
+        return Err(LendingError::InvalidAccount.into());
+    }
+
+    let mut reserve = {
+        let data = reserve_info.try_borrow_data()?;
+        let mut slice: &[u8] = &data;
+        Reserve::try_deserialize(&mut slice).map_err(|_| LendingError::InvalidAccount)?
+    };
+    if reserve.market != *market {
+        return Err(LendingError::InvalidMarketState.into());
+    }
+    if *oracle_info.key != reserve.price_oracle {
+        return Err(LendingError::OracleAccountMismatch.into());
+    }
+
+    // Interest is accrued on the copy only; the reserve account itself is not written
+    reserve.accrue_interest(clock.slot, clock.unix_timestamp as u64)?;
+
+    let price = OracleManager::get_price(oracle_info, &reserve)?;
+    price.validate(clock.unix_timestamp)?;
+
+    Ok((reserve, price))
+}
+
+/// Compact record of an obligation's borrowing and repayment behavior
+/// Counters only ever grow, so the record can back under-collateralized credit tiers
+/// or third-party underwriting without trusting off-chain indexers.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CreditHistory {
+    /// Highest debt the obligation has carried (whole USD)
+    pub max_debt_usd: u64,
+
+    /// Repayments made while the obligation was healthy
+    pub on_time_repayments: u32,
+
+    /// Repayments made while the obligation was liquidatable
+    pub late_repayments: u32,
+
+    /// Liquidations suffered by the obligation
+    pub liquidations: u32,
+
+    /// Slot of the last liquidation (0 if never liquidated)
+    pub last_liquidation_slot: u64,
+}
+
+impl CreditHistory {
+    /// Serialized size of the credit history in bytes
+    pub const SIZE: usize = 8 + // max_debt_usd
+        4 + // on_time_repayments
+        4 + // late_repayments
+        4 + // liquidations
+        8; // last_liquidation_slot
+}
+
 /// Collateral deposited in a reserve
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
 pub struct ObligationCollateral {
@@ -350,4 +832,544 @@ pub struct ObligationLiquidity {
 
     /// Current market value in USD
     pub market_value_usd: Decimal,
+
+    /// Reserve utilization rate when the borrow was originated
+    pub origination_utilization_rate: Decimal,
+
+    /// Reserve borrow rate when the borrow was originated
+    pub origination_borrow_rate: Decimal,
+
+    /// Slot at which the borrow was originated
+    pub origination_slot: u64,
+
+    /// Reserve cumulative borrow rate the borrowed amount was last accrued to
+    pub cumulative_borrow_rate_wads: Decimal,
+}
+
+impl ObligationLiquidity {
+    /// Compound the borrowed amount up to the reserve's cumulative borrow rate
+    /// Positions opened before the rate was tracked start accruing from the current rate.
+    pub fn accrue_interest(&mut self, cumulative_borrow_rate_wads: Decimal) -> Result<()> {
+        if self.cumulative_borrow_rate_wads.is_zero() {
+            self.cumulative_borrow_rate_wads = cumulative_borrow_rate_wads;
+            return Ok(());
+        }
+        if cumulative_borrow_rate_wads < self.cumulative_borrow_rate_wads {
+            return Err(LendingError::InvalidReserveState.into());
+        }
+        if cumulative_borrow_rate_wads == self.cumulative_borrow_rate_wads {
+            return Ok(());
+        }
+
+        let growth_factor = cumulative_borrow_rate_wads
+            .try_div(self.cumulative_borrow_rate_wads)?
+            .value;
+
+        // Scale whole and fractional units separately so the products stay within u128
+        let precision = PRECISION as u128;
+        let whole = self.borrowed_amount_wads.value / precision;
+        let fraction = self.borrowed_amount_wads.value % precision;
+        let accrued = whole
+            .checked_mul(growth_factor)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_add(
+                fraction
+                    .checked_mul(growth_factor)
+                    .ok_or(LendingError::MathOverflow)?
+                    / precision,
+            )
+            .ok_or(LendingError::MathOverflow)?;
+
+        self.borrowed_amount_wads = Decimal::from_scaled_val(accrued);
+        self.cumulative_borrow_rate_wads = cumulative_borrow_rate_wads;
+        Ok(())
+    }
+
+    /// Blend entry conditions of an additional borrow into this position,
+    /// weighting rates by principal so the record reflects the average entry.
+    /// Weights are whole token amounts, as the product of two wads overflows
+    pub fn merge_origination(&mut self, other: &ObligationLiquidity) -> Result<()> {
+        let amount = self.borrowed_amount_wads.try_floor_u64()? as u128;
+        let other_amount = other.borrowed_amount_wads.try_floor_u64()? as u128;
+        let total_amount = amount
+            .checked_add(other_amount)
+            .ok_or(LendingError::MathOverflow)?;
+
+        if total_amount == 0 {
+            return Ok(());
+        }
+
+        let blend = |rate: Decimal, other_rate: Decimal| -> Result<Decimal> {
+            let weighted = rate
+                .value
+                .checked_mul(amount)
+                .ok_or(LendingError::MathOverflow)?
+                .checked_add(
+                    other_rate
+                        .value
+                        .checked_mul(other_amount)
+                        .ok_or(LendingError::MathOverflow)?,
+                )
+                .ok_or(LendingError::MathOverflow)?;
+            Ok(Decimal::from_scaled_val(weighted / total_amount))
+        };
+
+        self.origination_utilization_rate = blend(
+            self.origination_utilization_rate,
+            other.origination_utilization_rate,
+        )?;
+        self.origination_borrow_rate =
+            blend(self.origination_borrow_rate, other.origination_borrow_rate)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collateral(reserve: Pubkey, amount: u64) -> ObligationCollateral {
+        ObligationCollateral {
+            deposit_reserve: reserve,
+            deposited_amount: amount,
+            market_value_usd: Decimal::zero(),
+            ltv_bps: 5000,
+            liquidation_threshold_bps: 6000,
+        }
+    }
+
+    #[test]
+    fn test_linked_accounts_keep_obligation_open() {
+        let mut obligation =
+            Obligation::new_for_test(Pubkey::new_unique(), Pubkey::new_unique(), 0);
+        assert!(obligation.is_empty());
+        assert!(obligation.unlink_account().is_err());
+
+        obligation.link_account().unwrap();
+        assert!(!obligation.is_empty());
+        obligation.unlink_account().unwrap();
+        assert!(obligation.is_empty());
+    }
+
+    #[test]
+    fn test_isolated_collateral_cannot_mix() {
+        let isolated = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let mut obligation = Obligation {
+            version: PROGRAM_VERSION,
+            market: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            deposits: Vec::new(),
+            borrows: Vec::new(),
+            deposited_value_usd: Decimal::zero(),
+            borrowed_value_usd: Decimal::zero(),
+            last_update_timestamp: 0,
+            last_update_slot: 0,
+            liquidation_snapshot_health_factor: None,
+            unhealthy_since_slot: 0,
+            emode_category: 0,
+            isolation_mode: false,
+            credit_history: CreditHistory::default(),
+            refreshed_slot: 0,
+            liquidation_grace_slots: 0,
+            liquidation_grace_floor_bps: 0,
+            obligation_index: 0,
+            linked_accounts: 0,
+            reserved: [0; 53],
+        };
+
+        // Isolated collateral may only be deposited alone
+        obligation.deposits.push(collateral(other, 100));
+        assert!(obligation.validate_collateral_isolation(&isolated, true).is_err());
+        obligation.remove_collateral_deposit(&other, 100).unwrap();
+
+        obligation.validate_collateral_isolation(&isolated, true).unwrap();
+        obligation.add_collateral_deposit(collateral(isolated, 100)).unwrap();
+        obligation.isolation_mode = true;
+
+        // Topping up the isolated asset is fine, other collateral is not
+        obligation.validate_collateral_isolation(&isolated, true).unwrap();
+        assert!(obligation.validate_collateral_isolation(&other, false).is_err());
+
+        // Withdrawing all isolated collateral leaves isolation mode
+        obligation.remove_collateral_deposit(&isolated, 100).unwrap();
+        assert!(!obligation.isolation_mode);
+        obligation.validate_collateral_isolation(&other, false).unwrap();
+    }
+
+    #[test]
+    fn test_credit_history_tracks_watermark_and_repayments() {
+        let mut obligation = Obligation {
+            version: PROGRAM_VERSION,
+            market: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            deposits: Vec::new(),
+            borrows: Vec::new(),
+            deposited_value_usd: Decimal::zero(),
+            borrowed_value_usd: Decimal::from_integer(1_500).unwrap(),
+            last_update_timestamp: 0,
+            last_update_slot: 0,
+            liquidation_snapshot_health_factor: None,
+            unhealthy_since_slot: 0,
+            emode_category: 0,
+            isolation_mode: false,
+            credit_history: CreditHistory::default(),
+            refreshed_slot: 0,
+            liquidation_grace_slots: 0,
+            liquidation_grace_floor_bps: 0,
+            obligation_index: 0,
+            linked_accounts: 0,
+            reserved: [0; 53],
+        };
+
+        // The watermark only moves up
+        obligation.record_debt_watermark().unwrap();
+        obligation.borrowed_value_usd = Decimal::from_integer(400).unwrap();
+        obligation.record_debt_watermark().unwrap();
+        assert_eq!(obligation.credit_history.max_debt_usd, 1_500);
+
+        // Repayments count as late while the obligation is liquidatable
+        obligation.record_repayment();
+        obligation.unhealthy_since_slot = 50;
+        obligation.record_repayment();
+        obligation.record_liquidation(60);
+
+        assert_eq!(
+            obligation.credit_history,
+            CreditHistory {
+                max_debt_usd: 1_500,
+                on_time_repayments: 1,
+                late_repayments: 1,
+                liquidations: 1,
+                last_liquidation_slot: 60,
+            }
+        );
+    }
+
+    #[test]
+    fn test_borrow_interest_and_refresh_age() {
+        let rate = |scaled: u128| Decimal::from_scaled_val(scaled);
+        let mut borrow = ObligationLiquidity {
+            borrowed_amount_wads: Decimal::from_integer(1_000).unwrap(),
+            cumulative_borrow_rate_wads: rate(PRECISION as u128 * 2),
+            ..ObligationLiquidity::default()
+        };
+
+        // The debt grows by the reserve rate's growth since the last accrual
+        let grown = rate(PRECISION as u128 * 22 / 10);
+        borrow.accrue_interest(grown).unwrap();
+        assert_eq!(borrow.borrowed_amount_wads.try_floor_u64().unwrap(), 1_100);
+        borrow.accrue_interest(grown).unwrap();
+        assert_eq!(borrow.borrowed_amount_wads.try_floor_u64().unwrap(), 1_100);
+
+        // The reserve rate never decreases
+        assert!(borrow.accrue_interest(rate(PRECISION as u128 * 2)).is_err());
+
+        let mut obligation = Obligation {
+            version: PROGRAM_VERSION,
+            market: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            deposits: Vec::new(),
+            borrows: vec![borrow],
+            deposited_value_usd: Decimal::zero(),
+            borrowed_value_usd: Decimal::zero(),
+            last_update_timestamp: 0,
+            last_update_slot: 0,
+            liquidation_snapshot_health_factor: None,
+            unhealthy_since_slot: 0,
+            emode_category: 0,
+            isolation_mode: false,
+            credit_history: CreditHistory::default(),
+            refreshed_slot: 0,
+            liquidation_grace_slots: 0,
+            liquidation_grace_floor_bps: 0,
+            obligation_index: 0,
+            linked_accounts: 0,
+            reserved: [0; 53],
+        };
+
+        // Never refreshed, then only fresh within the same slot
+        assert!(obligation.require_refreshed(100).is_err());
+        obligation.refreshed_slot = 100;
+        assert!(obligation.require_refreshed(100).is_ok());
+        assert!(obligation
+            .require_refreshed(101 + MAX_OBLIGATION_REFRESH_AGE_SLOTS)
+            .is_err());
+    }
+
+    #[test]
+    fn test_second_borrow_blends_origination_rates() {
+        let rate = |scaled: u128| Decimal::from_scaled_val(scaled);
+        let reserve = Pubkey::new_unique();
+        let borrow =
+            |amount: u64, utilization_pct: u128, borrow_rate_pct: u128| ObligationLiquidity {
+                borrow_reserve: reserve,
+                borrowed_amount_wads: Decimal::from_integer(amount).unwrap(),
+                origination_utilization_rate: rate(PRECISION as u128 * utilization_pct / 100),
+                origination_borrow_rate: rate(PRECISION as u128 * borrow_rate_pct / 100),
+                cumulative_borrow_rate_wads: rate(PRECISION as u128),
+                ..ObligationLiquidity::default()
+            };
+
+        // 1M and 3M USDC (6 decimals), far past where wad * wad overflows
+        let mut obligation =
+            Obligation::new_for_test(Pubkey::new_unique(), Pubkey::new_unique(), 0);
+        obligation
+            .add_liquidity_borrow(borrow(1_000_000_000_000, 40, 4))
+            .unwrap();
+        obligation
+            .add_liquidity_borrow(borrow(3_000_000_000_000, 80, 12))
+            .unwrap();
+
+        assert_eq!(obligation.borrows.len(), 1);
+        let position = &obligation.borrows[0];
+        assert_eq!(
+            position.borrowed_amount_wads.try_floor_u64().unwrap(),
+            4_000_000_000_000
+        );
+        assert_eq!(
+            position.origination_utilization_rate,
+            rate(PRECISION as u128 * 70 / 100)
+        );
+        assert_eq!(
+            position.origination_borrow_rate,
+            rate(PRECISION as u128 * 10 / 100)
+        );
+    }
+
+    #[test]
+    fn test_bad_debt_write_off_requires_no_collateral() {
+        let usd = |value: u64| Decimal::from_integer(value).unwrap();
+        let debt_reserve = Pubkey::new_unique();
+        let collateral_reserve = Pubkey::new_unique();
+        let mut obligation = Obligation {
+            version: PROGRAM_VERSION,
+            market: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            deposits: vec![collateral(collateral_reserve, 10)],
+            borrows: vec![ObligationLiquidity {
+                borrow_reserve: debt_reserve,
+                borrowed_amount_wads: usd(500),
+                market_value_usd: usd(500),
+                ..ObligationLiquidity::default()
+            }],
+            deposited_value_usd: Decimal::zero(),
+            borrowed_value_usd: usd(500),
+            last_update_timestamp: 0,
+            last_update_slot: 0,
+            liquidation_snapshot_health_factor: None,
+            unhealthy_since_slot: 0,
+            emode_category: 0,
+            isolation_mode: false,
+            credit_history: CreditHistory::default(),
+            refreshed_slot: 0,
+            liquidation_grace_slots: 0,
+            liquidation_grace_floor_bps: 0,
+            obligation_index: 0,
+            linked_accounts: 0,
+            reserved: [0; 53],
+        };
+
+        assert!(obligation
+            .write_off_liquidity_borrow(&debt_reserve)
+            .is_err());
+
+        obligation
+            .remove_collateral_deposit(&collateral_reserve, 10)
+            .unwrap();
+        assert!(obligation
+            .write_off_liquidity_borrow(&Pubkey::new_unique())
+            .is_err());
+        let written_off = obligation
+            .write_off_liquidity_borrow(&debt_reserve)
+            .unwrap();
+        assert_eq!(written_off.try_floor_u64().unwrap(), 500);
+        assert!(!obligation.has_borrows());
+        assert!(obligation.borrowed_value_usd.is_zero());
+    }
+
+    #[test]
+    fn test_max_withdrawable_collateral_keeps_health_factor() {
+        let usd = |value: u64| Decimal::from_integer(value).unwrap();
+        let reserve = Pubkey::new_unique();
+        let mut obligation = Obligation {
+            version: PROGRAM_VERSION,
+            market: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            deposits: vec![ObligationCollateral {
+                market_value_usd: usd(100),
+                liquidation_threshold_bps: 8000,
+                ..collateral(reserve, 100_000)
+            }],
+            borrows: Vec::new(),
+            deposited_value_usd: usd(100),
+            borrowed_value_usd: Decimal::zero(),
+            last_update_timestamp: 0,
+            last_update_slot: 0,
+            liquidation_snapshot_health_factor: None,
+            unhealthy_since_slot: 0,
+            emode_category: 0,
+            isolation_mode: false,
+            credit_history: CreditHistory::default(),
+            refreshed_slot: 0,
+            liquidation_grace_slots: 0,
+            liquidation_grace_floor_bps: 0,
+            obligation_index: 0,
+            linked_accounts: 0,
+            reserved: [0; 53],
+        };
+
+        // Without debt everything can be withdrawn
+        assert_eq!(
+            obligation
+                .max_withdrawable_collateral(&reserve, 10_000, None)
+                .unwrap(),
+            100_000
+        );
+
+        // $80 of threshold value against $40 of debt: half may go at 1.0, a fifth at 1.6
+        obligation.borrowed_value_usd = usd(40);
+        assert_eq!(
+            obligation
+                .max_withdrawable_collateral(&reserve, 10_000, None)
+                .unwrap(),
+            50_000
+        );
+        assert_eq!(
+            obligation
+                .max_withdrawable_collateral(&reserve, 16_000, None)
+                .unwrap(),
+            20_000
+        );
+
+        // Nothing once the minimum is already breached
+        obligation.borrowed_value_usd = usd(90);
+        assert_eq!(
+            obligation
+                .max_withdrawable_collateral(&reserve, 10_000, None)
+                .unwrap(),
+            0
+        );
+        assert!(obligation
+            .max_withdrawable_collateral(&Pubkey::new_unique(), 10_000, None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_liquidation_grace_period() {
+        let health = |bps: u64| {
+            Decimal::from_integer(bps)
+                .unwrap()
+                .try_div(Decimal::from_integer(BASIS_POINTS_PRECISION).unwrap())
+                .unwrap()
+        };
+        let mut obligation = Obligation {
+            version: PROGRAM_VERSION,
+            market: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            deposits: Vec::new(),
+            borrows: Vec::new(),
+            deposited_value_usd: Decimal::zero(),
+            borrowed_value_usd: Decimal::zero(),
+            last_update_timestamp: 0,
+            last_update_slot: 0,
+            liquidation_snapshot_health_factor: None,
+            unhealthy_since_slot: 0,
+            emode_category: 0,
+            isolation_mode: false,
+            credit_history: CreditHistory::default(),
+            refreshed_slot: 0,
+            liquidation_grace_slots: 0,
+            liquidation_grace_floor_bps: 0,
+            obligation_index: 0,
+            linked_accounts: 0,
+            reserved: [0; 53],
+        };
+
+        // Without a grace period liquidation is immediate
+        obligation.check_liquidation_grace(health(9900), 0).unwrap();
+
+        assert!(obligation
+            .set_liquidation_grace(MAX_LIQUIDATION_GRACE_SLOTS + 1, 9500)
+            .is_err());
+        assert!(obligation.set_liquidation_grace(150, 8000).is_err());
+        assert!(obligation.set_liquidation_grace(150, 10_000).is_err());
+        obligation.set_liquidation_grace(150, 9500).unwrap();
+
+        // Not yet observed unhealthy by a refresh, then inside and past the window
+        let warned = health(9900);
+        assert!(obligation.check_liquidation_grace(warned, 100).is_err());
+        obligation.unhealthy_since_slot = 100;
+        assert!(obligation.check_liquidation_grace(warned, 249).is_err());
+        obligation.check_liquidation_grace(warned, 250).unwrap();
+
+        // Falling below the floor ends the grace period early
+        let critical = health(9499);
+        obligation.check_liquidation_grace(critical, 101).unwrap();
+
+        obligation.set_liquidation_grace(0, 9500).unwrap();
+        assert_eq!(obligation.liquidation_grace_floor_bps, 0);
+        obligation.check_liquidation_grace(warned, 101).unwrap();
+    }
+
+    #[test]
+    fn test_obligation_index_seed() {
+        let owner = Pubkey::new_unique();
+        let (legacy, _) =
+            Pubkey::find_program_address(&[OBLIGATION_SEED, owner.as_ref()], &crate::ID);
+        let address = |index: u8| {
+            Pubkey::find_program_address(
+                &[
+                    OBLIGATION_SEED,
+                    owner.as_ref(),
+                    Obligation::index_seed(&index),
+                ],
+                &crate::ID,
+            )
+            .0
+        };
+
+        // Index 0 keeps the address obligations had before indexes existed
+        assert_eq!(address(0), legacy);
+        assert_ne!(address(1), legacy);
+        assert_ne!(address(1), address(2));
+    }
+
+    #[test]
+    fn test_loop_borrow_value_reaches_target_leverage() {
+        let usd = |value: u64| Decimal::from_integer(value).unwrap();
+        let mut obligation = Obligation {
+            version: PROGRAM_VERSION,
+            market: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            deposits: Vec::new(),
+            borrows: Vec::new(),
+            deposited_value_usd: usd(100),
+            borrowed_value_usd: Decimal::zero(),
+            last_update_timestamp: 0,
+            last_update_slot: 0,
+            liquidation_snapshot_health_factor: None,
+            unhealthy_since_slot: 0,
+            emode_category: 0,
+            isolation_mode: false,
+            credit_history: CreditHistory::default(),
+            refreshed_slot: 0,
+            liquidation_grace_slots: 0,
+            liquidation_grace_floor_bps: 0,
+            obligation_index: 0,
+            linked_accounts: 0,
+            reserved: [0; 53],
+        };
+
+        // $100 of equity at 3x holds $300 of collateral
+        assert_eq!(obligation.loop_borrow_value(30_000).unwrap(), usd(200));
+
+        // Already at 1.5x, only the difference is borrowed
+        obligation.deposited_value_usd = usd(150);
+        obligation.borrowed_value_usd = usd(50);
+        assert_eq!(obligation.loop_borrow_value(30_000).unwrap(), usd(150));
+        assert!(obligation.loop_borrow_value(15_000).is_err());
+        assert!(obligation.loop_borrow_value(10_000).is_err());
+    }
 }
@@ -1,6 +1,6 @@
 use crate::constants::*;
 use crate::error::LendingError;
-use crate::state::obligation::{ObligationCollateral, ObligationLiquidity};
+use crate::state::obligation::{CreditHistory, ObligationCollateral, ObligationLiquidity};
 use crate::utils::math::*;
 use anchor_lang::prelude::*;
 use std::collections::HashMap;
@@ -43,15 +43,69 @@ pub struct ObligationOptimized {
     /// Health factor snapshot during liquidation (prevents manipulation)
     pub liquidation_snapshot_health_factor: Option<Decimal>,
 
+    /// Slot at which the obligation was first observed unhealthy (0 if healthy)
+    pub unhealthy_since_slot: u64,
+
+    /// eMode category the obligation opted into (0 = none)
+    pub emode_category: u8,
+
+    /// Whether the obligation's collateral is an isolated asset
+    pub isolation_mode: bool,
+
+    /// Debt watermark and repayment record
+    pub credit_history: CreditHistory,
+
+    /// Slot of the last full revaluation by `refresh_obligation` (0 if never refreshed)
+    pub refreshed_slot: u64,
+
+    /// Slots an unhealthy obligation is shielded from liquidation (0 = no grace period)
+    pub liquidation_grace_slots: u64,
+
+    /// Health factor (basis points) below which the grace period no longer applies
+    pub liquidation_grace_floor_bps: u16,
+
     /// Performance metrics
     pub lookup_count: u64,
     pub cache_hits: u64,
 
+    /// Index of this obligation among its owner's obligations (0 = the original one)
+    pub obligation_index: u8,
+
+    /// Open accounts keyed by this obligation's address (see `Obligation::linked_accounts`)
+    pub linked_accounts: u16,
+
     /// Reserved space for future upgrades
-    pub reserved: [u8; 96],
+    pub reserved: [u8; 37],
 }
 
 impl ObligationOptimized {
+    /// Size of the ObligationOptimized account in bytes (estimated)
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // owner
+        4 + (MAX_OBLIGATION_RESERVES * std::mem::size_of::<ObligationCollateral>()) + // deposits
+        4 + (MAX_OBLIGATION_RESERVES * (32 + 8)) + // deposit_index
+        4 + (MAX_OBLIGATION_RESERVES * std::mem::size_of::<ObligationLiquidity>()) + // borrows
+        4 + (MAX_OBLIGATION_RESERVES * (32 + 8)) + // borrow_index
+        16 + // deposited_value_usd
+        16 + // borrowed_value_usd
+        8 + // last_update_timestamp
+        8 + // last_update_slot
+        1 + 16 + // liquidation_snapshot_health_factor
+        8 + // unhealthy_since_slot
+        1 + // emode_category
+        1 + // isolation_mode
+        CreditHistory::SIZE + // credit_history
+        8 + // refreshed_slot
+        8 + // liquidation_grace_slots
+        2 + // liquidation_grace_floor_bps
+        8 + // lookup_count
+        8 + // cache_hits
+        1 + // obligation_index
+        2 + // linked_accounts
+        37; // reserved
+
     /// Create a new optimized obligation
     pub fn new(market: Pubkey, owner: Pubkey) -> Result<Self> {
         let clock = Clock::get()?;
@@ -69,9 +123,18 @@ impl ObligationOptimized {
             last_update_timestamp: clock.unix_timestamp as u64,
             last_update_slot: clock.slot,
             liquidation_snapshot_health_factor: None,
+            unhealthy_since_slot: 0,
+            emode_category: 0,
+            isolation_mode: false,
+            credit_history: CreditHistory::default(),
+            refreshed_slot: 0,
+            liquidation_grace_slots: 0,
+            liquidation_grace_floor_bps: 0,
             lookup_count: 0,
             cache_hits: 0,
-            reserved: [0; 96],
+            obligation_index: 0,
+            linked_accounts: 0,
+            reserved: [0; 37],
         })
     }
 
@@ -135,6 +198,8 @@ impl ObligationOptimized {
 
         // O(1) lookup using HashMap
         if let Some(&index) = self.borrow_index.get(&borrow.borrow_reserve) {
+            self.borrows[index].accrue_interest(borrow.cumulative_borrow_rate_wads)?;
+            self.borrows[index].merge_origination(&borrow)?;
             self.borrows[index].borrowed_amount_wads = self.borrows[index]
                 .borrowed_amount_wads
                 .try_add(borrow.borrowed_amount_wads)?;
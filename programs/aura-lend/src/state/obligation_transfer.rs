@@ -0,0 +1,89 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+
+/// Pending transfer of an obligation to a new owner
+/// Obligations are PDAs of their owner, so a transfer moves the positions into the new
+/// owner's obligation PDA. The current owner proposes the transfer and the new owner
+/// accepts it, which guards against handing a position to a mistyped wallet.
+#[account]
+pub struct ObligationTransfer {
+    /// Version of the obligation transfer account structure
+    pub version: u8,
+
+    /// Market the obligation belongs to
+    pub market: Pubkey,
+
+    /// Obligation being transferred
+    pub obligation: Pubkey,
+
+    /// Current owner, who proposed the transfer and receives the rent back
+    pub owner: Pubkey,
+
+    /// Wallet that must accept the transfer
+    pub new_owner: Pubkey,
+
+    /// Slot the transfer was proposed at
+    pub proposed_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl ObligationTransfer {
+    /// Size of the ObligationTransfer account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // obligation
+        32 + // owner
+        32 + // new_owner
+        8 + // proposed_slot
+        1 + // bump
+        32; // reserved
+
+    /// Create a pending transfer
+    pub fn new(
+        market: Pubkey,
+        obligation: Pubkey,
+        owner: Pubkey,
+        new_owner: Pubkey,
+        proposed_slot: u64,
+        bump: u8,
+    ) -> Result<Self> {
+        if new_owner == owner || new_owner == Pubkey::default() {
+            return Err(LendingError::InvalidAccount.into());
+        }
+
+        Ok(Self {
+            version: PROGRAM_VERSION,
+            market,
+            obligation,
+            owner,
+            new_owner,
+            proposed_slot,
+            bump,
+            reserved: [0; 32],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_requires_a_different_owner() {
+        let owner = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        assert!(ObligationTransfer::new(key, key, owner, owner, 0, 255).is_err());
+        assert!(ObligationTransfer::new(key, key, owner, Pubkey::default(), 0, 255).is_err());
+
+        let transfer =
+            ObligationTransfer::new(key, key, owner, Pubkey::new_unique(), 10, 255).unwrap();
+        assert_eq!(transfer.proposed_slot, 10);
+    }
+}
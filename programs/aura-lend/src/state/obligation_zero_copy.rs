@@ -0,0 +1,370 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::obligation::{
+    CreditHistory, Obligation, ObligationCollateral, ObligationLiquidity,
+};
+use crate::utils::math::Decimal;
+use anchor_lang::prelude::*;
+
+/// Decimal stored as little-endian bytes
+/// `u128` is 16-byte aligned on some targets, which account data cannot guarantee.
+#[zero_copy]
+pub struct PodDecimal {
+    pub bytes: [u8; 16],
+}
+
+impl From<Decimal> for PodDecimal {
+    fn from(decimal: Decimal) -> Self {
+        Self {
+            bytes: decimal.value.to_le_bytes(),
+        }
+    }
+}
+
+impl From<PodDecimal> for Decimal {
+    fn from(pod: PodDecimal) -> Self {
+        Decimal::from_scaled_val(u128::from_le_bytes(pod.bytes))
+    }
+}
+
+/// Collateral deposit slot of a zero-copy obligation
+#[zero_copy]
+pub struct ZeroCopyCollateral {
+    pub deposit_reserve: Pubkey,
+    pub deposited_amount: u64,
+    pub market_value_usd: PodDecimal,
+    pub ltv_bps: u64,
+    pub liquidation_threshold_bps: u64,
+}
+
+/// Borrow slot of a zero-copy obligation
+#[zero_copy]
+pub struct ZeroCopyLiquidity {
+    pub borrow_reserve: Pubkey,
+    pub borrowed_amount_wads: PodDecimal,
+    pub market_value_usd: PodDecimal,
+    pub origination_utilization_rate: PodDecimal,
+    pub origination_borrow_rate: PodDecimal,
+    pub cumulative_borrow_rate_wads: PodDecimal,
+    pub origination_slot: u64,
+}
+
+/// Credit history of a zero-copy obligation
+#[zero_copy]
+pub struct ZeroCopyCreditHistory {
+    pub max_debt_usd: u64,
+    pub last_liquidation_slot: u64,
+    pub on_time_repayments: u32,
+    pub late_repayments: u32,
+    pub liquidations: u32,
+    pub padding: u32,
+}
+
+/// Version 2 obligation layout: fixed-size
+/// Positions live in fixed arrays of `MAX_OBLIGATION_RESERVES` slots, of which the first
+/// `deposits_len` / `borrows_len` are in use, so the account has a constant size and
+/// never needs a realloc. Instructions still go through `AnyObligation`, which copies the
+/// account out and unpacks it into an `Obligation`, so it costs no less compute than the
+/// Borsh layouts. `version`, `market` and `owner` sit at the same offsets as in the other
+/// layouts, so memcmp filters match all of them.
+#[account(zero_copy)]
+pub struct ObligationZeroCopy {
+    /// Version of the obligation account structure
+    pub version: u8,
+
+    /// Market this obligation belongs to
+    pub market: Pubkey,
+
+    /// Owner of this obligation (borrower)
+    pub owner: Pubkey,
+
+    /// eMode category the obligation opted into (0 = none)
+    pub emode_category: u8,
+
+    /// Whether the obligation's collateral is an isolated asset (0 or 1)
+    pub isolation_mode: u8,
+
+    /// Index of this obligation among its owner's obligations (0 = the original one)
+    pub obligation_index: u8,
+
+    /// Deposit slots in use
+    pub deposits_len: u8,
+
+    /// Borrow slots in use
+    pub borrows_len: u8,
+
+    /// Whether `liquidation_snapshot_health_factor` holds a snapshot (0 or 1)
+    pub has_liquidation_snapshot: u8,
+
+    pub padding0: u8,
+
+    /// Health factor (basis points) below which the grace period no longer applies
+    pub liquidation_grace_floor_bps: u16,
+
+    /// Open accounts keyed by this obligation's address (see `Obligation::linked_accounts`)
+    pub linked_accounts: u16,
+
+    pub padding1: [u8; 4],
+
+    /// Total deposited value in USD
+    pub deposited_value_usd: PodDecimal,
+
+    /// Total borrowed value in USD
+    pub borrowed_value_usd: PodDecimal,
+
+    /// Health factor snapshot during liquidation
+    pub liquidation_snapshot_health_factor: PodDecimal,
+
+    /// Timestamp of the last obligation update
+    pub last_update_timestamp: u64,
+
+    /// Slot of the last obligation update
+    pub last_update_slot: u64,
+
+    /// Slot at which the obligation was first observed unhealthy (0 if healthy)
+    pub unhealthy_since_slot: u64,
+
+    /// Slot of the last full revaluation by `refresh_obligation` (0 if never refreshed)
+    pub refreshed_slot: u64,
+
+    /// Slots an unhealthy obligation is shielded from liquidation (0 = no grace period)
+    pub liquidation_grace_slots: u64,
+
+    /// Debt watermark and repayment record
+    pub credit_history: ZeroCopyCreditHistory,
+
+    /// Collateral deposit slots
+    pub deposits: [ZeroCopyCollateral; MAX_OBLIGATION_RESERVES],
+
+    /// Borrow slots
+    pub borrows: [ZeroCopyLiquidity; MAX_OBLIGATION_RESERVES],
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 64],
+}
+
+impl ObligationZeroCopy {
+    /// Size of the ObligationZeroCopy account in bytes
+    pub const SIZE: usize = 8 + std::mem::size_of::<Self>();
+
+    /// Pack an obligation into the zero-copy layout
+    pub fn pack(obligation: &Obligation) -> Result<Self> {
+        if obligation.deposits.len() > MAX_OBLIGATION_RESERVES {
+            return Err(LendingError::ObligationDepositsMaxed.into());
+        }
+        if obligation.borrows.len() > MAX_OBLIGATION_RESERVES {
+            return Err(LendingError::ObligationBorrowsMaxed.into());
+        }
+
+        let mut packed: Self = bytemuck::Zeroable::zeroed();
+        packed.version = obligation.version;
+        packed.market = obligation.market;
+        packed.owner = obligation.owner;
+        packed.emode_category = obligation.emode_category;
+        packed.isolation_mode = obligation.isolation_mode as u8;
+        packed.obligation_index = obligation.obligation_index;
+        packed.deposits_len = obligation.deposits.len() as u8;
+        packed.borrows_len = obligation.borrows.len() as u8;
+        packed.liquidation_grace_floor_bps = obligation.liquidation_grace_floor_bps;
+        packed.linked_accounts = obligation.linked_accounts;
+        packed.deposited_value_usd = obligation.deposited_value_usd.into();
+        packed.borrowed_value_usd = obligation.borrowed_value_usd.into();
+        if let Some(snapshot) = obligation.liquidation_snapshot_health_factor {
+            packed.has_liquidation_snapshot = 1;
+            packed.liquidation_snapshot_health_factor = snapshot.into();
+        }
+        packed.last_update_timestamp = obligation.last_update_timestamp;
+        packed.last_update_slot = obligation.last_update_slot;
+        packed.unhealthy_since_slot = obligation.unhealthy_since_slot;
+        packed.refreshed_slot = obligation.refreshed_slot;
+        packed.liquidation_grace_slots = obligation.liquidation_grace_slots;
+
+        let history = &obligation.credit_history;
+        packed.credit_history = ZeroCopyCreditHistory {
+            max_debt_usd: history.max_debt_usd,
+            last_liquidation_slot: history.last_liquidation_slot,
+            on_time_repayments: history.on_time_repayments,
+            late_repayments: history.late_repayments,
+            liquidations: history.liquidations,
+            padding: 0,
+        };
+
+        for (slot, deposit) in packed.deposits.iter_mut().zip(&obligation.deposits) {
+            *slot = ZeroCopyCollateral {
+                deposit_reserve: deposit.deposit_reserve,
+                deposited_amount: deposit.deposited_amount,
+                market_value_usd: deposit.market_value_usd.into(),
+                ltv_bps: deposit.ltv_bps,
+                liquidation_threshold_bps: deposit.liquidation_threshold_bps,
+            };
+        }
+        for (slot, borrow) in packed.borrows.iter_mut().zip(&obligation.borrows) {
+            *slot = ZeroCopyLiquidity {
+                borrow_reserve: borrow.borrow_reserve,
+                borrowed_amount_wads: borrow.borrowed_amount_wads.into(),
+                market_value_usd: borrow.market_value_usd.into(),
+                origination_utilization_rate: borrow.origination_utilization_rate.into(),
+                origination_borrow_rate: borrow.origination_borrow_rate.into(),
+                cumulative_borrow_rate_wads: borrow.cumulative_borrow_rate_wads.into(),
+                origination_slot: borrow.origination_slot,
+            };
+        }
+
+        Ok(packed)
+    }
+
+    /// Unpack into the canonical obligation
+    pub fn unpack(&self) -> Result<Obligation> {
+        let deposits_len = self.deposits_len as usize;
+        let borrows_len = self.borrows_len as usize;
+        if deposits_len > MAX_OBLIGATION_RESERVES || borrows_len > MAX_OBLIGATION_RESERVES {
+            return Err(LendingError::InvalidAccount.into());
+        }
+
+        let history = &self.credit_history;
+        Ok(Obligation {
+            version: self.version,
+            market: self.market,
+            owner: self.owner,
+            deposits: self.deposits[..deposits_len]
+                .iter()
+                .map(|deposit| ObligationCollateral {
+                    deposit_reserve: deposit.deposit_reserve,
+                    deposited_amount: deposit.deposited_amount,
+                    market_value_usd: deposit.market_value_usd.into(),
+                    ltv_bps: deposit.ltv_bps,
+                    liquidation_threshold_bps: deposit.liquidation_threshold_bps,
+                })
+                .collect(),
+            borrows: self.borrows[..borrows_len]
+                .iter()
+                .map(|borrow| ObligationLiquidity {
+                    borrow_reserve: borrow.borrow_reserve,
+                    borrowed_amount_wads: borrow.borrowed_amount_wads.into(),
+                    market_value_usd: borrow.market_value_usd.into(),
+                    origination_utilization_rate: borrow.origination_utilization_rate.into(),
+                    origination_borrow_rate: borrow.origination_borrow_rate.into(),
+                    origination_slot: borrow.origination_slot,
+                    cumulative_borrow_rate_wads: borrow.cumulative_borrow_rate_wads.into(),
+                })
+                .collect(),
+            deposited_value_usd: self.deposited_value_usd.into(),
+            borrowed_value_usd: self.borrowed_value_usd.into(),
+            last_update_timestamp: self.last_update_timestamp,
+            last_update_slot: self.last_update_slot,
+            liquidation_snapshot_health_factor: (self.has_liquidation_snapshot != 0)
+                .then(|| self.liquidation_snapshot_health_factor.into()),
+            unhealthy_since_slot: self.unhealthy_since_slot,
+            emode_category: self.emode_category,
+            isolation_mode: self.isolation_mode != 0,
+            credit_history: CreditHistory {
+                max_debt_usd: history.max_debt_usd,
+                on_time_repayments: history.on_time_repayments,
+                late_repayments: history.late_repayments,
+                liquidations: history.liquidations,
+                last_liquidation_slot: history.last_liquidation_slot,
+            },
+            refreshed_slot: self.refreshed_slot,
+            liquidation_grace_slots: self.liquidation_grace_slots,
+            liquidation_grace_floor_bps: self.liquidation_grace_floor_bps,
+            obligation_index: self.obligation_index,
+            linked_accounts: self.linked_accounts,
+            reserved: [0; 53],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_is_fixed_and_keeps_filter_offsets() {
+        assert_eq!(std::mem::size_of::<ZeroCopyCollateral>(), 72);
+        assert_eq!(std::mem::size_of::<ZeroCopyLiquidity>(), 120);
+        assert_eq!(ObligationZeroCopy::SIZE, 8 + 2_568);
+
+        // market and owner at the same account offsets as the Borsh layouts (9 and 41)
+        assert_eq!(std::mem::offset_of!(ObligationZeroCopy, market), 1);
+        assert_eq!(std::mem::offset_of!(ObligationZeroCopy, owner), 33);
+    }
+
+    #[test]
+    fn test_pack_round_trip() {
+        let deposit_reserve = Pubkey::new_unique();
+        let borrow_reserve = Pubkey::new_unique();
+        let mut obligation = Obligation {
+            version: PROGRAM_VERSION,
+            market: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            deposits: vec![ObligationCollateral {
+                deposit_reserve,
+                deposited_amount: 1_000,
+                market_value_usd: Decimal::from_integer(1_000).unwrap(),
+                ltv_bps: 7500,
+                liquidation_threshold_bps: 8000,
+            }],
+            borrows: vec![ObligationLiquidity {
+                borrow_reserve,
+                borrowed_amount_wads: Decimal::from_scaled_val(500_123_456_789_000_000_000),
+                market_value_usd: Decimal::from_integer(500).unwrap(),
+                origination_slot: 7,
+                ..Default::default()
+            }],
+            deposited_value_usd: Decimal::from_integer(1_000).unwrap(),
+            borrowed_value_usd: Decimal::from_integer(500).unwrap(),
+            last_update_timestamp: 10,
+            last_update_slot: 20,
+            liquidation_snapshot_health_factor: None,
+            unhealthy_since_slot: 30,
+            emode_category: 2,
+            isolation_mode: true,
+            credit_history: CreditHistory {
+                max_debt_usd: 800,
+                on_time_repayments: 3,
+                liquidations: 1,
+                ..Default::default()
+            },
+            refreshed_slot: 40,
+            liquidation_grace_slots: 150,
+            liquidation_grace_floor_bps: 9500,
+            obligation_index: 3,
+            linked_accounts: 2,
+            reserved: [0; 53],
+        };
+
+        let restored = ObligationZeroCopy::pack(&obligation)
+            .unwrap()
+            .unpack()
+            .unwrap();
+        assert_eq!(restored.deposits.len(), 1);
+        assert_eq!(restored.deposits[0].deposit_reserve, deposit_reserve);
+        assert_eq!(restored.deposits[0].liquidation_threshold_bps, 8000);
+        assert_eq!(restored.borrows.len(), 1);
+        assert_eq!(
+            restored.borrows[0].borrowed_amount_wads,
+            obligation.borrows[0].borrowed_amount_wads
+        );
+        assert_eq!(restored.borrows[0].origination_slot, 7);
+        assert_eq!(restored.liquidation_snapshot_health_factor, None);
+        assert!(restored.isolation_mode);
+        assert_eq!(restored.credit_history, obligation.credit_history);
+        assert_eq!(restored.liquidation_grace_floor_bps, 9500);
+        assert_eq!(restored.obligation_index, 3);
+        assert_eq!(restored.linked_accounts, 2);
+
+        // A snapshot of zero health is kept distinct from no snapshot
+        obligation.liquidation_snapshot_health_factor = Some(Decimal::zero());
+        let restored = ObligationZeroCopy::pack(&obligation)
+            .unwrap()
+            .unpack()
+            .unwrap();
+        assert_eq!(
+            restored.liquidation_snapshot_health_factor,
+            Some(Decimal::zero())
+        );
+
+        obligation.deposits = vec![ObligationCollateral::default(); MAX_OBLIGATION_RESERVES + 1];
+        assert!(ObligationZeroCopy::pack(&obligation).is_err());
+    }
+}
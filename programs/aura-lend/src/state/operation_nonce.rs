@@ -0,0 +1,117 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+
+/// Idempotency record for a relayed user operation
+/// The client supplies a fresh keypair as the nonce account. The account is created
+/// when the operation executes, so a retried transaction carrying the same nonce fails
+/// at account creation instead of executing twice. Records can be closed once the
+/// retention window has passed, returning the rent to the original payer.
+#[account]
+pub struct OperationNonce {
+    /// Version of the nonce account structure
+    pub version: u8,
+
+    /// Authority that executed the operation
+    pub authority: Pubkey,
+
+    /// Account that paid the rent and receives it back on close
+    pub payer: Pubkey,
+
+    /// Operation guarded by this nonce
+    pub operation: NonceOperation,
+
+    /// Reserve the operation was executed against
+    pub reserve: Pubkey,
+
+    /// Amount passed to the operation
+    pub amount: u64,
+
+    /// Slot at which the operation executed
+    pub executed_slot: u64,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl OperationNonce {
+    /// Size of the OperationNonce account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // authority
+        32 + // payer
+        1 + // operation
+        32 + // reserve
+        8 + // amount
+        8 + // executed_slot
+        32; // reserved
+
+    /// Record an executed operation
+    pub fn new(
+        authority: Pubkey,
+        payer: Pubkey,
+        operation: NonceOperation,
+        reserve: Pubkey,
+        amount: u64,
+        executed_slot: u64,
+    ) -> Self {
+        Self {
+            version: PROGRAM_VERSION,
+            authority,
+            payer,
+            operation,
+            reserve,
+            amount,
+            executed_slot,
+            reserved: [0; 32],
+        }
+    }
+
+    /// Check that the retention window has passed so the record can be closed
+    pub fn validate_close(&self, current_slot: u64) -> Result<()> {
+        let closable_slot = self
+            .executed_slot
+            .checked_add(OPERATION_NONCE_RETENTION_SLOTS)
+            .ok_or(LendingError::MathOverflow)?;
+
+        if current_slot < closable_slot {
+            return Err(LendingError::OperationTooEarly.into());
+        }
+        Ok(())
+    }
+}
+
+/// Operations that accept an idempotency nonce
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NonceOperation {
+    /// deposit_reserve_liquidity
+    Deposit,
+    /// borrow_obligation_liquidity
+    Borrow,
+    /// repay_obligation_liquidity
+    Repay,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nonce_closable_after_retention() {
+        let nonce = OperationNonce::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            NonceOperation::Borrow,
+            Pubkey::new_unique(),
+            50_000,
+            1_000,
+        );
+
+        assert!(nonce
+            .validate_close(1_000 + OPERATION_NONCE_RETENTION_SLOTS - 1)
+            .is_err());
+        assert!(nonce
+            .validate_close(1_000 + OPERATION_NONCE_RETENTION_SLOTS)
+            .is_ok());
+    }
+}
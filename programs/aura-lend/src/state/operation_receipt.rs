@@ -0,0 +1,51 @@
+use crate::state::reserve::Reserve;
+use crate::utils::math::Decimal;
+use anchor_lang::prelude::*;
+
+/// Post-state summary of a user operation, set as the instruction's return data
+/// Clients read authoritative results from a simulation, and bots can chain follow-up
+/// instructions in a bundle, without re-deriving reserve and obligation state.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OperationReceipt {
+    /// Reserve whose liquidity moved (the repay reserve for liquidations)
+    pub reserve: Pubkey,
+
+    /// Liquidity moved after rounding and capping, e.g. a repayment capped at the debt;
+    /// the underlying equivalent for obligation collateral moves
+    pub liquidity_amount: u64,
+
+    /// Collateral tokens minted, burned, moved or seized
+    pub collateral_amount: u64,
+
+    /// Fees charged; for liquidations the bonus, in collateral tokens
+    pub fee_amount: u64,
+
+    /// Obligation health factor afterwards in bps (u64::MAX without debt), or None for
+    /// operations that do not touch an obligation. Instructions that take no eMode
+    /// category report it with per-reserve parameters.
+    pub health_factor_bps: Option<u64>,
+
+    /// Utilization of the reserve afterwards (bps)
+    pub utilization_rate_bps: u64,
+}
+
+impl OperationReceipt {
+    /// Summarize an operation on `reserve` from its post-operation state
+    pub fn new(
+        reserve_key: Pubkey,
+        reserve: &Reserve,
+        liquidity_amount: u64,
+        collateral_amount: u64,
+        fee_amount: u64,
+        health_factor: Option<Decimal>,
+    ) -> Result<Self> {
+        Ok(Self {
+            reserve: reserve_key,
+            liquidity_amount,
+            collateral_amount,
+            fee_amount,
+            health_factor_bps: health_factor.map(Decimal::to_health_bps_saturating),
+            utilization_rate_bps: reserve.utilization_rate_bps()?,
+        })
+    }
+}
@@ -0,0 +1,322 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::utils::math::*;
+use anchor_lang::prelude::*;
+
+/// Standing limit orders to buy liquidated collateral
+/// One book exists per (collateral reserve, payment reserve) pair. Orders escrow
+/// payment liquidity and are matched in the liquidation path before the external
+/// liquidator receives any seized collateral.
+#[account]
+pub struct CollateralOrderBook {
+    /// Version of the order book account structure
+    pub version: u8,
+
+    /// Market this order book belongs to
+    pub market: Pubkey,
+
+    /// Reserve whose collateral (aTokens) the orders buy
+    pub collateral_reserve: Pubkey,
+
+    /// Reserve whose liquidity the orders pay with (the liquidation repay asset)
+    pub payment_reserve: Pubkey,
+
+    /// Escrow token account holding payment liquidity of open orders
+    pub liquidity_escrow: Pubkey,
+
+    /// Escrow token account holding filled collateral awaiting claim
+    pub collateral_escrow: Pubkey,
+
+    /// Identifier assigned to the next order
+    pub next_order_id: u64,
+
+    /// Open orders
+    pub orders: Vec<CollateralOrder>,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 64],
+}
+
+impl CollateralOrderBook {
+    /// Size of the CollateralOrderBook account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // collateral_reserve
+        32 + // payment_reserve
+        32 + // liquidity_escrow
+        32 + // collateral_escrow
+        8 + // next_order_id
+        4 + (MAX_COLLATERAL_ORDERS * CollateralOrder::SIZE) + // orders
+        1 + // bump
+        64; // reserved
+
+    /// Create a new, empty order book
+    pub fn new(
+        market: Pubkey,
+        collateral_reserve: Pubkey,
+        payment_reserve: Pubkey,
+        liquidity_escrow: Pubkey,
+        collateral_escrow: Pubkey,
+        bump: u8,
+    ) -> Self {
+        Self {
+            version: PROGRAM_VERSION,
+            market,
+            collateral_reserve,
+            payment_reserve,
+            liquidity_escrow,
+            collateral_escrow,
+            next_order_id: 0,
+            orders: Vec::new(),
+            bump,
+            reserved: [0; 64],
+        }
+    }
+
+    /// Place a new order, returning its id
+    pub fn place_order(
+        &mut self,
+        owner: Pubkey,
+        max_price: Decimal,
+        liquidity_amount: u64,
+        slot: u64,
+    ) -> Result<u64> {
+        if self.orders.len() >= MAX_COLLATERAL_ORDERS {
+            return Err(LendingError::CollateralOrderBookFull.into());
+        }
+        if liquidity_amount == 0 || max_price.is_zero() {
+            return Err(LendingError::InvalidAmount.into());
+        }
+
+        let order_id = self.next_order_id;
+        self.next_order_id = self
+            .next_order_id
+            .checked_add(1)
+            .ok_or(LendingError::MathOverflow)?;
+
+        self.orders.push(CollateralOrder {
+            order_id,
+            owner,
+            max_price,
+            remaining_liquidity: liquidity_amount,
+            filled_collateral: 0,
+            created_slot: slot,
+        });
+
+        Ok(order_id)
+    }
+
+    /// Find an order by id
+    pub fn find_order(&self, order_id: u64) -> Option<&CollateralOrder> {
+        self.orders.iter().find(|o| o.order_id == order_id)
+    }
+
+    /// Remove an order owned by `owner`, returning (unfilled liquidity, filled collateral)
+    pub fn cancel_order(&mut self, order_id: u64, owner: &Pubkey) -> Result<(u64, u64)> {
+        let index = self.owned_order_index(order_id, owner)?;
+        let order = self.orders.remove(index);
+        Ok((order.remaining_liquidity, order.filled_collateral))
+    }
+
+    /// Take the filled collateral of an order owned by `owner`
+    /// Fully filled orders are removed from the book
+    pub fn claim_fills(&mut self, order_id: u64, owner: &Pubkey) -> Result<u64> {
+        let index = self.owned_order_index(order_id, owner)?;
+        let order = &mut self.orders[index];
+        let filled = order.filled_collateral;
+        order.filled_collateral = 0;
+
+        if order.remaining_liquidity == 0 {
+            self.orders.remove(index);
+        }
+
+        Ok(filled)
+    }
+
+    /// Match orders against a liquidation
+    /// `fill_price` is the discounted price per collateral unit the liquidation pays.
+    /// Orders with `max_price >= fill_price` fill best-bid first (then oldest first),
+    /// taking a proportional share of the seized collateral.
+    /// Returns (liquidity paid by orders, collateral allocated to orders).
+    pub fn match_liquidation(
+        &mut self,
+        fill_price: Decimal,
+        liquidity_amount: u64,
+        collateral_amount: u64,
+    ) -> Result<(u64, u64)> {
+        if liquidity_amount == 0 {
+            return Ok((0, 0));
+        }
+
+        let mut eligible: Vec<usize> = self
+            .orders
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| o.remaining_liquidity > 0 && o.max_price >= fill_price)
+            .map(|(i, _)| i)
+            .collect();
+        eligible.sort_by(|a, b| {
+            let (a, b) = (&self.orders[*a], &self.orders[*b]);
+            b.max_price
+                .cmp(&a.max_price)
+                .then(a.order_id.cmp(&b.order_id))
+        });
+
+        let mut liquidity_filled = 0u64;
+        let mut collateral_filled = 0u64;
+
+        for index in eligible {
+            let remaining = liquidity_amount - liquidity_filled;
+            if remaining == 0 {
+                break;
+            }
+
+            let order = &mut self.orders[index];
+            let fill_liquidity = std::cmp::min(order.remaining_liquidity, remaining);
+            let fill_collateral = (collateral_amount as u128)
+                .checked_mul(fill_liquidity as u128)
+                .ok_or(LendingError::MathOverflow)?
+                .checked_div(liquidity_amount as u128)
+                .ok_or(LendingError::DivisionByZero)? as u64;
+
+            order.remaining_liquidity -= fill_liquidity;
+            order.filled_collateral = order
+                .filled_collateral
+                .checked_add(fill_collateral)
+                .ok_or(LendingError::MathOverflow)?;
+
+            liquidity_filled += fill_liquidity;
+            collateral_filled = collateral_filled
+                .checked_add(fill_collateral)
+                .ok_or(LendingError::MathOverflow)?;
+        }
+
+        Ok((liquidity_filled, collateral_filled))
+    }
+
+    fn owned_order_index(&self, order_id: u64, owner: &Pubkey) -> Result<usize> {
+        let index = self
+            .orders
+            .iter()
+            .position(|o| o.order_id == order_id)
+            .ok_or(LendingError::CollateralOrderNotFound)?;
+
+        if self.orders[index].owner != *owner {
+            return Err(LendingError::InvalidAuthority.into());
+        }
+
+        Ok(index)
+    }
+}
+
+/// A standing order to buy liquidated collateral at or below a maximum price
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct CollateralOrder {
+    /// Unique order identifier within the book
+    pub order_id: u64,
+
+    /// Owner of the order
+    pub owner: Pubkey,
+
+    /// Maximum price per collateral unit the owner is willing to pay
+    pub max_price: Decimal,
+
+    /// Escrowed payment liquidity not yet used
+    pub remaining_liquidity: u64,
+
+    /// Collateral allocated to this order awaiting claim
+    pub filled_collateral: u64,
+
+    /// Slot at which the order was placed
+    pub created_slot: u64,
+}
+
+impl CollateralOrder {
+    /// Serialized size of a collateral order
+    pub const SIZE: usize = 8 + // order_id
+        32 + // owner
+        16 + // max_price
+        8 + // remaining_liquidity
+        8 + // filled_collateral
+        8; // created_slot
+}
+
+/// Parameters for placing a collateral order
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PlaceCollateralOrderParams {
+    pub max_price: Decimal,
+    pub liquidity_amount: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_book() -> CollateralOrderBook {
+        CollateralOrderBook::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            255,
+        )
+    }
+
+    #[test]
+    fn test_best_bid_fills_first() {
+        let mut book = test_book();
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+
+        book.place_order(alice, Decimal::from_integer(90).unwrap(), 1_000, 1)
+            .unwrap();
+        book.place_order(bob, Decimal::from_integer(100).unwrap(), 600, 2)
+            .unwrap();
+
+        let (liquidity, collateral) = book
+            .match_liquidation(Decimal::from_integer(80).unwrap(), 1_000, 2_000)
+            .unwrap();
+
+        assert_eq!(liquidity, 1_000);
+        assert_eq!(collateral, 2_000);
+        assert_eq!(book.find_order(1).unwrap().remaining_liquidity, 0);
+        assert_eq!(book.find_order(1).unwrap().filled_collateral, 1_200);
+        assert_eq!(book.find_order(0).unwrap().remaining_liquidity, 600);
+        assert_eq!(book.find_order(0).unwrap().filled_collateral, 800);
+    }
+
+    #[test]
+    fn test_orders_above_fill_price_do_not_match() {
+        let mut book = test_book();
+        book.place_order(Pubkey::new_unique(), Decimal::from_integer(50).unwrap(), 1_000, 1)
+            .unwrap();
+
+        let (liquidity, collateral) = book
+            .match_liquidation(Decimal::from_integer(80).unwrap(), 1_000, 2_000)
+            .unwrap();
+
+        assert_eq!((liquidity, collateral), (0, 0));
+    }
+
+    #[test]
+    fn test_cancel_and_claim_require_owner() {
+        let mut book = test_book();
+        let owner = Pubkey::new_unique();
+        let id = book
+            .place_order(owner, Decimal::from_integer(100).unwrap(), 500, 1)
+            .unwrap();
+
+        book.match_liquidation(Decimal::from_integer(80).unwrap(), 200, 400)
+            .unwrap();
+
+        assert!(book.claim_fills(id, &Pubkey::new_unique()).is_err());
+        assert_eq!(book.claim_fills(id, &owner).unwrap(), 400);
+        assert_eq!(book.cancel_order(id, &owner).unwrap(), (300, 0));
+        assert!(book.orders.is_empty());
+    }
+}
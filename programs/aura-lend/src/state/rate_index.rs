@@ -0,0 +1,250 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+
+/// Realized borrow rate of a reserve over one finalized epoch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EpochRate {
+    /// Epoch the rate was realized over
+    pub epoch: u64,
+
+    /// Time-weighted average annual borrow rate, scaled by 1e18 (saturates at u64::MAX)
+    pub average_borrow_rate: u64,
+
+    /// Number of rate observations taken during the epoch
+    pub samples: u32,
+}
+
+impl EpochRate {
+    /// Serialized size of an epoch rate entry in bytes
+    pub const SIZE: usize = 8 + // epoch
+        8 + // average_borrow_rate
+        4; // samples
+}
+
+/// Per-epoch realized borrow-rate index of a reserve
+/// Intended as a settlement reference for external rate derivatives. Guarantees:
+/// - The rate is sampled by the permissionless `update_rate_index` crank and treated
+///   as constant between samples, so each epoch's value is the slot-weighted average
+///   of the sampled rates over the slots of that epoch that were tracked.
+/// - An epoch is finalized by the first update in any later epoch. Once written to
+///   `history` an entry is never modified; it is only evicted after
+///   `MAX_RATE_INDEX_EPOCHS` newer epochs have been finalized.
+/// - Epochs in which no update landed are not recorded. Consumers should treat a
+///   missing epoch, or one with too few `samples`, as having no reference rate.
+#[account]
+pub struct ReserveRateIndex {
+    /// Version of the rate index account structure
+    pub version: u8,
+
+    /// Reserve whose borrow rate is indexed
+    pub reserve: Pubkey,
+
+    /// Epoch currently being accumulated
+    pub current_epoch: u64,
+
+    /// First tracked slot of the current epoch
+    pub epoch_start_slot: u64,
+
+    /// Slot of the last observation
+    pub last_update_slot: u64,
+
+    /// Borrow rate at the last observation, scaled by 1e18
+    pub last_borrow_rate: u128,
+
+    /// Sum of borrow rate × slots over the current epoch
+    pub accumulated_rate_slots: u128,
+
+    /// Observations taken during the current epoch
+    pub samples: u32,
+
+    /// Ring buffer position of the next finalized epoch
+    pub head: u16,
+
+    /// Number of finalized epochs stored
+    pub len: u16,
+
+    /// Finalized epochs, oldest overwritten first
+    pub history: [EpochRate; MAX_RATE_INDEX_EPOCHS],
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl ReserveRateIndex {
+    /// Size of the ReserveRateIndex account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // reserve
+        8 + // current_epoch
+        8 + // epoch_start_slot
+        8 + // last_update_slot
+        16 + // last_borrow_rate
+        16 + // accumulated_rate_slots
+        4 + // samples
+        2 + // head
+        2 + // len
+        EpochRate::SIZE * MAX_RATE_INDEX_EPOCHS + // history
+        1 + // bump
+        32; // reserved
+
+    /// Create a new index starting at the current slot
+    pub fn new(
+        reserve: Pubkey,
+        borrow_rate: u128,
+        current_slot: u64,
+        epoch: u64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            version: PROGRAM_VERSION,
+            reserve,
+            current_epoch: epoch,
+            epoch_start_slot: current_slot,
+            last_update_slot: current_slot,
+            last_borrow_rate: borrow_rate,
+            accumulated_rate_slots: 0,
+            samples: 1,
+            head: 0,
+            len: 0,
+            history: [EpochRate::default(); MAX_RATE_INDEX_EPOCHS],
+            bump,
+            reserved: [0; 32],
+        }
+    }
+
+    /// Record a borrow rate observation
+    /// `tracked_epoch_end_slot` is the first slot after `current_epoch` and
+    /// `epoch_start_slot` the first slot of `epoch`. Returns the finalized entry when
+    /// the observation moves the index into a new epoch.
+    pub fn update(
+        &mut self,
+        borrow_rate: u128,
+        current_slot: u64,
+        epoch: u64,
+        tracked_epoch_end_slot: u64,
+        epoch_start_slot: u64,
+    ) -> Result<Option<EpochRate>> {
+        if epoch < self.current_epoch {
+            return Err(LendingError::OperationExpired.into());
+        }
+
+        let mut finalized = None;
+        if epoch > self.current_epoch {
+            self.accumulate(std::cmp::min(current_slot, tracked_epoch_end_slot))?;
+            finalized = Some(self.finalize(tracked_epoch_end_slot)?);
+
+            self.current_epoch = epoch;
+            self.epoch_start_slot = epoch_start_slot;
+            self.last_update_slot = epoch_start_slot;
+            self.accumulated_rate_slots = 0;
+            self.samples = 0;
+        }
+
+        self.accumulate(current_slot)?;
+        self.last_borrow_rate = borrow_rate;
+        self.samples = self.samples.saturating_add(1);
+
+        Ok(finalized)
+    }
+
+    /// Finalized rate for `epoch`, if it is still stored
+    pub fn rate_for_epoch(&self, epoch: u64) -> Option<&EpochRate> {
+        self.history
+            .iter()
+            .take(self.len as usize)
+            .find(|entry| entry.epoch == epoch)
+    }
+
+    /// Add the last observed rate over the slots since the last observation
+    fn accumulate(&mut self, until_slot: u64) -> Result<()> {
+        let elapsed_slots = until_slot.saturating_sub(self.last_update_slot);
+        self.accumulated_rate_slots = self
+            .last_borrow_rate
+            .checked_mul(elapsed_slots as u128)
+            .and_then(|weighted| self.accumulated_rate_slots.checked_add(weighted))
+            .ok_or(LendingError::MathOverflow)?;
+        self.last_update_slot = std::cmp::max(self.last_update_slot, until_slot);
+        Ok(())
+    }
+
+    /// Close out the current epoch and append it to the history
+    fn finalize(&mut self, epoch_end_slot: u64) -> Result<EpochRate> {
+        let tracked_slots = epoch_end_slot.saturating_sub(self.epoch_start_slot);
+        let average = if tracked_slots == 0 {
+            self.last_borrow_rate
+        } else {
+            self.accumulated_rate_slots / tracked_slots as u128
+        };
+
+        let entry = EpochRate {
+            epoch: self.current_epoch,
+            average_borrow_rate: u64::try_from(average).unwrap_or(u64::MAX),
+            samples: self.samples,
+        };
+
+        self.history[self.head as usize] = entry;
+        self.head = ((self.head as usize + 1) % MAX_RATE_INDEX_EPOCHS) as u16;
+        self.len = std::cmp::min(self.len as usize + 1, MAX_RATE_INDEX_EPOCHS) as u16;
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPOCH_SLOTS: u64 = 432_000;
+    const RATE_5_PCT: u128 = 50_000_000_000_000_000;
+    const RATE_10_PCT: u128 = 100_000_000_000_000_000;
+
+    #[test]
+    fn test_epoch_average_is_slot_weighted() {
+        let mut index = ReserveRateIndex::new(Pubkey::new_unique(), RATE_5_PCT, 0, 0, 255);
+
+        // 5% for the first quarter of the epoch, 10% for the rest
+        assert!(index
+            .update(RATE_10_PCT, EPOCH_SLOTS / 4, 0, EPOCH_SLOTS, 0)
+            .unwrap()
+            .is_none());
+
+        let finalized = index
+            .update(RATE_10_PCT, EPOCH_SLOTS + 10, 1, EPOCH_SLOTS, EPOCH_SLOTS)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(finalized.epoch, 0);
+        assert_eq!(finalized.average_borrow_rate, 87_500_000_000_000_000);
+        assert_eq!(finalized.samples, 2);
+        assert_eq!(index.rate_for_epoch(0), Some(&finalized));
+        assert_eq!(index.rate_for_epoch(1), None);
+
+        // Slots of the new epoch before the first update use the last observed rate
+        assert_eq!(index.accumulated_rate_slots, RATE_10_PCT * 10);
+        assert_eq!(index.samples, 1);
+    }
+
+    #[test]
+    fn test_history_is_a_bounded_ring() {
+        let mut index = ReserveRateIndex::new(Pubkey::new_unique(), RATE_5_PCT, 0, 0, 255);
+
+        for epoch in 1..=(MAX_RATE_INDEX_EPOCHS as u64 + 2) {
+            let start = epoch * EPOCH_SLOTS;
+            index.update(RATE_5_PCT, start, epoch, start, start).unwrap();
+        }
+
+        assert_eq!(index.len as usize, MAX_RATE_INDEX_EPOCHS);
+        assert!(index.rate_for_epoch(0).is_none());
+        assert!(index.rate_for_epoch(1).is_none());
+        assert_eq!(
+            index.rate_for_epoch(2).unwrap().average_borrow_rate,
+            RATE_5_PCT as u64
+        );
+
+        // Observations cannot move backwards in epochs
+        assert!(index.update(RATE_5_PCT, 0, 0, 0, 0).is_err());
+    }
+}
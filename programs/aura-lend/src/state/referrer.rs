@@ -0,0 +1,177 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+
+/// Referrer registered with a market
+/// Accrues a governance-configured share of the borrow fees paid by the obligations
+/// it referred. Fees stay in each reserve's liquidity supply until the referrer claims
+/// them, so they are tracked per reserve.
+#[account]
+pub struct Referrer {
+    /// Version of the referrer account structure
+    pub version: u8,
+
+    /// Market this referrer belongs to
+    pub market: Pubkey,
+
+    /// Wallet that registered the referrer and may claim its fees
+    pub owner: Pubkey,
+
+    /// Fees accrued in each reserve
+    pub fee_balances: Vec<ReferralFeeBalance>,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 64],
+}
+
+/// Referral fees accrued in a single reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReferralFeeBalance {
+    /// Reserve the fees are denominated in
+    pub reserve: Pubkey,
+
+    /// Fees accrued and not yet claimed
+    pub claimable: u64,
+
+    /// Total fees ever accrued
+    pub total_earned: u64,
+
+    /// Deposits and borrows made through the referral
+    pub referred_volume: u64,
+}
+
+impl ReferralFeeBalance {
+    /// Size of a fee balance entry in bytes
+    pub const SIZE: usize = 32 + 8 + 8 + 8;
+}
+
+impl Referrer {
+    /// Size of the Referrer account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // owner
+        4 + (ReferralFeeBalance::SIZE * MAX_REFERRER_RESERVES) + // fee_balances
+        1 + // bump
+        64; // reserved
+
+    /// Create a new referrer
+    pub fn new(market: Pubkey, owner: Pubkey, bump: u8) -> Self {
+        Self {
+            version: PROGRAM_VERSION,
+            market,
+            owner,
+            fee_balances: Vec::new(),
+            bump,
+            reserved: [0; 64],
+        }
+    }
+
+    /// Check that the referrer may be credited for activity by `user`
+    pub fn validate_referred(&self, market: &Pubkey, user: &Pubkey) -> Result<()> {
+        if self.market != *market || self.owner == *user {
+            return Err(LendingError::InvalidReferrer.into());
+        }
+        Ok(())
+    }
+
+    /// Record `volume` referred in `reserve` earning `fee` for the referrer
+    pub fn record_referral(&mut self, reserve: Pubkey, volume: u64, fee: u64) -> Result<()> {
+        let index = match self.fee_balances.iter().position(|b| b.reserve == reserve) {
+            Some(index) => index,
+            None => {
+                if self.fee_balances.len() >= MAX_REFERRER_RESERVES {
+                    return Err(LendingError::ReferrerReservesFull.into());
+                }
+                self.fee_balances.push(ReferralFeeBalance {
+                    reserve,
+                    ..Default::default()
+                });
+                self.fee_balances.len() - 1
+            }
+        };
+
+        let balance = &mut self.fee_balances[index];
+        balance.claimable = balance
+            .claimable
+            .checked_add(fee)
+            .ok_or(LendingError::MathOverflow)?;
+        balance.total_earned = balance
+            .total_earned
+            .checked_add(fee)
+            .ok_or(LendingError::MathOverflow)?;
+        balance.referred_volume = balance
+            .referred_volume
+            .checked_add(volume)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Fees accrued in `reserve` and not yet claimed
+    pub fn claimable(&self, reserve: &Pubkey) -> u64 {
+        self.fee_balances
+            .iter()
+            .find(|b| b.reserve == *reserve)
+            .map_or(0, |b| b.claimable)
+    }
+
+    /// Zero the claimable balance in `reserve` and return it
+    pub fn take_claimable(&mut self, reserve: &Pubkey) -> Result<u64> {
+        let balance = self
+            .fee_balances
+            .iter_mut()
+            .find(|b| b.reserve == *reserve && b.claimable > 0)
+            .ok_or(LendingError::NoReferralFees)?;
+        Ok(std::mem::take(&mut balance.claimable))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_referral_fees_accrue_per_reserve() {
+        let mut referrer = Referrer::new(Pubkey::new_unique(), Pubkey::new_unique(), 255);
+        let usdc = Pubkey::new_unique();
+        let sol = Pubkey::new_unique();
+
+        referrer.record_referral(usdc, 10_000, 20).unwrap();
+        referrer.record_referral(usdc, 5_000, 10).unwrap();
+        referrer.record_referral(sol, 1_000, 0).unwrap();
+        assert_eq!(referrer.fee_balances.len(), 2);
+        assert_eq!(referrer.claimable(&usdc), 30);
+        assert_eq!(referrer.fee_balances[0].referred_volume, 15_000);
+
+        // Claiming empties the balance but keeps lifetime totals
+        assert_eq!(referrer.take_claimable(&usdc).unwrap(), 30);
+        assert_eq!(referrer.claimable(&usdc), 0);
+        assert_eq!(referrer.fee_balances[0].total_earned, 30);
+        assert!(referrer.take_claimable(&usdc).is_err());
+        assert!(referrer.take_claimable(&sol).is_err());
+
+        // Owners cannot refer themselves
+        assert!(referrer
+            .validate_referred(&referrer.market, &referrer.owner)
+            .is_err());
+        assert!(referrer
+            .validate_referred(&referrer.market, &Pubkey::new_unique())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_referrer_reserves_are_bounded() {
+        let mut referrer = Referrer::new(Pubkey::new_unique(), Pubkey::new_unique(), 255);
+        for _ in 0..MAX_REFERRER_RESERVES {
+            referrer
+                .record_referral(Pubkey::new_unique(), 100, 1)
+                .unwrap();
+        }
+        assert!(referrer
+            .record_referral(Pubkey::new_unique(), 100, 1)
+            .is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+
+/// Opt-in consent for keepers to close a user's empty, dormant accounts
+/// While the consent exists, any keeper may close the user's obligation once it holds
+/// no deposits or borrows and has seen no activity for `min_inactive_slots`. Rent is
+/// always returned to the owner's wallet; the keeper gains nothing but chain hygiene.
+/// Granting consent counts as activity, so sweeps never follow the grant immediately.
+#[account]
+pub struct RentReclaimConsent {
+    /// Version of the consent account structure
+    pub version: u8,
+
+    /// User who granted the consent and receives reclaimed rent
+    pub owner: Pubkey,
+
+    /// Inactivity period after which empty accounts may be closed
+    pub min_inactive_slots: u64,
+
+    /// Slot at which the consent was granted
+    pub granted_slot: u64,
+
+    /// Number of accounts closed under this consent
+    pub accounts_reclaimed: u32,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl RentReclaimConsent {
+    /// Size of the RentReclaimConsent account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // owner
+        8 + // min_inactive_slots
+        8 + // granted_slot
+        4 + // accounts_reclaimed
+        1 + // bump
+        32; // reserved
+
+    /// Create a new consent record
+    pub fn new(
+        owner: Pubkey,
+        min_inactive_slots: u64,
+        granted_slot: u64,
+        bump: u8,
+    ) -> Result<Self> {
+        if min_inactive_slots < MIN_RENT_RECLAIM_INACTIVITY_SLOTS {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+
+        Ok(Self {
+            version: PROGRAM_VERSION,
+            owner,
+            min_inactive_slots,
+            granted_slot,
+            accounts_reclaimed: 0,
+            bump,
+            reserved: [0; 32],
+        })
+    }
+
+    /// Whether an account last active at `last_activity_slot` may be swept
+    pub fn is_dormant(&self, last_activity_slot: u64, current_slot: u64) -> bool {
+        let last_activity = std::cmp::max(last_activity_slot, self.granted_slot);
+        current_slot.saturating_sub(last_activity) >= self.min_inactive_slots
+    }
+
+    /// Record a sweep performed under this consent
+    pub fn record_reclaim(&mut self) {
+        self.accounts_reclaimed = self.accounts_reclaimed.saturating_add(1);
+    }
+}
+
+/// Parameters for granting rent reclamation consent
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GrantRentReclaimConsentParams {
+    pub min_inactive_slots: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dormancy_counts_from_latest_activity() {
+        let period = MIN_RENT_RECLAIM_INACTIVITY_SLOTS;
+        let consent = RentReclaimConsent::new(Pubkey::new_unique(), period, 1_000, 255).unwrap();
+
+        // The grant itself resets the inactivity clock
+        assert!(!consent.is_dormant(0, period));
+        assert!(consent.is_dormant(0, 1_000 + period));
+
+        // Later account activity pushes the sweep back
+        assert!(!consent.is_dormant(5_000, 1_000 + period));
+        assert!(consent.is_dormant(5_000, 5_000 + period));
+
+        assert!(RentReclaimConsent::new(Pubkey::new_unique(), period - 1, 0, 255).is_err());
+    }
+}
@@ -1,6 +1,10 @@
 use crate::constants::*;
 use crate::error::LendingError;
+use crate::state::proposal_lifecycle::ProposalStatus;
+use crate::state::timelock::{TimelockOperationType, TimelockProposal};
+use crate::utils::config::ProtocolConfig;
 use crate::utils::math::*;
+use crate::utils::oracle::{OracleManager, OraclePrice};
 use anchor_lang::prelude::*;
 
 /// Reserve state account for each supported asset
@@ -25,10 +29,10 @@ pub struct Reserve {
     /// Fee receiver token account
     pub fee_receiver: Pubkey,
 
-    /// Pyth price oracle account
+    /// Price oracle account (Pyth price update or Switchboard aggregator)
     pub price_oracle: Pubkey,
 
-    /// Pyth price feed ID for this asset
+    /// Pyth price feed ID for this asset (unused by other oracle sources)
     pub oracle_feed_id: [u8; 32],
 
     /// Configuration parameters for this reserve
@@ -43,11 +47,81 @@ pub struct Reserve {
     /// Slot of the last state update
     pub last_update_slot: u64,
 
-    /// Reentrancy guard - prevents concurrent operations
-    pub reentrancy_guard: bool,
+    /// Split of collected protocol fees; the remainder goes to `fee_receiver`
+    pub fee_split: FeeSplit,
+
+    /// Asset class tag grouping interchangeable reserves (0 = unclassified)
+    pub asset_class: u8,
+
+    /// Where the reserve's price comes from
+    pub oracle_source: OracleSource,
+
+    /// USD price of a `FixedPrice` reserve, in units of 10^`FIXED_PRICE_EXPONENT`
+    pub fixed_price: u64,
+
+    /// Secondary oracle cross-checking the primary price (default pubkey = none)
+    pub secondary_oracle: Pubkey,
+
+    /// Price source of the secondary oracle
+    pub secondary_oracle_source: OracleSource,
+
+    /// Maximum divergence between the primary and secondary price (basis points)
+    pub max_oracle_deviation_bps: u64,
+
+    /// Time-weighted average of the primary price, in units of `twap_exponent`
+    pub twap_price: i64,
+
+    /// Exponent of `twap_price`
+    pub twap_exponent: i32,
+
+    /// Unix timestamp of the last TWAP update
+    pub twap_last_update_timestamp: u64,
+
+    /// Averaging window of the TWAP in seconds (0 disables smoothing)
+    pub twap_window_seconds: u64,
+
+    /// Unix timestamp the reserve's wind-down began (0 = not deprecated)
+    pub deprecation_start_timestamp: u64,
+
+    /// Unix timestamp at which the LTV reaches zero and deleveraging opens
+    pub deprecation_deadline_timestamp: u64,
+
+    /// Collateral bonus paid to keepers deleveraging positions (basis points)
+    pub deleverage_penalty_bps: u16,
+
+    /// Maximum USD debt isolated obligations may borrow against this reserve's
+    /// collateral (0 = uncapped)
+    pub debt_ceiling_usd: u64,
+
+    /// USD debt currently borrowed by isolated obligations against this reserve's
+    /// collateral, valued at borrow time
+    pub isolation_mode_total_debt: u64,
+
+    /// Utilization above which the surge withdrawal fee arms (basis points, 0 = disabled)
+    pub surge_utilization_threshold_bps: u16,
+
+    /// Slots utilization must stay above the threshold before the surge fee applies
+    pub surge_duration_slots: u64,
+
+    /// Share of redeemed liquidity withheld for remaining suppliers while the surge is
+    /// active (basis points)
+    pub surge_withdrawal_fee_bps: u16,
+
+    /// Slot utilization was first seen above the surge threshold (0 = below it)
+    pub high_utilization_since_slot: u64,
+
+    /// Liquidity withheld from redemptions by the surge fee
+    pub total_surge_fees: u64,
+
+    /// Operations disabled by an emergency guardian; only a timelocked unpause
+    /// re-enables them
+    pub emergency_pause_flags: ReserveConfigFlags,
+
+    /// Price derived from other oracles, read when `oracle_source` is `Derived`
+    pub derived_oracle: DerivedOracle,
 
     /// Reserved space for future upgrades
-    pub reserved: [u8; 255],
+    pub reserved: [u8; 8],
 }
 
 impl Reserve {
@@ -64,7 +138,30 @@ impl Reserve {
         std::mem::size_of::<ReserveState>() + // state
         8 + // last_update_timestamp
         8 + // last_update_slot
-        256; // reserved
+        FeeSplit::SIZE + // fee_split
+        1 + // asset_class
+        1 + // oracle_source
+        8 + // fixed_price
+        32 + // secondary_oracle
+        1 + // secondary_oracle_source
+        8 + // max_oracle_deviation_bps
+        8 + // twap_price
+        4 + // twap_exponent
+        8 + // twap_last_update_timestamp
+        8 + // twap_window_seconds
+        8 + // deprecation_start_timestamp
+        8 + // deprecation_deadline_timestamp
+        2 + // deleverage_penalty_bps
+        8 + // debt_ceiling_usd
+        8 + // isolation_mode_total_debt
+        2 + // surge_utilization_threshold_bps
+        8 + // surge_duration_slots
+        2 + // surge_withdrawal_fee_bps
+        8 + // high_utilization_since_slot
+        8 + // total_surge_fees
+        4 + // emergency_pause_flags
+        DerivedOracle::SIZE + // derived_oracle
+        9; // reserved
 
     /// Create a new reserve with the given parameters
     pub fn new(
@@ -80,7 +177,7 @@ impl Reserve {
         let clock = Clock::get()?;
 
         Ok(Self {
-            version: PROGRAM_VERSION,
+            version: RESERVE_VERSION,
             market,
             liquidity_mint,
             collateral_mint,
@@ -89,50 +186,73 @@ impl Reserve {
             price_oracle,
             oracle_feed_id,
             config,
-            state: ReserveState::default(),
+            state: ReserveState {
+                cumulative_borrow_rate_wads: Decimal::one(),
+                ..ReserveState::default()
+            },
             last_update_timestamp: clock.unix_timestamp as u64,
             last_update_slot: clock.slot,
-            reentrancy_guard: false,
-            reserved: [0; 255],
+            fee_split: FeeSplit::default(),
+            asset_class: 0,
+            oracle_source: OracleSource::Pyth,
+            fixed_price: 0,
+            secondary_oracle: Pubkey::default(),
+            secondary_oracle_source: OracleSource::Pyth,
+            max_oracle_deviation_bps: 0,
+            twap_price: 0,
+            twap_exponent: 0,
+            twap_last_update_timestamp: 0,
+            twap_window_seconds: 0,
+            deprecation_start_timestamp: 0,
+            deprecation_deadline_timestamp: 0,
+            deleverage_penalty_bps: 0,
+            debt_ceiling_usd: 0,
+            isolation_mode_total_debt: 0,
+            surge_utilization_threshold_bps: 0,
+            surge_duration_slots: 0,
+            surge_withdrawal_fee_bps: 0,
+            high_utilization_since_slot: 0,
+            total_surge_fees: 0,
+            emergency_pause_flags: ReserveConfigFlags::default(),
+            derived_oracle: DerivedOracle::default(),
+            reserved: [0; 8],
         })
     }
 
     /// Update interest rates and accrued interest
     pub fn update_interest(&mut self, current_slot: u64) -> Result<()> {
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        self.accrue_interest(current_slot, current_timestamp)
+    }
+
+    /// Accrue interest for the slots elapsed since the last update
+    /// Borrow interest grows total borrows; the supplier share grows total liquidity,
+    /// which raises the collateral exchange rate used by deposits and redemptions
+    pub fn accrue_interest(&mut self, current_slot: u64, current_timestamp: u64) -> Result<()> {
+        // Utilization left by earlier instructions decides whether the surge fee arms
+        self.track_surge_utilization(current_slot)?;
+
         if current_slot <= self.last_update_slot {
             return Ok(()); // Already updated or invalid slot
         }
 
         let slots_elapsed = current_slot - self.last_update_slot;
 
-        // Calculate current utilization rate
-        let utilization_rate =
-            Rate::utilization_rate(self.state.total_borrows, self.state.available_liquidity)?;
-
-        // Calculate new borrow interest rate
-        let borrow_rate = Rate::calculate_interest_rate(
-            self.config.base_borrow_rate_bps,
-            self.config.borrow_rate_multiplier_bps,
-            self.config.jump_rate_multiplier_bps,
-            self.config.optimal_utilization_rate_bps,
-            utilization_rate,
-        )?;
+        // Calculate current utilization and borrow interest rate
+        let (utilization_rate_bps, borrow_rate_bps) =
+            self.rates_at(self.state.total_borrows, self.state.available_liquidity)?;
 
-        // Calculate supply interest rate (borrow rate * utilization * (1 - protocol fee))
-        let protocol_fee_rate = Decimal::from_scaled_val(
-            (self.config.protocol_fee_bps as u128)
-                .checked_mul(PRECISION as u128)
-                .ok_or(LendingError::MathOverflow)?
-                .checked_div(BASIS_POINTS_PRECISION as u128)
-                .ok_or(LendingError::DivisionByZero)?,
-        );
+        let utilization_rate = bps_to_decimal(utilization_rate_bps)?;
+        let borrow_rate = bps_to_decimal(borrow_rate_bps)?;
 
+        // Calculate supply interest rate (borrow rate * utilization * (1 - protocol fee))
+        let protocol_fee_rate = bps_to_decimal(self.config.protocol_fee_bps)?;
         let fee_complement = Decimal::one().try_sub(protocol_fee_rate)?;
         let supply_rate = borrow_rate
             .try_mul(utilization_rate)?
             .try_mul(fee_complement)?;
 
-        // Compound interest over the time period
+        // Fraction of a year elapsed
         let time_fraction = Decimal::from_scaled_val(
             (slots_elapsed as u128)
                 .checked_mul(PRECISION as u128)
@@ -142,24 +262,24 @@ impl Reserve {
         );
 
         // Update borrow interest
-        if !borrow_rate.is_zero() && self.state.total_borrows > 0 {
-            let borrow_interest = Rate::compound_interest(
-                Decimal::from_integer(self.state.total_borrows)?,
-                borrow_rate,
-                SLOTS_PER_YEAR / 365, // Daily compounding
-                time_fraction,
-            )?;
+        let borrow_growth = growth_factor(borrow_rate, time_fraction)?;
+        self.state.cumulative_borrow_rate_wads =
+            self.cumulative_borrow_rate_wads().try_mul(borrow_growth)?;
 
-            let interest_earned =
-                borrow_interest.try_sub(Decimal::from_integer(self.state.total_borrows)?)?;
-            let _interest_amount = interest_earned.try_floor_u64()?;
+        if !borrow_rate.is_zero() && self.state.total_borrows > 0 {
+            let total_borrows = compound(self.state.total_borrows, borrow_growth)?;
+            let interest_earned = total_borrows
+                .checked_sub(self.state.total_borrows)
+                .ok_or(LendingError::MathUnderflow)?;
 
-            self.state.total_borrows = borrow_interest.try_floor_u64()?;
+            self.state.total_borrows = total_borrows;
 
             // Protocol fee on interest
-            let protocol_fee = interest_earned
-                .try_mul(protocol_fee_rate)?
-                .try_floor_u64()?;
+            let protocol_fee = (interest_earned as u128)
+                .checked_mul(self.config.protocol_fee_bps as u128)
+                .ok_or(LendingError::MathOverflow)?
+                .checked_div(BASIS_POINTS_PRECISION as u128)
+                .ok_or(LendingError::DivisionByZero)? as u64;
             self.state.accumulated_protocol_fees = self
                 .state
                 .accumulated_protocol_fees
@@ -169,58 +289,192 @@ impl Reserve {
 
         // Update supply interest (collateral exchange rate)
         if !supply_rate.is_zero() && self.state.total_liquidity > 0 {
-            let supply_interest = Rate::compound_interest(
-                Decimal::from_integer(self.state.total_liquidity)?,
-                supply_rate,
-                SLOTS_PER_YEAR / 365, // Daily compounding
-                time_fraction,
+            self.state.total_liquidity = compound(
+                self.state.total_liquidity,
+                growth_factor(supply_rate, time_fraction)?,
             )?;
-
-            self.state.total_liquidity = supply_interest.try_floor_u64()?;
         }
 
         // Update stored rates
-        self.state.current_borrow_rate = borrow_rate;
-        self.state.current_supply_rate = supply_rate;
-        self.state.current_utilization_rate = utilization_rate;
+        self.state.current_borrow_rate_bps = borrow_rate_bps;
+        self.state.current_supply_rate_bps = decimal_to_bps(supply_rate);
+        self.state.current_utilization_rate_bps = utilization_rate_bps;
 
         // Update timestamps
         self.last_update_slot = current_slot;
-        self.last_update_timestamp = Clock::get()?.unix_timestamp as u64;
+        self.last_update_timestamp = current_timestamp;
 
         Ok(())
     }
 
+    /// Cumulative borrow rate that borrow positions accrue their debt against
+    /// Reserves created before the rate was tracked start from 1.
+    pub fn cumulative_borrow_rate_wads(&self) -> Decimal {
+        if self.state.cumulative_borrow_rate_wads.is_zero() {
+            Decimal::one()
+        } else {
+            self.state.cumulative_borrow_rate_wads
+        }
+    }
+
+    /// Utilization and borrow rate (both in bps) for the given borrows and liquidity
+    /// The borrow rate is capped by the configured maximum
+    /// Invested liquidity still belongs to suppliers, so investing it does not raise
+    /// utilization.
+    fn rates_at(&self, total_borrows: u64, available_liquidity: u64) -> Result<(u64, u64)> {
+        let utilization_rate_bps = interest::calculate_utilization_rate(
+            total_borrows,
+            total_borrows
+                .checked_add(available_liquidity)
+                .and_then(|total| total.checked_add(self.state.invested_liquidity))
+                .ok_or(LendingError::MathOverflow)?,
+        )?;
+
+        let mut borrow_rate_bps = self
+            .config
+            .interest_rate_strategy
+            .borrow_rate_bps(utilization_rate_bps, &self.config)?;
+        if self.config.max_borrow_rate_bps > 0 {
+            borrow_rate_bps = borrow_rate_bps.min(self.config.max_borrow_rate_bps);
+        }
+
+        Ok((utilization_rate_bps, borrow_rate_bps))
+    }
+
+    /// Current utilization in bps
+    pub fn utilization_rate_bps(&self) -> Result<u64> {
+        Ok(self
+            .rates_at(self.state.total_borrows, self.state.available_liquidity)?
+            .0)
+    }
+
+    /// Borrow rate in bps after an additional borrow of `amount`
+    pub fn projected_borrow_rate_bps(&self, amount: u64) -> Result<u64> {
+        Ok(self.projected_rates_after_borrow(amount)?.1)
+    }
+
+    /// Utilization and borrow rate in bps after an additional borrow of `amount`
+    pub fn projected_rates_after_borrow(&self, amount: u64) -> Result<(u64, u64)> {
+        let total_borrows = self
+            .state
+            .total_borrows
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        let available_liquidity = self
+            .state
+            .available_liquidity
+            .checked_sub(amount)
+            .ok_or(LendingError::InsufficientLiquidity)?;
+
+        self.rates_at(total_borrows, available_liquidity)
+    }
+
+    /// Utilization and borrow rate in bps after `amount` of liquidity is redeemed
+    pub fn projected_rates_after_redeem(&self, amount: u64) -> Result<(u64, u64)> {
+        let available_liquidity = self
+            .state
+            .available_liquidity
+            .checked_sub(amount)
+            .ok_or(LendingError::InsufficientLiquidity)?;
+
+        self.rates_at(self.state.total_borrows, available_liquidity)
+    }
+
+    /// Current borrow rate in bps
+    pub fn current_borrow_rate_bps(&self) -> Result<u64> {
+        Ok(self
+            .rates_at(self.state.total_borrows, self.state.available_liquidity)?
+            .1)
+    }
+
+    /// Current supply rate in bps: the borrow rate on utilized liquidity, net of the
+    /// protocol fee (as accrued by `accrue_interest`)
+    pub fn current_supply_rate_bps(&self) -> Result<u64> {
+        let (utilization_rate_bps, borrow_rate_bps) =
+            self.rates_at(self.state.total_borrows, self.state.available_liquidity)?;
+        let fee_complement_bps =
+            BASIS_POINTS_PRECISION.saturating_sub(self.config.protocol_fee_bps);
+
+        let supply_rate_bps = (borrow_rate_bps as u128)
+            .checked_mul(utilization_rate_bps as u128)
+            .and_then(|rate| rate.checked_mul(fee_complement_bps as u128))
+            .ok_or(LendingError::MathOverflow)?
+            / (BASIS_POINTS_PRECISION as u128 * BASIS_POINTS_PRECISION as u128);
+        Ok(supply_rate_bps as u64)
+    }
+
+    /// Annual yield in bps of an annual rate compounded the way interest accrues
+    pub fn annual_yield_bps(rate_bps: u64) -> Result<u64> {
+        let growth = growth_factor(bps_to_decimal(rate_bps)?, Decimal::one())?;
+        let yield_bps = growth
+            .try_sub(Decimal::one())?
+            .value
+            .checked_mul(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::MathOverflow)?
+            / PRECISION as u128;
+        u64::try_from(yield_bps).map_err(|_| LendingError::MathOverflow.into())
+    }
+
     /// Calculate the exchange rate between collateral and liquidity
     pub fn collateral_exchange_rate(&self) -> Result<Decimal> {
         if self.state.collateral_mint_supply == 0 {
             return Ok(Decimal::one());
         }
 
-        let total_liquidity = Decimal::from_integer(self.state.total_liquidity);
-        let collateral_supply = Decimal::from_integer(self.state.collateral_mint_supply);
-
-        total_liquidity?.try_div(collateral_supply?)
+        Ok(Decimal::from_scaled_val(
+            (self.state.total_liquidity as u128)
+                .checked_mul(PRECISION as u128)
+                .ok_or(LendingError::MathOverflow)?
+                .checked_div(self.state.collateral_mint_supply as u128)
+                .ok_or(LendingError::DivisionByZero)?,
+        ))
     }
 
     /// Calculate collateral tokens to mint for a liquidity deposit
     pub fn liquidity_to_collateral(&self, liquidity_amount: u64) -> Result<u64> {
-        if self.state.collateral_mint_supply == 0 {
+        if self.state.collateral_mint_supply == 0 || self.state.total_liquidity == 0 {
             return Ok(liquidity_amount); // 1:1 for first deposit
         }
 
-        let exchange_rate = self.collateral_exchange_rate()?;
-        let liquidity_decimal = Decimal::from_integer(liquidity_amount);
-
-        liquidity_decimal?.try_div(exchange_rate)?.try_floor_u64()
+        // liquidity * collateral_supply / total_liquidity, rounded down in favour of the reserve
+        u64::try_from(
+            (liquidity_amount as u128)
+                .checked_mul(self.state.collateral_mint_supply as u128)
+                .ok_or(LendingError::MathOverflow)?
+                .checked_div(self.state.total_liquidity as u128)
+                .ok_or(LendingError::DivisionByZero)?,
+        )
+        .map_err(|_| LendingError::MathOverflow.into())
     }
 
     /// Calculate liquidity tokens to withdraw for collateral redemption
     pub fn collateral_to_liquidity(&self, collateral_amount: u64) -> Result<u64> {
-        let exchange_rate = self.collateral_exchange_rate()?;
-        let collateral_decimal = Decimal::from_integer(collateral_amount);
+        if self.state.collateral_mint_supply == 0 {
+            return Ok(collateral_amount);
+        }
+
+        // collateral * total_liquidity / collateral_supply, rounded down in favour of the reserve
+        u64::try_from(
+            (collateral_amount as u128)
+                .checked_mul(self.state.total_liquidity as u128)
+                .ok_or(LendingError::MathOverflow)?
+                .checked_div(self.state.collateral_mint_supply as u128)
+                .ok_or(LendingError::DivisionByZero)?,
+        )
+        .map_err(|_| LendingError::MathOverflow.into())
+    }
 
-        collateral_decimal?.try_mul(exchange_rate)?.try_floor_u64()
+    /// USD value of `collateral_amount` collateral tokens at the current exchange rate
+    pub fn collateral_value_usd(
+        &self,
+        collateral_amount: u64,
+        price: &OraclePrice,
+    ) -> Result<Decimal> {
+        OracleManager::calculate_usd_value(
+            self.collateral_to_liquidity(collateral_amount)?,
+            price,
+            self.config.decimals,
+        )
     }
 
     /// Check if the reserve needs to be refreshed
@@ -266,161 +520,1142 @@ impl Reserve {
         Ok(())
     }
 
-    /// Add a borrow to the reserve
-    pub fn add_borrow(&mut self, amount: u64) -> Result<()> {
-        if self.state.available_liquidity < amount {
-            return Err(LendingError::InsufficientLiquidity.into());
+    /// Check that supplying `amount` more liquidity keeps the reserve within its supply cap
+    pub fn validate_supply_cap(&self, amount: u64) -> Result<()> {
+        let total_liquidity = self
+            .state
+            .total_liquidity
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+
+        if self.config.supply_cap != 0 && total_liquidity > self.config.supply_cap {
+            return Err(LendingError::SupplyCapExceeded.into());
         }
+        Ok(())
+    }
 
-        self.state.available_liquidity = self
-            .state
-            .available_liquidity
-            .checked_sub(amount)
-            .ok_or(LendingError::MathUnderflow)?;
+    /// Liquidity that can still be supplied before the supply cap is reached
+    pub fn supply_cap_headroom(&self) -> u64 {
+        if self.config.supply_cap == 0 {
+            return u64::MAX;
+        }
+        self.config
+            .supply_cap
+            .saturating_sub(self.state.total_liquidity)
+    }
 
-        self.state.total_borrows = self
+    /// Check that borrowing `amount` more keeps the reserve within its borrow cap
+    pub fn validate_borrow_cap(&self, amount: u64) -> Result<()> {
+        let total_borrows = self
             .state
             .total_borrows
             .checked_add(amount)
             .ok_or(LendingError::MathOverflow)?;
 
+        if self.config.borrow_cap != 0 && total_borrows > self.config.borrow_cap {
+            return Err(LendingError::BorrowCapExceeded.into());
+        }
         Ok(())
     }
 
-    /// Repay a borrow to the reserve
-    pub fn repay_borrow(&mut self, amount: u64) -> Result<()> {
-        let actual_repay = std::cmp::min(amount, self.state.total_borrows);
-
-        self.state.available_liquidity = self
-            .state
-            .available_liquidity
-            .checked_add(actual_repay)
+    /// Record `debt_usd` borrowed by an isolated obligation against this reserve's
+    /// collateral, failing if it would exceed the debt ceiling
+    pub fn add_isolation_mode_debt(&mut self, debt_usd: u64) -> Result<()> {
+        let total_debt = self
+            .isolation_mode_total_debt
+            .checked_add(debt_usd)
             .ok_or(LendingError::MathOverflow)?;
 
-        self.state.total_borrows = self
-            .state
-            .total_borrows
-            .checked_sub(actual_repay)
-            .ok_or(LendingError::MathUnderflow)?;
+        if self.debt_ceiling_usd != 0 && total_debt > self.debt_ceiling_usd {
+            return Err(LendingError::IsolationDebtCeilingExceeded.into());
+        }
+        self.isolation_mode_total_debt = total_debt;
+        Ok(())
+    }
+
+    /// Release `debt_usd` repaid by an isolated obligation
+    /// Debt is valued at repayment, so the total saturates at zero rather than failing
+    /// when prices moved since the borrow.
+    pub fn remove_isolation_mode_debt(&mut self, debt_usd: u64) {
+        self.isolation_mode_total_debt = self.isolation_mode_total_debt.saturating_sub(debt_usd);
+    }
 
+    /// Whether nothing is supplied, borrowed, minted or owed to the protocol
+    pub fn is_empty(&self) -> bool {
+        self.state.available_liquidity == 0
+            && self.state.total_liquidity == 0
+            && self.state.total_borrows == 0
+            && self.state.collateral_mint_supply == 0
+            && self.state.accumulated_protocol_fees == 0
+            && self.state.invested_liquidity == 0
+    }
+
+    /// Whether prices are cross-checked against a secondary oracle
+    pub fn has_secondary_oracle(&self) -> bool {
+        self.secondary_oracle != Pubkey::default()
+    }
+
+    /// Configure or clear (default pubkey) the secondary oracle
+    /// The secondary must read a live feed, so fixed prices are not accepted.
+    pub fn set_secondary_oracle(
+        &mut self,
+        params: &UpdateReserveSecondaryOracleParams,
+    ) -> Result<()> {
+        if params.secondary_oracle != Pubkey::default()
+            && (params.secondary_oracle == self.price_oracle
+                || params.secondary_oracle_source == OracleSource::FixedPrice
+                || params.secondary_oracle_source == OracleSource::Derived
+                || params.max_oracle_deviation_bps == 0
+                || params.max_oracle_deviation_bps > BASIS_POINTS_PRECISION)
+        {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+
+        self.secondary_oracle = params.secondary_oracle;
+        self.secondary_oracle_source = params.secondary_oracle_source;
+        self.max_oracle_deviation_bps = params.max_oracle_deviation_bps;
         Ok(())
     }
 
-    /// Atomic lock operation to prevent reentrancy - checks and sets in single operation
-    pub fn try_lock(&mut self) -> Result<()> {
-        // Atomic check-and-set operation
-        match self.reentrancy_guard {
-            false => {
-                self.reentrancy_guard = true;
-                Ok(())
+    /// Configure or clear (`DerivedOracleKind::None`) the derived price
+    /// Components must read live feeds. A reserve priced by `Derived` cannot clear it, and
+    /// any change discards the cached price until the next `refresh_derived_price`.
+    pub fn set_derived_oracle(&mut self, params: &UpdateReserveDerivedOracleParams) -> Result<()> {
+        let pool_set = params.pool != Pubkey::default();
+        let valid = match params.kind {
+            DerivedOracleKind::None => self.oracle_source != OracleSource::Derived,
+            DerivedOracleKind::StakePool => pool_set && params.base.is_valid_feed(),
+            DerivedOracleKind::LiquidityPool => {
+                pool_set
+                    && params.base.is_valid_feed()
+                    && params.quote.is_valid_feed()
+                    && params.base.vault != Pubkey::default()
+                    && params.quote.vault != Pubkey::default()
+                    && params.base.vault != params.quote.vault
+                    && params.base.decimals <= MAX_DERIVED_TOKEN_DECIMALS
+                    && params.quote.decimals <= MAX_DERIVED_TOKEN_DECIMALS
             }
-            true => Err(LendingError::OperationInProgress.into()),
+        };
+        if !valid {
+            return Err(LendingError::InvalidConfiguration.into());
         }
+
+        self.derived_oracle = DerivedOracle {
+            kind: params.kind,
+            pool: params.pool,
+            base: params.base,
+            quote: params.quote,
+            ..DerivedOracle::default()
+        };
+        Ok(())
     }
 
-    /// Unlock the reserve after operation completion with validation
-    pub fn unlock(&mut self) -> Result<()> {
-        if !self.reentrancy_guard {
-            return Err(LendingError::InvalidUnlockOperation.into());
+    /// Fold a fresh primary price into the TWAP
+    /// Each update moves the average towards `price` by `elapsed / (elapsed + window)`,
+    /// so repeated refreshes within one second cannot drag it. The average restarts
+    /// from the spot price when smoothing is disabled or the feed's exponent changes.
+    pub fn update_twap(&mut self, price: &OraclePrice, timestamp: u64) -> Result<()> {
+        let elapsed = timestamp.saturating_sub(self.twap_last_update_timestamp);
+
+        if self.twap_window_seconds == 0
+            || self.twap_price <= 0
+            || self.twap_exponent != price.exponent
+        {
+            self.twap_price = price.price;
+            self.twap_exponent = price.exponent;
+        } else if elapsed > 0 {
+            let delta = (price.price as i128 - self.twap_price as i128)
+                .checked_mul(elapsed as i128)
+                .ok_or(LendingError::MathOverflow)?
+                / (elapsed as i128 + self.twap_window_seconds as i128);
+            // The new average lies between the old one and the spot price
+            self.twap_price = (self.twap_price as i128 + delta) as i64;
         }
-        self.reentrancy_guard = false;
+
+        self.twap_last_update_timestamp = timestamp;
         Ok(())
     }
 
-    /// Check if reserve is currently locked
-    pub fn is_locked(&self) -> bool {
-        self.reentrancy_guard
+    /// Check that an executed timelock proposal authorizes switching this reserve to
+    /// `strategy`
+    /// The proposal must be an `UpdateReserveConfig` operation of the market's
+    /// controller whose instruction data is the serialized strategy and whose targets
+    /// include the reserve.
+    pub fn validate_strategy_proposal(
+        &self,
+        reserve: &Pubkey,
+        proposal: &TimelockProposal,
+        timelock_controller: &Pubkey,
+        strategy: InterestRateStrategy,
+    ) -> Result<()> {
+        Self::validate_config_proposal(reserve, proposal, timelock_controller, &strategy)
     }
 
-    /// Force unlock (emergency only - requires admin authority)
-    pub fn force_unlock(&mut self) {
-        self.reentrancy_guard = false;
+    /// Check that an executed `UpdateReserveConfig` proposal targeting `reserve` carries
+    /// `payload` as its instruction data
+    pub fn validate_config_proposal<T: AnchorSerialize>(
+        reserve: &Pubkey,
+        proposal: &TimelockProposal,
+        timelock_controller: &Pubkey,
+        payload: &T,
+    ) -> Result<()> {
+        if proposal.status != ProposalStatus::Executed {
+            return Err(LendingError::ProposalNotExecuted.into());
+        }
+        if proposal.operation_type != TimelockOperationType::UpdateReserveConfig {
+            return Err(LendingError::InvalidOperationType.into());
+        }
+        if proposal.controller != *timelock_controller
+            || proposal.instruction_data != payload.try_to_vec()?
+            || !proposal.target_accounts.contains(reserve)
+        {
+            return Err(LendingError::TimelockPayloadMismatch.into());
+        }
+        Ok(())
     }
-}
 
-/// Configuration parameters for a reserve
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
-pub struct ReserveConfig {
-    /// Loan-to-value ratio in basis points (max borrowable amount / collateral value)
-    pub loan_to_value_ratio_bps: u64,
+    /// Disable `operations` right away (emergency guardian)
+    pub fn emergency_pause(&mut self, operations: ReserveConfigFlags) -> Result<()> {
+        if operations.is_empty() || !ReserveConfigFlags::EMERGENCY_PAUSABLE.contains(operations) {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+        self.config.flags.insert(operations);
+        self.emergency_pause_flags.insert(operations);
+        Ok(())
+    }
 
-    /// Liquidation threshold in basis points (liquidation trigger / collateral value)
-    pub liquidation_threshold_bps: u64,
+    /// Re-enable operations disabled by `emergency_pause` (timelocked)
+    pub fn emergency_unpause(&mut self, operations: ReserveConfigFlags) -> Result<()> {
+        if operations.is_empty() || !self.emergency_pause_flags.contains(operations) {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+        self.config.flags.remove(operations);
+        self.emergency_pause_flags.remove(operations);
+        Ok(())
+    }
 
-    /// Liquidation penalty in basis points (bonus for liquidators)
-    pub liquidation_penalty_bps: u64,
+    /// Check that a new configuration keeps every emergency-paused operation disabled
+    pub fn validate_emergency_pauses(&self, config: &ReserveConfig) -> Result<()> {
+        if !config.flags.contains(self.emergency_pause_flags) {
+            return Err(LendingError::ReserveEmergencyPaused.into());
+        }
+        Ok(())
+    }
 
-    /// Base borrow rate in basis points (annual)
-    pub base_borrow_rate_bps: u64,
+    /// Set the TWAP window (0 disables smoothing)
+    pub fn set_twap_window(&mut self, twap_window_seconds: u64) -> Result<()> {
+        if twap_window_seconds > MAX_TWAP_WINDOW_SECONDS {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+        self.twap_window_seconds = twap_window_seconds;
+        Ok(())
+    }
 
-    /// Borrow rate multiplier in basis points
-    pub borrow_rate_multiplier_bps: u64,
+    /// Configure the surge withdrawal fee; a threshold of 0 disables it
+    pub fn set_surge_fee(&mut self, params: &UpdateReserveSurgeFeeParams) -> Result<()> {
+        if params.utilization_threshold_bps != 0
+            && (u64::from(params.utilization_threshold_bps) >= BASIS_POINTS_PRECISION
+                || params.duration_slots == 0
+                || params.duration_slots > MAX_SURGE_DURATION_SLOTS
+                || params.withdrawal_fee_bps == 0
+                || params.withdrawal_fee_bps > MAX_SURGE_WITHDRAWAL_FEE_BPS)
+        {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
 
-    /// Jump rate multiplier in basis points (kicks in after optimal utilization)
-    pub jump_rate_multiplier_bps: u64,
+        self.surge_utilization_threshold_bps = params.utilization_threshold_bps;
+        self.surge_duration_slots = params.duration_slots;
+        self.surge_withdrawal_fee_bps = params.withdrawal_fee_bps;
+        self.high_utilization_since_slot = 0;
+        Ok(())
+    }
 
-    /// Optimal utilization rate in basis points
-    pub optimal_utilization_rate_bps: u64,
+    /// Start or clear the high-utilization period the surge fee is measured from
+    pub fn track_surge_utilization(&mut self, current_slot: u64) -> Result<()> {
+        if self.surge_utilization_threshold_bps == 0
+            || self.utilization_rate_bps()? <= u64::from(self.surge_utilization_threshold_bps)
+        {
+            self.high_utilization_since_slot = 0;
+        } else if self.high_utilization_since_slot == 0 {
+            self.high_utilization_since_slot = current_slot;
+        }
+        Ok(())
+    }
 
-    /// Protocol fee in basis points (taken from interest)
-    pub protocol_fee_bps: u64,
+    /// Whether utilization has stayed above the surge threshold for the configured slots
+    pub fn is_surge_active(&self, current_slot: u64) -> bool {
+        self.high_utilization_since_slot != 0
+            && current_slot.saturating_sub(self.high_utilization_since_slot)
+                >= self.surge_duration_slots
+    }
 
-    /// Maximum borrow rate in basis points
-    pub max_borrow_rate_bps: u64,
+    /// Surge fee withheld from redeeming `liquidity_amount` (0 unless the surge is active)
+    pub fn surge_withdrawal_fee(&self, liquidity_amount: u64, current_slot: u64) -> Result<u64> {
+        if !self.is_surge_active(current_slot) {
+            return Ok(0);
+        }
+        Ok((liquidity_amount as u128)
+            .checked_mul(self.surge_withdrawal_fee_bps as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::DivisionByZero)? as u64)
+    }
 
-    /// Asset decimals (6 for USDC, 9 for SOL, etc.)
-    pub decimals: u8,
+    /// Price a redemption of `collateral_amount` aTokens against the reserve's withdrawal limits
+    /// Every path turning collateral back into liquidity goes through here, so the
+    /// withdrawals-disabled flag, utilization cap and surge fee apply alike. Callers add
+    /// their own large withdrawal rule and call `settle_redemption` after paying out.
+    pub fn quote_redemption(
+        &self,
+        config: &ProtocolConfig,
+        collateral_amount: u64,
+        current_slot: u64,
+    ) -> Result<Redemption> {
+        if self
+            .config
+            .flags
+            .contains(ReserveConfigFlags::WITHDRAWALS_DISABLED)
+        {
+            return Err(LendingError::FeatureDisabled.into());
+        }
 
-    /// Reserve flags
-    pub flags: ReserveConfigFlags,
-}
+        let liquidity_amount = self.collateral_to_liquidity(collateral_amount)?;
+        if liquidity_amount == 0 {
+            return Err(LendingError::AmountTooSmall.into());
+        }
 
-/// Current state of a reserve
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
-pub struct ReserveState {
-    /// Total liquidity available for borrowing
-    pub available_liquidity: u64,
+        if self.state.available_liquidity < liquidity_amount {
+            return Err(LendingError::InsufficientLiquidity.into());
+        }
 
-    /// Total amount borrowed from this reserve
-    pub total_borrows: u64,
+        // A single exit may not push borrowers into the jump segment of the rate curve
+        if config.exceeds_withdraw_utilization(
+            liquidity_amount,
+            self.state.total_borrows,
+            self.state.available_liquidity,
+        )? {
+            return Err(LendingError::WithdrawUtilizationTooHigh.into());
+        }
 
-    /// Total liquidity in the reserve (available + borrowed)
-    pub total_liquidity: u64,
+        // While utilization has stayed high, part of the exit stays with remaining suppliers
+        let surge_fee = self.surge_withdrawal_fee(liquidity_amount, current_slot)?;
+        let withdrawn_amount = liquidity_amount
+            .checked_sub(surge_fee)
+            .ok_or(LendingError::MathUnderflow)?;
 
-    /// Total supply of collateral tokens (aTokens)
-    pub collateral_mint_supply: u64,
+        Ok(Redemption {
+            collateral_amount,
+            liquidity_amount,
+            surge_fee,
+            withdrawn_amount,
+        })
+    }
 
-    /// Current borrow interest rate (annual)
-    pub current_borrow_rate: Decimal,
+    /// Record a paid-out redemption; the surge fee stays in the reserve, raising the exchange rate
+    pub fn settle_redemption(&mut self, redemption: &Redemption) -> Result<()> {
+        self.remove_liquidity(redemption.withdrawn_amount)?;
+        self.total_surge_fees = self
+            .total_surge_fees
+            .checked_add(redemption.surge_fee)
+            .ok_or(LendingError::MathOverflow)?;
+        self.state.collateral_mint_supply = self
+            .state
+            .collateral_mint_supply
+            .checked_sub(redemption.collateral_amount)
+            .ok_or(LendingError::MathUnderflow)?;
+        Ok(())
+    }
 
-    /// Current supply interest rate (annual)
-    pub current_supply_rate: Decimal,
+    /// Price at which liquidations value this reserve's collateral
+    /// The higher of spot and TWAP, so a flash crash does not make collateral look
+    /// cheaper than it has been trading.
+    pub fn liquidation_collateral_price(&self, spot: &OraclePrice) -> OraclePrice {
+        match self.smoothed_price(spot) {
+            Some(twap) if twap > spot.price => OraclePrice {
+                price: twap,
+                ..*spot
+            },
+            _ => *spot,
+        }
+    }
 
-    /// Current utilization rate
-    pub current_utilization_rate: Decimal,
+    /// Price at which liquidations value debt borrowed from this reserve
+    /// The lower of spot and TWAP, so a brief spike does not inflate the debt.
+    pub fn liquidation_debt_price(&self, spot: &OraclePrice) -> OraclePrice {
+        match self.smoothed_price(spot) {
+            Some(twap) if twap < spot.price => OraclePrice {
+                price: twap,
+                ..*spot
+            },
+            _ => *spot,
+        }
+    }
 
-    /// Protocol fees accumulated but not yet collected
-    pub accumulated_protocol_fees: u64,
-}
+    /// Price recorded by the last refresh, for valuing the reserve without an oracle
+    /// This is the TWAP, which tracks the spot price when smoothing is disabled.
+    pub fn last_refreshed_price(&self) -> Option<OraclePrice> {
+        (self.twap_price > 0).then_some(OraclePrice {
+            price: self.twap_price,
+            confidence: 0,
+            exponent: self.twap_exponent,
+            publish_time: self.twap_last_update_timestamp as i64,
+        })
+    }
 
-/// Reserve configuration flags
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
-pub struct ReserveConfigFlags {
-    bits: u32,
-}
+    /// USD value of `amount` at the price of the last refresh, or zero if never priced
+    pub fn last_refreshed_value_usd(&self, amount: u64) -> Result<Decimal> {
+        match self.last_refreshed_price() {
+            Some(price) => OracleManager::calculate_usd_value(amount, &price, self.config.decimals),
+            None => Ok(Decimal::zero()),
+        }
+    }
 
-impl ReserveConfigFlags {
-    /// Deposits are disabled
-    pub const DEPOSITS_DISABLED: Self = Self { bits: 1 << 0 };
+    /// TWAP comparable with `spot`, if smoothing is enabled
+    fn smoothed_price(&self, spot: &OraclePrice) -> Option<i64> {
+        (self.twap_window_seconds > 0 && self.twap_price > 0 && self.twap_exponent == spot.exponent)
+            .then_some(self.twap_price)
+    }
 
-    /// Withdrawals are disabled
-    pub const WITHDRAWALS_DISABLED: Self = Self { bits: 1 << 1 };
+    /// Check if the reserve is being wound down
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecation_deadline_timestamp > 0
+    }
 
-    /// Borrowing is disabled
-    pub const BORROWING_DISABLED: Self = Self { bits: 1 << 2 };
+    /// Start winding the reserve down at `now`, or cancel a wind-down with a deadline of 0
+    /// The deadline must leave at least `MIN_DEPRECATION_PERIOD_SECONDS` for users to
+    /// unwind, and the deleverage penalty may not exceed the liquidation penalty.
+    pub fn set_deprecation(
+        &mut self,
+        now: u64,
+        deadline_timestamp: u64,
+        deleverage_penalty_bps: u16,
+    ) -> Result<()> {
+        if deadline_timestamp == 0 {
+            self.deprecation_start_timestamp = 0;
+            self.deprecation_deadline_timestamp = 0;
+            self.deleverage_penalty_bps = 0;
+            return Ok(());
+        }
 
-    /// Repayments are disabled
-    pub const REPAYMENTS_DISABLED: Self = Self { bits: 1 << 3 };
+        // Restarting a wind-down would raise the LTV back to its configured value
+        if self.is_deprecated() {
+            return Err(LendingError::InvalidReserveState.into());
+        }
+
+        let earliest_deadline = now
+            .checked_add(MIN_DEPRECATION_PERIOD_SECONDS)
+            .ok_or(LendingError::MathOverflow)?;
+        if deadline_timestamp < earliest_deadline
+            || deleverage_penalty_bps as u64 > self.config.liquidation_penalty_bps
+        {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+
+        self.deprecation_start_timestamp = now;
+        self.deprecation_deadline_timestamp = deadline_timestamp;
+        self.deleverage_penalty_bps = deleverage_penalty_bps;
+        Ok(())
+    }
+
+    /// LTV applied to this reserve's collateral at `timestamp`
+    /// A deprecated reserve's LTV walks linearly from its configured value at the start
+    /// of the wind-down to zero at the deadline.
+    pub fn effective_loan_to_value_ratio_bps(&self, timestamp: u64) -> u64 {
+        if !self.is_deprecated() {
+            return self.config.loan_to_value_ratio_bps;
+        }
+        if timestamp >= self.deprecation_deadline_timestamp {
+            return 0;
+        }
+
+        let remaining =
+            self.deprecation_deadline_timestamp - timestamp.max(self.deprecation_start_timestamp);
+        let period = self
+            .deprecation_deadline_timestamp
+            .saturating_sub(self.deprecation_start_timestamp)
+            .max(1);
+        ((self.config.loan_to_value_ratio_bps as u128 * remaining as u128) / period as u128) as u64
+    }
+
+    /// Close factor for liquidations repaying this reserve's debt, in basis points
+    /// The reserve's `liquidation_close_factor_bps` overrides the protocol-wide value.
+    pub fn liquidation_close_factor_bps(&self, protocol_close_factor_bps: u64) -> u64 {
+        match self.config.liquidation_close_factor_bps {
+            0 => protocol_close_factor_bps,
+            close_factor_bps => close_factor_bps,
+        }
+    }
+
+    /// Check if keepers may deleverage positions in this reserve at `timestamp`
+    pub fn is_past_deprecation_deadline(&self, timestamp: u64) -> bool {
+        self.is_deprecated() && timestamp >= self.deprecation_deadline_timestamp
+    }
+
+    /// Add a borrow to the reserve
+    pub fn add_borrow(&mut self, amount: u64) -> Result<()> {
+        if self.state.available_liquidity < amount {
+            return Err(LendingError::InsufficientLiquidity.into());
+        }
+
+        self.state.available_liquidity = self
+            .state
+            .available_liquidity
+            .checked_sub(amount)
+            .ok_or(LendingError::MathUnderflow)?;
+
+        self.state.total_borrows = self
+            .state
+            .total_borrows
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Repay a borrow to the reserve
+    pub fn repay_borrow(&mut self, amount: u64) -> Result<()> {
+        let actual_repay = std::cmp::min(amount, self.state.total_borrows);
+
+        self.state.available_liquidity = self
+            .state
+            .available_liquidity
+            .checked_add(actual_repay)
+            .ok_or(LendingError::MathOverflow)?;
+
+        self.state.total_borrows = self
+            .state
+            .total_borrows
+            .checked_sub(actual_repay)
+            .ok_or(LendingError::MathUnderflow)?;
+
+        Ok(())
+    }
+
+    /// Write off debt that will never be repaid
+    /// The loss comes out of total liquidity, lowering the collateral exchange rate so
+    /// suppliers share it pro rata. Returns the amount written off, capped by the
+    /// reserve's total borrows.
+    pub fn socialize_bad_debt(&mut self, amount: u64) -> Result<u64> {
+        let amount = std::cmp::min(amount, self.state.total_borrows);
+
+        self.state.total_borrows = self
+            .state
+            .total_borrows
+            .checked_sub(amount)
+            .ok_or(LendingError::MathUnderflow)?;
+        self.state.total_liquidity = self
+            .state
+            .total_liquidity
+            .checked_sub(amount)
+            .ok_or(LendingError::MathUnderflow)?;
+
+        Ok(amount)
+    }
+
+    /// Take accumulated protocol fees out of the reserve's available liquidity
+    /// Fees are not part of `total_liquidity`, so suppliers' exchange rate is unaffected.
+    /// Returns the amount collected, capped by the liquidity currently available.
+    pub fn take_protocol_fees(&mut self) -> Result<u64> {
+        let amount = std::cmp::min(
+            self.state.accumulated_protocol_fees,
+            self.state.available_liquidity,
+        );
+
+        self.state.accumulated_protocol_fees = self
+            .state
+            .accumulated_protocol_fees
+            .checked_sub(amount)
+            .ok_or(LendingError::MathUnderflow)?;
+        self.state.available_liquidity = self
+            .state
+            .available_liquidity
+            .checked_sub(amount)
+            .ok_or(LendingError::MathUnderflow)?;
+
+        Ok(amount)
+    }
+
+    /// Fee charged on a flash loan of `amount`, rounded up so no loan is free
+    pub fn flash_loan_fee(amount: u64) -> Result<u64> {
+        let fee = (amount as u128)
+            .checked_mul(FLASH_LOAN_FEE_BPS as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_add(BASIS_POINTS_PRECISION as u128 - 1)
+            .ok_or(LendingError::MathOverflow)?
+            / BASIS_POINTS_PRECISION as u128;
+        u64::try_from(fee).map_err(|_| LendingError::MathOverflow.into())
+    }
+
+    /// Account for a repaid flash loan fee
+    /// The protocol share accrues to `accumulated_protocol_fees`; the remainder grows
+    /// total liquidity, raising the collateral exchange rate for suppliers.
+    /// Returns the protocol share.
+    pub fn collect_flash_loan_fee(&mut self, fee: u64) -> Result<u64> {
+        let protocol_fee = (fee as u128)
+            .checked_mul(self.config.protocol_fee_bps as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::DivisionByZero)? as u64;
+        let supplier_fee = fee
+            .checked_sub(protocol_fee)
+            .ok_or(LendingError::MathUnderflow)?;
+
+        self.state.available_liquidity = self
+            .state
+            .available_liquidity
+            .checked_add(fee)
+            .ok_or(LendingError::MathOverflow)?;
+        self.state.total_liquidity = self
+            .state
+            .total_liquidity
+            .checked_add(supplier_fee)
+            .ok_or(LendingError::MathOverflow)?;
+        self.state.accumulated_protocol_fees = self
+            .state
+            .accumulated_protocol_fees
+            .checked_add(protocol_fee)
+            .ok_or(LendingError::MathOverflow)?;
+
+        Ok(protocol_fee)
+    }
+
+    /// Liquidity that may be invested in yield venues
+    /// Accumulated protocol fees are paid out of available liquidity, so they stay idle.
+    pub fn investable_liquidity(&self) -> u64 {
+        self.state
+            .available_liquidity
+            .saturating_sub(self.state.accumulated_protocol_fees)
+    }
+
+    /// Move `amount` of available liquidity into a yield venue
+    pub fn invest_liquidity(&mut self, amount: u64) -> Result<()> {
+        if amount > self.investable_liquidity() {
+            return Err(LendingError::InsufficientLiquidity.into());
+        }
+
+        self.state.available_liquidity -= amount;
+        self.state.invested_liquidity = self
+            .state
+            .invested_liquidity
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Account for `returned` liquidity received for divesting `cost_basis`
+    /// A gain is split like interest: the protocol share accrues to
+    /// `accumulated_protocol_fees` and the rest raises the collateral exchange rate. A loss
+    /// lowers total liquidity and is borne by suppliers. Returns the protocol share.
+    pub fn divest_liquidity(&mut self, cost_basis: u64, returned: u64) -> Result<u64> {
+        self.state.invested_liquidity = self
+            .state
+            .invested_liquidity
+            .checked_sub(cost_basis)
+            .ok_or(LendingError::MathUnderflow)?;
+        self.state.available_liquidity = self
+            .state
+            .available_liquidity
+            .checked_add(returned)
+            .ok_or(LendingError::MathOverflow)?;
+
+        if returned < cost_basis {
+            self.state.total_liquidity = self
+                .state
+                .total_liquidity
+                .checked_sub(cost_basis - returned)
+                .ok_or(LendingError::MathUnderflow)?;
+            return Ok(0);
+        }
+
+        let gain = returned - cost_basis;
+        let protocol_fee = (gain as u128)
+            .checked_mul(self.config.protocol_fee_bps as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::DivisionByZero)? as u64;
+        self.state.total_liquidity = self
+            .state
+            .total_liquidity
+            .checked_add(gain - protocol_fee)
+            .ok_or(LendingError::MathOverflow)?;
+        self.state.accumulated_protocol_fees = self
+            .state
+            .accumulated_protocol_fees
+            .checked_add(protocol_fee)
+            .ok_or(LendingError::MathOverflow)?;
+
+        Ok(protocol_fee)
+    }
+
+    /// Origination fee on a borrow of `amount`, rounded up so a configured fee is never zero
+    pub fn borrow_fee(&self, amount: u64) -> Result<u64> {
+        if self.config.borrow_fee_bps == 0 {
+            return Ok(0);
+        }
+
+        let fee = (amount as u128)
+            .checked_mul(self.config.borrow_fee_bps as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_add(BASIS_POINTS_PRECISION as u128 - 1)
+            .ok_or(LendingError::MathOverflow)?
+            / BASIS_POINTS_PRECISION as u128;
+        u64::try_from(fee).map_err(|_| LendingError::MathOverflow.into())
+    }
+
+    /// Split protocol fee revenue into (treasury, insurance fund) shares
+    /// Rounding dust stays with the treasury.
+    pub fn split_insurance_fee(&self, fee: u64) -> Result<(u64, u64)> {
+        let insurance_fee = (fee as u128)
+            .checked_mul(self.config.insurance_fund_share_bps as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::DivisionByZero)? as u64;
+        let treasury_fee = fee
+            .checked_sub(insurance_fee)
+            .ok_or(LendingError::MathUnderflow)?;
+        Ok((treasury_fee, insurance_fee))
+    }
+
+    /// Protocol's share of the bonus in a liquidation seizing `collateral_amount` of this
+    /// reserve's collateral at `liquidation_bonus_bps`, in collateral tokens
+    /// The seized amount includes the bonus, so the bonus is the
+    /// `bonus / (1 + bonus)` slice of it. Rounds down in favour of the liquidator.
+    pub fn liquidation_protocol_fee(
+        &self,
+        collateral_amount: u64,
+        liquidation_bonus_bps: u64,
+    ) -> Result<u64> {
+        let fee = (collateral_amount as u128)
+            .checked_mul(liquidation_bonus_bps as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_mul(self.config.liquidation_protocol_fee_bps as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(
+                (BASIS_POINTS_PRECISION as u128 + liquidation_bonus_bps as u128)
+                    * BASIS_POINTS_PRECISION as u128,
+            )
+            .ok_or(LendingError::DivisionByZero)?;
+        u64::try_from(fee).map_err(|_| LendingError::MathOverflow.into())
+    }
+
+    /// Burn `collateral_amount` of seized collateral worth `liquidity_amount` into
+    /// `accumulated_protocol_fees`, for a liquidation fee the reserve lacks the free
+    /// liquidity to pay out
+    /// The liquidity leaves supplier value at the current exchange rate and is collected
+    /// with the other protocol fees once it is available.
+    pub fn accrue_liquidation_protocol_fee(
+        &mut self,
+        collateral_amount: u64,
+        liquidity_amount: u64,
+    ) -> Result<()> {
+        self.state.collateral_mint_supply = self
+            .state
+            .collateral_mint_supply
+            .checked_sub(collateral_amount)
+            .ok_or(LendingError::MathUnderflow)?;
+        self.state.total_liquidity = self
+            .state
+            .total_liquidity
+            .checked_sub(liquidity_amount)
+            .ok_or(LendingError::MathUnderflow)?;
+        self.state.accumulated_protocol_fees = self
+            .state
+            .accumulated_protocol_fees
+            .checked_add(liquidity_amount)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Release accrued protocol fees that are paid out of the supply right away
+    /// Used for the insurance fund share of flash loan fees, which leaves the reserve
+    /// instead of waiting for fee collection.
+    pub fn release_protocol_fees(&mut self, amount: u64) -> Result<()> {
+        self.state.accumulated_protocol_fees = self
+            .state
+            .accumulated_protocol_fees
+            .checked_sub(amount)
+            .ok_or(LendingError::MathUnderflow)?;
+        self.state.available_liquidity = self
+            .state
+            .available_liquidity
+            .checked_sub(amount)
+            .ok_or(LendingError::InsufficientLiquidity)?;
+        Ok(())
+    }
+
+    /// Atomic lock operation to prevent reentrancy - checks and sets in single operation
+    pub fn try_lock(&mut self) -> Result<()> {
+        // Atomic check-and-set operation
+        if self.is_locked() {
+            return Err(LendingError::OperationInProgress.into());
+        }
+        self.state.flags.insert(ReserveStateFlags::LOCKED);
+        Ok(())
+    }
+
+    /// Unlock the reserve after operation completion with validation
+    pub fn unlock(&mut self) -> Result<()> {
+        if !self.is_locked() {
+            return Err(LendingError::InvalidUnlockOperation.into());
+        }
+        self.state.flags.remove(ReserveStateFlags::LOCKED);
+        Ok(())
+    }
+
+    /// Check if reserve is currently locked
+    pub fn is_locked(&self) -> bool {
+        self.state.flags.contains(ReserveStateFlags::LOCKED)
+    }
+
+    /// Force unlock (emergency only - requires admin authority)
+    pub fn force_unlock(&mut self) {
+        self.state.flags.remove(ReserveStateFlags::LOCKED);
+    }
+}
+
+/// Convert basis points into a Decimal fraction
+pub(crate) fn bps_to_decimal(bps: u64) -> Result<Decimal> {
+    Ok(Decimal::from_scaled_val(
+        (bps as u128)
+            .checked_mul(PRECISION as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::DivisionByZero)?,
+    ))
+}
+
+/// Convert a Decimal fraction into basis points, truncating (saturates at u64::MAX)
+pub(crate) fn decimal_to_bps(rate: Decimal) -> u64 {
+    let bps = rate.value / (PRECISION as u128 / BASIS_POINTS_PRECISION as u128);
+    bps.min(u64::MAX as u128) as u64
+}
+
+/// Growth factor of continuous compounding at an annual `rate` over `time_fraction` of a year
+fn growth_factor(rate: Decimal, time_fraction: Decimal) -> Result<Decimal> {
+    Ok(Decimal::from_scaled_val(
+        fast_math::compound_interest_taylor(PRECISION as u128, rate.value, time_fraction.value, 8)?,
+    ))
+}
+
+/// Grow `amount` by a compounding `growth_factor`
+fn compound(amount: u64, growth_factor: Decimal) -> Result<u64> {
+    u64::try_from(
+        (amount as u128)
+            .checked_mul(growth_factor.value)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(PRECISION as u128)
+            .ok_or(LendingError::DivisionByZero)?,
+    )
+    .map_err(|_| LendingError::MathOverflow.into())
+}
+
+/// Configuration parameters for a reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ReserveConfig {
+    /// Loan-to-value ratio in basis points (max borrowable amount / collateral value)
+    pub loan_to_value_ratio_bps: u64,
+
+    /// Liquidation threshold in basis points (liquidation trigger / collateral value)
+    pub liquidation_threshold_bps: u64,
+
+    /// Liquidation penalty in basis points (bonus for liquidators)
+    pub liquidation_penalty_bps: u64,
+
+    /// Base borrow rate in basis points (annual)
+    pub base_borrow_rate_bps: u64,
+
+    /// Borrow rate multiplier in basis points
+    pub borrow_rate_multiplier_bps: u64,
+
+    /// Jump rate multiplier in basis points (kicks in after optimal utilization)
+    pub jump_rate_multiplier_bps: u64,
+
+    /// Optimal utilization rate in basis points
+    pub optimal_utilization_rate_bps: u64,
+
+    /// Protocol fee in basis points (taken from interest)
+    pub protocol_fee_bps: u64,
+
+    /// Maximum borrow rate in basis points
+    pub max_borrow_rate_bps: u64,
+
+    /// Asset decimals (6 for USDC, 9 for SOL, etc.)
+    pub decimals: u8,
+
+    /// Reserve flags
+    pub flags: ReserveConfigFlags,
+
+    /// Maximum total liquidity supplied to the reserve (0 = uncapped)
+    pub supply_cap: u64,
+
+    /// Maximum total liquidity borrowed from the reserve (0 = uncapped)
+    pub borrow_cap: u64,
+
+    /// Origination fee in basis points, deducted from each borrow
+    pub borrow_fee_bps: u64,
+
+    /// Share of borrow fees and flash loan protocol fees paid into the insurance fund
+    /// (basis points); the rest goes to the treasury
+    pub insurance_fund_share_bps: u64,
+    /// Curve mapping utilization to the borrow rate
+    pub interest_rate_strategy: InterestRateStrategy,
+
+    /// Widest oracle confidence interval accepted for this reserve, in basis points of
+    /// the price (0 = the protocol-wide `MAX_ORACLE_CONFIDENCE_BPS`)
+    pub max_confidence_bps: u64,
+
+    /// Oldest oracle price accepted for this reserve, in slots (0 = the protocol-wide
+    /// `MAX_ORACLE_STALENESS_SLOTS`)
+    pub max_staleness_slots: u64,
+
+    /// Share of a borrow one liquidation may repay at a health factor just below 1.0,
+    /// in basis points (0 = the protocol-wide `liquidation_close_factor_bps`)
+    pub liquidation_close_factor_bps: u64,
+
+    /// Share of the liquidation bonus on this reserve's collateral paid into its insurance
+    /// fund instead of to the liquidator (basis points)
+    pub liquidation_protocol_fee_bps: u64,
+}
+
+/// Current state of a reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ReserveState {
+    /// Total liquidity available for borrowing
+    pub available_liquidity: u64,
+
+    /// Total amount borrowed from this reserve
+    pub total_borrows: u64,
+
+    /// Total liquidity in the reserve (available + borrowed)
+    pub total_liquidity: u64,
+
+    /// Total supply of collateral tokens (aTokens)
+    pub collateral_mint_supply: u64,
+
+    /// Borrow interest rate (annual, basis points) at the last accrual
+    pub current_borrow_rate_bps: u64,
+
+    /// Supply interest rate (annual, basis points) at the last accrual
+    pub current_supply_rate_bps: u64,
+
+    /// Utilization rate (basis points) at the last accrual
+    pub current_utilization_rate_bps: u64,
+
+    /// Protocol fees accumulated but not yet collected
+    pub accumulated_protocol_fees: u64,
+
+    /// Product of all borrow interest growth since the reserve was created (starts at 1)
+    pub cumulative_borrow_rate_wads: Decimal,
+
+    /// Liquidity invested in external yield venues, at cost (part of total liquidity)
+    pub invested_liquidity: u64,
+
+    /// Runtime flags of the reserve (reentrancy lock)
+    pub flags: ReserveStateFlags,
+}
+
+impl ReserveState {
+    /// Borrow rate at the last accrual
+    pub fn borrow_rate(&self) -> Result<Decimal> {
+        bps_to_decimal(self.current_borrow_rate_bps)
+    }
+
+    /// Supply rate at the last accrual
+    pub fn supply_rate(&self) -> Result<Decimal> {
+        bps_to_decimal(self.current_supply_rate_bps)
+    }
+
+    /// Utilization rate at the last accrual
+    pub fn utilization_rate(&self) -> Result<Decimal> {
+        bps_to_decimal(self.current_utilization_rate_bps)
+    }
+}
+
+/// Reserve account of reserves before `RESERVE_VERSION`, frozen at the original layout
+/// Only read when compacting a legacy reserve into the current layout.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ReserveV1 {
+    pub version: u8,
+    pub market: Pubkey,
+    pub liquidity_mint: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub liquidity_supply: Pubkey,
+    pub fee_receiver: Pubkey,
+    pub price_oracle: Pubkey,
+    pub oracle_feed_id: [u8; 32],
+    pub config: ReserveConfigV1,
+    pub state: ReserveStateV1,
+    pub last_update_timestamp: u64,
+    pub last_update_slot: u64,
+    pub reentrancy_guard: bool,
+}
+
+impl ReserveV1 {
+    /// Reserve in the current layout carrying the legacy fields
+    /// Fields added since take the values `Reserve::new` gives a new reserve.
+    pub fn into_current(self) -> Reserve {
+        Reserve {
+            version: RESERVE_VERSION,
+            market: self.market,
+            liquidity_mint: self.liquidity_mint,
+            collateral_mint: self.collateral_mint,
+            liquidity_supply: self.liquidity_supply,
+            fee_receiver: self.fee_receiver,
+            price_oracle: self.price_oracle,
+            oracle_feed_id: self.oracle_feed_id,
+            config: self.config.into_current(),
+            state: self.state.into_packed(self.reentrancy_guard),
+            last_update_timestamp: self.last_update_timestamp,
+            last_update_slot: self.last_update_slot,
+            fee_split: FeeSplit::default(),
+            asset_class: 0,
+            oracle_source: OracleSource::Pyth,
+            fixed_price: 0,
+            secondary_oracle: Pubkey::default(),
+            secondary_oracle_source: OracleSource::Pyth,
+            max_oracle_deviation_bps: 0,
+            twap_price: 0,
+            twap_exponent: 0,
+            twap_last_update_timestamp: 0,
+            twap_window_seconds: 0,
+            deprecation_start_timestamp: 0,
+            deprecation_deadline_timestamp: 0,
+            deleverage_penalty_bps: 0,
+            debt_ceiling_usd: 0,
+            isolation_mode_total_debt: 0,
+            surge_utilization_threshold_bps: 0,
+            surge_duration_slots: 0,
+            surge_withdrawal_fee_bps: 0,
+            high_utilization_since_slot: 0,
+            total_surge_fees: 0,
+            emergency_pause_flags: ReserveConfigFlags::default(),
+            derived_oracle: DerivedOracle::default(),
+            reserved: [0; 8],
+        }
+    }
+}
+
+/// Reserve configuration of reserves before `RESERVE_VERSION`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ReserveConfigV1 {
+    pub loan_to_value_ratio_bps: u64,
+    pub liquidation_threshold_bps: u64,
+    pub liquidation_penalty_bps: u64,
+    pub base_borrow_rate_bps: u64,
+    pub borrow_rate_multiplier_bps: u64,
+    pub jump_rate_multiplier_bps: u64,
+    pub optimal_utilization_rate_bps: u64,
+    pub protocol_fee_bps: u64,
+    pub max_borrow_rate_bps: u64,
+    pub decimals: u8,
+    pub flags: ReserveConfigFlags,
+}
+
+impl ReserveConfigV1 {
+    /// Current configuration with the legacy parameters; the rest use their defaults
+    pub fn into_current(self) -> ReserveConfig {
+        ReserveConfig {
+            loan_to_value_ratio_bps: self.loan_to_value_ratio_bps,
+            liquidation_threshold_bps: self.liquidation_threshold_bps,
+            liquidation_penalty_bps: self.liquidation_penalty_bps,
+            base_borrow_rate_bps: self.base_borrow_rate_bps,
+            borrow_rate_multiplier_bps: self.borrow_rate_multiplier_bps,
+            jump_rate_multiplier_bps: self.jump_rate_multiplier_bps,
+            optimal_utilization_rate_bps: self.optimal_utilization_rate_bps,
+            protocol_fee_bps: self.protocol_fee_bps,
+            max_borrow_rate_bps: self.max_borrow_rate_bps,
+            decimals: self.decimals,
+            flags: self.flags,
+            ..ReserveConfig::default()
+        }
+    }
+}
+
+/// Reserve state of reserves before `RESERVE_VERSION`
+/// Rates were stored as Decimals and the reentrancy guard was a separate field of the
+/// reserve.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ReserveStateV1 {
+    pub available_liquidity: u64,
+    pub total_borrows: u64,
+    pub total_liquidity: u64,
+    pub collateral_mint_supply: u64,
+    pub current_borrow_rate: Decimal,
+    pub current_supply_rate: Decimal,
+    pub current_utilization_rate: Decimal,
+    pub accumulated_protocol_fees: u64,
+}
+
+impl ReserveStateV1 {
+    /// Packed state carrying the same amounts, with the rates truncated to bps
+    /// The cumulative borrow rate starts at one, as for a new reserve.
+    pub fn into_packed(self, reentrancy_guard: bool) -> ReserveState {
+        let mut flags = ReserveStateFlags::default();
+        if reentrancy_guard {
+            flags.insert(ReserveStateFlags::LOCKED);
+        }
+
+        ReserveState {
+            available_liquidity: self.available_liquidity,
+            total_borrows: self.total_borrows,
+            total_liquidity: self.total_liquidity,
+            collateral_mint_supply: self.collateral_mint_supply,
+            current_borrow_rate_bps: decimal_to_bps(self.current_borrow_rate),
+            current_supply_rate_bps: decimal_to_bps(self.current_supply_rate),
+            current_utilization_rate_bps: decimal_to_bps(self.current_utilization_rate),
+            accumulated_protocol_fees: self.accumulated_protocol_fees,
+            cumulative_borrow_rate_wads: Decimal::one(),
+            invested_liquidity: 0,
+            flags,
+        }
+    }
+}
+
+/// Runtime flags of a reserve, packed into one byte
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct ReserveStateFlags {
+    bits: u8,
+}
+
+impl ReserveStateFlags {
+    /// An operation holds the reserve's reentrancy lock
+    pub const LOCKED: Self = Self { bits: 1 << 0 };
+
+    pub fn contains(&self, flag: Self) -> bool {
+        (self.bits & flag.bits) == flag.bits
+    }
+
+    pub fn bits(&self) -> u8 {
+        self.bits
+    }
+
+    pub fn insert(&mut self, flag: Self) {
+        self.bits |= flag.bits;
+    }
+
+    pub fn remove(&mut self, flag: Self) {
+        self.bits &= !flag.bits;
+    }
+}
+
+/// Reserve configuration flags
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct ReserveConfigFlags {
+    bits: u32,
+}
+
+impl ReserveConfigFlags {
+    /// Deposits are disabled
+    pub const DEPOSITS_DISABLED: Self = Self { bits: 1 << 0 };
+
+    /// Withdrawals are disabled
+    pub const WITHDRAWALS_DISABLED: Self = Self { bits: 1 << 1 };
+
+    /// Borrowing is disabled
+    pub const BORROWING_DISABLED: Self = Self { bits: 1 << 2 };
+
+    /// Repayments are disabled
+    pub const REPAYMENTS_DISABLED: Self = Self { bits: 1 << 3 };
 
     /// Liquidations are disabled
     pub const LIQUIDATIONS_DISABLED: Self = Self { bits: 1 << 4 };
@@ -428,9 +1663,319 @@ impl ReserveConfigFlags {
     /// Reserve can be used as collateral
     pub const COLLATERAL_ENABLED: Self = Self { bits: 1 << 5 };
 
+    /// Flash loans are disabled
+    pub const FLASH_LOANS_DISABLED: Self = Self { bits: 1 << 6 };
+
+    /// Collateral is isolated: it cannot be combined with other collateral and only
+    /// backs borrows from reserves flagged `BORROWABLE_IN_ISOLATION`
+    pub const ISOLATED: Self = Self { bits: 1 << 7 };
+
+    /// Governance approved this reserve as debt for isolated collateral
+    pub const BORROWABLE_IN_ISOLATION: Self = Self { bits: 1 << 8 };
+
+    /// Collateral is liquidated through Dutch auctions instead of at the fixed bonus
+    pub const AUCTION_LIQUIDATIONS: Self = Self { bits: 1 << 9 };
+
+    /// Operations an emergency guardian may disable on a single reserve
+    pub const EMERGENCY_PAUSABLE: Self = Self {
+        bits: Self::DEPOSITS_DISABLED.bits
+            | Self::WITHDRAWALS_DISABLED.bits
+            | Self::BORROWING_DISABLED.bits
+            | Self::LIQUIDATIONS_DISABLED.bits,
+    };
+
     pub fn contains(&self, flag: Self) -> bool {
         (self.bits & flag.bits) == flag.bits
     }
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    pub fn insert(&mut self, flag: Self) {
+        self.bits |= flag.bits;
+    }
+
+    pub fn remove(&mut self, flag: Self) {
+        self.bits &= !flag.bits;
+    }
+}
+
+/// A weighted recipient of collected protocol fees
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeRecipient {
+    /// Liquidity token account receiving the share
+    pub receiver: Pubkey,
+
+    /// Share of collected fees in basis points (0 = disabled)
+    pub weight_bps: u16,
+}
+
+impl FeeRecipient {
+    /// Serialized size of a fee recipient
+    pub const SIZE: usize = 32 + // receiver
+        2; // weight_bps
+
+    /// Whether this recipient receives a share
+    pub fn is_enabled(&self) -> bool {
+        self.weight_bps > 0
+    }
+}
+
+/// Split of collected protocol fees across up to four recipients
+/// The treasury (the reserve's `fee_receiver`) receives whatever the other
+/// recipients' weights leave over, including rounding dust. A zeroed split sends
+/// everything to the treasury.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeSplit {
+    /// Insurance fund share
+    pub insurance: FeeRecipient,
+
+    /// Staker rewards share
+    pub stakers: FeeRecipient,
+
+    /// Grants program share
+    pub grants: FeeRecipient,
+}
+
+impl FeeSplit {
+    /// Serialized size of a fee split
+    pub const SIZE: usize = 3 * FeeRecipient::SIZE;
+
+    /// Non-treasury recipients in collection order (insurance, stakers, grants)
+    pub fn recipients(&self) -> [&FeeRecipient; 3] {
+        [&self.insurance, &self.stakers, &self.grants]
+    }
+
+    /// Validate weights and receivers
+    pub fn validate(&self) -> Result<()> {
+        let mut total_weight_bps: u64 = 0;
+        for recipient in self.recipients() {
+            if recipient.is_enabled() && recipient.receiver == Pubkey::default() {
+                return Err(LendingError::InvalidReserveConfig.into());
+            }
+            total_weight_bps += recipient.weight_bps as u64;
+        }
+
+        if total_weight_bps > BASIS_POINTS_PRECISION {
+            return Err(LendingError::InvalidReserveConfig.into());
+        }
+        Ok(())
+    }
+
+    /// Split `amount` into (treasury, [insurance, stakers, grants]) shares
+    pub fn allocate(&self, amount: u64) -> Result<(u64, [u64; 3])> {
+        let mut shares = [0u64; 3];
+        let mut treasury = amount;
+
+        for (share, recipient) in shares.iter_mut().zip(self.recipients()) {
+            *share = (amount as u128)
+                .checked_mul(recipient.weight_bps as u128)
+                .ok_or(LendingError::MathOverflow)?
+                .checked_div(BASIS_POINTS_PRECISION as u128)
+                .ok_or(LendingError::DivisionByZero)? as u64;
+            treasury = treasury
+                .checked_sub(*share)
+                .ok_or(LendingError::MathUnderflow)?;
+        }
+
+        Ok((treasury, shares))
+    }
+}
+
+/// Collateral redemption priced by `Reserve::quote_redemption`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Redemption {
+    /// aTokens burned
+    pub collateral_amount: u64,
+    /// Liquidity the aTokens are worth
+    pub liquidity_amount: u64,
+    /// Surge fee withheld in the reserve
+    pub surge_fee: u64,
+    /// Liquidity paid out of the reserve
+    pub withdrawn_amount: u64,
+}
+
+/// Price source of a reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OracleSource {
+    /// Pyth price update account, selected by `oracle_feed_id`
+    Pyth,
+    /// Switchboard V2 aggregator account
+    Switchboard,
+    /// Governance-set `fixed_price`, for stable assets without a feed
+    FixedPrice,
+    /// Cached `derived_oracle` price, for LSTs and LP tokens
+    Derived,
+}
+
+/// How a reserve's price is derived from other oracles
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DerivedOracleKind {
+    /// No derived price
+    None,
+    /// Liquid staking token: SOL price times the SPL stake pool's lamports per pool token
+    StakePool,
+    /// Constant-product LP token: fair value of the pool's reserves per LP token
+    LiquidityPool,
+}
+
+impl Default for DerivedOracleKind {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+/// Oracle (and, for LP tokens, pool vault) of one asset a derived price is built from
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DerivedOracleComponent {
+    /// Price oracle account of the asset
+    pub oracle: Pubkey,
+
+    /// Price source of `oracle` (Pyth or Switchboard)
+    pub source: OracleSource,
+
+    /// Pyth price feed ID (unused by Switchboard)
+    pub feed_id: [u8; 32],
+
+    /// Pool token account holding the asset (LP tokens only)
+    pub vault: Pubkey,
+
+    /// Decimals of the asset (LP tokens only)
+    pub decimals: u8,
+}
+
+impl DerivedOracleComponent {
+    /// Serialized size of a component
+    pub const SIZE: usize = 32 + 1 + 32 + 32 + 1;
+
+    /// Whether the component reads a live feed
+    fn is_valid_feed(&self) -> bool {
+        self.oracle != Pubkey::default()
+            && match self.source {
+                OracleSource::Pyth => self.feed_id != [0u8; 32],
+                OracleSource::Switchboard => true,
+                OracleSource::FixedPrice | OracleSource::Derived => false,
+            }
+    }
+}
+
+/// Price of an LST or LP token derived from its components' oracles
+/// `refresh_derived_price` recomputes and caches the price; reserves priced by
+/// `OracleSource::Derived` read the cache. The cached price carries the publish time of
+/// its oldest component, so it goes stale with them.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DerivedOracle {
+    /// How the price is derived
+    pub kind: DerivedOracleKind,
+
+    /// SPL stake pool account (StakePool) or LP token mint (LiquidityPool)
+    pub pool: Pubkey,
+
+    /// SOL (StakePool) or the pool's first token (LiquidityPool)
+    pub base: DerivedOracleComponent,
+
+    /// The pool's second token (LiquidityPool only)
+    pub quote: DerivedOracleComponent,
+
+    /// Cached USD price, in units of 10^`FIXED_PRICE_EXPONENT` (0 = not yet derived)
+    pub price: u64,
+
+    /// Confidence interval of the cached price, in the same units
+    pub confidence: u64,
+
+    /// Publish time of the oldest component price
+    pub publish_time: i64,
+}
+
+impl DerivedOracle {
+    /// Serialized size of a derived oracle
+    pub const SIZE: usize = 1 + 32 + 2 * DerivedOracleComponent::SIZE + 8 + 8 + 8;
+
+    /// Cache a freshly derived price
+    pub fn set_price(&mut self, price: &OraclePrice) -> Result<()> {
+        self.price = u64::try_from(price.price).map_err(|_| LendingError::OraclePriceInvalid)?;
+        self.confidence = price.confidence;
+        self.publish_time = price.publish_time;
+        Ok(())
+    }
+
+    /// The cached price
+    pub fn cached_price(&self) -> Result<OraclePrice> {
+        if self.kind == DerivedOracleKind::None || self.price == 0 {
+            return Err(LendingError::OraclePriceInvalid.into());
+        }
+
+        Ok(OraclePrice {
+            price: i64::try_from(self.price).map_err(|_| LendingError::OraclePriceInvalid)?,
+            confidence: self.confidence,
+            exponent: FIXED_PRICE_EXPONENT,
+            publish_time: self.publish_time,
+        })
+    }
+}
+
+impl Default for OracleSource {
+    fn default() -> Self {
+        Self::Pyth
+    }
+}
+
+/// Borrow rate curve of a reserve
+/// Every strategy reads its parameters from the reserve config and is capped by
+/// `max_borrow_rate_bps`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InterestRateStrategy {
+    /// Base rate plus a slope up to optimal utilization and a jump slope above it
+    Kinked,
+    /// Base rate plus `borrow_rate_multiplier_bps` scaled by utilization
+    Linear,
+    /// Kinked up to optimal utilization, rising quadratically above it
+    Curve,
+    /// `base_borrow_rate_bps` regardless of utilization
+    Fixed,
+}
+
+impl Default for InterestRateStrategy {
+    fn default() -> Self {
+        Self::Kinked
+    }
+}
+
+impl InterestRateStrategy {
+    /// Borrow rate in bps at `utilization_rate_bps`, before the configured cap
+    pub fn borrow_rate_bps(
+        &self,
+        utilization_rate_bps: u64,
+        config: &ReserveConfig,
+    ) -> Result<u64> {
+        match self {
+            Self::Kinked => interest::calculate_borrow_rate(
+                utilization_rate_bps,
+                config.base_borrow_rate_bps,
+                config.borrow_rate_multiplier_bps,
+                config.jump_rate_multiplier_bps,
+                config.optimal_utilization_rate_bps,
+            ),
+            Self::Linear => interest::calculate_linear_borrow_rate(
+                utilization_rate_bps,
+                config.base_borrow_rate_bps,
+                config.borrow_rate_multiplier_bps,
+            ),
+            Self::Curve => interest::calculate_curve_borrow_rate(
+                utilization_rate_bps,
+                config.base_borrow_rate_bps,
+                config.borrow_rate_multiplier_bps,
+                config.jump_rate_multiplier_bps,
+                config.optimal_utilization_rate_bps,
+            ),
+            Self::Fixed => Ok(config.base_borrow_rate_bps),
+        }
+    }
 }
 
 /// Parameters for initializing a reserve
@@ -438,8 +1983,11 @@ impl ReserveConfigFlags {
 pub struct InitializeReserveParams {
     pub liquidity_mint: Pubkey,
     pub price_oracle: Pubkey,
-    pub oracle_feed_id: [u8; 32], // Pyth or Switchboard feed ID
+    pub oracle_feed_id: [u8; 32], // Pyth feed ID
+    pub oracle_source: OracleSource,
+    pub fixed_price: u64, // Only used by FixedPrice reserves
     pub config: ReserveConfig,
+    pub initial_liquidity: Option<u64>, // Seed deposit taken from the payer
 }
 
 /// Parameters for updating reserve configuration
@@ -447,3 +1995,798 @@ pub struct InitializeReserveParams {
 pub struct UpdateReserveConfigParams {
     pub config: ReserveConfig,
 }
+
+/// Parameters for updating the supply and borrow caps of a reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateReserveCapsParams {
+    pub supply_cap: u64,
+    pub borrow_cap: u64,
+    pub debt_ceiling_usd: u64, // Only applies to isolated collateral
+}
+
+/// Parameters for configuring the secondary oracle of a reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateReserveSecondaryOracleParams {
+    pub secondary_oracle: Pubkey, // Default pubkey disables the cross-check
+    pub secondary_oracle_source: OracleSource,
+    pub max_oracle_deviation_bps: u64,
+}
+
+/// Parameters for configuring the derived price of a reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateReserveDerivedOracleParams {
+    pub kind: DerivedOracleKind, // None clears the derived price
+    pub pool: Pubkey,
+    pub base: DerivedOracleComponent,
+    pub quote: DerivedOracleComponent, // LiquidityPool only
+}
+
+/// Parameters for configuring the price TWAP of a reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateReserveTwapWindowParams {
+    pub twap_window_seconds: u64, // 0 disables smoothing
+}
+
+/// Parameters for configuring the surge withdrawal fee of a reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateReserveSurgeFeeParams {
+    pub utilization_threshold_bps: u16, // 0 disables the surge fee
+    pub duration_slots: u64,
+    pub withdrawal_fee_bps: u16,
+}
+
+/// Parameters for deprecating a reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DeprecateReserveParams {
+    pub deadline_timestamp: u64, // 0 cancels the wind-down
+    pub deleverage_penalty_bps: u16,
+}
+
+/// Parameters for updating the protocol fee split of a reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateReserveFeeSplitParams {
+    pub fee_split: FeeSplit,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::obligation::ObligationLiquidity;
+
+    fn test_reserve() -> Reserve {
+        Reserve {
+            version: RESERVE_VERSION,
+            market: Pubkey::new_unique(),
+            liquidity_mint: Pubkey::new_unique(),
+            collateral_mint: Pubkey::new_unique(),
+            liquidity_supply: Pubkey::new_unique(),
+            fee_receiver: Pubkey::new_unique(),
+            price_oracle: Pubkey::new_unique(),
+            oracle_feed_id: [0; 32],
+            config: ReserveConfig {
+                loan_to_value_ratio_bps: 7500,
+                liquidation_threshold_bps: 8000,
+                liquidation_penalty_bps: 500,
+                base_borrow_rate_bps: 200,
+                borrow_rate_multiplier_bps: 1000,
+                jump_rate_multiplier_bps: 10000,
+                optimal_utilization_rate_bps: 8000,
+                protocol_fee_bps: 1000,
+                max_borrow_rate_bps: 0,
+                decimals: 6,
+                flags: ReserveConfigFlags::default(),
+                supply_cap: 0,
+                borrow_cap: 0,
+                borrow_fee_bps: 0,
+                insurance_fund_share_bps: 0,
+                interest_rate_strategy: InterestRateStrategy::Kinked,
+                max_confidence_bps: 0,
+                max_staleness_slots: 0,
+                liquidation_close_factor_bps: 0,
+                liquidation_protocol_fee_bps: 0,
+            },
+            state: ReserveState::default(),
+            last_update_timestamp: 0,
+            last_update_slot: 1_000,
+            fee_split: FeeSplit::default(),
+            asset_class: 0,
+            oracle_source: OracleSource::Pyth,
+            fixed_price: 0,
+            secondary_oracle: Pubkey::default(),
+            secondary_oracle_source: OracleSource::Pyth,
+            max_oracle_deviation_bps: 0,
+            twap_price: 0,
+            twap_exponent: 0,
+            twap_last_update_timestamp: 0,
+            twap_window_seconds: 0,
+            deprecation_start_timestamp: 0,
+            deprecation_deadline_timestamp: 0,
+            deleverage_penalty_bps: 0,
+            debt_ceiling_usd: 0,
+            isolation_mode_total_debt: 0,
+            surge_utilization_threshold_bps: 0,
+            surge_duration_slots: 0,
+            surge_withdrawal_fee_bps: 0,
+            high_utilization_since_slot: 0,
+            total_surge_fees: 0,
+            emergency_pause_flags: ReserveConfigFlags::default(),
+            derived_oracle: DerivedOracle::default(),
+            reserved: [0; 8],
+        }
+    }
+
+    /// Deposit `amount`, returning the collateral minted (mirrors deposit_reserve_liquidity)
+    fn deposit(reserve: &mut Reserve, amount: u64, slot: u64) -> u64 {
+        reserve.accrue_interest(slot, slot / 2).unwrap();
+        let collateral = reserve.liquidity_to_collateral(amount).unwrap();
+        reserve.add_liquidity(amount).unwrap();
+        reserve.state.collateral_mint_supply += collateral;
+        collateral
+    }
+
+    #[test]
+    fn test_exchange_rate_grows_with_slots() {
+        let mut reserve = test_reserve();
+        deposit(&mut reserve, 1_000_000_000, 1_000);
+        reserve.add_borrow(500_000_000).unwrap();
+
+        let rate_before = reserve.collateral_exchange_rate().unwrap();
+        assert_eq!(rate_before, Decimal::one());
+
+        // Advance ~1 day of slots
+        reserve.accrue_interest(1_000 + SLOTS_PER_YEAR / 365, 0).unwrap();
+
+        let rate_after = reserve.collateral_exchange_rate().unwrap();
+        assert!(rate_after > rate_before);
+        assert!(reserve.state.total_borrows > 500_000_000);
+        assert!(reserve.state.accumulated_protocol_fees > 0);
+        assert!(reserve.state.current_supply_rate_bps > 0);
+
+        // A borrow position accrues against the cumulative rate in step with total borrows
+        let mut borrow = ObligationLiquidity {
+            borrowed_amount_wads: Decimal::from_integer(500_000_000).unwrap(),
+            cumulative_borrow_rate_wads: Decimal::one(),
+            ..ObligationLiquidity::default()
+        };
+        borrow
+            .accrue_interest(reserve.cumulative_borrow_rate_wads())
+            .unwrap();
+        assert_eq!(
+            borrow.borrowed_amount_wads.try_floor_u64().unwrap(),
+            reserve.state.total_borrows
+        );
+    }
+
+    #[test]
+    fn test_interest_rate_strategies() {
+        let config = test_reserve().config;
+        let rate = |strategy: InterestRateStrategy, utilization: u64| {
+            strategy.borrow_rate_bps(utilization, &config).unwrap()
+        };
+
+        // Kinked: 2% base, +10% up to the 80% kink, +100% jump to full utilization
+        assert_eq!(rate(InterestRateStrategy::Kinked, 4000), 700);
+        assert_eq!(rate(InterestRateStrategy::Kinked, 9000), 6200);
+
+        // Linear ignores the kink
+        assert_eq!(rate(InterestRateStrategy::Linear, 5000), 700);
+        assert_eq!(rate(InterestRateStrategy::Linear, 10000), 1200);
+
+        // Curve matches kinked below the kink and at full utilization, but rises slower
+        // just above it
+        assert_eq!(rate(InterestRateStrategy::Curve, 4000), 700);
+        assert_eq!(rate(InterestRateStrategy::Curve, 9000), 3700);
+        assert_eq!(
+            rate(InterestRateStrategy::Curve, 10000),
+            rate(InterestRateStrategy::Kinked, 10000)
+        );
+
+        assert_eq!(rate(InterestRateStrategy::Fixed, 0), 200);
+        assert_eq!(rate(InterestRateStrategy::Fixed, 10000), 200);
+
+        // The reserve accrues with its configured strategy
+        let mut reserve = test_reserve();
+        reserve.config.interest_rate_strategy = InterestRateStrategy::Fixed;
+        deposit(&mut reserve, 1_000_000_000, 1_000);
+        reserve.add_borrow(900_000_000).unwrap();
+        reserve.accrue_interest(1_001, 0).unwrap();
+        assert_eq!(reserve.state.current_borrow_rate_bps, 200);
+    }
+
+    #[test]
+    fn test_deposit_after_accrual_uses_fresh_rate() {
+        let mut reserve = test_reserve();
+        let first = deposit(&mut reserve, 1_000_000_000, 1_000);
+        reserve.add_borrow(800_000_000).unwrap();
+
+        // A later depositor receives fewer aTokens per unit once interest accrued
+        let second = deposit(&mut reserve, 1_000_000_000, 1_000 + SLOTS_PER_YEAR / 12);
+        assert!(second < first);
+
+        // Redeeming the first position returns principal plus interest
+        assert!(reserve.collateral_to_liquidity(first).unwrap() > 1_000_000_000);
+        // Redeeming the second position immediately returns no more than was deposited
+        assert!(reserve.collateral_to_liquidity(second).unwrap() <= 1_000_000_000);
+    }
+
+    #[test]
+    fn test_accrual_is_idempotent_within_slot() {
+        let mut reserve = test_reserve();
+        deposit(&mut reserve, 1_000_000_000, 1_000);
+        reserve.add_borrow(500_000_000).unwrap();
+
+        reserve.accrue_interest(5_000, 0).unwrap();
+        let borrows = reserve.state.total_borrows;
+        let liquidity = reserve.state.total_liquidity;
+
+        reserve.accrue_interest(5_000, 0).unwrap();
+        reserve.accrue_interest(4_000, 0).unwrap();
+
+        assert_eq!(reserve.state.total_borrows, borrows);
+        assert_eq!(reserve.state.total_liquidity, liquidity);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_accrual_never_shrinks_debt_or_liquidity(
+            supplied in 1_000_000u64..1_000_000_000_000,
+            utilization_bps in 0u64..=BASIS_POINTS_PRECISION,
+            slots_elapsed in 0..SLOTS_PER_YEAR,
+        ) {
+            let mut reserve = test_reserve();
+            deposit(&mut reserve, supplied, 1_000);
+            let borrowed = supplied / BASIS_POINTS_PRECISION * utilization_bps;
+            if borrowed > 0 {
+                reserve.add_borrow(borrowed).unwrap();
+            }
+            let cumulative_rate = reserve.cumulative_borrow_rate_wads();
+            let exchange_rate = reserve.collateral_exchange_rate().unwrap();
+            let borrows = reserve.state.total_borrows;
+
+            reserve.accrue_interest(1_000 + slots_elapsed, 0).unwrap();
+
+            proptest::prop_assert!(reserve.cumulative_borrow_rate_wads() >= cumulative_rate);
+            proptest::prop_assert!(reserve.collateral_exchange_rate().unwrap() >= exchange_rate);
+            proptest::prop_assert!(reserve.state.total_borrows >= borrows);
+        }
+    }
+
+    #[test]
+    fn test_caps_bound_supply_and_borrows() {
+        let mut reserve = test_reserve();
+        deposit(&mut reserve, 1_000_000, 1_000);
+        reserve.add_borrow(400_000).unwrap();
+
+        // Uncapped by default
+        reserve.validate_supply_cap(u64::MAX - 1_000_000).unwrap();
+        reserve.validate_borrow_cap(600_000).unwrap();
+
+        reserve.config.supply_cap = 1_500_000;
+        reserve.config.borrow_cap = 500_000;
+        reserve.validate_supply_cap(500_000).unwrap();
+        assert!(reserve.validate_supply_cap(500_001).is_err());
+        reserve.validate_borrow_cap(100_000).unwrap();
+        assert!(reserve.validate_borrow_cap(100_001).is_err());
+
+        // A cap lowered below current usage blocks further growth
+        reserve.config.supply_cap = 900_000;
+        assert!(reserve.validate_supply_cap(0).is_err());
+    }
+
+    #[test]
+    fn test_invested_liquidity_round_trip() {
+        let mut reserve = test_reserve();
+        reserve.config.protocol_fee_bps = 1_000;
+        deposit(&mut reserve, 1_000_000, 1_000);
+        reserve.add_borrow(400_000).unwrap();
+        let utilization = reserve.utilization_rate_bps().unwrap();
+
+        // Protocol fees stay idle; investing does not move utilization
+        reserve.state.accumulated_protocol_fees = 100_000;
+        assert!(reserve.invest_liquidity(500_001).is_err());
+        reserve.invest_liquidity(500_000).unwrap();
+        assert_eq!(reserve.state.available_liquidity, 100_000);
+        assert_eq!(reserve.utilization_rate_bps().unwrap(), utilization);
+        assert!(!reserve.is_empty());
+
+        // A gain is split like interest
+        let rate = reserve.collateral_exchange_rate().unwrap();
+        assert_eq!(reserve.divest_liquidity(250_000, 260_000).unwrap(), 1_000);
+        assert_eq!(reserve.state.total_liquidity, 1_009_000);
+        assert!(reserve.collateral_exchange_rate().unwrap() > rate);
+
+        // A loss is borne by suppliers
+        assert_eq!(reserve.divest_liquidity(250_000, 249_000).unwrap(), 0);
+        assert_eq!(reserve.state.total_liquidity, 1_008_000);
+        assert_eq!(reserve.state.invested_liquidity, 0);
+        assert_eq!(reserve.state.available_liquidity, 609_000);
+    }
+
+    #[test]
+    fn test_isolation_debt_ceiling() {
+        let mut reserve = test_reserve();
+
+        // Uncapped by default
+        reserve.add_isolation_mode_debt(1_000_000).unwrap();
+
+        reserve.debt_ceiling_usd = 1_500_000;
+        reserve.add_isolation_mode_debt(500_000).unwrap();
+        assert!(reserve.add_isolation_mode_debt(1).is_err());
+        assert_eq!(reserve.isolation_mode_total_debt, 1_500_000);
+
+        // Repayments free room under the ceiling and never underflow
+        reserve.remove_isolation_mode_debt(250_000);
+        reserve.add_isolation_mode_debt(250_000).unwrap();
+        reserve.remove_isolation_mode_debt(u64::MAX);
+        assert_eq!(reserve.isolation_mode_total_debt, 0);
+    }
+
+    #[test]
+    fn test_projected_borrow_rate_follows_kink() {
+        let mut reserve = test_reserve();
+        reserve.state.available_liquidity = 1_000_000;
+
+        // 80% utilization sits at the kink: base + multiplier
+        assert_eq!(reserve.projected_borrow_rate_bps(800_000).unwrap(), 1_200);
+        // 90% utilization adds half of the jump multiplier
+        assert_eq!(reserve.projected_borrow_rate_bps(900_000).unwrap(), 6_200);
+        assert!(reserve.projected_borrow_rate_bps(1_000_001).is_err());
+
+        // Redeeming liquidity raises utilization of the remaining borrows
+        reserve.state.total_borrows = 400_000;
+        reserve.state.available_liquidity = 600_000;
+        assert_eq!(
+            reserve.projected_rates_after_redeem(500_000).unwrap(),
+            (8_000, 1_200)
+        );
+        assert!(reserve.projected_rates_after_redeem(600_001).is_err());
+    }
+
+    #[test]
+    fn test_current_rates_and_yields() {
+        let mut reserve = test_reserve();
+        reserve.state.available_liquidity = 200_000;
+        reserve.state.total_borrows = 800_000;
+
+        assert_eq!(reserve.current_borrow_rate_bps().unwrap(), 1_200);
+        let expected_supply_bps =
+            1_200 * 8_000 * (BASIS_POINTS_PRECISION - reserve.config.protocol_fee_bps)
+                / (BASIS_POINTS_PRECISION * BASIS_POINTS_PRECISION);
+        assert_eq!(
+            reserve.current_supply_rate_bps().unwrap(),
+            expected_supply_bps
+        );
+
+        // Continuous compounding of 12% yields e^0.12 - 1 ~= 12.75%
+        let apy_bps = Reserve::annual_yield_bps(1_200).unwrap();
+        assert!((1_274..=1_275).contains(&apy_bps));
+        assert_eq!(Reserve::annual_yield_bps(0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_fee_split_allocation() {
+        let recipient = |weight_bps| FeeRecipient {
+            receiver: Pubkey::new_unique(),
+            weight_bps,
+        };
+        let split = FeeSplit {
+            insurance: recipient(2_500),
+            stakers: recipient(3_333),
+            grants: FeeRecipient::default(),
+        };
+        split.validate().unwrap();
+
+        let (treasury, shares) = split.allocate(1_001).unwrap();
+        assert_eq!(shares, [250, 333, 0]);
+        assert_eq!(treasury, 418); // Includes rounding dust
+        assert_eq!(FeeSplit::default().allocate(1_001).unwrap(), (1_001, [0, 0, 0]));
+
+        let overweight = FeeSplit {
+            grants: recipient(5_000),
+            ..split
+        };
+        assert!(overweight.validate().is_err());
+
+        let missing_receiver = FeeSplit {
+            grants: FeeRecipient {
+                receiver: Pubkey::default(),
+                weight_bps: 100,
+            },
+            ..split
+        };
+        assert!(missing_receiver.validate().is_err());
+    }
+
+    #[test]
+    fn test_take_protocol_fees_leaves_exchange_rate() {
+        let mut reserve = test_reserve();
+        deposit(&mut reserve, 1_000_000_000, 1_000);
+        reserve.add_borrow(500_000_000).unwrap();
+        reserve.accrue_interest(1_000 + SLOTS_PER_YEAR / 365, 0).unwrap();
+        reserve.repay_borrow(reserve.state.total_borrows).unwrap();
+
+        let fees = reserve.state.accumulated_protocol_fees;
+        let available = reserve.state.available_liquidity;
+        let rate = reserve.collateral_exchange_rate().unwrap();
+
+        assert_eq!(reserve.take_protocol_fees().unwrap(), fees);
+        assert_eq!(reserve.state.accumulated_protocol_fees, 0);
+        assert_eq!(reserve.state.available_liquidity, available - fees);
+        assert_eq!(reserve.collateral_exchange_rate().unwrap(), rate);
+    }
+
+    #[test]
+    fn test_flash_loan_fee_splits_between_suppliers_and_protocol() {
+        let mut reserve = test_reserve();
+        deposit(&mut reserve, 1_000_000_000, 1_000);
+        let rate = reserve.collateral_exchange_rate().unwrap();
+
+        assert_eq!(Reserve::flash_loan_fee(1_000_000).unwrap(), 900);
+        assert_eq!(Reserve::flash_loan_fee(1).unwrap(), 1);
+
+        let protocol_fee = reserve.collect_flash_loan_fee(900).unwrap();
+        assert_eq!(protocol_fee, 90);
+        assert_eq!(reserve.state.accumulated_protocol_fees, 90);
+        assert_eq!(reserve.state.available_liquidity, 1_000_000_900);
+        assert_eq!(reserve.state.total_liquidity, 1_000_000_810);
+        assert!(reserve.collateral_exchange_rate().unwrap() > rate);
+    }
+
+    #[test]
+    fn test_bad_debt_is_covered_first_then_socialized() {
+        let mut reserve = test_reserve();
+        deposit(&mut reserve, 1_000_000, 1_000);
+        reserve.add_borrow(400_000).unwrap();
+        let rate = reserve.collateral_exchange_rate().unwrap();
+
+        // Insurance coverage repays the debt and leaves suppliers whole
+        reserve.repay_borrow(100_000).unwrap();
+        assert_eq!(reserve.collateral_exchange_rate().unwrap(), rate);
+
+        // The uncovered remainder lowers the exchange rate
+        assert_eq!(reserve.socialize_bad_debt(50_000).unwrap(), 50_000);
+        assert_eq!(reserve.state.total_borrows, 250_000);
+        assert_eq!(reserve.state.total_liquidity, 950_000);
+        assert!(reserve.collateral_exchange_rate().unwrap() < rate);
+
+        assert_eq!(reserve.socialize_bad_debt(u64::MAX).unwrap(), 250_000);
+        assert_eq!(reserve.state.total_borrows, 0);
+    }
+
+    #[test]
+    fn test_borrow_fee_splits_between_treasury_and_insurance_fund() {
+        let mut reserve = test_reserve();
+        assert_eq!(reserve.borrow_fee(1_000_000).unwrap(), 0);
+
+        reserve.config.borrow_fee_bps = 10;
+        reserve.config.insurance_fund_share_bps = 2500;
+        assert_eq!(reserve.borrow_fee(1_000_000).unwrap(), 1_000);
+        assert_eq!(reserve.borrow_fee(1).unwrap(), 1);
+        assert_eq!(reserve.split_insurance_fee(1_000).unwrap(), (750, 250));
+        assert_eq!(reserve.split_insurance_fee(3).unwrap(), (3, 0));
+
+        // The insurance share of a flash loan fee leaves the supply immediately
+        deposit(&mut reserve, 1_000_000_000, 1_000);
+        let protocol_fee = reserve.collect_flash_loan_fee(900).unwrap();
+        let (_, insurance_fee) = reserve.split_insurance_fee(protocol_fee).unwrap();
+        reserve.release_protocol_fees(insurance_fee).unwrap();
+        assert_eq!(insurance_fee, 22);
+        assert_eq!(reserve.state.accumulated_protocol_fees, 68);
+        assert_eq!(reserve.state.available_liquidity, 1_000_000_878);
+        assert_eq!(reserve.state.total_liquidity, 1_000_000_810);
+    }
+
+    #[test]
+    fn test_liquidation_protocol_fee_takes_share_of_bonus() {
+        let mut reserve = test_reserve();
+        assert_eq!(reserve.liquidation_protocol_fee(10_500, 500).unwrap(), 0);
+
+        // 10_500 seized at a 5% bonus carries 500 of bonus, of which 20% is the protocol's
+        reserve.config.liquidation_protocol_fee_bps = 2000;
+        assert_eq!(reserve.liquidation_protocol_fee(10_500, 500).unwrap(), 100);
+        assert_eq!(reserve.liquidation_protocol_fee(10_500, 0).unwrap(), 0);
+
+        reserve.config.liquidation_protocol_fee_bps = BASIS_POINTS_PRECISION;
+        assert_eq!(reserve.liquidation_protocol_fee(10_500, 500).unwrap(), 500);
+    }
+
+    #[test]
+    fn test_accrued_liquidation_fee_keeps_exchange_rate() {
+        let mut reserve = test_reserve();
+        reserve.state.total_liquidity = 2_000;
+        reserve.state.available_liquidity = 50;
+        reserve.state.collateral_mint_supply = 1_000;
+        let rate = reserve.collateral_exchange_rate().unwrap();
+
+        reserve.accrue_liquidation_protocol_fee(100, 200).unwrap();
+        assert_eq!(reserve.collateral_exchange_rate().unwrap(), rate);
+        assert_eq!(reserve.state.accumulated_protocol_fees, 200);
+        assert_eq!(reserve.state.available_liquidity, 50);
+
+        // Collection pays out only what is available
+        assert_eq!(reserve.take_protocol_fees().unwrap(), 50);
+        assert_eq!(reserve.state.accumulated_protocol_fees, 150);
+    }
+
+    #[test]
+    fn test_twap_smooths_liquidation_prices() {
+        let mut reserve = test_reserve();
+        let price = |price: i64, exponent: i32| OraclePrice {
+            price,
+            confidence: 0,
+            exponent,
+            publish_time: 0,
+        };
+
+        // Without a window the TWAP tracks spot and liquidations use spot
+        reserve.update_twap(&price(100, -8), 0).unwrap();
+        assert_eq!(reserve.liquidation_collateral_price(&price(50, -8)).price, 50);
+        assert!(reserve.set_twap_window(MAX_TWAP_WINDOW_SECONDS + 1).is_err());
+        reserve.set_twap_window(60).unwrap();
+
+        // A crash after one window moves the average halfway
+        reserve.update_twap(&price(50, -8), 60).unwrap();
+        assert_eq!(reserve.twap_price, 75);
+        reserve.update_twap(&price(10, -8), 60).unwrap();
+        assert_eq!(reserve.twap_price, 75);
+
+        // Collateral takes the higher and debt the lower of spot and TWAP
+        let spot = price(50, -8);
+        assert_eq!(reserve.liquidation_collateral_price(&spot).price, 75);
+        assert_eq!(reserve.liquidation_debt_price(&spot).price, 50);
+        assert_eq!(reserve.liquidation_debt_price(&price(90, -8)).price, 75);
+
+        // A feed with a different exponent restarts the average
+        reserve.update_twap(&price(4_000, -10), 120).unwrap();
+        assert_eq!((reserve.twap_price, reserve.twap_exponent), (4_000, -10));
+    }
+
+    #[test]
+    fn test_deprecation_walks_ltv_down_to_deadline() {
+        let mut reserve = test_reserve();
+        let start = 1_000;
+        let deadline = start + MIN_DEPRECATION_PERIOD_SECONDS;
+        assert_eq!(reserve.effective_loan_to_value_ratio_bps(start), 7500);
+
+        // The wind-down must be long enough and pay at most the liquidation penalty
+        assert!(reserve.set_deprecation(start, deadline - 1, 0).is_err());
+        assert!(reserve.set_deprecation(start, deadline, 501).is_err());
+        reserve.set_deprecation(start, deadline, 100).unwrap();
+        assert!(reserve.set_deprecation(start, deadline, 100).is_err());
+
+        let halfway = start + MIN_DEPRECATION_PERIOD_SECONDS / 2;
+        assert_eq!(reserve.effective_loan_to_value_ratio_bps(start), 7500);
+        assert_eq!(reserve.effective_loan_to_value_ratio_bps(halfway), 3750);
+        assert!(!reserve.is_past_deprecation_deadline(deadline - 1));
+        assert_eq!(reserve.effective_loan_to_value_ratio_bps(deadline), 0);
+        assert!(reserve.is_past_deprecation_deadline(deadline));
+
+        // Cancelling restores the configured LTV
+        reserve.set_deprecation(halfway, 0, 0).unwrap();
+        assert!(!reserve.is_deprecated());
+        assert_eq!(reserve.effective_loan_to_value_ratio_bps(deadline), 7500);
+    }
+
+    #[test]
+    fn test_surge_fee_arms_after_sustained_utilization() {
+        let mut reserve = test_reserve();
+        deposit(&mut reserve, 1_000_000, 1_000);
+        reserve.add_borrow(950_000).unwrap();
+
+        let params = UpdateReserveSurgeFeeParams {
+            utilization_threshold_bps: 9_000,
+            duration_slots: 100,
+            withdrawal_fee_bps: 50,
+        };
+        assert!(reserve
+            .set_surge_fee(&UpdateReserveSurgeFeeParams {
+                withdrawal_fee_bps: MAX_SURGE_WITHDRAWAL_FEE_BPS + 1,
+                ..params.clone()
+            })
+            .is_err());
+        reserve.set_surge_fee(&params).unwrap();
+
+        // The fee applies only once utilization has stayed above the threshold long enough
+        reserve.accrue_interest(1_010, 0).unwrap();
+        assert_eq!(reserve.high_utilization_since_slot, 1_010);
+        assert_eq!(reserve.surge_withdrawal_fee(10_000, 1_109).unwrap(), 0);
+        reserve.accrue_interest(1_110, 0).unwrap();
+        assert_eq!(reserve.surge_withdrawal_fee(10_000, 1_110).unwrap(), 50);
+
+        // Falling back below the threshold disarms it
+        reserve.add_liquidity(1_000_000).unwrap();
+        reserve.accrue_interest(1_120, 0).unwrap();
+        assert_eq!(reserve.high_utilization_since_slot, 0);
+        assert_eq!(reserve.surge_withdrawal_fee(10_000, 1_120).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_redemption_applies_withdrawal_limits() {
+        let mut reserve = test_reserve();
+        let config = ProtocolConfig::default();
+        deposit(&mut reserve, 1_000_000, 1_000);
+        reserve.add_borrow(900_000).unwrap();
+
+        // Leaving 20,000 of 920,000 unborrowed is over the 95% cap; 50,000 of 950,000 is not
+        assert!(reserve.quote_redemption(&config, 80_000, 1_000).is_err());
+        let redemption = reserve.quote_redemption(&config, 50_000, 1_000).unwrap();
+        assert_eq!(redemption.surge_fee, 0);
+        assert_eq!(redemption.withdrawn_amount, 50_000);
+
+        // Once the surge is active the fee is withheld from the payout
+        reserve
+            .set_surge_fee(&UpdateReserveSurgeFeeParams {
+                utilization_threshold_bps: 8_500,
+                duration_slots: 100,
+                withdrawal_fee_bps: 50,
+            })
+            .unwrap();
+        reserve.track_surge_utilization(1_010).unwrap();
+        let redemption = reserve.quote_redemption(&config, 50_000, 1_110).unwrap();
+        assert_eq!(redemption.surge_fee, 250);
+        assert_eq!(redemption.withdrawn_amount, 49_750);
+
+        reserve.settle_redemption(&redemption).unwrap();
+        assert_eq!(reserve.state.available_liquidity, 50_250);
+        assert_eq!(reserve.state.collateral_mint_supply, 950_000);
+        assert_eq!(reserve.total_surge_fees, 250);
+
+        reserve
+            .config
+            .flags
+            .insert(ReserveConfigFlags::WITHDRAWALS_DISABLED);
+        assert!(reserve.quote_redemption(&config, 1_000, 1_110).is_err());
+    }
+
+    #[test]
+    fn test_emergency_pause_survives_config_updates() {
+        let mut reserve = test_reserve();
+        let borrowing = ReserveConfigFlags::BORROWING_DISABLED;
+
+        assert!(reserve
+            .emergency_pause(ReserveConfigFlags::default())
+            .is_err());
+        assert!(reserve
+            .emergency_pause(ReserveConfigFlags::AUCTION_LIQUIDATIONS)
+            .is_err());
+        reserve.emergency_pause(borrowing).unwrap();
+        assert!(reserve.config.flags.contains(borrowing));
+
+        // A config update cannot lift the pause; only an unpause can
+        let mut config = reserve.config.clone();
+        config.flags.remove(borrowing);
+        assert!(reserve.validate_emergency_pauses(&config).is_err());
+        assert!(reserve
+            .emergency_unpause(ReserveConfigFlags::DEPOSITS_DISABLED)
+            .is_err());
+        reserve.emergency_unpause(borrowing).unwrap();
+        assert!(!reserve.config.flags.contains(borrowing));
+        reserve.validate_emergency_pauses(&config).unwrap();
+    }
+
+    #[test]
+    fn test_derived_oracle_configuration() {
+        let mut reserve = test_reserve();
+        let sol = DerivedOracleComponent {
+            oracle: Pubkey::new_unique(),
+            source: OracleSource::Pyth,
+            feed_id: [1; 32],
+            ..DerivedOracleComponent::default()
+        };
+        let stake_pool = UpdateReserveDerivedOracleParams {
+            kind: DerivedOracleKind::StakePool,
+            pool: Pubkey::new_unique(),
+            base: sol,
+            quote: DerivedOracleComponent::default(),
+        };
+
+        // Components must read live feeds
+        let fixed = UpdateReserveDerivedOracleParams {
+            base: DerivedOracleComponent {
+                source: OracleSource::FixedPrice,
+                ..sol
+            },
+            ..stake_pool.clone()
+        };
+        assert!(reserve.set_derived_oracle(&fixed).is_err());
+
+        // An LP token needs both tokens' oracles and distinct vaults
+        let vault = Pubkey::new_unique();
+        let lp = UpdateReserveDerivedOracleParams {
+            kind: DerivedOracleKind::LiquidityPool,
+            base: DerivedOracleComponent {
+                vault,
+                decimals: 9,
+                ..sol
+            },
+            quote: DerivedOracleComponent {
+                vault,
+                decimals: 6,
+                ..sol
+            },
+            ..stake_pool.clone()
+        };
+        assert!(reserve.set_derived_oracle(&lp).is_err());
+
+        // Reconfiguring discards the cached price
+        reserve.set_derived_oracle(&stake_pool).unwrap();
+        reserve.derived_oracle.price = 16_500_000_000;
+        reserve.set_derived_oracle(&stake_pool).unwrap();
+        assert!(reserve.derived_oracle.cached_price().is_err());
+
+        // A reserve priced by its derived oracle cannot clear it
+        reserve.oracle_source = OracleSource::Derived;
+        let clear = UpdateReserveDerivedOracleParams {
+            kind: DerivedOracleKind::None,
+            ..stake_pool
+        };
+        assert!(reserve.set_derived_oracle(&clear).is_err());
+    }
+
+    #[test]
+    fn test_compact_legacy_reserve() {
+        let keys: Vec<Pubkey> = (0..6).map(|_| Pubkey::new_unique()).collect();
+        let borrow_rate = Decimal::from_scaled_val(PRECISION as u128 / 20);
+        let utilization_rate = Decimal::from_scaled_val(PRECISION as u128 * 3 / 5);
+
+        // A version 1 account in the original layout, written field by field
+        let mut legacy = Reserve::DISCRIMINATOR.to_vec();
+        legacy.push(1);
+        for key in &keys {
+            legacy.extend_from_slice(key.as_ref());
+        }
+        legacy.extend_from_slice(&[7; 32]);
+        for config_bps in [
+            7_500u64, 8_000, 500, 200, 1_000, 10_000, 8_000, 1_000, 30_000,
+        ] {
+            legacy.extend_from_slice(&config_bps.to_le_bytes());
+        }
+        legacy.push(6);
+        legacy.extend_from_slice(&(1u32 << 5).to_le_bytes());
+        for amount in [400_000_000u64, 600_000_000, 1_000_000_000, 950_000_000] {
+            legacy.extend_from_slice(&amount.to_le_bytes());
+        }
+        for rate in [borrow_rate, Decimal::zero(), utilization_rate] {
+            legacy.extend_from_slice(&rate.value.to_le_bytes());
+        }
+        legacy.extend_from_slice(&12_345u64.to_le_bytes());
+        legacy.extend_from_slice(&1_700_000_000u64.to_le_bytes());
+        legacy.extend_from_slice(&2_000u64.to_le_bytes());
+        legacy.push(1);
+        legacy.extend_from_slice(&[0; 255]);
+
+        let compacted = crate::migration::compact_reserve_data(&legacy)
+            .unwrap()
+            .unwrap();
+        assert_eq!(compacted.len(), Reserve::SIZE);
+        assert!(crate::migration::compact_reserve_data(&compacted)
+            .unwrap()
+            .is_none());
+
+        let loaded = Reserve::try_deserialize(&mut compacted.as_slice()).unwrap();
+        assert_eq!(loaded.version, RESERVE_VERSION);
+        assert_eq!(loaded.market, keys[0]);
+        assert_eq!(loaded.price_oracle, keys[5]);
+        assert_eq!(loaded.oracle_feed_id, [7; 32]);
+        assert_eq!(loaded.config.loan_to_value_ratio_bps, 7_500);
+        assert_eq!(loaded.config.max_borrow_rate_bps, 30_000);
+        assert_eq!(loaded.config.decimals, 6);
+        assert!(loaded
+            .config
+            .flags
+            .contains(ReserveConfigFlags::COLLATERAL_ENABLED));
+        assert_eq!(loaded.config.supply_cap, 0);
+        assert_eq!(loaded.config.liquidation_protocol_fee_bps, 0);
+        assert_eq!(loaded.state.available_liquidity, 400_000_000);
+        assert_eq!(loaded.state.collateral_mint_supply, 950_000_000);
+        assert_eq!(loaded.state.current_borrow_rate_bps, 500);
+        assert_eq!(loaded.state.current_utilization_rate_bps, 6_000);
+        assert_eq!(loaded.state.accumulated_protocol_fees, 12_345);
+        assert_eq!(loaded.state.cumulative_borrow_rate_wads, Decimal::one());
+        assert_eq!(loaded.state.invested_liquidity, 0);
+        assert_eq!(loaded.last_update_slot, 2_000);
+        assert!(loaded.is_locked());
+        assert_eq!(loaded.oracle_source, OracleSource::Pyth);
+        assert_eq!(loaded.secondary_oracle, Pubkey::default());
+    }
+}
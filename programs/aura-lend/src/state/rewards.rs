@@ -0,0 +1,420 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::obligation::Obligation;
+use crate::utils::math::Decimal;
+use anchor_lang::prelude::*;
+
+/// Side of a reserve a reward pool incentivizes
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RewardSide {
+    /// aTokens of the reserve deposited as obligation collateral
+    Supply,
+    /// Liquidity borrowed from the reserve
+    Borrow,
+}
+
+/// Reward emissions for one side of a reserve
+/// Governance funds the pool's vault and sets a per-slot emission rate. Emissions are
+/// shared pro rata among the positions of obligations that opted in, tracked by a
+/// cumulative reward-per-unit index.
+#[account]
+pub struct RewardPool {
+    /// Version of the reward pool account structure
+    pub version: u8,
+
+    /// Market this pool belongs to
+    pub market: Pubkey,
+
+    /// Reserve whose positions earn rewards
+    pub reserve: Pubkey,
+
+    /// Side of the reserve that earns rewards
+    pub side: RewardSide,
+
+    /// Mint of the reward token
+    pub reward_mint: Pubkey,
+
+    /// Token account holding undistributed and unclaimed rewards
+    pub reward_vault: Pubkey,
+
+    /// Reward tokens emitted per slot while the schedule runs
+    pub emission_per_slot: u64,
+
+    /// Slot the index was last accrued to
+    pub last_update_slot: u64,
+
+    /// Slot the current schedule stops emitting at
+    pub end_slot: u64,
+
+    /// Cumulative rewards per unit of position
+    pub reward_index: Decimal,
+
+    /// Sum of the positions of all opted-in obligations
+    pub total_position: u64,
+
+    /// Total reward tokens paid into the vault
+    pub total_funded: u64,
+
+    /// Total reward tokens distributed to positions
+    pub total_emitted: u64,
+
+    /// Total reward tokens claimed
+    pub total_claimed: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 64],
+}
+
+impl RewardPool {
+    /// Size of the RewardPool account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // reserve
+        1 + // side
+        32 + // reward_mint
+        32 + // reward_vault
+        8 + // emission_per_slot
+        8 + // last_update_slot
+        8 + // end_slot
+        16 + // reward_index
+        8 + // total_position
+        8 + // total_funded
+        8 + // total_emitted
+        8 + // total_claimed
+        1 + // bump
+        64; // reserved
+
+    /// Create a pool with no schedule
+    pub fn new(
+        market: Pubkey,
+        reserve: Pubkey,
+        side: RewardSide,
+        reward_mint: Pubkey,
+        reward_vault: Pubkey,
+        slot: u64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            version: PROGRAM_VERSION,
+            market,
+            reserve,
+            side,
+            reward_mint,
+            reward_vault,
+            emission_per_slot: 0,
+            last_update_slot: slot,
+            end_slot: slot,
+            reward_index: Decimal::zero(),
+            total_position: 0,
+            total_funded: 0,
+            total_emitted: 0,
+            total_claimed: 0,
+            bump,
+            reserved: [0; 64],
+        }
+    }
+
+    /// Accrue emissions up to `slot` into the reward index
+    /// Slots in which no position is opted in emit nothing; those rewards stay in the
+    /// vault for a later schedule.
+    pub fn update_index(&mut self, slot: u64) -> Result<()> {
+        let elapsed = slot
+            .min(self.end_slot)
+            .saturating_sub(self.last_update_slot);
+
+        if elapsed > 0 && self.total_position > 0 && self.emission_per_slot > 0 {
+            let emitted = self
+                .emission_per_slot
+                .checked_mul(elapsed)
+                .ok_or(LendingError::MathOverflow)?;
+            let index_increase = Decimal::from_integer(emitted)?
+                .try_div(Decimal::from_integer(self.total_position)?)?;
+            self.reward_index = self.reward_index.try_add(index_increase)?;
+            self.total_emitted = self
+                .total_emitted
+                .checked_add(emitted)
+                .ok_or(LendingError::MathOverflow)?;
+        }
+
+        self.last_update_slot = self.last_update_slot.max(slot);
+        Ok(())
+    }
+
+    /// Reward tokens in the vault not yet distributed to positions
+    pub fn unemitted(&self) -> u64 {
+        self.total_funded.saturating_sub(self.total_emitted)
+    }
+
+    /// Record reward tokens paid into the vault
+    pub fn record_funding(&mut self, amount: u64) -> Result<()> {
+        self.total_funded = self
+            .total_funded
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Replace the schedule with `emission_per_slot` for `duration_slots` from `slot`
+    /// The index must already be accrued to `slot`. The whole schedule has to be
+    /// covered by undistributed rewards, so claims can never exceed the vault.
+    pub fn set_schedule(
+        &mut self,
+        emission_per_slot: u64,
+        duration_slots: u64,
+        slot: u64,
+    ) -> Result<()> {
+        if duration_slots > MAX_REWARD_SCHEDULE_SLOTS {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+        let scheduled = emission_per_slot
+            .checked_mul(duration_slots)
+            .ok_or(LendingError::MathOverflow)?;
+        if scheduled > self.unemitted() {
+            return Err(LendingError::RewardScheduleUnderfunded.into());
+        }
+
+        self.emission_per_slot = emission_per_slot;
+        self.end_slot = slot
+            .checked_add(duration_slots)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Position `obligation` holds on the side of the reserve this pool incentivizes
+    pub fn position_of(&self, obligation: &Obligation) -> Result<u64> {
+        match self.side {
+            RewardSide::Supply => Ok(obligation
+                .find_collateral_deposit(&self.reserve)
+                .map_or(0, |deposit| deposit.deposited_amount)),
+            RewardSide::Borrow => match obligation.find_liquidity_borrow(&self.reserve) {
+                Some(borrow) => borrow.borrowed_amount_wads.try_floor_u64(),
+                None => Ok(0),
+            },
+        }
+    }
+
+    /// Settle an obligation's rewards and move its tracked position to `position`
+    /// The index must already be accrued to the current slot. Rewards since the last sync
+    /// are earned on the smaller of the tracked and the current position, so a position
+    /// that shrank without the reward accounts stops earning on what it no longer holds.
+    pub fn sync(&mut self, rewards: &mut ObligationRewards, position: u64) -> Result<()> {
+        rewards.accrue(self.reward_index, rewards.position.min(position))?;
+        self.total_position = self
+            .total_position
+            .checked_sub(rewards.position)
+            .ok_or(LendingError::MathUnderflow)?
+            .checked_add(position)
+            .ok_or(LendingError::MathOverflow)?;
+        rewards.position = position;
+        Ok(())
+    }
+
+    /// Record reward tokens claimed from the vault
+    pub fn record_claim(&mut self, amount: u64) -> Result<()> {
+        self.total_claimed = self
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+/// An obligation's participation in a reward pool
+#[account]
+pub struct ObligationRewards {
+    /// Version of the obligation rewards account structure
+    pub version: u8,
+
+    /// Reward pool the obligation opted into
+    pub pool: Pubkey,
+
+    /// Obligation whose position earns rewards
+    pub obligation: Pubkey,
+
+    /// Owner of the obligation, who may claim
+    pub owner: Pubkey,
+
+    /// Position last synced into the pool
+    pub position: u64,
+
+    /// Pool reward index at the last sync
+    pub index_snapshot: Decimal,
+
+    /// Rewards earned and not yet claimed
+    pub accrued: u64,
+
+    /// Total rewards claimed
+    pub total_claimed: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl ObligationRewards {
+    /// Size of the ObligationRewards account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // pool
+        32 + // obligation
+        32 + // owner
+        8 + // position
+        16 + // index_snapshot
+        8 + // accrued
+        8 + // total_claimed
+        1 + // bump
+        32; // reserved
+
+    /// Create an empty participation starting at `index`
+    pub fn new(pool: Pubkey, obligation: Pubkey, owner: Pubkey, index: Decimal, bump: u8) -> Self {
+        Self {
+            version: PROGRAM_VERSION,
+            pool,
+            obligation,
+            owner,
+            position: 0,
+            index_snapshot: index,
+            accrued: 0,
+            total_claimed: 0,
+            bump,
+            reserved: [0; 32],
+        }
+    }
+
+    /// Add the rewards `position` earned since the last snapshot
+    pub fn accrue(&mut self, index: Decimal, position: u64) -> Result<()> {
+        let earned = index
+            .try_sub(self.index_snapshot)?
+            .try_mul(Decimal::from_integer(position)?)?
+            .try_floor_u64()?;
+        self.accrued = self
+            .accrued
+            .checked_add(earned)
+            .ok_or(LendingError::MathOverflow)?;
+        self.index_snapshot = index;
+        Ok(())
+    }
+
+    /// Zero the accrued rewards and return them
+    pub fn take_accrued(&mut self) -> Result<u64> {
+        if self.accrued == 0 {
+            return Err(LendingError::NoRewardsToClaim.into());
+        }
+        let amount = std::mem::take(&mut self.accrued);
+        self.total_claimed = self
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_pool() -> RewardPool {
+        RewardPool::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            RewardSide::Supply,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            100,
+            255,
+        )
+    }
+
+    fn test_rewards(pool: &RewardPool) -> ObligationRewards {
+        ObligationRewards::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            pool.reward_index,
+            255,
+        )
+    }
+
+    #[test]
+    fn test_emissions_are_shared_pro_rata() {
+        let mut pool = test_pool();
+        pool.record_funding(10_000).unwrap();
+        pool.set_schedule(10, 1_000, 100).unwrap();
+
+        let mut alice = test_rewards(&pool);
+        let mut bob = test_rewards(&pool);
+        pool.sync(&mut alice, 300).unwrap();
+        pool.sync(&mut bob, 100).unwrap();
+        assert_eq!(pool.total_position, 400);
+
+        // 100 slots at 10 per slot split 3:1; Bob settles before withdrawing
+        pool.update_index(200).unwrap();
+        pool.sync(&mut alice, 300).unwrap();
+        pool.sync(&mut bob, 100).unwrap();
+        pool.sync(&mut bob, 0).unwrap();
+        assert_eq!(alice.accrued, 750);
+        assert_eq!(bob.accrued, 250);
+        assert_eq!(pool.total_position, 300);
+
+        // Alice alone earns everything until the schedule ends
+        pool.update_index(5_000).unwrap();
+        pool.sync(&mut alice, 300).unwrap();
+        assert_eq!(alice.accrued, 750 + 9_000);
+        assert_eq!(pool.total_emitted, 10_000);
+        assert_eq!(pool.unemitted(), 0);
+
+        assert_eq!(alice.take_accrued().unwrap(), 9_750);
+        assert!(alice.take_accrued().is_err());
+    }
+
+    #[test]
+    fn test_stale_position_earns_only_what_is_held() {
+        let mut pool = test_pool();
+        pool.record_funding(10_000).unwrap();
+        pool.set_schedule(10, 1_000, 100).unwrap();
+
+        let mut alice = test_rewards(&pool);
+        pool.sync(&mut alice, 300).unwrap();
+        pool.update_index(200).unwrap();
+        pool.sync(&mut alice, 300).unwrap();
+        assert_eq!(alice.accrued, 1_000);
+
+        // The whole position was withdrawn without a sync; the next one credits nothing
+        // for the slots since and drops the position from the pool
+        pool.update_index(300).unwrap();
+        pool.sync(&mut alice, 0).unwrap();
+        assert_eq!(alice.accrued, 1_000);
+        assert_eq!(alice.position, 0);
+        assert_eq!(pool.total_position, 0);
+
+        // A position that grew without a sync earns on what was tracked
+        pool.sync(&mut alice, 100).unwrap();
+        pool.update_index(400).unwrap();
+        pool.sync(&mut alice, 500).unwrap();
+        assert_eq!(alice.accrued, 2_000);
+        assert_eq!(pool.total_position, 500);
+    }
+
+    #[test]
+    fn test_schedule_must_be_funded() {
+        let mut pool = test_pool();
+        pool.record_funding(1_000).unwrap();
+        assert!(pool.set_schedule(2, 501, 100).is_err());
+        pool.set_schedule(2, 500, 100).unwrap();
+        assert_eq!(pool.end_slot, 600);
+        assert!(pool
+            .set_schedule(1, MAX_REWARD_SCHEDULE_SLOTS + 1, 100)
+            .is_err());
+
+        // Nothing is emitted while no position is opted in
+        pool.update_index(600).unwrap();
+        assert_eq!(pool.total_emitted, 0);
+        assert_eq!(pool.unemitted(), 1_000);
+    }
+}
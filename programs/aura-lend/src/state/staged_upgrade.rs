@@ -0,0 +1,229 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+
+/// Program upgrade staged by governance ahead of execution
+/// Records the buffer to deploy, the reviewed hash of the program it contains and the
+/// buffer's length. The buffer's write authority must already be the governance
+/// authority staging it, so from then on only governance can change its contents.
+/// `upgrade_program` refuses to deploy a buffer without a staged record, or whose
+/// authority or length changed, so a compromised deployer key cannot swap in a different
+/// binary between review and execution. Rehashing a program-sized buffer on chain would
+/// not fit in a transaction's compute budget, so reviewers compare `program_hash` with
+/// the buffer off chain before approving the stage.
+#[account]
+pub struct StagedUpgrade {
+    /// Version of the staged upgrade account structure
+    pub version: u8,
+
+    /// Market whose governance staged the upgrade
+    pub market: Pubkey,
+
+    /// Buffer account holding the new program
+    pub buffer: Pubkey,
+
+    /// Reviewed SHA-256 hash of the program in the buffer, checked off chain
+    pub program_hash: [u8; 32],
+
+    /// Authority that staged the upgrade
+    pub staged_by: Pubkey,
+
+    /// Slot at which the upgrade was staged
+    pub staged_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Length of the buffer account when the upgrade was staged
+    pub buffer_len: u64,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 24],
+}
+
+impl StagedUpgrade {
+    /// Size of the StagedUpgrade account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // buffer
+        32 + // program_hash
+        32 + // staged_by
+        8 + // staged_slot
+        1 + // bump
+        8 + // buffer_len
+        24; // reserved
+
+    /// Create a new staged upgrade record
+    pub fn new(
+        market: Pubkey,
+        buffer: Pubkey,
+        program_hash: [u8; 32],
+        staged_by: Pubkey,
+        staged_slot: u64,
+        bump: u8,
+        buffer_len: u64,
+    ) -> Result<Self> {
+        if program_hash == [0; 32] {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+
+        Ok(Self {
+            version: PROGRAM_VERSION,
+            market,
+            buffer,
+            program_hash,
+            staged_by,
+            staged_slot,
+            bump,
+            buffer_len,
+            reserved: [0; 24],
+        })
+    }
+
+    /// Write authority recorded in a loader buffer's metadata
+    pub fn buffer_authority(buffer_data: &[u8]) -> Result<Option<Pubkey>> {
+        let metadata = buffer_data
+            .get(..UpgradeableLoaderState::size_of_buffer_metadata())
+            .ok_or(LendingError::InvalidAccount)?;
+
+        // Bincode layout: u32 variant index (1 = Buffer), then Option<Pubkey>
+        if metadata[..4] != 1u32.to_le_bytes() {
+            return Err(LendingError::InvalidAccount.into());
+        }
+        match metadata[4] {
+            0 => Ok(None),
+            1 => Ok(Some(Pubkey::new_from_array(
+                metadata[5..37]
+                    .try_into()
+                    .map_err(|_| LendingError::InvalidAccount)?,
+            ))),
+            _ => Err(LendingError::InvalidAccount.into()),
+        }
+    }
+
+    /// Verify that a buffer account is still the one staged
+    /// Only the buffer metadata is read, so the cost does not grow with the program size.
+    /// While `staged_by` holds the write authority nobody else can write to the buffer,
+    /// and a write that changed the program's size would show in the length.
+    pub fn verify_buffer(&self, buffer: &AccountInfo) -> Result<()> {
+        if buffer.key() != self.buffer {
+            return Err(LendingError::UpgradeNotStaged.into());
+        }
+        if buffer.owner != &bpf_loader_upgradeable::id() {
+            return Err(LendingError::InvalidAccountOwner.into());
+        }
+
+        let data = buffer.try_borrow_data()?;
+        if Self::buffer_authority(&data)? != Some(self.staged_by) {
+            return Err(LendingError::UpgradeBufferAuthorityMismatch.into());
+        }
+        if data.len() as u64 != self.buffer_len {
+            return Err(LendingError::UpgradeBufferChanged.into());
+        }
+        Ok(())
+    }
+}
+
+/// Parameters for staging a program upgrade
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct StageUpgradeParams {
+    pub program_hash: [u8; 32],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Loader buffer with write authority `authority` holding `program_len` bytes
+    fn buffer_data(authority: Option<Pubkey>, program_len: usize) -> Vec<u8> {
+        let mut data = 1u32.to_le_bytes().to_vec();
+        match authority {
+            Some(authority) => {
+                data.push(1);
+                data.extend_from_slice(authority.as_ref());
+            }
+            None => data.extend_from_slice(&[0; 33]),
+        }
+        data.resize(
+            UpgradeableLoaderState::size_of_buffer_metadata() + program_len,
+            0x7f,
+        );
+        data
+    }
+
+    #[test]
+    fn test_program_sized_buffer_is_verified_from_metadata() {
+        // A program at the loader's size limit
+        let program_len = 10 * 1024 * 1024;
+        let governance = Pubkey::new_unique();
+        let buffer_key = Pubkey::new_unique();
+        let loader = bpf_loader_upgradeable::id();
+        let mut data = buffer_data(Some(governance), program_len);
+        let mut lamports = 0;
+
+        let staged = StagedUpgrade::new(
+            Pubkey::new_unique(),
+            buffer_key,
+            [1; 32],
+            governance,
+            50,
+            255,
+            data.len() as u64,
+        )
+        .unwrap();
+
+        let buffer = AccountInfo::new(
+            &buffer_key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &loader,
+            false,
+            0,
+        );
+        staged.verify_buffer(&buffer).unwrap();
+
+        // Another buffer address was not staged
+        let other = StagedUpgrade {
+            buffer: Pubkey::new_unique(),
+            ..staged.clone()
+        };
+        assert!(other.verify_buffer(&buffer).is_err());
+
+        // A buffer that grew or shrank since staging no longer matches
+        let resized = StagedUpgrade {
+            buffer_len: staged.buffer_len - 1,
+            ..staged.clone()
+        };
+        assert!(resized.verify_buffer(&buffer).is_err());
+    }
+
+    #[test]
+    fn test_buffer_must_stay_under_governance_authority() {
+        let governance = Pubkey::new_unique();
+        assert_eq!(
+            StagedUpgrade::buffer_authority(&buffer_data(Some(governance), 16)).unwrap(),
+            Some(governance)
+        );
+
+        // Writable by a deployer key, or by nobody once finalized
+        let deployer = buffer_data(Some(Pubkey::new_unique()), 16);
+        assert_ne!(
+            StagedUpgrade::buffer_authority(&deployer).unwrap(),
+            Some(governance)
+        );
+        assert_eq!(
+            StagedUpgrade::buffer_authority(&buffer_data(None, 16)).unwrap(),
+            None
+        );
+
+        // Program data and truncated accounts are not buffers
+        let mut program_data = buffer_data(Some(governance), 16);
+        program_data[0] = 3;
+        assert!(StagedUpgrade::buffer_authority(&program_data).is_err());
+        assert!(StagedUpgrade::buffer_authority(&[1, 0, 0, 0]).is_err());
+    }
+}
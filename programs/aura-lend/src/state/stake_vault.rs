@@ -0,0 +1,328 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+
+/// Staking wrapper around a reserve's aTokens
+/// Stakers lock aTokens in the vault for shares recorded on their `StakePosition`.
+/// aTokens already accrue supply interest through the exchange rate, and liquidity
+/// paid into the vault's compound account (e.g. incentives in the underlying asset) is
+/// deposited back into the reserve, so each share is worth a growing amount of
+/// aTokens. Leaving requires a cooldown.
+#[account]
+pub struct StakeVault {
+    /// Version of the stake vault account structure
+    pub version: u8,
+
+    /// Market this vault belongs to
+    pub market: Pubkey,
+
+    /// Reserve whose aTokens are staked
+    pub reserve: Pubkey,
+
+    /// Token account holding the staked aTokens
+    pub vault_collateral: Pubkey,
+
+    /// Token account collecting liquidity to compound into the vault
+    pub compound_liquidity: Pubkey,
+
+    /// aTokens backing the shares (direct transfers to the vault are not counted)
+    pub total_collateral: u64,
+
+    /// Shares outstanding across all positions
+    pub total_shares: u64,
+
+    /// Seconds between requesting an unstake and being able to execute it
+    pub cooldown_seconds: i64,
+
+    /// Total aTokens added by compounding
+    pub total_compounded: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 64],
+}
+
+impl StakeVault {
+    /// Size of the StakeVault account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // reserve
+        32 + // vault_collateral
+        32 + // compound_liquidity
+        8 + // total_collateral
+        8 + // total_shares
+        8 + // cooldown_seconds
+        8 + // total_compounded
+        1 + // bump
+        64; // reserved
+
+    /// Create an empty vault
+    pub fn new(
+        market: Pubkey,
+        reserve: Pubkey,
+        vault_collateral: Pubkey,
+        compound_liquidity: Pubkey,
+        cooldown_seconds: i64,
+        bump: u8,
+    ) -> Result<Self> {
+        if !(0..=MAX_STAKE_COOLDOWN_SECONDS).contains(&cooldown_seconds) {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+
+        Ok(Self {
+            version: PROGRAM_VERSION,
+            market,
+            reserve,
+            vault_collateral,
+            compound_liquidity,
+            total_collateral: 0,
+            total_shares: 0,
+            cooldown_seconds,
+            total_compounded: 0,
+            bump,
+            reserved: [0; 64],
+        })
+    }
+
+    /// Shares minted for staking `collateral` aTokens
+    /// Virtual shares keep the first staker from inflating the share price against
+    /// later ones.
+    pub fn shares_for_collateral(&self, collateral: u64) -> Result<u64> {
+        let shares = (collateral as u128)
+            .checked_mul(self.total_shares as u128 + STAKE_VAULT_VIRTUAL_SHARES as u128)
+            .ok_or(LendingError::MathOverflow)?
+            / (self.total_collateral as u128 + 1);
+        u64::try_from(shares).map_err(|_| LendingError::MathOverflow.into())
+    }
+
+    /// aTokens `shares` are worth
+    pub fn collateral_for_shares(&self, shares: u64) -> Result<u64> {
+        let collateral = (shares as u128)
+            .checked_mul(self.total_collateral as u128 + 1)
+            .ok_or(LendingError::MathOverflow)?
+            / (self.total_shares as u128 + STAKE_VAULT_VIRTUAL_SHARES as u128);
+        u64::try_from(collateral).map_err(|_| LendingError::MathOverflow.into())
+    }
+
+    /// Record `collateral` aTokens staked for `shares`
+    pub fn record_stake(&mut self, collateral: u64, shares: u64) -> Result<()> {
+        self.total_collateral = self
+            .total_collateral
+            .checked_add(collateral)
+            .ok_or(LendingError::MathOverflow)?;
+        self.total_shares = self
+            .total_shares
+            .checked_add(shares)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Record `shares` redeemed for `collateral` aTokens
+    pub fn record_unstake(&mut self, collateral: u64, shares: u64) -> Result<()> {
+        self.total_collateral = self
+            .total_collateral
+            .checked_sub(collateral)
+            .ok_or(LendingError::MathUnderflow)?;
+        self.total_shares = self
+            .total_shares
+            .checked_sub(shares)
+            .ok_or(LendingError::MathUnderflow)?;
+        Ok(())
+    }
+
+    /// Record aTokens added by compounding, raising the value of every share
+    pub fn record_compound(&mut self, collateral: u64) -> Result<()> {
+        self.total_collateral = self
+            .total_collateral
+            .checked_add(collateral)
+            .ok_or(LendingError::MathOverflow)?;
+        self.total_compounded = self
+            .total_compounded
+            .checked_add(collateral)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+/// A staker's receipt in a stake vault
+/// The receipt is a PDA of the vault and owner rather than a token, so it cannot be
+/// transferred; integrations read its active shares for governance weight and
+/// reward boosts.
+#[account]
+pub struct StakePosition {
+    /// Version of the stake position account structure
+    pub version: u8,
+
+    /// Vault the shares are in
+    pub vault: Pubkey,
+
+    /// Staker
+    pub owner: Pubkey,
+
+    /// Shares held, including those pending unstake
+    pub shares: u64,
+
+    /// Shares requested for unstaking
+    pub pending_unstake_shares: u64,
+
+    /// Time from which the pending shares may be unstaked
+    pub cooldown_end: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl StakePosition {
+    /// Size of the StakePosition account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // vault
+        32 + // owner
+        8 + // shares
+        8 + // pending_unstake_shares
+        8 + // cooldown_end
+        1 + // bump
+        32; // reserved
+
+    /// Create an empty position
+    pub fn new(vault: Pubkey, owner: Pubkey, bump: u8) -> Self {
+        Self {
+            version: PROGRAM_VERSION,
+            vault,
+            owner,
+            shares: 0,
+            pending_unstake_shares: 0,
+            cooldown_end: 0,
+            bump,
+            reserved: [0; 32],
+        }
+    }
+
+    /// Shares not pending unstake; these count for governance weight and boosts
+    pub fn active_shares(&self) -> u64 {
+        self.shares.saturating_sub(self.pending_unstake_shares)
+    }
+
+    /// Record newly staked shares
+    pub fn add_shares(&mut self, shares: u64) -> Result<()> {
+        self.shares = self
+            .shares
+            .checked_add(shares)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Check if the pending request was not executed within its unstake window
+    pub fn is_unstake_lapsed(&self, now: i64) -> bool {
+        self.pending_unstake_shares > 0
+            && now
+                >= self
+                    .cooldown_end
+                    .saturating_add(STAKE_UNSTAKE_WINDOW_SECONDS)
+    }
+
+    /// Start the cooldown for `shares` more active shares
+    /// Adding to a pending request restarts its cooldown; a lapsed request is dropped
+    /// first, returning its shares to active.
+    pub fn request_unstake(&mut self, shares: u64, now: i64, cooldown_seconds: i64) -> Result<()> {
+        if self.is_unstake_lapsed(now) {
+            self.pending_unstake_shares = 0;
+        }
+        if shares == 0 || shares > self.active_shares() {
+            return Err(LendingError::InsufficientStakeShares.into());
+        }
+        self.pending_unstake_shares += shares;
+        self.cooldown_end = now
+            .checked_add(cooldown_seconds)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Remove the pending shares once their cooldown has passed and return them
+    /// The request lapses if not executed within `STAKE_UNSTAKE_WINDOW_SECONDS`; the
+    /// shares must then be requested again.
+    pub fn take_unstakeable(&mut self, now: i64) -> Result<u64> {
+        if self.pending_unstake_shares == 0 {
+            return Err(LendingError::NoUnstakeRequest.into());
+        }
+        if now < self.cooldown_end {
+            return Err(LendingError::StakeCooldownActive.into());
+        }
+        if self.is_unstake_lapsed(now) {
+            return Err(LendingError::StakeUnstakeWindowExpired.into());
+        }
+
+        let shares = std::mem::take(&mut self.pending_unstake_shares);
+        self.shares -= shares;
+        Ok(shares)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_vault() -> StakeVault {
+        StakeVault::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            86_400,
+            255,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_compounding_raises_share_value() {
+        let mut vault = test_vault();
+        let shares = vault.shares_for_collateral(1_000_000).unwrap();
+        vault.record_stake(1_000_000, shares).unwrap();
+        assert_eq!(vault.collateral_for_shares(shares).unwrap(), 1_000_000);
+
+        vault.record_compound(500_000).unwrap();
+        let value = vault.collateral_for_shares(shares).unwrap();
+        assert!(value > 1_499_000 && value <= 1_500_000);
+
+        // A later staker buys in at the compounded price
+        let later = vault.shares_for_collateral(1_500_000).unwrap();
+        assert!(later < shares + shares / 1_000);
+
+        // A 1-aToken first stake followed by a donation cannot zero out the next staker
+        let mut vault = test_vault();
+        vault
+            .record_stake(1, vault.shares_for_collateral(1).unwrap())
+            .unwrap();
+        vault.record_compound(1_000_000).unwrap();
+        assert!(vault.shares_for_collateral(1_000_000).unwrap() > 0);
+    }
+
+    #[test]
+    fn test_unstake_requires_cooldown_within_window() {
+        let mut position = StakePosition::new(Pubkey::new_unique(), Pubkey::new_unique(), 255);
+        position.add_shares(1_000).unwrap();
+        assert!(position.request_unstake(1_001, 0, 100).is_err());
+        position.request_unstake(400, 0, 100).unwrap();
+        assert_eq!(position.active_shares(), 600);
+
+        assert!(position.take_unstakeable(99).is_err());
+        assert_eq!(position.take_unstakeable(100).unwrap(), 400);
+        assert_eq!(position.shares, 600);
+        assert!(position.take_unstakeable(100).is_err());
+
+        // A lapsed request must be made again, restarting the cooldown
+        position.request_unstake(600, 1_000, 100).unwrap();
+        let lapsed_at = 1_100 + STAKE_UNSTAKE_WINDOW_SECONDS;
+        assert!(position.take_unstakeable(lapsed_at).is_err());
+        position.request_unstake(600, lapsed_at, 100).unwrap();
+        assert_eq!(position.pending_unstake_shares, 600);
+        assert_eq!(position.take_unstakeable(lapsed_at + 100).unwrap(), 600);
+    }
+}
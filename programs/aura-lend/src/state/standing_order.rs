@@ -0,0 +1,139 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::utils::math::Decimal;
+use anchor_lang::prelude::*;
+
+/// A borrower's standing order to deleverage their obligation once it gets risky
+/// While the obligation's health factor is below `trigger_health_bps`, any keeper may
+/// execute the order: the keeper repays up to `max_repay_amount` of the borrow in
+/// `repay_reserve` and receives collateral from `withdraw_reserve` worth the repayment
+/// plus `tip_bps`. It is a keeper-funded repay-with-collateral that the borrower
+/// authorized in advance, so it can run before the position becomes liquidatable.
+#[account]
+pub struct StandingOrder {
+    /// Version of the standing order account structure
+    pub version: u8,
+
+    /// Obligation the order deleverages
+    pub obligation: Pubkey,
+
+    /// Owner of the obligation who placed the order
+    pub owner: Pubkey,
+
+    /// Reserve whose debt is repaid
+    pub repay_reserve: Pubkey,
+
+    /// Reserve whose collateral pays for the repayment
+    pub withdraw_reserve: Pubkey,
+
+    /// Health factor (basis points) below which the order may be executed
+    pub trigger_health_bps: u64,
+
+    /// Most liquidity a single execution may repay
+    pub max_repay_amount: u64,
+
+    /// Keeper tip on the repaid value, paid in collateral (basis points)
+    pub tip_bps: u16,
+
+    /// Slot the order was placed
+    pub created_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl StandingOrder {
+    /// Size of the StandingOrder account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // obligation
+        32 + // owner
+        32 + // repay_reserve
+        32 + // withdraw_reserve
+        8 + // trigger_health_bps
+        8 + // max_repay_amount
+        2 + // tip_bps
+        8 + // created_slot
+        1 + // bump
+        32; // reserved
+
+    /// Check the order's terms
+    /// The trigger must sit above the liquidation threshold (a health factor of 1.0),
+    /// since below it the obligation is liquidated instead, and the tip is capped.
+    pub fn validate(&self) -> Result<()> {
+        if self.repay_reserve == self.withdraw_reserve {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+        if self.trigger_health_bps <= BASIS_POINTS_PRECISION {
+            return Err(LendingError::ConfigurationOutOfRange.into());
+        }
+        if self.max_repay_amount == 0 {
+            return Err(LendingError::AmountTooSmall.into());
+        }
+        if self.tip_bps > MAX_STANDING_ORDER_TIP_BPS {
+            return Err(LendingError::ConfigurationOutOfRange.into());
+        }
+        Ok(())
+    }
+
+    /// Whether an obligation at `health_factor` may be deleveraged by the order
+    pub fn is_triggered(&self, health_factor: Decimal) -> bool {
+        health_factor.to_health_bps_saturating() < self.trigger_health_bps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_order() -> StandingOrder {
+        StandingOrder {
+            version: PROGRAM_VERSION,
+            obligation: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            repay_reserve: Pubkey::new_unique(),
+            withdraw_reserve: Pubkey::new_unique(),
+            trigger_health_bps: 12_000,
+            max_repay_amount: 1_000,
+            tip_bps: 100,
+            created_slot: 100,
+            bump: 255,
+            reserved: [0; 32],
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        test_order().validate().unwrap();
+
+        // The trigger must be above the point where liquidations take over
+        let mut order = test_order();
+        order.trigger_health_bps = BASIS_POINTS_PRECISION;
+        assert!(order.validate().is_err());
+
+        let mut order = test_order();
+        order.tip_bps = MAX_STANDING_ORDER_TIP_BPS + 1;
+        assert!(order.validate().is_err());
+
+        let mut order = test_order();
+        order.max_repay_amount = 0;
+        assert!(order.validate().is_err());
+
+        let mut order = test_order();
+        order.withdraw_reserve = order.repay_reserve;
+        assert!(order.validate().is_err());
+    }
+
+    #[test]
+    fn test_is_triggered() {
+        let order = test_order();
+        let health = |bps: u128| Decimal::from_scaled_val(bps * 100_000_000_000_000);
+
+        assert!(order.is_triggered(health(11_900)));
+        assert!(!order.is_triggered(health(12_000)));
+        assert!(!order.is_triggered(health(15_000)));
+    }
+}
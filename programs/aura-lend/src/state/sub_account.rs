@@ -0,0 +1,339 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::state::obligation::Obligation;
+use crate::state::reserve::bps_to_decimal;
+use crate::utils::math::Decimal;
+use anchor_lang::prelude::*;
+
+/// Side of an obligation a position sits on
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PositionKind {
+    /// Collateral deposit
+    Deposit,
+    /// Liquidity borrow
+    Borrow,
+}
+
+/// Share of one obligation position assigned to a non-default sub-account
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubAccountAllocation {
+    /// Sub-account holding the share (never the default sub-account)
+    pub sub_account: u8,
+
+    /// Reserve of the position
+    pub reserve: Pubkey,
+
+    /// Deposit or borrow
+    pub kind: PositionKind,
+
+    /// Share of the position in basis points
+    pub share_bps: u16,
+}
+
+impl SubAccountAllocation {
+    /// Serialized size of an allocation
+    pub const SIZE: usize = 1 + // sub_account
+        32 + // reserve
+        1 + // kind
+        2; // share_bps
+}
+
+/// Collateral and debt attributed to one sub-account
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubAccountHealth {
+    /// Sub-account the figures belong to
+    pub sub_account: u8,
+
+    /// Collateral value weighted by liquidation thresholds
+    pub weighted_collateral_usd: Decimal,
+
+    /// Borrowed value
+    pub borrowed_value_usd: Decimal,
+}
+
+impl SubAccountHealth {
+    /// Whether the sub-account could not be liquidated on its own
+    pub fn is_healthy(&self) -> bool {
+        self.borrowed_value_usd.is_zero() || self.weighted_collateral_usd >= self.borrowed_value_usd
+    }
+}
+
+/// Labeled sub-accounts grouping the positions of a single obligation
+/// Each deposit and borrow is split across sub-accounts in basis point shares, with
+/// whatever is not assigned elsewhere belonging to the default sub-account 0. Shares
+/// scale with the position, so interest, deposits and withdrawals on the obligation
+/// apply pro rata to every sub-account. Health is computed per sub-account from the
+/// obligation's refreshed market values, and moving a share requires both sides to
+/// stay healthy. Obligation-wide operations still check the aggregate health.
+#[account]
+pub struct ObligationSubAccounts {
+    /// Version of the sub-account record structure
+    pub version: u8,
+
+    /// Obligation the sub-accounts partition
+    pub obligation: Pubkey,
+
+    /// User-chosen labels, indexed by sub-account id
+    pub labels: [[u8; SUB_ACCOUNT_LABEL_LEN]; MAX_SUB_ACCOUNTS],
+
+    /// Position shares assigned away from the default sub-account
+    pub allocations: Vec<SubAccountAllocation>,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl ObligationSubAccounts {
+    /// Size of the ObligationSubAccounts account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // obligation
+        SUB_ACCOUNT_LABEL_LEN * MAX_SUB_ACCOUNTS + // labels
+        4 + SubAccountAllocation::SIZE * MAX_SUB_ACCOUNT_ALLOCATIONS + // allocations
+        1 + // bump
+        32; // reserved
+
+    /// Create a record with every position in the default sub-account
+    pub fn new(obligation: Pubkey, bump: u8) -> Self {
+        Self {
+            version: PROGRAM_VERSION,
+            obligation,
+            labels: [[0; SUB_ACCOUNT_LABEL_LEN]; MAX_SUB_ACCOUNTS],
+            allocations: Vec::new(),
+            bump,
+            reserved: [0; 32],
+        }
+    }
+
+    /// Label a sub-account
+    pub fn set_label(&mut self, sub_account: u8, label: [u8; SUB_ACCOUNT_LABEL_LEN]) -> Result<()> {
+        Self::validate_id(sub_account)?;
+        self.labels[sub_account as usize] = label;
+        Ok(())
+    }
+
+    /// Share of a position held by a sub-account
+    pub fn share_bps(&self, sub_account: u8, reserve: &Pubkey, kind: PositionKind) -> u16 {
+        let assigned = self
+            .allocations
+            .iter()
+            .filter(|allocation| allocation.reserve == *reserve && allocation.kind == kind);
+
+        if sub_account == DEFAULT_SUB_ACCOUNT {
+            let elsewhere: u16 = assigned.map(|allocation| allocation.share_bps).sum();
+            (BASIS_POINTS_PRECISION as u16).saturating_sub(elsewhere)
+        } else {
+            assigned
+                .filter(|allocation| allocation.sub_account == sub_account)
+                .map(|allocation| allocation.share_bps)
+                .sum()
+        }
+    }
+
+    /// Move `share_bps` of a position from one sub-account to another
+    pub fn move_share(
+        &mut self,
+        from: u8,
+        to: u8,
+        reserve: Pubkey,
+        kind: PositionKind,
+        share_bps: u16,
+    ) -> Result<()> {
+        Self::validate_id(from)?;
+        Self::validate_id(to)?;
+        if from == to || share_bps == 0 {
+            return Err(LendingError::SubAccountInvalid.into());
+        }
+        if self.share_bps(from, &reserve, kind) < share_bps {
+            return Err(LendingError::SubAccountShareInsufficient.into());
+        }
+
+        if from != DEFAULT_SUB_ACCOUNT {
+            let index = self
+                .position(from, &reserve, kind)
+                .ok_or(LendingError::SubAccountShareInsufficient)?;
+            self.allocations[index].share_bps -= share_bps;
+            if self.allocations[index].share_bps == 0 {
+                self.allocations.remove(index);
+            }
+        }
+
+        if to != DEFAULT_SUB_ACCOUNT {
+            match self.position(to, &reserve, kind) {
+                Some(index) => self.allocations[index].share_bps += share_bps,
+                None => {
+                    if self.allocations.len() >= MAX_SUB_ACCOUNT_ALLOCATIONS {
+                        return Err(LendingError::SubAccountAllocationsFull.into());
+                    }
+                    self.allocations.push(SubAccountAllocation {
+                        sub_account: to,
+                        reserve,
+                        kind,
+                        share_bps,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Drop allocations of positions the obligation no longer holds
+    pub fn prune(&mut self, obligation: &Obligation) {
+        self.allocations.retain(|allocation| match allocation.kind {
+            PositionKind::Deposit => obligation
+                .find_collateral_deposit(&allocation.reserve)
+                .is_some(),
+            PositionKind::Borrow => obligation
+                .find_liquidity_borrow(&allocation.reserve)
+                .is_some(),
+        });
+    }
+
+    /// Collateral and debt attributed to a sub-account at the obligation's market values
+    pub fn health(&self, obligation: &Obligation, sub_account: u8) -> Result<SubAccountHealth> {
+        Self::validate_id(sub_account)?;
+
+        let mut weighted_collateral_usd = Decimal::zero();
+        for deposit in &obligation.deposits {
+            let share =
+                self.share_bps(sub_account, &deposit.deposit_reserve, PositionKind::Deposit);
+            let weighted = deposit
+                .market_value_usd
+                .try_mul(bps_to_decimal(share as u64)?)?
+                .try_mul(bps_to_decimal(deposit.liquidation_threshold_bps)?)?;
+            weighted_collateral_usd = weighted_collateral_usd.try_add(weighted)?;
+        }
+
+        let mut borrowed_value_usd = Decimal::zero();
+        for borrow in &obligation.borrows {
+            let share = self.share_bps(sub_account, &borrow.borrow_reserve, PositionKind::Borrow);
+            let value = borrow
+                .market_value_usd
+                .try_mul(bps_to_decimal(share as u64)?)?;
+            borrowed_value_usd = borrowed_value_usd.try_add(value)?;
+        }
+
+        Ok(SubAccountHealth {
+            sub_account,
+            weighted_collateral_usd,
+            borrowed_value_usd,
+        })
+    }
+
+    /// Index of the allocation a non-default sub-account holds in a position
+    fn position(&self, sub_account: u8, reserve: &Pubkey, kind: PositionKind) -> Option<usize> {
+        self.allocations.iter().position(|allocation| {
+            allocation.sub_account == sub_account
+                && allocation.reserve == *reserve
+                && allocation.kind == kind
+        })
+    }
+
+    fn validate_id(sub_account: u8) -> Result<()> {
+        if sub_account as usize >= MAX_SUB_ACCOUNTS {
+            return Err(LendingError::SubAccountInvalid.into());
+        }
+        Ok(())
+    }
+}
+
+/// Parameters for moving a position share between sub-accounts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MoveSubAccountPositionParams {
+    pub from_sub_account: u8,
+    pub to_sub_account: u8,
+    pub reserve: Pubkey,
+    pub kind: PositionKind,
+    pub share_bps: u16,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::obligation::{ObligationCollateral, ObligationLiquidity};
+
+    fn usd(value: u64) -> Decimal {
+        Decimal::from_integer(value).unwrap()
+    }
+
+    #[test]
+    fn test_health_is_isolated_per_sub_account() {
+        let collateral_reserve = Pubkey::new_unique();
+        let borrow_reserve = Pubkey::new_unique();
+
+        let mut obligation = Obligation {
+            version: PROGRAM_VERSION,
+            market: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            deposits: Vec::new(),
+            borrows: Vec::new(),
+            deposited_value_usd: Decimal::zero(),
+            borrowed_value_usd: Decimal::zero(),
+            last_update_timestamp: 0,
+            last_update_slot: 0,
+            liquidation_snapshot_health_factor: None,
+            unhealthy_since_slot: 0,
+            emode_category: 0,
+            isolation_mode: false,
+            credit_history: Default::default(),
+            refreshed_slot: 0,
+            liquidation_grace_slots: 0,
+            liquidation_grace_floor_bps: 0,
+            obligation_index: 0,
+            linked_accounts: 0,
+            reserved: [0; 53],
+        };
+        obligation.deposits.push(ObligationCollateral {
+            deposit_reserve: collateral_reserve,
+            deposited_amount: 1_000,
+            market_value_usd: usd(1_000),
+            ltv_bps: 7500,
+            liquidation_threshold_bps: 8000,
+        });
+        obligation.borrows.push(ObligationLiquidity {
+            borrow_reserve,
+            market_value_usd: usd(600),
+            ..ObligationLiquidity::default()
+        });
+
+        let mut sub_accounts = ObligationSubAccounts::new(Pubkey::new_unique(), 255);
+
+        // Half the collateral and a quarter of the debt move to sub-account 1
+        sub_accounts
+            .move_share(0, 1, collateral_reserve, PositionKind::Deposit, 5000)
+            .unwrap();
+        sub_accounts
+            .move_share(0, 1, borrow_reserve, PositionKind::Borrow, 2500)
+            .unwrap();
+
+        let default = sub_accounts.health(&obligation, 0).unwrap();
+        assert_eq!(default.weighted_collateral_usd, usd(400));
+        assert_eq!(default.borrowed_value_usd, usd(450));
+        assert!(!default.is_healthy());
+
+        let isolated = sub_accounts.health(&obligation, 1).unwrap();
+        assert_eq!(isolated.weighted_collateral_usd, usd(400));
+        assert_eq!(isolated.borrowed_value_usd, usd(150));
+        assert!(isolated.is_healthy());
+
+        // Moving the share back removes the allocation entirely
+        sub_accounts
+            .move_share(1, 0, borrow_reserve, PositionKind::Borrow, 2500)
+            .unwrap();
+        assert_eq!(sub_accounts.allocations.len(), 1);
+        assert!(sub_accounts
+            .move_share(1, 2, collateral_reserve, PositionKind::Deposit, 5001)
+            .is_err());
+        assert!(sub_accounts
+            .move_share(0, MAX_SUB_ACCOUNTS as u8, borrow_reserve, PositionKind::Borrow, 1)
+            .is_err());
+
+        obligation.deposits.clear();
+        sub_accounts.prune(&obligation);
+        assert!(sub_accounts.allocations.is_empty());
+    }
+}
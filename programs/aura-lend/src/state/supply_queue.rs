@@ -0,0 +1,305 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+
+/// First-in, first-out queue of deposits and withdrawals blocked by a reserve's caps
+/// One queue exists per reserve. A queued deposit escrows liquidity until the supply cap
+/// has room for it; a queued withdrawal escrows collateral (aTokens) until the withdraw
+/// utilization cap lets it out. `process_queue` fills entries as capacity opens, and
+/// the owner claims the aTokens or liquidity they were filled with.
+#[account]
+pub struct SupplyQueue {
+    /// Version of the supply queue account structure
+    pub version: u8,
+
+    /// Market this queue belongs to
+    pub market: Pubkey,
+
+    /// Reserve whose caps the queue waits on
+    pub reserve: Pubkey,
+
+    /// Escrow token account holding queued deposits and filled withdrawals
+    pub liquidity_escrow: Pubkey,
+
+    /// Escrow token account holding queued withdrawals and filled deposits
+    pub collateral_escrow: Pubkey,
+
+    /// Identifier assigned to the next entry
+    pub next_entry_id: u64,
+
+    /// Queued entries, oldest first
+    pub entries: Vec<SupplyQueueEntry>,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 64],
+}
+
+impl SupplyQueue {
+    /// Size of the SupplyQueue account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // reserve
+        32 + // liquidity_escrow
+        32 + // collateral_escrow
+        8 + // next_entry_id
+        4 + (MAX_SUPPLY_QUEUE_ENTRIES * SupplyQueueEntry::SIZE) + // entries
+        1 + // bump
+        64; // reserved
+
+    /// Create a new, empty queue
+    pub fn new(
+        market: Pubkey,
+        reserve: Pubkey,
+        liquidity_escrow: Pubkey,
+        collateral_escrow: Pubkey,
+        bump: u8,
+    ) -> Self {
+        Self {
+            version: PROGRAM_VERSION,
+            market,
+            reserve,
+            liquidity_escrow,
+            collateral_escrow,
+            next_entry_id: 0,
+            entries: Vec::new(),
+            bump,
+            reserved: [0; 64],
+        }
+    }
+
+    /// Queue a deposit of liquidity or a withdrawal of collateral, returning its id
+    pub fn enqueue(
+        &mut self,
+        owner: Pubkey,
+        kind: SupplyQueueKind,
+        amount: u64,
+        slot: u64,
+    ) -> Result<u64> {
+        if self.entries.len() >= MAX_SUPPLY_QUEUE_ENTRIES {
+            return Err(LendingError::SupplyQueueFull.into());
+        }
+        if amount == 0 {
+            return Err(LendingError::InvalidAmount.into());
+        }
+
+        let entry_id = self.next_entry_id;
+        self.next_entry_id = self
+            .next_entry_id
+            .checked_add(1)
+            .ok_or(LendingError::MathOverflow)?;
+
+        self.entries.push(SupplyQueueEntry {
+            entry_id,
+            owner,
+            kind,
+            remaining_amount: amount,
+            filled_amount: 0,
+            queued_slot: slot,
+        });
+
+        Ok(entry_id)
+    }
+
+    /// Find an entry by id
+    pub fn find_entry(&self, entry_id: u64) -> Option<&SupplyQueueEntry> {
+        self.entries.iter().find(|e| e.entry_id == entry_id)
+    }
+
+    /// Index of the oldest entry of `kind` that is not yet completely filled
+    pub fn next_unfilled(&self, kind: SupplyQueueKind) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|e| e.kind == kind && e.remaining_amount > 0)
+    }
+
+    /// Record that `consumed` of an entry's escrowed amount filled into `produced`
+    pub fn record_fill(&mut self, index: usize, consumed: u64, produced: u64) -> Result<()> {
+        let entry = &mut self.entries[index];
+        entry.remaining_amount = entry
+            .remaining_amount
+            .checked_sub(consumed)
+            .ok_or(LendingError::MathUnderflow)?;
+        entry.filled_amount = entry
+            .filled_amount
+            .checked_add(produced)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Remove an entry owned by `owner`, returning it so both escrowed amounts can be refunded
+    pub fn cancel_entry(&mut self, entry_id: u64, owner: &Pubkey) -> Result<SupplyQueueEntry> {
+        let index = self.owned_entry_index(entry_id, owner)?;
+        Ok(self.entries.remove(index))
+    }
+
+    /// Take the filled amount of an entry owned by `owner`
+    /// Completely filled entries are removed from the queue
+    pub fn claim_fills(&mut self, entry_id: u64, owner: &Pubkey) -> Result<(SupplyQueueKind, u64)> {
+        let index = self.owned_entry_index(entry_id, owner)?;
+        let entry = &mut self.entries[index];
+        let filled = entry.filled_amount;
+        let kind = entry.kind;
+        entry.filled_amount = 0;
+
+        if entry.remaining_amount == 0 {
+            self.entries.remove(index);
+        }
+
+        Ok((kind, filled))
+    }
+
+    fn owned_entry_index(&self, entry_id: u64, owner: &Pubkey) -> Result<usize> {
+        let index = self
+            .entries
+            .iter()
+            .position(|e| e.entry_id == entry_id)
+            .ok_or(LendingError::SupplyQueueEntryNotFound)?;
+
+        if self.entries[index].owner != *owner {
+            return Err(LendingError::InvalidAuthority.into());
+        }
+
+        Ok(index)
+    }
+}
+
+/// Whether a queue entry supplies or withdraws
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SupplyQueueKind {
+    /// Deposit of liquidity, filled with aTokens
+    #[default]
+    Deposit,
+    /// Withdrawal of collateral (aTokens), filled with liquidity
+    Withdraw,
+}
+
+/// A queued deposit or withdrawal
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct SupplyQueueEntry {
+    /// Unique entry identifier within the queue
+    pub entry_id: u64,
+
+    /// Owner of the entry
+    pub owner: Pubkey,
+
+    /// Deposit or withdrawal
+    pub kind: SupplyQueueKind,
+
+    /// Escrowed amount not yet filled (liquidity for deposits, aTokens for withdrawals)
+    pub remaining_amount: u64,
+
+    /// Amount filled and awaiting claim (aTokens for deposits, liquidity for withdrawals)
+    pub filled_amount: u64,
+
+    /// Slot at which the entry was queued
+    pub queued_slot: u64,
+}
+
+impl SupplyQueueEntry {
+    /// Serialized size of a queue entry
+    pub const SIZE: usize = 8 + // entry_id
+        32 + // owner
+        1 + // kind
+        8 + // remaining_amount
+        8 + // filled_amount
+        8; // queued_slot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_queue() -> SupplyQueue {
+        SupplyQueue::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            255,
+        )
+    }
+
+    #[test]
+    fn test_entries_fill_in_order_per_kind() {
+        let mut queue = test_queue();
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+
+        queue
+            .enqueue(alice, SupplyQueueKind::Withdraw, 500, 1)
+            .unwrap();
+        queue
+            .enqueue(alice, SupplyQueueKind::Deposit, 1_000, 2)
+            .unwrap();
+        queue
+            .enqueue(bob, SupplyQueueKind::Deposit, 600, 3)
+            .unwrap();
+
+        // The oldest deposit fills first, and partially filled entries stay at the front
+        assert_eq!(queue.next_unfilled(SupplyQueueKind::Deposit), Some(1));
+        queue.record_fill(1, 400, 390).unwrap();
+        assert_eq!(queue.next_unfilled(SupplyQueueKind::Deposit), Some(1));
+        queue.record_fill(1, 600, 585).unwrap();
+        assert_eq!(queue.next_unfilled(SupplyQueueKind::Deposit), Some(2));
+        assert_eq!(queue.next_unfilled(SupplyQueueKind::Withdraw), Some(0));
+
+        assert!(queue.record_fill(2, 601, 0).is_err());
+    }
+
+    #[test]
+    fn test_claim_and_cancel_require_owner() {
+        let mut queue = test_queue();
+        let owner = Pubkey::new_unique();
+        let id = queue
+            .enqueue(owner, SupplyQueueKind::Deposit, 1_000, 1)
+            .unwrap();
+        queue.record_fill(0, 400, 380).unwrap();
+
+        assert!(queue.claim_fills(id, &Pubkey::new_unique()).is_err());
+        assert_eq!(
+            queue.claim_fills(id, &owner).unwrap(),
+            (SupplyQueueKind::Deposit, 380)
+        );
+        assert_eq!(queue.find_entry(id).unwrap().filled_amount, 0);
+
+        let entry = queue.cancel_entry(id, &owner).unwrap();
+        assert_eq!((entry.remaining_amount, entry.filled_amount), (600, 0));
+        assert!(queue.entries.is_empty());
+    }
+
+    #[test]
+    fn test_filled_entries_leave_on_claim() {
+        let mut queue = test_queue();
+        let owner = Pubkey::new_unique();
+        let id = queue
+            .enqueue(owner, SupplyQueueKind::Withdraw, 500, 1)
+            .unwrap();
+        queue.record_fill(0, 500, 510).unwrap();
+
+        assert_eq!(
+            queue.claim_fills(id, &owner).unwrap(),
+            (SupplyQueueKind::Withdraw, 510)
+        );
+        assert!(queue.find_entry(id).is_none());
+    }
+
+    #[test]
+    fn test_queue_capacity() {
+        let mut queue = test_queue();
+        for slot in 0..MAX_SUPPLY_QUEUE_ENTRIES as u64 {
+            queue
+                .enqueue(Pubkey::new_unique(), SupplyQueueKind::Deposit, 1, slot)
+                .unwrap();
+        }
+        assert!(queue
+            .enqueue(Pubkey::new_unique(), SupplyQueueKind::Deposit, 1, 0)
+            .is_err());
+        assert!(test_queue()
+            .enqueue(Pubkey::new_unique(), SupplyQueueKind::Deposit, 0, 0)
+            .is_err());
+    }
+}
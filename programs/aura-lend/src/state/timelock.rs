@@ -1,6 +1,8 @@
 use crate::constants::*;
 use crate::error::LendingError;
+use crate::state::proposal_lifecycle::{ProposalLifecycle, ProposalStatus};
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
 
 /// Timelock controller for delayed execution of critical operations
 #[account]
@@ -32,7 +34,7 @@ impl TimelockController {
     pub const SIZE: usize = 8 + // discriminator
         1 + // version
         32 + // multisig
-        4 + (10 * std::mem::size_of::<TimelockDelay>()) + // min_delays (assume max 10 operation types)
+        4 + (16 * std::mem::size_of::<TimelockDelay>()) + // min_delays (assume max 16 operation types)
         4 + (Self::MAX_ACTIVE_PROPOSALS * 32) + // active_proposals
         8 + // created_at
         128; // reserved
@@ -55,6 +57,10 @@ impl TimelockController {
                 operation_type: TimelockOperationType::UpdateReserveConfig,
                 delay_seconds: TIMELOCK_DELAY_MEDIUM, // 1 day
             },
+            TimelockDelay {
+                operation_type: TimelockOperationType::UpdateReserveCaps,
+                delay_seconds: TIMELOCK_DELAY_LOW, // 6 hours
+            },
             TimelockDelay {
                 operation_type: TimelockOperationType::InitializeReserve,
                 delay_seconds: TIMELOCK_DELAY_LOW, // 6 hours
@@ -67,6 +73,10 @@ impl TimelockController {
                 operation_type: TimelockOperationType::WithdrawFees,
                 delay_seconds: TIMELOCK_DELAY_LOW, // 6 hours
             },
+            TimelockDelay {
+                operation_type: TimelockOperationType::WithdrawInsuranceFund,
+                delay_seconds: TIMELOCK_DELAY_HIGH, // 3 days
+            },
             // Program upgrade operations - critical delays
             TimelockDelay {
                 operation_type: TimelockOperationType::ProgramUpgrade,
@@ -85,6 +95,11 @@ impl TimelockController {
                 operation_type: TimelockOperationType::DataMigration,
                 delay_seconds: TIMELOCK_DELAY_HIGH, // 3 days
             },
+            // Arbitrary calls signed by the timelock - critical delay
+            TimelockDelay {
+                operation_type: TimelockOperationType::ExecuteCalls,
+                delay_seconds: TIMELOCK_DELAY_CRITICAL, // 7 days
+            },
         ];
 
         Ok(Self {
@@ -106,6 +121,28 @@ impl TimelockController {
             .unwrap_or(TIMELOCK_DELAY_DEFAULT)
     }
 
+    /// Get the delay of a proposal, which is at least the calls delay if it carries calls
+    /// Controllers created before calls existed have no `ExecuteCalls` entry and use the
+    /// critical delay for them.
+    pub fn get_proposal_delay(
+        &self,
+        operation_type: TimelockOperationType,
+        has_calls: bool,
+    ) -> u64 {
+        let delay = self.get_min_delay(operation_type);
+        if !has_calls {
+            return delay;
+        }
+
+        let calls_delay = self
+            .min_delays
+            .iter()
+            .find(|d| d.operation_type == TimelockOperationType::ExecuteCalls)
+            .map(|d| d.delay_seconds)
+            .unwrap_or(TIMELOCK_DELAY_CRITICAL);
+        delay.max(calls_delay)
+    }
+
     /// Add a proposal to active list
     pub fn add_active_proposal(&mut self, proposal: Pubkey) -> Result<()> {
         if self.active_proposals.len() >= Self::MAX_ACTIVE_PROPOSALS {
@@ -153,7 +190,7 @@ pub struct TimelockProposal {
     pub execution_time: i64,
 
     /// Status of the proposal
-    pub status: TimelockStatus,
+    pub status: ProposalStatus,
 
     /// Account that created this proposal
     pub proposer: Pubkey,
@@ -164,6 +201,9 @@ pub struct TimelockProposal {
     /// Hash of the operation data for validation
     pub operation_hash: [u8; 32],
 
+    /// Instructions invoked with the timelock PDA as signer when the proposal executes
+    pub calls: Vec<TimelockCall>,
+
     /// Reserved space for future upgrades
     pub reserved: [u8; 64],
 }
@@ -175,6 +215,12 @@ impl TimelockProposal {
     /// Maximum number of target accounts
     pub const MAX_TARGET_ACCOUNTS: usize = 10;
 
+    /// Maximum number of calls
+    pub const MAX_CALLS: usize = 4;
+
+    /// Maximum serialized size of all calls
+    pub const MAX_CALLS_SIZE: usize = 2048;
+
     /// Account size calculation
     pub const SIZE: usize = 8 + // discriminator
         1 + // version
@@ -187,6 +233,7 @@ impl TimelockProposal {
         32 + // proposer
         4 + (Self::MAX_TARGET_ACCOUNTS * 32) + // target_accounts
         32 + // operation_hash
+        Self::MAX_CALLS_SIZE + // calls
         64; // reserved
 
     /// Create a new timelock proposal
@@ -197,6 +244,7 @@ impl TimelockProposal {
         delay_seconds: u64,
         proposer: Pubkey,
         target_accounts: Vec<Pubkey>,
+        calls: Vec<TimelockCall>,
     ) -> Result<Self> {
         if instruction_data.len() > Self::MAX_INSTRUCTION_SIZE {
             return Err(LendingError::InstructionTooLarge.into());
@@ -206,6 +254,13 @@ impl TimelockProposal {
             return Err(LendingError::TooManyTargetAccounts.into());
         }
 
+        let encoded_calls = calls
+            .try_to_vec()
+            .map_err(|_| LendingError::InvalidInstruction)?;
+        if calls.len() > Self::MAX_CALLS || encoded_calls.len() > Self::MAX_CALLS_SIZE {
+            return Err(LendingError::InstructionTooLarge.into());
+        }
+
         let clock = Clock::get()?;
         let execution_time = clock
             .unix_timestamp
@@ -217,6 +272,7 @@ impl TimelockProposal {
         use std::hash::{Hash, Hasher};
         instruction_data.hash(&mut hasher);
         target_accounts.hash(&mut hasher);
+        encoded_calls.hash(&mut hasher);
         let operation_hash_u64 = hasher.finish();
         let mut operation_hash = [0u8; 32];
         operation_hash[0..8].copy_from_slice(&operation_hash_u64.to_le_bytes());
@@ -228,17 +284,26 @@ impl TimelockProposal {
             instruction_data,
             created_at: clock.unix_timestamp,
             execution_time,
-            status: TimelockStatus::Pending,
+            status: ProposalStatus::Active,
             proposer,
             target_accounts,
             operation_hash,
+            calls,
             reserved: [0; 64],
         })
     }
 
+    /// Veto the proposal while it is still waiting out its delay
+    pub fn veto(&mut self, now: i64) -> Result<()> {
+        if self.status != ProposalStatus::Active || now >= self.execution_time {
+            return Err(LendingError::VetoWindowClosed.into());
+        }
+        self.mark_cancelled()
+    }
+
     /// Check if proposal is ready for execution
     pub fn is_ready_for_execution(&self) -> Result<bool> {
-        if self.status != TimelockStatus::Pending {
+        if !matches!(self.status, ProposalStatus::Active | ProposalStatus::Ready) {
             return Ok(false);
         }
 
@@ -246,6 +311,14 @@ impl TimelockProposal {
         Ok(clock.unix_timestamp >= self.execution_time)
     }
 
+    /// Promote an active proposal to ready once its delay has elapsed at `now`
+    pub fn sync_delay(&mut self, now: i64) -> Result<()> {
+        if self.status == ProposalStatus::Active && now >= self.execution_time {
+            self.transition(ProposalStatus::Ready)?;
+        }
+        Ok(())
+    }
+
     /// Check if proposal is expired
     pub fn is_expired(&self) -> Result<bool> {
         let clock = Clock::get()?;
@@ -257,25 +330,19 @@ impl TimelockProposal {
 
         Ok(clock.unix_timestamp > expiry_time)
     }
+}
 
-    /// Mark proposal as executed
-    pub fn mark_executed(&mut self) -> Result<()> {
-        if self.status != TimelockStatus::Pending {
-            return Err(LendingError::ProposalNotPending.into());
-        }
-
-        self.status = TimelockStatus::Executed;
-        Ok(())
+impl ProposalLifecycle for TimelockProposal {
+    fn status(&self) -> ProposalStatus {
+        self.status
     }
 
-    /// Mark proposal as cancelled
-    pub fn mark_cancelled(&mut self) -> Result<()> {
-        if self.status != TimelockStatus::Pending {
-            return Err(LendingError::ProposalNotPending.into());
-        }
+    fn set_status(&mut self, status: ProposalStatus) {
+        self.status = status;
+    }
 
-        self.status = TimelockStatus::Cancelled;
-        Ok(())
+    fn expiry_time(&self) -> Option<i64> {
+        Some(self.execution_time.saturating_add(TIMELOCK_EXPIRY_PERIOD))
     }
 }
 
@@ -315,6 +382,12 @@ pub enum TimelockOperationType {
     FreezeProgram,
     /// Data migration operations (high - 3 days)
     DataMigration,
+    /// Update reserve supply and borrow caps (low - 6 hours)
+    UpdateReserveCaps,
+    /// Withdraw from a reserve's insurance fund (high - 3 days)
+    WithdrawInsuranceFund,
+    /// Invoke arbitrary instructions signed by the timelock (critical - 7 days)
+    ExecuteCalls,
 }
 
 impl Default for TimelockOperationType {
@@ -323,29 +396,121 @@ impl Default for TimelockOperationType {
     }
 }
 
-/// Status of a timelock proposal
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
-pub enum TimelockStatus {
-    /// Proposal is waiting for execution time
-    Pending,
-    /// Proposal has been executed
-    Executed,
-    /// Proposal has been cancelled
-    Cancelled,
-    /// Proposal has expired
-    Expired,
-}
-
-impl Default for TimelockStatus {
-    fn default() -> Self {
-        Self::Pending
-    }
-}
-
 /// Parameters for creating a timelock proposal
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct CreateTimelockProposalParams {
     pub operation_type: TimelockOperationType,
     pub instruction_data: Vec<u8>,
     pub target_accounts: Vec<Pubkey>,
+    pub calls: Vec<TimelockCall>,
+}
+
+/// Instruction a timelock proposal invokes when it executes
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TimelockCall {
+    /// Program to invoke
+    pub program_id: Pubkey,
+
+    /// Accounts of the instruction, in order
+    pub accounts: Vec<TimelockAccountMeta>,
+
+    /// Instruction data
+    pub data: Vec<u8>,
+}
+
+impl TimelockCall {
+    /// Build the instruction to invoke
+    pub fn to_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: self
+                .accounts
+                .iter()
+                .map(|meta| AccountMeta {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: self.data.clone(),
+        }
+    }
+}
+
+/// Account of a timelock call
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimelockAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_proposal(execution_time: i64) -> TimelockProposal {
+        TimelockProposal {
+            version: PROGRAM_VERSION,
+            controller: Pubkey::new_unique(),
+            operation_type: TimelockOperationType::UpdateReserveConfig,
+            instruction_data: Vec::new(),
+            created_at: 0,
+            execution_time,
+            status: ProposalStatus::Active,
+            proposer: Pubkey::new_unique(),
+            target_accounts: Vec::new(),
+            operation_hash: [0; 32],
+            calls: Vec::new(),
+            reserved: [0; 64],
+        }
+    }
+
+    #[test]
+    fn test_veto_only_within_delay() {
+        let mut proposal = test_proposal(1_000);
+        assert!(proposal.veto(1_000).is_err());
+        proposal.veto(999).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Cancelled);
+        assert!(proposal.veto(999).is_err());
+
+        let mut ready = test_proposal(1_000);
+        ready.sync_delay(1_000).unwrap();
+        assert!(ready.veto(500).is_err());
+    }
+
+    #[test]
+    fn test_calls_wait_at_least_the_calls_delay() {
+        let mut controller = TimelockController {
+            version: PROGRAM_VERSION,
+            multisig: Pubkey::new_unique(),
+            min_delays: vec![TimelockDelay {
+                operation_type: TimelockOperationType::UpdateReserveCaps,
+                delay_seconds: TIMELOCK_DELAY_LOW,
+            }],
+            active_proposals: Vec::new(),
+            created_at: 0,
+            reserved: [0; 128],
+        };
+        let caps = TimelockOperationType::UpdateReserveCaps;
+
+        // Without an ExecuteCalls entry, calls fall back to the critical delay
+        assert_eq!(
+            controller.get_proposal_delay(caps, false),
+            TIMELOCK_DELAY_LOW
+        );
+        assert_eq!(
+            controller.get_proposal_delay(caps, true),
+            TIMELOCK_DELAY_CRITICAL
+        );
+
+        controller.min_delays.push(TimelockDelay {
+            operation_type: TimelockOperationType::ExecuteCalls,
+            delay_seconds: TIMELOCK_DELAY_HIGH,
+        });
+        assert_eq!(
+            controller.get_proposal_delay(caps, true),
+            TIMELOCK_DELAY_HIGH
+        );
+    }
 }
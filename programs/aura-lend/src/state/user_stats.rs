@@ -0,0 +1,249 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::utils::math::Decimal;
+use anchor_lang::prelude::*;
+
+/// Principal still owed on one of the user's borrow positions
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrackedBorrow {
+    /// Obligation holding the borrow
+    pub obligation: Pubkey,
+
+    /// Reserve borrowed from
+    pub reserve: Pubkey,
+
+    /// Borrowed amount not yet repaid, excluding interest
+    pub principal: u64,
+}
+
+impl TrackedBorrow {
+    /// Serialized size of a tracked borrow in bytes
+    pub const SIZE: usize = 32 + // obligation
+        32 + // reserve
+        8; // principal
+}
+
+/// Lifetime statistics of a user in a market
+/// Optional on deposits, borrows, repayments, liquidations and reward claims, and updated
+/// whenever it is passed, so wallets can show a user's history without an indexer. USD
+/// amounts are valued when the operation happens. Interest is the part of a repayment
+/// exceeding the principal borrowed while the account was passed; repayments of
+/// untracked borrows count as principal.
+#[account]
+pub struct UserStats {
+    /// Version of the user stats account structure
+    pub version: u8,
+
+    /// Market the statistics cover
+    pub market: Pubkey,
+
+    /// Wallet whose activity is recorded
+    pub owner: Pubkey,
+
+    /// Liquidity ever supplied (USD)
+    pub total_supplied_usd: Decimal,
+
+    /// Liquidity ever borrowed (USD)
+    pub total_borrowed_usd: Decimal,
+
+    /// Interest ever repaid, including by liquidators (USD)
+    pub total_interest_paid_usd: Decimal,
+
+    /// Liquidations of the user's obligations
+    pub liquidations_suffered: u32,
+
+    /// Reward tokens ever claimed, in base units of the pools' reward mints
+    pub total_rewards_earned: u64,
+
+    /// Outstanding principal of the user's borrows, for separating interest from repayments
+    pub tracked_borrows: Vec<TrackedBorrow>,
+
+    /// Slot the account was created
+    pub created_slot: u64,
+
+    /// Slot of the last recorded operation
+    pub last_update_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 64],
+}
+
+impl UserStats {
+    /// Size of the UserStats account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // owner
+        16 + // total_supplied_usd
+        16 + // total_borrowed_usd
+        16 + // total_interest_paid_usd
+        4 + // liquidations_suffered
+        8 + // total_rewards_earned
+        4 + (TrackedBorrow::SIZE * MAX_USER_STATS_TRACKED_BORROWS) + // tracked_borrows
+        8 + // created_slot
+        8 + // last_update_slot
+        1 + // bump
+        64; // reserved
+
+    /// Create empty statistics
+    pub fn new(market: Pubkey, owner: Pubkey, bump: u8, slot: u64) -> Self {
+        Self {
+            version: PROGRAM_VERSION,
+            market,
+            owner,
+            total_supplied_usd: Decimal::zero(),
+            total_borrowed_usd: Decimal::zero(),
+            total_interest_paid_usd: Decimal::zero(),
+            liquidations_suffered: 0,
+            total_rewards_earned: 0,
+            tracked_borrows: Vec::new(),
+            created_slot: slot,
+            last_update_slot: slot,
+            bump,
+            reserved: [0; 64],
+        }
+    }
+
+    /// Check that the statistics belong to `owner` in `market`
+    pub fn validate_owner(&self, market: &Pubkey, owner: &Pubkey) -> Result<()> {
+        if self.market != *market || self.owner != *owner {
+            return Err(LendingError::InvalidAccount.into());
+        }
+        Ok(())
+    }
+
+    /// Record a deposit worth `value_usd`
+    pub fn record_supply(&mut self, value_usd: Decimal, slot: u64) -> Result<()> {
+        self.total_supplied_usd = self.total_supplied_usd.try_add(value_usd)?;
+        self.last_update_slot = slot;
+        Ok(())
+    }
+
+    /// Record a borrow of `amount` from `reserve` worth `value_usd`
+    /// Once `MAX_USER_STATS_TRACKED_BORROWS` positions are tracked, the principal of a new
+    /// position is not, and its repayments count entirely as principal.
+    pub fn record_borrow(
+        &mut self,
+        obligation: Pubkey,
+        reserve: Pubkey,
+        amount: u64,
+        value_usd: Decimal,
+        slot: u64,
+    ) -> Result<()> {
+        self.total_borrowed_usd = self.total_borrowed_usd.try_add(value_usd)?;
+
+        if let Some(tracked) = self
+            .tracked_borrows
+            .iter_mut()
+            .find(|b| b.obligation == obligation && b.reserve == reserve)
+        {
+            tracked.principal = tracked
+                .principal
+                .checked_add(amount)
+                .ok_or(LendingError::MathOverflow)?;
+        } else if self.tracked_borrows.len() < MAX_USER_STATS_TRACKED_BORROWS {
+            self.tracked_borrows.push(TrackedBorrow {
+                obligation,
+                reserve,
+                principal: amount,
+            });
+        }
+
+        self.last_update_slot = slot;
+        Ok(())
+    }
+
+    /// Apply a repayment of `repaid` against a debt of `debt` (principal plus accrued
+    /// interest), returning the part of it that paid interest
+    /// Interest is repaid before principal. The position stops being tracked once the
+    /// debt is repaid in full.
+    pub fn settle_repayment(
+        &mut self,
+        obligation: &Pubkey,
+        reserve: &Pubkey,
+        debt: u64,
+        repaid: u64,
+    ) -> u64 {
+        let Some(index) = self
+            .tracked_borrows
+            .iter()
+            .position(|b| b.obligation == *obligation && b.reserve == *reserve)
+        else {
+            return 0;
+        };
+
+        let tracked = &mut self.tracked_borrows[index];
+        let interest = repaid.min(debt.saturating_sub(tracked.principal));
+        tracked.principal = tracked.principal.saturating_sub(repaid - interest);
+
+        if repaid >= debt {
+            self.tracked_borrows.swap_remove(index);
+        }
+        interest
+    }
+
+    /// Record interest repaid worth `value_usd`
+    pub fn record_interest_paid(&mut self, value_usd: Decimal, slot: u64) -> Result<()> {
+        self.total_interest_paid_usd = self.total_interest_paid_usd.try_add(value_usd)?;
+        self.last_update_slot = slot;
+        Ok(())
+    }
+
+    /// Record a liquidation of one of the user's obligations
+    pub fn record_liquidation(&mut self, slot: u64) {
+        self.liquidations_suffered = self.liquidations_suffered.saturating_add(1);
+        self.last_update_slot = slot;
+    }
+
+    /// Record `amount` reward tokens claimed
+    pub fn record_rewards(&mut self, amount: u64, slot: u64) -> Result<()> {
+        self.total_rewards_earned = self
+            .total_rewards_earned
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        self.last_update_slot = slot;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repayments_split_interest_from_principal() {
+        let mut stats = UserStats::new(Pubkey::new_unique(), Pubkey::new_unique(), 255, 1);
+        let obligation = Pubkey::new_unique();
+        let reserve = Pubkey::new_unique();
+
+        stats
+            .record_borrow(obligation, reserve, 1_000, Decimal::zero(), 2)
+            .unwrap();
+        stats
+            .record_borrow(obligation, reserve, 500, Decimal::zero(), 3)
+            .unwrap();
+        assert_eq!(stats.tracked_borrows[0].principal, 1_500);
+
+        // 100 of interest accrued; it is repaid before any principal
+        assert_eq!(stats.settle_repayment(&obligation, &reserve, 1_600, 60), 60);
+        assert_eq!(stats.tracked_borrows[0].principal, 1_500);
+        assert_eq!(
+            stats.settle_repayment(&obligation, &reserve, 1_540, 540),
+            40
+        );
+        assert_eq!(stats.tracked_borrows[0].principal, 1_000);
+
+        // A full repayment stops tracking the position
+        assert_eq!(
+            stats.settle_repayment(&obligation, &reserve, 1_010, 1_010),
+            10
+        );
+        assert!(stats.tracked_borrows.is_empty());
+
+        // Untracked positions report no interest
+        assert_eq!(stats.settle_repayment(&obligation, &reserve, 500, 500), 0);
+    }
+}
@@ -0,0 +1,151 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+
+/// Pre-announced large redemption from a reserve
+/// Redemptions above the configured share of reserve liquidity must be requested
+/// in advance and can only execute once the delay has elapsed (or a guardian waived it)
+#[account]
+pub struct LargeWithdrawalRequest {
+    /// Version of the request account structure
+    pub version: u8,
+
+    /// Reserve the redemption is made from
+    pub reserve: Pubkey,
+
+    /// Owner of the collateral being redeemed
+    pub owner: Pubkey,
+
+    /// Maximum collateral tokens (aTokens) that may be redeemed with this request
+    pub collateral_amount: u64,
+
+    /// Slot at which the request was created
+    pub requested_slot: u64,
+
+    /// First slot at which the redemption may execute
+    pub executable_slot: u64,
+
+    /// Last slot at which the redemption may execute
+    pub expires_slot: u64,
+
+    /// Whether a guardian waived the delay
+    pub waived: bool,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 32],
+}
+
+impl LargeWithdrawalRequest {
+    /// Size of the LargeWithdrawalRequest account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // reserve
+        32 + // owner
+        8 + // collateral_amount
+        8 + // requested_slot
+        8 + // executable_slot
+        8 + // expires_slot
+        1 + // waived
+        32; // reserved
+
+    /// Create a new request maturing after `delay_slots`
+    pub fn new(
+        reserve: Pubkey,
+        owner: Pubkey,
+        collateral_amount: u64,
+        current_slot: u64,
+        delay_slots: u64,
+    ) -> Result<Self> {
+        if collateral_amount == 0 {
+            return Err(LendingError::AmountTooSmall.into());
+        }
+
+        let executable_slot = current_slot
+            .checked_add(delay_slots)
+            .ok_or(LendingError::MathOverflow)?;
+        let expires_slot = executable_slot
+            .checked_add(LARGE_WITHDRAWAL_EXECUTION_WINDOW_SLOTS)
+            .ok_or(LendingError::MathOverflow)?;
+
+        Ok(Self {
+            version: PROGRAM_VERSION,
+            reserve,
+            owner,
+            collateral_amount,
+            requested_slot: current_slot,
+            executable_slot,
+            expires_slot,
+            waived: false,
+            reserved: [0; 32],
+        })
+    }
+
+    /// Check that this request covers the given redemption at `current_slot`
+    pub fn validate_execution(
+        &self,
+        reserve: &Pubkey,
+        owner: &Pubkey,
+        collateral_amount: u64,
+        current_slot: u64,
+    ) -> Result<()> {
+        if self.reserve != *reserve || self.owner != *owner {
+            return Err(LendingError::LargeWithdrawalRequestMismatch.into());
+        }
+
+        if collateral_amount > self.collateral_amount {
+            return Err(LendingError::LargeWithdrawalRequestMismatch.into());
+        }
+
+        if !self.waived && current_slot < self.executable_slot {
+            return Err(LendingError::OperationTooEarly.into());
+        }
+
+        if current_slot > self.expires_slot {
+            return Err(LendingError::OperationExpired.into());
+        }
+
+        Ok(())
+    }
+
+    /// Waive the remaining delay (guardian emergency action)
+    pub fn waive(&mut self, current_slot: u64) -> Result<()> {
+        self.waived = true;
+        self.expires_slot = current_slot
+            .checked_add(LARGE_WITHDRAWAL_EXECUTION_WINDOW_SLOTS)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_matures_after_delay() {
+        let reserve = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let request = LargeWithdrawalRequest::new(reserve, owner, 1_000, 100, 300).unwrap();
+
+        assert!(request.validate_execution(&reserve, &owner, 1_000, 399).is_err());
+        assert!(request.validate_execution(&reserve, &owner, 1_000, 400).is_ok());
+        assert!(request.validate_execution(&reserve, &owner, 1_001, 400).is_err());
+        assert!(request
+            .validate_execution(&reserve, &owner, 1_000, 400 + LARGE_WITHDRAWAL_EXECUTION_WINDOW_SLOTS + 1)
+            .is_err());
+    }
+
+    #[test]
+    fn test_waived_request_executes_immediately() {
+        let reserve = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut request = LargeWithdrawalRequest::new(reserve, owner, 1_000, 100, 300).unwrap();
+
+        request.waive(101).unwrap();
+
+        assert!(request.validate_execution(&reserve, &owner, 500, 101).is_ok());
+        assert!(request
+            .validate_execution(&reserve, &Pubkey::new_unique(), 500, 101)
+            .is_err());
+    }
+}
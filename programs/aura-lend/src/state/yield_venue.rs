@@ -0,0 +1,279 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use anchor_lang::prelude::*;
+
+/// External venue a reserve's idle liquidity may be invested in
+/// Governance whitelists an adapter program per reserve (e.g. a stake pool) together
+/// with the share of the reserve's liquidity it may hold. Keepers move liquidity
+/// between the reserve's supply and the venue through the adapter; the venue's receipt
+/// tokens are held in `position_account`, owned by the reserve's liquidity supply
+/// authority. Invested liquidity is tracked at cost and gains or losses are realized
+/// when it is divested.
+#[account]
+pub struct YieldVenue {
+    /// Version of the yield venue account structure
+    pub version: u8,
+
+    /// Market this venue belongs to
+    pub market: Pubkey,
+
+    /// Reserve whose liquidity is invested
+    pub reserve: Pubkey,
+
+    /// Program moving liquidity into and out of the venue
+    pub adapter_program: Pubkey,
+
+    /// Mint of the receipt tokens the venue issues
+    pub receipt_mint: Pubkey,
+
+    /// Token account holding the receipt tokens
+    pub position_account: Pubkey,
+
+    /// Largest share of the reserve's total liquidity the venue may hold (basis points)
+    pub max_allocation_bps: u64,
+
+    /// Largest loss a divestment may realize against its cost basis (basis points)
+    pub max_slippage_bps: u64,
+
+    /// Liquidity currently invested, at cost
+    pub invested_amount: u64,
+
+    /// Gains realized by divestments
+    pub total_realized_yield: u64,
+
+    /// Losses realized by divestments
+    pub total_realized_loss: u64,
+
+    /// Whether keepers may invest more liquidity (divesting is always allowed)
+    pub enabled: bool,
+
+    /// Slot of the last investment or divestment
+    pub last_rebalance_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 64],
+}
+
+impl YieldVenue {
+    /// Size of the YieldVenue account in bytes
+    pub const SIZE: usize = 8 + // discriminator
+        1 + // version
+        32 + // market
+        32 + // reserve
+        32 + // adapter_program
+        32 + // receipt_mint
+        32 + // position_account
+        8 + // max_allocation_bps
+        8 + // max_slippage_bps
+        8 + // invested_amount
+        8 + // total_realized_yield
+        8 + // total_realized_loss
+        1 + // enabled
+        8 + // last_rebalance_slot
+        1 + // bump
+        64; // reserved
+
+    /// Create an enabled venue with nothing invested
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        market: Pubkey,
+        reserve: Pubkey,
+        adapter_program: Pubkey,
+        receipt_mint: Pubkey,
+        position_account: Pubkey,
+        max_allocation_bps: u64,
+        max_slippage_bps: u64,
+        bump: u8,
+    ) -> Result<Self> {
+        if adapter_program == crate::ID {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+        Self::validate_max_allocation_bps(max_allocation_bps)?;
+        Self::validate_max_slippage_bps(max_slippage_bps)?;
+
+        Ok(Self {
+            version: PROGRAM_VERSION,
+            market,
+            reserve,
+            adapter_program,
+            receipt_mint,
+            position_account,
+            max_allocation_bps,
+            max_slippage_bps,
+            invested_amount: 0,
+            total_realized_yield: 0,
+            total_realized_loss: 0,
+            enabled: true,
+            last_rebalance_slot: 0,
+            bump,
+            reserved: [0; 64],
+        })
+    }
+
+    /// Validate the allocation configured by governance
+    pub fn validate_max_allocation_bps(max_allocation_bps: u64) -> Result<()> {
+        if max_allocation_bps > BASIS_POINTS_PRECISION {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+        Ok(())
+    }
+
+    /// Validate the divestment loss bound configured by governance
+    pub fn validate_max_slippage_bps(max_slippage_bps: u64) -> Result<()> {
+        if max_slippage_bps > MAX_YIELD_VENUE_SLIPPAGE_BPS {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+        Ok(())
+    }
+
+    /// Liquidity that may still be invested given the reserve's `total_liquidity`
+    pub fn allocation_headroom(&self, total_liquidity: u64) -> Result<u64> {
+        let max_allocation = (total_liquidity as u128)
+            .checked_mul(self.max_allocation_bps as u128)
+            .ok_or(LendingError::MathOverflow)?
+            / BASIS_POINTS_PRECISION as u128;
+        Ok((max_allocation as u64).saturating_sub(self.invested_amount))
+    }
+
+    /// Cost basis released by spending `receipt_spent` of `receipt_balance` receipt tokens
+    pub fn cost_basis_of(&self, receipt_spent: u64, receipt_balance: u64) -> Result<u64> {
+        if receipt_spent >= receipt_balance {
+            return Ok(self.invested_amount);
+        }
+        let cost_basis = (self.invested_amount as u128)
+            .checked_mul(receipt_spent as u128)
+            .ok_or(LendingError::MathOverflow)?
+            / receipt_balance as u128;
+        Ok(cost_basis as u64)
+    }
+
+    /// Smallest return accepted for divesting `cost_basis`
+    pub fn min_divest_return(&self, cost_basis: u64) -> Result<u64> {
+        let min_return = (cost_basis as u128)
+            .checked_mul((BASIS_POINTS_PRECISION - self.max_slippage_bps) as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_add(BASIS_POINTS_PRECISION as u128 - 1)
+            .ok_or(LendingError::MathOverflow)?
+            / BASIS_POINTS_PRECISION as u128;
+        Ok(min_return as u64)
+    }
+
+    /// Record `amount` of liquidity invested
+    pub fn record_investment(&mut self, amount: u64, slot: u64) -> Result<()> {
+        self.invested_amount = self
+            .invested_amount
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        self.last_rebalance_slot = slot;
+        Ok(())
+    }
+
+    /// Record `cost_basis` of liquidity divested for `returned`
+    pub fn record_divestment(&mut self, cost_basis: u64, returned: u64, slot: u64) -> Result<()> {
+        self.invested_amount = self
+            .invested_amount
+            .checked_sub(cost_basis)
+            .ok_or(LendingError::MathUnderflow)?;
+        if returned >= cost_basis {
+            self.total_realized_yield = self
+                .total_realized_yield
+                .checked_add(returned - cost_basis)
+                .ok_or(LendingError::MathOverflow)?;
+        } else {
+            self.total_realized_loss = self
+                .total_realized_loss
+                .checked_add(cost_basis - returned)
+                .ok_or(LendingError::MathOverflow)?;
+        }
+        self.last_rebalance_slot = slot;
+        Ok(())
+    }
+}
+
+/// Parameters for whitelisting a yield venue
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AddYieldVenueParams {
+    pub max_allocation_bps: u64,
+    pub max_slippage_bps: u64,
+}
+
+/// Parameters for updating a yield venue
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateYieldVenueParams {
+    pub max_allocation_bps: Option<u64>,
+    pub max_slippage_bps: Option<u64>,
+    pub enabled: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_venue() -> YieldVenue {
+        YieldVenue::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            2_000,
+            50,
+            255,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_allocation_headroom() {
+        let mut venue = test_venue();
+        assert_eq!(venue.allocation_headroom(1_000_000).unwrap(), 200_000);
+
+        venue.record_investment(150_000, 1).unwrap();
+        assert_eq!(venue.allocation_headroom(1_000_000).unwrap(), 50_000);
+
+        // Shrinking liquidity or allocation leaves no headroom rather than failing
+        assert_eq!(venue.allocation_headroom(500_000).unwrap(), 0);
+        venue.max_allocation_bps = 0;
+        assert_eq!(venue.allocation_headroom(1_000_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_divestment_releases_proportional_cost_basis() {
+        let mut venue = test_venue();
+        venue.record_investment(1_000_000, 1).unwrap();
+
+        // Spending a quarter of the receipts releases a quarter of the cost basis
+        let cost_basis = venue.cost_basis_of(250, 1_000).unwrap();
+        assert_eq!(cost_basis, 250_000);
+        venue.record_divestment(cost_basis, 260_000, 2).unwrap();
+        assert_eq!(venue.invested_amount, 750_000);
+        assert_eq!(venue.total_realized_yield, 10_000);
+
+        // Spending the rest releases everything that is left
+        assert_eq!(venue.cost_basis_of(750, 750).unwrap(), 750_000);
+        assert_eq!(venue.min_divest_return(750_000).unwrap(), 746_250);
+        venue.record_divestment(750_000, 746_250, 3).unwrap();
+        assert_eq!(venue.invested_amount, 0);
+        assert_eq!(venue.total_realized_loss, 3_750);
+    }
+
+    #[test]
+    fn test_configuration_bounds() {
+        assert!(YieldVenue::validate_max_allocation_bps(10_001).is_err());
+        assert!(YieldVenue::validate_max_slippage_bps(MAX_YIELD_VENUE_SLIPPAGE_BPS + 1).is_err());
+        assert!(YieldVenue::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            crate::ID,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            2_000,
+            50,
+            255,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,68 @@
+use crate::constants::BATCH_COMPUTE_UNITS_RESERVE;
+use anchor_lang::solana_program::compute_units::sol_remaining_compute_units;
+
+/// Stops a batch instruction before it runs out of compute units
+/// A step only starts if the units left cover the most expensive step seen so far (or
+/// the initial estimate) plus `BATCH_COMPUTE_UNITS_RESERVE` for the work after the
+/// loop. Steps that do not fit are left for the next transaction instead of failing
+/// the whole batch at the compute limit.
+#[derive(Debug)]
+pub struct ComputeBudgetGuard {
+    /// Largest measured cost of a step, starting at the estimate
+    max_step_units: u64,
+    /// Units left when the previous step started
+    step_start: Option<u64>,
+}
+
+impl ComputeBudgetGuard {
+    pub fn new(step_estimate: u64) -> Self {
+        Self {
+            max_step_units: step_estimate,
+            step_start: None,
+        }
+    }
+
+    /// Whether another step fits in the remaining compute units
+    /// Call once before each step; the cost of the previous step is measured up to here.
+    pub fn try_begin_step(&mut self) -> bool {
+        self.try_begin_step_with(sol_remaining_compute_units())
+    }
+
+    fn try_begin_step_with(&mut self, remaining: u64) -> bool {
+        if let Some(start) = self.step_start.take() {
+            self.max_step_units = self.max_step_units.max(start.saturating_sub(remaining));
+        }
+
+        let needed = self
+            .max_step_units
+            .saturating_add(BATCH_COMPUTE_UNITS_RESERVE);
+        if remaining < needed {
+            return false;
+        }
+        self.step_start = Some(remaining);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guard_tracks_the_most_expensive_step() {
+        let mut guard = ComputeBudgetGuard::new(10_000);
+
+        // The estimate decides the first step
+        assert!(!guard.try_begin_step_with(10_000 + BATCH_COMPUTE_UNITS_RESERVE - 1));
+        assert!(guard.try_begin_step_with(200_000));
+
+        // A 50k step raises the estimate; a cheaper one does not lower it
+        assert!(guard.try_begin_step_with(150_000));
+        assert!(guard.try_begin_step_with(145_000));
+        assert_eq!(guard.max_step_units, 50_000);
+
+        // A step costing more than what is left after it stops the batch
+        assert!(!guard.try_begin_step_with(80_000));
+        assert_eq!(guard.max_step_units, 65_000);
+    }
+}
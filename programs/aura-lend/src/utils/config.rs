@@ -1,5 +1,6 @@
 use crate::constants::*;
 use crate::error::LendingError;
+use crate::utils::math::{interest, Decimal};
 use anchor_lang::prelude::*;
 
 /// Dynamic configuration management for the protocol
@@ -20,11 +21,15 @@ pub struct ProtocolConfig {
     pub max_protocol_fee_bps: u64,
     pub liquidation_close_factor_bps: u64,
     pub max_liquidation_bonus_bps: u64,
+    pub referral_fee_share_bps: u64,
 
     // Risk parameters
     pub min_health_factor: u64,
     pub max_ltv_ratio: u64,
     pub min_liquidation_threshold: u64,
+    pub large_withdrawal_threshold_bps: u64,
+    pub large_withdrawal_delay_slots: u64,
+    pub max_withdraw_utilization_bps: u64,
 
     // Oracle settings
     pub max_oracle_staleness_slots: u64,
@@ -49,6 +54,14 @@ pub struct ProtocolConfig {
     pub pause_withdrawals: bool,
     pub pause_borrows: bool,
     pub pause_liquidations: bool,
+    pub withdraw_utilization_cap_lifted: bool,
+
+    // Keeper settings
+    pub keeper_priority_window_slots: u64,
+
+    // Position limits
+    /// Smallest borrow or collateral position, in whole USD, an obligation may hold
+    pub min_position_value_usd: u64,
 }
 
 impl Default for ProtocolConfig {
@@ -69,11 +82,15 @@ impl Default for ProtocolConfig {
             max_protocol_fee_bps: MAX_PROTOCOL_FEE,
             liquidation_close_factor_bps: LIQUIDATION_CLOSE_FACTOR,
             max_liquidation_bonus_bps: MAX_LIQUIDATION_BONUS,
+            referral_fee_share_bps: DEFAULT_REFERRAL_FEE_SHARE_BPS,
 
             // Risk parameters
             min_health_factor: MIN_HEALTH_FACTOR,
             max_ltv_ratio: MAX_LTV_RATIO,
             min_liquidation_threshold: MIN_LIQUIDATION_THRESHOLD,
+            large_withdrawal_threshold_bps: DEFAULT_LARGE_WITHDRAWAL_THRESHOLD_BPS,
+            large_withdrawal_delay_slots: DEFAULT_LARGE_WITHDRAWAL_DELAY_SLOTS,
+            max_withdraw_utilization_bps: DEFAULT_MAX_WITHDRAW_UTILIZATION_BPS,
 
             // Oracle settings
             max_oracle_staleness_slots: ORACLE_STALENESS_THRESHOLD,
@@ -98,6 +115,13 @@ impl Default for ProtocolConfig {
             pause_withdrawals: false,
             pause_borrows: false,
             pause_liquidations: false,
+            withdraw_utilization_cap_lifted: false,
+
+            // Keeper settings
+            keeper_priority_window_slots: DEFAULT_KEEPER_PRIORITY_WINDOW_SLOTS,
+
+            // Position limits
+            min_position_value_usd: DEFAULT_MIN_POSITION_VALUE_USD,
         }
     }
 }
@@ -115,9 +139,13 @@ impl ProtocolConfig {
         8 + // max_protocol_fee_bps
         8 + // liquidation_close_factor_bps
         8 + // max_liquidation_bonus_bps
+        8 + // referral_fee_share_bps
         8 + // min_health_factor
         8 + // max_ltv_ratio
         8 + // min_liquidation_threshold
+        8 + // large_withdrawal_threshold_bps
+        8 + // large_withdrawal_delay_slots
+        8 + // max_withdraw_utilization_bps
         8 + // max_oracle_staleness_slots
         8 + // max_oracle_confidence_threshold
         1 + // min_oracle_sources
@@ -134,6 +162,9 @@ impl ProtocolConfig {
         1 + // pause_withdrawals
         1 + // pause_borrows
         1 + // pause_liquidations
+        1 + // withdraw_utilization_cap_lifted
+        8 + // keeper_priority_window_slots
+        8 + // min_position_value_usd
         64; // padding
 
     /// Validate configuration parameters
@@ -170,6 +201,10 @@ impl ProtocolConfig {
             self.max_liquidation_bonus_bps <= 2000,
             LendingError::InvalidConfiguration
         ); // Max 20%
+        require!(
+            self.referral_fee_share_bps <= MAX_REFERRAL_FEE_SHARE_BPS,
+            LendingError::InvalidConfiguration
+        );
 
         // Risk parameters validation
         require!(
@@ -184,6 +219,18 @@ impl ProtocolConfig {
             self.min_liquidation_threshold >= self.max_ltv_ratio,
             LendingError::InvalidConfiguration
         );
+        require!(
+            self.large_withdrawal_threshold_bps <= BASIS_POINTS_PRECISION,
+            LendingError::InvalidConfiguration
+        ); // 0 disables the check
+        require!(
+            self.large_withdrawal_delay_slots <= LARGE_WITHDRAWAL_EXECUTION_WINDOW_SLOTS,
+            LendingError::InvalidConfiguration
+        );
+        require!(
+            self.max_withdraw_utilization_bps <= BASIS_POINTS_PRECISION,
+            LendingError::InvalidConfiguration
+        ); // 0 disables the check
 
         // Oracle settings validation
         require!(
@@ -237,6 +284,18 @@ impl ProtocolConfig {
             LendingError::InvalidConfiguration
         );
 
+        // Keeper settings validation
+        require!(
+            self.keeper_priority_window_slots <= MAX_KEEPER_PRIORITY_WINDOW_SLOTS,
+            LendingError::InvalidConfiguration
+        ); // 0 disables the window
+
+        // Position limits validation
+        require!(
+            self.min_position_value_usd <= MAX_MIN_POSITION_VALUE_USD,
+            LendingError::InvalidConfiguration
+        ); // 0 disables the minimum
+
         Ok(())
     }
 
@@ -269,6 +328,116 @@ impl ProtocolConfig {
         self.pause_liquidations // Note: liquidations should remain active even in emergency
     }
 
+    /// Check if a position worth `value_usd` is below the minimum position value
+    /// Positions this small cost liquidators more in fees than the bonus pays, so they
+    /// can neither be opened nor left behind, and any that remain may be closed in full.
+    pub fn is_dust_position(&self, value_usd: Decimal) -> Result<bool> {
+        if self.min_position_value_usd == 0 {
+            return Ok(false);
+        }
+
+        Ok(value_usd < Decimal::from_integer(self.min_position_value_usd)?)
+    }
+
+    /// Reject a position worth `value_usd` that is below the minimum position value
+    pub fn validate_position_value(&self, value_usd: Decimal) -> Result<()> {
+        if self.is_dust_position(value_usd)? {
+            return Err(LendingError::PositionTooSmall.into());
+        }
+        Ok(())
+    }
+
+    /// Check if a redemption is large enough to require a pre-announced request
+    pub fn is_large_withdrawal(&self, liquidity_amount: u64, total_liquidity: u64) -> Result<bool> {
+        if self.large_withdrawal_threshold_bps == 0 {
+            return Ok(false);
+        }
+
+        let threshold = (total_liquidity as u128)
+            .checked_mul(self.large_withdrawal_threshold_bps as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::DivisionByZero)?;
+
+        Ok(liquidity_amount as u128 > threshold)
+    }
+
+    /// Check if redeeming `liquidity_amount` would push a reserve's utilization above
+    /// the cap
+    /// Emergency responders can lift the cap so suppliers can exit regardless.
+    pub fn exceeds_withdraw_utilization(
+        &self,
+        liquidity_amount: u64,
+        total_borrows: u64,
+        available_liquidity: u64,
+    ) -> Result<bool> {
+        if self.max_withdraw_utilization_bps == 0
+            || self.withdraw_utilization_cap_lifted
+            || total_borrows == 0
+        {
+            return Ok(false);
+        }
+
+        let remaining_liquidity = available_liquidity
+            .checked_sub(liquidity_amount)
+            .ok_or(LendingError::InsufficientLiquidity)?;
+        let utilization_bps = interest::calculate_utilization_rate(
+            total_borrows,
+            total_borrows
+                .checked_add(remaining_liquidity)
+                .ok_or(LendingError::MathOverflow)?,
+        )?;
+
+        Ok(utilization_bps > self.max_withdraw_utilization_bps)
+    }
+
+    /// Most liquidity that can be redeemed without exceeding the withdraw utilization cap
+    pub fn max_withdrawable_liquidity(
+        &self,
+        total_borrows: u64,
+        available_liquidity: u64,
+    ) -> Result<u64> {
+        if self.max_withdraw_utilization_bps == 0
+            || self.max_withdraw_utilization_bps >= BASIS_POINTS_PRECISION
+            || self.withdraw_utilization_cap_lifted
+            || total_borrows == 0
+        {
+            return Ok(available_liquidity);
+        }
+
+        // Utilization rounds down, so it stays within the cap while the remaining supply
+        // exceeds borrows / (cap + 1 bps)
+        let min_supplied = (total_borrows as u128)
+            .checked_mul(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(self.max_withdraw_utilization_bps as u128 + 1)
+            .ok_or(LendingError::DivisionByZero)?
+            + 1;
+        let min_remaining = u64::try_from(min_supplied)
+            .map_err(|_| LendingError::MathOverflow)?
+            .saturating_sub(total_borrows);
+
+        Ok(available_liquidity.saturating_sub(min_remaining))
+    }
+
+    /// Share of a protocol fee owed to the referrer of the account paying it
+    pub fn referral_fee(&self, fee: u64) -> Result<u64> {
+        let referral_fee = (fee as u128)
+            .checked_mul(self.referral_fee_share_bps as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::DivisionByZero)?;
+        Ok(referral_fee as u64)
+    }
+
+    /// Check a remaining_accounts list against the per-instruction account cap
+    pub fn validate_account_count(&self, count: usize) -> Result<()> {
+        if count > self.max_accounts_per_instruction as usize {
+            return Err(LendingError::TooManyRemainingAccounts.into());
+        }
+        Ok(())
+    }
+
     /// Get effective protocol fee for a reserve
     pub fn get_protocol_fee_bps(&self, reserve_fee_bps: Option<u64>) -> u64 {
         reserve_fee_bps
@@ -296,7 +465,7 @@ pub enum TimelockPriority {
 }
 
 /// Configuration update parameters
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
 pub struct ConfigUpdateParams {
     // Market limits
     pub max_reserves: Option<u64>,
@@ -308,11 +477,15 @@ pub struct ConfigUpdateParams {
     pub max_protocol_fee_bps: Option<u64>,
     pub liquidation_close_factor_bps: Option<u64>,
     pub max_liquidation_bonus_bps: Option<u64>,
+    pub referral_fee_share_bps: Option<u64>,
 
     // Risk parameters
     pub min_health_factor: Option<u64>,
     pub max_ltv_ratio: Option<u64>,
     pub min_liquidation_threshold: Option<u64>,
+    pub large_withdrawal_threshold_bps: Option<u64>,
+    pub large_withdrawal_delay_slots: Option<u64>,
+    pub max_withdraw_utilization_bps: Option<u64>,
 
     // Oracle settings
     pub max_oracle_staleness_slots: Option<u64>,
@@ -337,6 +510,13 @@ pub struct ConfigUpdateParams {
     pub pause_withdrawals: Option<bool>,
     pub pause_borrows: Option<bool>,
     pub pause_liquidations: Option<bool>,
+    pub withdraw_utilization_cap_lifted: Option<bool>,
+
+    // Keeper settings
+    pub keeper_priority_window_slots: Option<u64>,
+
+    // Position limits
+    pub min_position_value_usd: Option<u64>,
 }
 
 impl ConfigUpdateParams {
@@ -366,6 +546,9 @@ impl ConfigUpdateParams {
         if let Some(value) = self.max_liquidation_bonus_bps {
             config.max_liquidation_bonus_bps = value;
         }
+        if let Some(value) = self.referral_fee_share_bps {
+            config.referral_fee_share_bps = value;
+        }
 
         // Risk parameters
         if let Some(value) = self.min_health_factor {
@@ -377,6 +560,15 @@ impl ConfigUpdateParams {
         if let Some(value) = self.min_liquidation_threshold {
             config.min_liquidation_threshold = value;
         }
+        if let Some(value) = self.large_withdrawal_threshold_bps {
+            config.large_withdrawal_threshold_bps = value;
+        }
+        if let Some(value) = self.large_withdrawal_delay_slots {
+            config.large_withdrawal_delay_slots = value;
+        }
+        if let Some(value) = self.max_withdraw_utilization_bps {
+            config.max_withdraw_utilization_bps = value;
+        }
 
         // Oracle settings
         if let Some(value) = self.max_oracle_staleness_slots {
@@ -433,6 +625,19 @@ impl ConfigUpdateParams {
         if let Some(value) = self.pause_liquidations {
             config.pause_liquidations = value;
         }
+        if let Some(value) = self.withdraw_utilization_cap_lifted {
+            config.withdraw_utilization_cap_lifted = value;
+        }
+
+        // Keeper settings
+        if let Some(value) = self.keeper_priority_window_slots {
+            config.keeper_priority_window_slots = value;
+        }
+
+        // Position limits
+        if let Some(value) = self.min_position_value_usd {
+            config.min_position_value_usd = value;
+        }
     }
 }
 
@@ -505,4 +710,123 @@ mod tests {
         assert_eq!(config.get_timelock_delay(TimelockPriority::Medium), 3600); // 1 hour
         assert_eq!(config.get_timelock_delay(TimelockPriority::Low), 900); // 15 minutes
     }
+
+    #[test]
+    fn test_large_withdrawal_threshold() {
+        let config = ProtocolConfig {
+            large_withdrawal_threshold_bps: 1000, // 10%
+            ..Default::default()
+        };
+
+        assert!(!config.is_large_withdrawal(100_000, 1_000_000).unwrap());
+        assert!(config.is_large_withdrawal(100_001, 1_000_000).unwrap());
+
+        let disabled = ProtocolConfig {
+            large_withdrawal_threshold_bps: 0,
+            ..Default::default()
+        };
+        assert!(!disabled.is_large_withdrawal(1_000_000, 1_000_000).unwrap());
+    }
+
+    #[test]
+    fn test_referral_fee_share() {
+        let config = ProtocolConfig {
+            referral_fee_share_bps: 2000, // 20%
+            ..Default::default()
+        };
+        assert_eq!(config.referral_fee(1_000).unwrap(), 200);
+        assert_eq!(config.referral_fee(4).unwrap(), 0);
+
+        let excessive = ProtocolConfig {
+            referral_fee_share_bps: MAX_REFERRAL_FEE_SHARE_BPS + 1,
+            ..Default::default()
+        };
+        assert!(excessive.validate().is_err());
+    }
+
+    #[test]
+    fn test_account_count_boundary() {
+        let config = ProtocolConfig {
+            max_accounts_per_instruction: 32,
+            ..Default::default()
+        };
+
+        assert!(config.validate_account_count(0).is_ok());
+        assert!(config.validate_account_count(32).is_ok());
+        assert!(config.validate_account_count(33).is_err());
+    }
+
+    #[test]
+    fn test_withdraw_utilization_cap() {
+        let mut config = ProtocolConfig {
+            max_withdraw_utilization_bps: 9500, // 95%
+            ..Default::default()
+        };
+
+        // 9_500 borrowed with 1_000 available: withdrawing 500 leaves exactly 95%
+        assert!(!config
+            .exceeds_withdraw_utilization(500, 9_500, 1_000)
+            .unwrap());
+        assert!(config
+            .exceeds_withdraw_utilization(600, 9_500, 1_000)
+            .unwrap());
+
+        // A reserve without borrows can always be emptied
+        assert!(!config
+            .exceeds_withdraw_utilization(1_000, 0, 1_000)
+            .unwrap());
+
+        // The emergency override lifts the cap
+        config.withdraw_utilization_cap_lifted = true;
+        assert!(!config
+            .exceeds_withdraw_utilization(1_000, 9_500, 1_000)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_max_withdrawable_liquidity() {
+        let mut config = ProtocolConfig {
+            max_withdraw_utilization_bps: 9500, // 95%
+            ..Default::default()
+        };
+
+        // The largest redemption the cap allows, and not a unit more
+        let max = config.max_withdrawable_liquidity(9_500, 1_000).unwrap();
+        assert_eq!(max, 501);
+        assert!(!config
+            .exceeds_withdraw_utilization(max, 9_500, 1_000)
+            .unwrap());
+        assert!(config
+            .exceeds_withdraw_utilization(max + 1, 9_500, 1_000)
+            .unwrap());
+
+        // Already above the cap, nothing can be redeemed
+        assert_eq!(config.max_withdrawable_liquidity(9_500, 400).unwrap(), 0);
+
+        assert_eq!(config.max_withdrawable_liquidity(0, 1_000).unwrap(), 1_000);
+        config.withdraw_utilization_cap_lifted = true;
+        assert_eq!(
+            config.max_withdrawable_liquidity(9_500, 1_000).unwrap(),
+            1_000
+        );
+    }
+
+    #[test]
+    fn test_dust_position() {
+        let mut config = ProtocolConfig {
+            min_position_value_usd: 10,
+            ..Default::default()
+        };
+
+        let nine = Decimal::from_integer(9).unwrap();
+        let ten = Decimal::from_integer(10).unwrap();
+        assert!(config.is_dust_position(nine).unwrap());
+        assert!(!config.is_dust_position(ten).unwrap());
+        assert!(config.validate_position_value(nine).is_err());
+        config.validate_position_value(ten).unwrap();
+
+        // 0 disables the minimum
+        config.min_position_value_usd = 0;
+        assert!(!config.is_dust_position(Decimal::zero()).unwrap());
+    }
 }
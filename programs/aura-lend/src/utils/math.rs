@@ -47,7 +47,10 @@ pub mod fast_math {
                 result = result.checked_mul(base).ok_or(LendingError::MathOverflow)?;
             }
 
-            base = base.checked_mul(base).ok_or(LendingError::MathOverflow)?;
+            // The last square is never used and may overflow
+            if exp > 1 {
+                base = base.checked_mul(base).ok_or(LendingError::MathOverflow)?;
+            }
             exp >>= 1;
         }
 
@@ -124,6 +127,7 @@ pub mod fast_math {
 }
 
 /// Decimal type for high-precision calculations
+/// Fixed point with `PRECISION` (10^18) as one. This is the program's only decimal type.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
 pub struct Decimal {
     pub value: u128,
@@ -207,26 +211,23 @@ impl Decimal {
         })
     }
 
-    /// Optimized multiply operation using u256 intermediate
+    /// Multiply, rounding down
+    /// The product of the scaled values must fit in a u128.
     #[inline(always)]
     pub fn try_mul(self, rhs: Decimal) -> Result<Decimal> {
         if self.value == 0 || rhs.value == 0 {
             return Ok(Decimal::zero());
         }
 
-        // Use u256 arithmetic to prevent overflow
-        let intermediate = (self.value as u128)
-            .checked_mul(rhs.value as u128)
+        let intermediate = self
+            .value
+            .checked_mul(rhs.value)
             .ok_or(LendingError::MathOverflow)?;
 
         let result = intermediate
             .checked_div(PRECISION as u128)
             .ok_or(LendingError::DivisionByZero)?;
 
-        if result > u128::MAX {
-            return Err(LendingError::MathOverflow.into());
-        }
-
         Ok(Decimal { value: result })
     }
 
@@ -246,12 +247,13 @@ impl Decimal {
             return Ok(Decimal::one());
         }
 
-        let intermediate = (self.value as u128)
+        let intermediate = self
+            .value
             .checked_mul(PRECISION as u128)
             .ok_or(LendingError::MathOverflow)?;
 
         let result = intermediate
-            .checked_div(rhs.value as u128)
+            .checked_div(rhs.value)
             .ok_or(LendingError::DivisionByZero)?;
 
         Ok(Decimal { value: result })
@@ -263,13 +265,14 @@ impl Decimal {
             return Ok(Decimal::zero());
         }
 
-        // Scale up for precision, then scale back
-        let scaled_value = self
-            .value
-            .checked_mul(PRECISION as u128)
-            .ok_or(LendingError::MathOverflow)?;
-
-        let sqrt_result = fast_math::fast_sqrt(scaled_value)?;
+        // Scale up for precision, then scale back. Values too large to scale take the root
+        // of the scaled value instead, giving up the last 9 digits of precision for range.
+        let sqrt_result = match self.value.checked_mul(PRECISION as u128) {
+            Some(scaled_value) => fast_math::fast_sqrt(scaled_value)?,
+            None => fast_math::fast_sqrt(self.value)?
+                .checked_mul(1_000_000_000)
+                .ok_or(LendingError::MathOverflow)?,
+        };
 
         Ok(Decimal { value: sqrt_result })
     }
@@ -322,6 +325,29 @@ impl Decimal {
         Ok(Decimal { value: result })
     }
 
+    /// Convert to u64, rounding down
+    pub fn try_floor_u64(self) -> Result<u64> {
+        let result = self
+            .value
+            .checked_div(PRECISION as u128)
+            .ok_or(LendingError::DivisionByZero)?;
+
+        if result > u64::MAX as u128 {
+            return Err(LendingError::MathOverflow.into());
+        }
+
+        Ok(result as u64)
+    }
+
+    /// Pack a health factor into basis points (1.0 = 10_000), rounding down and
+    /// saturating at u64::MAX. This is the canonical u64 health representation used by
+    /// indexes, logs and buckets: rounding down keeps `health < 1.0` equivalent to
+    /// `bps < BASIS_POINTS_PRECISION`.
+    pub fn to_health_bps_saturating(self) -> u64 {
+        let bps = self.value / (PRECISION as u128 / BASIS_POINTS_PRECISION as u128);
+        u64::try_from(bps).unwrap_or(u64::MAX)
+    }
+
     /// Convert to floating point representation for display
     pub fn to_scaled_val(self) -> u128 {
         self.value
@@ -424,6 +450,60 @@ pub mod interest {
         }
     }
 
+    /// Linear interest rate model: base_rate + utilization * multiplier
+    pub fn calculate_linear_borrow_rate(
+        utilization_rate_bps: u64,
+        base_rate_bps: u64,
+        multiplier_bps: u64,
+    ) -> Result<u64> {
+        let slope = (utilization_rate_bps as u128)
+            .checked_mul(multiplier_bps as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::DivisionByZero)? as u64;
+
+        Ok(base_rate_bps
+            .checked_add(slope)
+            .ok_or(LendingError::MathOverflow)?)
+    }
+
+    /// Kinked model whose segment above optimal utilization is quadratic
+    /// The rate leaves the kink with a flat slope and reaches the same maximum as the
+    /// kinked model at full utilization, so it penalizes only sustained high utilization.
+    pub fn calculate_curve_borrow_rate(
+        utilization_rate_bps: u64,
+        base_rate_bps: u64,
+        multiplier_bps: u64,
+        jump_multiplier_bps: u64,
+        optimal_utilization_bps: u64,
+    ) -> Result<u64> {
+        if utilization_rate_bps <= optimal_utilization_bps {
+            return calculate_borrow_rate(
+                utilization_rate_bps,
+                base_rate_bps,
+                multiplier_bps,
+                jump_multiplier_bps,
+                optimal_utilization_bps,
+            );
+        }
+
+        // Quadratic portion: base + multiplier + jump_multiplier * (excess / remaining)^2
+        let excess_utilization = (utilization_rate_bps - optimal_utilization_bps) as u128;
+        let remaining_utilization = (BASIS_POINTS_PRECISION - optimal_utilization_bps) as u128;
+
+        let jump_rate = excess_utilization
+            .checked_mul(excess_utilization)
+            .and_then(|v| v.checked_mul(jump_multiplier_bps as u128))
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(remaining_utilization * remaining_utilization)
+            .ok_or(LendingError::DivisionByZero)? as u64;
+
+        Ok(base_rate_bps
+            .checked_add(multiplier_bps)
+            .and_then(|v| v.checked_add(jump_rate))
+            .ok_or(LendingError::MathOverflow)?)
+    }
+
     /// Calculate supply rate from borrow rate
     pub fn calculate_supply_rate(
         borrow_rate_bps: u64,
@@ -474,6 +554,22 @@ pub mod health {
         health_factor < Decimal::one()
     }
 
+    /// Check if a packed health factor (see `Decimal::to_health_bps_saturating`) is liquidatable
+    #[inline(always)]
+    pub fn is_liquidatable_bps(health_bps: u64) -> bool {
+        health_bps < BASIS_POINTS_PRECISION
+    }
+
+    /// Bucket index of a packed health factor
+    /// Bucket `i` holds `HEALTH_BUCKET_BOUNDARIES_BPS[i-1] <= bps < HEALTH_BUCKET_BOUNDARIES_BPS[i]`,
+    /// so bucket 0 is exactly the liquidatable range
+    pub fn health_bucket(health_bps: u64) -> usize {
+        HEALTH_BUCKET_BOUNDARIES_BPS
+            .iter()
+            .position(|&boundary| health_bps < boundary)
+            .unwrap_or(HEALTH_BUCKET_BOUNDARIES_BPS.len())
+    }
+
     /// Calculate maximum liquidation amount (typically 50% of debt)
     pub fn calculate_max_liquidation_amount(
         debt_amount: u64,
@@ -487,6 +583,97 @@ pub mod health {
             .try_into()
             .map_err(|_| LendingError::MathOverflow.into())
     }
+
+    /// Close factor for a liquidation at a packed health factor, in basis points
+    /// `base_close_factor_bps` applies from a health factor of 1.0 down, rising linearly
+    /// to 100% at `FULL_CLOSE_FACTOR_HEALTH_BPS` so a deeply underwater position can be
+    /// closed out before its bad debt grows.
+    pub fn close_factor_bps(base_close_factor_bps: u64, health_bps: u64) -> u64 {
+        if base_close_factor_bps >= BASIS_POINTS_PRECISION
+            || health_bps <= FULL_CLOSE_FACTOR_HEALTH_BPS
+        {
+            return BASIS_POINTS_PRECISION;
+        }
+        if health_bps >= BASIS_POINTS_PRECISION {
+            return base_close_factor_bps;
+        }
+
+        let shortfall_bps = (BASIS_POINTS_PRECISION - health_bps) as u128;
+        let range_bps = (BASIS_POINTS_PRECISION - FULL_CLOSE_FACTOR_HEALTH_BPS) as u128;
+        let increase =
+            (BASIS_POINTS_PRECISION - base_close_factor_bps) as u128 * shortfall_bps / range_bps;
+        base_close_factor_bps + increase as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal_operations() {
+        let a = Decimal::from_integer(10).unwrap();
+        let b = Decimal::from_integer(5).unwrap();
+
+        // Test addition
+        let sum = a.try_add(b).unwrap();
+        assert_eq!(sum.try_floor_u64().unwrap(), 15);
+
+        // Test subtraction
+        let diff = a.try_sub(b).unwrap();
+        assert_eq!(diff.try_floor_u64().unwrap(), 5);
+
+        // Test multiplication
+        let product = a.try_mul(b).unwrap();
+        assert_eq!(product.try_floor_u64().unwrap(), 50);
+
+        // Test division
+        let quotient = a.try_div(b).unwrap();
+        assert_eq!(quotient.try_floor_u64().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_interest_calculations() {
+        // Test utilization rate
+        let utilization = interest::calculate_utilization_rate(8000, 10000).unwrap();
+        assert_eq!(utilization, 8000); // 80%
+
+        // Test borrow rate calculation
+        let borrow_rate = interest::calculate_borrow_rate(
+            8000, // 80% utilization
+            100,  // 1% base rate
+            1000, // 10% multiplier
+            5000, // 50% jump multiplier
+            8000, // 80% optimal utilization
+        )
+        .unwrap();
+        assert_eq!(borrow_rate, 1100); // 11% at optimal utilization
+    }
+
+    #[test]
+    fn test_health_factor() {
+        let collateral = Decimal::from_integer(1000).unwrap();
+        let debt = Decimal::from_integer(500).unwrap();
+        let threshold = Decimal::from_scaled_val(800 * PRECISION as u128 / 10000); // 80%
+
+        let health = health::calculate_health_factor(collateral, debt, threshold).unwrap();
+        assert!(health.try_floor_u64().unwrap() >= 1); // Should be healthy
+
+        assert!(!health::is_liquidatable(health));
+    }
+
+    #[test]
+    fn test_close_factor_scales_when_deeply_underwater() {
+        assert_eq!(health::close_factor_bps(5000, 10_000), 5000);
+        assert_eq!(health::close_factor_bps(5000, 9_999), 5010);
+        assert_eq!(health::close_factor_bps(5000, 9_750), 7500);
+        assert_eq!(
+            health::close_factor_bps(5000, FULL_CLOSE_FACTOR_HEALTH_BPS),
+            10_000
+        );
+        assert_eq!(health::close_factor_bps(5000, 0), 10_000);
+        assert_eq!(health::close_factor_bps(10_000, 9_999), 10_000);
+    }
 }
 
 // Performance testing utilities
@@ -536,4 +723,122 @@ mod performance_tests {
 
         assert!(duration.as_millis() < 50); // Should be very fast
     }
+
+    #[test]
+    fn test_health_bps_packing() {
+        let just_below_one = Decimal::from_scaled_val(PRECISION as u128 - 1);
+        assert_eq!(just_below_one.to_health_bps_saturating(), 9_999);
+        assert!(health::is_liquidatable_bps(just_below_one.to_health_bps_saturating()));
+
+        assert_eq!(Decimal::one().to_health_bps_saturating(), 10_000);
+        assert!(!health::is_liquidatable_bps(Decimal::one().to_health_bps_saturating()));
+
+        let infinite = Decimal::from_scaled_val(u128::MAX);
+        assert_eq!(infinite.to_health_bps_saturating(), u64::MAX);
+
+        assert_eq!(health::health_bucket(9_999), 0);
+        assert_eq!(health::health_bucket(10_000), 1);
+        assert_eq!(health::health_bucket(u64::MAX), HEALTH_BUCKET_BOUNDARIES_BPS.len());
+    }
+}
+
+#[cfg(test)]
+mod property_tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const ONE: u128 = PRECISION as u128;
+
+    /// Pairs whose scaled product fits in a u128, so `try_mul` succeeds
+    fn multipliable() -> impl Strategy<Value = (u128, u128)> {
+        (0..=10u128.pow(30)).prop_flat_map(|a| (Just(a), 1..=u128::MAX / a.max(1)))
+    }
+
+    proptest! {
+        #[test]
+        fn prop_mul_then_div_round_trips((a, b) in multipliable()) {
+            let a_decimal = Decimal::from_scaled_val(a);
+            let b_decimal = Decimal::from_scaled_val(b);
+
+            let round_trip = a_decimal.try_mul(b_decimal).unwrap().try_div(b_decimal).unwrap();
+
+            // Both steps round down; the product's rounding is scaled up by 1 / b
+            prop_assert!(round_trip.value <= a);
+            prop_assert!(a - round_trip.value <= ONE / b + 1);
+        }
+
+        #[test]
+        fn prop_add_sub_round_trips(a in any::<u128>(), b in any::<u128>()) {
+            let a_decimal = Decimal::from_scaled_val(a);
+            let b_decimal = Decimal::from_scaled_val(b);
+
+            match a_decimal.try_add(b_decimal) {
+                Ok(sum) => prop_assert_eq!(sum.try_sub(b_decimal).unwrap(), a_decimal),
+                Err(_) => prop_assert!(a.checked_add(b).is_none()),
+            }
+        }
+
+        #[test]
+        fn prop_operations_never_panic(a in any::<u128>(), b in any::<u128>(), exp in 0u32..64) {
+            let a_decimal = Decimal::from_scaled_val(a);
+            let b_decimal = Decimal::from_scaled_val(b);
+
+            let _ = a_decimal.try_add(b_decimal);
+            let _ = a_decimal.try_sub(b_decimal);
+            let _ = a_decimal.try_mul(b_decimal);
+            let _ = a_decimal.try_div(b_decimal);
+            let _ = a_decimal.try_sqrt();
+            let _ = a_decimal.try_pow(exp);
+            let _ = a_decimal.try_floor_u64();
+            let _ = a_decimal.compound_interest(b_decimal, exp);
+            let _ = a_decimal.to_health_bps_saturating();
+            let _ = fast_math::fast_log2(a);
+        }
+
+        #[test]
+        fn prop_fast_pow_of_one_is_identity(base in any::<u128>()) {
+            prop_assert_eq!(fast_math::fast_pow(base, 1).unwrap(), base);
+        }
+
+        #[test]
+        fn prop_fast_sqrt_is_floor_root(n in any::<u128>()) {
+            if let Ok(root) = fast_math::fast_sqrt(n) {
+                prop_assert!(root * root <= n);
+                prop_assert!((root + 1).checked_mul(root + 1).map_or(true, |next| next > n));
+            }
+        }
+
+        #[test]
+        fn prop_compounding_is_monotonic(
+            principal in 0..=u64::MAX as u128,
+            rate in 0..=ONE,
+            time in 0..=ONE,
+            extra_rate in 0..=ONE,
+            extra_time in 0..=ONE,
+        ) {
+            let grown = fast_math::compound_interest_taylor(principal, rate, time, 8).unwrap();
+            let longer =
+                fast_math::compound_interest_taylor(principal, rate, time + extra_time, 8).unwrap();
+            let faster =
+                fast_math::compound_interest_taylor(principal, rate + extra_rate, time, 8).unwrap();
+
+            prop_assert!(grown >= principal);
+            prop_assert!(longer >= grown);
+            prop_assert!(faster >= grown);
+        }
+
+        #[test]
+        fn prop_borrow_rate_is_monotonic_in_utilization(
+            utilization in 0..=BASIS_POINTS_PRECISION,
+            increase in 0..=BASIS_POINTS_PRECISION,
+            optimal in 1..BASIS_POINTS_PRECISION,
+        ) {
+            let higher = (utilization + increase).min(BASIS_POINTS_PRECISION);
+            let rate = |utilization| {
+                interest::calculate_borrow_rate(utilization, 100, 1_000, 10_000, optimal).unwrap()
+            };
+
+            prop_assert!(rate(higher) >= rate(utilization));
+        }
+    }
 }
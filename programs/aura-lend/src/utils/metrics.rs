@@ -1,5 +1,6 @@
+use crate::constants::HEALTH_BUCKET_BOUNDARIES_BPS;
 use crate::error::LendingError;
-use crate::utils::math::Decimal;
+use crate::utils::math::{health, Decimal};
 use anchor_lang::prelude::*;
 
 /// Protocol metrics for monitoring and analytics
@@ -17,7 +18,7 @@ pub struct ProtocolMetrics {
     /// Total borrowed amount across all reserves
     pub total_borrowed_usd: u64,
 
-    /// Total fees collected by the protocol
+    /// Protocol fees accrued across all reserves and not yet withdrawn
     pub total_fees_collected_usd: u64,
 
     /// Number of active users
@@ -41,8 +42,23 @@ pub struct ProtocolMetrics {
     /// Last update slot
     pub last_update_slot: u64,
 
+    /// Deposits counted since the account was created
+    pub deposit_count: u64,
+
+    /// Borrows counted since the account was created
+    pub borrow_count: u64,
+
+    /// Liquidations counted since the account was created
+    pub liquidation_count: u64,
+
+    /// Start of the current `liquidations_24h` window (unix timestamp)
+    pub liquidations_window_start: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
     /// Reserved space for future metrics
-    pub reserved: [u8; 128],
+    pub reserved: [u8; 95],
 }
 
 impl ProtocolMetrics {
@@ -60,10 +76,18 @@ impl ProtocolMetrics {
         8 + // protocol_utilization_rate
         8 + // last_update_timestamp
         8 + // last_update_slot
-        128; // reserved
+        8 + // deposit_count
+        8 + // borrow_count
+        8 + // liquidation_count
+        8 + // liquidations_window_start
+        1 + // bump
+        95; // reserved
+
+    /// Length of the `liquidations_24h` window in seconds
+    pub const LIQUIDATION_WINDOW_SECONDS: u64 = 24 * 3600;
 
     /// Create new protocol metrics
-    pub fn new(market: Pubkey) -> Result<Self> {
+    pub fn new(market: Pubkey, bump: u8) -> Result<Self> {
         let clock = Clock::get()?;
 
         Ok(Self {
@@ -79,7 +103,12 @@ impl ProtocolMetrics {
             protocol_utilization_rate: 0,
             last_update_timestamp: clock.unix_timestamp as u64,
             last_update_slot: clock.slot,
-            reserved: [0; 128],
+            deposit_count: 0,
+            borrow_count: 0,
+            liquidation_count: 0,
+            liquidations_window_start: clock.unix_timestamp as u64,
+            bump,
+            reserved: [0; 95],
         })
     }
 
@@ -120,12 +149,34 @@ impl ProtocolMetrics {
         Ok(())
     }
 
-    /// Increment liquidation counter
-    pub fn record_liquidation(&mut self) -> Result<()> {
+    /// Count a deposit
+    pub fn record_deposit(&mut self) {
+        self.deposit_count = self.deposit_count.saturating_add(1);
+    }
+
+    /// Count a borrow
+    pub fn record_borrow(&mut self) {
+        self.borrow_count = self.borrow_count.saturating_add(1);
+    }
+
+    /// Count a liquidation at `timestamp`
+    pub fn record_liquidation(&mut self, timestamp: u64) -> Result<()> {
+        self.roll_liquidation_window(timestamp);
         self.liquidations_24h = self.liquidations_24h.saturating_add(1);
+        self.liquidation_count = self.liquidation_count.saturating_add(1);
         Ok(())
     }
 
+    /// Start a new `liquidations_24h` window once the current one has lasted a day
+    pub fn roll_liquidation_window(&mut self, timestamp: u64) {
+        if timestamp.saturating_sub(self.liquidations_window_start)
+            >= Self::LIQUIDATION_WINDOW_SECONDS
+        {
+            self.liquidations_24h = 0;
+            self.liquidations_window_start = timestamp;
+        }
+    }
+
     /// Reset 24h counters (should be called daily)
     pub fn reset_daily_counters(&mut self) -> Result<()> {
         self.liquidations_24h = 0;
@@ -283,7 +334,20 @@ impl ReserveMetrics {
 pub struct MetricsAggregator;
 
 impl MetricsAggregator {
-    /// Calculate average health factor from a list of obligations
+    /// Count obligations per health bucket (see `health::health_bucket`)
+    /// Health factors are packed health bps (`Decimal::to_health_bps_saturating`)
+    pub fn health_distribution(
+        health_factors: &[u64],
+    ) -> [u32; HEALTH_BUCKET_BOUNDARIES_BPS.len() + 1] {
+        let mut distribution = [0u32; HEALTH_BUCKET_BOUNDARIES_BPS.len() + 1];
+        for &health_bps in health_factors {
+            let bucket = health::health_bucket(health_bps);
+            distribution[bucket] = distribution[bucket].saturating_add(1);
+        }
+        distribution
+    }
+
+    /// Calculate average health factor from a list of obligations (health bps)
     pub fn calculate_average_health_factor(health_factors: &[u64]) -> u64 {
         if health_factors.is_empty() {
             return 10000; // 100% if no obligations
@@ -390,7 +454,12 @@ mod tests {
             protocol_utilization_rate: 5000,
             last_update_timestamp: 0,
             last_update_slot: 0,
-            reserved: [0; 128],
+            deposit_count: 0,
+            borrow_count: 0,
+            liquidation_count: 0,
+            liquidations_window_start: 0,
+            bump: 255,
+            reserved: [0; 95],
         };
 
         let current = ProtocolMetrics {
@@ -403,4 +472,39 @@ mod tests {
         let anomalies = MetricsAggregator::detect_anomalies(&current, &previous);
         assert!(anomalies.len() >= 2); // Should detect multiple anomalies
     }
+
+    #[test]
+    fn test_liquidation_window_rolls_daily() {
+        let mut metrics = ProtocolMetrics {
+            version: 1,
+            market: Pubkey::default(),
+            total_value_locked_usd: 0,
+            total_borrowed_usd: 0,
+            total_fees_collected_usd: 0,
+            active_users: 0,
+            active_reserves: 0,
+            liquidations_24h: 0,
+            average_health_factor: 10000,
+            protocol_utilization_rate: 0,
+            last_update_timestamp: 0,
+            last_update_slot: 0,
+            deposit_count: 0,
+            borrow_count: 0,
+            liquidation_count: 0,
+            liquidations_window_start: 1_000,
+            bump: 255,
+            reserved: [0; 95],
+        };
+
+        metrics.record_liquidation(1_000).unwrap();
+        metrics.record_liquidation(1_000 + 3600).unwrap();
+        assert_eq!(metrics.liquidations_24h, 2);
+
+        // A liquidation a day after the window opened starts a new one
+        let next_day = 1_000 + ProtocolMetrics::LIQUIDATION_WINDOW_SECONDS;
+        metrics.record_liquidation(next_day).unwrap();
+        assert_eq!(metrics.liquidations_24h, 1);
+        assert_eq!(metrics.liquidations_window_start, next_day);
+        assert_eq!(metrics.liquidation_count, 3);
+    }
 }
@@ -1,29 +1,33 @@
+pub mod compute_budget;
 pub mod config;
 pub mod iterator_optimized;
 pub mod logging;
 pub mod math;
-pub mod math_optimized;
 pub mod memory_optimized;
 pub mod metrics;
 pub mod oracle;
 pub mod pagination;
 pub mod pagination_optimized;
 pub mod rbac;
+pub mod reentrancy;
+pub mod swap;
 pub mod token;
 
 use anchor_lang::prelude::*;
 
+pub use compute_budget::*;
 pub use config::*;
 pub use iterator_optimized::*;
 pub use logging::*;
 pub use math::*;
-pub use math_optimized::*;
 pub use memory_optimized::*;
 pub use metrics::*;
 pub use oracle::*;
 pub use pagination::*;
 pub use pagination_optimized::*;
 pub use rbac::*;
+pub use reentrancy::*;
+pub use swap::*;
 pub use token::*;
 
 /// Validates that the provided account is a signer
@@ -1,11 +1,29 @@
 use crate::constants::*;
 use crate::error::LendingError;
-use crate::utils::math::Decimal;
+use crate::events::PriceManipulationDetected;
+use crate::state::reserve::{DerivedOracleComponent, OracleSource, Reserve, ReserveConfig};
+use crate::utils::math::{fast_math, Decimal};
 use anchor_lang::prelude::*;
 use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, VerificationLevel};
 
+/// Anchor discriminator of Switchboard V2 `AggregatorAccountData`
+const SWITCHBOARD_AGGREGATOR_DISCRIMINATOR: [u8; 8] = [217, 230, 65, 101, 201, 162, 27, 125];
+
+// Offsets into the packed Switchboard V2 aggregator account (including the discriminator)
+const SWITCHBOARD_MIN_ORACLE_RESULTS_OFFSET: usize = 236;
+const SWITCHBOARD_ROUND_NUM_SUCCESS_OFFSET: usize = 341;
+const SWITCHBOARD_ROUND_OPEN_TIMESTAMP_OFFSET: usize = 358;
+const SWITCHBOARD_ROUND_RESULT_OFFSET: usize = 366;
+const SWITCHBOARD_ROUND_STD_DEVIATION_OFFSET: usize = 386;
+const SWITCHBOARD_AGGREGATOR_MIN_LEN: usize = 406;
+
+// Offsets into the packed SPL stake pool account
+const STAKE_POOL_ACCOUNT_TYPE: u8 = 1;
+const STAKE_POOL_TOTAL_LAMPORTS_OFFSET: usize = 258;
+const STAKE_POOL_POOL_TOKEN_SUPPLY_OFFSET: usize = 266;
+
 /// Oracle price information
-#[derive(Clone, Copy, Debug)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
 pub struct OraclePrice {
     pub price: i64,
     pub confidence: u64,
@@ -56,6 +74,17 @@ impl OraclePrice {
         Ok(Decimal::from_scaled_val(decimal_price))
     }
 
+    /// Confidence interval in basis points of the price (u64::MAX for a zero price)
+    pub fn confidence_bps(&self) -> u64 {
+        let price_abs = self.price.unsigned_abs() as u128;
+        if price_abs == 0 {
+            return u64::MAX;
+        }
+
+        let bps = self.confidence as u128 * BASIS_POINTS_PRECISION as u128 / price_abs;
+        u64::try_from(bps).unwrap_or(u64::MAX)
+    }
+
     /// Check if the price is stale based on current slot
     pub fn is_stale(&self, current_timestamp: i64, max_staleness_seconds: u64) -> bool {
         let age = current_timestamp - self.publish_time;
@@ -92,8 +121,7 @@ impl OraclePrice {
         }
 
         // Check staleness - convert slots to seconds properly
-        // Solana has ~400ms per slot, so max staleness in seconds = slots * 0.4
-        let max_staleness_seconds = (MAX_ORACLE_STALENESS_SLOTS as f64 * 0.4) as u64;
+        let max_staleness_seconds = slots_to_seconds(MAX_ORACLE_STALENESS_SLOTS);
         if self.is_stale(current_timestamp, max_staleness_seconds) {
             return Err(LendingError::OraclePriceStale.into());
         }
@@ -114,7 +142,7 @@ impl OraclePrice {
         }
 
         // Looser staleness check for emergency mode
-        let emergency_staleness_seconds = (EMERGENCY_ORACLE_STALENESS_SLOTS as f64 * 0.4) as u64;
+        let emergency_staleness_seconds = slots_to_seconds(EMERGENCY_ORACLE_STALENESS_SLOTS);
         if self.is_stale(current_timestamp, emergency_staleness_seconds) {
             return Err(LendingError::OraclePriceStale.into());
         }
@@ -141,6 +169,232 @@ impl OraclePrice {
 pub struct OracleManager;
 
 impl OracleManager {
+    /// Get the current price of a reserve from its configured oracle source
+    /// `oracle_account` is the reserve's `price_oracle`; it is not read for fixed-price
+    /// reserves. The price must meet the reserve's own confidence and staleness limits.
+    pub fn get_price(oracle_account: &AccountInfo, reserve: &Reserve) -> Result<OraclePrice> {
+        let price = Self::get_source_price(reserve.oracle_source, oracle_account, reserve)?;
+        Self::validate_reserve_limits(&price, &reserve.config, Clock::get()?.unix_timestamp)?;
+        Ok(price)
+    }
+
+    /// Check a price against a reserve's `max_confidence_bps` and `max_staleness_slots`
+    /// Unset limits (0) leave the price to the protocol-wide checks of
+    /// `OraclePrice::validate`.
+    pub fn validate_reserve_limits(
+        price: &OraclePrice,
+        config: &ReserveConfig,
+        current_timestamp: i64,
+    ) -> Result<()> {
+        if config.max_confidence_bps > 0 && price.confidence_bps() > config.max_confidence_bps {
+            return Err(LendingError::OracleConfidenceTooWide.into());
+        }
+
+        if config.max_staleness_slots > 0
+            && price.is_stale(
+                current_timestamp,
+                slots_to_seconds(config.max_staleness_slots),
+            )
+        {
+            return Err(LendingError::OraclePriceStale.into());
+        }
+
+        Ok(())
+    }
+
+    /// Get a reserve's price for an operation that can only improve obligation health
+    /// Pure deposits and repayments must stay possible during oracle outages, so a
+    /// price that cannot be read or fails validation yields None instead of an error and
+    /// the caller values the operation conservatively.
+    pub fn get_price_if_valid(
+        oracle_account: &AccountInfo,
+        reserve: &Reserve,
+        current_timestamp: i64,
+    ) -> Option<OraclePrice> {
+        let price = Self::get_price(oracle_account, reserve).ok()?;
+        price.validate(current_timestamp).ok()?;
+        Some(price)
+    }
+
+    /// Get a reserve's price, cross-checked against its secondary oracle if configured
+    /// A divergence beyond the reserve's `max_oracle_deviation_bps` emits
+    /// `PriceManipulationDetected` and fails, blocking the operation.
+    pub fn get_checked_price(
+        oracle_account: &AccountInfo,
+        secondary_oracle_account: Option<&AccountInfo>,
+        reserve: &Reserve,
+    ) -> Result<OraclePrice> {
+        let primary = Self::get_price(oracle_account, reserve)?;
+        if !reserve.has_secondary_oracle() {
+            return Ok(primary);
+        }
+
+        let secondary_oracle_account = secondary_oracle_account
+            .filter(|account| *account.key == reserve.secondary_oracle)
+            .ok_or(LendingError::OracleAccountMismatch)?;
+        let secondary = Self::get_source_price(
+            reserve.secondary_oracle_source,
+            secondary_oracle_account,
+            reserve,
+        )?;
+
+        let primary_price = primary.to_decimal()?;
+        let secondary_price = secondary.to_decimal()?;
+        let deviation_bps = Self::price_deviation_bps(primary_price, secondary_price)?;
+        if deviation_bps > reserve.max_oracle_deviation_bps {
+            emit!(PriceManipulationDetected {
+                liquidity_mint: reserve.liquidity_mint,
+                primary_price: primary_price.to_scaled_val(),
+                secondary_price: secondary_price.to_scaled_val(),
+                deviation_bps,
+                max_deviation_bps: reserve.max_oracle_deviation_bps,
+                slot: Clock::get()?.slot,
+            });
+            return Err(LendingError::OraclePriceDeviationTooHigh.into());
+        }
+
+        Ok(primary)
+    }
+
+    /// Divergence of `other` from `reference` in basis points (saturating)
+    pub fn price_deviation_bps(reference: Decimal, other: Decimal) -> Result<u64> {
+        let reference = reference.to_scaled_val();
+        let other = other.to_scaled_val();
+        let deviation_bps = reference
+            .abs_diff(other)
+            .checked_mul(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(reference)
+            .ok_or(LendingError::DivisionByZero)?;
+
+        Ok(u64::try_from(deviation_bps).unwrap_or(u64::MAX))
+    }
+
+    /// Get a price from one of the reserve's oracle sources
+    fn get_source_price(
+        source: OracleSource,
+        oracle_account: &AccountInfo,
+        reserve: &Reserve,
+    ) -> Result<OraclePrice> {
+        match source {
+            OracleSource::Pyth => Self::get_pyth_price(oracle_account, &reserve.oracle_feed_id),
+            OracleSource::Switchboard => Self::get_switchboard_price(oracle_account),
+            OracleSource::FixedPrice => Ok(OraclePrice {
+                price: i64::try_from(reserve.fixed_price)
+                    .map_err(|_| LendingError::OraclePriceInvalid)?,
+                confidence: 0,
+                exponent: FIXED_PRICE_EXPONENT,
+                publish_time: Clock::get()?.unix_timestamp,
+            }),
+            OracleSource::Derived => reserve.derived_oracle.cached_price(),
+        }
+    }
+
+    /// Get the price of a derived price's component from its oracle account
+    pub fn get_component_price(
+        component: &DerivedOracleComponent,
+        oracle_account: &AccountInfo,
+    ) -> Result<OraclePrice> {
+        if *oracle_account.key != component.oracle {
+            return Err(LendingError::OracleAccountMismatch.into());
+        }
+
+        match component.source {
+            OracleSource::Pyth => Self::get_pyth_price(oracle_account, &component.feed_id),
+            OracleSource::Switchboard => Self::get_switchboard_price(oracle_account),
+            OracleSource::FixedPrice | OracleSource::Derived => {
+                Err(LendingError::OracleAccountMismatch.into())
+            }
+        }
+    }
+
+    /// Read the total lamports and pool token supply of an SPL stake pool
+    pub fn get_stake_pool_balances(stake_pool_account: &AccountInfo) -> Result<(u64, u64)> {
+        if stake_pool_account.owner != &SPL_STAKE_POOL_PROGRAM_ID {
+            return Err(LendingError::OracleAccountMismatch.into());
+        }
+
+        let data = stake_pool_account.try_borrow_data()?;
+        Self::parse_stake_pool_balances(&data)
+    }
+
+    /// Parse the total lamports and pool token supply of SPL stake pool account data
+    pub fn parse_stake_pool_balances(data: &[u8]) -> Result<(u64, u64)> {
+        if data.first() != Some(&STAKE_POOL_ACCOUNT_TYPE) {
+            return Err(LendingError::OracleAccountMismatch.into());
+        }
+
+        let total_lamports =
+            u64::from_le_bytes(read_bytes(data, STAKE_POOL_TOTAL_LAMPORTS_OFFSET)?);
+        let pool_token_supply =
+            u64::from_le_bytes(read_bytes(data, STAKE_POOL_POOL_TOKEN_SUPPLY_OFFSET)?);
+        Ok((total_lamports, pool_token_supply))
+    }
+
+    /// Price of a stake pool token: the SOL price times the pool's lamports per token
+    /// Both SOL and the pool token have 9 decimals, so the ratio needs no rescaling.
+    pub fn derive_stake_pool_price(
+        sol_price: &OraclePrice,
+        total_lamports: u64,
+        pool_token_supply: u64,
+    ) -> Result<OraclePrice> {
+        if total_lamports == 0 || pool_token_supply == 0 {
+            return Err(LendingError::OraclePriceInvalid.into());
+        }
+
+        let price = to_fixed_price_units(sol_price)?
+            .checked_mul(total_lamports as u128)
+            .ok_or(LendingError::MathOverflow)?
+            / pool_token_supply as u128;
+        to_derived_price(price, sol_price.confidence_bps(), sol_price.publish_time)
+    }
+
+    /// Fair price of a constant-product LP token
+    /// `2 * sqrt(base_value * quote_value) / lp_supply`, with each side's value taken at its
+    /// oracle price. Trading against the pool moves its reserves along `x * y = k` but
+    /// leaves this product unchanged, so unlike the pool's spot composition the price
+    /// cannot be inflated by skewing the pool within the transaction.
+    pub fn derive_lp_price(
+        base_price: &OraclePrice,
+        base_amount: u64,
+        base_decimals: u8,
+        quote_price: &OraclePrice,
+        quote_amount: u64,
+        quote_decimals: u8,
+        lp_supply: u64,
+        lp_decimals: u8,
+    ) -> Result<OraclePrice> {
+        if lp_supply == 0 {
+            return Err(LendingError::OraclePriceInvalid.into());
+        }
+
+        // Values in units of 10^`FIXED_PRICE_EXPONENT` USD
+        let base_value = fixed_price_value(base_amount, base_price, base_decimals)?;
+        let quote_value = fixed_price_value(quote_amount, quote_price, quote_decimals)?;
+
+        // Pools too large for the product of their values take the roots separately
+        let geometric_mean = match base_value.checked_mul(quote_value) {
+            Some(product) => fast_math::fast_sqrt(product)?,
+            None => fast_math::fast_sqrt(base_value)?
+                .checked_mul(fast_math::fast_sqrt(quote_value)?)
+                .ok_or(LendingError::MathOverflow)?,
+        };
+        let price = geometric_mean
+            .checked_mul(2)
+            .and_then(|value| value.checked_mul(10u128.checked_pow(lp_decimals as u32)?))
+            .ok_or(LendingError::MathOverflow)?
+            / lp_supply as u128;
+
+        // The fair value moves with the square root of each price, so each contributes
+        // half of its relative uncertainty
+        let confidence_bps = base_price
+            .confidence_bps()
+            .saturating_add(quote_price.confidence_bps())
+            .div_ceil(2);
+        let publish_time = base_price.publish_time.min(quote_price.publish_time);
+        to_derived_price(price, confidence_bps, publish_time)
+    }
+
     /// Get price from Pyth price update account
     pub fn get_pyth_price(
         price_update_account: &AccountInfo,
@@ -186,6 +440,76 @@ impl OracleManager {
         })
     }
 
+    /// Get price from a Switchboard V2 aggregator account
+    pub fn get_switchboard_price(aggregator_account: &AccountInfo) -> Result<OraclePrice> {
+        if aggregator_account.owner != &SWITCHBOARD_V2_PROGRAM_ID {
+            return Err(LendingError::OracleAccountMismatch.into());
+        }
+
+        let data = aggregator_account.try_borrow_data()?;
+        Self::parse_switchboard_aggregator(&data)
+    }
+
+    /// Parse the latest confirmed round of Switchboard V2 aggregator account data
+    /// The round result and its standard deviation (used as the confidence interval) are
+    /// `SwitchboardDecimal`s: an i128 mantissa with a u32 decimal scale. Both are brought
+    /// to a common exponent that fits the i64 price.
+    pub fn parse_switchboard_aggregator(data: &[u8]) -> Result<OraclePrice> {
+        if data.len() < SWITCHBOARD_AGGREGATOR_MIN_LEN
+            || data[..8] != SWITCHBOARD_AGGREGATOR_DISCRIMINATOR
+        {
+            return Err(LendingError::OracleAccountMismatch.into());
+        }
+
+        let min_oracle_results =
+            u32::from_le_bytes(read_bytes(data, SWITCHBOARD_MIN_ORACLE_RESULTS_OFFSET)?);
+        let num_success =
+            u32::from_le_bytes(read_bytes(data, SWITCHBOARD_ROUND_NUM_SUCCESS_OFFSET)?);
+        if num_success == 0 || num_success < min_oracle_results {
+            return Err(LendingError::OraclePriceInvalid.into());
+        }
+
+        let publish_time =
+            i64::from_le_bytes(read_bytes(data, SWITCHBOARD_ROUND_OPEN_TIMESTAMP_OFFSET)?);
+        let (mut mantissa, mut scale) =
+            read_switchboard_decimal(data, SWITCHBOARD_ROUND_RESULT_OFFSET)?;
+        let (std_mantissa, std_scale) =
+            read_switchboard_decimal(data, SWITCHBOARD_ROUND_STD_DEVIATION_OFFSET)?;
+
+        // Validate price is not negative
+        if mantissa < 0 {
+            return Err(LendingError::OraclePriceInvalid.into());
+        }
+
+        // Drop precision until the result fits the price
+        while mantissa > i64::MAX as i128 {
+            if scale == 0 {
+                return Err(LendingError::MathOverflow.into());
+            }
+            mantissa /= 10;
+            scale -= 1;
+        }
+
+        // Express the standard deviation at the price's scale
+        let std_abs = std_mantissa.unsigned_abs();
+        let confidence = if std_scale >= scale {
+            std_abs
+                .checked_div(10u128.pow(std_scale - scale))
+                .ok_or(LendingError::DivisionByZero)?
+        } else {
+            std_abs
+                .checked_mul(10u128.pow(scale - std_scale))
+                .ok_or(LendingError::MathOverflow)?
+        };
+
+        Ok(OraclePrice {
+            price: mantissa as i64,
+            confidence: u64::try_from(confidence).map_err(|_| LendingError::MathOverflow)?,
+            exponent: -(scale as i32),
+            publish_time,
+        })
+    }
+
     /// Calculate asset value in USD using oracle price
     pub fn calculate_usd_value(
         amount: u64,
@@ -204,6 +528,23 @@ impl OracleManager {
         amount_decimal.try_mul(price_decimal)
     }
 
+    /// Token amount worth `value_usd` at the oracle price, rounded down
+    pub fn calculate_token_amount(
+        value_usd: Decimal,
+        oracle_price: &OraclePrice,
+        asset_decimals: u8,
+    ) -> Result<u64> {
+        let price_decimal = oracle_price.to_decimal()?;
+        if price_decimal.is_zero() {
+            return Err(LendingError::OraclePriceInvalid.into());
+        }
+
+        value_usd
+            .try_div(price_decimal)?
+            .try_mul(Decimal::from_integer(10u64.pow(asset_decimals as u32))?)?
+            .try_floor_u64()
+    }
+
     /// Calculate liquidation threshold value
     pub fn calculate_liquidation_value(
         collateral_amount: u64,
@@ -324,3 +665,237 @@ impl OracleManager {
         })
     }
 }
+
+/// Seconds spanned by `slots` at ~400ms per slot
+fn slots_to_seconds(slots: u64) -> u64 {
+    slots.saturating_mul(2) / 5
+}
+
+/// Read a fixed-size little-endian field from account data
+fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N]> {
+    data.get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| LendingError::OracleAccountMismatch.into())
+}
+
+/// A price in units of 10^`FIXED_PRICE_EXPONENT` USD
+fn to_fixed_price_units(price: &OraclePrice) -> Result<u128> {
+    Ok(price.to_decimal()?.to_scaled_val() / 10u128.pow((18 + FIXED_PRICE_EXPONENT) as u32))
+}
+
+/// Value of `amount` base units in units of 10^`FIXED_PRICE_EXPONENT` USD
+fn fixed_price_value(amount: u64, price: &OraclePrice, decimals: u8) -> Result<u128> {
+    let unit = 10u128
+        .checked_pow(decimals as u32)
+        .ok_or(LendingError::MathOverflow)?;
+    Ok(to_fixed_price_units(price)?
+        .checked_mul(amount as u128)
+        .ok_or(LendingError::MathOverflow)?
+        / unit)
+}
+
+/// Build a derived price from a value in units of 10^`FIXED_PRICE_EXPONENT` USD
+/// The confidence interval is rounded up so the derived price never looks tighter than
+/// its components.
+fn to_derived_price(price: u128, confidence_bps: u64, publish_time: i64) -> Result<OraclePrice> {
+    let price = i64::try_from(price).map_err(|_| LendingError::OraclePriceInvalid)?;
+    if price <= 0 {
+        return Err(LendingError::OraclePriceInvalid.into());
+    }
+
+    let confidence = (price as u128)
+        .checked_mul(confidence_bps as u128)
+        .ok_or(LendingError::MathOverflow)?
+        .div_ceil(BASIS_POINTS_PRECISION as u128);
+    Ok(OraclePrice {
+        price,
+        confidence: u64::try_from(confidence).map_err(|_| LendingError::MathOverflow)?,
+        exponent: FIXED_PRICE_EXPONENT,
+        publish_time,
+    })
+}
+
+/// Read a Switchboard decimal (i128 mantissa, u32 scale) from account data
+fn read_switchboard_decimal(data: &[u8], offset: usize) -> Result<(i128, u32)> {
+    let mantissa = i128::from_le_bytes(read_bytes(data, offset)?);
+    let scale = u32::from_le_bytes(read_bytes(data, offset + 16)?);
+    // Scales beyond 10^38 cannot be represented
+    if scale > 38 {
+        return Err(LendingError::OraclePriceInvalid.into());
+    }
+    Ok((mantissa, scale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aggregator(num_success: u32, mantissa: i128, scale: u32, std_mantissa: i128) -> Vec<u8> {
+        let mut data = vec![0u8; SWITCHBOARD_AGGREGATOR_MIN_LEN];
+        data[..8].copy_from_slice(&SWITCHBOARD_AGGREGATOR_DISCRIMINATOR);
+        let mut write = |offset: usize, bytes: &[u8]| {
+            data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        };
+        write(SWITCHBOARD_MIN_ORACLE_RESULTS_OFFSET, &1u32.to_le_bytes());
+        write(SWITCHBOARD_ROUND_NUM_SUCCESS_OFFSET, &num_success.to_le_bytes());
+        write(SWITCHBOARD_ROUND_OPEN_TIMESTAMP_OFFSET, &1_700_000_000i64.to_le_bytes());
+        write(SWITCHBOARD_ROUND_RESULT_OFFSET, &mantissa.to_le_bytes());
+        write(SWITCHBOARD_ROUND_RESULT_OFFSET + 16, &scale.to_le_bytes());
+        write(SWITCHBOARD_ROUND_STD_DEVIATION_OFFSET, &std_mantissa.to_le_bytes());
+        write(SWITCHBOARD_ROUND_STD_DEVIATION_OFFSET + 16, &scale.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_parse_switchboard_aggregator() {
+        // $1.0001 with 28 decimals narrows to fit an i64 price
+        let scaled = 10_001 * 10i128.pow(24);
+        let data = aggregator(3, scaled, 28, 10i128.pow(24));
+        let price = OracleManager::parse_switchboard_aggregator(&data).unwrap();
+        assert_eq!(price.exponent, -18);
+        assert_eq!(price.price, 1_000_100_000_000_000_000);
+        assert_eq!(price.confidence, 100_000_000_000_000);
+        assert_eq!(price.publish_time, 1_700_000_000);
+        assert_eq!(
+            price.to_decimal().unwrap(),
+            Decimal::from_scaled_val(1_000_100_000_000_000_000)
+        );
+
+        // Rounds without successful responses and foreign accounts are rejected
+        let unanswered = aggregator(0, scaled, 28, 0);
+        assert!(OracleManager::parse_switchboard_aggregator(&unanswered).is_err());
+        let mut foreign = aggregator(3, scaled, 28, 0);
+        foreign[0] = 0;
+        assert!(OracleManager::parse_switchboard_aggregator(&foreign).is_err());
+    }
+
+    #[test]
+    fn test_token_amount_inverts_usd_value() {
+        // SOL (9 decimals) at $150
+        let price = OraclePrice {
+            price: 15_000_000_000,
+            confidence: 0,
+            exponent: -8,
+            publish_time: 0,
+        };
+        let value = OracleManager::calculate_usd_value(2_000_000_000, &price, 9).unwrap();
+        assert_eq!(value, Decimal::from_integer(300).unwrap());
+        assert_eq!(
+            OracleManager::calculate_token_amount(value, &price, 9).unwrap(),
+            2_000_000_000
+        );
+    }
+
+    #[test]
+    fn test_price_deviation_bps() {
+        let price = |value: u64| Decimal::from_integer(value).unwrap();
+
+        assert_eq!(OracleManager::price_deviation_bps(price(100), price(100)).unwrap(), 0);
+        assert_eq!(OracleManager::price_deviation_bps(price(100), price(103)).unwrap(), 300);
+        assert_eq!(OracleManager::price_deviation_bps(price(100), price(95)).unwrap(), 500);
+        assert!(OracleManager::price_deviation_bps(Decimal::zero(), price(1)).is_err());
+    }
+
+    #[test]
+    fn test_reserve_oracle_limits() {
+        let now = 1_700_000_000;
+        let price = |confidence: u64, age: i64| OraclePrice {
+            price: 100_000_000,
+            confidence,
+            exponent: -8,
+            publish_time: now - age,
+        };
+        let check = |config: &ReserveConfig, price: OraclePrice| {
+            OracleManager::validate_reserve_limits(&price, config, now)
+        };
+
+        // Unset limits defer to the protocol-wide checks
+        let defaults = ReserveConfig::default();
+        assert!(check(&defaults, price(1_500_000, 60)).is_ok());
+
+        // A long-tail asset accepts 0.5% of confidence and 25 slots (10 seconds) of age
+        let strict = ReserveConfig {
+            max_confidence_bps: 50,
+            max_staleness_slots: 25,
+            ..ReserveConfig::default()
+        };
+        assert_eq!(price(500_000, 0).confidence_bps(), 50);
+        assert!(check(&strict, price(500_000, 10)).is_ok());
+        assert!(check(&strict, price(600_000, 0)).is_err());
+        assert!(check(&strict, price(0, 11)).is_err());
+    }
+
+    #[test]
+    fn test_derive_stake_pool_price() {
+        // SOL at $150.00000000 with a 0.1% confidence interval
+        let sol = OraclePrice {
+            price: 15_000_000_000,
+            confidence: 15_000_000,
+            exponent: -8,
+            publish_time: 1_700_000_000,
+        };
+
+        let mut data = vec![0u8; STAKE_POOL_POOL_TOKEN_SUPPLY_OFFSET + 8];
+        data[0] = STAKE_POOL_ACCOUNT_TYPE;
+        data[STAKE_POOL_TOTAL_LAMPORTS_OFFSET..STAKE_POOL_TOTAL_LAMPORTS_OFFSET + 8]
+            .copy_from_slice(&1_100_000_000_000u64.to_le_bytes());
+        data[STAKE_POOL_POOL_TOKEN_SUPPLY_OFFSET..]
+            .copy_from_slice(&1_000_000_000_000u64.to_le_bytes());
+        let (lamports, supply) = OracleManager::parse_stake_pool_balances(&data).unwrap();
+
+        // 1.1 SOL per pool token
+        let price = OracleManager::derive_stake_pool_price(&sol, lamports, supply).unwrap();
+        assert_eq!(price.price, 16_500_000_000);
+        assert_eq!(price.exponent, FIXED_PRICE_EXPONENT);
+        assert_eq!(price.confidence_bps(), 10);
+        assert_eq!(price.publish_time, sol.publish_time);
+
+        data[0] = 2;
+        assert!(OracleManager::parse_stake_pool_balances(&data).is_err());
+        assert!(OracleManager::derive_stake_pool_price(&sol, lamports, 0).is_err());
+    }
+
+    #[test]
+    fn test_derive_lp_price_ignores_pool_skew() {
+        let price = |price: i64, publish_time: i64| OraclePrice {
+            price,
+            confidence: 0,
+            exponent: -8,
+            publish_time,
+        };
+        // SOL at $100 (9 decimals) against USDC at $1 (6 decimals)
+        let sol = price(10_000_000_000, 1_700_000_000);
+        let usdc = price(100_000_000, 1_699_999_990);
+
+        // A balanced pool of 1,000 SOL and 100,000 USDC is worth $200,000 over 1,000 LP
+        let balanced = OracleManager::derive_lp_price(
+            &sol,
+            1_000_000_000_000,
+            9,
+            &usdc,
+            100_000_000_000,
+            6,
+            1_000_000_000,
+            6,
+        )
+        .unwrap();
+        assert_eq!(balanced.price, 20_000_000_000);
+        assert_eq!(balanced.publish_time, 1_699_999_990);
+
+        // Swapping 90,000 USDC in for ~474 SOL keeps k but inflates the spot value
+        let skewed = OracleManager::derive_lp_price(
+            &sol,
+            526_315_789_474,
+            9,
+            &usdc,
+            190_000_000_000,
+            6,
+            1_000_000_000,
+            6,
+        )
+        .unwrap();
+        assert!(skewed.price.abs_diff(balanced.price) <= balanced.price as u64 / 1_000_000);
+
+        assert!(OracleManager::derive_lp_price(&sol, 1, 9, &usdc, 1, 6, 0, 6).is_err());
+    }
+}
@@ -153,7 +153,7 @@ pub struct ObligationQueryParams {
     /// Optional filter by owner
     pub owner: Option<Pubkey>,
     /// Optional filter by health factor below threshold (for liquidation)
-    pub max_health_factor: Option<u64>, // In basis points (10000 = 100%)
+    pub max_health_factor: Option<u64>, // Health bps, see `Decimal::to_health_bps_saturating`
     /// Optional filter by minimum borrowed value
     pub min_borrowed_value: Option<u64>,
     /// Sort by health factor (ascending if true)
@@ -1,3 +1,4 @@
+use crate::constants::{BASIS_POINTS_PRECISION, HEALTH_BUCKET_BOUNDARIES_BPS};
 use crate::error::LendingError;
 use crate::utils::math::Decimal;
 use anchor_lang::prelude::*;
@@ -43,7 +44,7 @@ pub enum SortField {
 
 /// Pre-built indices for fast filtered queries
 pub struct ObligationIndex {
-    /// Health factor index (BTreeMap for range queries)
+    /// Health factor index keyed by health bps (`Decimal::to_health_bps_saturating`)
     pub health_factor_index: BTreeMap<u64, Vec<Pubkey>>,
     /// Borrowed value index
     pub borrowed_value_index: BTreeMap<u64, Vec<Pubkey>>,
@@ -193,6 +194,41 @@ impl ObligationIndex {
         results
     }
 
+    /// Obligations whose packed health is below 1.0 - O(log n + k)
+    pub fn get_liquidatable_obligations(&self, limit: usize) -> Vec<Pubkey> {
+        self.health_factor_index
+            .range(..BASIS_POINTS_PRECISION)
+            .flat_map(|(_, obligations)| obligations.iter().cloned())
+            .take(limit)
+            .collect()
+    }
+
+    /// Obligations in a health bucket (see `health::health_bucket`) - O(log n + k)
+    pub fn get_obligations_by_health_bucket(&self, bucket: usize, limit: usize) -> Vec<Pubkey> {
+        let lower = match bucket {
+            0 => 0,
+            _ => match HEALTH_BUCKET_BOUNDARIES_BPS.get(bucket - 1) {
+                Some(&boundary) => boundary,
+                None => return Vec::new(),
+            },
+        };
+        let upper = HEALTH_BUCKET_BOUNDARIES_BPS
+            .get(bucket)
+            .copied()
+            .unwrap_or(u64::MAX);
+
+        let range = if bucket < HEALTH_BUCKET_BOUNDARIES_BPS.len() {
+            self.health_factor_index.range(lower..upper)
+        } else {
+            self.health_factor_index.range(lower..)
+        };
+
+        range
+            .flat_map(|(_, obligations)| obligations.iter().cloned())
+            .take(limit)
+            .collect()
+    }
+
     /// Fast owner-based query - O(1) lookup
     pub fn get_obligations_by_owner(&self, owner: &Pubkey) -> Option<&Vec<Pubkey>> {
         self.owner_index.get(owner)
@@ -435,6 +471,7 @@ impl PaginationEngine {
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
 pub struct ObligationFilters {
     pub owner: Option<Pubkey>,
+    /// Inclusive upper bound in health bps (`Decimal::to_health_bps_saturating`)
     pub max_health_factor: Option<u64>,
     pub min_borrowed_value: Option<u64>,
     pub reserve: Option<Pubkey>,
@@ -530,6 +567,36 @@ mod tests {
         assert_eq!(healthy.len(), 1); // Should contain our obligation
     }
 
+    #[test]
+    fn test_health_buckets_agree_with_packing() {
+        let mut index = ObligationIndex::new();
+        let unhealthy = Pubkey::new_unique();
+        let at_one = Pubkey::new_unique();
+
+        let health_bps = |value: u128| Decimal::from_scaled_val(value).to_health_bps_saturating();
+        index.add_obligation(
+            unhealthy,
+            Pubkey::new_unique(),
+            health_bps(999_999_999_999_999_999),
+            0,
+            0,
+            &[],
+        );
+        index.add_obligation(
+            at_one,
+            Pubkey::new_unique(),
+            health_bps(1_000_000_000_000_000_000),
+            0,
+            0,
+            &[],
+        );
+
+        assert_eq!(index.get_liquidatable_obligations(10), vec![unhealthy]);
+        assert_eq!(index.get_obligations_by_health_bucket(0, 10), vec![unhealthy]);
+        assert_eq!(index.get_obligations_by_health_bucket(1, 10), vec![at_one]);
+        assert!(index.get_obligations_by_health_bucket(99, 10).is_empty());
+    }
+
     #[test]
     fn test_cursor_pagination() {
         let engine = PaginationEngine::new();
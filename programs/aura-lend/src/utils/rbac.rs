@@ -1,3 +1,4 @@
+use crate::state::governance::{GovernanceRegistry, Permission, PermissionChecker};
 use anchor_lang::prelude::*;
 
 /// Role-based access control definitions
@@ -38,3 +39,31 @@ pub fn is_admin(role: &Role) -> bool {
 pub fn is_operator_or_higher(role: &Role) -> bool {
     matches!(role, Role::SuperAdmin | Role::Admin | Role::Operator)
 }
+
+/// Validates that `holder` signed and holds `permission` in the governance registry
+pub fn require_permission(
+    registry: &GovernanceRegistry,
+    holder: &AccountInfo,
+    permission: Permission,
+) -> Result<()> {
+    super::validate_signer(holder)?;
+    PermissionChecker::check_permission(registry, holder.key, permission)
+}
+
+/// Authorizes an admin instruction signed by `authority`
+/// The market multisig may perform any admin instruction; any other signer needs
+/// `permission` in the market's governance registry.
+pub fn require_admin(
+    authority: &AccountInfo,
+    multisig_owner: &Pubkey,
+    governance: Option<&GovernanceRegistry>,
+    permission: Permission,
+) -> Result<()> {
+    if authority.key == multisig_owner {
+        return super::validate_signer(authority);
+    }
+    match governance {
+        Some(registry) => require_permission(registry, authority, permission),
+        None => Err(error!(crate::error::LendingError::InvalidAuthority)),
+    }
+}
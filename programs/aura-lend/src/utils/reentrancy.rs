@@ -0,0 +1,54 @@
+use crate::error::LendingError;
+use crate::state::Reserve;
+use anchor_lang::prelude::*;
+use std::ops::{Deref, DerefMut};
+
+/// Reentrancy lock held on a reserve for the lifetime of the guard
+/// Acquiring the guard sets the reserve's LOCKED flag and writes it to the account data
+/// right away, so any call nested inside a CPI made while the guard is held sees the
+/// reserve as locked. Dropping the guard clears the flag in memory; the cleared flag is
+/// persisted when the instruction's accounts are written back. An instruction that fails
+/// is rolled back as a whole, so the lock can never be left behind by an error.
+pub struct ReserveLock<'a, 'info> {
+    reserve: &'a mut Account<'info, Reserve>,
+}
+
+impl<'a, 'info> ReserveLock<'a, 'info> {
+    /// Lock `reserve`, failing if another operation already holds it
+    pub fn acquire(reserve: &'a mut Account<'info, Reserve>) -> Result<Self> {
+        lock_reserve(reserve)?;
+
+        Ok(Self { reserve })
+    }
+}
+
+/// Lock `reserve` and write the flag to its account data, as `ReserveLock::acquire` does
+/// For reserves whose lock outlives the function taking it, such as liquidation queue
+/// entries; the caller releases it with `Reserve::unlock`.
+pub fn lock_reserve(reserve: &mut Account<Reserve>) -> Result<()> {
+    if reserve.is_locked() {
+        return Err(LendingError::ReentrantCall.into());
+    }
+    reserve.try_lock()?;
+    reserve.exit(&crate::ID)
+}
+
+impl<'info> Deref for ReserveLock<'_, 'info> {
+    type Target = Account<'info, Reserve>;
+
+    fn deref(&self) -> &Self::Target {
+        self.reserve
+    }
+}
+
+impl DerefMut for ReserveLock<'_, '_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.reserve
+    }
+}
+
+impl Drop for ReserveLock<'_, '_> {
+    fn drop(&mut self) {
+        self.reserve.force_unlock();
+    }
+}
@@ -0,0 +1,210 @@
+use crate::constants::*;
+use crate::error::LendingError;
+use crate::utils::oracle::OraclePrice;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+use anchor_spl::token_interface::TokenAccount;
+
+/// Result of an executed swap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapResult {
+    /// Source tokens spent
+    pub amount_in: u64,
+    /// Destination tokens received
+    pub amount_out: u64,
+}
+
+/// Executes DEX swaps through whitelisted programs
+pub struct SwapExecutor;
+
+impl SwapExecutor {
+    /// Check that a swap program is on the whitelist and is not this program
+    pub fn validate_program(swap_program: &Pubkey, whitelist: &[Pubkey]) -> Result<()> {
+        if *swap_program == crate::ID || !whitelist.contains(swap_program) {
+            return Err(LendingError::SwapProgramNotWhitelisted.into());
+        }
+        Ok(())
+    }
+
+    /// CPI a swap route and verify the resulting balance changes
+    /// `route_accounts` and `route_data` are passed through to the DEX unchanged; when
+    /// `signer_seeds` is non-empty, the PDA they derive is marked as a signer so it can
+    /// authorize spending from `source`. Fails if more than `max_amount_in` was spent or
+    /// less than `min_amount_out` was received.
+    pub fn execute<'info>(
+        swap_program: &AccountInfo<'info>,
+        route_accounts: &[AccountInfo<'info>],
+        route_data: Vec<u8>,
+        signer: Option<&Pubkey>,
+        signer_seeds: &[&[&[u8]]],
+        source: &mut InterfaceAccount<'info, TokenAccount>,
+        destination: &mut InterfaceAccount<'info, TokenAccount>,
+        max_amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<SwapResult> {
+        let source_before = source.amount;
+        let destination_before = destination.amount;
+
+        let accounts = route_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: *account.key,
+                is_signer: account.is_signer || Some(account.key) == signer,
+                is_writable: account.is_writable,
+            })
+            .collect();
+
+        let instruction = Instruction {
+            program_id: *swap_program.key,
+            accounts,
+            data: route_data,
+        };
+
+        let mut account_infos = route_accounts.to_vec();
+        account_infos.push(swap_program.clone());
+        invoke_signed(&instruction, &account_infos, signer_seeds)?;
+
+        source.reload()?;
+        destination.reload()?;
+
+        let amount_in = source_before
+            .checked_sub(source.amount)
+            .ok_or(LendingError::InvalidAmount)?;
+        let amount_out = destination
+            .amount
+            .checked_sub(destination_before)
+            .ok_or(LendingError::InvalidAmount)?;
+
+        if amount_in > max_amount_in {
+            return Err(LendingError::AmountTooLarge.into());
+        }
+        if amount_out < min_amount_out {
+            return Err(LendingError::SlippageExceeded.into());
+        }
+
+        Ok(SwapResult {
+            amount_in,
+            amount_out,
+        })
+    }
+
+    /// Settle a same-asset leg by internal accounting instead of a DEX swap
+    /// When both sides of a leg hold the same mint, `amount` is passed through one to
+    /// one, saving the DEX fee and slippage. The leg must not carry a route, and the
+    /// same spending and output bounds as `execute` apply.
+    pub fn net(
+        route_accounts: &[AccountInfo],
+        amount: u64,
+        max_amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<SwapResult> {
+        if !route_accounts.is_empty() {
+            return Err(LendingError::InvalidAccount.into());
+        }
+        if amount > max_amount_in {
+            return Err(LendingError::AmountTooLarge.into());
+        }
+        if amount < min_amount_out {
+            return Err(LendingError::SlippageExceeded.into());
+        }
+
+        Ok(SwapResult {
+            amount_in: amount,
+            amount_out: amount,
+        })
+    }
+
+    /// Minimum acceptable output for `amount_in` at oracle prices, less `max_slippage_bps`
+    pub fn oracle_min_amount_out(
+        amount_in: u64,
+        price_in: &OraclePrice,
+        decimals_in: u8,
+        price_out: &OraclePrice,
+        decimals_out: u8,
+        max_slippage_bps: u64,
+    ) -> Result<u64> {
+        if max_slippage_bps > BASIS_POINTS_PRECISION {
+            return Err(LendingError::InvalidConfiguration.into());
+        }
+
+        let price_in = price_in.to_decimal()?;
+        let price_out = price_out.to_decimal()?;
+        if price_out.is_zero() {
+            return Err(LendingError::OraclePriceInvalid.into());
+        }
+
+        // amount_in * price_in / 10^decimals_in is the USD value scaled by 1e18
+        let expected_out = (amount_in as u128)
+            .checked_mul(price_in.value)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(10u128.pow(decimals_in as u32))
+            .ok_or(LendingError::DivisionByZero)?
+            .checked_mul(10u128.pow(decimals_out as u32))
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(price_out.value)
+            .ok_or(LendingError::DivisionByZero)?;
+
+        let min_out = expected_out
+            .checked_mul((BASIS_POINTS_PRECISION - max_slippage_bps) as u128)
+            .ok_or(LendingError::MathOverflow)?
+            .checked_div(BASIS_POINTS_PRECISION as u128)
+            .ok_or(LendingError::DivisionByZero)?;
+
+        u64::try_from(min_out).map_err(|_| LendingError::MathOverflow.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(price: i64, exponent: i32) -> OraclePrice {
+        OraclePrice {
+            price,
+            confidence: 0,
+            exponent,
+            publish_time: 0,
+        }
+    }
+
+    #[test]
+    fn test_oracle_min_amount_out_across_decimals() {
+        // 2 SOL (9 decimals) at $150 into USDC (6 decimals) at $1, 1% slippage
+        let min_out = SwapExecutor::oracle_min_amount_out(
+            2_000_000_000,
+            &price(15_000_000_000, -8),
+            9,
+            &price(100_000_000, -8),
+            6,
+            100,
+        )
+        .unwrap();
+
+        assert_eq!(min_out, 297_000_000);
+    }
+
+    #[test]
+    fn test_net_applies_swap_bounds() {
+        let result = SwapExecutor::net(&[], 500, 500, 500).unwrap();
+        assert_eq!(
+            result,
+            SwapResult {
+                amount_in: 500,
+                amount_out: 500,
+            }
+        );
+
+        assert!(SwapExecutor::net(&[], 501, 500, 0).is_err());
+        assert!(SwapExecutor::net(&[], 499, 500, 500).is_err());
+    }
+
+    #[test]
+    fn test_whitelist_rejects_self_and_unknown_programs() {
+        let dex = Pubkey::new_unique();
+        let whitelist = [dex, crate::ID];
+
+        assert!(SwapExecutor::validate_program(&dex, &whitelist).is_ok());
+        assert!(SwapExecutor::validate_program(&crate::ID, &whitelist).is_err());
+        assert!(SwapExecutor::validate_program(&Pubkey::new_unique(), &whitelist).is_err());
+    }
+}
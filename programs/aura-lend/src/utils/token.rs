@@ -1,23 +1,33 @@
 use crate::error::LendingError;
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+};
+use anchor_spl::token_interface::{
+    self, Burn, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
+};
 use spl_token::instruction::AuthorityType;
 
-/// Token utility functions for SPL token operations
+/// Token utility functions for SPL Token and Token-2022 operations
 pub struct TokenUtils;
 
 impl TokenUtils {
     /// Transfer tokens from one account to another
+    /// Uses `transfer_checked`, which Token-2022 requires for mints with a transfer fee.
+    /// The destination receives `amount` less any fee the mint withholds.
     pub fn transfer_tokens<'info>(
-        token_program: &Program<'info, Token>,
-        from: &Account<'info, TokenAccount>,
-        to: &Account<'info, TokenAccount>,
+        token_program: &Interface<'info, TokenInterface>,
+        from: &InterfaceAccount<'info, TokenAccount>,
+        to: &InterfaceAccount<'info, TokenAccount>,
+        mint: &InterfaceAccount<'info, Mint>,
         authority: &AccountInfo<'info>,
         authority_signer_seeds: &[&[&[u8]]],
         amount: u64,
     ) -> Result<()> {
-        let cpi_accounts = Transfer {
+        let cpi_accounts = TransferChecked {
             from: from.to_account_info(),
+            mint: mint.to_account_info(),
             to: to.to_account_info(),
             authority: authority.clone(),
         };
@@ -32,14 +42,63 @@ impl TokenUtils {
             )
         };
 
-        token::transfer(cpi_context, amount)
+        token_interface::transfer_checked(cpi_context, amount, mint.decimals)
+    }
+
+    /// Transfer fee configuration of a Token-2022 mint, if it has one
+    fn transfer_fee_config(mint: &InterfaceAccount<Mint>) -> Result<Option<TransferFeeConfig>> {
+        let mint_info = mint.to_account_info();
+        if *mint_info.owner != spl_token_2022::ID {
+            return Ok(None);
+        }
+
+        let mint_data = mint_info.try_borrow_data()?;
+        let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+        Ok(mint_state
+            .get_extension::<TransferFeeConfig>()
+            .ok()
+            .copied())
+    }
+
+    /// Transfer fee a mint withholds when `amount` is transferred in the current epoch
+    /// Legacy SPL Token mints and Token-2022 mints without a transfer fee charge nothing.
+    pub fn transfer_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+        let fee = match Self::transfer_fee_config(mint)? {
+            Some(transfer_fee_config) => transfer_fee_config
+                .calculate_epoch_fee(Clock::get()?.epoch, amount)
+                .ok_or(LendingError::MathOverflow)?,
+            None => 0,
+        };
+
+        Ok(fee)
+    }
+
+    /// Amount the destination receives when `amount` is transferred
+    pub fn amount_after_transfer_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+        let fee = Self::transfer_fee(mint, amount)?;
+        let received = amount.checked_sub(fee).ok_or(LendingError::MathUnderflow)?;
+
+        Ok(received)
+    }
+
+    /// Amount to transfer so that the destination receives exactly `amount`
+    pub fn amount_before_transfer_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+        let fee = match Self::transfer_fee_config(mint)? {
+            Some(transfer_fee_config) => transfer_fee_config
+                .calculate_inverse_epoch_fee(Clock::get()?.epoch, amount)
+                .ok_or(LendingError::MathOverflow)?,
+            None => 0,
+        };
+        let sent = amount.checked_add(fee).ok_or(LendingError::MathOverflow)?;
+
+        Ok(sent)
     }
 
     /// Mint new tokens to an account
     pub fn mint_tokens<'info>(
-        token_program: &Program<'info, Token>,
-        mint: &Account<'info, Mint>,
-        to: &Account<'info, TokenAccount>,
+        token_program: &Interface<'info, TokenInterface>,
+        mint: &InterfaceAccount<'info, Mint>,
+        to: &InterfaceAccount<'info, TokenAccount>,
         mint_authority: &AccountInfo<'info>,
         authority_signer_seeds: &[&[&[u8]]],
         amount: u64,
@@ -60,14 +119,14 @@ impl TokenUtils {
             )
         };
 
-        token::mint_to(cpi_context, amount)
+        token_interface::mint_to(cpi_context, amount)
     }
 
     /// Burn tokens from an account
     pub fn burn_tokens<'info>(
-        token_program: &Program<'info, Token>,
-        mint: &Account<'info, Mint>,
-        from: &Account<'info, TokenAccount>,
+        token_program: &Interface<'info, TokenInterface>,
+        mint: &InterfaceAccount<'info, Mint>,
+        from: &InterfaceAccount<'info, TokenAccount>,
         authority: &AccountInfo<'info>,
         authority_signer_seeds: &[&[&[u8]]],
         amount: u64,
@@ -88,7 +147,7 @@ impl TokenUtils {
             )
         };
 
-        token::burn(cpi_context, amount)
+        token_interface::burn(cpi_context, amount)
     }
 
     /// Get the amount of tokens accounting for decimals
@@ -103,7 +162,7 @@ impl TokenUtils {
 
     /// Validate that token accounts have the expected mint
     pub fn validate_token_mint(
-        token_account: &Account<TokenAccount>,
+        token_account: &InterfaceAccount<TokenAccount>,
         expected_mint: &Pubkey,
     ) -> Result<()> {
         if token_account.mint != *expected_mint {
@@ -114,7 +173,7 @@ impl TokenUtils {
 
     /// Validate that token account has the expected owner
     pub fn validate_token_owner(
-        token_account: &Account<TokenAccount>,
+        token_account: &InterfaceAccount<TokenAccount>,
         expected_owner: &Pubkey,
     ) -> Result<()> {
         if token_account.owner != *expected_owner {
@@ -125,7 +184,7 @@ impl TokenUtils {
 
     /// Check if account has sufficient token balance
     pub fn validate_sufficient_balance(
-        token_account: &Account<TokenAccount>,
+        token_account: &InterfaceAccount<TokenAccount>,
         required_amount: u64,
     ) -> Result<()> {
         if token_account.amount < required_amount {
@@ -204,9 +263,9 @@ impl TokenUtils {
         Ok(withdraw_amount as u64)
     }
 
-    /// Validate that the token program is the expected SPL Token program
+    /// Validate that the token program is SPL Token or Token-2022
     pub fn validate_token_program(token_program: &AccountInfo) -> Result<()> {
-        if token_program.key() != spl_token::ID {
+        if token_program.key() != spl_token::ID && token_program.key() != spl_token_2022::ID {
             return Err(LendingError::InvalidTokenProgram.into());
         }
         Ok(())
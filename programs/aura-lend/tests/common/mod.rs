@@ -0,0 +1,565 @@
+//! Harness shared by the program-test suites
+//!
+//! Starts the program's BPF build under solana-program-test with a market, two fixed
+//! price reserves and a borrower's obligation holding collateral in the first one.
+
+#![allow(dead_code)]
+
+use anchor_lang::{AccountDeserialize, AccountSerialize};
+use anchor_spl::token::spl_token;
+use aura_lend::constants::*;
+use aura_lend::state::{
+    InitializeFeeConverterParams, InitializeMarketParams, InitializeReserveParams, Obligation,
+    OracleSource, Reserve, ReserveConfig, ReserveConfigFlags,
+};
+use aura_lend::utils::config::ConfigUpdateParams;
+use aura_lend_cpi::ix::{self, ObligationKeys};
+use aura_lend_cpi::{accounts, instruction, obligation_refresh_accounts, pda, ReserveKeys};
+use solana_program_test::{BanksClient, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::AccountSharedData;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use solana_sdk::{system_instruction, system_program, sysvar};
+
+/// 1.00 USD in `FIXED_PRICE_EXPONENT` units
+pub const ONE_USD: u64 = 100_000_000;
+
+pub const DECIMALS: u8 = 6;
+pub const ONE_TOKEN: u64 = 1_000_000;
+
+/// Liquidity minted to each wallet per mint
+pub const WALLET_BALANCE: u64 = 1_000_000 * ONE_TOKEN;
+
+/// Accounts of one reserve and the wallets' token accounts of it
+pub struct TestReserve {
+    pub keys: ReserveKeys,
+
+    /// Reserve's token account holding obligation collateral
+    pub collateral_supply: Pubkey,
+
+    /// Liquidity and aToken accounts of each wallet, indexed like `Harness::wallets`
+    pub liquidity: Vec<Pubkey>,
+    pub collateral: Vec<Pubkey>,
+}
+
+/// Market with a collateral reserve and a borrow reserve, both fixed price
+pub struct Harness {
+    pub context: ProgramTestContext,
+
+    /// Market owner, then the borrower, then the liquidator
+    pub wallets: Vec<Keypair>,
+
+    pub collateral: TestReserve,
+    pub borrow: TestReserve,
+    pub obligation: ObligationKeys,
+}
+
+pub const OWNER: usize = 0;
+pub const BORROWER: usize = 1;
+pub const LIQUIDATOR: usize = 2;
+
+impl Harness {
+    pub async fn new() -> Self {
+        Self::with_programs(|_| {}).await
+    }
+
+    /// Like `new`, with further programs such as a mock DEX added by `add_programs`
+    pub async fn with_programs(add_programs: impl FnOnce(&mut ProgramTest)) -> Self {
+        let mut program_test = ProgramTest::new("aura_lend", aura_lend::ID, None);
+        add_programs(&mut program_test);
+        let mut context = program_test.start_with_context().await;
+        let wallets: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+        let payer = context.payer.insecure_clone();
+        for wallet in &wallets {
+            send(
+                &mut context,
+                &[system_instruction::transfer(
+                    &payer.pubkey(),
+                    &wallet.pubkey(),
+                    100_000_000_000,
+                )],
+                &[],
+            )
+            .await;
+        }
+        let owner = wallets[OWNER].insecure_clone();
+
+        // Market, audit log and protocol configuration
+        let quote_mint = create_mint(&mut context, &owner.pubkey()).await;
+        let aura_mint_authority = Pubkey::new_unique();
+        let aura_mint = create_mint(&mut context, &aura_mint_authority).await;
+        let market = pda::market();
+        let admin_audit_log =
+            Pubkey::find_program_address(&[ADMIN_AUDIT_LOG_SEED], &aura_lend::ID).0;
+        send(
+            &mut context,
+            &[
+                ix::build(
+                    accounts::InitializeMarket {
+                        market,
+                        quote_currency_mint: quote_mint,
+                        aura_token_mint: aura_mint,
+                        aura_mint_authority,
+                        payer: owner.pubkey(),
+                        system_program: system_program::ID,
+                    },
+                    instruction::InitializeMarket {
+                        params: InitializeMarketParams {
+                            multisig_owner: owner.pubkey(),
+                            emergency_authority: owner.pubkey(),
+                            governance: owner.pubkey(),
+                            timelock_controller: owner.pubkey(),
+                            quote_currency: quote_mint,
+                            aura_token_mint: aura_mint,
+                        },
+                    },
+                ),
+                ix::build(
+                    accounts::InitializeAdminAuditLog {
+                        market,
+                        admin_audit_log,
+                        authority: owner.pubkey(),
+                        payer: owner.pubkey(),
+                        system_program: system_program::ID,
+                    },
+                    instruction::InitializeAdminAuditLog {},
+                ),
+                ix::build(
+                    accounts::InitializeConfig {
+                        config: pda::config(),
+                        authority: owner.pubkey(),
+                        system_program: system_program::ID,
+                    },
+                    instruction::InitializeConfig {
+                        params: ConfigUpdateParams::default(),
+                    },
+                ),
+            ],
+            &[&owner],
+        )
+        .await;
+
+        let collateral = create_reserve(&mut context, &wallets, admin_audit_log).await;
+        let borrow = create_reserve(&mut context, &wallets, admin_audit_log).await;
+
+        // The borrower's obligation, with 1,000 tokens of collateral
+        let borrower = wallets[BORROWER].insecure_clone();
+        let obligation = ObligationKeys {
+            obligation: pda::obligation(&borrower.pubkey(), 0),
+            owner: borrower.pubkey(),
+            emode_category: None,
+            isolated_collateral_reserve: None,
+        };
+        send(
+            &mut context,
+            &[
+                ix::build(
+                    accounts::InitObligation {
+                        market,
+                        obligation: obligation.obligation,
+                        obligation_owner: borrower.pubkey(),
+                        payer: borrower.pubkey(),
+                        system_program: system_program::ID,
+                    },
+                    instruction::InitObligation {
+                        obligation_index: 0,
+                    },
+                ),
+                ix::deposit_reserve_liquidity(
+                    &collateral.keys,
+                    &borrower.pubkey(),
+                    &collateral.liquidity[BORROWER],
+                    &collateral.collateral[BORROWER],
+                    1_000 * ONE_TOKEN,
+                ),
+                ix::deposit_obligation_collateral(
+                    &collateral.keys,
+                    &obligation,
+                    &collateral.collateral[BORROWER],
+                    &collateral.collateral_supply,
+                    1_000 * ONE_TOKEN,
+                ),
+            ],
+            &[&borrower],
+        )
+        .await;
+
+        Self {
+            context,
+            wallets,
+            collateral,
+            borrow,
+            obligation,
+        }
+    }
+
+    pub fn reserves(&self) -> [ReserveKeys; 2] {
+        [self.collateral.keys, self.borrow.keys]
+    }
+
+    /// Refresh both reserves, then the obligation
+    pub async fn refreshes(&mut self) -> Vec<Instruction> {
+        let obligation: Obligation =
+            load(&mut self.context.banks_client, &self.obligation.obligation).await;
+        let refresh_accounts = obligation_refresh_accounts(&obligation, &self.reserves()).unwrap();
+
+        let mut refreshes: Vec<Instruction> =
+            self.reserves().iter().map(ix::refresh_reserve).collect();
+        refreshes.push(ix::refresh_obligation(
+            &self.obligation.obligation,
+            refresh_accounts,
+        ));
+        refreshes
+    }
+
+    /// Compute units `instruction` consumes after `prefix`, in one transaction
+    pub async fn measure(&mut self, prefix: &[Instruction], instruction: Instruction) -> u64 {
+        let with_instruction = [prefix, &[instruction]].concat();
+        let total = self.simulate(&with_instruction).await;
+        let before = if prefix.is_empty() {
+            0
+        } else {
+            self.simulate(prefix).await
+        };
+        total - before
+    }
+
+    /// Compute units of a successful simulation of `instructions`, signed by the wallets
+    /// they require
+    pub async fn simulate(&mut self, instructions: &[Instruction]) -> u64 {
+        let payer = self.context.payer.insecure_clone();
+        let mut signers = vec![&payer];
+        signers.extend(self.wallets.iter().filter(|wallet| {
+            instructions.iter().any(|instruction| {
+                instruction
+                    .accounts
+                    .iter()
+                    .any(|meta| meta.is_signer && meta.pubkey == wallet.pubkey())
+            })
+        }));
+
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer.pubkey()),
+            &signers,
+            blockhash,
+        );
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await
+            .unwrap();
+        let details = simulation.simulation_details.unwrap();
+        if let Some(Err(error)) = simulation.result {
+            panic!("simulation failed: {error:?}\n{}", details.logs.join("\n"));
+        }
+        details.units_consumed
+    }
+
+    /// Create the market's fee converter, whitelisting `swap_programs` for position swaps
+    pub async fn init_fee_converter(&mut self, swap_programs: Vec<Pubkey>) -> Pubkey {
+        let owner = self.wallets[OWNER].insecure_clone();
+        let market = pda::market();
+        let fee_converter =
+            Pubkey::find_program_address(&[FEE_CONVERTER_SEED, market.as_ref()], &aura_lend::ID).0;
+        let insurance_fund = pda::insurance_fund(&self.borrow.keys.reserve);
+        send(
+            &mut self.context,
+            &[ix::build(
+                accounts::InitializeFeeConverter {
+                    market,
+                    fee_converter,
+                    target_mint: self.borrow.keys.liquidity_mint,
+                    destination: pda::insurance_fund_vault(&insurance_fund),
+                    authority: owner.pubkey(),
+                    admin_audit_log: Pubkey::find_program_address(
+                        &[ADMIN_AUDIT_LOG_SEED],
+                        &aura_lend::ID,
+                    )
+                    .0,
+                    payer: owner.pubkey(),
+                    system_program: system_program::ID,
+                    governance: None,
+                },
+                instruction::InitializeFeeConverter {
+                    params: InitializeFeeConverterParams {
+                        max_slippage_bps: 100,
+                        swap_programs,
+                    },
+                },
+            )],
+            &[&owner],
+        )
+        .await;
+        fee_converter
+    }
+
+    /// Reprice the collateral reserve's fixed price to `price`
+    pub async fn set_collateral_price(&mut self, price: u64) {
+        let address = self.collateral.keys.reserve;
+        let mut account = self
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap();
+        let mut reserve = Reserve::try_deserialize(&mut account.data.as_slice()).unwrap();
+        reserve.fixed_price = price;
+
+        let mut data = Vec::new();
+        reserve.try_serialize(&mut data).unwrap();
+        account.data[..data.len()].copy_from_slice(&data);
+        self.context
+            .set_account(&address, &AccountSharedData::from(account));
+    }
+}
+
+/// Create a fixed price reserve of a new mint seeded with liquidity, its insurance fund,
+/// and every wallet's token accounts of it
+pub async fn create_reserve(
+    context: &mut ProgramTestContext,
+    wallets: &[Keypair],
+    admin_audit_log: Pubkey,
+) -> TestReserve {
+    let owner = wallets[OWNER].insecure_clone();
+    let liquidity_mint = create_mint(context, &owner.pubkey()).await;
+    let mut liquidity = Vec::new();
+    for wallet in wallets {
+        let account = create_token_account(context, &liquidity_mint, &wallet.pubkey()).await;
+        mint_to(context, &liquidity_mint, &account, &owner, WALLET_BALANCE).await;
+        liquidity.push(account);
+    }
+
+    let reserve = pda::reserve(&liquidity_mint);
+    let collateral_mint = pda::collateral_mint(&liquidity_mint);
+    let fee_receiver = Keypair::new();
+    let treasury_collateral = Keypair::new();
+    let price_oracle = Pubkey::new_unique();
+    let initialize_reserve = ix::build(
+        accounts::InitializeReserve {
+            market: pda::market(),
+            reserve,
+            liquidity_mint,
+            collateral_mint,
+            collateral_mint_authority: pda::collateral_authority(&liquidity_mint),
+            liquidity_supply: pda::liquidity_supply(&liquidity_mint),
+            liquidity_supply_authority: pda::liquidity_supply_authority(&liquidity_mint),
+            fee_receiver: fee_receiver.pubkey(),
+            treasury_collateral: treasury_collateral.pubkey(),
+            payer_liquidity: Some(liquidity[OWNER]),
+            owner: owner.pubkey(),
+            admin_audit_log,
+            payer: owner.pubkey(),
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::InitializeReserve {
+            params: InitializeReserveParams {
+                liquidity_mint,
+                price_oracle,
+                oracle_feed_id: [0; 32],
+                oracle_source: OracleSource::FixedPrice,
+                fixed_price: ONE_USD,
+                config: reserve_config(),
+                initial_liquidity: Some(100_000 * ONE_TOKEN),
+            },
+        },
+    );
+
+    let insurance_fund = pda::insurance_fund(&reserve);
+    let initialize_insurance_fund = ix::build(
+        accounts::InitializeInsuranceFund {
+            market: pda::market(),
+            reserve,
+            liquidity_mint,
+            insurance_fund,
+            liquidity_vault: pda::insurance_fund_vault(&insurance_fund),
+            authority: owner.pubkey(),
+            admin_audit_log,
+            payer: owner.pubkey(),
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::InitializeInsuranceFund {},
+    );
+    send(
+        context,
+        &[initialize_reserve, initialize_insurance_fund],
+        &[&owner, &fee_receiver, &treasury_collateral],
+    )
+    .await;
+
+    let mut collateral = Vec::new();
+    for wallet in wallets {
+        collateral.push(create_token_account(context, &collateral_mint, &wallet.pubkey()).await);
+    }
+    let collateral_supply = create_token_account(
+        context,
+        &collateral_mint,
+        &pda::collateral_authority(&liquidity_mint),
+    )
+    .await;
+
+    TestReserve {
+        keys: ReserveKeys {
+            reserve,
+            liquidity_mint,
+            collateral_mint,
+            liquidity_supply: pda::liquidity_supply(&liquidity_mint),
+            fee_receiver: fee_receiver.pubkey(),
+            price_oracle,
+            secondary_oracle: None,
+            token_program: spl_token::ID,
+        },
+        collateral_supply,
+        liquidity,
+        collateral,
+    }
+}
+
+pub fn reserve_config() -> ReserveConfig {
+    ReserveConfig {
+        loan_to_value_ratio_bps: 7_500,
+        liquidation_threshold_bps: 8_000,
+        liquidation_penalty_bps: 500,
+        base_borrow_rate_bps: 100,
+        borrow_rate_multiplier_bps: 1_000,
+        jump_rate_multiplier_bps: 10_000,
+        optimal_utilization_rate_bps: 8_000,
+        protocol_fee_bps: 1_000,
+        max_borrow_rate_bps: 30_000,
+        decimals: DECIMALS,
+        flags: ReserveConfigFlags::COLLATERAL_ENABLED,
+        ..ReserveConfig::default()
+    }
+}
+
+pub async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) {
+    try_send(context, instructions, signers).await.unwrap();
+}
+
+/// Like `send`, returning the transaction's error instead of panicking on it
+pub async fn try_send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let payer = &context.payer;
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+pub async fn load<T: AccountDeserialize>(banks_client: &mut BanksClient, address: &Pubkey) -> T {
+    let account = banks_client.get_account(*address).await.unwrap().unwrap();
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+/// Token balance of a token account
+pub async fn token_balance(banks_client: &mut BanksClient, address: &Pubkey) -> u64 {
+    let account = banks_client.get_account(*address).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
+pub async fn create_mint(context: &mut ProgramTestContext, authority: &Pubkey) -> Pubkey {
+    let mint = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    send(
+        context,
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint2(
+                &spl_token::ID,
+                &mint.pubkey(),
+                authority,
+                None,
+                DECIMALS,
+            )
+            .unwrap(),
+        ],
+        &[&mint],
+    )
+    .await;
+    mint.pubkey()
+}
+
+pub async fn create_token_account(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Pubkey {
+    let account = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    send(
+        context,
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &account.pubkey(),
+                rent.minimum_balance(spl_token::state::Account::LEN),
+                spl_token::state::Account::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_account3(
+                &spl_token::ID,
+                &account.pubkey(),
+                mint,
+                owner,
+            )
+            .unwrap(),
+        ],
+        &[&account],
+    )
+    .await;
+    account.pubkey()
+}
+
+pub async fn mint_to(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    account: &Pubkey,
+    authority: &Keypair,
+    amount: u64,
+) {
+    send(
+        context,
+        &[spl_token::instruction::mint_to(
+            &spl_token::ID,
+            mint,
+            account,
+            &authority.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap()],
+        &[authority],
+    )
+    .await;
+}
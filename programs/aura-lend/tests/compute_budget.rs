@@ -0,0 +1,133 @@
+//! Compute unit regression harness
+//!
+//! Runs the program's BPF build under solana-program-test and checks the compute units
+//! of refresh_reserve, deposit, borrow, repay and liquidate against the budgets in
+//! `constants.rs`. Build the program and run it with
+//! `cargo test-sbf --features test-sbf --test compute_budget`.
+//!
+//! An instruction needing a prefix in its transaction (refreshes before a borrow) is
+//! measured as the units of prefix + instruction minus those of the prefix alone.
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use aura_lend::constants::*;
+use aura_lend::state::Obligation;
+use aura_lend_cpi::ix::{self, LiquidatorKeys};
+use aura_lend_cpi::obligation_refresh_accounts;
+use common::*;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Signer;
+
+fn assert_within_budget(name: &str, units: u64, budget: u64) {
+    println!("{name}: {units} / {budget} compute units");
+    assert!(
+        units <= budget,
+        "{name} consumed {units} compute units, over its budget of {budget}"
+    );
+}
+
+#[tokio::test]
+async fn test_refresh_reserve_within_budget() {
+    let mut harness = Harness::new().await;
+
+    let refresh = ix::refresh_reserve(&harness.borrow.keys);
+    let units = harness.measure(&[], refresh).await;
+    assert_within_budget("refresh_reserve", units, REFRESH_RESERVE_COMPUTE_BUDGET);
+}
+
+#[tokio::test]
+async fn test_deposit_within_budget() {
+    let mut harness = Harness::new().await;
+
+    let deposit = ix::deposit_reserve_liquidity(
+        &harness.borrow.keys,
+        &harness.wallets[BORROWER].pubkey(),
+        &harness.borrow.liquidity[BORROWER],
+        &harness.borrow.collateral[BORROWER],
+        100 * ONE_TOKEN,
+    );
+    let units = harness.measure(&[], deposit).await;
+    assert_within_budget("deposit_reserve_liquidity", units, DEPOSIT_COMPUTE_BUDGET);
+}
+
+#[tokio::test]
+async fn test_borrow_and_repay_within_budget() {
+    let mut harness = Harness::new().await;
+    let borrower = harness.wallets[BORROWER].insecure_clone();
+
+    let refreshes = harness.refreshes().await;
+    let borrow = ix::borrow_obligation_liquidity(
+        &harness.borrow.keys,
+        &harness.obligation,
+        &harness.borrow.liquidity[BORROWER],
+        500 * ONE_TOKEN,
+    );
+    let units = harness.measure(&refreshes, borrow.clone()).await;
+    assert_within_budget("borrow_obligation_liquidity", units, BORROW_COMPUTE_BUDGET);
+
+    send(
+        &mut harness.context,
+        &[refreshes, vec![borrow]].concat(),
+        &[&borrower],
+    )
+    .await;
+
+    let repay = ix::repay_obligation_liquidity(
+        &harness.borrow.keys,
+        &harness.obligation,
+        &harness.borrow.liquidity[BORROWER],
+        100 * ONE_TOKEN,
+    );
+    let units = harness.measure(&[], repay).await;
+    assert_within_budget("repay_obligation_liquidity", units, REPAY_COMPUTE_BUDGET);
+}
+
+#[tokio::test]
+async fn test_liquidate_within_budget() {
+    let mut harness = Harness::new().await;
+    let borrower = harness.wallets[BORROWER].insecure_clone();
+
+    let refreshes = harness.refreshes().await;
+    let borrow = ix::borrow_obligation_liquidity(
+        &harness.borrow.keys,
+        &harness.obligation,
+        &harness.borrow.liquidity[BORROWER],
+        700 * ONE_TOKEN,
+    );
+    send(
+        &mut harness.context,
+        &[refreshes, vec![borrow]].concat(),
+        &[&borrower],
+    )
+    .await;
+
+    // 700 of debt against 1,000 of collateral at 0.80 is past the 80% threshold
+    harness.set_collateral_price(ONE_USD * 4 / 5).await;
+
+    let obligation: Obligation = load(
+        &mut harness.context.banks_client,
+        &harness.obligation.obligation,
+    )
+    .await;
+    let refresh_accounts = obligation_refresh_accounts(&obligation, &harness.reserves()).unwrap();
+    let reserve_refreshes: Vec<Instruction> =
+        harness.reserves().iter().map(ix::refresh_reserve).collect();
+    let liquidate = ix::liquidate_obligation(
+        &harness.borrow.keys,
+        &harness.collateral.keys,
+        &harness.obligation,
+        &LiquidatorKeys {
+            liquidator: harness.wallets[LIQUIDATOR].pubkey(),
+            source_liquidity: harness.borrow.liquidity[LIQUIDATOR],
+            destination_collateral: harness.collateral.collateral[LIQUIDATOR],
+            keeper: None,
+        },
+        &harness.collateral.collateral_supply,
+        refresh_accounts,
+        100 * ONE_TOKEN,
+    );
+    let units = harness.measure(&reserve_refreshes, liquidate).await;
+    assert_within_budget("liquidate_obligation", units, LIQUIDATE_COMPUTE_BUDGET);
+}
@@ -0,0 +1,87 @@
+//! On-behalf instructions under solana-program-test
+//!
+//! Build the program and run it with
+//! `cargo test-sbf --features test-sbf --test on_behalf`.
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use aura_lend::state::Obligation;
+use aura_lend_cpi::{accounts, instruction, ix, pda};
+use common::*;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Signer;
+
+/// `deposit_obligation_collateral_for` of the liquidator's aTokens of `reserve` into the
+/// borrower's obligation
+fn deposit_for(harness: &Harness, reserve: &TestReserve, collateral_amount: u64) -> Instruction {
+    let payer = harness.wallets[LIQUIDATOR].pubkey();
+    ix::build(
+        accounts::DepositObligationCollateralFor {
+            market: pda::market(),
+            obligation: harness.obligation.obligation,
+            deposit_reserve: reserve.keys.reserve,
+            price_oracle: reserve.keys.price_oracle,
+            collateral_mint: reserve.keys.collateral_mint,
+            source_collateral: reserve.collateral[LIQUIDATOR],
+            destination_collateral: reserve.collateral_supply,
+            collateral_supply_authority: pda::collateral_authority(&reserve.keys.liquidity_mint),
+            payer,
+            flash_loan_guard: pda::flash_loan_guard(&payer),
+            token_program: reserve.keys.token_program,
+            config: pda::config(),
+            reward_pool: None,
+            obligation_rewards: None,
+        },
+        instruction::DepositObligationCollateralFor {
+            collateral_amount,
+            integrator_id: None,
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_deposit_for_only_adds_to_held_collateral() {
+    let mut harness = Harness::new().await;
+    let payer = harness.wallets[LIQUIDATOR].insecure_clone();
+    send(
+        &mut harness.context,
+        &[
+            ix::deposit_reserve_liquidity(
+                &harness.collateral.keys,
+                &payer.pubkey(),
+                &harness.collateral.liquidity[LIQUIDATOR],
+                &harness.collateral.collateral[LIQUIDATOR],
+                100 * ONE_TOKEN,
+            ),
+            ix::deposit_reserve_liquidity(
+                &harness.borrow.keys,
+                &payer.pubkey(),
+                &harness.borrow.liquidity[LIQUIDATOR],
+                &harness.borrow.collateral[LIQUIDATOR],
+                100 * ONE_TOKEN,
+            ),
+        ],
+        &[&payer],
+    )
+    .await;
+
+    // The obligation holds no collateral in the borrow reserve
+    let deposit = deposit_for(&harness, &harness.borrow, 100 * ONE_TOKEN);
+    assert!(try_send(&mut harness.context, &[deposit], &[&payer])
+        .await
+        .is_err());
+
+    // Topping up the collateral it holds goes through
+    let deposit = deposit_for(&harness, &harness.collateral, 100 * ONE_TOKEN);
+    send(&mut harness.context, &[deposit], &[&payer]).await;
+
+    let obligation: Obligation = load(
+        &mut harness.context.banks_client,
+        &harness.obligation.obligation,
+    )
+    .await;
+    assert_eq!(obligation.deposits.len(), 1);
+    assert_eq!(obligation.deposits[0].deposited_amount, 1_100 * ONE_TOKEN);
+}
@@ -0,0 +1,285 @@
+//! Position swap instructions under solana-program-test
+//!
+//! Build the program and run it with
+//! `cargo test-sbf --features test-sbf --test position_swap`.
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::spl_token;
+use aura_lend::instructions::RepayWithCollateralParams;
+use aura_lend::state::{Obligation, Reserve};
+use aura_lend_cpi::{accounts, instruction, ix, pda};
+use common::*;
+use solana_program_test::processor;
+use solana_sdk::account_info::AccountInfo;
+use solana_sdk::entrypoint::ProgramResult;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::program::invoke;
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+
+/// Error the mock swap fails with when it finds a reserve unlocked
+const RESERVE_UNLOCKED: u32 = 1;
+
+/// DEX stand-in that pays out without taking any input
+/// Accounts are the two reserves of the position swap, then the output mint, the
+/// destination, the mint authority and the token program; data is the amount to mint.
+/// Fails if either reserve reads as unlocked: a call back into the program from here
+/// would see the same account data.
+fn mock_swap(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [withdraw_reserve, repay_reserve, mint, destination, mint_authority, token_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    for reserve in [withdraw_reserve, repay_reserve] {
+        let reserve = Reserve::try_deserialize(&mut &reserve.data.borrow()[..])
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        if !reserve.is_locked() {
+            return Err(ProgramError::Custom(RESERVE_UNLOCKED));
+        }
+    }
+
+    let amount = u64::from_le_bytes(
+        data.try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?,
+    );
+    invoke(
+        &spl_token::instruction::mint_to(
+            token_program.key,
+            mint.key,
+            destination.key,
+            mint_authority.key,
+            &[],
+            amount,
+        )?,
+        &[
+            mint.clone(),
+            destination.clone(),
+            mint_authority.clone(),
+            token_program.clone(),
+        ],
+    )
+}
+
+/// `repay_with_collateral` of the collateral reserve's aTokens into `repay`'s debt
+/// `route` is appended as the swap route's accounts.
+fn repay_with_collateral(
+    harness: &Harness,
+    fee_converter: Pubkey,
+    swap_program: Pubkey,
+    repay: &TestReserve,
+    params: RepayWithCollateralParams,
+    route: &[AccountMeta],
+) -> Instruction {
+    let withdraw = harness.collateral.keys;
+    let owner = harness.obligation.owner;
+
+    let mut instruction = ix::build(
+        accounts::RepayWithCollateral {
+            market: pda::market(),
+            config: pda::config(),
+            fee_converter,
+            obligation: harness.obligation.obligation,
+            emode_category: None,
+            withdraw_reserve: withdraw.reserve,
+            repay_reserve: repay.keys.reserve,
+            withdraw_price_oracle: withdraw.price_oracle,
+            repay_price_oracle: repay.keys.price_oracle,
+            withdraw_secondary_price_oracle: None,
+            repay_secondary_price_oracle: None,
+            withdraw_collateral_mint: withdraw.collateral_mint,
+            withdraw_reserve_collateral_supply: harness.collateral.collateral_supply,
+            withdraw_collateral_supply_authority: pda::collateral_authority(
+                &withdraw.liquidity_mint,
+            ),
+            withdraw_liquidity_mint: withdraw.liquidity_mint,
+            withdraw_reserve_liquidity_supply: withdraw.liquidity_supply,
+            withdraw_liquidity_supply_authority: pda::liquidity_supply_authority(
+                &withdraw.liquidity_mint,
+            ),
+            withdrawn_liquidity: harness.collateral.liquidity[BORROWER],
+            repay_liquidity: repay.liquidity[BORROWER],
+            repay_liquidity_mint: repay.keys.liquidity_mint,
+            repay_reserve_liquidity_supply: repay.keys.liquidity_supply,
+            swap_program,
+            obligation_owner: owner,
+            flash_loan_guard: pda::flash_loan_guard(&owner),
+            token_program: repay.keys.token_program,
+            collateral_token_program: withdraw.token_program,
+        },
+        instruction::RepayWithCollateral { params },
+    );
+    instruction.accounts.extend_from_slice(route);
+    instruction
+}
+
+#[tokio::test]
+async fn test_repay_with_collateral_nets_same_asset() {
+    let mut harness = Harness::new().await;
+    let borrower = harness.wallets[BORROWER].insecure_clone();
+    let swap_program = Pubkey::new_unique();
+    let fee_converter = harness.init_fee_converter(vec![swap_program]).await;
+
+    // Borrow the collateral's own asset: 300 against 1,000 deposited
+    let refreshes = harness.refreshes().await;
+    let borrow = ix::borrow_obligation_liquidity(
+        &harness.collateral.keys,
+        &harness.obligation,
+        &harness.collateral.liquidity[BORROWER],
+        300 * ONE_TOKEN,
+    );
+    send(
+        &mut harness.context,
+        &[refreshes, vec![borrow]].concat(),
+        &[&borrower],
+    )
+    .await;
+
+    let reserve_before: Reserve = load(
+        &mut harness.context.banks_client,
+        &harness.collateral.keys.reserve,
+    )
+    .await;
+    let balance_before = token_balance(
+        &mut harness.context.banks_client,
+        &harness.collateral.liquidity[BORROWER],
+    )
+    .await;
+
+    // The swap program is never invoked and no route accounts follow
+    let refreshes = harness.refreshes().await;
+    let repay = repay_with_collateral(
+        &harness,
+        fee_converter,
+        swap_program,
+        &harness.collateral,
+        RepayWithCollateralParams {
+            collateral_amount: 200 * ONE_TOKEN,
+            min_amount_out: 200 * ONE_TOKEN,
+            max_slippage_bps: 0,
+            route_data: Vec::new(),
+        },
+        &[],
+    );
+    send(
+        &mut harness.context,
+        &[refreshes, vec![repay]].concat(),
+        &[&borrower],
+    )
+    .await;
+
+    let obligation: Obligation = load(
+        &mut harness.context.banks_client,
+        &harness.obligation.obligation,
+    )
+    .await;
+    assert_eq!(obligation.deposits[0].deposited_amount, 800 * ONE_TOKEN);
+    let debt = obligation.borrows[0]
+        .borrowed_amount_wads
+        .try_floor_u64()
+        .unwrap();
+    assert!((99 * ONE_TOKEN..=101 * ONE_TOKEN).contains(&debt));
+
+    // The redeemed liquidity went straight into the repayment
+    let balance_after = token_balance(
+        &mut harness.context.banks_client,
+        &harness.collateral.liquidity[BORROWER],
+    )
+    .await;
+    assert_eq!(balance_after, balance_before);
+
+    // Both sides' changes reached the one reserve account
+    let reserve_after: Reserve = load(
+        &mut harness.context.banks_client,
+        &harness.collateral.keys.reserve,
+    )
+    .await;
+    assert_eq!(
+        reserve_after.state.collateral_mint_supply,
+        reserve_before.state.collateral_mint_supply - 200 * ONE_TOKEN
+    );
+    assert!(
+        reserve_after.state.total_borrows <= reserve_before.state.total_borrows - 199 * ONE_TOKEN
+    );
+    assert!(!reserve_after.is_locked());
+}
+
+#[tokio::test]
+async fn test_repay_with_collateral_holds_reserve_locks_during_swap() {
+    let swap_program = Pubkey::new_unique();
+    let mut harness = Harness::with_programs(|program_test| {
+        program_test.add_program("mock_swap", swap_program, processor!(mock_swap));
+    })
+    .await;
+    let borrower = harness.wallets[BORROWER].insecure_clone();
+    let owner = harness.wallets[OWNER].insecure_clone();
+    let fee_converter = harness.init_fee_converter(vec![swap_program]).await;
+
+    let refreshes = harness.refreshes().await;
+    let borrow = ix::borrow_obligation_liquidity(
+        &harness.borrow.keys,
+        &harness.obligation,
+        &harness.borrow.liquidity[BORROWER],
+        300 * ONE_TOKEN,
+    );
+    send(
+        &mut harness.context,
+        &[refreshes, vec![borrow]].concat(),
+        &[&borrower],
+    )
+    .await;
+
+    // Both reserves must read as locked from inside the swap CPI; the mock fails otherwise
+    let route = [
+        AccountMeta::new_readonly(harness.collateral.keys.reserve, false),
+        AccountMeta::new_readonly(harness.borrow.keys.reserve, false),
+        AccountMeta::new(harness.borrow.keys.liquidity_mint, false),
+        AccountMeta::new(harness.borrow.liquidity[BORROWER], false),
+        AccountMeta::new_readonly(owner.pubkey(), true),
+        AccountMeta::new_readonly(spl_token::ID, false),
+    ];
+    let refreshes = harness.refreshes().await;
+    let repay = repay_with_collateral(
+        &harness,
+        fee_converter,
+        swap_program,
+        &harness.borrow,
+        RepayWithCollateralParams {
+            collateral_amount: 100 * ONE_TOKEN,
+            min_amount_out: 100 * ONE_TOKEN,
+            max_slippage_bps: 0,
+            route_data: (100 * ONE_TOKEN).to_le_bytes().to_vec(),
+        },
+        &route,
+    );
+    send(
+        &mut harness.context,
+        &[refreshes, vec![repay]].concat(),
+        &[&borrower, &owner],
+    )
+    .await;
+
+    let obligation: Obligation = load(
+        &mut harness.context.banks_client,
+        &harness.obligation.obligation,
+    )
+    .await;
+    assert_eq!(obligation.deposits[0].deposited_amount, 900 * ONE_TOKEN);
+    let debt = obligation.borrows[0]
+        .borrowed_amount_wads
+        .try_floor_u64()
+        .unwrap();
+    assert!((199 * ONE_TOKEN..=201 * ONE_TOKEN).contains(&debt));
+
+    // The locks are released once the instruction completes
+    for reserve in harness.reserves() {
+        let reserve: Reserve = load(&mut harness.context.banks_client, &reserve.reserve).await;
+        assert!(!reserve.is_locked());
+    }
+}
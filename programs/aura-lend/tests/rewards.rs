@@ -0,0 +1,440 @@
+//! Reward pools under solana-program-test
+//!
+//! Build the program and run it with
+//! `cargo test-sbf --features test-sbf --test rewards`.
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use anchor_spl::token::spl_token;
+use aura_lend::constants::*;
+use aura_lend::state::{Obligation, ObligationRewards, RewardPool, RewardSide};
+use aura_lend_cpi::ix::ObligationKeys;
+use aura_lend_cpi::{accounts, instruction, ix, pda};
+use common::*;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use solana_sdk::system_program;
+
+/// Reward pool on the supply side of the collateral reserve, emitting `ONE_TOKEN` per
+/// slot, with the borrower's obligation opted in
+async fn init_supply_rewards(harness: &mut Harness) -> (RewardPool, Pubkey, Pubkey) {
+    let owner = harness.wallets[OWNER].insecure_clone();
+    let borrower = harness.wallets[BORROWER].insecure_clone();
+    let market = pda::market();
+    let admin_audit_log = Pubkey::find_program_address(&[ADMIN_AUDIT_LOG_SEED], &aura_lend::ID).0;
+
+    let reward_mint = create_mint(&mut harness.context, &owner.pubkey()).await;
+    let source_rewards =
+        create_token_account(&mut harness.context, &reward_mint, &owner.pubkey()).await;
+    mint_to(
+        &mut harness.context,
+        &reward_mint,
+        &source_rewards,
+        &owner,
+        10_000 * ONE_TOKEN,
+    )
+    .await;
+
+    let reserve = harness.collateral.keys.reserve;
+    let reward_pool = Pubkey::find_program_address(
+        &[
+            REWARD_POOL_SEED,
+            reserve.as_ref(),
+            &[RewardSide::Supply as u8],
+        ],
+        &aura_lend::ID,
+    )
+    .0;
+    let reward_vault = Pubkey::find_program_address(
+        &[REWARD_POOL_SEED, reward_pool.as_ref(), b"vault"],
+        &aura_lend::ID,
+    )
+    .0;
+    let obligation_rewards = Pubkey::find_program_address(
+        &[
+            OBLIGATION_REWARDS_SEED,
+            reward_pool.as_ref(),
+            harness.obligation.obligation.as_ref(),
+        ],
+        &aura_lend::ID,
+    )
+    .0;
+
+    send(
+        &mut harness.context,
+        &[
+            ix::build(
+                accounts::InitializeRewardPool {
+                    market,
+                    reserve,
+                    reward_mint,
+                    reward_pool,
+                    reward_vault,
+                    authority: owner.pubkey(),
+                    admin_audit_log,
+                    payer: owner.pubkey(),
+                    system_program: system_program::ID,
+                    token_program: spl_token::ID,
+                    governance: None,
+                },
+                instruction::InitializeRewardPool {
+                    side: RewardSide::Supply,
+                },
+            ),
+            ix::build(
+                accounts::FundRewardPool {
+                    market,
+                    reward_pool,
+                    reward_mint,
+                    reward_vault,
+                    source_rewards,
+                    authority: owner.pubkey(),
+                    admin_audit_log,
+                    token_program: spl_token::ID,
+                    governance: None,
+                },
+                instruction::FundRewardPool {
+                    amount: 10_000 * ONE_TOKEN,
+                    emission_per_slot: ONE_TOKEN,
+                    duration_slots: 10_000,
+                },
+            ),
+        ],
+        &[&owner],
+    )
+    .await;
+    send(
+        &mut harness.context,
+        &[ix::build(
+            accounts::InitObligationRewards {
+                reward_pool,
+                obligation: harness.obligation.obligation,
+                obligation_rewards,
+                obligation_owner: borrower.pubkey(),
+                system_program: system_program::ID,
+            },
+            instruction::InitObligationRewards {},
+        )],
+        &[&borrower],
+    )
+    .await;
+
+    let pool = load(&mut harness.context.banks_client, &reward_pool).await;
+    (pool, reward_pool, obligation_rewards)
+}
+
+/// `claim_rewards` of the borrower's rewards in `reward_pool` into `destination_rewards`
+fn claim_rewards(
+    harness: &Harness,
+    pool: &RewardPool,
+    reward_pool: Pubkey,
+    obligation_rewards: Pubkey,
+    destination_rewards: Pubkey,
+) -> Instruction {
+    ix::build(
+        accounts::ClaimRewards {
+            reward_pool,
+            obligation: harness.obligation.obligation,
+            obligation_rewards,
+            reward_mint: pool.reward_mint,
+            reward_vault: pool.reward_vault,
+            destination_rewards,
+            obligation_owner: harness.obligation.owner,
+            token_program: spl_token::ID,
+            user_stats: None,
+        },
+        instruction::ClaimRewards {},
+    )
+}
+
+/// Move the bank `slots` slots ahead
+async fn warp(harness: &mut Harness, slots: u64) {
+    let clock: Clock = harness.context.banks_client.get_sysvar().await.unwrap();
+    harness.context.warp_to_slot(clock.slot + slots).unwrap();
+}
+
+#[tokio::test]
+async fn test_withdraw_without_reward_accounts_stops_emissions() {
+    let mut harness = Harness::new().await;
+    let borrower = harness.wallets[BORROWER].insecure_clone();
+    let (pool, reward_pool, obligation_rewards) = init_supply_rewards(&mut harness).await;
+    assert_eq!(pool.total_position, 1_000 * ONE_TOKEN);
+
+    // Rewards earned while the collateral was held are settled by a keeper
+    warp(&mut harness, 100).await;
+    send(
+        &mut harness.context,
+        &[ix::build(
+            accounts::SyncObligationRewards {
+                reward_pool,
+                obligation: harness.obligation.obligation,
+                obligation_rewards,
+            },
+            instruction::SyncObligationRewards {},
+        )],
+        &[],
+    )
+    .await;
+    let earned: ObligationRewards =
+        load(&mut harness.context.banks_client, &obligation_rewards).await;
+    assert!(earned.accrued > 0);
+
+    // The whole position is withdrawn without the reward accounts
+    let refreshes = harness.refreshes().await;
+    let withdraw = ix::withdraw_obligation_collateral(
+        &harness.collateral.keys,
+        &harness.obligation,
+        &harness.collateral.collateral_supply,
+        &harness.collateral.collateral[BORROWER],
+        1_000 * ONE_TOKEN,
+    );
+    send(
+        &mut harness.context,
+        &[refreshes, vec![withdraw]].concat(),
+        &[&borrower],
+    )
+    .await;
+    warp(&mut harness, 1_000).await;
+
+    // The claim pays only what was earned before the withdrawal
+    let destination_rewards =
+        create_token_account(&mut harness.context, &pool.reward_mint, &borrower.pubkey()).await;
+    let claim = claim_rewards(
+        &harness,
+        &pool,
+        reward_pool,
+        obligation_rewards,
+        destination_rewards,
+    );
+    send(&mut harness.context, &[claim], &[&borrower]).await;
+    assert_eq!(
+        token_balance(&mut harness.context.banks_client, &destination_rewards).await,
+        earned.accrued
+    );
+
+    // The stale position no longer dilutes the pool
+    let rewards: ObligationRewards =
+        load(&mut harness.context.banks_client, &obligation_rewards).await;
+    assert_eq!(rewards.position, 0);
+    let pool: RewardPool = load(&mut harness.context.banks_client, &reward_pool).await;
+    assert_eq!(pool.total_position, 0);
+}
+
+#[tokio::test]
+async fn test_transfer_waits_for_reward_participation_to_close() {
+    let mut harness = Harness::new().await;
+    let borrower = harness.wallets[BORROWER].insecure_clone();
+    let new_owner = harness.wallets[LIQUIDATOR].insecure_clone();
+    let (pool, reward_pool, obligation_rewards) = init_supply_rewards(&mut harness).await;
+    let market = pda::market();
+    let obligation = harness.obligation.obligation;
+    let obligation_transfer = Pubkey::find_program_address(
+        &[OBLIGATION_TRANSFER_SEED, obligation.as_ref()],
+        &aura_lend::ID,
+    )
+    .0;
+
+    send(
+        &mut harness.context,
+        &[ix::build(
+            accounts::TransferObligationOwnership {
+                market,
+                obligation,
+                obligation_transfer,
+                owner: borrower.pubkey(),
+                system_program: system_program::ID,
+            },
+            instruction::TransferObligationOwnership {
+                new_owner: new_owner.pubkey(),
+            },
+        )],
+        &[&borrower],
+    )
+    .await;
+
+    let new_obligation = pda::obligation(&new_owner.pubkey(), 0);
+    let accept = ix::build(
+        accounts::AcceptObligationOwnership {
+            market,
+            obligation_transfer,
+            obligation,
+            new_obligation,
+            liquidation_auction: Pubkey::find_program_address(
+                &[LIQUIDATION_AUCTION_SEED, obligation.as_ref()],
+                &aura_lend::ID,
+            )
+            .0,
+            standing_order: pda::standing_order(&obligation),
+            sub_accounts: Pubkey::find_program_address(
+                &[SUB_ACCOUNTS_SEED, obligation.as_ref()],
+                &aura_lend::ID,
+            )
+            .0,
+            liquidation_queue: pda::liquidation_queue(&market),
+            previous_owner: borrower.pubkey(),
+            new_owner: new_owner.pubkey(),
+            system_program: system_program::ID,
+        },
+        instruction::AcceptObligationOwnership {
+            obligation_index: 0,
+        },
+    );
+
+    // The participation is keyed by the old address and would be orphaned
+    assert!(
+        try_send(&mut harness.context, &[accept.clone()], &[&new_owner])
+            .await
+            .is_err()
+    );
+
+    // Claiming and opting out lets the obligation move
+    warp(&mut harness, 10).await;
+    let destination_rewards =
+        create_token_account(&mut harness.context, &pool.reward_mint, &borrower.pubkey()).await;
+    let claim = claim_rewards(
+        &harness,
+        &pool,
+        reward_pool,
+        obligation_rewards,
+        destination_rewards,
+    );
+    let close = ix::build(
+        accounts::CloseObligationRewards {
+            reward_pool,
+            obligation,
+            obligation_rewards,
+            obligation_owner: borrower.pubkey(),
+        },
+        instruction::CloseObligationRewards {},
+    );
+    send(&mut harness.context, &[claim, close], &[&borrower]).await;
+    let pool: RewardPool = load(&mut harness.context.banks_client, &reward_pool).await;
+    assert_eq!(pool.total_position, 0);
+
+    send(&mut harness.context, &[accept], &[&new_owner]).await;
+    let moved: Obligation = load(&mut harness.context.banks_client, &new_obligation).await;
+    assert_eq!(moved.owner, new_owner.pubkey());
+    assert_eq!(moved.deposits[0].deposited_amount, 1_000 * ONE_TOKEN);
+    assert_eq!(moved.linked_accounts, 0);
+}
+
+#[tokio::test]
+async fn test_supply_transfer_settles_both_obligations() {
+    let mut harness = Harness::new().await;
+    let borrower = harness.wallets[BORROWER].insecure_clone();
+    let recipient = harness.wallets[LIQUIDATOR].insecure_clone();
+    let (_, reward_pool, source_rewards) = init_supply_rewards(&mut harness).await;
+    let market = pda::market();
+
+    // The recipient holds 100 tokens of the same collateral and earns in the same pool
+    let recipient_obligation = ObligationKeys {
+        obligation: pda::obligation(&recipient.pubkey(), 0),
+        owner: recipient.pubkey(),
+        emode_category: None,
+        isolated_collateral_reserve: None,
+    };
+    let destination_rewards = Pubkey::find_program_address(
+        &[
+            OBLIGATION_REWARDS_SEED,
+            reward_pool.as_ref(),
+            recipient_obligation.obligation.as_ref(),
+        ],
+        &aura_lend::ID,
+    )
+    .0;
+    send(
+        &mut harness.context,
+        &[
+            ix::build(
+                accounts::InitObligation {
+                    market,
+                    obligation: recipient_obligation.obligation,
+                    obligation_owner: recipient.pubkey(),
+                    payer: recipient.pubkey(),
+                    system_program: system_program::ID,
+                },
+                instruction::InitObligation {
+                    obligation_index: 0,
+                },
+            ),
+            ix::deposit_reserve_liquidity(
+                &harness.collateral.keys,
+                &recipient.pubkey(),
+                &harness.collateral.liquidity[LIQUIDATOR],
+                &harness.collateral.collateral[LIQUIDATOR],
+                100 * ONE_TOKEN,
+            ),
+            ix::deposit_obligation_collateral(
+                &harness.collateral.keys,
+                &recipient_obligation,
+                &harness.collateral.collateral[LIQUIDATOR],
+                &harness.collateral.collateral_supply,
+                100 * ONE_TOKEN,
+            ),
+            ix::build(
+                accounts::InitObligationRewards {
+                    reward_pool,
+                    obligation: recipient_obligation.obligation,
+                    obligation_rewards: destination_rewards,
+                    obligation_owner: recipient.pubkey(),
+                    system_program: system_program::ID,
+                },
+                instruction::InitObligationRewards {},
+            ),
+        ],
+        &[&recipient],
+    )
+    .await;
+
+    warp(&mut harness, 100).await;
+    let refreshes = harness.refreshes().await;
+    let transfer = ix::build(
+        accounts::TransferSupplyPosition {
+            market,
+            reserve: harness.collateral.keys.reserve,
+            price_oracle: harness.collateral.keys.price_oracle,
+            source_obligation: harness.obligation.obligation,
+            destination_obligation: recipient_obligation.obligation,
+            emode_category: None,
+            owner: borrower.pubkey(),
+            flash_loan_guard: pda::flash_loan_guard(&borrower.pubkey()),
+            config: pda::config(),
+            reward_pool: Some(reward_pool),
+            source_obligation_rewards: Some(source_rewards),
+            destination_obligation_rewards: Some(destination_rewards),
+        },
+        instruction::TransferSupplyPosition {
+            collateral_amount: 400 * ONE_TOKEN,
+        },
+    );
+    send(
+        &mut harness.context,
+        &[refreshes, vec![transfer]].concat(),
+        &[&borrower],
+    )
+    .await;
+
+    // Both obligations earned on what they held up to the move
+    let source: ObligationRewards = load(&mut harness.context.banks_client, &source_rewards).await;
+    let destination: ObligationRewards =
+        load(&mut harness.context.banks_client, &destination_rewards).await;
+    assert!(source.accrued > destination.accrued);
+    assert!(destination.accrued > 0);
+
+    // The positions follow the collateral and the pool total is unchanged
+    assert_eq!(source.position, 600 * ONE_TOKEN);
+    assert_eq!(destination.position, 500 * ONE_TOKEN);
+    let pool: RewardPool = load(&mut harness.context.banks_client, &reward_pool).await;
+    assert_eq!(pool.total_position, 1_100 * ONE_TOKEN);
+
+    let moved: Obligation = load(
+        &mut harness.context.banks_client,
+        &recipient_obligation.obligation,
+    )
+    .await;
+    assert_eq!(moved.deposits[0].deposited_amount, 500 * ONE_TOKEN);
+}
@@ -0,0 +1,348 @@
+use crate::error::{Result, SdkError};
+use anchor_lang::{AccountDeserialize, Discriminator};
+use aura_lend::instructions::{ObligationSummary, ReserveSummary};
+use aura_lend::state::{
+    AnyObligation, EModeCategory, Market, Obligation, ObligationOptimized, ObligationZeroCopy,
+    Reserve,
+};
+use aura_lend::utils::ProtocolConfig;
+use aura_lend_cpi::{obligation_refresh_accounts, pda, ReserveKeys, ID};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::account::{from_account, Account};
+use solana_sdk::clock::Clock;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::signer::Signer;
+use solana_sdk::sysvar;
+use solana_sdk::transaction::Transaction;
+
+/// Offset of `market` in obligation accounts of any layout (discriminator, version)
+pub const OBLIGATION_MARKET_OFFSET: usize = 8 + 1;
+
+/// Offset of `owner` in obligation accounts of any layout (discriminator, version, market)
+pub const OBLIGATION_OWNER_OFFSET: usize = OBLIGATION_MARKET_OFFSET + 32;
+
+/// Size of an SPL token account, and offsets of its mint and owner
+const TOKEN_ACCOUNT_SIZE: u64 = 165;
+const TOKEN_ACCOUNT_OWNER_OFFSET: usize = 32;
+
+/// Async client for reading Aura Lend state and sending its transactions
+pub struct AuraLendClient {
+    rpc: RpcClient,
+}
+
+impl AuraLendClient {
+    /// Connect to `rpc_url` at confirmed commitment
+    pub fn new(rpc_url: String) -> Self {
+        Self::from_rpc(RpcClient::new_with_commitment(
+            rpc_url,
+            CommitmentConfig::confirmed(),
+        ))
+    }
+
+    /// Wrap an existing RPC client
+    pub fn from_rpc(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
+
+    /// Underlying RPC client
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    /// Fetch a raw account owned by the program
+    async fn get_program_account(&self, address: &Pubkey) -> Result<Account> {
+        let account = self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())
+            .await?
+            .value
+            .ok_or(SdkError::AccountNotFound(*address))?;
+        if account.owner != ID {
+            return Err(SdkError::InvalidOwner(*address));
+        }
+        Ok(account)
+    }
+
+    /// Fetch and decode an Anchor account of the program
+    pub async fn get_account<T: AccountDeserialize>(&self, address: &Pubkey) -> Result<T> {
+        let account = self.get_program_account(address).await?;
+        Ok(T::try_deserialize(&mut account.data.as_slice())?)
+    }
+
+    /// Fetch every program account of type `T`, with extra `filters` applied
+    async fn get_all<T: Discriminator>(
+        &self,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        let mut all_filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            T::DISCRIMINATOR,
+        ))];
+        all_filters.extend(filters);
+
+        let config = RpcProgramAccountsConfig {
+            filters: Some(all_filters),
+            account_config: RpcAccountInfoConfig {
+                commitment: Some(self.rpc.commitment()),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        Ok(self
+            .rpc
+            .get_program_accounts_with_config(&ID, config)
+            .await?)
+    }
+
+    /// Fetch the market
+    pub async fn get_market(&self) -> Result<Market> {
+        self.get_account(&pda::market()).await
+    }
+
+    /// Fetch a reserve
+    pub async fn get_reserve(&self, address: &Pubkey) -> Result<Reserve> {
+        self.get_account(address).await
+    }
+
+    /// Fetch the reserve of `liquidity_mint`
+    pub async fn get_reserve_by_mint(&self, liquidity_mint: &Pubkey) -> Result<Reserve> {
+        self.get_account(&pda::reserve(liquidity_mint)).await
+    }
+
+    /// Fetch every reserve of the program
+    pub async fn get_reserves(&self) -> Result<Vec<(Pubkey, Reserve)>> {
+        self.get_all::<Reserve>(Vec::new())
+            .await?
+            .into_iter()
+            .map(|(address, account)| {
+                Ok((
+                    address,
+                    Reserve::try_deserialize(&mut account.data.as_slice())?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Fetch an obligation stored in any layout
+    pub async fn get_obligation(&self, address: &Pubkey) -> Result<Obligation> {
+        let account = self.get_program_account(address).await?;
+        Ok(AnyObligation::from_data(&account.data)?.into_inner())
+    }
+
+    /// Fetch every obligation of the program, in any layout
+    pub async fn get_obligations(&self) -> Result<Vec<(Pubkey, Obligation)>> {
+        self.get_obligations_with_filters(Vec::new()).await
+    }
+
+    /// Fetch every obligation of `owner`, in any layout
+    pub async fn get_obligations_by_owner(
+        &self,
+        owner: &Pubkey,
+    ) -> Result<Vec<(Pubkey, Obligation)>> {
+        self.get_obligations_with_filters(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            OBLIGATION_OWNER_OFFSET,
+            owner.as_ref(),
+        ))])
+        .await
+    }
+
+    /// Fetch obligations of every layout matching `filters`, e.g. memcmp filters at
+    /// `OBLIGATION_MARKET_OFFSET` or `OBLIGATION_OWNER_OFFSET`
+    pub async fn get_obligations_with_filters(
+        &self,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<Vec<(Pubkey, Obligation)>> {
+        let mut accounts = self.get_all::<Obligation>(filters.clone()).await?;
+        accounts.extend(self.get_all::<ObligationOptimized>(filters.clone()).await?);
+        accounts.extend(self.get_all::<ObligationZeroCopy>(filters).await?);
+
+        accounts
+            .into_iter()
+            .map(|(address, account)| {
+                Ok((
+                    address,
+                    AnyObligation::from_data(&account.data)?.into_inner(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Current cluster clock
+    pub async fn get_clock(&self) -> Result<Clock> {
+        let account = self
+            .rpc
+            .get_account_with_commitment(&sysvar::clock::ID, self.rpc.commitment())
+            .await?
+            .value
+            .ok_or(SdkError::AccountNotFound(sysvar::clock::ID))?;
+        from_account(&account).ok_or(SdkError::InvalidOwner(sysvar::clock::ID))
+    }
+
+    /// Addresses a reserve's instructions need, including its liquidity token program
+    pub async fn reserve_keys(&self, address: &Pubkey) -> Result<ReserveKeys> {
+        let reserve = self.get_reserve(address).await?;
+        let mint = self
+            .rpc
+            .get_account_with_commitment(&reserve.liquidity_mint, self.rpc.commitment())
+            .await?
+            .value
+            .ok_or(SdkError::AccountNotFound(reserve.liquidity_mint))?;
+        Ok(ReserveKeys::new(*address, &reserve, mint.owner))
+    }
+
+    /// Token account holding a reserve's obligation collateral
+    /// Found by scanning the token program for the collateral mint's account owned by the
+    /// collateral authority, as the program does not record its address.
+    pub async fn collateral_supply(&self, reserve: &ReserveKeys) -> Result<Pubkey> {
+        let authority = pda::collateral_authority(&reserve.liquidity_mint);
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(TOKEN_ACCOUNT_SIZE),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                    0,
+                    reserve.collateral_mint.as_ref(),
+                )),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                    TOKEN_ACCOUNT_OWNER_OFFSET,
+                    authority.as_ref(),
+                )),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                commitment: Some(self.rpc.commitment()),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+
+        self.rpc
+            .get_program_accounts_with_config(&reserve.token_program, config)
+            .await?
+            .into_iter()
+            .map(|(address, _)| address)
+            .next()
+            .ok_or(SdkError::AccountNotFound(authority))
+    }
+
+    /// Keys of every reserve `obligation` has a position in
+    pub async fn position_reserve_keys(&self, obligation: &Obligation) -> Result<Vec<ReserveKeys>> {
+        let mut keys: Vec<ReserveKeys> = Vec::new();
+        let positions = obligation
+            .deposits
+            .iter()
+            .map(|deposit| deposit.deposit_reserve)
+            .chain(
+                obligation
+                    .borrows
+                    .iter()
+                    .map(|borrow| borrow.borrow_reserve),
+            );
+
+        for reserve in positions {
+            if !keys.iter().any(|k| k.reserve == reserve) {
+                keys.push(self.reserve_keys(&reserve).await?);
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Remaining accounts refreshing `obligation` in `refresh_obligation` and liquidations
+    pub async fn refresh_accounts(&self, obligation: &Obligation) -> Result<Vec<AccountMeta>> {
+        let keys = self.position_reserve_keys(obligation).await?;
+        Ok(obligation_refresh_accounts(obligation, &keys).unwrap_or_default())
+    }
+
+    /// Rates, APYs and balances of a reserve with interest accrued to the current slot
+    pub async fn reserve_summary(&self, address: &Pubkey) -> Result<ReserveSummary> {
+        let reserve = self.get_reserve(address).await?;
+        let clock = self.get_clock().await?;
+        Ok(ReserveSummary::new(
+            *address,
+            &reserve,
+            clock.slot,
+            clock.unix_timestamp as u64,
+        )?)
+    }
+
+    /// Health factor and borrowing power of an obligation as of its last refresh
+    /// The obligation's values are those cached by its last `refresh_obligation`; refresh
+    /// it first for current prices.
+    pub async fn obligation_summary(&self, address: &Pubkey) -> Result<ObligationSummary> {
+        let obligation = self.get_obligation(address).await?;
+        let category = match obligation.emode_category {
+            0 => None,
+            id => Some(
+                self.get_account::<EModeCategory>(&pda::emode_category(&obligation.market, id))
+                    .await?,
+            ),
+        };
+        let emode = EModeCategory::resolve(category.as_ref(), &obligation, None)?;
+        let config = self.get_account::<ProtocolConfig>(&pda::config()).await?;
+        Ok(ObligationSummary::new(
+            *address,
+            &obligation,
+            emode.as_ref(),
+            config.liquidation_close_factor_bps,
+        )?)
+    }
+
+    /// Build an unsigned transaction of `instructions` paid by `payer`, with a recent
+    /// blockhash
+    pub async fn build_transaction(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+    ) -> Result<Transaction> {
+        let mut transaction = Transaction::new_with_payer(instructions, Some(payer));
+        transaction.message.recent_blockhash = self.rpc.get_latest_blockhash().await?;
+        Ok(transaction)
+    }
+
+    /// Sign `instructions` with `payer` and `signers`, send them and wait for confirmation
+    pub async fn send_instructions(
+        &self,
+        instructions: &[Instruction],
+        payer: &Keypair,
+        signers: &[&Keypair],
+    ) -> Result<Signature> {
+        let recent_blockhash = self.rpc.get_latest_blockhash().await?;
+        let mut all_signers: Vec<&Keypair> = vec![payer];
+        all_signers.extend(signers.iter().copied());
+
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer.pubkey()),
+            &all_signers,
+            recent_blockhash,
+        );
+        Ok(self.rpc.send_and_confirm_transaction(&transaction).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountSerialize;
+
+    #[test]
+    fn test_obligation_owner_offset() {
+        let market = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let obligation = Obligation::new_for_test(market, owner, 0);
+
+        let mut data = Vec::new();
+        obligation.try_serialize(&mut data).unwrap();
+        assert_eq!(
+            &data[OBLIGATION_MARKET_OFFSET..OBLIGATION_MARKET_OFFSET + 32],
+            market.as_ref()
+        );
+        assert_eq!(
+            &data[OBLIGATION_OWNER_OFFSET..OBLIGATION_OWNER_OFFSET + 32],
+            owner.as_ref()
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use solana_client::client_error::ClientError;
+use solana_sdk::pubkey::Pubkey;
+use thiserror::Error;
+
+/// Errors returned by the SDK
+#[derive(Debug, Error)]
+pub enum SdkError {
+    /// The RPC request failed
+    #[error("RPC error: {0}")]
+    Rpc(Box<ClientError>),
+
+    /// No account exists at the address
+    #[error("Account {0} not found")]
+    AccountNotFound(Pubkey),
+
+    /// The account is not owned by the expected program
+    #[error("Account {0} has an unexpected owner")]
+    InvalidOwner(Pubkey),
+
+    /// The account data could not be decoded, or program math failed on it
+    #[error("Program error: {0}")]
+    Program(Box<anchor_lang::error::Error>),
+}
+
+impl From<ClientError> for SdkError {
+    fn from(error: ClientError) -> Self {
+        Self::Rpc(Box::new(error))
+    }
+}
+
+impl From<anchor_lang::error::Error> for SdkError {
+    fn from(error: anchor_lang::error::Error) -> Self {
+        Self::Program(Box::new(error))
+    }
+}
+
+/// Result type of the SDK
+pub type Result<T> = std::result::Result<T, SdkError>;
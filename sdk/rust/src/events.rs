@@ -0,0 +1,50 @@
+//! The program's events and their decoding from `Program data:` log payloads
+
+pub use aura_lend::events::*;
+
+use anchor_lang::Event;
+
+/// Decode an event of type `T` from a base64-decoded `Program data:` log payload
+///
+/// Returns `None` when the payload belongs to another event type or does not parse.
+pub fn decode_event<T: Event>(data: &[u8]) -> Option<T> {
+    let mut payload = data.strip_prefix(T::DISCRIMINATOR)?;
+    T::deserialize(&mut payload).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aura_lend::state::OperationReceipt;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn test_decode_borrow_event_origination() {
+        let event = BorrowEvent {
+            obligation: Pubkey::new_unique(),
+            borrower: Pubkey::new_unique(),
+            receipt: OperationReceipt {
+                reserve: Pubkey::new_unique(),
+                liquidity_amount: 1_000_000,
+                collateral_amount: 0,
+                fee_amount: 100,
+                health_factor_bps: Some(15_000),
+                utilization_rate_bps: 8_000,
+            },
+            origination_utilization_rate: 800_000_000_000_000_000,
+            origination_borrow_rate: 120_000_000_000_000_000,
+            slot: 42,
+        };
+
+        let decoded = decode_event::<BorrowEvent>(&event.data()).unwrap();
+        assert_eq!(
+            decoded.origination_utilization_rate,
+            800_000_000_000_000_000
+        );
+        assert_eq!(decoded.origination_borrow_rate, 120_000_000_000_000_000);
+        assert_eq!(decoded.receipt, event.receipt);
+
+        // Another event's discriminator does not match
+        assert!(decode_event::<RepayEvent>(&event.data()).is_none());
+    }
+}
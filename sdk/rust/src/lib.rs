@@ -0,0 +1,23 @@
+//! Rust SDK for Aura Lend
+//!
+//! An async client over Solana RPC for off-chain integrations such as liquidation bots:
+//! - `AuraLendClient` fetches and decodes markets, reserves and obligations (in any
+//!   obligation layout), scans the program's accounts and sends transactions
+//! - `pda` derives the program's addresses and `ix` builds its instructions; `ix::build`
+//!   covers any instruction from the Anchor-generated `accounts` and `instruction` types
+//! - `ReserveSummary` and `ObligationSummary` compute rates, APYs and health with the
+//!   program's own code, so client figures match what the views return on-chain
+//! - `events` re-exports the program's events, and `events::decode_event` decodes them
+//!   from transaction logs (e.g. a `BorrowEvent`'s origination rates)
+
+pub mod client;
+pub mod error;
+pub mod events;
+
+pub use aura_lend::instructions::{MarketSummary, ObligationSummary, ReserveSummary};
+pub use aura_lend::state::{EModeCategory, EModeParams, Market, Obligation, Reserve};
+pub use aura_lend_cpi::{
+    accounts, instruction, ix, obligation_refresh_accounts, pda, ReserveKeys, ID,
+};
+pub use client::AuraLendClient;
+pub use error::{Result, SdkError};
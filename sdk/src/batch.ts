@@ -0,0 +1,112 @@
+/** Maximum operations the program accepts in one batch (MAX_BATCH_OPERATIONS) */
+export const MAX_BATCH_OPERATIONS = 20;
+
+/** Compute unit limit of a single transaction */
+export const MAX_TRANSACTION_COMPUTE_UNITS = 1_400_000;
+
+export interface BatchOperationResult {
+  operationId: number;
+  success: boolean;
+  errorCode: number | null;
+  /** Compute units the operation consumed, measured on-chain */
+  computeUnits: bigint;
+}
+
+export interface BatchExecutionReport {
+  /** Per-operation results, grouped by operation type */
+  results: BatchOperationResult[];
+  /** Compute units consumed by the whole batch */
+  totalComputeUnits: bigint;
+  /** Compute units left in the transaction after the batch */
+  remainingComputeUnits: bigint;
+}
+
+/**
+ * Decode the report a batch instruction leaves in its return data
+ * Accepts the raw bytes or the base64 string returned by `simulateTransaction`.
+ */
+export function decodeBatchExecutionReport(
+  returnData: Buffer | string
+): BatchExecutionReport {
+  const data = typeof returnData === 'string' ? Buffer.from(returnData, 'base64') : returnData;
+  let offset = 0;
+
+  const count = data.readUInt32LE(offset);
+  offset += 4;
+
+  const results: BatchOperationResult[] = [];
+  for (let i = 0; i < count; i++) {
+    const operationId = data.readUInt32LE(offset);
+    const success = data.readUInt8(offset + 4) === 1;
+    offset += 5;
+
+    let errorCode: number | null = null;
+    if (data.readUInt8(offset) === 1) {
+      errorCode = data.readUInt32LE(offset + 1);
+      offset += 4;
+    }
+    offset += 1;
+
+    const computeUnits = data.readBigUInt64LE(offset);
+    offset += 8;
+
+    results.push({ operationId, success, errorCode, computeUnits });
+  }
+
+  const totalComputeUnits = data.readBigUInt64LE(offset);
+  const remainingComputeUnits = data.readBigUInt64LE(offset + 8);
+
+  return { results, totalComputeUnits, remainingComputeUnits };
+}
+
+/**
+ * Sizes batches from the compute units previous batches actually consumed
+ * Feed every report to `record`; `nextBatchSize` then returns how many operations fit
+ * in the transaction's compute budget, keeping `headroomBps` of it in reserve.
+ */
+export class BatchSizer {
+  private operations = 0n;
+  private operationUnits = 0n;
+  private overheadUnits = 0n;
+
+  constructor(
+    private computeBudget: number = MAX_TRANSACTION_COMPUTE_UNITS,
+    private headroomBps: number = 1_000
+  ) {}
+
+  /** Record the measurements of an executed or simulated batch */
+  record(report: BatchExecutionReport): void {
+    const measured = report.results.reduce((sum, result) => sum + result.computeUnits, 0n);
+    this.operations += BigInt(report.results.length);
+    this.operationUnits += measured;
+
+    // Grouping, cache preloading and logging are paid once per batch
+    const overhead = report.totalComputeUnits - measured;
+    if (overhead > this.overheadUnits) {
+      this.overheadUnits = overhead;
+    }
+  }
+
+  /** Average compute units per operation observed so far, if any */
+  averageOperationUnits(): bigint | null {
+    if (this.operations === 0n) {
+      return null;
+    }
+    return this.operationUnits / this.operations;
+  }
+
+  /** Number of operations to put in the next batch */
+  nextBatchSize(): number {
+    const average = this.averageOperationUnits();
+    if (average === null || average === 0n) {
+      return MAX_BATCH_OPERATIONS;
+    }
+
+    const budget =
+      (BigInt(this.computeBudget) * BigInt(10_000 - this.headroomBps)) / 10_000n -
+      this.overheadUnits;
+    const fit = budget > 0n ? Number(budget / average) : 0;
+
+    return Math.max(1, Math.min(MAX_BATCH_OPERATIONS, fit));
+  }
+}
@@ -1,14 +1,19 @@
 // External dependencies
 import {
+  AddressLookupTableAccount,
   Commitment,
   Connection,
   Keypair,
   PublicKey,
   SendOptions,
   Transaction,
+  TransactionInstruction,
+  TransactionMessage,
+  VersionedTransaction,
 } from '@solana/web3.js';
 import { AnchorProvider, Program, Wallet } from '@coral-xyz/anchor';
 import {
+  TOKEN_PROGRAM_ID,
   createAssociatedTokenAccountInstruction,
   getAssociatedTokenAddressSync,
 } from '@solana/spl-token';
@@ -19,7 +24,16 @@ import { BorrowingInstructions } from './instructions/borrowing';
 import { LendingInstructions } from './instructions/lending';
 import { LiquidationInstructions } from './instructions/liquidation';
 import { MarketInstructions } from './instructions/market';
-import { Market, Obligation, Reserve } from './state';
+import {
+  LiquidationQueue,
+  Market,
+  MarketLookupTable,
+  Obligation,
+  Reserve,
+  ReserveRateIndex,
+  UserStats,
+} from './state';
+import { RewardSide } from './types';
 
 /**
  * Configuration interface for initializing the AuraLendClient
@@ -136,6 +150,100 @@ export class AuraLendClient {
     return signature;
   }
 
+  /**
+   * Builds a v0 transaction compiled against the market's lookup table
+   * 
+   * Falls back to no lookup tables when the market has none recorded, so the
+   * result is always usable; instructions touching many reserves need the table
+   * to stay within account limits.
+   * 
+   * @param instructions - Instructions to include
+   * @param signers - Additional signers (wallet is automatically included)
+   * @returns Signed versioned transaction
+   */
+  async buildV0Transaction(
+    instructions: TransactionInstruction[],
+    signers: Keypair[] = []
+  ): Promise<VersionedTransaction> {
+    const lookupTable = await this.getMarketLookupTable();
+    const { blockhash } = await this.connection.getLatestBlockhash();
+
+    const message = new TransactionMessage({
+      payerKey: this.wallet.publicKey,
+      recentBlockhash: blockhash,
+      instructions,
+    }).compileToV0Message(lookupTable ? [lookupTable] : []);
+
+    const transaction = new VersionedTransaction(message);
+    transaction.sign([this.wallet.payer, ...signers]);
+    return transaction;
+  }
+
+  /**
+   * Sends and confirms a v0 transaction compiled against the market's lookup table
+   * 
+   * @param instructions - Instructions to include
+   * @param signers - Additional signers (wallet is automatically included)
+   * @returns Transaction signature
+   */
+  async sendAndConfirmV0Transaction(
+    instructions: TransactionInstruction[],
+    signers: Keypair[] = []
+  ): Promise<string> {
+    const transaction = await this.buildV0Transaction(instructions, signers);
+    const signature = await this.connection.sendTransaction(transaction);
+
+    await this.connection.confirmTransaction(signature, 'confirmed');
+    return signature;
+  }
+
+  /**
+   * Retrieves the market's canonical Address Lookup Table
+   * 
+   * @returns The lookup table account or null if none is recorded
+   */
+  async getMarketLookupTable(): Promise<AddressLookupTableAccount | null> {
+    const recordKey = this.getMarketLookupTableAddress();
+
+    try {
+      const accountInfo = await this.connection.getAccountInfo(recordKey);
+      if (!accountInfo) return null;
+      const record = MarketLookupTable.fromAccountInfo(recordKey, accountInfo);
+      const { value } = await this.connection.getAddressLookupTable(record.data.lookupTable);
+      return value;
+    } catch (error) {
+      // Lookup table not recorded - transactions fall back to legacy account lists
+      return null;
+    }
+  }
+
+  /**
+   * Lists the accounts governance should place in the market lookup table
+   * 
+   * @param reserves - Reserves of the market
+   * @returns Deduplicated addresses shared across user transactions
+   */
+  getLookupTableAddresses(reserves: Reserve[]): PublicKey[] {
+    const addresses = [this.getMarketAddress()];
+
+    for (const reserve of reserves) {
+      const liquidityMint = reserve.data.liquidityMint;
+      addresses.push(
+        reserve.address,
+        liquidityMint,
+        reserve.data.collateralMint,
+        reserve.data.liquiditySupply,
+        reserve.data.priceOracle,
+        this.getCollateralMintAuthorityAddress(liquidityMint),
+        this.getLiquiditySupplyAuthorityAddress(liquidityMint),
+        this.getRateIndexAddress(reserve.address)
+      );
+    }
+
+    const unique = new Map(addresses.map((address) => [address.toBase58(), address]));
+    return [...unique.values()];
+  }
+
   /**
    * Retrieves market account data
    * 
@@ -174,6 +282,62 @@ export class AuraLendClient {
     }
   }
 
+  /**
+   * Retrieves the realized borrow-rate index of a reserve
+   * 
+   * @param reserve - The reserve account address
+   * @returns Rate index account data or null if not initialized
+   */
+  async getRateIndex(reserve: PublicKey): Promise<ReserveRateIndex | null> {
+    const rateIndexKey = this.getRateIndexAddress(reserve);
+    
+    try {
+      const accountInfo = await this.connection.getAccountInfo(rateIndexKey);
+      if (!accountInfo) return null;
+      return ReserveRateIndex.fromAccountInfo(rateIndexKey, accountInfo);
+    } catch (error) {
+      // Rate index not found - this is expected before it is initialized
+      return null;
+    }
+  }
+
+  /**
+   * Retrieves the market's queue of unhealthy obligations
+   * 
+   * @returns Liquidation queue account data or null if not initialized
+   */
+  async getLiquidationQueue(): Promise<LiquidationQueue | null> {
+    const queueKey = this.getLiquidationQueueAddress();
+    
+    try {
+      const accountInfo = await this.connection.getAccountInfo(queueKey);
+      if (!accountInfo) return null;
+      return LiquidationQueue.fromAccountInfo(queueKey, accountInfo);
+    } catch (error) {
+      // Queue not found - this is expected before it is initialized
+      return null;
+    }
+  }
+
+  /**
+   * Retrieves a wallet's lifetime statistics in the market
+   * 
+   * @param owner - The wallet whose statistics to fetch
+   * @returns User stats account data or null if not initialized
+   */
+  async getUserStats(owner: PublicKey): Promise<UserStats | null> {
+    const userStatsKey = this.getUserStatsAddress(owner);
+    
+    try {
+      const accountInfo = await this.connection.getAccountInfo(userStatsKey);
+      if (!accountInfo) return null;
+      return UserStats.fromAccountInfo(userStatsKey, accountInfo);
+    } catch (error) {
+      // Stats not found - this is expected before the wallet opts in
+      return null;
+    }
+  }
+
   /**
    * Retrieves obligation account data for a given owner
    * 
@@ -264,14 +428,358 @@ export class AuraLendClient {
    * Derives the obligation PDA address for a given owner
    * 
    * @param owner - The obligation owner's public key
+   * @param obligationIndex - Which of the owner's obligations (0 = the original)
    * @returns The obligation account public key
    */
-  getObligationAddress(owner: PublicKey): PublicKey {
-    const [obligationPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from('obligation'), owner.toBuffer()],
+  getObligationAddress(owner: PublicKey, obligationIndex = 0): PublicKey {
+    // Index 0 adds no seed, keeping the address obligations had before indexes existed
+    const seeds = [Buffer.from('obligation'), owner.toBuffer()];
+    if (obligationIndex > 0) {
+      seeds.push(Buffer.from([obligationIndex]));
+    }
+    const [obligationPda] = PublicKey.findProgramAddressSync(seeds, this.programId);
+    return obligationPda;
+  }
+  /**
+   * Derives the pending ownership transfer PDA address for an obligation
+   * 
+   * @param obligation - The obligation account address
+   * @returns The obligation transfer account public key
+   */
+  getObligationTransferAddress(obligation: PublicKey): PublicKey {
+    const [obligationTransferPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('obligation_transfer'), obligation.toBuffer()],
       this.programId
     );
-    return obligationPda;
+    return obligationTransferPda;
+  }
+
+
+  /**
+   * Derives the market lookup table record PDA address
+   * 
+   * @returns The lookup table record public key
+   */
+  getMarketLookupTableAddress(): PublicKey {
+    const [recordPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('lookup_table'), this.getMarketAddress().toBuffer()],
+      this.programId
+    );
+    return recordPda;
+  }
+
+  /**
+   * Derives the admin audit log PDA address
+   * 
+   * @returns The admin audit log public key
+   */
+  getAdminAuditLogAddress(): PublicKey {
+    const [logPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('admin_audit_log')],
+      this.programId
+    );
+    return logPda;
+  }
+
+  /**
+   * Derives the rent reclamation consent PDA address for a given user
+   * 
+   * @param owner - The user who grants the consent
+   * @returns The consent record public key
+   */
+  getRentReclaimConsentAddress(owner: PublicKey): PublicKey {
+    const [consentPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('rent_reclaim_consent'), owner.toBuffer()],
+      this.programId
+    );
+    return consentPda;
+  }
+
+  /**
+   * Derives the eMode category PDA address for a given category id
+   * 
+   * @param market - The market account address
+   * @param categoryId - The category id (1-255)
+   * @returns The eMode category account public key
+   */
+  getEModeCategoryAddress(market: PublicKey, categoryId: number): PublicKey {
+    const [categoryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('emode_category'), market.toBuffer(), Buffer.from([categoryId])],
+      this.programId
+    );
+    return categoryPda;
+  }
+
+  /**
+   * Derives the rate index PDA address for a given reserve
+   * 
+   * @param reserve - The reserve account address
+   * @returns The rate index account public key
+   */
+  getRateIndexAddress(reserve: PublicKey): PublicKey {
+    const [rateIndexPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('rate_index'), reserve.toBuffer()],
+      this.programId
+    );
+    return rateIndexPda;
+  }
+
+  /**
+   * Derives the insurance fund PDA address for a given reserve
+   * 
+   * @param reserve - The reserve account address
+   * @returns The insurance fund account public key
+   */
+  getInsuranceFundAddress(reserve: PublicKey): PublicKey {
+    const [insuranceFundPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('insurance_fund'), reserve.toBuffer()],
+      this.programId
+    );
+    return insuranceFundPda;
+  }
+
+  /**
+   * Derives the insurance fund vault PDA address for a given reserve
+   * 
+   * @param reserve - The reserve account address
+   * @returns The insurance fund vault token account public key
+   */
+  getInsuranceFundVaultAddress(reserve: PublicKey): PublicKey {
+    const [vaultPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('insurance_fund'),
+        this.getInsuranceFundAddress(reserve).toBuffer(),
+        Buffer.from('liquidity'),
+      ],
+      this.programId
+    );
+    return vaultPda;
+  }
+
+  /**
+   * Derives the referrer PDA address for a given wallet
+   * 
+   * @param owner - The wallet registered as a referrer
+   * @returns The referrer account public key
+   */
+  getReferrerAddress(owner: PublicKey): PublicKey {
+    const [referrerPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('referrer'), this.getMarketAddress().toBuffer(), owner.toBuffer()],
+      this.programId
+    );
+    return referrerPda;
+  }
+
+  /**
+   * Derives the user stats PDA address for a given wallet
+   * 
+   * @param owner - The wallet whose activity the account records
+   * @returns The user stats account public key
+   */
+  getUserStatsAddress(owner: PublicKey): PublicKey {
+    const [userStatsPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('user_stats'), this.getMarketAddress().toBuffer(), owner.toBuffer()],
+      this.programId
+    );
+    return userStatsPda;
+  }
+
+  /**
+   * Derives the reward pool PDA address for one side of a reserve
+   * 
+   * @param reserve - The reserve account address
+   * @param side - The side of the reserve the pool incentivizes
+   * @returns The reward pool account public key
+   */
+  getRewardPoolAddress(reserve: PublicKey, side: RewardSide): PublicKey {
+    const [rewardPoolPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('reward_pool'), reserve.toBuffer(), Buffer.from([side])],
+      this.programId
+    );
+    return rewardPoolPda;
+  }
+
+  /**
+   * Derives the reward vault PDA address for a given reward pool
+   * 
+   * @param rewardPool - The reward pool account address
+   * @returns The reward vault token account public key
+   */
+  getRewardVaultAddress(rewardPool: PublicKey): PublicKey {
+    const [vaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('reward_pool'), rewardPool.toBuffer(), Buffer.from('vault')],
+      this.programId
+    );
+    return vaultPda;
+  }
+
+  /**
+   * Derives the PDA address of an obligation's participation in a reward pool
+   * 
+   * @param rewardPool - The reward pool account address
+   * @param obligation - The obligation account address
+   * @returns The obligation rewards account public key
+   */
+  getObligationRewardsAddress(rewardPool: PublicKey, obligation: PublicKey): PublicKey {
+    const [obligationRewardsPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('obligation_rewards'), rewardPool.toBuffer(), obligation.toBuffer()],
+      this.programId
+    );
+    return obligationRewardsPda;
+  }
+
+  /**
+   * Derives the stake vault PDA address for a reserve
+   * 
+   * @param reserve - The reserve account address
+   * @returns The stake vault account public key
+   */
+  getStakeVaultAddress(reserve: PublicKey): PublicKey {
+    const [stakeVaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('stake_vault'), reserve.toBuffer()],
+      this.programId
+    );
+    return stakeVaultPda;
+  }
+
+  /**
+   * Derives the token account holding a stake vault's aTokens
+   * 
+   * @param stakeVault - The stake vault account address
+   * @returns The vault collateral token account public key
+   */
+  getStakeVaultCollateralAddress(stakeVault: PublicKey): PublicKey {
+    const [vaultCollateralPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('stake_vault'), stakeVault.toBuffer(), Buffer.from('collateral')],
+      this.programId
+    );
+    return vaultCollateralPda;
+  }
+
+  /**
+   * Derives the token account collecting liquidity to compound into a stake vault
+   * 
+   * @param stakeVault - The stake vault account address
+   * @returns The compound liquidity token account public key
+   */
+  getStakeVaultCompoundAddress(stakeVault: PublicKey): PublicKey {
+    const [compoundLiquidityPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('stake_vault'), stakeVault.toBuffer(), Buffer.from('compound')],
+      this.programId
+    );
+    return compoundLiquidityPda;
+  }
+
+  /**
+   * Derives the stake position PDA address for a staker
+   * 
+   * @param stakeVault - The stake vault account address
+   * @param owner - The staker's wallet address
+   * @returns The stake position account public key
+   */
+  getStakePositionAddress(stakeVault: PublicKey, owner: PublicKey): PublicKey {
+    const [stakePositionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('stake_position'), stakeVault.toBuffer(), owner.toBuffer()],
+      this.programId
+    );
+    return stakePositionPda;
+  }
+
+  /**
+   * Derives the keeper registry PDA address for the market
+   * 
+   * @returns The keeper registry account public key
+   */
+  getKeeperRegistryAddress(): PublicKey {
+    const [keeperRegistryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('keeper_registry'), this.getMarketAddress().toBuffer()],
+      this.programId
+    );
+    return keeperRegistryPda;
+  }
+
+  /**
+   * Derives the keeper PDA address of a wallet in the keeper registry
+   * 
+   * @param authority - The keeper's wallet address
+   * @returns The keeper account public key
+   */
+  getKeeperAddress(authority: PublicKey): PublicKey {
+    const [keeperPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('keeper'), this.getKeeperRegistryAddress().toBuffer(), authority.toBuffer()],
+      this.programId
+    );
+    return keeperPda;
+  }
+
+  /**
+   * Derives the liquidation queue PDA address for the market
+   * 
+   * @returns The liquidation queue account public key
+   */
+  getLiquidationQueueAddress(): PublicKey {
+    const [liquidationQueuePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('liquidation_queue'), this.getMarketAddress().toBuffer()],
+      this.programId
+    );
+    return liquidationQueuePda;
+  }
+
+  /**
+   * Derives the liquidation commitment PDA address of a liquidator for an obligation
+   * 
+   * @param obligation - The obligation account address
+   * @param liquidator - The liquidator's wallet address
+   * @returns The liquidation commitment account public key
+   */
+  getLiquidationCommitmentAddress(obligation: PublicKey, liquidator: PublicKey): PublicKey {
+    const [commitmentPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('liquidation_commitment'), obligation.toBuffer(), liquidator.toBuffer()],
+      this.programId
+    );
+    return commitmentPda;
+  }
+
+  /**
+   * Derives the liquidation auction PDA address for a given obligation
+   * 
+   * @param obligation - The obligation account address
+   * @returns The liquidation auction account public key
+   */
+  getLiquidationAuctionAddress(obligation: PublicKey): PublicKey {
+    const [auctionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('liquidation_auction'), obligation.toBuffer()],
+      this.programId
+    );
+    return auctionPda;
+  }
+
+  /**
+   * Derives the standing order PDA address for a given obligation
+   * 
+   * @param obligation - The obligation account address
+   * @returns The standing order account public key
+   */
+  getStandingOrderAddress(obligation: PublicKey): PublicKey {
+    const [standingOrderPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('standing_order'), obligation.toBuffer()],
+      this.programId
+    );
+    return standingOrderPda;
+  }
+
+  /**
+   * Derives the sub-account record PDA address for a given obligation
+   * 
+   * @param obligation - The obligation account address
+   * @returns The sub-account record public key
+   */
+  getSubAccountsAddress(obligation: PublicKey): PublicKey {
+    const [subAccountsPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('sub_accounts'), obligation.toBuffer()],
+      this.programId
+    );
+    return subAccountsPda;
   }
 
   /**
@@ -345,6 +853,20 @@ export class AuraLendClient {
     }
   }
 
+  /**
+   * Resolves the token program that owns a mint (SPL Token or Token-2022)
+   * 
+   * A reserve's liquidity supply and aToken mint live under the same program
+   * as its liquidity mint.
+   * 
+   * @param mint - The token mint address
+   * @returns The owning token program, or SPL Token if the mint is not found
+   */
+  async getTokenProgram(mint: PublicKey): Promise<PublicKey> {
+    const accountInfo = await this.connection.getAccountInfo(mint);
+    return accountInfo?.owner ?? TOKEN_PROGRAM_ID;
+  }
+
   /**
    * Creates an Associated Token Account if it doesn't exist
    * 
@@ -356,7 +878,8 @@ export class AuraLendClient {
     mint: PublicKey,
     owner: PublicKey = this.wallet.publicKey
   ): Promise<PublicKey> {
-    const ata = getAssociatedTokenAddressSync(mint, owner);
+    const tokenProgram = await this.getTokenProgram(mint);
+    const ata = getAssociatedTokenAddressSync(mint, owner, false, tokenProgram);
 
     const accountInfo = await this.connection.getAccountInfo(ata);
     
@@ -366,7 +889,8 @@ export class AuraLendClient {
           this.wallet.publicKey,
           ata,
           owner,
-          mint
+          mint,
+          tokenProgram
         )
       );
 
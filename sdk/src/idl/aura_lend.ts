@@ -92,41 +92,1198 @@ export type AuraLend = {
           isMut: true;
           isSigner: false;
         },
+        {
+          name: "treasuryCollateral";
+          isMut: true;
+          isSigner: true;
+        },
+        {
+          name: "payerLiquidity";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "owner";
+          isMut: false;
+          isSigner: true;
+        },
+        {
+          name: "payer";
+          isMut: true;
+          isSigner: true;
+        },
+        {
+          name: "systemProgram";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "tokenProgram";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "rent";
+          isMut: false;
+          isSigner: false;
+        }
+      ];
+      args: [
+        {
+          name: "params";
+          type: "InitializeReserveParams";
+        }
+      ];
+    },
+    {
+      name: "depositReserveLiquidity";
+      accounts: [
+        {
+          name: "market";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "reserve";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "liquidityMint";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "destinationLiquidity";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "liquiditySupplyAuthority";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "collateralMint";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "collateralMintAuthority";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "sourceLiquidity";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "destinationCollateral";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "userTransferAuthority";
+          isMut: false;
+          isSigner: true;
+        },
+        {
+          name: "tokenProgram";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "referrer";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "protocolMetrics";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "userStats";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        }
+      ];
+      args: [
+        {
+          name: "liquidityAmount";
+          type: "u64";
+        }
+      ];
+    },
+    {
+      name: "redeemReserveCollateral";
+      accounts: [
+        {
+          name: "market";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "reserve";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "liquidityMint";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "sourceLiquidity";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "liquiditySupplyAuthority";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "collateralMint";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "collateralMintAuthority";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "destinationCollateral";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "sourceCollateral";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "userTransferAuthority";
+          isMut: false;
+          isSigner: true;
+        },
+        {
+          name: "tokenProgram";
+          isMut: false;
+          isSigner: false;
+        }
+      ];
+      args: [
+        {
+          name: "collateralAmount";
+          type: "u64";
+        }
+      ];
+    },
+    {
+      name: "transferSupplyPosition";
+      accounts: [
+        {
+          name: "market";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "reserve";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "priceOracle";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "sourceObligation";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "destinationObligation";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "emodeCategory";
+          isMut: false;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "owner";
+          isMut: false;
+          isSigner: true;
+        },
+        {
+          name: "flashLoanGuard";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "config";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "rewardPool";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "sourceObligationRewards";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "destinationObligationRewards";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        }
+      ];
+      args: [
+        {
+          name: "collateralAmount";
+          type: "u64";
+        }
+      ];
+    },
+    {
+      name: "initObligation";
+      accounts: [
+        {
+          name: "market";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "obligation";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "obligationOwner";
+          isMut: false;
+          isSigner: true;
+        },
+        {
+          name: "payer";
+          isMut: true;
+          isSigner: true;
+        },
+        {
+          name: "systemProgram";
+          isMut: false;
+          isSigner: false;
+        }
+      ];
+      args: [
+        {
+          name: "obligationIndex";
+          type: "u8";
+        }
+      ];
+    },
+    {
+      name: "closeObligation";
+      accounts: [
+        {
+          name: "market";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "obligation";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "obligationOwner";
+          isMut: true;
+          isSigner: true;
+        }
+      ];
+      args: [];
+    },
+    {
+      name: "transferObligationOwnership";
+      accounts: [
+        {
+          name: "market";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "obligation";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "obligationTransfer";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "owner";
+          isMut: true;
+          isSigner: true;
+        },
+        {
+          name: "systemProgram";
+          isMut: false;
+          isSigner: false;
+        }
+      ];
+      args: [
+        {
+          name: "newOwner";
+          type: "publicKey";
+        }
+      ];
+    },
+    {
+      name: "cancelObligationTransfer";
+      accounts: [
+        {
+          name: "obligationTransfer";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "owner";
+          isMut: true;
+          isSigner: true;
+        }
+      ];
+      args: [];
+    },
+    {
+      name: "acceptObligationOwnership";
+      accounts: [
+        {
+          name: "market";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "obligationTransfer";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "obligation";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "newObligation";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "liquidationAuction";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "standingOrder";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "subAccounts";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "liquidationQueue";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "previousOwner";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "newOwner";
+          isMut: true;
+          isSigner: true;
+        },
+        {
+          name: "systemProgram";
+          isMut: false;
+          isSigner: false;
+        }
+      ];
+      args: [
+        {
+          name: "obligationIndex";
+          type: "u8";
+        }
+      ];
+    },
+    {
+      name: "borrowObligationLiquidity";
+      accounts: [
+        {
+          name: "market";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "obligation";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "borrowReserve";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "liquidityMint";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "liquiditySupply";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "liquiditySupplyAuthority";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "destinationLiquidity";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "feeReceiver";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "insuranceFund";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "insuranceFundVault";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "obligationOwner";
+          isMut: false;
+          isSigner: true;
+        },
+        {
+          name: "priceOracle";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "tokenProgram";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "config";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "referrer";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "rewardPool";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "obligationRewards";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "isolatedCollateralReserve";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "protocolMetrics";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "userStats";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        }
+      ];
+      args: [
+        {
+          name: "liquidityAmount";
+          type: "u64";
+        }
+      ];
+    },
+    {
+      name: "registerReferrer";
+      accounts: [
+        {
+          name: "market";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "referrer";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "owner";
+          isMut: true;
+          isSigner: true;
+        },
+        {
+          name: "systemProgram";
+          isMut: false;
+          isSigner: false;
+        }
+      ];
+      args: [];
+    },
+    {
+      name: "claimReferralFees";
+      accounts: [
+        {
+          name: "market";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "reserve";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "referrer";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "liquidityMint";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "sourceLiquidity";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "destinationLiquidity";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "liquiditySupplyAuthority";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "owner";
+          isMut: false;
+          isSigner: true;
+        },
+        {
+          name: "tokenProgram";
+          isMut: false;
+          isSigner: false;
+        }
+      ];
+      args: [];
+    },
+    {
+      name: "initUserStats";
+      accounts: [
+        {
+          name: "market";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "userStats";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "owner";
+          isMut: true;
+          isSigner: true;
+        },
+        {
+          name: "systemProgram";
+          isMut: false;
+          isSigner: false;
+        }
+      ];
+      args: [];
+    },
+    {
+      name: "initObligationRewards";
+      accounts: [
+        {
+          name: "rewardPool";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "obligation";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "obligationRewards";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "obligationOwner";
+          isMut: true;
+          isSigner: true;
+        },
+        {
+          name: "systemProgram";
+          isMut: false;
+          isSigner: false;
+        }
+      ];
+      args: [];
+    },
+    {
+      name: "syncObligationRewards";
+      accounts: [
+        {
+          name: "rewardPool";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "obligation";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "obligationRewards";
+          isMut: true;
+          isSigner: false;
+        }
+      ];
+      args: [];
+    },
+    {
+      name: "claimRewards";
+      accounts: [
+        {
+          name: "rewardPool";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "obligation";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "obligationRewards";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "rewardMint";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "rewardVault";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "destinationRewards";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "obligationOwner";
+          isMut: false;
+          isSigner: true;
+        },
+        {
+          name: "tokenProgram";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "userStats";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        }
+      ];
+      args: [];
+    },
+    {
+      name: "closeObligationRewards";
+      accounts: [
+        {
+          name: "rewardPool";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "obligation";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "obligationRewards";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "obligationOwner";
+          isMut: true;
+          isSigner: true;
+        }
+      ];
+      args: [];
+    },
+    {
+      name: "initializeStakeVault";
+      accounts: [
+        {
+          name: "market";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "reserve";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "liquidityMint";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "collateralMint";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "stakeVault";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "vaultCollateral";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "compoundLiquidity";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "authority";
+          isMut: false;
+          isSigner: true;
+        },
+        {
+          name: "adminAuditLog";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "payer";
+          isMut: true;
+          isSigner: true;
+        },
+        {
+          name: "systemProgram";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "tokenProgram";
+          isMut: false;
+          isSigner: false;
+        }
+      ];
+      args: [
+        {
+          name: "cooldownSeconds";
+          type: "i64";
+        }
+      ];
+    },
+    {
+      name: "openStakePosition";
+      accounts: [
+        {
+          name: "stakeVault";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "stakePosition";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "owner";
+          isMut: true;
+          isSigner: true;
+        },
+        {
+          name: "systemProgram";
+          isMut: false;
+          isSigner: false;
+        }
+      ];
+      args: [];
+    },
+    {
+      name: "stake";
+      accounts: [
+        {
+          name: "market";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "stakeVault";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "stakePosition";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "collateralMint";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "vaultCollateral";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "sourceCollateral";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "owner";
+          isMut: false;
+          isSigner: true;
+        },
+        {
+          name: "tokenProgram";
+          isMut: false;
+          isSigner: false;
+        }
+      ];
+      args: [
+        {
+          name: "collateralAmount";
+          type: "u64";
+        }
+      ];
+    },
+    {
+      name: "requestUnstake";
+      accounts: [
+        {
+          name: "stakeVault";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "stakePosition";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "owner";
+          isMut: false;
+          isSigner: true;
+        }
+      ];
+      args: [
+        {
+          name: "shares";
+          type: "u64";
+        }
+      ];
+    },
+    {
+      name: "unstake";
+      accounts: [
+        {
+          name: "market";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "stakeVault";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "stakePosition";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "collateralMint";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "vaultCollateral";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "destinationCollateral";
+          isMut: true;
+          isSigner: false;
+        },
         {
           name: "owner";
           isMut: false;
           isSigner: true;
         },
         {
-          name: "payer";
+          name: "tokenProgram";
+          isMut: false;
+          isSigner: false;
+        }
+      ];
+      args: [];
+    },
+    {
+      name: "compoundStakeVault";
+      accounts: [
+        {
+          name: "market";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "reserve";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "stakeVault";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "liquidityMint";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "compoundLiquidity";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "destinationLiquidity";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "collateralMint";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "collateralMintAuthority";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "vaultCollateral";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "tokenProgram";
+          isMut: false;
+          isSigner: false;
+        }
+      ];
+      args: [];
+    },
+    {
+      name: "liquidateObligation";
+      accounts: [
+        {
+          name: "market";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "obligation";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "repayReserve";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "withdrawReserve";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "repayLiquidityMint";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "withdrawCollateralMint";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "liquidator";
+          isMut: true;
+          isSigner: true;
+        },
+        {
+          name: "tokenProgram";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "collateralTokenProgram";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "keeperRegistry";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "keeper";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "liquidationQueue";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "liquidationCommitment";
           isMut: true;
-          isSigner: true;
+          isSigner: false;
+          isOptional: true;
         },
         {
-          name: "systemProgram";
-          isMut: false;
+          name: "protocolMetrics";
+          isMut: true;
           isSigner: false;
+          isOptional: true;
         },
         {
-          name: "tokenProgram";
+          name: "userStats";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "withdrawLiquiditySupplyAuthority";
           isMut: false;
           isSigner: false;
         },
         {
-          name: "rent";
+          name: "withdrawLiquidityMint";
           isMut: false;
           isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "withdrawReserveLiquiditySupply";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "withdrawInsuranceFund";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "withdrawInsuranceFundVault";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
         }
       ];
       args: [
         {
-          name: "params";
-          type: "InitializeReserveParams";
+          name: "liquidityAmount";
+          type: "u64";
         }
       ];
     },
     {
-      name: "depositReserveLiquidity";
+      name: "commitLiquidation";
       accounts: [
         {
           name: "market";
@@ -134,60 +1291,67 @@ export type AuraLend = {
           isSigner: false;
         },
         {
-          name: "reserve";
-          isMut: true;
+          name: "obligation";
+          isMut: false;
           isSigner: false;
         },
         {
-          name: "destinationLiquidity";
-          isMut: true;
+          name: "repayReserve";
+          isMut: false;
           isSigner: false;
         },
         {
-          name: "liquiditySupplyAuthority";
+          name: "withdrawReserve";
           isMut: false;
           isSigner: false;
         },
         {
-          name: "collateralMint";
-          isMut: true;
+          name: "repayPriceOracle";
+          isMut: false;
           isSigner: false;
         },
         {
-          name: "collateralMintAuthority";
+          name: "withdrawPriceOracle";
           isMut: false;
           isSigner: false;
         },
         {
-          name: "sourceLiquidity";
-          isMut: true;
+          name: "repaySecondaryPriceOracle";
+          isMut: false;
           isSigner: false;
+          isOptional: true;
         },
         {
-          name: "destinationCollateral";
+          name: "withdrawSecondaryPriceOracle";
+          isMut: false;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "liquidationCommitment";
           isMut: true;
           isSigner: false;
         },
         {
-          name: "userTransferAuthority";
-          isMut: false;
+          name: "liquidator";
+          isMut: true;
           isSigner: true;
         },
         {
-          name: "tokenProgram";
+          name: "systemProgram";
           isMut: false;
           isSigner: false;
         }
       ];
       args: [
         {
-          name: "liquidityAmount";
-          type: "u64";
+          name: "commitmentHash";
+          type: { array: ["u8", 32] };
         }
       ];
     },
     {
-      name: "redeemReserveCollateral";
+      name: "executeLiquidation";
       accounts: [
         {
           name: "market";
@@ -195,78 +1359,143 @@ export type AuraLend = {
           isSigner: false;
         },
         {
-          name: "reserve";
+          name: "obligation";
           isMut: true;
           isSigner: false;
         },
         {
-          name: "sourceLiquidity";
+          name: "repayReserve";
           isMut: true;
           isSigner: false;
         },
         {
-          name: "liquiditySupplyAuthority";
+          name: "withdrawReserve";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "repayLiquidityMint";
           isMut: false;
           isSigner: false;
         },
         {
-          name: "collateralMint";
+          name: "withdrawCollateralMint";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "liquidator";
           isMut: true;
+          isSigner: true;
+        },
+        {
+          name: "tokenProgram";
+          isMut: false;
           isSigner: false;
         },
         {
-          name: "collateralMintAuthority";
+          name: "collateralTokenProgram";
           isMut: false;
           isSigner: false;
         },
         {
-          name: "destinationCollateral";
+          name: "keeperRegistry";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "keeper";
           isMut: true;
           isSigner: false;
+          isOptional: true;
         },
         {
-          name: "sourceCollateral";
+          name: "liquidationQueue";
           isMut: true;
           isSigner: false;
+          isOptional: true;
         },
         {
-          name: "userTransferAuthority";
-          isMut: false;
-          isSigner: true;
+          name: "liquidationCommitment";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
         },
         {
-          name: "tokenProgram";
-          isMut: false;
+          name: "protocolMetrics";
+          isMut: true;
+          isSigner: false;
+          isOptional: true;
+        },
+        {
+          name: "userStats";
+          isMut: true;
           isSigner: false;
+          isOptional: true;
         }
       ];
       args: [
         {
-          name: "collateralAmount";
+          name: "liquidityAmount";
           type: "u64";
+        },
+        {
+          name: "salt";
+          type: { array: ["u8", 32] };
         }
       ];
     },
     {
-      name: "initObligation";
+      name: "cancelLiquidationCommitment";
+      accounts: [
+        {
+          name: "liquidationCommitment";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "liquidator";
+          isMut: true;
+          isSigner: true;
+        }
+      ];
+      args: [];
+    },
+    {
+      name: "startLiquidationAuction";
       accounts: [
         {
           name: "market";
           isMut: false;
           isSigner: false;
         },
+        {
+          name: "config";
+          isMut: false;
+          isSigner: false;
+        },
         {
           name: "obligation";
-          isMut: true;
+          isMut: false;
           isSigner: false;
         },
         {
-          name: "obligationOwner";
+          name: "repayReserve";
           isMut: false;
-          isSigner: true;
+          isSigner: false;
         },
         {
-          name: "payer";
+          name: "withdrawReserve";
+          isMut: false;
+          isSigner: false;
+        },
+        {
+          name: "auction";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "initiator";
           isMut: true;
           isSigner: true;
         },
@@ -279,7 +1508,7 @@ export type AuraLend = {
       args: [];
     },
     {
-      name: "borrowObligationLiquidity";
+      name: "bidLiquidationAuction";
       accounts: [
         {
           name: "market";
@@ -292,37 +1521,42 @@ export type AuraLend = {
           isSigner: false;
         },
         {
-          name: "borrowReserve";
+          name: "auction";
           isMut: true;
           isSigner: false;
         },
         {
-          name: "liquiditySupply";
+          name: "repayReserve";
           isMut: true;
           isSigner: false;
         },
         {
-          name: "liquiditySupplyAuthority";
+          name: "withdrawReserve";
+          isMut: true;
+          isSigner: false;
+        },
+        {
+          name: "repayLiquidityMint";
           isMut: false;
           isSigner: false;
         },
         {
-          name: "destinationLiquidity";
-          isMut: true;
+          name: "withdrawCollateralMint";
+          isMut: false;
           isSigner: false;
         },
         {
-          name: "obligationOwner";
+          name: "bidder";
           isMut: false;
           isSigner: true;
         },
         {
-          name: "priceOracle";
+          name: "tokenProgram";
           isMut: false;
           isSigner: false;
         },
         {
-          name: "tokenProgram";
+          name: "collateralTokenProgram";
           isMut: false;
           isSigner: false;
         }
@@ -331,11 +1565,15 @@ export type AuraLend = {
         {
           name: "liquidityAmount";
           type: "u64";
+        },
+        {
+          name: "minCollateralAmount";
+          type: "u64";
         }
       ];
     },
     {
-      name: "liquidateObligation";
+      name: "refreshObligation";
       accounts: [
         {
           name: "market";
@@ -343,37 +1581,23 @@ export type AuraLend = {
           isSigner: false;
         },
         {
-          name: "obligation";
-          isMut: true;
+          name: "config";
+          isMut: false;
           isSigner: false;
         },
         {
-          name: "repayReserve";
+          name: "obligation";
           isMut: true;
           isSigner: false;
         },
         {
-          name: "withdrawReserve";
+          name: "liquidationQueue";
           isMut: true;
           isSigner: false;
-        },
-        {
-          name: "liquidator";
-          isMut: false;
-          isSigner: true;
-        },
-        {
-          name: "tokenProgram";
-          isMut: false;
-          isSigner: false;
-        }
-      ];
-      args: [
-        {
-          name: "liquidityAmount";
-          type: "u64";
+          isOptional: true;
         }
       ];
+      args: [];
     }
   ];
   accounts: [
@@ -449,51 +1673,139 @@ export type AuraLend = {
             type: "publicKey";
           },
           {
-            name: "collateralMint";
-            type: "publicKey";
+            name: "collateralMint";
+            type: "publicKey";
+          },
+          {
+            name: "liquiditySupply";
+            type: "publicKey";
+          },
+          {
+            name: "feeReceiver";
+            type: "publicKey";
+          },
+          {
+            name: "priceOracle";
+            type: "publicKey";
+          },
+          {
+            name: "oracleFeedId";
+            type: {
+              array: ["u8", 32];
+            };
+          },
+          {
+            name: "config";
+            type: "ReserveConfig";
+          },
+          {
+            name: "state";
+            type: "ReserveState";
+          },
+          {
+            name: "lastUpdateTimestamp";
+            type: "u64";
+          },
+          {
+            name: "lastUpdateSlot";
+            type: "u64";
+          },
+          {
+            name: "feeSplit";
+            type: "FeeSplit";
+          },
+          {
+            name: "assetClass";
+            type: "u8";
+          },
+          {
+            name: "oracleSource";
+            type: "OracleSource";
+          },
+          {
+            name: "fixedPrice";
+            type: "u64";
+          },
+          {
+            name: "secondaryOracle";
+            type: "publicKey";
+          },
+          {
+            name: "secondaryOracleSource";
+            type: "OracleSource";
+          },
+          {
+            name: "maxOracleDeviationBps";
+            type: "u64";
+          },
+          {
+            name: "twapPrice";
+            type: "i64";
+          },
+          {
+            name: "twapExponent";
+            type: "i32";
+          },
+          {
+            name: "twapLastUpdateTimestamp";
+            type: "u64";
+          },
+          {
+            name: "twapWindowSeconds";
+            type: "u64";
+          },
+          {
+            name: "deprecationStartTimestamp";
+            type: "u64";
+          },
+          {
+            name: "deprecationDeadlineTimestamp";
+            type: "u64";
+          },
+          {
+            name: "deleveragePenaltyBps";
+            type: "u16";
           },
           {
-            name: "liquiditySupply";
-            type: "publicKey";
+            name: "debtCeilingUsd";
+            type: "u64";
           },
           {
-            name: "feeReceiver";
-            type: "publicKey";
+            name: "isolationModeTotalDebt";
+            type: "u64";
           },
           {
-            name: "priceOracle";
-            type: "publicKey";
+            name: "surgeUtilizationThresholdBps";
+            type: "u16";
           },
           {
-            name: "oracleFeedId";
-            type: {
-              array: ["u8", 32];
-            };
+            name: "surgeDurationSlots";
+            type: "u64";
           },
           {
-            name: "config";
-            type: "ReserveConfig";
+            name: "surgeWithdrawalFeeBps";
+            type: "u16";
           },
           {
-            name: "state";
-            type: "ReserveState";
+            name: "highUtilizationSinceSlot";
+            type: "u64";
           },
           {
-            name: "lastUpdateTimestamp";
+            name: "totalSurgeFees";
             type: "u64";
           },
           {
-            name: "lastUpdateSlot";
-            type: "u64";
+            name: "emergencyPauseFlags";
+            type: "ReserveConfigFlags";
           },
           {
-            name: "reentrancyGuard";
-            type: "bool";
+            name: "derivedOracle";
+            type: "DerivedOracle";
           },
           {
             name: "reserved";
             type: {
-              array: ["u8", 255];
+              array: ["u8", 8];
             };
           }
         ];
@@ -550,10 +1862,46 @@ export type AuraLend = {
               option: "Decimal";
             };
           },
+          {
+            name: "unhealthySinceSlot";
+            type: "u64";
+          },
+          {
+            name: "emodeCategory";
+            type: "u8";
+          },
+          {
+            name: "isolationMode";
+            type: "bool";
+          },
+          {
+            name: "creditHistory";
+            type: "CreditHistory";
+          },
+          {
+            name: "refreshedSlot";
+            type: "u64";
+          },
+          {
+            name: "liquidationGraceSlots";
+            type: "u64";
+          },
+          {
+            name: "liquidationGraceFloorBps";
+            type: "u16";
+          },
+          {
+            name: "obligationIndex";
+            type: "u8";
+          },
+          {
+            name: "linkedAccounts";
+            type: "u16";
+          },
           {
             name: "reserved";
             type: {
-              array: ["u8", 112];
+              array: ["u8", 53];
             };
           }
         ];
@@ -604,9 +1952,146 @@ export type AuraLend = {
               array: ["u8", 32];
             };
           },
+          {
+            name: "oracleSource";
+            type: "OracleSource";
+          },
+          {
+            name: "fixedPrice";
+            type: "u64";
+          },
           {
             name: "config";
             type: "ReserveConfig";
+          },
+          {
+            name: "initialLiquidity";
+            type: {
+              option: "u64";
+            };
+          }
+        ];
+      };
+    },
+    {
+      name: "OracleSource";
+      type: {
+        kind: "enum";
+        variants: [
+          {
+            name: "Pyth";
+          },
+          {
+            name: "Switchboard";
+          },
+          {
+            name: "FixedPrice";
+          },
+          {
+            name: "Derived";
+          }
+        ];
+      };
+    },
+    {
+      name: "DerivedOracleKind";
+      type: {
+        kind: "enum";
+        variants: [
+          {
+            name: "None";
+          },
+          {
+            name: "StakePool";
+          },
+          {
+            name: "LiquidityPool";
+          }
+        ];
+      };
+    },
+    {
+      name: "DerivedOracleComponent";
+      type: {
+        kind: "struct";
+        fields: [
+          {
+            name: "oracle";
+            type: "publicKey";
+          },
+          {
+            name: "source";
+            type: "OracleSource";
+          },
+          {
+            name: "feedId";
+            type: {
+              array: ["u8", 32];
+            };
+          },
+          {
+            name: "vault";
+            type: "publicKey";
+          },
+          {
+            name: "decimals";
+            type: "u8";
+          }
+        ];
+      };
+    },
+    {
+      name: "DerivedOracle";
+      type: {
+        kind: "struct";
+        fields: [
+          {
+            name: "kind";
+            type: "DerivedOracleKind";
+          },
+          {
+            name: "pool";
+            type: "publicKey";
+          },
+          {
+            name: "base";
+            type: "DerivedOracleComponent";
+          },
+          {
+            name: "quote";
+            type: "DerivedOracleComponent";
+          },
+          {
+            name: "price";
+            type: "u64";
+          },
+          {
+            name: "confidence";
+            type: "u64";
+          },
+          {
+            name: "publishTime";
+            type: "i64";
+          }
+        ];
+      };
+    },
+    {
+      name: "InterestRateStrategy";
+      type: {
+        kind: "enum";
+        variants: [
+          {
+            name: "Kinked";
+          },
+          {
+            name: "Linear";
+          },
+          {
+            name: "Curve";
+          },
+          {
+            name: "Fixed";
           }
         ];
       };
@@ -659,6 +2144,42 @@ export type AuraLend = {
           {
             name: "flags";
             type: "ReserveConfigFlags";
+          },
+          {
+            name: "supplyCap";
+            type: "u64";
+          },
+          {
+            name: "borrowCap";
+            type: "u64";
+          },
+          {
+            name: "borrowFeeBps";
+            type: "u64";
+          },
+          {
+            name: "insuranceFundShareBps";
+            type: "u64";
+          },
+          {
+            name: "interestRateStrategy";
+            type: "InterestRateStrategy";
+          },
+          {
+            name: "maxConfidenceBps";
+            type: "u64";
+          },
+          {
+            name: "maxStalenessSlots";
+            type: "u64";
+          },
+          {
+            name: "liquidationCloseFactorBps";
+            type: "u64";
+          },
+          {
+            name: "liquidationProtocolFeeBps";
+            type: "u64";
           }
         ];
       };
@@ -685,20 +2206,44 @@ export type AuraLend = {
             type: "u64";
           },
           {
-            name: "currentBorrowRate";
-            type: "Decimal";
+            name: "currentBorrowRateBps";
+            type: "u64";
           },
           {
-            name: "currentSupplyRate";
-            type: "Decimal";
+            name: "currentSupplyRateBps";
+            type: "u64";
           },
           {
-            name: "currentUtilizationRate";
-            type: "Decimal";
+            name: "currentUtilizationRateBps";
+            type: "u64";
           },
           {
             name: "accumulatedProtocolFees";
             type: "u64";
+          },
+          {
+            name: "cumulativeBorrowRateWads";
+            type: "Decimal";
+          },
+          {
+            name: "investedLiquidity";
+            type: "u64";
+          },
+          {
+            name: "flags";
+            type: "ReserveStateFlags";
+          }
+        ];
+      };
+    },
+    {
+      name: "ReserveStateFlags";
+      type: {
+        kind: "struct";
+        fields: [
+          {
+            name: "locked";
+            type: "bool";
           }
         ];
       };
@@ -747,6 +2292,22 @@ export type AuraLend = {
           {
             name: "marketValueUsd";
             type: "Decimal";
+          },
+          {
+            name: "originationUtilizationRate";
+            type: "Decimal";
+          },
+          {
+            name: "originationBorrowRate";
+            type: "Decimal";
+          },
+          {
+            name: "originationSlot";
+            type: "u64";
+          },
+          {
+            name: "cumulativeBorrowRateWads";
+            type: "Decimal";
           }
         ];
       };
@@ -801,24 +2362,88 @@ export type AuraLend = {
             type: "bool";
           },
           {
-            name: "withdrawalsDisabled";
-            type: "bool";
+            name: "withdrawalsDisabled";
+            type: "bool";
+          },
+          {
+            name: "borrowingDisabled";
+            type: "bool";
+          },
+          {
+            name: "repaymentsDisabled";
+            type: "bool";
+          },
+          {
+            name: "liquidationsDisabled";
+            type: "bool";
+          },
+          {
+            name: "collateralEnabled";
+            type: "bool";
+          }
+        ];
+      };
+    },
+    {
+      name: "FeeRecipient";
+      type: {
+        kind: "struct";
+        fields: [
+          {
+            name: "receiver";
+            type: "publicKey";
+          },
+          {
+            name: "weightBps";
+            type: "u16";
+          }
+        ];
+      };
+    },
+    {
+      name: "FeeSplit";
+      type: {
+        kind: "struct";
+        fields: [
+          {
+            name: "insurance";
+            type: "FeeRecipient";
+          },
+          {
+            name: "stakers";
+            type: "FeeRecipient";
+          },
+          {
+            name: "grants";
+            type: "FeeRecipient";
+          }
+        ];
+      };
+    },
+    {
+      name: "CreditHistory";
+      type: {
+        kind: "struct";
+        fields: [
+          {
+            name: "maxDebtUsd";
+            type: "u64";
           },
           {
-            name: "borrowingDisabled";
-            type: "bool";
+            name: "onTimeRepayments";
+            type: "u32";
           },
           {
-            name: "repaymentsDisabled";
-            type: "bool";
+            name: "lateRepayments";
+            type: "u32";
           },
           {
-            name: "liquidationsDisabled";
-            type: "bool";
+            name: "liquidations";
+            type: "u32";
           },
           {
-            name: "collateralEnabled";
-            type: "bool";
+            name: "lastLiquidationSlot";
+            type: "u64";
           }
         ];
       };
@@ -911,6 +2536,8 @@ export const IDL: AuraLend = {
         { name: "liquiditySupply", isMut: true, isSigner: false },
         { name: "liquiditySupplyAuthority", isMut: false, isSigner: false },
         { name: "feeReceiver", isMut: true, isSigner: false },
+        { name: "treasuryCollateral", isMut: true, isSigner: true },
+        { name: "payerLiquidity", isMut: true, isSigner: false, isOptional: true },
         { name: "owner", isMut: false, isSigner: true },
         { name: "payer", isMut: true, isSigner: true },
         { name: "systemProgram", isMut: false, isSigner: false },
@@ -924,6 +2551,7 @@ export const IDL: AuraLend = {
       accounts: [
         { name: "market", isMut: false, isSigner: false },
         { name: "reserve", isMut: true, isSigner: false },
+        { name: "liquidityMint", isMut: false, isSigner: false },
         { name: "destinationLiquidity", isMut: true, isSigner: false },
         { name: "liquiditySupplyAuthority", isMut: false, isSigner: false },
         { name: "collateralMint", isMut: true, isSigner: false },
@@ -931,7 +2559,10 @@ export const IDL: AuraLend = {
         { name: "sourceLiquidity", isMut: true, isSigner: false },
         { name: "destinationCollateral", isMut: true, isSigner: false },
         { name: "userTransferAuthority", isMut: false, isSigner: true },
-        { name: "tokenProgram", isMut: false, isSigner: false }
+        { name: "tokenProgram", isMut: false, isSigner: false },
+        { name: "referrer", isMut: true, isSigner: false, isOptional: true },
+        { name: "protocolMetrics", isMut: true, isSigner: false, isOptional: true },
+        { name: "userStats", isMut: true, isSigner: false, isOptional: true }
       ],
       args: [{ name: "liquidityAmount", type: "u64" }]
     },
@@ -940,6 +2571,7 @@ export const IDL: AuraLend = {
       accounts: [
         { name: "market", isMut: false, isSigner: false },
         { name: "reserve", isMut: true, isSigner: false },
+        { name: "liquidityMint", isMut: false, isSigner: false },
         { name: "sourceLiquidity", isMut: true, isSigner: false },
         { name: "liquiditySupplyAuthority", isMut: false, isSigner: false },
         { name: "collateralMint", isMut: true, isSigner: false },
@@ -951,6 +2583,24 @@ export const IDL: AuraLend = {
       ],
       args: [{ name: "collateralAmount", type: "u64" }]
     },
+    {
+      name: "transferSupplyPosition",
+      accounts: [
+        { name: "market", isMut: false, isSigner: false },
+        { name: "reserve", isMut: true, isSigner: false },
+        { name: "priceOracle", isMut: false, isSigner: false },
+        { name: "sourceObligation", isMut: true, isSigner: false },
+        { name: "destinationObligation", isMut: true, isSigner: false },
+        { name: "emodeCategory", isMut: false, isSigner: false, isOptional: true },
+        { name: "owner", isMut: false, isSigner: true },
+        { name: "flashLoanGuard", isMut: true, isSigner: false },
+        { name: "config", isMut: false, isSigner: false },
+        { name: "rewardPool", isMut: true, isSigner: false, isOptional: true },
+        { name: "sourceObligationRewards", isMut: true, isSigner: false, isOptional: true },
+        { name: "destinationObligationRewards", isMut: true, isSigner: false, isOptional: true }
+      ],
+      args: [{ name: "collateralAmount", type: "u64" }]
+    },
     {
       name: "initObligation",
       accounts: [
@@ -960,23 +2610,240 @@ export const IDL: AuraLend = {
         { name: "payer", isMut: true, isSigner: true },
         { name: "systemProgram", isMut: false, isSigner: false }
       ],
+      args: [{ name: "obligationIndex", type: "u8" }]
+    },
+    {
+      name: "closeObligation",
+      accounts: [
+        { name: "market", isMut: false, isSigner: false },
+        { name: "obligation", isMut: true, isSigner: false },
+        { name: "obligationOwner", isMut: true, isSigner: true }
+      ],
+      args: []
+    },
+    {
+      name: "transferObligationOwnership",
+      accounts: [
+        { name: "market", isMut: false, isSigner: false },
+        { name: "obligation", isMut: false, isSigner: false },
+        { name: "obligationTransfer", isMut: true, isSigner: false },
+        { name: "owner", isMut: true, isSigner: true },
+        { name: "systemProgram", isMut: false, isSigner: false }
+      ],
+      args: [{ name: "newOwner", type: "publicKey" }]
+    },
+    {
+      name: "cancelObligationTransfer",
+      accounts: [
+        { name: "obligationTransfer", isMut: true, isSigner: false },
+        { name: "owner", isMut: true, isSigner: true }
+      ],
       args: []
     },
+    {
+      name: "acceptObligationOwnership",
+      accounts: [
+        { name: "market", isMut: false, isSigner: false },
+        { name: "obligationTransfer", isMut: true, isSigner: false },
+        { name: "obligation", isMut: true, isSigner: false },
+        { name: "newObligation", isMut: true, isSigner: false },
+        { name: "liquidationAuction", isMut: false, isSigner: false },
+        { name: "standingOrder", isMut: false, isSigner: false },
+        { name: "subAccounts", isMut: true, isSigner: false },
+        { name: "liquidationQueue", isMut: true, isSigner: false },
+        { name: "previousOwner", isMut: true, isSigner: false },
+        { name: "newOwner", isMut: true, isSigner: true },
+        { name: "systemProgram", isMut: false, isSigner: false }
+      ],
+      args: [{ name: "obligationIndex", type: "u8" }]
+    },
     {
       name: "borrowObligationLiquidity",
       accounts: [
         { name: "market", isMut: false, isSigner: false },
         { name: "obligation", isMut: true, isSigner: false },
         { name: "borrowReserve", isMut: true, isSigner: false },
+        { name: "liquidityMint", isMut: false, isSigner: false },
         { name: "liquiditySupply", isMut: true, isSigner: false },
         { name: "liquiditySupplyAuthority", isMut: false, isSigner: false },
         { name: "destinationLiquidity", isMut: true, isSigner: false },
+        { name: "feeReceiver", isMut: true, isSigner: false },
+        { name: "insuranceFund", isMut: true, isSigner: false },
+        { name: "insuranceFundVault", isMut: true, isSigner: false },
         { name: "obligationOwner", isMut: false, isSigner: true },
         { name: "priceOracle", isMut: false, isSigner: false },
-        { name: "tokenProgram", isMut: false, isSigner: false }
+        { name: "tokenProgram", isMut: false, isSigner: false },
+        { name: "config", isMut: false, isSigner: false },
+        { name: "referrer", isMut: true, isSigner: false, isOptional: true },
+        { name: "rewardPool", isMut: true, isSigner: false, isOptional: true },
+        { name: "obligationRewards", isMut: true, isSigner: false, isOptional: true },
+        { name: "isolatedCollateralReserve", isMut: true, isSigner: false, isOptional: true },
+        { name: "protocolMetrics", isMut: true, isSigner: false, isOptional: true },
+        { name: "userStats", isMut: true, isSigner: false, isOptional: true }
       ],
       args: [{ name: "liquidityAmount", type: "u64" }]
     },
+    {
+      name: "registerReferrer",
+      accounts: [
+        { name: "market", isMut: false, isSigner: false },
+        { name: "referrer", isMut: true, isSigner: false },
+        { name: "owner", isMut: true, isSigner: true },
+        { name: "systemProgram", isMut: false, isSigner: false }
+      ],
+      args: []
+    },
+    {
+      name: "claimReferralFees",
+      accounts: [
+        { name: "market", isMut: false, isSigner: false },
+        { name: "reserve", isMut: false, isSigner: false },
+        { name: "referrer", isMut: true, isSigner: false },
+        { name: "liquidityMint", isMut: false, isSigner: false },
+        { name: "sourceLiquidity", isMut: true, isSigner: false },
+        { name: "destinationLiquidity", isMut: true, isSigner: false },
+        { name: "liquiditySupplyAuthority", isMut: false, isSigner: false },
+        { name: "owner", isMut: false, isSigner: true },
+        { name: "tokenProgram", isMut: false, isSigner: false }
+      ],
+      args: []
+    },
+    {
+      name: "initUserStats",
+      accounts: [
+        { name: "market", isMut: false, isSigner: false },
+        { name: "userStats", isMut: true, isSigner: false },
+        { name: "owner", isMut: true, isSigner: true },
+        { name: "systemProgram", isMut: false, isSigner: false }
+      ],
+      args: []
+    },
+    {
+      name: "initObligationRewards",
+      accounts: [
+        { name: "rewardPool", isMut: true, isSigner: false },
+        { name: "obligation", isMut: true, isSigner: false },
+        { name: "obligationRewards", isMut: true, isSigner: false },
+        { name: "obligationOwner", isMut: true, isSigner: true },
+        { name: "systemProgram", isMut: false, isSigner: false }
+      ],
+      args: []
+    },
+    {
+      name: "syncObligationRewards",
+      accounts: [
+        { name: "rewardPool", isMut: true, isSigner: false },
+        { name: "obligation", isMut: false, isSigner: false },
+        { name: "obligationRewards", isMut: true, isSigner: false }
+      ],
+      args: []
+    },
+    {
+      name: "claimRewards",
+      accounts: [
+        { name: "rewardPool", isMut: true, isSigner: false },
+        { name: "obligation", isMut: false, isSigner: false },
+        { name: "obligationRewards", isMut: true, isSigner: false },
+        { name: "rewardMint", isMut: false, isSigner: false },
+        { name: "rewardVault", isMut: true, isSigner: false },
+        { name: "destinationRewards", isMut: true, isSigner: false },
+        { name: "obligationOwner", isMut: false, isSigner: true },
+        { name: "tokenProgram", isMut: false, isSigner: false },
+        { name: "userStats", isMut: true, isSigner: false, isOptional: true }
+      ],
+      args: []
+    },
+    {
+      name: "closeObligationRewards",
+      accounts: [
+        { name: "rewardPool", isMut: true, isSigner: false },
+        { name: "obligation", isMut: true, isSigner: false },
+        { name: "obligationRewards", isMut: true, isSigner: false },
+        { name: "obligationOwner", isMut: true, isSigner: true }
+      ],
+      args: []
+    },
+    {
+      name: "initializeStakeVault",
+      accounts: [
+        { name: "market", isMut: false, isSigner: false },
+        { name: "reserve", isMut: false, isSigner: false },
+        { name: "liquidityMint", isMut: false, isSigner: false },
+        { name: "collateralMint", isMut: false, isSigner: false },
+        { name: "stakeVault", isMut: true, isSigner: false },
+        { name: "vaultCollateral", isMut: true, isSigner: false },
+        { name: "compoundLiquidity", isMut: true, isSigner: false },
+        { name: "authority", isMut: false, isSigner: true },
+        { name: "adminAuditLog", isMut: true, isSigner: false },
+        { name: "payer", isMut: true, isSigner: true },
+        { name: "systemProgram", isMut: false, isSigner: false },
+        { name: "tokenProgram", isMut: false, isSigner: false }
+      ],
+      args: [{ name: "cooldownSeconds", type: "i64" }]
+    },
+    {
+      name: "openStakePosition",
+      accounts: [
+        { name: "stakeVault", isMut: false, isSigner: false },
+        { name: "stakePosition", isMut: true, isSigner: false },
+        { name: "owner", isMut: true, isSigner: true },
+        { name: "systemProgram", isMut: false, isSigner: false }
+      ],
+      args: []
+    },
+    {
+      name: "stake",
+      accounts: [
+        { name: "market", isMut: false, isSigner: false },
+        { name: "stakeVault", isMut: true, isSigner: false },
+        { name: "stakePosition", isMut: true, isSigner: false },
+        { name: "collateralMint", isMut: false, isSigner: false },
+        { name: "vaultCollateral", isMut: true, isSigner: false },
+        { name: "sourceCollateral", isMut: true, isSigner: false },
+        { name: "owner", isMut: false, isSigner: true },
+        { name: "tokenProgram", isMut: false, isSigner: false }
+      ],
+      args: [{ name: "collateralAmount", type: "u64" }]
+    },
+    {
+      name: "requestUnstake",
+      accounts: [
+        { name: "stakeVault", isMut: false, isSigner: false },
+        { name: "stakePosition", isMut: true, isSigner: false },
+        { name: "owner", isMut: false, isSigner: true }
+      ],
+      args: [{ name: "shares", type: "u64" }]
+    },
+    {
+      name: "unstake",
+      accounts: [
+        { name: "market", isMut: false, isSigner: false },
+        { name: "stakeVault", isMut: true, isSigner: false },
+        { name: "stakePosition", isMut: true, isSigner: false },
+        { name: "collateralMint", isMut: false, isSigner: false },
+        { name: "vaultCollateral", isMut: true, isSigner: false },
+        { name: "destinationCollateral", isMut: true, isSigner: false },
+        { name: "owner", isMut: false, isSigner: true },
+        { name: "tokenProgram", isMut: false, isSigner: false }
+      ],
+      args: []
+    },
+    {
+      name: "compoundStakeVault",
+      accounts: [
+        { name: "market", isMut: false, isSigner: false },
+        { name: "reserve", isMut: true, isSigner: false },
+        { name: "stakeVault", isMut: true, isSigner: false },
+        { name: "liquidityMint", isMut: false, isSigner: false },
+        { name: "compoundLiquidity", isMut: true, isSigner: false },
+        { name: "destinationLiquidity", isMut: true, isSigner: false },
+        { name: "collateralMint", isMut: true, isSigner: false },
+        { name: "collateralMintAuthority", isMut: false, isSigner: false },
+        { name: "vaultCollateral", isMut: true, isSigner: false },
+        { name: "tokenProgram", isMut: false, isSigner: false }
+      ],
+      args: []
+    },
     {
       name: "liquidateObligation",
       accounts: [
@@ -984,10 +2851,113 @@ export const IDL: AuraLend = {
         { name: "obligation", isMut: true, isSigner: false },
         { name: "repayReserve", isMut: true, isSigner: false },
         { name: "withdrawReserve", isMut: true, isSigner: false },
-        { name: "liquidator", isMut: false, isSigner: true },
-        { name: "tokenProgram", isMut: false, isSigner: false }
+        { name: "repayLiquidityMint", isMut: false, isSigner: false },
+        { name: "withdrawCollateralMint", isMut: true, isSigner: false },
+        { name: "liquidator", isMut: true, isSigner: true },
+        { name: "tokenProgram", isMut: false, isSigner: false },
+        { name: "collateralTokenProgram", isMut: false, isSigner: false },
+        { name: "keeperRegistry", isMut: false, isSigner: false },
+        { name: "keeper", isMut: true, isSigner: false, isOptional: true },
+        { name: "liquidationQueue", isMut: true, isSigner: false, isOptional: true },
+        { name: "liquidationCommitment", isMut: true, isSigner: false, isOptional: true },
+        { name: "protocolMetrics", isMut: true, isSigner: false, isOptional: true },
+        { name: "userStats", isMut: true, isSigner: false, isOptional: true },
+        { name: "withdrawLiquiditySupplyAuthority", isMut: false, isSigner: false },
+        { name: "withdrawLiquidityMint", isMut: false, isSigner: false, isOptional: true },
+        { name: "withdrawReserveLiquiditySupply", isMut: true, isSigner: false, isOptional: true },
+        { name: "withdrawInsuranceFund", isMut: true, isSigner: false, isOptional: true },
+        { name: "withdrawInsuranceFundVault", isMut: true, isSigner: false, isOptional: true }
       ],
       args: [{ name: "liquidityAmount", type: "u64" }]
+    },
+    {
+      name: "commitLiquidation",
+      accounts: [
+        { name: "market", isMut: false, isSigner: false },
+        { name: "obligation", isMut: false, isSigner: false },
+        { name: "repayReserve", isMut: false, isSigner: false },
+        { name: "withdrawReserve", isMut: false, isSigner: false },
+        { name: "repayPriceOracle", isMut: false, isSigner: false },
+        { name: "withdrawPriceOracle", isMut: false, isSigner: false },
+        { name: "repaySecondaryPriceOracle", isMut: false, isSigner: false, isOptional: true },
+        { name: "withdrawSecondaryPriceOracle", isMut: false, isSigner: false, isOptional: true },
+        { name: "liquidationCommitment", isMut: true, isSigner: false },
+        { name: "liquidator", isMut: true, isSigner: true },
+        { name: "systemProgram", isMut: false, isSigner: false }
+      ],
+      args: [{ name: "commitmentHash", type: { array: ["u8", 32] } }]
+    },
+    {
+      name: "executeLiquidation",
+      accounts: [
+        { name: "market", isMut: false, isSigner: false },
+        { name: "obligation", isMut: true, isSigner: false },
+        { name: "repayReserve", isMut: true, isSigner: false },
+        { name: "withdrawReserve", isMut: true, isSigner: false },
+        { name: "repayLiquidityMint", isMut: false, isSigner: false },
+        { name: "withdrawCollateralMint", isMut: false, isSigner: false },
+        { name: "liquidator", isMut: true, isSigner: true },
+        { name: "tokenProgram", isMut: false, isSigner: false },
+        { name: "collateralTokenProgram", isMut: false, isSigner: false },
+        { name: "keeperRegistry", isMut: false, isSigner: false },
+        { name: "keeper", isMut: true, isSigner: false, isOptional: true },
+        { name: "liquidationQueue", isMut: true, isSigner: false, isOptional: true },
+        { name: "liquidationCommitment", isMut: true, isSigner: false, isOptional: true },
+        { name: "protocolMetrics", isMut: true, isSigner: false, isOptional: true },
+        { name: "userStats", isMut: true, isSigner: false, isOptional: true }
+      ],
+      args: [{ name: "liquidityAmount", type: "u64" }, { name: "salt", type: { array: ["u8", 32] } }]
+    },
+    {
+      name: "cancelLiquidationCommitment",
+      accounts: [
+        { name: "liquidationCommitment", isMut: true, isSigner: false },
+        { name: "liquidator", isMut: true, isSigner: true }
+      ],
+      args: []
+    },
+    {
+      name: "startLiquidationAuction",
+      accounts: [
+        { name: "market", isMut: false, isSigner: false },
+        { name: "config", isMut: false, isSigner: false },
+        { name: "obligation", isMut: false, isSigner: false },
+        { name: "repayReserve", isMut: false, isSigner: false },
+        { name: "withdrawReserve", isMut: false, isSigner: false },
+        { name: "auction", isMut: true, isSigner: false },
+        { name: "initiator", isMut: true, isSigner: true },
+        { name: "systemProgram", isMut: false, isSigner: false }
+      ],
+      args: []
+    },
+    {
+      name: "bidLiquidationAuction",
+      accounts: [
+        { name: "market", isMut: false, isSigner: false },
+        { name: "obligation", isMut: true, isSigner: false },
+        { name: "auction", isMut: true, isSigner: false },
+        { name: "repayReserve", isMut: true, isSigner: false },
+        { name: "withdrawReserve", isMut: true, isSigner: false },
+        { name: "repayLiquidityMint", isMut: false, isSigner: false },
+        { name: "withdrawCollateralMint", isMut: false, isSigner: false },
+        { name: "bidder", isMut: false, isSigner: true },
+        { name: "tokenProgram", isMut: false, isSigner: false },
+        { name: "collateralTokenProgram", isMut: false, isSigner: false }
+      ],
+      args: [
+        { name: "liquidityAmount", type: "u64" },
+        { name: "minCollateralAmount", type: "u64" }
+      ]
+    },
+    {
+      name: "refreshObligation",
+      accounts: [
+        { name: "market", isMut: false, isSigner: false },
+        { name: "config", isMut: false, isSigner: false },
+        { name: "obligation", isMut: true, isSigner: false },
+        { name: "liquidationQueue", isMut: true, isSigner: false, isOptional: true }
+      ],
+      args: []
     }
   ],
   accounts: [
@@ -1027,8 +2997,30 @@ export const IDL: AuraLend = {
           { name: "state", type: "ReserveState" },
           { name: "lastUpdateTimestamp", type: "u64" },
           { name: "lastUpdateSlot", type: "u64" },
-          { name: "reentrancyGuard", type: "bool" },
-          { name: "reserved", type: { array: ["u8", 255] } }
+          { name: "feeSplit", type: "FeeSplit" },
+          { name: "assetClass", type: "u8" },
+          { name: "oracleSource", type: "OracleSource" },
+          { name: "fixedPrice", type: "u64" },
+          { name: "secondaryOracle", type: "publicKey" },
+          { name: "secondaryOracleSource", type: "OracleSource" },
+          { name: "maxOracleDeviationBps", type: "u64" },
+          { name: "twapPrice", type: "i64" },
+          { name: "twapExponent", type: "i32" },
+          { name: "twapLastUpdateTimestamp", type: "u64" },
+          { name: "twapWindowSeconds", type: "u64" },
+          { name: "deprecationStartTimestamp", type: "u64" },
+          { name: "deprecationDeadlineTimestamp", type: "u64" },
+          { name: "deleveragePenaltyBps", type: "u16" },
+          { name: "debtCeilingUsd", type: "u64" },
+          { name: "isolationModeTotalDebt", type: "u64" },
+          { name: "surgeUtilizationThresholdBps", type: "u16" },
+          { name: "surgeDurationSlots", type: "u64" },
+          { name: "surgeWithdrawalFeeBps", type: "u16" },
+          { name: "highUtilizationSinceSlot", type: "u64" },
+          { name: "totalSurgeFees", type: "u64" },
+          { name: "emergencyPauseFlags", type: "ReserveConfigFlags" },
+          { name: "derivedOracle", type: "DerivedOracle" },
+          { name: "reserved", type: { array: ["u8", 8] } }
         ]
       }
     },
@@ -1047,7 +3039,16 @@ export const IDL: AuraLend = {
           { name: "lastUpdateTimestamp", type: "u64" },
           { name: "lastUpdateSlot", type: "u64" },
           { name: "liquidationSnapshotHealthFactor", type: { option: "Decimal" } },
-          { name: "reserved", type: { array: ["u8", 112] } }
+          { name: "unhealthySinceSlot", type: "u64" },
+          { name: "emodeCategory", type: "u8" },
+          { name: "isolationMode", type: "bool" },
+          { name: "creditHistory", type: "CreditHistory" },
+          { name: "refreshedSlot", type: "u64" },
+          { name: "liquidationGraceSlots", type: "u64" },
+          { name: "liquidationGraceFloorBps", type: "u16" },
+          { name: "obligationIndex", type: "u8" },
+          { name: "linkedAccounts", type: "u16" },
+          { name: "reserved", type: { array: ["u8", 53] } }
         ]
       }
     }
@@ -1073,10 +3074,67 @@ export const IDL: AuraLend = {
           { name: "liquidityMint", type: "publicKey" },
           { name: "priceOracle", type: "publicKey" },
           { name: "oracleFeedId", type: { array: ["u8", 32] } },
-          { name: "config", type: "ReserveConfig" }
+          { name: "oracleSource", type: "OracleSource" },
+          { name: "fixedPrice", type: "u64" },
+          { name: "config", type: "ReserveConfig" },
+          { name: "initialLiquidity", type: { option: "u64" } }
+        ]
+      }
+    },
+    {
+      name: "OracleSource",
+      type: {
+        kind: "enum",
+        variants: [
+          { name: "Pyth" },
+          { name: "Switchboard" },
+          { name: "FixedPrice" },
+          { name: "Derived" }
+        ]
+      }
+    },
+    {
+      name: "DerivedOracleKind",
+      type: {
+        kind: "enum",
+        variants: [{ name: "None" }, { name: "StakePool" }, { name: "LiquidityPool" }]
+      }
+    },
+    {
+      name: "DerivedOracleComponent",
+      type: {
+        kind: "struct",
+        fields: [
+          { name: "oracle", type: "publicKey" },
+          { name: "source", type: "OracleSource" },
+          { name: "feedId", type: { array: ["u8", 32] } },
+          { name: "vault", type: "publicKey" },
+          { name: "decimals", type: "u8" }
+        ]
+      }
+    },
+    {
+      name: "DerivedOracle",
+      type: {
+        kind: "struct",
+        fields: [
+          { name: "kind", type: "DerivedOracleKind" },
+          { name: "pool", type: "publicKey" },
+          { name: "base", type: "DerivedOracleComponent" },
+          { name: "quote", type: "DerivedOracleComponent" },
+          { name: "price", type: "u64" },
+          { name: "confidence", type: "u64" },
+          { name: "publishTime", type: "i64" }
         ]
       }
     },
+    {
+      name: "InterestRateStrategy",
+      type: {
+        kind: "enum",
+        variants: [{ name: "Kinked" }, { name: "Linear" }, { name: "Curve" }, { name: "Fixed" }]
+      }
+    },
     {
       name: "ReserveConfig",
       type: {
@@ -1092,7 +3150,16 @@ export const IDL: AuraLend = {
           { name: "protocolFeeBps", type: "u64" },
           { name: "maxBorrowRateBps", type: "u64" },
           { name: "decimals", type: "u8" },
-          { name: "flags", type: "ReserveConfigFlags" }
+          { name: "flags", type: "ReserveConfigFlags" },
+          { name: "supplyCap", type: "u64" },
+          { name: "borrowCap", type: "u64" },
+          { name: "borrowFeeBps", type: "u64" },
+          { name: "insuranceFundShareBps", type: "u64" },
+          { name: "interestRateStrategy", type: "InterestRateStrategy" },
+          { name: "maxConfidenceBps", type: "u64" },
+          { name: "maxStalenessSlots", type: "u64" },
+          { name: "liquidationCloseFactorBps", type: "u64" },
+          { name: "liquidationProtocolFeeBps", type: "u64" }
         ]
       }
     },
@@ -1105,13 +3172,23 @@ export const IDL: AuraLend = {
           { name: "totalBorrows", type: "u64" },
           { name: "totalLiquidity", type: "u64" },
           { name: "collateralMintSupply", type: "u64" },
-          { name: "currentBorrowRate", type: "Decimal" },
-          { name: "currentSupplyRate", type: "Decimal" },
-          { name: "currentUtilizationRate", type: "Decimal" },
-          { name: "accumulatedProtocolFees", type: "u64" }
+          { name: "currentBorrowRateBps", type: "u64" },
+          { name: "currentSupplyRateBps", type: "u64" },
+          { name: "currentUtilizationRateBps", type: "u64" },
+          { name: "accumulatedProtocolFees", type: "u64" },
+          { name: "cumulativeBorrowRateWads", type: "Decimal" },
+          { name: "investedLiquidity", type: "u64" },
+          { name: "flags", type: "ReserveStateFlags" }
         ]
       }
     },
+    {
+      name: "ReserveStateFlags",
+      type: {
+        kind: "struct",
+        fields: [{ name: "locked", type: "bool" }]
+      }
+    },
     {
       name: "ObligationCollateral",
       type: {
@@ -1132,7 +3209,11 @@ export const IDL: AuraLend = {
         fields: [
           { name: "borrowReserve", type: "publicKey" },
           { name: "borrowedAmountWads", type: "Decimal" },
-          { name: "marketValueUsd", type: "Decimal" }
+          { name: "marketValueUsd", type: "Decimal" },
+          { name: "originationUtilizationRate", type: "Decimal" },
+          { name: "originationBorrowRate", type: "Decimal" },
+          { name: "originationSlot", type: "u64" },
+          { name: "cumulativeBorrowRateWads", type: "Decimal" }
         ]
       }
     },
@@ -1169,6 +3250,40 @@ export const IDL: AuraLend = {
           { name: "collateralEnabled", type: "bool" }
         ]
       }
+    },
+    {
+      name: "FeeRecipient",
+      type: {
+        kind: "struct",
+        fields: [
+          { name: "receiver", type: "publicKey" },
+          { name: "weightBps", type: "u16" }
+        ]
+      }
+    },
+    {
+      name: "FeeSplit",
+      type: {
+        kind: "struct",
+        fields: [
+          { name: "insurance", type: "FeeRecipient" },
+          { name: "stakers", type: "FeeRecipient" },
+          { name: "grants", type: "FeeRecipient" }
+        ]
+      }
+    },
+    {
+      name: "CreditHistory",
+      type: {
+        kind: "struct",
+        fields: [
+          { name: "maxDebtUsd", type: "u64" },
+          { name: "onTimeRepayments", type: "u32" },
+          { name: "lateRepayments", type: "u32" },
+          { name: "liquidations", type: "u32" },
+          { name: "lastLiquidationSlot", type: "u64" }
+        ]
+      }
     }
   ],
   errors: [
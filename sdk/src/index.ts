@@ -1,7 +1,9 @@
 export * from './client';
 export * from './instructions';
 export * from './state';
-export { InitializeMarketParams, InitializeReserveParams } from './types';
+export * from './batch';
+export * from './receipt';
+export { InitializeMarketParams, InitializeReserveParams, RewardSide } from './types';
 export { AuraLend, IDL } from './idl';
 
 // Main client class
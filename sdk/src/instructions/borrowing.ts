@@ -1,17 +1,66 @@
-import { PublicKey, SystemProgram, Transaction } from '@solana/web3.js';
-import { TOKEN_PROGRAM_ID } from '@solana/spl-token';
+import {
+  AccountMeta,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
 import { AuraLendClient } from '../client';
+import { Obligation, Reserve } from '../state';
 import BN from 'bn.js';
 
 export interface InitObligationParams {
   obligationOwner: PublicKey;
+  /** Which of the owner's obligations to create (defaults to 0, the original) */
+  obligationIndex?: number;
 }
 
 export interface BorrowObligationLiquidityParams {
   obligation: PublicKey;
   borrowReserve: PublicKey;
   amount: BN;
+  integratorId?: number;
   destinationLiquidity: PublicKey;
+  referrer?: PublicKey; // Wallet of the referrer credited with a share of the borrow fee
+  rewardPool?: PublicKey; // Borrow-side reward pool the obligation opted into, synced after the borrow
+}
+
+export interface ObligationRewardsParams {
+  obligation: PublicKey;
+  rewardPool: PublicKey;
+}
+
+export interface ClaimRewardsParams extends ObligationRewardsParams {
+  destinationRewards: PublicKey;
+}
+
+/**
+ * Remaining accounts `refresh_obligation` revalues an obligation from
+ * A (reserve, price oracle) pair for each deposit, then for each borrow. Liquidations
+ * refresh the obligation inline and take the same accounts.
+ */
+export async function obligationRefreshAccounts(
+  client: AuraLendClient,
+  obligation: Obligation
+): Promise<AccountMeta[]> {
+  const positionReserves = [
+    ...obligation.data.deposits.map((deposit) => deposit.depositReserve),
+    ...obligation.data.borrows.map((borrow) => borrow.borrowReserve),
+  ];
+  const reserveInfos = await client.connection.getMultipleAccountsInfo(positionReserves);
+
+  return positionReserves.flatMap((reserveKey, index) => {
+    const reserveInfo = reserveInfos[index];
+    if (!reserveInfo) {
+      throw new Error(`Reserve ${reserveKey.toBase58()} not found`);
+    }
+    const reserve = Reserve.fromAccountInfo(reserveKey, reserveInfo);
+
+    return [
+      { pubkey: reserveKey, isSigner: false, isWritable: false },
+      { pubkey: reserve.data.priceOracle, isSigner: false, isWritable: false },
+    ];
+  });
 }
 
 export class BorrowingInstructions {
@@ -19,10 +68,11 @@ export class BorrowingInstructions {
 
   async initObligation(params: InitObligationParams): Promise<Transaction> {
     const marketPda = this.client.getMarketAddress();
-    const obligationPda = this.client.getObligationAddress(params.obligationOwner);
+    const obligationIndex = params.obligationIndex ?? 0;
+    const obligationPda = this.client.getObligationAddress(params.obligationOwner, obligationIndex);
 
     return this.client.program.methods
-      .initObligation()
+      .initObligation(obligationIndex)
       .accounts({
         market: marketPda,
         obligation: obligationPda,
@@ -33,6 +83,112 @@ export class BorrowingInstructions {
       .transaction();
   }
 
+  /** Close the wallet's obligation once it holds no deposits or borrows, reclaiming its rent */
+  async closeObligation(): Promise<Transaction> {
+    const owner = this.client.wallet.publicKey;
+
+    return this.client.program.methods
+      .closeObligation()
+      .accounts({
+        market: this.client.getMarketAddress(),
+        obligation: this.client.getObligationAddress(owner),
+        obligationOwner: owner,
+      })
+      .transaction();
+  }
+
+  /**
+   * Propose moving the wallet's obligation to a new owner
+   * Nothing moves until the new owner calls `acceptObligationOwnership`.
+   */
+  async transferObligationOwnership(newOwner: PublicKey): Promise<Transaction> {
+    const owner = this.client.wallet.publicKey;
+    const obligation = this.client.getObligationAddress(owner);
+
+    return this.client.program.methods
+      .transferObligationOwnership(newOwner)
+      .accounts({
+        market: this.client.getMarketAddress(),
+        obligation,
+        obligationTransfer: this.client.getObligationTransferAddress(obligation),
+        owner,
+        systemProgram: SystemProgram.programId,
+      })
+      .transaction();
+  }
+
+  /** Cancel the pending transfer of the wallet's obligation */
+  async cancelObligationTransfer(): Promise<Transaction> {
+    const owner = this.client.wallet.publicKey;
+    const obligation = this.client.getObligationAddress(owner);
+
+    return this.client.program.methods
+      .cancelObligationTransfer()
+      .accounts({
+        obligationTransfer: this.client.getObligationTransferAddress(obligation),
+        owner,
+      })
+      .transaction();
+  }
+
+  /**
+   * Accept an obligation transferred to the wallet by `previousOwner`
+   * It lands in the wallet's obligation at `obligationIndex`, which must not exist yet.
+   * Reward participations, credit lines, credit delegations and a standing order on the
+   * obligation must be closed by the previous owner first.
+   */
+  async acceptObligationOwnership(
+    previousOwner: PublicKey,
+    obligationIndex = 0
+  ): Promise<Transaction> {
+    const newOwner = this.client.wallet.publicKey;
+    const obligation = this.client.getObligationAddress(previousOwner);
+
+    return this.client.program.methods
+      .acceptObligationOwnership(obligationIndex)
+      .accounts({
+        market: this.client.getMarketAddress(),
+        obligationTransfer: this.client.getObligationTransferAddress(obligation),
+        obligation,
+        newObligation: this.client.getObligationAddress(newOwner, obligationIndex),
+        liquidationAuction: this.client.getLiquidationAuctionAddress(obligation),
+        standingOrder: this.client.getStandingOrderAddress(obligation),
+        subAccounts: this.client.getSubAccountsAddress(obligation),
+        liquidationQueue: this.client.getLiquidationQueueAddress(),
+        previousOwner,
+        newOwner,
+        systemProgram: SystemProgram.programId,
+      })
+      .transaction();
+  }
+
+  /**
+   * Build a `refresh_obligation` instruction
+   * Borrows and withdrawals against an obligation with debt must be preceded by one in
+   * the same slot, so it is normally placed in the same transaction.
+   */
+  async refreshObligation(obligation: Obligation): Promise<TransactionInstruction> {
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('config')],
+      this.client.programId
+    );
+
+    // Keep the market's liquidation queue current once it exists
+    const liquidationQueue = this.client.getLiquidationQueueAddress();
+    const liquidationQueueInfo = await this.client.connection.getAccountInfo(liquidationQueue);
+
+    return this.client.program.methods
+      .refreshObligation()
+      .accounts({
+        market: this.client.getMarketAddress(),
+        config: configPda,
+        obligation: obligation.address,
+        liquidationQueue: liquidationQueueInfo ? liquidationQueue : null,
+      })
+      .remainingAccounts(await obligationRefreshAccounts(this.client, obligation))
+      .instruction();
+  }
+
   async borrowObligationLiquidity(params: BorrowObligationLiquidityParams): Promise<Transaction> {
     const marketPda = this.client.getMarketAddress();
     const reserve = await this.client.getReserve(params.borrowReserve);
@@ -41,6 +197,12 @@ export class BorrowingInstructions {
       throw new Error('Reserve not found');
     }
 
+    const obligationInfo = await this.client.connection.getAccountInfo(params.obligation);
+    if (!obligationInfo) {
+      throw new Error('Obligation not found');
+    }
+    const obligation = Obligation.fromAccountInfo(params.obligation, obligationInfo);
+
     const liquiditySupplyPda = this.client.getLiquiditySupplyAddress(reserve.data.liquidityMint);
     
     const [liquiditySupplyAuthority] = PublicKey.findProgramAddressSync(
@@ -48,18 +210,109 @@ export class BorrowingInstructions {
       this.client.programId
     );
 
+    const tokenProgram = await this.client.getTokenProgram(reserve.data.liquidityMint);
+
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('config')],
+      this.client.programId
+    );
+
     return this.client.program.methods
-      .borrowObligationLiquidity(params.amount)
+      .borrowObligationLiquidity(params.amount, params.integratorId ?? null)
       .accounts({
         market: marketPda,
         obligation: params.obligation,
         borrowReserve: params.borrowReserve,
+        liquidityMint: reserve.data.liquidityMint,
         liquiditySupply: liquiditySupplyPda,
         liquiditySupplyAuthority,
         destinationLiquidity: params.destinationLiquidity,
+        feeReceiver: reserve.data.feeReceiver,
+        insuranceFund: this.client.getInsuranceFundAddress(params.borrowReserve),
+        insuranceFundVault: this.client.getInsuranceFundVaultAddress(params.borrowReserve),
         obligationOwner: this.client.wallet.publicKey,
         priceOracle: reserve.data.priceOracle,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram,
+        config: configPda,
+        referrer: params.referrer ? this.client.getReferrerAddress(params.referrer) : null,
+        rewardPool: params.rewardPool ?? null,
+        obligationRewards: params.rewardPool
+          ? this.client.getObligationRewardsAddress(params.rewardPool, params.obligation)
+          : null,
+        // Isolated collateral tracks the debt borrowed against it
+        isolatedCollateralReserve: obligation.data.isolationMode
+          ? obligation.data.deposits[0].depositReserve
+          : null,
+      })
+      .preInstructions([await this.refreshObligation(obligation)])
+      .transaction();
+  }
+
+  /** Opt the wallet's obligation into a reward pool; its current position starts earning */
+  async initObligationRewards(params: ObligationRewardsParams): Promise<Transaction> {
+    return this.client.program.methods
+      .initObligationRewards()
+      .accounts({
+        rewardPool: params.rewardPool,
+        obligation: params.obligation,
+        obligationRewards: this.client.getObligationRewardsAddress(params.rewardPool, params.obligation),
+        obligationOwner: this.client.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .transaction();
+  }
+
+  /** Resync an obligation's reward position (permissionless) */
+  async syncObligationRewards(params: ObligationRewardsParams): Promise<Transaction> {
+    return this.client.program.methods
+      .syncObligationRewards()
+      .accounts({
+        rewardPool: params.rewardPool,
+        obligation: params.obligation,
+        obligationRewards: this.client.getObligationRewardsAddress(params.rewardPool, params.obligation),
+      })
+      .transaction();
+  }
+
+  /** Claim the rewards the wallet's obligation earned in a pool */
+  async claimRewards(params: ClaimRewardsParams): Promise<Transaction> {
+    const poolInfo = await this.client.connection.getAccountInfo(params.rewardPool);
+    if (!poolInfo) {
+      throw new Error('Reward pool not found');
+    }
+    // reward_mint follows the discriminator, version, market, reserve and side
+    const rewardMintOffset = 8 + 1 + 32 + 32 + 1;
+    const rewardMint = new PublicKey(
+      poolInfo.data.subarray(rewardMintOffset, rewardMintOffset + 32)
+    );
+
+    return this.client.program.methods
+      .claimRewards()
+      .accounts({
+        rewardPool: params.rewardPool,
+        obligation: params.obligation,
+        obligationRewards: this.client.getObligationRewardsAddress(params.rewardPool, params.obligation),
+        rewardMint,
+        rewardVault: this.client.getRewardVaultAddress(params.rewardPool),
+        destinationRewards: params.destinationRewards,
+        obligationOwner: this.client.wallet.publicKey,
+        tokenProgram: await this.client.getTokenProgram(rewardMint),
+      })
+      .transaction();
+  }
+
+  /**
+   * Opt the wallet's obligation out of a reward pool
+   * Accrued rewards must be claimed first; the rent goes back to the wallet.
+   */
+  async closeObligationRewards(params: ObligationRewardsParams): Promise<Transaction> {
+    return this.client.program.methods
+      .closeObligationRewards()
+      .accounts({
+        rewardPool: params.rewardPool,
+        obligation: params.obligation,
+        obligationRewards: this.client.getObligationRewardsAddress(params.rewardPool, params.obligation),
+        obligationOwner: this.client.wallet.publicKey,
       })
       .transaction();
   }
@@ -1,22 +1,54 @@
-import { PublicKey, Transaction } from '@solana/web3.js';
-import { TOKEN_PROGRAM_ID } from '@solana/spl-token';
+import { PublicKey, SystemProgram, Transaction } from '@solana/web3.js';
 import { AuraLendClient } from '../client';
 import BN from 'bn.js';
 
 export interface DepositLiquidityParams {
   reserve: PublicKey;
   amount: BN;
+  integratorId?: number;
   userTokenAccount: PublicKey;
   userCollateralAccount: PublicKey;
+  referrer?: PublicKey; // Wallet of the referrer credited with the deposit volume
 }
 
 export interface RedeemCollateralParams {
   reserve: PublicKey;
   amount: BN;
+  integratorId?: number;
   userTokenAccount: PublicKey;
   userCollateralAccount: PublicKey;
 }
 
+export interface TransferSupplyPositionParams {
+  reserve: PublicKey;
+  amount: BN; // aTokens moved out of the wallet's obligation
+  obligation: PublicKey; // Wallet's obligation holding the collateral
+  recipientObligation: PublicKey; // Must already hold collateral in the reserve
+  emodeCategory?: PublicKey; // Category of the wallet's obligation, when it opted into one
+  rewardPool?: PublicKey; // Supply-side reward pool both obligations opted into
+}
+
+export interface ClaimReferralFeesParams {
+  reserve: PublicKey;
+  destinationLiquidity: PublicKey;
+}
+
+export interface StakeParams {
+  reserve: PublicKey;
+  amount: BN; // aTokens to lock in the reserve's stake vault
+  userCollateralAccount: PublicKey;
+}
+
+export interface RequestUnstakeParams {
+  reserve: PublicKey;
+  shares: BN;
+}
+
+export interface UnstakeParams {
+  reserve: PublicKey;
+  userCollateralAccount: PublicKey;
+}
+
 export class LendingInstructions {
   constructor(private client: AuraLendClient) {}
 
@@ -41,11 +73,14 @@ export class LendingInstructions {
       this.client.programId
     );
 
+    const tokenProgram = await this.client.getTokenProgram(reserve.data.liquidityMint);
+
     return this.client.program.methods
-      .depositReserveLiquidity(params.amount)
+      .depositReserveLiquidity(params.amount, params.integratorId ?? null)
       .accounts({
         market: marketPda,
         reserve: params.reserve,
+        liquidityMint: reserve.data.liquidityMint,
         destinationLiquidity: liquiditySupplyPda,
         liquiditySupplyAuthority,
         collateralMint: collateralMintPda,
@@ -53,7 +88,8 @@ export class LendingInstructions {
         sourceLiquidity: params.userTokenAccount,
         destinationCollateral: params.userCollateralAccount,
         userTransferAuthority: this.client.wallet.publicKey,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram,
+        referrer: params.referrer ? this.client.getReferrerAddress(params.referrer) : null,
       })
       .transaction();
   }
@@ -79,11 +115,14 @@ export class LendingInstructions {
       this.client.programId
     );
 
+    const tokenProgram = await this.client.getTokenProgram(reserve.data.liquidityMint);
+
     return this.client.program.methods
-      .redeemReserveCollateral(params.amount)
+      .redeemReserveCollateral(params.amount, params.integratorId ?? null)
       .accounts({
         market: marketPda,
         reserve: params.reserve,
+        liquidityMint: reserve.data.liquidityMint,
         sourceLiquidity: liquiditySupplyPda,
         liquiditySupplyAuthority,
         collateralMint: collateralMintPda,
@@ -91,7 +130,211 @@ export class LendingInstructions {
         destinationCollateral: params.userTokenAccount,
         sourceCollateral: params.userCollateralAccount,
         userTransferAuthority: this.client.wallet.publicKey,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram,
+      })
+      .transaction();
+  }
+
+  /**
+   * Move collateral from the wallet's obligation into another obligation
+   * An obligation with borrows must be refreshed in the same transaction first.
+   */
+  async transferSupplyPosition(params: TransferSupplyPositionParams): Promise<Transaction> {
+    const marketPda = this.client.getMarketAddress();
+    const reserve = await this.client.getReserve(params.reserve);
+
+    if (!reserve) {
+      throw new Error('Reserve not found');
+    }
+
+    const owner = this.client.wallet.publicKey;
+    const [flashLoanGuard] = PublicKey.findProgramAddressSync(
+      [Buffer.from('flash_loan_guard'), owner.toBuffer()],
+      this.client.programId
+    );
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('config')],
+      this.client.programId
+    );
+
+    return this.client.program.methods
+      .transferSupplyPosition(params.amount)
+      .accounts({
+        market: marketPda,
+        reserve: params.reserve,
+        priceOracle: reserve.data.priceOracle,
+        sourceObligation: params.obligation,
+        destinationObligation: params.recipientObligation,
+        emodeCategory: params.emodeCategory ?? null,
+        owner,
+        flashLoanGuard,
+        config: configPda,
+        rewardPool: params.rewardPool ?? null,
+        sourceObligationRewards: params.rewardPool
+          ? this.client.getObligationRewardsAddress(params.rewardPool, params.obligation)
+          : null,
+        destinationObligationRewards: params.rewardPool
+          ? this.client.getObligationRewardsAddress(params.rewardPool, params.recipientObligation)
+          : null,
+      })
+      .transaction();
+  }
+
+  /** Register the wallet as a referrer of the market */
+  async registerReferrer(): Promise<Transaction> {
+    const owner = this.client.wallet.publicKey;
+
+    return this.client.program.methods
+      .registerReferrer()
+      .accounts({
+        market: this.client.getMarketAddress(),
+        referrer: this.client.getReferrerAddress(owner),
+        owner,
+        systemProgram: SystemProgram.programId,
+      })
+      .transaction();
+  }
+
+  /** Claim the referral fees the wallet accrued in a reserve */
+  async claimReferralFees(params: ClaimReferralFeesParams): Promise<Transaction> {
+    const owner = this.client.wallet.publicKey;
+    const reserve = await this.client.getReserve(params.reserve);
+
+    if (!reserve) {
+      throw new Error('Reserve not found');
+    }
+
+    const [liquiditySupplyAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from('liquidity'), reserve.data.liquidityMint.toBuffer(), Buffer.from('authority')],
+      this.client.programId
+    );
+
+    const tokenProgram = await this.client.getTokenProgram(reserve.data.liquidityMint);
+
+    return this.client.program.methods
+      .claimReferralFees()
+      .accounts({
+        market: this.client.getMarketAddress(),
+        reserve: params.reserve,
+        referrer: this.client.getReferrerAddress(owner),
+        liquidityMint: reserve.data.liquidityMint,
+        sourceLiquidity: this.client.getLiquiditySupplyAddress(reserve.data.liquidityMint),
+        destinationLiquidity: params.destinationLiquidity,
+        liquiditySupplyAuthority,
+        owner,
+        tokenProgram,
+      })
+      .transaction();
+  }
+
+  /** Open the wallet's position in a reserve's stake vault */
+  async openStakePosition(reserve: PublicKey): Promise<Transaction> {
+    const owner = this.client.wallet.publicKey;
+    const stakeVault = this.client.getStakeVaultAddress(reserve);
+
+    return this.client.program.methods
+      .openStakePosition()
+      .accounts({
+        stakeVault,
+        stakePosition: this.client.getStakePositionAddress(stakeVault, owner),
+        owner,
+        systemProgram: SystemProgram.programId,
+      })
+      .transaction();
+  }
+
+  /** Lock aTokens in a reserve's stake vault for shares */
+  async stake(params: StakeParams): Promise<Transaction> {
+    const owner = this.client.wallet.publicKey;
+    const reserve = await this.client.getReserve(params.reserve);
+
+    if (!reserve) {
+      throw new Error('Reserve not found');
+    }
+
+    const stakeVault = this.client.getStakeVaultAddress(params.reserve);
+
+    return this.client.program.methods
+      .stake(params.amount)
+      .accounts({
+        market: this.client.getMarketAddress(),
+        stakeVault,
+        stakePosition: this.client.getStakePositionAddress(stakeVault, owner),
+        collateralMint: reserve.data.collateralMint,
+        vaultCollateral: this.client.getStakeVaultCollateralAddress(stakeVault),
+        sourceCollateral: params.userCollateralAccount,
+        owner,
+        tokenProgram: await this.client.getTokenProgram(reserve.data.collateralMint),
+      })
+      .transaction();
+  }
+
+  /** Start the cooldown for unstaking shares */
+  async requestUnstake(params: RequestUnstakeParams): Promise<Transaction> {
+    const owner = this.client.wallet.publicKey;
+    const stakeVault = this.client.getStakeVaultAddress(params.reserve);
+
+    return this.client.program.methods
+      .requestUnstake(params.shares)
+      .accounts({
+        stakeVault,
+        stakePosition: this.client.getStakePositionAddress(stakeVault, owner),
+        owner,
+      })
+      .transaction();
+  }
+
+  /** Redeem the shares whose unstake cooldown has passed for their aTokens */
+  async unstake(params: UnstakeParams): Promise<Transaction> {
+    const owner = this.client.wallet.publicKey;
+    const reserve = await this.client.getReserve(params.reserve);
+
+    if (!reserve) {
+      throw new Error('Reserve not found');
+    }
+
+    const stakeVault = this.client.getStakeVaultAddress(params.reserve);
+
+    return this.client.program.methods
+      .unstake()
+      .accounts({
+        market: this.client.getMarketAddress(),
+        stakeVault,
+        stakePosition: this.client.getStakePositionAddress(stakeVault, owner),
+        collateralMint: reserve.data.collateralMint,
+        vaultCollateral: this.client.getStakeVaultCollateralAddress(stakeVault),
+        destinationCollateral: params.userCollateralAccount,
+        owner,
+        tokenProgram: await this.client.getTokenProgram(reserve.data.collateralMint),
+      })
+      .transaction();
+  }
+
+  /** Deposit the liquidity collected for a stake vault and add the aTokens to it */
+  async compoundStakeVault(reserveAddress: PublicKey): Promise<Transaction> {
+    const reserve = await this.client.getReserve(reserveAddress);
+
+    if (!reserve) {
+      throw new Error('Reserve not found');
+    }
+
+    const stakeVault = this.client.getStakeVaultAddress(reserveAddress);
+
+    return this.client.program.methods
+      .compoundStakeVault()
+      .accounts({
+        market: this.client.getMarketAddress(),
+        reserve: reserveAddress,
+        stakeVault,
+        liquidityMint: reserve.data.liquidityMint,
+        compoundLiquidity: this.client.getStakeVaultCompoundAddress(stakeVault),
+        destinationLiquidity: reserve.data.liquiditySupply,
+        collateralMint: reserve.data.collateralMint,
+        collateralMintAuthority: this.client.getCollateralMintAuthorityAddress(
+          reserve.data.liquidityMint
+        ),
+        vaultCollateral: this.client.getStakeVaultCollateralAddress(stakeVault),
+        tokenProgram: await this.client.getTokenProgram(reserve.data.liquidityMint),
       })
       .transaction();
   }
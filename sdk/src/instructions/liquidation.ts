@@ -1,6 +1,8 @@
 import { PublicKey, Transaction } from '@solana/web3.js';
-import { TOKEN_PROGRAM_ID } from '@solana/spl-token';
+import { createHash } from 'crypto';
 import { AuraLendClient } from '../client';
+import { Obligation, Reserve } from '../state';
+import { obligationRefreshAccounts } from './borrowing';
 import BN from 'bn.js';
 
 export interface LiquidateObligationParams {
@@ -8,24 +10,275 @@ export interface LiquidateObligationParams {
   repayReserve: PublicKey;
   withdrawReserve: PublicKey;
   amount: BN;
+  integratorId?: number;
+}
+
+export interface CommitLiquidationParams {
+  obligation: PublicKey;
+  repayReserve: PublicKey;
+  withdrawReserve: PublicKey;
+  amount: BN;
+  /** Secret revealed by `executeLiquidation`; keep it until then */
+  salt: Buffer;
+}
+
+export interface ExecuteLiquidationParams extends LiquidateObligationParams {
+  salt: Buffer;
+}
+
+/**
+ * Hash a liquidator commits to for liquidating `amount` of an obligation
+ */
+export function liquidationCommitmentHash(
+  obligation: PublicKey,
+  liquidator: PublicKey,
+  amount: BN,
+  salt: Buffer
+): Buffer {
+  return createHash('sha256')
+    .update(obligation.toBuffer())
+    .update(liquidator.toBuffer())
+    .update(amount.toArrayLike(Buffer, 'le', 8))
+    .update(salt)
+    .digest();
+}
+
+/** A reserve's secondary oracle, or null when it has none */
+function secondaryOracle(reserve: Reserve): PublicKey | null {
+  return reserve.data.secondaryOracle.equals(PublicKey.default) ? null : reserve.data.secondaryOracle;
+}
+
+export interface StartLiquidationAuctionParams {
+  obligation: PublicKey;
+  repayReserve: PublicKey;
+  withdrawReserve: PublicKey;
+}
+
+export interface BidLiquidationAuctionParams {
+  obligation: PublicKey;
+  amount: BN;
+  minCollateralAmount: BN;
 }
 
 export class LiquidationInstructions {
   constructor(private client: AuraLendClient) {}
 
   async liquidateObligation(params: LiquidateObligationParams): Promise<Transaction> {
-    const marketPda = this.client.getMarketAddress();
+    return this.buildLiquidation(params, null);
+  }
+
+  /**
+   * Commit to a liquidation, pinning the current liquidation prices. Reveal it with
+   * `executeLiquidation` a few slots later, before the commitment expires.
+   */
+  async commitLiquidation(params: CommitLiquidationParams): Promise<Transaction> {
+    const liquidator = this.client.wallet.publicKey;
+
+    const [repayReserveInfo, withdrawReserveInfo] = await Promise.all([
+      this.client.connection.getAccountInfo(params.repayReserve),
+      this.client.connection.getAccountInfo(params.withdrawReserve),
+    ]);
+    if (!repayReserveInfo || !withdrawReserveInfo) {
+      throw new Error('Reserve not found');
+    }
+    const repayReserve = Reserve.fromAccountInfo(params.repayReserve, repayReserveInfo);
+    const withdrawReserve = Reserve.fromAccountInfo(params.withdrawReserve, withdrawReserveInfo);
+
+    const commitmentHash = liquidationCommitmentHash(
+      params.obligation,
+      liquidator,
+      params.amount,
+      params.salt
+    );
 
     return this.client.program.methods
-      .liquidateObligation(params.amount)
+      .commitLiquidation(Array.from(commitmentHash))
+      .accounts({
+        market: this.client.getMarketAddress(),
+        obligation: params.obligation,
+        repayReserve: params.repayReserve,
+        withdrawReserve: params.withdrawReserve,
+        repayPriceOracle: repayReserve.data.priceOracle,
+        withdrawPriceOracle: withdrawReserve.data.priceOracle,
+        repaySecondaryPriceOracle: secondaryOracle(repayReserve),
+        withdrawSecondaryPriceOracle: secondaryOracle(withdrawReserve),
+        liquidationCommitment: this.client.getLiquidationCommitmentAddress(
+          params.obligation,
+          liquidator
+        ),
+        liquidator,
+      })
+      .transaction();
+  }
+
+  /** Liquidate at the prices pinned by `commitLiquidation`, revealing its amount and salt */
+  async executeLiquidation(params: ExecuteLiquidationParams): Promise<Transaction> {
+    return this.buildLiquidation(params, params.salt);
+  }
+
+  /** Close an unrevealed liquidation commitment, refunding its rent */
+  async cancelLiquidationCommitment(obligation: PublicKey): Promise<Transaction> {
+    const liquidator = this.client.wallet.publicKey;
+    return this.client.program.methods
+      .cancelLiquidationCommitment()
+      .accounts({
+        liquidationCommitment: this.client.getLiquidationCommitmentAddress(obligation, liquidator),
+        liquidator,
+      })
+      .transaction();
+  }
+
+  private async buildLiquidation(
+    params: LiquidateObligationParams,
+    salt: Buffer | null
+  ): Promise<Transaction> {
+    const marketPda = this.client.getMarketAddress();
+
+    const obligationInfo = await this.client.connection.getAccountInfo(params.obligation);
+    if (!obligationInfo) {
+      throw new Error('Obligation not found');
+    }
+    const obligation = Obligation.fromAccountInfo(params.obligation, obligationInfo);
+
+    const [repayReserveInfo, withdrawReserveInfo] = await Promise.all([
+      this.client.connection.getAccountInfo(params.repayReserve),
+      this.client.connection.getAccountInfo(params.withdrawReserve),
+    ]);
+    if (!repayReserveInfo || !withdrawReserveInfo) {
+      throw new Error('Reserve not found');
+    }
+    const repayReserve = Reserve.fromAccountInfo(params.repayReserve, repayReserveInfo);
+    const withdrawReserve = Reserve.fromAccountInfo(params.withdrawReserve, withdrawReserveInfo);
+
+    // Each reserve's liquidity and aTokens live under its liquidity mint's token program
+    const [tokenProgram, collateralTokenProgram] = await Promise.all([
+      this.client.getTokenProgram(repayReserve.data.liquidityMint),
+      this.client.getTokenProgram(withdrawReserve.data.liquidityMint),
+    ]);
+
+    // Registered keepers pass their registration to liquidate within the priority window
+    const keeper = this.client.getKeeperAddress(this.client.wallet.publicKey);
+    const liquidationQueue = this.client.getLiquidationQueueAddress();
+    const [keeperInfo, liquidationQueueInfo] = await Promise.all([
+      this.client.connection.getAccountInfo(keeper),
+      this.client.connection.getAccountInfo(liquidationQueue),
+    ]);
+
+    const methods = salt
+      ? this.client.program.methods.executeLiquidation(
+          params.amount,
+          Array.from(salt),
+          params.integratorId ?? null
+        )
+      : this.client.program.methods.liquidateObligation(params.amount, params.integratorId ?? null);
+
+    return methods
       .accounts({
         market: marketPda,
         obligation: params.obligation,
         repayReserve: params.repayReserve,
         withdrawReserve: params.withdrawReserve,
+        repayLiquidityMint: repayReserve.data.liquidityMint,
+        withdrawCollateralMint: withdrawReserve.data.collateralMint,
         liquidator: this.client.wallet.publicKey,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram,
+        collateralTokenProgram,
+        keeperRegistry: this.client.getKeeperRegistryAddress(),
+        keeper: keeperInfo ? keeper : null,
+        liquidationQueue: liquidationQueueInfo ? liquidationQueue : null,
+        liquidationCommitment: salt
+          ? this.client.getLiquidationCommitmentAddress(params.obligation, this.client.wallet.publicKey)
+          : null,
+        // Redeem the insurance fund's share of the bonus, when the reserve charges one
+        withdrawLiquiditySupplyAuthority: this.client.getLiquiditySupplyAuthorityAddress(
+          withdrawReserve.data.liquidityMint
+        ),
+        ...(withdrawReserve.data.config.liquidationProtocolFeeBps > 0n
+          ? {
+              withdrawLiquidityMint: withdrawReserve.data.liquidityMint,
+              withdrawReserveLiquiditySupply: withdrawReserve.data.liquiditySupply,
+              withdrawInsuranceFund: this.client.getInsuranceFundAddress(params.withdrawReserve),
+              withdrawInsuranceFundVault: this.client.getInsuranceFundVaultAddress(
+                params.withdrawReserve
+              ),
+            }
+          : {
+              withdrawLiquidityMint: null,
+              withdrawReserveLiquiditySupply: null,
+              withdrawInsuranceFund: null,
+              withdrawInsuranceFundVault: null,
+            }),
+      })
+      .remainingAccounts(await obligationRefreshAccounts(this.client, obligation))
+      .transaction();
+  }
+  async startLiquidationAuction(params: StartLiquidationAuctionParams): Promise<Transaction> {
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('config')],
+      this.client.programId
+    );
+
+    return this.client.program.methods
+      .startLiquidationAuction()
+      .accounts({
+        market: this.client.getMarketAddress(),
+        config: configPda,
+        obligation: params.obligation,
+        repayReserve: params.repayReserve,
+        withdrawReserve: params.withdrawReserve,
+        auction: this.client.getLiquidationAuctionAddress(params.obligation),
+        initiator: this.client.wallet.publicKey,
+      })
+      .transaction();
+  }
+
+  /** Bid on a running auction; the auction fixes the repay and withdraw reserves */
+  async bidLiquidationAuction(params: BidLiquidationAuctionParams): Promise<Transaction> {
+    const auction = this.client.getLiquidationAuctionAddress(params.obligation);
+    const auctionInfo = await this.client.connection.getAccountInfo(auction);
+    if (!auctionInfo) {
+      throw new Error('Liquidation auction not found');
+    }
+
+    const obligationInfo = await this.client.connection.getAccountInfo(params.obligation);
+    if (!obligationInfo) {
+      throw new Error('Obligation not found');
+    }
+    const obligation = Obligation.fromAccountInfo(params.obligation, obligationInfo);
+
+    // Discriminator, version, market and obligation precede the auction's reserves
+    const repayReserveAddress = new PublicKey(auctionInfo.data.subarray(73, 105));
+    const withdrawReserveAddress = new PublicKey(auctionInfo.data.subarray(105, 137));
+    const [repayReserveInfo, withdrawReserveInfo] = await Promise.all([
+      this.client.connection.getAccountInfo(repayReserveAddress),
+      this.client.connection.getAccountInfo(withdrawReserveAddress),
+    ]);
+    if (!repayReserveInfo || !withdrawReserveInfo) {
+      throw new Error('Reserve not found');
+    }
+    const repayReserve = Reserve.fromAccountInfo(repayReserveAddress, repayReserveInfo);
+    const withdrawReserve = Reserve.fromAccountInfo(withdrawReserveAddress, withdrawReserveInfo);
+
+    const [tokenProgram, collateralTokenProgram] = await Promise.all([
+      this.client.getTokenProgram(repayReserve.data.liquidityMint),
+      this.client.getTokenProgram(withdrawReserve.data.liquidityMint),
+    ]);
+
+    return this.client.program.methods
+      .bidLiquidationAuction(params.amount, params.minCollateralAmount)
+      .accounts({
+        market: this.client.getMarketAddress(),
+        obligation: params.obligation,
+        auction,
+        repayReserve: repayReserveAddress,
+        withdrawReserve: withdrawReserveAddress,
+        repayLiquidityMint: repayReserve.data.liquidityMint,
+        withdrawCollateralMint: withdrawReserve.data.collateralMint,
+        bidder: this.client.wallet.publicKey,
+        tokenProgram,
+        collateralTokenProgram,
       })
+      .remainingAccounts(await obligationRefreshAccounts(this.client, obligation))
       .transaction();
   }
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
-import { PublicKey, SystemProgram, Transaction } from '@solana/web3.js';
-import { TOKEN_PROGRAM_ID } from '@solana/spl-token';
+import { Keypair, PublicKey, SystemProgram, Transaction } from '@solana/web3.js';
+import { getAssociatedTokenAddressSync } from '@solana/spl-token';
 import { AuraLendClient } from '../client';
 import { InitializeMarketParams, InitializeReserveParams } from '../types';
 
@@ -32,7 +32,15 @@ export class MarketInstructions {
       .transaction();
   }
 
-  async initializeReserve(params: InitializeReserveParams): Promise<Transaction> {
+  /**
+   * Initialize a reserve, optionally seeding it with `params.initialLiquidity`
+   * The seed is taken from the wallet's associated token account and its aTokens are
+   * minted to `treasuryCollateral`, a new token account that must sign the transaction.
+   */
+  async initializeReserve(
+    params: InitializeReserveParams,
+    treasuryCollateral: Keypair
+  ): Promise<Transaction> {
     const marketPda = this.client.getMarketAddress();
     const reservePda = this.client.getReserveAddress(params.liquidityMint);
     const collateralMintPda = this.client.getCollateralMintAddress(params.liquidityMint);
@@ -49,6 +57,7 @@ export class MarketInstructions {
     );
 
     // Create fee receiver ATA
+    const tokenProgram = await this.client.getTokenProgram(params.liquidityMint);
     const feeReceiver = await this.client.createAssociatedTokenAccount(
       params.liquidityMint,
       this.client.wallet.publicKey
@@ -65,10 +74,20 @@ export class MarketInstructions {
         liquiditySupply: liquiditySupplyPda,
         liquiditySupplyAuthority,
         feeReceiver,
+        treasuryCollateral: treasuryCollateral.publicKey,
+        payerLiquidity: params.initialLiquidity
+          ? getAssociatedTokenAddressSync(
+              params.liquidityMint,
+              this.client.wallet.publicKey,
+              false,
+              tokenProgram
+            )
+          : null,
         owner: this.client.wallet.publicKey,
+        adminAuditLog: this.client.getAdminAuditLogAddress(),
         payer: this.client.wallet.publicKey,
         systemProgram: SystemProgram.programId,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram,
         rent: PublicKey.default, // SYSVAR_RENT_PUBKEY
       })
       .transaction();
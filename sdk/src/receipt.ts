@@ -0,0 +1,55 @@
+import { PublicKey } from '@solana/web3.js';
+
+/** Health factor reported for obligations without debt (u64::MAX) */
+export const NO_DEBT_HEALTH_FACTOR_BPS = 18_446_744_073_709_551_615n;
+
+/** Post-state summary returned by deposit, redeem, borrow, repay, withdraw and liquidate */
+export interface OperationReceipt {
+  /** Reserve whose liquidity moved (the repay reserve for liquidations) */
+  reserve: PublicKey;
+  /** Liquidity moved after rounding and capping; the underlying equivalent for collateral moves */
+  liquidityAmount: bigint;
+  /** Collateral tokens minted, burned, moved or seized */
+  collateralAmount: bigint;
+  /** Fees charged; for liquidations the bonus, in collateral tokens */
+  feeAmount: bigint;
+  /** Obligation health factor afterwards in bps, or null if no obligation was touched */
+  healthFactorBps: bigint | null;
+  /** Utilization of the reserve afterwards in bps */
+  utilizationRateBps: bigint;
+}
+
+/**
+ * Decode the receipt an operation leaves in its return data
+ * Accepts the raw bytes or the base64 string returned by `simulateTransaction`.
+ */
+export function decodeOperationReceipt(returnData: Buffer | string): OperationReceipt {
+  const data = typeof returnData === 'string' ? Buffer.from(returnData, 'base64') : returnData;
+  let offset = 0;
+
+  const reserve = new PublicKey(data.subarray(offset, offset + 32));
+  offset += 32;
+
+  const liquidityAmount = data.readBigUInt64LE(offset);
+  const collateralAmount = data.readBigUInt64LE(offset + 8);
+  const feeAmount = data.readBigUInt64LE(offset + 16);
+  offset += 24;
+
+  let healthFactorBps: bigint | null = null;
+  if (data.readUInt8(offset) === 1) {
+    healthFactorBps = data.readBigUInt64LE(offset + 1);
+    offset += 8;
+  }
+  offset += 1;
+
+  const utilizationRateBps = data.readBigUInt64LE(offset);
+
+  return {
+    reserve,
+    liquidityAmount,
+    collateralAmount,
+    feeAmount,
+    healthFactorBps,
+    utilizationRateBps,
+  };
+}
@@ -1,6 +1,20 @@
 export { Market, MarketData, MarketFlags } from './market';
 export { Reserve, ReserveData, ReserveConfig, ReserveConfigFlags, ReserveState } from './reserve';
-export { Obligation, ObligationData, ObligationCollateral, ObligationLiquidity } from './obligation';
+export {
+  Obligation,
+  ObligationData,
+  ObligationCollateral,
+  ObligationLiquidity,
+  CreditHistory
+} from './obligation';
+export { MarketLookupTable, MarketLookupTableData } from './lookupTable';
+export {
+  LiquidationQueue,
+  LiquidationQueueData,
+  LiquidationQueueEntry
+} from './liquidationQueue';
+export { ReserveRateIndex, ReserveRateIndexData, EpochRate } from './rateIndex';
+export { UserStats, UserStatsData, TrackedBorrow } from './userStats';
 
 // Export shared types
 export type { Decimal } from './reserve';
\ No newline at end of file
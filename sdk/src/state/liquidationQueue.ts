@@ -0,0 +1,74 @@
+import { AccountInfo, PublicKey } from '@solana/web3.js';
+
+export interface LiquidationQueueEntry {
+  obligation: PublicKey;
+  /** Health factor at the last refresh, in basis points */
+  healthFactorBps: bigint;
+  unhealthySinceSlot: bigint;
+  refreshedSlot: bigint;
+}
+
+export interface LiquidationQueueData {
+  version: number;
+  market: PublicKey;
+  entries: LiquidationQueueEntry[];
+  totalEnqueued: bigint;
+}
+
+export class LiquidationQueue {
+  static readonly ENTRY_SIZE = 56;
+  /** Slots an entry stays listed without a refresh */
+  static readonly ENTRY_TTL_SLOTS = 1_500n;
+
+  constructor(
+    public address: PublicKey,
+    public data: LiquidationQueueData
+  ) {}
+
+  static fromAccountInfo(address: PublicKey, accountInfo: AccountInfo<Buffer>): LiquidationQueue {
+    if (!accountInfo.data) {
+      throw new Error('Invalid liquidation queue account data');
+    }
+
+    // Skip the 8-byte account discriminator
+    let offset = 8;
+    const data = accountInfo.data;
+
+    const version = data.readUInt8(offset);
+    offset += 1;
+
+    const market = new PublicKey(data.subarray(offset, offset + 32));
+    offset += 32;
+
+    const len = data.readUInt32LE(offset);
+    offset += 4;
+
+    const entries: LiquidationQueueEntry[] = [];
+    for (let i = 0; i < len; i++) {
+      const start = offset + i * LiquidationQueue.ENTRY_SIZE;
+      entries.push({
+        obligation: new PublicKey(data.subarray(start, start + 32)),
+        healthFactorBps: data.readBigUInt64LE(start + 32),
+        unhealthySinceSlot: data.readBigUInt64LE(start + 40),
+        refreshedSlot: data.readBigUInt64LE(start + 48),
+      });
+    }
+    offset += len * LiquidationQueue.ENTRY_SIZE;
+
+    return new LiquidationQueue(address, {
+      version,
+      market,
+      entries,
+      totalEnqueued: data.readBigUInt64LE(offset),
+    });
+  }
+
+  /**
+   * Entries that have not expired at `slot`, least healthy first
+   */
+  liveEntries(slot: bigint): LiquidationQueueEntry[] {
+    return this.data.entries
+      .filter((entry) => slot - entry.refreshedSlot <= LiquidationQueue.ENTRY_TTL_SLOTS)
+      .sort((a, b) => Number(a.healthFactorBps - b.healthFactorBps));
+  }
+}
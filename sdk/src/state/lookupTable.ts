@@ -0,0 +1,52 @@
+import { AccountInfo, PublicKey } from '@solana/web3.js';
+
+export interface MarketLookupTableData {
+  version: number;
+  market: PublicKey;
+  lookupTable: PublicKey;
+  coveredReserves: bigint;
+  revision: bigint;
+  lastUpdateSlot: bigint;
+}
+
+export class MarketLookupTable {
+  constructor(
+    public address: PublicKey,
+    public data: MarketLookupTableData
+  ) {}
+
+  static fromAccountInfo(address: PublicKey, accountInfo: AccountInfo<Buffer>): MarketLookupTable {
+    if (!accountInfo.data) {
+      throw new Error('Invalid lookup table record data');
+    }
+
+    // Skip the 8-byte account discriminator
+    let offset = 8;
+    const data = accountInfo.data;
+
+    const version = data.readUInt8(offset);
+    offset += 1;
+
+    const market = new PublicKey(data.subarray(offset, offset + 32));
+    offset += 32;
+
+    const lookupTable = new PublicKey(data.subarray(offset, offset + 32));
+    offset += 32;
+
+    return new MarketLookupTable(address, {
+      version,
+      market,
+      lookupTable,
+      coveredReserves: data.readBigUInt64LE(offset),
+      revision: data.readBigUInt64LE(offset + 8),
+      lastUpdateSlot: data.readBigUInt64LE(offset + 16),
+    });
+  }
+
+  /**
+   * Whether the table still covers every reserve of the market
+   */
+  coversAllReserves(reservesCount: bigint): boolean {
+    return this.data.coveredReserves >= reservesCount;
+  }
+}
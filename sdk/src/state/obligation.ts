@@ -16,6 +16,20 @@ export interface ObligationLiquidity {
   borrowReserve: PublicKey;
   borrowedAmountWads: Decimal;
   marketValueUsd: Decimal;
+  originationUtilizationRate: Decimal;
+  originationBorrowRate: Decimal;
+  originationSlot: bigint;
+  /** Reserve cumulative borrow rate the borrowed amount was last accrued to */
+  cumulativeBorrowRateWads: Decimal;
+}
+
+export interface CreditHistory {
+  /** Highest debt the obligation has carried, in whole USD */
+  maxDebtUsd: bigint;
+  onTimeRepayments: number;
+  lateRepayments: number;
+  liquidations: number;
+  lastLiquidationSlot: bigint;
 }
 
 export interface ObligationData {
@@ -29,6 +43,20 @@ export interface ObligationData {
   lastUpdateTimestamp: bigint;
   lastUpdateSlot: bigint;
   liquidationSnapshotHealthFactor?: Decimal;
+  unhealthySinceSlot: bigint;
+  emodeCategory: number;
+  isolationMode: boolean;
+  creditHistory: CreditHistory;
+  /** Slot of the last full revaluation by `refresh_obligation` (0 if never refreshed) */
+  refreshedSlot: bigint;
+  /** Slots an unhealthy obligation is shielded from liquidation (0 = no grace period) */
+  liquidationGraceSlots: bigint;
+  /** Health factor (bps) below which the grace period no longer applies */
+  liquidationGraceFloorBps: number;
+  /** Index of this obligation among its owner's obligations (0 = the original) */
+  obligationIndex: number;
+  /** Open reward participations, credit lines and credit delegations keyed by this obligation */
+  linkedAccounts: number;
 }
 
 export class Obligation {
@@ -99,10 +127,26 @@ export class Obligation {
       const marketValueUsd = { value: data.readBigUInt64LE(offset) };
       offset += 16;
 
+      const originationUtilizationRate = { value: data.readBigUInt64LE(offset) };
+      offset += 16;
+
+      const originationBorrowRate = { value: data.readBigUInt64LE(offset) };
+      offset += 16;
+
+      const originationSlot = data.readBigUInt64LE(offset);
+      offset += 8;
+
+      const cumulativeBorrowRateWads = { value: data.readBigUInt64LE(offset) };
+      offset += 16;
+
       borrows.push({
         borrowReserve,
         borrowedAmountWads,
         marketValueUsd,
+        originationUtilizationRate,
+        originationBorrowRate,
+        originationSlot,
+        cumulativeBorrowRateWads,
       });
     }
 
@@ -124,8 +168,42 @@ export class Obligation {
     let liquidationSnapshotHealthFactor: Decimal | undefined;
     if (hasLiquidationSnapshot) {
       liquidationSnapshotHealthFactor = { value: data.readBigUInt64LE(offset) };
+      offset += 16;
     }
 
+    const unhealthySinceSlot = data.readBigUInt64LE(offset);
+    offset += 8;
+
+    const emodeCategory = data.readUInt8(offset);
+    offset += 1;
+
+    const isolationMode = data.readUInt8(offset) === 1;
+    offset += 1;
+
+    const creditHistory: CreditHistory = {
+      maxDebtUsd: data.readBigUInt64LE(offset),
+      onTimeRepayments: data.readUInt32LE(offset + 8),
+      lateRepayments: data.readUInt32LE(offset + 12),
+      liquidations: data.readUInt32LE(offset + 16),
+      lastLiquidationSlot: data.readBigUInt64LE(offset + 20),
+    };
+    offset += 28;
+
+    const refreshedSlot = data.readBigUInt64LE(offset);
+    offset += 8;
+
+    const liquidationGraceSlots = data.readBigUInt64LE(offset);
+    offset += 8;
+
+    const liquidationGraceFloorBps = data.readUInt16LE(offset);
+    offset += 2;
+
+    const obligationIndex = data.readUInt8(offset);
+    offset += 1;
+
+    const linkedAccounts = data.readUInt16LE(offset);
+    offset += 2;
+
     return new Obligation(address, {
       version,
       market,
@@ -137,6 +215,15 @@ export class Obligation {
       lastUpdateTimestamp,
       lastUpdateSlot,
       liquidationSnapshotHealthFactor,
+      unhealthySinceSlot,
+      emodeCategory,
+      isolationMode,
+      creditHistory,
+      refreshedSlot,
+      liquidationGraceSlots,
+      liquidationGraceFloorBps,
+      obligationIndex,
+      linkedAccounts,
     });
   }
 
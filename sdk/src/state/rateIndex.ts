@@ -0,0 +1,86 @@
+import { AccountInfo, PublicKey } from '@solana/web3.js';
+
+export interface EpochRate {
+  epoch: bigint;
+  /** Time-weighted average annual borrow rate, scaled by 1e18 */
+  averageBorrowRate: bigint;
+  samples: number;
+}
+
+export interface ReserveRateIndexData {
+  version: number;
+  reserve: PublicKey;
+  currentEpoch: bigint;
+  epochStartSlot: bigint;
+  lastUpdateSlot: bigint;
+  samples: number;
+  /** Finalized epochs, oldest first */
+  history: EpochRate[];
+}
+
+export class ReserveRateIndex {
+  static readonly MAX_EPOCHS = 64;
+  static readonly EPOCH_RATE_SIZE = 20;
+
+  constructor(
+    public address: PublicKey,
+    public data: ReserveRateIndexData
+  ) {}
+
+  static fromAccountInfo(address: PublicKey, accountInfo: AccountInfo<Buffer>): ReserveRateIndex {
+    if (!accountInfo.data) {
+      throw new Error('Invalid rate index account data');
+    }
+
+    // Skip the 8-byte account discriminator
+    let offset = 8;
+    const data = accountInfo.data;
+
+    const version = data.readUInt8(offset);
+    offset += 1;
+
+    const reserve = new PublicKey(data.subarray(offset, offset + 32));
+    offset += 32;
+
+    const currentEpoch = data.readBigUInt64LE(offset);
+    const epochStartSlot = data.readBigUInt64LE(offset + 8);
+    const lastUpdateSlot = data.readBigUInt64LE(offset + 16);
+    // last_borrow_rate and accumulated_rate_slots (u128 each) are internal state
+    offset += 24 + 32;
+
+    const samples = data.readUInt32LE(offset);
+    const head = data.readUInt16LE(offset + 4);
+    const len = data.readUInt16LE(offset + 6);
+    offset += 8;
+
+    // Entries are stored in a ring buffer; unroll it oldest first
+    const history: EpochRate[] = [];
+    const first = len < ReserveRateIndex.MAX_EPOCHS ? 0 : head;
+    for (let i = 0; i < len; i++) {
+      const start =
+        offset + ((first + i) % ReserveRateIndex.MAX_EPOCHS) * ReserveRateIndex.EPOCH_RATE_SIZE;
+      history.push({
+        epoch: data.readBigUInt64LE(start),
+        averageBorrowRate: data.readBigUInt64LE(start + 8),
+        samples: data.readUInt32LE(start + 16),
+      });
+    }
+
+    return new ReserveRateIndex(address, {
+      version,
+      reserve,
+      currentEpoch,
+      epochStartSlot,
+      lastUpdateSlot,
+      samples,
+      history,
+    });
+  }
+
+  /**
+   * Finalized realized borrow rate for an epoch, if it is still retained
+   */
+  getEpochRate(epoch: bigint): EpochRate | undefined {
+    return this.data.history.find((entry) => entry.epoch === epoch);
+  }
+}
@@ -1,4 +1,5 @@
 import { AccountInfo, PublicKey } from '@solana/web3.js';
+import { DerivedOracleKind, InterestRateStrategy, OracleSource } from '../types';
 
 export interface ReserveConfigFlags {
   depositsDisabled: boolean;
@@ -21,6 +22,19 @@ export interface ReserveConfig {
   maxBorrowRateBps: bigint;
   decimals: number;
   flags: ReserveConfigFlags;
+  supplyCap: bigint;
+  borrowCap: bigint;
+  borrowFeeBps: bigint;
+  insuranceFundShareBps: bigint;
+  interestRateStrategy: InterestRateStrategy;
+  /** Widest oracle confidence accepted, in bps of the price (0 = protocol default) */
+  maxConfidenceBps: bigint;
+  /** Oldest oracle price accepted, in slots (0 = protocol default) */
+  maxStalenessSlots: bigint;
+  /** Share of a borrow one liquidation may repay, in bps (0 = protocol default) */
+  liquidationCloseFactorBps: bigint;
+  /** Share of the liquidation bonus paid into the insurance fund, in bps */
+  liquidationProtocolFeeBps: bigint;
 }
 
 export interface Decimal {
@@ -32,10 +46,51 @@ export interface ReserveState {
   totalBorrows: bigint;
   totalLiquidity: bigint;
   collateralMintSupply: bigint;
-  currentBorrowRate: Decimal;
-  currentSupplyRate: Decimal;
-  currentUtilizationRate: Decimal;
+  /** Annual borrow rate at the last accrual, in basis points */
+  currentBorrowRateBps: bigint;
+  /** Annual supply rate at the last accrual, in basis points */
+  currentSupplyRateBps: bigint;
+  /** Utilization at the last accrual, in basis points */
+  currentUtilizationRateBps: bigint;
   accumulatedProtocolFees: bigint;
+  /** Product of all borrow interest growth since the reserve was created */
+  cumulativeBorrowRateWads: Decimal;
+  /** Liquidity invested in external yield venues, at cost */
+  investedLiquidity: bigint;
+  /** An operation holds the reserve's reentrancy lock */
+  locked: boolean;
+}
+
+export interface FeeRecipient {
+  receiver: PublicKey;
+  weightBps: number;
+}
+
+export interface FeeSplit {
+  insurance: FeeRecipient;
+  stakers: FeeRecipient;
+  grants: FeeRecipient;
+}
+
+export interface DerivedOracleComponent {
+  oracle: PublicKey;
+  source: OracleSource;
+  feedId: Buffer;
+  /** Pool vault holding the asset (LP tokens only) */
+  vault: PublicKey;
+  decimals: number;
+}
+
+export interface DerivedOracle {
+  kind: DerivedOracleKind;
+  /** SPL stake pool (stake pools) or LP token mint (LP tokens) */
+  pool: PublicKey;
+  base: DerivedOracleComponent;
+  quote: DerivedOracleComponent;
+  /** Cached USD price with 8 decimals (0 = not yet derived) */
+  price: bigint;
+  confidence: bigint;
+  publishTime: bigint;
 }
 
 export interface ReserveData {
@@ -51,11 +106,44 @@ export interface ReserveData {
   state: ReserveState;
   lastUpdateTimestamp: bigint;
   lastUpdateSlot: bigint;
-  reentrancyGuard: boolean;
+  feeSplit: FeeSplit;
+  assetClass: number;
+  oracleSource: OracleSource;
+  fixedPrice: bigint;
+  secondaryOracle: PublicKey;
+  secondaryOracleSource: OracleSource;
+  maxOracleDeviationBps: bigint;
+  /** Time-weighted average primary price, scaled by 10^twapExponent */
+  twapPrice: bigint;
+  twapExponent: number;
+  twapLastUpdateTimestamp: bigint;
+  twapWindowSeconds: bigint;
+  /** Unix timestamp the wind-down began (0 = not deprecated) */
+  deprecationStartTimestamp: bigint;
+  /** Unix timestamp at which the LTV reaches zero and deleveraging opens */
+  deprecationDeadlineTimestamp: bigint;
+  deleveragePenaltyBps: number;
+  /** Maximum USD debt isolated obligations may take against this collateral (0 = uncapped) */
+  debtCeilingUsd: bigint;
+  /** USD debt isolated obligations currently owe against this collateral */
+  isolationModeTotalDebt: bigint;
+  /** Utilization above which the surge withdrawal fee arms (bps, 0 = disabled) */
+  surgeUtilizationThresholdBps: number;
+  /** Slots utilization must stay above the threshold before the surge fee applies */
+  surgeDurationSlots: bigint;
+  /** Share of redeemed liquidity left to remaining suppliers during a surge (bps) */
+  surgeWithdrawalFeeBps: number;
+  /** Slot utilization was first seen above the surge threshold (0 = below it) */
+  highUtilizationSinceSlot: bigint;
+  totalSurgeFees: bigint;
+  /** Bits of `ReserveConfigFlags` disabled by an emergency responder (0 = none) */
+  emergencyPauseFlags: number;
+  /** Price derived from other oracles, read when `oracleSource` is `Derived` */
+  derivedOracle: DerivedOracle;
 }
 
 export class Reserve {
-  static readonly ACCOUNT_SIZE = 679;
+  static readonly ACCOUNT_SIZE = 984;
 
   constructor(
     public address: PublicKey,
@@ -112,21 +200,33 @@ export class Reserve {
         repaymentsDisabled: data.readUInt8(offset + 76) === 1,
         liquidationsDisabled: data.readUInt8(offset + 77) === 1,
         collateralEnabled: data.readUInt8(offset + 78) === 1,
-      }
+      },
+      supplyCap: data.readBigUInt64LE(offset + 79),
+      borrowCap: data.readBigUInt64LE(offset + 87),
+      borrowFeeBps: data.readBigUInt64LE(offset + 95),
+      insuranceFundShareBps: data.readBigUInt64LE(offset + 103),
+      interestRateStrategy: data.readUInt8(offset + 111) as InterestRateStrategy,
+      maxConfidenceBps: data.readBigUInt64LE(offset + 112),
+      maxStalenessSlots: data.readBigUInt64LE(offset + 120),
+      liquidationCloseFactorBps: data.readBigUInt64LE(offset + 128),
+      liquidationProtocolFeeBps: data.readBigUInt64LE(offset + 136),
     };
-    offset += 79;
+    offset += 144;
 
     const state: ReserveState = {
       availableLiquidity: data.readBigUInt64LE(offset),
       totalBorrows: data.readBigUInt64LE(offset + 8),
       totalLiquidity: data.readBigUInt64LE(offset + 16),
       collateralMintSupply: data.readBigUInt64LE(offset + 24),
-      currentBorrowRate: { value: data.readBigUInt64LE(offset + 32) },
-      currentSupplyRate: { value: data.readBigUInt64LE(offset + 48) },
-      currentUtilizationRate: { value: data.readBigUInt64LE(offset + 64) },
-      accumulatedProtocolFees: data.readBigUInt64LE(offset + 80),
+      currentBorrowRateBps: data.readBigUInt64LE(offset + 32),
+      currentSupplyRateBps: data.readBigUInt64LE(offset + 40),
+      currentUtilizationRateBps: data.readBigUInt64LE(offset + 48),
+      accumulatedProtocolFees: data.readBigUInt64LE(offset + 56),
+      cumulativeBorrowRateWads: { value: data.readBigUInt64LE(offset + 64) },
+      investedLiquidity: data.readBigUInt64LE(offset + 80),
+      locked: (data.readUInt8(offset + 88) & 1) === 1,
     };
-    offset += 88;
+    offset += 89;
 
     const lastUpdateTimestamp = data.readBigUInt64LE(offset);
     offset += 8;
@@ -134,7 +234,70 @@ export class Reserve {
     const lastUpdateSlot = data.readBigUInt64LE(offset);
     offset += 8;
 
-    const reentrancyGuard = data.readUInt8(offset) === 1;
+    const readFeeRecipient = (start: number): FeeRecipient => ({
+      receiver: new PublicKey(data.subarray(start, start + 32)),
+      weightBps: data.readUInt16LE(start + 32),
+    });
+    const feeSplit: FeeSplit = {
+      insurance: readFeeRecipient(offset),
+      stakers: readFeeRecipient(offset + 34),
+      grants: readFeeRecipient(offset + 68),
+    };
+    offset += 102;
+
+    const assetClass = data.readUInt8(offset);
+    offset += 1;
+
+    const oracleSource = data.readUInt8(offset) as OracleSource;
+    offset += 1;
+
+    const fixedPrice = data.readBigUInt64LE(offset);
+    offset += 8;
+
+    const secondaryOracle = new PublicKey(data.subarray(offset, offset + 32));
+    offset += 32;
+
+    const secondaryOracleSource = data.readUInt8(offset) as OracleSource;
+    offset += 1;
+
+    const maxOracleDeviationBps = data.readBigUInt64LE(offset);
+    offset += 8;
+
+    const twapPrice = data.readBigInt64LE(offset);
+    const twapExponent = data.readInt32LE(offset + 8);
+    const twapLastUpdateTimestamp = data.readBigUInt64LE(offset + 12);
+    const twapWindowSeconds = data.readBigUInt64LE(offset + 20);
+    offset += 28;
+
+    const deprecationStartTimestamp = data.readBigUInt64LE(offset);
+    const deprecationDeadlineTimestamp = data.readBigUInt64LE(offset + 8);
+    const deleveragePenaltyBps = data.readUInt16LE(offset + 16);
+    const debtCeilingUsd = data.readBigUInt64LE(offset + 18);
+    const isolationModeTotalDebt = data.readBigUInt64LE(offset + 26);
+    const surgeUtilizationThresholdBps = data.readUInt16LE(offset + 34);
+    const surgeDurationSlots = data.readBigUInt64LE(offset + 36);
+    const surgeWithdrawalFeeBps = data.readUInt16LE(offset + 44);
+    const highUtilizationSinceSlot = data.readBigUInt64LE(offset + 46);
+    const totalSurgeFees = data.readBigUInt64LE(offset + 54);
+    const emergencyPauseFlags = data.readUInt32LE(offset + 62);
+    offset += 66;
+
+    const readDerivedOracleComponent = (start: number): DerivedOracleComponent => ({
+      oracle: new PublicKey(data.subarray(start, start + 32)),
+      source: data.readUInt8(start + 32) as OracleSource,
+      feedId: data.subarray(start + 33, start + 65),
+      vault: new PublicKey(data.subarray(start + 65, start + 97)),
+      decimals: data.readUInt8(start + 97),
+    });
+    const derivedOracle: DerivedOracle = {
+      kind: data.readUInt8(offset) as DerivedOracleKind,
+      pool: new PublicKey(data.subarray(offset + 1, offset + 33)),
+      base: readDerivedOracleComponent(offset + 33),
+      quote: readDerivedOracleComponent(offset + 131),
+      price: data.readBigUInt64LE(offset + 229),
+      confidence: data.readBigUInt64LE(offset + 237),
+      publishTime: data.readBigInt64LE(offset + 245),
+    };
 
     return new Reserve(address, {
       version,
@@ -149,7 +312,29 @@ export class Reserve {
       state,
       lastUpdateTimestamp,
       lastUpdateSlot,
-      reentrancyGuard,
+      feeSplit,
+      assetClass,
+      oracleSource,
+      fixedPrice,
+      secondaryOracle,
+      secondaryOracleSource,
+      maxOracleDeviationBps,
+      twapPrice,
+      twapExponent,
+      twapLastUpdateTimestamp,
+      twapWindowSeconds,
+      deprecationStartTimestamp,
+      deprecationDeadlineTimestamp,
+      deleveragePenaltyBps,
+      debtCeilingUsd,
+      isolationModeTotalDebt,
+      surgeUtilizationThresholdBps,
+      surgeDurationSlots,
+      surgeWithdrawalFeeBps,
+      highUtilizationSinceSlot,
+      totalSurgeFees,
+      emergencyPauseFlags,
+      derivedOracle,
     });
   }
 
@@ -169,15 +354,27 @@ export class Reserve {
     return this.data.config.flags.collateralEnabled;
   }
 
+  /**
+   * Whether an emergency responder has paused operations that only a timelocked
+   * unpause can re-enable
+   */
+  isEmergencyPaused(): boolean {
+    return this.data.emergencyPauseFlags !== 0;
+  }
+
+  isDeprecated(): boolean {
+    return this.data.deprecationDeadlineTimestamp > 0n;
+  }
+
   getUtilizationRate(): number {
-    return Number(this.data.state.currentUtilizationRate.value) / 1e18;
+    return Number(this.data.state.currentUtilizationRateBps) / 10_000;
   }
 
   getBorrowRate(): number {
-    return Number(this.data.state.currentBorrowRate.value) / 1e18;
+    return Number(this.data.state.currentBorrowRateBps) / 10_000;
   }
 
   getSupplyRate(): number {
-    return Number(this.data.state.currentSupplyRate.value) / 1e18;
+    return Number(this.data.state.currentSupplyRateBps) / 10_000;
   }
 }
\ No newline at end of file
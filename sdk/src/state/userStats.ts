@@ -0,0 +1,89 @@
+import { AccountInfo, PublicKey } from '@solana/web3.js';
+
+export interface TrackedBorrow {
+  obligation: PublicKey;
+  reserve: PublicKey;
+  /** Borrowed amount not yet repaid, excluding interest */
+  principal: bigint;
+}
+
+export interface UserStatsData {
+  version: number;
+  market: PublicKey;
+  owner: PublicKey;
+  /** USD amounts are scaled by 1e18 */
+  totalSuppliedUsd: bigint;
+  totalBorrowedUsd: bigint;
+  totalInterestPaidUsd: bigint;
+  liquidationsSuffered: number;
+  /** Reward tokens claimed, in base units of the pools' reward mints */
+  totalRewardsEarned: bigint;
+  trackedBorrows: TrackedBorrow[];
+  createdSlot: bigint;
+  lastUpdateSlot: bigint;
+}
+
+export class UserStats {
+  static readonly TRACKED_BORROW_SIZE = 72;
+
+  constructor(
+    public address: PublicKey,
+    public data: UserStatsData
+  ) {}
+
+  static fromAccountInfo(address: PublicKey, accountInfo: AccountInfo<Buffer>): UserStats {
+    if (!accountInfo.data) {
+      throw new Error('Invalid user stats account data');
+    }
+
+    // Skip the 8-byte account discriminator
+    let offset = 8;
+    const data = accountInfo.data;
+    const readU128 = (at: number): bigint =>
+      data.readBigUInt64LE(at) + (data.readBigUInt64LE(at + 8) << 64n);
+
+    const version = data.readUInt8(offset);
+    offset += 1;
+
+    const market = new PublicKey(data.subarray(offset, offset + 32));
+    const owner = new PublicKey(data.subarray(offset + 32, offset + 64));
+    offset += 64;
+
+    const totalSuppliedUsd = readU128(offset);
+    const totalBorrowedUsd = readU128(offset + 16);
+    const totalInterestPaidUsd = readU128(offset + 32);
+    offset += 48;
+
+    const liquidationsSuffered = data.readUInt32LE(offset);
+    const totalRewardsEarned = data.readBigUInt64LE(offset + 4);
+    offset += 12;
+
+    const len = data.readUInt32LE(offset);
+    offset += 4;
+
+    const trackedBorrows: TrackedBorrow[] = [];
+    for (let i = 0; i < len; i++) {
+      const start = offset + i * UserStats.TRACKED_BORROW_SIZE;
+      trackedBorrows.push({
+        obligation: new PublicKey(data.subarray(start, start + 32)),
+        reserve: new PublicKey(data.subarray(start + 32, start + 64)),
+        principal: data.readBigUInt64LE(start + 64),
+      });
+    }
+    offset += len * UserStats.TRACKED_BORROW_SIZE;
+
+    return new UserStats(address, {
+      version,
+      market,
+      owner,
+      totalSuppliedUsd,
+      totalBorrowedUsd,
+      totalInterestPaidUsd,
+      liquidationsSuffered,
+      totalRewardsEarned,
+      trackedBorrows,
+      createdSlot: data.readBigUInt64LE(offset),
+      lastUpdateSlot: data.readBigUInt64LE(offset + 8),
+    });
+  }
+}
@@ -65,6 +65,15 @@ export interface ReserveConfig {
   maxBorrowRateBps: number;
   decimals: number;
   flags: ReserveConfigFlags;
+  supplyCap: BN;
+  borrowCap: BN;
+  borrowFeeBps: BN;
+  insuranceFundShareBps: BN;
+  interestRateStrategy: { kinked: {} } | { linear: {} } | { curve: {} } | { fixed: {} };
+  maxConfidenceBps: BN; // 0 = protocol default
+  maxStalenessSlots: BN; // 0 = protocol default
+  liquidationCloseFactorBps: BN; // 0 = protocol default
+  liquidationProtocolFeeBps: BN; // share of the liquidation bonus to the insurance fund
 }
 
 export interface ReserveConfigFlags {
@@ -82,9 +91,9 @@ export interface ReserveState {
   totalBorrows: BN;
   totalLiquidity: BN;
   collateralMintSupply: BN;
-  currentBorrowRate: Decimal;
-  currentSupplyRate: Decimal;
-  currentUtilizationRate: Decimal;
+  currentBorrowRateBps: BN;
+  currentSupplyRateBps: BN;
+  currentUtilizationRateBps: BN;
   accumulatedProtocolFees: BN;
 }
 
@@ -147,6 +156,9 @@ export interface ObligationLiquidity {
   borrowReserve: PublicKey;
   borrowedAmountWads: Decimal;
   marketValueUsd: Decimal;
+  originationUtilizationRate: Decimal;
+  originationBorrowRate: Decimal;
+  originationSlot: bigint;
 }
 
 // Market flags
@@ -170,13 +182,37 @@ export interface InitializeReserveParams {
   liquidityMint: PublicKey;
   priceOracle: PublicKey;
   oracleFeedId: Buffer;
+  oracleSource: { pyth: {} } | { switchboard: {} } | { fixedPrice: {} };
+  fixedPrice: BN; // USD price with 8 decimals, FixedPrice reserves only
   config: ReserveConfig;
+  initialLiquidity: BN | null; // Seed deposit taken from the payer's token account
 }
 
 export interface UpdateReserveConfigParams {
   config: ReserveConfig;
 }
 
+export interface UpdateReserveCapsParams {
+  supplyCap: BN;
+  borrowCap: BN;
+  debtCeilingUsd: BN; // Only applies to isolated collateral
+}
+
+export interface UpdateReserveSecondaryOracleParams {
+  secondaryOracle: PublicKey; // PublicKey.default disables the cross-check
+  secondaryOracleSource: { pyth: {} } | { switchboard: {} };
+  maxOracleDeviationBps: BN;
+}
+
+export interface UpdateReserveTwapWindowParams {
+  twapWindowSeconds: BN; // 0 disables smoothing
+}
+
+export interface DeprecateReserveParams {
+  deadlineTimestamp: BN; // 0 cancels the wind-down
+  deleveragePenaltyBps: number;
+}
+
 export interface LiquidationParams {
   liquidityAmount: BN;
   minCollateralAmount: BN;
@@ -212,6 +248,35 @@ export enum HealthFactorCategory {
   Liquidatable = 'liquidatable' // < 1.0
 }
 
+// Price sources a reserve can be configured with
+export enum OracleSource {
+  Pyth = 0,
+  Switchboard = 1,
+  FixedPrice = 2,
+  Derived = 3 // Cached LST or LP token price, see `refresh_derived_price`
+}
+
+// How a reserve's derived price is computed
+export enum DerivedOracleKind {
+  None = 0,
+  StakePool = 1, // SOL price times the SPL stake pool's exchange rate
+  LiquidityPool = 2 // Fair value of a constant-product LP token
+}
+
+// Borrow rate curves a reserve can follow
+export enum InterestRateStrategy {
+  Kinked = 0,
+  Linear = 1,
+  Curve = 2, // Quadratic above optimal utilization
+  Fixed = 3
+}
+
+// Side of a reserve a reward pool incentivizes
+export enum RewardSide {
+  Supply = 0, // aTokens deposited as obligation collateral
+  Borrow = 1
+}
+
 // Interest rate model types
 export enum InterestRateModel {
   Linear = 'linear',
@@ -18,6 +18,7 @@ describe("aura-lend", () => {
   let usdcReserve: PublicKey;
   let userKeypair: Keypair;
   let obligationPubkey: PublicKey;
+  let adminAuditLog: PublicKey;
 
   // Test constants
   const USDC_DECIMALS = 6;
@@ -74,6 +75,11 @@ describe("aura-lend", () => {
       [Buffer.from("obligation"), userKeypair.publicKey.toBuffer()],
       program.programId
     );
+
+    [adminAuditLog] = PublicKey.findProgramAddressSync(
+      [Buffer.from("admin_audit_log")],
+      program.programId
+    );
   });
 
   it("Initializes the market", async () => {
@@ -113,6 +119,23 @@ describe("aura-lend", () => {
     assert.equal(marketAccount.reservesCount.toString(), "0");
   });
 
+  it("Initializes the admin audit log", async () => {
+    await program.methods
+      .initializeAdminAuditLog()
+      .accounts({
+        market: marketPubkey,
+        adminAuditLog: adminAuditLog,
+        authority: provider.wallet.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    // The initialization itself is the first recorded action
+    const logAccount = await program.account.adminAuditLog.fetch(adminAuditLog);
+    assert.equal(logAccount.nextSequence.toString(), "1");
+  });
+
   it("Initializes a USDC reserve", async () => {
     const [collateralMint] = PublicKey.findProgramAddressSync(
       [Buffer.from("collateral"), usdcMint.toBuffer()],
@@ -161,13 +184,26 @@ describe("aura-lend", () => {
         liquidationsDisabled: false,
         collateralEnabled: true,
       },
+      supplyCap: new anchor.BN(0), // uncapped
+      borrowCap: new anchor.BN(0), // uncapped
+      borrowFeeBps: new anchor.BN(0), // no origination fee
+      insuranceFundShareBps: new anchor.BN(0),
+      interestRateStrategy: { kinked: {} },
+      maxConfidenceBps: new anchor.BN(0), // protocol default
+      maxStalenessSlots: new anchor.BN(0), // protocol default
+      liquidationCloseFactorBps: new anchor.BN(0), // protocol default
+      liquidationProtocolFeeBps: new anchor.BN(0), // bonus goes to the liquidator
     };
 
     const params = {
       liquidityMint: usdcMint,
       priceOracle: PublicKey.default, // Mock oracle for testing
+      oracleSource: { pyth: {} },
+      fixedPrice: new anchor.BN(0),
       config: reserveConfig,
+      initialLiquidity: null,
     };
+    const treasuryCollateral = Keypair.generate();
 
     await program.methods
       .initializeReserve(params)
@@ -180,12 +216,16 @@ describe("aura-lend", () => {
         liquiditySupply: liquiditySupply,
         liquiditySupplyAuthority: liquiditySupplyAuthority,
         feeReceiver: feeReceiver,
+        treasuryCollateral: treasuryCollateral.publicKey,
+        payerLiquidity: null,
         owner: provider.wallet.publicKey,
+        adminAuditLog: adminAuditLog,
         payer: provider.wallet.publicKey,
         systemProgram: SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
       })
+      .signers([treasuryCollateral])
       .rpc();
 
     // Verify reserve was created
@@ -247,6 +287,7 @@ describe("aura-lend", () => {
       .accounts({
         market: marketPubkey,
         reserve: usdcReserve,
+        liquidityMint: usdcMint,
         destinationLiquidity: liquiditySupply,
         liquiditySupplyAuthority: liquiditySupplyAuthority,
         collateralMint: collateralMint,
@@ -270,7 +311,7 @@ describe("aura-lend", () => {
 
   it("Initializes user obligation", async () => {
     await program.methods
-      .initObligation()
+      .initObligation(0)
       .accounts({
         market: marketPubkey,
         obligation: obligationPubkey,
@@ -311,6 +352,7 @@ describe("aura-lend", () => {
         .accounts({
           market: marketPubkey,
           reserve: usdcReserve,
+          liquidityMint: usdcMint,
           destinationLiquidity: PublicKey.default,
           liquiditySupplyAuthority: PublicKey.default,
           collateralMint: PublicKey.default,
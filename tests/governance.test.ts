@@ -3,6 +3,7 @@ import { Program } from "@coral-xyz/anchor";
 import { AuraLend } from "../target/types/aura_lend";
 import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
 import { assert, expect } from "chai";
+import { createHash } from "crypto";
 
 describe("Governance System Tests", () => {
   const provider = anchor.AnchorProvider.env();
@@ -106,8 +107,9 @@ describe("Governance System Tests", () => {
             proposalId,
             operationType: "UpdateReserveConfig",
             instructionData: Buffer.from("test_instruction_data"),
+            payloadHash: Array.from(createHash("sha256").update("test_instruction_data").digest()),
             targetAccounts: [marketPubkey],
-            expiresAt: null, // No expiration
+            expiresAt: null, // Default lifetime
           })
           .accounts({
             multisig: multisigPubkey,
@@ -199,7 +201,7 @@ describe("Governance System Tests", () => {
 
         // Execute the proposal
         await program.methods
-          .executeMultisigProposal()
+          .executeMultisigProposal(Buffer.from("test_instruction_data"))
           .accounts({
             multisig: multisigPubkey,
             proposal: proposalPubkey,
@@ -244,6 +246,7 @@ describe("Governance System Tests", () => {
             operationType: "UpdateMarketOwner", // Critical operation
             instructionData: Buffer.from("owner_change_data"),
             targetAccounts: [marketPubkey],
+            calls: [],
           })
           .accounts({
             timelock: timelockPubkey,
@@ -7,6 +7,7 @@ import {
   SystemProgram 
 } from "@solana/web3.js";
 import { expect } from "chai";
+import { createHash } from "crypto";
 import { AuraLend } from "../target/types/aura_lend";
 
 describe("Program Upgradability Tests", () => {
@@ -297,6 +298,7 @@ describe("Program Upgradability Tests", () => {
           .createMultisigProposal({
             operationType: { programUpgrade: {} },
             instructionData: Buffer.from("mock upgrade data"),
+            payloadHash: Array.from(createHash("sha256").update("mock upgrade data").digest()),
             expiresAt: null,
           })
           .accounts({